@@ -0,0 +1,151 @@
+//! `agtx adopt`: track a worktree set up by hand, and optionally the window its agent already
+//! runs in, as a Running task. The inverse of reconciliation: instead of a task whose
+//! resources are gone, resources without a task.
+//!
+//! Nothing is created. The worktree must be one git lists for the project and the window must
+//! exist, and neither may already belong to a task, so adopting never duplicates anything.
+//! The worktree must also sit directly in the configured `worktree_dir`: tasks find theirs by
+//! slug there (presence checks, reconciliation), so one elsewhere would look missing.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::db::{Task, TaskStatus};
+use crate::git::GitOperations;
+use crate::tmux::{self, TmuxOperations};
+
+/// Whether two paths name the same directory (symlinks resolved when both exist)
+fn same_path(a: &Path, b: &Path) -> bool {
+    a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+fn worktree_in_use(path: &Path, tasks: &[Task]) -> bool {
+    tasks.iter().filter_map(|t| t.worktree_path.as_deref()).any(|wt| same_path(Path::new(wt), path))
+}
+
+/// Whether `path` is where a task whose slug is its directory name keeps its worktree
+pub fn in_worktree_dir(path: &Path, project_path: &Path, git_ops: &dyn GitOperations) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|slug| same_path(&git_ops.worktree_path(project_path, slug), path))
+}
+
+/// The worktrees no task points at
+pub fn untracked_worktrees(worktrees: &[PathBuf], tasks: &[Task]) -> Vec<PathBuf> {
+    worktrees.iter().filter(|path| !worktree_in_use(path, tasks)).cloned().collect()
+}
+
+/// The windows of `session` no task points at
+pub fn untracked_windows(session: &str, windows: &[String], tasks: &[Task]) -> Vec<String> {
+    windows
+        .iter()
+        .filter(|name| {
+            let target = format!("{}:{}", session, name);
+            !tasks.iter().any(|t| t.session_name.as_deref() == Some(target.as_str()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// The worktree `arg` names: its path, or just its directory name
+pub fn find_worktree(worktrees: &[PathBuf], arg: &str) -> Option<PathBuf> {
+    let wanted = Path::new(arg);
+    worktrees
+        .iter()
+        .find(|path| same_path(path, wanted))
+        .or_else(|| worktrees.iter().find(|path| path.file_name().is_some_and(|name| name == arg)))
+        .cloned()
+}
+
+/// The full target of `window`: a bare name is a window of `session`
+pub fn window_target(session: &str, window: &str) -> String {
+    if window.contains(':') {
+        window.to_string()
+    } else {
+        format!("{}:{}", session, window)
+    }
+}
+
+/// Title for a task adopted from the worktree directory `name`, with `-` and `_` read as spaces
+pub fn title_from_slug(name: &str) -> String {
+    name.replace(['-', '_'], " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A checked worktree and window, ready to become a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adoption {
+    pub worktree: PathBuf,
+    /// Full `session:window` target
+    pub window: Option<String>,
+}
+
+impl Adoption {
+    /// Look up `worktree` among the project's worktrees and `window` (a name in `session`, or
+    /// a full target) among the multiplexer's windows, and make sure no task in `tasks`
+    /// already has either
+    pub fn check(
+        worktree: &str,
+        window: Option<&str>,
+        project_path: &Path,
+        session: &str,
+        tasks: &[Task],
+        tmux_ops: &dyn TmuxOperations,
+        git_ops: &dyn GitOperations,
+    ) -> Result<Self> {
+        let worktrees = git_ops.list_worktrees(project_path).context("Failed to list worktrees")?;
+        let Some(path) = find_worktree(&worktrees, worktree) else {
+            bail!("'{}' is not a worktree of {}", worktree, project_path.display());
+        };
+        if !path.is_dir() {
+            bail!("Worktree {} does not exist", path.display());
+        }
+        if !in_worktree_dir(&path, project_path, git_ops) {
+            let dir = git_ops.worktree_path(project_path, "");
+            bail!(
+                "Worktree {} is outside the worktree directory {}; move it there first (git worktree move)",
+                path.display(),
+                dir.display()
+            );
+        }
+        if let Some(task) = tasks
+            .iter()
+            .find(|t| t.worktree_path.as_deref().is_some_and(|wt| same_path(Path::new(wt), &path)))
+        {
+            bail!("Worktree {} already belongs to '{}'", path.display(), task.title);
+        }
+
+        let window = match window {
+            Some(window) => {
+                let target = window_target(session, window);
+                let (session, name) = tmux::split_target(&target);
+                let windows = tmux_ops.list_windows(session).context("Failed to list windows")?;
+                if !windows.iter().any(|w| w == name) {
+                    bail!("No window '{}' in session '{}'", name, session);
+                }
+                if let Some(task) = tasks.iter().find(|t| t.session_name.as_deref() == Some(target.as_str())) {
+                    bail!("Window {} already belongs to '{}'", target, task.title);
+                }
+                Some(target)
+            }
+            None => None,
+        };
+        Ok(Self { worktree: path, window })
+    }
+
+    /// A Running task pointing at the worktree and window. Its slug is the worktree's
+    /// directory name, and without a `title` so is its title.
+    pub fn into_task(self, title: Option<&str>, branch: Option<String>, agent: &str, project_id: &str) -> Task {
+        let slug = self.worktree.file_name().map(|name| name.to_string_lossy().to_string());
+        let title = match title.map(str::trim).filter(|t| !t.is_empty()) {
+            Some(title) => title.to_string(),
+            None => title_from_slug(slug.as_deref().unwrap_or("adopted")),
+        };
+        let mut task = Task::new(title, agent, project_id);
+        task.status = TaskStatus::Running;
+        task.worktree_path = Some(self.worktree.to_string_lossy().to_string());
+        task.branch_name = branch.filter(|b| !b.is_empty() && b != "HEAD");
+        task.session_name = self.window;
+        task.slug = slug;
+        task
+    }
+}
@@ -1,4 +1,5 @@
 mod operations;
+pub mod usage;
 
 pub use operations::{AgentOperations, AgentRegistry, CodingAgent, RealAgentRegistry};
 
@@ -58,6 +59,32 @@ impl Agent {
             _ => format!("{} '{}'", self.command, escaped_prompt),
         }
     }
+
+    /// Whether the agent can be started under an id of our choosing and resumed by it later
+    pub fn supports_session_resume(&self) -> bool {
+        self.name == "claude"
+    }
+
+    /// Like `build_interactive_command`, but starting a conversation with the given id so it
+    /// can be resumed with `build_resume_command`. Agents without resume support ignore the id.
+    pub fn build_interactive_command_in_session(&self, prompt: &str, session_id: &str) -> String {
+        let command = self.build_interactive_command(prompt);
+        if !self.supports_session_resume() {
+            return command;
+        }
+        match command.split_once(' ') {
+            Some((program, rest)) => format!("{} --session-id {} {}", program, session_id, rest),
+            None => format!("{} --session-id {}", command, session_id),
+        }
+    }
+
+    /// Command that restarts the agent in a recorded conversation, if the agent supports it:
+    /// the agent's usual start command (`build_interactive_command` without a prompt) with
+    /// `--resume <id>` added
+    pub fn build_resume_command(&self, session_id: &str) -> Option<String> {
+        self.supports_session_resume()
+            .then(|| format!("{} --resume {}", self.build_interactive_command(""), session_id))
+    }
 }
 
 /// Get the list of known agents
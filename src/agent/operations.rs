@@ -11,7 +11,8 @@ use std::sync::Arc;
 #[cfg(feature = "test-mocks")]
 use mockall::automock;
 
-use super::Agent;
+use super::{usage, Agent};
+use crate::db::AgentUsage;
 
 /// Operations for coding agents (Claude, Aider, Codex, etc.)
 #[cfg_attr(feature = "test-mocks", automock)]
@@ -27,6 +28,18 @@ pub trait AgentOperations: Send + Sync {
     /// Build the shell command to start the agent interactively.
     /// When prompt is empty, the agent starts with no initial message.
     fn build_interactive_command(&self, prompt: &str) -> String;
+
+    /// A fresh conversation id to start the agent with, if it can resume conversations by id
+    fn new_session_id(&self) -> Option<String>;
+
+    /// `build_interactive_command`, starting the conversation under `session_id`
+    fn build_interactive_command_in_session(&self, prompt: &str, session_id: &str) -> String;
+
+    /// Shell command that restarts the agent in a recorded conversation (None if unsupported)
+    fn build_resume_command(&self, session_id: &str) -> Option<String>;
+
+    /// Token/cost totals shown in the agent's window output (None if absent or unparseable)
+    fn parse_usage(&self, output: &str) -> Option<AgentUsage>;
 }
 
 /// Generic agent implementation that works with any Agent config
@@ -71,6 +84,22 @@ impl AgentOperations for CodingAgent {
     fn build_interactive_command(&self, prompt: &str) -> String {
         self.agent.build_interactive_command(prompt)
     }
+
+    fn new_session_id(&self) -> Option<String> {
+        self.agent.supports_session_resume().then(|| uuid::Uuid::new_v4().to_string())
+    }
+
+    fn build_interactive_command_in_session(&self, prompt: &str, session_id: &str) -> String {
+        self.agent.build_interactive_command_in_session(prompt, session_id)
+    }
+
+    fn build_resume_command(&self, session_id: &str) -> Option<String> {
+        self.agent.build_resume_command(session_id)
+    }
+
+    fn parse_usage(&self, output: &str) -> Option<AgentUsage> {
+        usage::parser_for(&self.agent.name)?.parse(output)
+    }
 }
 
 /// Registry that maps agent names to AgentOperations instances.
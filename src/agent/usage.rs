@@ -0,0 +1,85 @@
+//! Token and cost figures read back from what an agent prints in its window.
+//!
+//! Each agent formats its usage differently (or not at all), so parsers are looked up by agent
+//! name with `parser_for`. A parser returns `None` whenever it can't find its format, and the
+//! task's recorded usage is then left as it was. The numbers are the agent's own running totals
+//! for its conversation, so they're rough: a fresh conversation starts counting again.
+
+use crate::db::AgentUsage;
+
+/// Reads an agent's usage summary out of its pane text
+pub trait UsageParser: Send + Sync {
+    fn parse(&self, output: &str) -> Option<AgentUsage>;
+}
+
+/// The usage parser of an agent, if its output can be parsed
+pub fn parser_for(agent_name: &str) -> Option<&'static dyn UsageParser> {
+    match agent_name {
+        "claude" => Some(&ClaudeUsageParser),
+        _ => None,
+    }
+}
+
+/// Claude Code's `/cost` and exit summary:
+///
+/// ```text
+/// Total cost:            $0.2456
+/// ...
+/// Usage by model:
+///        claude-sonnet:  23 input, 4.5k output, 120.3k cache read, 15.2k cache write
+/// ```
+///
+/// The last summary on screen wins. Cache reads and writes count as input.
+pub struct ClaudeUsageParser;
+
+const COST_LABEL: &str = "Total cost:";
+
+impl UsageParser for ClaudeUsageParser {
+    fn parse(&self, output: &str) -> Option<AgentUsage> {
+        let lines: Vec<&str> = output.lines().collect();
+        let start = lines.iter().rposition(|l| l.contains(COST_LABEL))?;
+        let (_, cost) = lines[start].split_once(COST_LABEL)?;
+        let cost_usd = cost.trim().trim_start_matches('$').parse::<f64>().ok();
+
+        let mut usage = AgentUsage { cost_usd, ..Default::default() };
+        let mut found_model = false;
+        for line in &lines[start + 1..] {
+            let Some((_, counts)) = line.split_once(": ") else { continue };
+            if !counts.contains(" input") {
+                continue;
+            }
+            found_model = true;
+            for part in counts.split(',') {
+                let Some((count, kind)) = part.trim().split_once(' ') else { continue };
+                let Some(count) = parse_count(count) else { continue };
+                match kind {
+                    "input" | "cache read" | "cache write" => usage.tokens_in += count,
+                    "output" => usage.tokens_out += count,
+                    _ => {}
+                }
+            }
+        }
+        (found_model || usage.cost_usd.is_some()).then_some(usage)
+    }
+}
+
+/// A token count as agents print it: `85`, `1,234`, `4.5k`, `1.2m`
+pub fn parse_count(text: &str) -> Option<u64> {
+    let text = text.trim().replace(',', "");
+    let (number, scale) = match text.chars().last()? {
+        'k' | 'K' => (&text[..text.len() - 1], 1_000.0),
+        'm' | 'M' => (&text[..text.len() - 1], 1_000_000.0),
+        _ => (text.as_str(), 1.0),
+    };
+    let value: f64 = number.parse().ok()?;
+    (value >= 0.0).then(|| (value * scale).round() as u64)
+}
+
+/// `1234567` as `1.2M`, `4500` as `4.5k`
+pub fn format_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1_000.0),
+        _ => format!("{:.1}M", count as f64 / 1_000_000.0),
+    }
+}
@@ -0,0 +1,192 @@
+//! Headless scripting API (`agtx serve`): newline-delimited JSON over a Unix socket.
+//!
+//! A client writes one request per line and reads one response line back. Every message
+//! carries `"version"`; a request for any other version is rejected, so scripts fail loudly
+//! instead of misreading a changed schema. Bump `API_VERSION` on incompatible changes.
+//!
+//! Requests (version 1):
+//!
+//! ```text
+//! {"version": 1, "command": "list_tasks"}
+//! {"version": 1, "command": "create_task", "title": "Fix login", "description": "...", "agent": "claude"}
+//! {"version": 1, "command": "move_task", "id": "<task id>", "direction": "forward"}
+//! {"version": 1, "command": "delete_task", "id": "<task id>"}
+//! ```
+//!
+//! `description` and `agent` are optional (the agent defaults to `default_agent`);
+//! `direction` is `forward` (the default, like `m`) or `back` (like `r`: Running → Planning,
+//! Review → Running). Both are checked by `workflow::request_transition`, so a move the
+//! workflow doesn't allow gets an error response and changes nothing.
+//!
+//! Responses:
+//!
+//! ```text
+//! {"version": 1, "ok": true, "result": ...}
+//! {"version": 1, "ok": false, "error": "why"}
+//! ```
+//!
+//! `list_tasks` returns an array of `TaskInfo`; `create_task` and `move_task` return the
+//! task's `TaskInfo` after the change; `delete_task` returns `{"id": "<task id>"}`.
+//!
+//! With `--events`, every entry added to a task's history (`TaskEvent`) is also written to
+//! stdout as one `EventLine` as it's recorded, whichever request, background job or other agtx
+//! caused it:
+//!
+//! ```text
+//! {"version": 1, "task_id": "<task id>", "at": "2026-01-02T03:04:05Z", "kind": "moved", "detail": "backlog → planning"}
+//! {"version": 1, "task_id": "<task id>", "at": "...", "kind": "failed", "detail": "why"}
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::db::{Task, TaskEvent};
+
+/// Version of the message schema described in the module docs
+pub const API_VERSION: u32 = 1;
+
+/// Socket `agtx serve` listens on when `--socket` isn't given
+pub const DEFAULT_SOCKET_FILE: &str = ".agtx/api.sock";
+
+/// Default socket path for a project
+pub fn default_socket_path(project_path: &Path) -> PathBuf {
+    project_path.join(crate::platform::native_path(DEFAULT_SOCKET_FILE))
+}
+
+/// A command from a client
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Request {
+    ListTasks,
+    CreateTask {
+        title: String,
+        #[serde(default)]
+        description: Option<String>,
+        #[serde(default)]
+        agent: Option<String>,
+    },
+    MoveTask {
+        id: String,
+        #[serde(default)]
+        direction: MoveDirection,
+    },
+    DeleteTask {
+        id: String,
+    },
+}
+
+/// Which way `move_task` moves a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoveDirection {
+    /// One column to the right, as the `move_forward` key does
+    #[default]
+    Forward,
+    /// Running → Planning or Review → Running, as the `move_back`/`resume` keys do
+    Back,
+}
+
+/// Parse one request line, checking its version. Errors are sent back to the client as is.
+pub fn parse_request(line: &str) -> Result<Request, String> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e))?;
+    let object = value.as_object_mut().ok_or("request must be a JSON object")?;
+    match object.remove("version").map(|v| v.as_u64()) {
+        Some(Some(v)) if v == u64::from(API_VERSION) => {}
+        Some(Some(v)) => {
+            return Err(format!("unsupported API version {} (this agtx speaks version {})", v, API_VERSION))
+        }
+        Some(None) => return Err("\"version\" must be a number".to_string()),
+        None => return Err(format!("missing \"version\" (this agtx speaks version {})", API_VERSION)),
+    }
+    serde_json::from_value(value).map_err(|e| format!("invalid request: {}", e))
+}
+
+/// A task as the API reports it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskInfo {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    /// `backlog`, `planning`, `running`, `review` or `done`
+    pub status: String,
+    pub agent: String,
+    pub tags: Vec<String>,
+    pub archived: bool,
+    pub worktree_path: Option<String>,
+    pub branch_name: Option<String>,
+    /// Multiplexer target of the agent window (`session:window`)
+    pub session_name: Option<String>,
+    pub pr_url: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Task> for TaskInfo {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id.clone(),
+            title: task.title.clone(),
+            description: task.description.clone(),
+            status: task.status.as_str().to_string(),
+            agent: task.agent.clone(),
+            tags: task.tags.clone(),
+            archived: task.archived,
+            worktree_path: task.worktree_path.clone(),
+            branch_name: task.branch_name.clone(),
+            session_name: task.session_name.clone(),
+            pr_url: task.pr_url.clone(),
+            created_at: task.created_at,
+            updated_at: task.updated_at,
+        }
+    }
+}
+
+/// One line of the `--events` stream: a task's history entry, as stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventLine {
+    pub version: u32,
+    pub task_id: String,
+    #[serde(flatten)]
+    pub event: TaskEvent,
+}
+
+impl EventLine {
+    pub fn new(task_id: impl Into<String>, event: TaskEvent) -> Self {
+        Self { version: API_VERSION, task_id: task_id.into(), event }
+    }
+
+    /// The event as a single line of JSON (without the newline)
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A reply to one request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub version: u32,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    pub fn ok(result: serde_json::Value) -> Self {
+        Self { version: API_VERSION, ok: true, result: Some(result), error: None }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { version: API_VERSION, ok: false, result: None, error: Some(message.into()) }
+    }
+
+    /// The response as a single line of JSON (without the newline)
+    pub fn to_line(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|e| {
+            format!("{{\"version\":{},\"ok\":false,\"error\":\"{}\"}}", API_VERSION, e)
+        })
+    }
+}
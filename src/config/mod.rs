@@ -1,6 +1,18 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::db::{Task, TaskStatus};
+use crate::git::{GitTimeouts, RealGitOps};
+use crate::slug;
+
+mod theme;
+mod view;
+
+pub use theme::{Theme, THEME_NAMES};
+pub use view::{apply_view, next_view, TaskFilter, View};
 
 /// Global configuration (stored in ~/.config/agtx/)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,8 +26,101 @@ pub struct GlobalConfig {
     pub worktree: WorktreeConfig,
 
     /// UI theme/colors
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_theme")]
     pub theme: ThemeConfig,
+
+    /// Ask for confirmation before actions that kill a tmux window or remove a worktree
+    #[serde(default = "default_true")]
+    pub confirm_destructive: bool,
+
+    /// Most agents working at once; tasks moved to Running beyond this wait in a queue (0 = no limit)
+    #[serde(default)]
+    pub max_concurrent_agents: usize,
+
+    /// Seconds before a hung git command is killed, per kind of operation
+    #[serde(default)]
+    pub git_timeouts: GitTimeoutConfig,
+
+    /// Key overrides for board actions (action name -> key spec, e.g. `move_forward = "n"`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub keybindings: HashMap<String, String>,
+
+    /// Named task templates offered when creating a task (`[[templates]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub templates: Vec<Template>,
+
+    /// tmux session (on the agtx server) that task windows are created in.
+    /// Unset = one session per project, named after the project directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tmux_session: Option<String>,
+
+    /// Prompt templates sent to the agent per phase (`[prompts]`)
+    #[serde(default, skip_serializing_if = "PromptTemplates::is_empty")]
+    pub prompts: PromptTemplates,
+
+    /// Shell commands run when a task changes column (`[hooks]`)
+    #[serde(default, skip_serializing_if = "HooksConfig::is_empty")]
+    pub hooks: HooksConfig,
+
+    /// Environment variables exported in every task window before the agent starts (`[env]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+
+    /// Shell command run once in each new task window before the agent (e.g. `nvm use`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_startup_command: Option<String>,
+
+    /// Name of new task windows, with `{slug}`, `{id}` and `{title}` placeholders.
+    /// Unset = `task-{slug}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_name_template: Option<String>,
+
+    /// Test command run in the worktree when a task enters Review (e.g. `cargo test`).
+    /// Unset = no test run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_command: Option<String>,
+
+    /// Run without tmux: tasks still move and get worktrees, but no agent windows are created.
+    /// Same as starting with `--no-tmux`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_tmux: bool,
+
+    /// Terminal multiplexer task windows live in (`"tmux"` or `"zellij"`)
+    #[serde(default, skip_serializing_if = "Multiplexer::is_default")]
+    pub multiplexer: Multiplexer,
+
+    /// Minimum milliseconds between commands/prompts sent to agents, across all tasks (0 = no limit)
+    #[serde(default)]
+    pub send_keys_interval_ms: u64,
+
+    /// How a prompt reaches an agent started with it: `"auto"`, `"file"` or `"inline"`
+    #[serde(default, skip_serializing_if = "PromptDelivery::is_default")]
+    pub prompt_delivery: PromptDelivery,
+
+    /// Lines of agent output kept in memory per task (for reading usage); older lines are
+    /// dropped first. 0 = keep none, only the pane as it is now is read.
+    #[serde(default = "default_max_captured_lines")]
+    pub max_captured_lines: usize,
+
+    /// Saved board filters, cycled with the `cycle_view` key (`[[views]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<View>,
+
+    /// Per-column settings, keyed by status (`[columns.review]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub columns: BTreeMap<String, ColumnConfig>,
+
+    /// HTTP endpoint told about every column change (`[webhook]`)
+    #[serde(default, skip_serializing_if = "WebhookConfig::is_empty")]
+    pub webhook: WebhookConfig,
+
+    /// What moving to Done and deleting a task remove (`[cleanup.done]`, `[cleanup.delete]`)
+    #[serde(default, skip_serializing_if = "CleanupConfig::is_empty")]
+    pub cleanup: CleanupConfig,
+
+    /// Card tints for tasks that have sat in their column a long time (`[staleness]`)
+    #[serde(default)]
+    pub staleness: StalenessConfig,
 }
 
 impl Default for GlobalConfig {
@@ -24,118 +129,443 @@ impl Default for GlobalConfig {
             default_agent: default_agent(),
             worktree: WorktreeConfig::default(),
             theme: ThemeConfig::default(),
+            confirm_destructive: true,
+            max_concurrent_agents: 0,
+            git_timeouts: GitTimeoutConfig::default(),
+            keybindings: HashMap::new(),
+            templates: Vec::new(),
+            tmux_session: None,
+            prompts: PromptTemplates::default(),
+            hooks: HooksConfig::default(),
+            env: BTreeMap::new(),
+            window_startup_command: None,
+            window_name_template: None,
+            test_command: None,
+            no_tmux: false,
+            multiplexer: Multiplexer::default(),
+            send_keys_interval_ms: 0,
+            prompt_delivery: PromptDelivery::default(),
+            max_captured_lines: default_max_captured_lines(),
+            views: Vec::new(),
+            columns: BTreeMap::new(),
+            webhook: WebhookConfig::default(),
+            cleanup: CleanupConfig::default(),
+            staleness: StalenessConfig::default(),
         }
     }
 }
 
-/// Theme configuration with hex colors
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `[theme]` section: a built-in palette (`name`) plus optional per-color overrides (hex, e.g. "#FFFF00").
+/// See `Theme::resolve` for how they combine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ThemeConfig {
-    /// Border color for selected elements (hex, e.g. "#FFFF00")
-    #[serde(default = "default_color_selected")]
-    pub color_selected: String,
+    /// Built-in palette: "default", "high-contrast" or "solarized"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Border color for selected elements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_selected: Option<String>,
+
+    /// Border color for normal/unselected elements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_normal: Option<String>,
+
+    /// Border color for dimmed/inactive elements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_dimmed: Option<String>,
+
+    /// Text color for titles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_text: Option<String>,
+
+    /// Accent color for highlights
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_accent: Option<String>,
+
+    /// Color for task descriptions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_description: Option<String>,
 
-    /// Border color for normal/unselected elements (hex, e.g. "#00FFFF")
-    #[serde(default = "default_color_normal")]
-    pub color_normal: String,
+    /// Color for summary labels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_column_header: Option<String>,
 
-    /// Border color for dimmed/inactive elements (hex, e.g. "#666666")
-    #[serde(default = "default_color_dimmed")]
-    pub color_dimmed: String,
+    /// Color for popup borders
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_popup_border: Option<String>,
 
-    /// Text color for titles (hex, e.g. "#FFFFFF")
-    #[serde(default = "default_color_text")]
-    pub color_text: String,
+    /// Background color for popup headers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_popup_header: Option<String>,
 
-    /// Accent color for highlights (hex, e.g. "#00FFFF")
-    #[serde(default = "default_color_accent")]
-    pub color_accent: String,
+    /// Backlog column header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_backlog: Option<String>,
 
-    /// Color for task descriptions (hex, e.g. "#FFB6C1")
-    #[serde(default = "default_color_description")]
-    pub color_description: String,
+    /// Planning column header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_planning: Option<String>,
+
+    /// Running column header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_running: Option<String>,
+
+    /// Review column header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_review: Option<String>,
+
+    /// Done column header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_done: Option<String>,
+
+    /// Phase indicator when the agent is done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_ready: Option<String>,
+
+    /// Phase indicator while the agent works
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_working: Option<String>,
+
+    /// Phase indicator when the agent exited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_exited: Option<String>,
+}
 
-    /// Color for column headers when not selected (hex, e.g. "#AAAAAA")
-    #[serde(default = "default_color_column_header")]
-    pub color_column_header: String,
+/// `theme = "solarized"` is shorthand for `[theme] name = "solarized"`
+fn deserialize_theme<'de, D>(deserializer: D) -> std::result::Result<ThemeConfig, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ThemeSetting {
+        Name(String),
+        Table(Box<ThemeConfig>),
+    }
 
-    /// Color for popup borders (hex, e.g. "#00FF00")
-    #[serde(default = "default_color_popup_border")]
-    pub color_popup_border: String,
+    Ok(match ThemeSetting::deserialize(deserializer)? {
+        ThemeSetting::Name(name) => ThemeConfig { name: Some(name), ..ThemeConfig::default() },
+        ThemeSetting::Table(config) => *config,
+    })
+}
 
-    /// Background color for popup headers (hex, e.g. "#00FFFF")
-    #[serde(default = "default_color_popup_header")]
-    pub color_popup_header: String,
+impl ThemeConfig {
+    /// Parse a hex color string to RGB tuple
+    pub fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+        let hex = hex.trim_start_matches('#');
+        if hex.len() != 6 {
+            return None;
+        }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        Some((r, g, b))
+    }
 }
 
-impl Default for ThemeConfig {
+fn default_agent() -> String {
+    "claude".to_string()
+}
+
+/// Git command timeouts in seconds (`[git_timeouts]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitTimeoutConfig {
+    /// Local-only commands: commit, stash, branch delete
+    #[serde(default = "default_git_local_secs")]
+    pub local_secs: u64,
+
+    /// `git worktree add` / `git worktree remove`
+    #[serde(default = "default_git_worktree_secs")]
+    pub worktree_secs: u64,
+
+    /// Commands that talk to a remote, e.g. push
+    #[serde(default = "default_git_network_secs")]
+    pub network_secs: u64,
+}
+
+impl Default for GitTimeoutConfig {
     fn default() -> Self {
         Self {
-            color_selected: default_color_selected(),
-            color_normal: default_color_normal(),
-            color_dimmed: default_color_dimmed(),
-            color_text: default_color_text(),
-            color_accent: default_color_accent(),
-            color_description: default_color_description(),
-            color_column_header: default_color_column_header(),
-            color_popup_border: default_color_popup_border(),
-            color_popup_header: default_color_popup_header(),
+            local_secs: default_git_local_secs(),
+            worktree_secs: default_git_worktree_secs(),
+            network_secs: default_git_network_secs(),
         }
     }
 }
 
-fn default_color_selected() -> String {
-    "#ead49a".to_string() // Yellow
+impl GitTimeoutConfig {
+    pub fn to_timeouts(&self) -> GitTimeouts {
+        GitTimeouts {
+            local: Duration::from_secs(self.local_secs),
+            worktree: Duration::from_secs(self.worktree_secs),
+            network: Duration::from_secs(self.network_secs),
+        }
+    }
 }
 
-fn default_color_normal() -> String {
-    "#5cfff7".to_string() // Cyan
+/// When cards turn yellow and red for time spent in their current column (`[staleness]`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StalenessConfig {
+    /// Tint cards at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Hours in a column before a card is yellow
+    #[serde(default = "default_stale_hours")]
+    pub stale_hours: u64,
+
+    /// Hours in a column before a card is red
+    #[serde(default = "default_very_stale_hours")]
+    pub very_stale_hours: u64,
 }
 
-fn default_color_dimmed() -> String {
-    "#9C9991".to_string() // Dark Gray
+impl Default for StalenessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stale_hours: default_stale_hours(),
+            very_stale_hours: default_very_stale_hours(),
+        }
+    }
 }
 
-fn default_color_text() -> String {
-    "#f2ece6".to_string() // Light Rose
+fn default_stale_hours() -> u64 {
+    24
 }
 
-fn default_color_accent() -> String {
-    "#5cfff7".to_string() // Cyan
+fn default_very_stale_hours() -> u64 {
+    72
 }
 
-fn default_color_description() -> String {
-    "#C4B0AC".to_string() // Rose (dimmed 80%)
+fn default_git_local_secs() -> u64 {
+    GitTimeouts::default().local.as_secs()
 }
 
-fn default_color_column_header() -> String {
-    "#a0d2fa".to_string() // Light Blue Gray
+fn default_git_worktree_secs() -> u64 {
+    GitTimeouts::default().worktree.as_secs()
 }
 
-fn default_color_popup_border() -> String {
-    "#9ffcf8".to_string() // Light Cyan
+fn default_git_network_secs() -> u64 {
+    GitTimeouts::default().network.as_secs()
 }
 
-fn default_color_popup_header() -> String {
-    "#69fae7".to_string() // Light Cyan
+/// A reusable starting point for new tasks (e.g. bugfix, feature, refactor)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    /// Name shown in the template picker
+    pub name: String,
+
+    /// Prepended to the title typed by the user (e.g. "fix: ")
+    #[serde(default)]
+    pub title_prefix: String,
+
+    /// Description scaffolding the prompt starts with
+    pub description: Option<String>,
+
+    /// Agent to use instead of the default agent
+    pub agent: Option<String>,
+
+    /// Tags attached to tasks created from this template
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Environment overrides for tasks created from this template
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
 }
 
-impl ThemeConfig {
-    /// Parse a hex color string to RGB tuple
-    pub fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
-        let hex = hex.trim_start_matches('#');
-        if hex.len() != 6 {
-            return None;
+/// `[prompts]` section: the message sent to the agent when a task enters each phase.
+/// Placeholders are filled by `skills::render_prompt`; unset phases use the plugin's prompt,
+/// then the built-in default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptTemplates {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub research: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub planning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub running: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<String>,
+    /// The project's `prompt_prefix`/`prompt_suffix`, set by `MergedConfig::merge`
+    #[serde(skip)]
+    pub prefix: Option<String>,
+    #[serde(skip)]
+    pub suffix: Option<String>,
+}
+
+impl PromptTemplates {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Per phase, `self` wins and `fallback` fills the gaps
+    pub fn or(&self, fallback: &PromptTemplates) -> PromptTemplates {
+        PromptTemplates {
+            research: self.research.clone().or_else(|| fallback.research.clone()),
+            planning: self.planning.clone().or_else(|| fallback.planning.clone()),
+            running: self.running.clone().or_else(|| fallback.running.clone()),
+            review: self.review.clone().or_else(|| fallback.review.clone()),
+            prefix: self.prefix.clone().or_else(|| fallback.prefix.clone()),
+            suffix: self.suffix.clone().or_else(|| fallback.suffix.clone()),
         }
-        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
-        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
-        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
-        Some((r, g, b))
+    }
+
+    /// `prompt` between the prefix and suffix. An empty prompt stays empty, since it means
+    /// "send nothing" (e.g. the void plugin).
+    pub fn wrap(&self, prompt: String) -> String {
+        if prompt.is_empty() {
+            return prompt;
+        }
+        let parts: Vec<&str> = [self.prefix.as_deref(), Some(prompt.as_str()), self.suffix.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        parts.join("\n\n")
     }
 }
 
-fn default_agent() -> String {
-    "claude".to_string()
+/// `[hooks]` section: shell commands run after a task changes column, once the built-in side
+/// effects (worktree, window, PR) are done. Keys name a transition (`running_to_review`) or only
+/// the destination (`to_done`); both run when both are set, the exact transition first.
+/// Placeholders are filled by `hooks::render_hook`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Block the transition when a hook fails (default: log the failure and move on)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict: bool,
+
+    /// Hook name -> command template
+    #[serde(flatten)]
+    pub commands: BTreeMap<String, String>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Hook names for a transition, most specific first
+    pub fn names_for(from: TaskStatus, to: TaskStatus) -> [String; 2] {
+        [format!("{}_to_{}", from.as_str(), to.as_str()), format!("to_{}", to.as_str())]
+    }
+
+    /// (name, command) of the hooks configured for a transition, in the order they run
+    pub fn commands_for(&self, from: TaskStatus, to: TaskStatus) -> Vec<(String, &str)> {
+        Self::names_for(from, to)
+            .into_iter()
+            .filter_map(|name| {
+                let command = self.commands.get(&name)?.trim();
+                (!command.is_empty()).then_some((name, command))
+            })
+            .collect()
+    }
+
+    /// Reject names that no transition would ever use (e.g. a typo like `runing_to_review`)
+    pub fn validate(&self) -> Result<()> {
+        let columns = TaskStatus::columns();
+        for name in self.commands.keys() {
+            let known = columns.iter().any(|to| {
+                name == &format!("to_{}", to.as_str())
+                    || columns.iter().any(|from| name == &format!("{}_to_{}", from.as_str(), to.as_str()))
+            });
+            if !known {
+                anyhow::bail!(
+                    "Unknown hook '{}' (expected <from>_to_<to> or to_<status>, e.g. running_to_review, to_done)",
+                    name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Per hook, `self` wins and `fallback` fills the gaps; strict if either is
+    pub fn or(&self, fallback: &HooksConfig) -> HooksConfig {
+        let mut commands = fallback.commands.clone();
+        commands.extend(self.commands.clone());
+        HooksConfig { strict: self.strict || fallback.strict, commands }
+    }
+}
+
+/// `[columns.<status>]` section: settings for one board column, keyed by status name
+/// (`backlog`, `planning`, `running`, `review`, `done`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ColumnConfig {
+    /// Sent to the task's agent window (once the agent is ready) when a task enters the column.
+    /// Placeholders as in `[prompts]`; unset or empty sends nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_enter_prompt: Option<String>,
+
+    /// Whether entering the column sends anything to the agent: the phase's skill command and
+    /// prompt, and `on_enter_prompt`. With `false` a Planning task's agent starts idle for the
+    /// human to drive. Unset = true.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dispatch_agent: Option<bool>,
+}
+
+/// `[webhook]` section: where to POST a JSON payload (see `webhook::Payload`) each time a task
+/// changes column. Unset `url` turns it off.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Extra header sent with each request, e.g. `"Authorization: Bearer <token>"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
+}
+
+impl WebhookConfig {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The URL to post to, if the webhook is on
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref().map(str::trim).filter(|u| !u.is_empty())
+    }
+
+    /// Reject URLs curl wouldn't treat as HTTP and headers without a `Name: value` shape
+    pub fn validate(&self) -> Result<()> {
+        if let Some(url) = self.url() {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                anyhow::bail!("Webhook url '{}' must start with http:// or https://", url);
+            }
+        }
+        if let Some(header) = &self.auth_header {
+            let valid = header.split_once(':').is_some_and(|(name, _)| {
+                !name.trim().is_empty() && !name.contains(char::is_whitespace)
+            });
+            if !valid {
+                anyhow::bail!("Webhook auth_header must look like 'Name: value'");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that every `[columns.<name>]` names a board column
+pub fn validate_columns(columns: &BTreeMap<String, ColumnConfig>) -> Result<()> {
+    if let Some(name) = columns.keys().find(|name| TaskStatus::from_str(name).is_none()) {
+        anyhow::bail!("Unknown column '{}' (expected backlog, planning, running, review or done)", name);
+    }
+    Ok(())
+}
+
+/// Whether `name` can be exported by a POSIX shell (`[A-Za-z_][A-Za-z0-9_]*`)
+pub fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Reject `[env]` names the shell couldn't export
+pub fn validate_env(env: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(name) = env.keys().find(|name| !is_env_name(name)) {
+        anyhow::bail!("'{}' is not a valid environment variable name", name);
+    }
+    Ok(())
 }
 
 /// Worktree configuration
@@ -149,9 +579,183 @@ pub struct WorktreeConfig {
     #[serde(default = "default_true")]
     pub auto_cleanup: bool,
 
-    /// Base branch to create worktrees from
-    #[serde(default = "default_base_branch")]
-    pub base_branch: String,
+    /// Base branch to create worktrees from. Unset: detected per project from
+    /// `origin/HEAD`, falling back to `main` (see `git::default_branch`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_branch: Option<String>,
+
+    /// Commit any uncommitted worktree changes when a task moves Running → Review
+    #[serde(default)]
+    pub auto_commit_on_review: bool,
+
+    /// What to do when a worktree with uncommitted changes is about to be removed
+    #[serde(default)]
+    pub dirty_policy: DirtyWorktreePolicy,
+
+    /// Where task worktrees are created: relative to the project root, or an absolute path
+    /// (each project then gets its own subdirectory)
+    #[serde(default = "default_worktree_dir")]
+    pub worktree_dir: String,
+
+    /// Refuse to create a worktree while the filesystem it goes on has less than this many MB
+    /// free. Unset (or 0): no check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_free_mb: Option<u64>,
+}
+
+/// How the prompt an agent is started with gets onto its command line (`prompt_delivery`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptDelivery {
+    /// Inline, unless it's longer than `LONG_PROMPT_BYTES`
+    #[default]
+    Auto,
+    /// Always written to `.agtx/prompt.md` in the worktree and read back by the shell
+    File,
+    /// Always quoted into the command, however long
+    Inline,
+}
+
+impl PromptDelivery {
+    /// Longest prompt `Auto` puts on the command line. tmux refuses commands of more than about
+    /// 16 KB, and quoting and multi-byte text can grow a prompt well past its length in chars.
+    pub const LONG_PROMPT_BYTES: usize = 4096;
+
+    /// Whether `prompt` goes through the prompt file
+    pub fn uses_file(&self, prompt: &str) -> bool {
+        match self {
+            PromptDelivery::Auto => prompt.len() > Self::LONG_PROMPT_BYTES,
+            PromptDelivery::File => !prompt.is_empty(),
+            PromptDelivery::Inline => false,
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == PromptDelivery::default()
+    }
+}
+
+/// Terminal multiplexer backing `TmuxOperations`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Multiplexer {
+    /// A dedicated tmux server (`tmux -L agtx`)
+    #[default]
+    Tmux,
+    /// zellij sessions, with one tab per task (see `tmux::ZellijOps`)
+    Zellij,
+}
+
+impl Multiplexer {
+    /// The program that has to be installed
+    pub fn program(&self) -> &'static str {
+        match self {
+            Multiplexer::Tmux => "tmux",
+            Multiplexer::Zellij => "zellij",
+        }
+    }
+
+    /// Why the multiplexer can't run on this OS (see `platform::multiplexer_unsupported`)
+    pub fn unsupported_reason(&self) -> Option<String> {
+        crate::platform::multiplexer_unsupported(self.program(), std::env::consts::OS)
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Multiplexer::default()
+    }
+}
+
+/// Policy for removing a worktree that still has uncommitted changes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirtyWorktreePolicy {
+    /// Refuse to remove the worktree (the task stays where it is)
+    #[default]
+    Abort,
+    /// Stash the changes into a named stash, then remove the worktree
+    Stash,
+    /// Remove the worktree and discard the changes
+    Force,
+}
+
+/// The resources a cleanup removes. A branch is only ever deleted together with its worktree
+/// (git won't delete a checked-out branch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CleanupPolicy {
+    pub kill_window: bool,
+    pub remove_worktree: bool,
+    pub delete_branch: bool,
+}
+
+/// The actions that clean up after a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CleanupAction {
+    /// Moving to Done: the branch is kept so the task can be reopened
+    Done,
+    /// Deleting the task: everything goes
+    Delete,
+}
+
+impl CleanupAction {
+    pub fn default_policy(self) -> CleanupPolicy {
+        CleanupPolicy { kill_window: true, remove_worktree: true, delete_branch: self == CleanupAction::Delete }
+    }
+}
+
+/// One `[cleanup.<action>]` table; unset flags keep the action's default
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupRule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<bool>,
+}
+
+/// `[cleanup]` section, e.g. `[cleanup.done] worktree = false` to keep finished worktrees
+/// around for reference
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupConfig {
+    #[serde(default, skip_serializing_if = "cleanup_rule_is_empty")]
+    pub done: CleanupRule,
+    #[serde(default, skip_serializing_if = "cleanup_rule_is_empty")]
+    pub delete: CleanupRule,
+}
+
+fn cleanup_rule_is_empty(rule: &CleanupRule) -> bool {
+    rule == &CleanupRule::default()
+}
+
+impl CleanupConfig {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// What `action` removes: the configured flags over the action's defaults
+    pub fn policy(&self, action: CleanupAction) -> CleanupPolicy {
+        let rule = match action {
+            CleanupAction::Done => &self.done,
+            CleanupAction::Delete => &self.delete,
+        };
+        let default = action.default_policy();
+        CleanupPolicy {
+            kill_window: rule.window.unwrap_or(default.kill_window),
+            remove_worktree: rule.worktree.unwrap_or(default.remove_worktree),
+            delete_branch: rule.branch.unwrap_or(default.delete_branch),
+        }
+    }
+
+    /// Reject deleting a branch whose worktree is kept
+    pub fn validate(&self) -> Result<()> {
+        for (name, action) in [("done", CleanupAction::Done), ("delete", CleanupAction::Delete)] {
+            let policy = self.policy(action);
+            if policy.delete_branch && !policy.remove_worktree {
+                anyhow::bail!("[cleanup.{}] can't delete the branch while keeping its worktree", name);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Default for WorktreeConfig {
@@ -159,7 +763,11 @@ impl Default for WorktreeConfig {
         Self {
             enabled: true,
             auto_cleanup: true,
-            base_branch: "main".to_string(),
+            base_branch: None,
+            auto_commit_on_review: false,
+            dirty_policy: DirtyWorktreePolicy::default(),
+            worktree_dir: default_worktree_dir(),
+            min_free_mb: None,
         }
     }
 }
@@ -168,8 +776,12 @@ fn default_true() -> bool {
     true
 }
 
-fn default_base_branch() -> String {
-    "main".to_string()
+fn default_max_captured_lines() -> usize {
+    crate::tmux::DEFAULT_MAX_CAPTURED_LINES
+}
+
+fn default_worktree_dir() -> String {
+    crate::git::DEFAULT_WORKTREE_DIR.to_string()
 }
 
 /// Project-specific configuration (stored in .agtx/config.toml)
@@ -192,6 +804,56 @@ pub struct ProjectConfig {
 
     /// Workflow plugin name (e.g. "gsd", "spec-kit")
     pub workflow_plugin: Option<String>,
+
+    /// Override auto-commit on Running → Review for this project
+    pub auto_commit_on_review: Option<bool>,
+
+    /// Trusted project: no confirmations and auto-commit on Review, unless
+    /// `confirm_destructive`/`auto_commit_on_review` are set here explicitly
+    #[serde(default)]
+    pub fast_mode: bool,
+
+    /// Override the global `confirm_destructive` for this project
+    pub confirm_destructive: Option<bool>,
+
+    /// Override the tmux session task windows are created in
+    pub tmux_session: Option<String>,
+
+    /// Override prompt templates per phase (falls back to the global `[prompts]`)
+    #[serde(default)]
+    pub prompts: PromptTemplates,
+
+    /// Standing instructions put before every phase prompt sent to this project's agents
+    /// (after a skill command, as its argument)
+    pub prompt_prefix: Option<String>,
+
+    /// ...and after it
+    pub prompt_suffix: Option<String>,
+
+    /// Per-project status hooks, added to (and overriding by name) the global `[hooks]`
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Per-project environment, added to (and overriding by name) the global `[env]`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Override the command run in new task windows before the agent ("" disables it)
+    pub window_startup_command: Option<String>,
+
+    /// Override the name of new task windows ("" uses the global one)
+    pub window_name_template: Option<String>,
+
+    /// Override the test command run on entering Review ("" disables it)
+    pub test_command: Option<String>,
+
+    /// Per-project column settings, overriding the global ones column by column
+    #[serde(default)]
+    pub columns: BTreeMap<String, ColumnConfig>,
+
+    /// Per-project webhook, replacing the global `[webhook]` when it has a url
+    #[serde(default)]
+    pub webhook: WebhookConfig,
 }
 
 impl GlobalConfig {
@@ -208,6 +870,13 @@ impl GlobalConfig {
         }
     }
 
+    /// Git operations with the configured timeouts, worktree directory and free-space check
+    pub fn git_ops(&self) -> RealGitOps {
+        RealGitOps::with_timeouts(self.git_timeouts.to_timeouts())
+            .with_worktree_dir(self.worktree.worktree_dir.clone())
+            .with_min_free_mb(self.worktree.min_free_mb)
+    }
+
     /// Save global config to default location
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
@@ -304,17 +973,41 @@ pub struct MergedConfig {
     pub default_agent: String,
     pub worktree_enabled: bool,
     pub auto_cleanup: bool,
-    pub base_branch: String,
+    /// Configured base branch (project, then global); None = detect it
+    pub base_branch: Option<String>,
     pub github_url: Option<String>,
-    pub theme: ThemeConfig,
+    pub theme: Theme,
     pub copy_files: Option<String>,
     pub init_script: Option<String>,
     pub workflow_plugin: Option<String>,
+    /// The project's `fast_mode`, already applied to the settings below
+    pub fast_mode: bool,
+    pub confirm_destructive: bool,
+    pub auto_commit_on_review: bool,
+    pub dirty_worktree_policy: DirtyWorktreePolicy,
+    pub templates: Vec<Template>,
+    pub views: Vec<View>,
+    pub max_concurrent_agents: usize,
+    pub max_captured_lines: usize,
+    pub prompt_delivery: PromptDelivery,
+    pub tmux_session: Option<String>,
+    pub prompts: PromptTemplates,
+    pub hooks: HooksConfig,
+    pub env: BTreeMap<String, String>,
+    pub window_startup_command: Option<String>,
+    pub window_name_template: String,
+    pub test_command: Option<String>,
+    pub columns: BTreeMap<String, ColumnConfig>,
+    pub webhook: WebhookConfig,
+    pub cleanup: CleanupConfig,
+    pub staleness: StalenessConfig,
 }
 
 impl MergedConfig {
-    /// Create merged config from global and project configs
+    /// Create merged config from global and project configs. A project's `fast_mode` replaces
+    /// the global confirmation and auto-commit defaults; the project's own settings still win.
     pub fn merge(global: &GlobalConfig, project: &ProjectConfig) -> Self {
+        let fast = project.fast_mode;
         Self {
             default_agent: project
                 .default_agent
@@ -322,17 +1015,87 @@ impl MergedConfig {
                 .unwrap_or_else(|| global.default_agent.clone()),
             worktree_enabled: global.worktree.enabled,
             auto_cleanup: global.worktree.auto_cleanup,
-            base_branch: project
-                .base_branch
-                .clone()
-                .unwrap_or_else(|| global.worktree.base_branch.clone()),
+            base_branch: project.base_branch.clone().or_else(|| global.worktree.base_branch.clone()),
             github_url: project.github_url.clone(),
-            theme: global.theme.clone(),
+            // Unknown names are rejected at startup; fall back to the default palette here
+            theme: Theme::resolve(&global.theme).unwrap_or_default(),
             copy_files: project.copy_files.clone(),
             init_script: project.init_script.clone(),
             workflow_plugin: project.workflow_plugin.clone(),
+            fast_mode: fast,
+            confirm_destructive: project.confirm_destructive.unwrap_or(!fast && global.confirm_destructive),
+            auto_commit_on_review: project
+                .auto_commit_on_review
+                .unwrap_or(fast || global.worktree.auto_commit_on_review),
+            dirty_worktree_policy: global.worktree.dirty_policy,
+            templates: global.templates.clone(),
+            views: global.views.clone(),
+            max_concurrent_agents: global.max_concurrent_agents,
+            max_captured_lines: global.max_captured_lines,
+            prompt_delivery: global.prompt_delivery,
+            tmux_session: project
+                .tmux_session
+                .clone()
+                .or_else(|| global.tmux_session.clone())
+                .filter(|s| !s.trim().is_empty()),
+            prompts: PromptTemplates {
+                prefix: project.prompt_prefix.clone().filter(|s| !s.trim().is_empty()),
+                suffix: project.prompt_suffix.clone().filter(|s| !s.trim().is_empty()),
+                ..project.prompts.or(&global.prompts)
+            },
+            hooks: project.hooks.or(&global.hooks),
+            env: global.env.clone().into_iter().chain(project.env.clone()).collect(),
+            window_startup_command: project
+                .window_startup_command
+                .clone()
+                .or_else(|| global.window_startup_command.clone())
+                .filter(|c| !c.trim().is_empty()),
+            window_name_template: [&project.window_name_template, &global.window_name_template]
+                .into_iter()
+                .flatten()
+                .find(|t| !t.trim().is_empty())
+                .cloned()
+                .unwrap_or_else(|| slug::DEFAULT_WINDOW_TEMPLATE.to_string()),
+            test_command: project
+                .test_command
+                .clone()
+                .or_else(|| global.test_command.clone())
+                .filter(|c| !c.trim().is_empty()),
+            columns: global.columns.clone().into_iter().chain(project.columns.clone()).collect(),
+            webhook: if project.webhook.url().is_some() { project.webhook.clone() } else { global.webhook.clone() },
+            cleanup: global.cleanup.clone(),
+            staleness: global.staleness,
         }
     }
+
+    /// Whether a task entering `status` gets its agent sent the phase command
+    /// (`[columns.<status>] dispatch_agent`, on by default)
+    pub fn should_dispatch(&self, status: TaskStatus) -> bool {
+        self.columns
+            .get(status.as_str())
+            .and_then(|c| c.dispatch_agent)
+            .unwrap_or(true)
+    }
+
+    /// The prompt to send when a task enters `status` (`[columns.<status>] on_enter_prompt`)
+    pub fn on_enter_prompt(&self, status: TaskStatus) -> Option<&str> {
+        self.columns
+            .get(status.as_str())
+            .and_then(|c| c.on_enter_prompt.as_deref())
+            .filter(|p| !p.trim().is_empty())
+    }
+
+    /// The environment for a task's window: the configured `[env]` with the task's overrides
+    pub fn env_for(&self, task: &Task) -> BTreeMap<String, String> {
+        let mut env = self.env.clone();
+        env.extend(task.env.clone());
+        env
+    }
+
+    /// The tmux session a project's task windows live in
+    pub fn tmux_session_for(&self, project_name: &str) -> String {
+        self.tmux_session.clone().unwrap_or_else(|| project_name.to_string())
+    }
 }
 
 /// Workflow plugin configuration loaded from plugin.toml
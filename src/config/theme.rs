@@ -0,0 +1,170 @@
+//! Built-in color palettes, selected with `[theme] name` (or `theme = "<name>"`).
+
+use anyhow::{bail, Result};
+
+use super::ThemeConfig;
+use crate::db::TaskStatus;
+
+/// Names of the built-in palettes, in the order they're listed in errors
+pub const THEME_NAMES: &[&str] = &["default", "high-contrast", "solarized"];
+
+/// Resolved board colors (hex, e.g. "#FFFF00"): a built-in palette plus any `[theme]` overrides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    /// Border/text for the selected card, column and list entry
+    pub color_selected: String,
+    /// Border for unselected cards and columns
+    pub color_normal: String,
+    /// Hints, archived tasks and inactive elements
+    pub color_dimmed: String,
+    /// Task titles and popup text
+    pub color_text: String,
+    /// Highlights (search prompt, plugin label)
+    pub color_accent: String,
+    /// Task descriptions
+    pub color_description: String,
+    /// Labels in summary popups
+    pub color_column_header: String,
+    /// Popup borders
+    pub color_popup_border: String,
+    /// Background of popup headers
+    pub color_popup_header: String,
+    /// Header of each unselected column, by status
+    pub color_backlog: String,
+    pub color_planning: String,
+    pub color_running: String,
+    pub color_review: String,
+    pub color_done: String,
+    /// Agent phase indicator on task cards: finished, still working, exited
+    pub color_ready: String,
+    pub color_working: String,
+    pub color_exited: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            color_selected: "#ead49a".to_string(),      // Yellow
+            color_normal: "#5cfff7".to_string(),        // Cyan
+            color_dimmed: "#9C9991".to_string(),        // Dark Gray
+            color_text: "#f2ece6".to_string(),          // Light Rose
+            color_accent: "#5cfff7".to_string(),        // Cyan
+            color_description: "#C4B0AC".to_string(),   // Rose (dimmed 80%)
+            color_column_header: "#a0d2fa".to_string(), // Light Blue Gray
+            color_popup_border: "#9ffcf8".to_string(),  // Light Cyan
+            color_popup_header: "#69fae7".to_string(),  // Light Cyan
+            color_backlog: "#a0d2fa".to_string(),
+            color_planning: "#a0d2fa".to_string(),
+            color_running: "#a0d2fa".to_string(),
+            color_review: "#a0d2fa".to_string(),
+            color_done: "#a0d2fa".to_string(),
+            color_ready: "#5fd75f".to_string(),         // Green
+            color_working: "#ead49a".to_string(),       // Yellow
+            color_exited: "#ff5f5f".to_string(),        // Red
+        }
+    }
+}
+
+impl Theme {
+    /// Pure black-and-primary palette for low-vision use and projectors
+    fn high_contrast() -> Self {
+        Self {
+            color_selected: "#FFFF00".to_string(),
+            color_normal: "#FFFFFF".to_string(),
+            color_dimmed: "#C0C0C0".to_string(),
+            color_text: "#FFFFFF".to_string(),
+            color_accent: "#00FFFF".to_string(),
+            color_description: "#FFFFFF".to_string(),
+            color_column_header: "#FFFFFF".to_string(),
+            color_popup_border: "#FFFF00".to_string(),
+            color_popup_header: "#00FFFF".to_string(),
+            color_backlog: "#FFFFFF".to_string(),
+            color_planning: "#00FFFF".to_string(),
+            color_running: "#FFFF00".to_string(),
+            color_review: "#FF00FF".to_string(),
+            color_done: "#00FF00".to_string(),
+            color_ready: "#00FF00".to_string(),
+            color_working: "#FFFF00".to_string(),
+            color_exited: "#FF0000".to_string(),
+        }
+    }
+
+    /// Solarized (dark) accent colors
+    fn solarized() -> Self {
+        Self {
+            color_selected: "#b58900".to_string(),      // yellow
+            color_normal: "#2aa198".to_string(),        // cyan
+            color_dimmed: "#586e75".to_string(),        // base01
+            color_text: "#93a1a1".to_string(),          // base1
+            color_accent: "#268bd2".to_string(),        // blue
+            color_description: "#839496".to_string(),   // base0
+            color_column_header: "#93a1a1".to_string(), // base1
+            color_popup_border: "#2aa198".to_string(),  // cyan
+            color_popup_header: "#268bd2".to_string(),  // blue
+            color_backlog: "#839496".to_string(),       // base0
+            color_planning: "#6c71c4".to_string(),      // violet
+            color_running: "#b58900".to_string(),       // yellow
+            color_review: "#d33682".to_string(),        // magenta
+            color_done: "#859900".to_string(),          // green
+            color_ready: "#859900".to_string(),         // green
+            color_working: "#b58900".to_string(),       // yellow
+            color_exited: "#dc322f".to_string(),        // red
+        }
+    }
+
+    /// A built-in palette by name
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Self::default()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "solarized" => Some(Self::solarized()),
+            _ => None,
+        }
+    }
+
+    /// Pick the configured palette and apply per-color overrides on top of it
+    pub fn resolve(config: &ThemeConfig) -> Result<Self> {
+        let name = config.name.as_deref().unwrap_or("default");
+        let Some(mut theme) = Self::builtin(name) else {
+            bail!("Unknown theme '{}' (expected one of: {})", name, THEME_NAMES.join(", "));
+        };
+
+        let overrides = [
+            (&mut theme.color_selected, &config.color_selected),
+            (&mut theme.color_normal, &config.color_normal),
+            (&mut theme.color_dimmed, &config.color_dimmed),
+            (&mut theme.color_text, &config.color_text),
+            (&mut theme.color_accent, &config.color_accent),
+            (&mut theme.color_description, &config.color_description),
+            (&mut theme.color_column_header, &config.color_column_header),
+            (&mut theme.color_popup_border, &config.color_popup_border),
+            (&mut theme.color_popup_header, &config.color_popup_header),
+            (&mut theme.color_backlog, &config.color_backlog),
+            (&mut theme.color_planning, &config.color_planning),
+            (&mut theme.color_running, &config.color_running),
+            (&mut theme.color_review, &config.color_review),
+            (&mut theme.color_done, &config.color_done),
+            (&mut theme.color_ready, &config.color_ready),
+            (&mut theme.color_working, &config.color_working),
+            (&mut theme.color_exited, &config.color_exited),
+        ];
+        for (color, value) in overrides {
+            if let Some(value) = value {
+                *color = value.clone();
+            }
+        }
+
+        Ok(theme)
+    }
+
+    /// Color used for a status (column headers)
+    pub fn status_color(&self, status: TaskStatus) -> &str {
+        match status {
+            TaskStatus::Backlog => &self.color_backlog,
+            TaskStatus::Planning => &self.color_planning,
+            TaskStatus::Running => &self.color_running,
+            TaskStatus::Review => &self.color_review,
+            TaskStatus::Done => &self.color_done,
+        }
+    }
+}
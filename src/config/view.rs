@@ -0,0 +1,86 @@
+//! Saved board views (`[[views]]`): named task filters cycled with a key.
+//!
+//! A filter is a small predicate tree, written as TOML inline tables:
+//! `filter = { all = [{ tag = "urgent" }, { status = "running" }] }`.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::db::{Task, TaskStatus};
+
+/// A predicate over tasks. Leaves test one property; `all`/`any`/`not` combine them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskFilter {
+    /// Has this tag (without the `#`, case-insensitive)
+    Tag(String),
+    /// In this column (`backlog`, `planning`, `running`, `review`, `done`)
+    Status(String),
+    /// Assigned to this agent
+    Agent(String),
+    /// Title or description contains the text (case-insensitive)
+    Text(String),
+    /// Every filter matches (an empty list matches everything)
+    All(Vec<TaskFilter>),
+    /// At least one filter matches
+    Any(Vec<TaskFilter>),
+    /// The filter doesn't match
+    Not(Box<TaskFilter>),
+}
+
+impl TaskFilter {
+    pub fn matches(&self, task: &Task) -> bool {
+        match self {
+            TaskFilter::Tag(tag) => {
+                let tag = tag.trim_start_matches('#');
+                task.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+            }
+            TaskFilter::Status(status) => task.status.as_str().eq_ignore_ascii_case(status),
+            TaskFilter::Agent(agent) => task.agent == *agent,
+            TaskFilter::Text(text) => {
+                let text = text.to_lowercase();
+                task.title.to_lowercase().contains(&text)
+                    || task.description.as_deref().is_some_and(|d| d.to_lowercase().contains(&text))
+            }
+            TaskFilter::All(filters) => filters.iter().all(|f| f.matches(task)),
+            TaskFilter::Any(filters) => filters.iter().any(|f| f.matches(task)),
+            TaskFilter::Not(filter) => !filter.matches(task),
+        }
+    }
+
+    /// Reject status names that could never match
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            TaskFilter::Status(status) => {
+                if TaskStatus::from_str(&status.to_lowercase()).is_none() {
+                    bail!("unknown status '{}' (expected backlog, planning, running, review or done)", status);
+                }
+                Ok(())
+            }
+            TaskFilter::All(filters) | TaskFilter::Any(filters) => filters.iter().try_for_each(|f| f.validate()),
+            TaskFilter::Not(filter) => filter.validate(),
+            TaskFilter::Tag(_) | TaskFilter::Agent(_) | TaskFilter::Text(_) => Ok(()),
+        }
+    }
+}
+
+/// A named filter from `[[views]]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct View {
+    pub name: String,
+    pub filter: TaskFilter,
+}
+
+/// The tasks a view shows, in their original order
+pub fn apply_view<'a>(view: &View, tasks: &'a [Task]) -> Vec<&'a Task> {
+    tasks.iter().filter(|t| view.filter.matches(t)).collect()
+}
+
+/// The view after `current` when cycling: none → first → ... → last → none
+pub fn next_view(views: &[View], current: Option<&str>) -> Option<View> {
+    let next = match current {
+        None => 0,
+        Some(name) => views.iter().position(|v| v.name == name).map_or(views.len(), |i| i + 1),
+    };
+    views.get(next).cloned()
+}
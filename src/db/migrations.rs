@@ -0,0 +1,290 @@
+//! Versioned schema migrations.
+//!
+//! Each `Migration` moves a database up one version. The version a database is at is kept in
+//! SQLite's `PRAGMA user_version` (0 for a new or pre-versioning database). Opening a database
+//! applies every pending migration in a single transaction, so a failure leaves it untouched.
+
+use anyhow::{Context, Result};
+use rusqlite::Connection;
+
+/// One schema change
+#[derive(Debug, Clone, Copy)]
+pub enum Step {
+    /// Arbitrary SQL (use `IF NOT EXISTS` so pre-versioning databases are handled)
+    Sql(&'static str),
+    /// Add a column unless it's already there (pre-versioning databases added columns ad hoc)
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
+
+/// The steps that take a database to `version`
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub steps: &'static [Step],
+}
+
+/// Migrations for per-project databases (tasks). Append new versions; never edit old ones.
+pub const PROJECT_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "tasks table",
+        steps: &[
+            Step::Sql(
+                r#"
+                CREATE TABLE IF NOT EXISTS tasks (
+                    id TEXT PRIMARY KEY,
+                    title TEXT NOT NULL,
+                    description TEXT,
+                    status TEXT NOT NULL DEFAULT 'backlog',
+                    agent TEXT NOT NULL,
+                    project_id TEXT NOT NULL,
+                    session_name TEXT,
+                    worktree_path TEXT,
+                    branch_name TEXT,
+                    pr_number INTEGER,
+                    pr_url TEXT,
+                    plugin TEXT,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
+                CREATE INDEX IF NOT EXISTS idx_tasks_project ON tasks(project_id);
+                "#,
+            ),
+            Step::AddColumn { table: "tasks", column: "branch_name", definition: "TEXT" },
+            Step::AddColumn { table: "tasks", column: "pr_number", definition: "INTEGER" },
+            Step::AddColumn { table: "tasks", column: "pr_url", definition: "TEXT" },
+            Step::AddColumn { table: "tasks", column: "plugin", definition: "TEXT" },
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "archived flag",
+        steps: &[Step::AddColumn {
+            table: "tasks",
+            column: "archived",
+            definition: "INTEGER NOT NULL DEFAULT 0",
+        }],
+    },
+    Migration {
+        version: 3,
+        description: "task tags",
+        steps: &[Step::AddColumn { table: "tasks", column: "tags", definition: "TEXT" }],
+    },
+    Migration {
+        version: 4,
+        description: "blocked-by dependencies",
+        steps: &[Step::AddColumn { table: "tasks", column: "depends_on", definition: "TEXT" }],
+    },
+    Migration {
+        version: 5,
+        description: "trash",
+        steps: &[Step::AddColumn { table: "tasks", column: "deleted_at", definition: "TEXT" }],
+    },
+    Migration {
+        version: 6,
+        description: "agent session id",
+        steps: &[Step::AddColumn { table: "tasks", column: "agent_session_id", definition: "TEXT" }],
+    },
+    Migration {
+        version: 7,
+        description: "task env overrides",
+        steps: &[Step::AddColumn { table: "tasks", column: "env", definition: "TEXT" }],
+    },
+    Migration {
+        version: 8,
+        description: "review test status",
+        steps: &[Step::AddColumn { table: "tasks", column: "test_status", definition: "INTEGER" }],
+    },
+    Migration {
+        version: 9,
+        description: "task notes",
+        steps: &[Step::AddColumn { table: "tasks", column: "notes", definition: "TEXT" }],
+    },
+    Migration {
+        version: 10,
+        description: "task event history",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS task_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                task_id TEXT NOT NULL,
+                at TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                detail TEXT NOT NULL DEFAULT ''
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_task_events_task ON task_events(task_id);
+            "#,
+        )],
+    },
+    Migration {
+        version: 11,
+        description: "agent exit code",
+        steps: &[Step::AddColumn { table: "tasks", column: "last_exit_code", definition: "INTEGER" }],
+    },
+    Migration {
+        version: 12,
+        description: "pinned flag",
+        steps: &[Step::AddColumn {
+            table: "tasks",
+            column: "pinned",
+            definition: "INTEGER NOT NULL DEFAULT 0",
+        }],
+    },
+    Migration {
+        version: 13,
+        description: "agent token usage",
+        steps: &[Step::AddColumn { table: "tasks", column: "usage", definition: "TEXT" }],
+    },
+    Migration {
+        version: 14,
+        description: "task working subdirectory",
+        steps: &[Step::AddColumn { table: "tasks", column: "subdir", definition: "TEXT" }],
+    },
+    Migration {
+        version: 15,
+        description: "task base ref",
+        steps: &[Step::AddColumn { table: "tasks", column: "from_ref", definition: "TEXT" }],
+    },
+    Migration {
+        version: 16,
+        description: "time of last column change",
+        steps: &[
+            Step::AddColumn { table: "tasks", column: "status_changed_at", definition: "TEXT" },
+            // Tasks moved before this version: their latest move in the history
+            Step::Sql(
+                r#"
+                UPDATE tasks SET status_changed_at = (
+                    SELECT MAX(at) FROM task_events e WHERE e.task_id = tasks.id AND e.kind = 'moved'
+                ) WHERE status_changed_at IS NULL;
+                "#,
+            ),
+        ],
+    },
+    Migration {
+        version: 17,
+        description: "stored task slug",
+        steps: &[
+            Step::AddColumn { table: "tasks", column: "slug", definition: "TEXT" },
+            // Tasks that already have resources: their branch is `task/<slug>`
+            Step::Sql(
+                r#"
+                UPDATE tasks SET slug = substr(branch_name, 6)
+                WHERE slug IS NULL AND branch_name LIKE 'task/%';
+                "#,
+            ),
+        ],
+    },
+    Migration {
+        version: 18,
+        description: "queued agent dispatches",
+        steps: &[Step::Sql(
+            r#"
+            CREATE TABLE IF NOT EXISTS dispatch_queue (
+                task_id TEXT PRIMARY KEY,
+                position INTEGER NOT NULL,
+                target TEXT NOT NULL,
+                command TEXT NOT NULL
+            );
+            "#,
+        )],
+    },
+];
+
+/// Migrations for the global index database (projects, running agents)
+pub const GLOBAL_MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "projects and running agents",
+    steps: &[Step::Sql(
+        r#"
+        CREATE TABLE IF NOT EXISTS projects (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            path TEXT NOT NULL UNIQUE,
+            github_url TEXT,
+            default_agent TEXT,
+            last_opened TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS running_agents (
+            session_name TEXT PRIMARY KEY,
+            project_id TEXT NOT NULL,
+            task_id TEXT NOT NULL,
+            agent_name TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            status TEXT NOT NULL,
+            FOREIGN KEY (project_id) REFERENCES projects(id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_running_project ON running_agents(project_id);
+        "#,
+    )],
+}];
+
+/// Latest version in a migration list
+pub fn latest_version(migrations: &[Migration]) -> u32 {
+    migrations.iter().map(|m| m.version).max().unwrap_or(0)
+}
+
+/// Version a database has been migrated to
+pub fn schema_version(conn: &Connection) -> Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("Failed to read schema version")
+}
+
+/// Apply all migrations newer than the database's version, in order, in one transaction.
+/// Returns the resulting version.
+pub fn migrate(conn: &mut Connection, migrations: &[Migration]) -> Result<u32> {
+    let current = schema_version(conn)?;
+    let latest = latest_version(migrations);
+    if current > latest {
+        anyhow::bail!(
+            "Database schema version {} is newer than this agtx supports ({}); please upgrade agtx",
+            current,
+            latest
+        );
+    }
+
+    let tx = conn.transaction().context("Failed to start migration")?;
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        for step in migration.steps {
+            apply_step(&tx, step).with_context(|| {
+                format!("Migration to v{} ({}) failed", migration.version, migration.description)
+            })?;
+        }
+        tx.pragma_update(None, "user_version", migration.version)?;
+    }
+    tx.commit().context("Failed to commit migration")?;
+
+    Ok(latest.max(current))
+}
+
+fn apply_step(conn: &Connection, step: &Step) -> Result<()> {
+    match *step {
+        Step::Sql(sql) => conn.execute_batch(sql)?,
+        Step::AddColumn { table, column, definition } => {
+            if !has_column(conn, table, column)? {
+                conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    for name in names {
+        if name? == column {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
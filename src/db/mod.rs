@@ -1,3 +1,4 @@
+pub mod migrations;
 mod models;
 mod schema;
 
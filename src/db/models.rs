@@ -1,5 +1,9 @@
+use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::config::Template;
 
 /// Task status in the kanban board
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -44,6 +48,9 @@ impl TaskStatus {
     }
 }
 
+/// Task identifier (a UUID string)
+pub type TaskId = String;
+
 /// A task on the kanban board
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -59,10 +66,163 @@ pub struct Task {
     pub pr_number: Option<i32>,
     pub pr_url: Option<String>,
     pub plugin: Option<String>,
+    /// Hidden from the board (Done tasks only); kept for history
+    #[serde(default)]
+    pub archived: bool,
+    /// Free-form labels, usually inherited from a task template
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Tasks that must be Done before this one can start running
+    #[serde(default)]
+    pub depends_on: Vec<TaskId>,
+    /// When the task was moved to the trash; trashed tasks are hidden from the board
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// The agent's conversation id, for agents that can resume one (set when the agent starts)
+    #[serde(default)]
+    pub agent_session_id: Option<String>,
+    /// Environment variables for this task's window, overriding the configured `[env]`
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Result of the configured `test_command` from the last time the task entered Review
+    #[serde(default)]
+    pub test_status: Option<bool>,
+    /// Timestamped notes for humans (never sent to the agent), oldest first
+    #[serde(default)]
+    pub notes: Vec<TaskNote>,
+    /// Exit code of the agent process, once it has exited (cleared when a new agent starts)
+    #[serde(default)]
+    pub last_exit_code: Option<i32>,
+    /// Kept at the top of its column (see `board::sort_column`)
+    #[serde(default)]
+    pub pinned: bool,
+    /// Tokens and cost the agent last reported (see `agent::usage`); None until it reports any
+    #[serde(default)]
+    pub usage: Option<AgentUsage>,
+    /// Directory inside the worktree the agent works in (`/`-separated, relative to the
+    /// worktree root); None for the root itself
+    #[serde(default)]
+    pub subdir: Option<String>,
+    /// Branch, tag or commit the task's worktree branches from; None for the base branch
+    #[serde(default)]
+    pub from_ref: Option<String>,
+    /// When the task entered its current column (set by `Database::update_task`); None for
+    /// tasks that haven't moved since they were created
+    #[serde(default)]
+    pub status_changed_at: Option<DateTime<Utc>>,
+    /// Slug the worktree, branch and window were named after, stored when the worktree is
+    /// first created so a title edit never renames them; None until then (see `resource_slug`)
+    #[serde(default)]
+    pub slug: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// What happened in a `TaskEvent`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskEventKind {
+    Created,
+    Moved,
+    WorktreeCreated,
+    WindowCreated,
+    AgentDispatched,
+    PrOpened,
+    TestsRun,
+    AgentExited,
+    Failed,
+    Trashed,
+    Restored,
+    Interrupted,
+    Abandoned,
+}
+
+impl TaskEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskEventKind::Created => "created",
+            TaskEventKind::Moved => "moved",
+            TaskEventKind::WorktreeCreated => "worktree_created",
+            TaskEventKind::WindowCreated => "window_created",
+            TaskEventKind::AgentDispatched => "agent_dispatched",
+            TaskEventKind::PrOpened => "pr_opened",
+            TaskEventKind::TestsRun => "tests_run",
+            TaskEventKind::AgentExited => "agent_exited",
+            TaskEventKind::Failed => "failed",
+            TaskEventKind::Trashed => "trashed",
+            TaskEventKind::Restored => "restored",
+            TaskEventKind::Interrupted => "interrupted",
+            TaskEventKind::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s {
+            "created" => Some(TaskEventKind::Created),
+            "moved" => Some(TaskEventKind::Moved),
+            "worktree_created" => Some(TaskEventKind::WorktreeCreated),
+            "window_created" => Some(TaskEventKind::WindowCreated),
+            "agent_dispatched" => Some(TaskEventKind::AgentDispatched),
+            "pr_opened" => Some(TaskEventKind::PrOpened),
+            "tests_run" => Some(TaskEventKind::TestsRun),
+            "agent_exited" => Some(TaskEventKind::AgentExited),
+            "failed" => Some(TaskEventKind::Failed),
+            "trashed" => Some(TaskEventKind::Trashed),
+            "restored" => Some(TaskEventKind::Restored),
+            "interrupted" => Some(TaskEventKind::Interrupted),
+            "abandoned" => Some(TaskEventKind::Abandoned),
+            _ => None,
+        }
+    }
+}
+
+/// One entry in a task's history, recorded automatically (see `Database::add_task_event`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskEvent {
+    pub at: DateTime<Utc>,
+    pub kind: TaskEventKind,
+    pub detail: String,
+}
+
+impl TaskEvent {
+    pub fn new(kind: TaskEventKind, detail: impl Into<String>) -> Self {
+        Self { at: Utc::now(), kind, detail: detail.into() }
+    }
+}
+
+/// Running totals an agent reported for its conversation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentUsage {
+    /// Input tokens, including cache reads and writes
+    pub tokens_in: u64,
+    pub tokens_out: u64,
+    /// Estimated cost in US dollars, when the agent shows one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+}
+
+impl AgentUsage {
+    pub fn tokens(&self) -> u64 {
+        self.tokens_in + self.tokens_out
+    }
+}
+
+/// A Running command still waiting for an agent slot (see `Database::save_dispatch_queue`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedCommand {
+    pub task_id: String,
+    /// tmux target (the task's session/window name)
+    pub target: String,
+    pub command: String,
+}
+
+/// A comment jotted on a task (see `Task::add_note`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskNote {
+    pub at: DateTime<Utc>,
+    pub text: String,
+}
+
 impl Task {
     pub fn new(title: impl Into<String>, agent: impl Into<String>, project_id: impl Into<String>) -> Self {
         let id = uuid::Uuid::new_v4().to_string();
@@ -80,11 +240,82 @@ impl Task {
             pr_number: None,
             pr_url: None,
             plugin: None,
+            archived: false,
+            tags: Vec::new(),
+            depends_on: Vec::new(),
+            deleted_at: None,
+            agent_session_id: None,
+            env: BTreeMap::new(),
+            test_status: None,
+            notes: Vec::new(),
+            last_exit_code: None,
+            pinned: false,
+            usage: None,
+            subdir: None,
+            from_ref: None,
+            status_changed_at: None,
+            slug: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// Build a Backlog task from a template: the title gets the template's prefix and the
+    /// description, agent and tags are copied over. The agent is left empty when the template
+    /// doesn't set one and `project_id` is left empty; the caller fills both in.
+    pub fn from_template(template: &Template, title: &str) -> Task {
+        let mut task = Task::new(
+            format!("{}{}", template.title_prefix, title),
+            template.agent.clone().unwrap_or_default(),
+            "",
+        );
+        task.description = template.description.clone();
+        task.tags = template.tags.clone();
+        task.env = template.env.clone();
+        task
+    }
+
+    /// Append a note stamped with the current time. Blank text is ignored.
+    pub fn add_note(&mut self, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        self.notes.push(TaskNote { at: Utc::now(), text: text.to_string() });
+    }
+
+    /// Remove the most recent note (notes are otherwise append-only)
+    pub fn remove_last_note(&mut self) -> Option<TaskNote> {
+        self.notes.pop()
+    }
+
+    pub fn is_trashed(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    /// The slug for the task's worktree, branch and window: the stored one once they exist,
+    /// otherwise one from the current title
+    pub fn resource_slug(&self) -> String {
+        self.slug.clone().unwrap_or_else(|| crate::slug::slugify(&self.id, &self.title))
+    }
+
+    /// Take the task out of the trash as a fresh Backlog task. Its window, worktree and branch
+    /// were removed when it was trashed, so those references are dropped; a new worktree is
+    /// created on the next move to Planning.
+    pub fn restore_from_trash(&mut self) {
+        self.deleted_at = None;
+        self.status = TaskStatus::Backlog;
+        self.archived = false;
+        self.session_name = None;
+        self.worktree_path = None;
+        self.branch_name = None;
+        self.slug = None;
+        self.pr_number = None;
+        self.pr_url = None;
+        self.agent_session_id = None;
+        self.updated_at = Utc::now();
+    }
+
     /// Generate tmux session name: task-{id}--{project}--{slug}
     pub fn generate_session_name(&self, project_name: &str) -> String {
         let slug = self
@@ -100,6 +331,97 @@ impl Task {
     }
 }
 
+/// A new Backlog task with the same content as `task`: the title (suffixed " (copy)"),
+/// description, agent, project, workflow plugin, tags, env, working directory (`subdir`) and
+/// the ref it branches from (`from_ref`) are copied. Status, resources (worktree, branch, window, PR), notes, timestamps and the
+/// event history all start fresh.
+pub fn duplicate(task: &Task) -> Task {
+    let mut copy = Task::new(format!("{} (copy)", task.title), task.agent.clone(), task.project_id.clone());
+    copy.description = task.description.clone();
+    copy.plugin = task.plugin.clone();
+    copy.tags = task.tags.clone();
+    copy.env = task.env.clone();
+    copy.subdir = task.subdir.clone();
+    copy.from_ref = task.from_ref.clone();
+    copy
+}
+
+/// Open (not Done, not trashed) tasks of `project` titled `title`, ignoring case and surrounding
+/// whitespace. Slugs and window names start from the title, so callers warn about these;
+/// resources still get unique names (`slug::unique_slug`, `WindowSetup::unique_window_name`).
+pub fn find_title_conflicts(title: &str, tasks: &[Task], project: &str) -> Vec<TaskId> {
+    let title = title.trim().to_lowercase();
+    tasks
+        .iter()
+        .filter(|t| t.project_id == project && t.status != TaskStatus::Done && t.deleted_at.is_none())
+        .filter(|t| t.title.trim().to_lowercase() == title)
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+/// Dependencies of `task` that aren't Done yet. Ids that no longer match a task are ignored.
+pub fn blocking_tasks<'a>(task: &Task, all: &'a [Task]) -> Vec<&'a Task> {
+    task.depends_on
+        .iter()
+        .filter_map(|id| all.iter().find(|t| &t.id == id))
+        .filter(|t| t.status != TaskStatus::Done)
+        .collect()
+}
+
+/// Whether `task` has a dependency that isn't Done, so it may not move into Running
+pub fn is_blocked(task: &Task, all: &[Task]) -> bool {
+    !blocking_tasks(task, all).is_empty()
+}
+
+/// Make `task` depend on `dependency_id`, refusing self-dependencies, unknown tasks and
+/// anything that would close a dependency cycle.
+pub fn add_dependency(task: &mut Task, dependency_id: &str, all: &[Task]) -> Result<()> {
+    if task.id == dependency_id {
+        bail!("A task can't depend on itself");
+    }
+    let Some(dependency) = all.iter().find(|t| t.id == dependency_id) else {
+        bail!("Unknown task {}", dependency_id);
+    };
+    if task.depends_on.iter().any(|id| id == dependency_id) {
+        return Ok(());
+    }
+    if let Some(path) = dependency_path(dependency_id, &task.id, all) {
+        let titles: Vec<&str> = std::iter::once(task.title.as_str())
+            .chain(path.iter().map(|t| t.title.as_str()))
+            .collect();
+        bail!(
+            "'{}' can't depend on '{}': that would create a cycle ({})",
+            task.title,
+            dependency.title,
+            titles.join(" → ")
+        );
+    }
+    task.depends_on.push(dependency_id.to_string());
+    Ok(())
+}
+
+/// Chain of tasks from `from` to `to` following `depends_on` edges, if one exists
+fn dependency_path<'a>(from: &str, to: &str, all: &'a [Task]) -> Option<Vec<&'a Task>> {
+    let mut stack: Vec<Vec<&Task>> = all.iter().filter(|t| t.id == from).map(|t| vec![t]).collect();
+    let mut visited: Vec<&str> = Vec::new();
+    while let Some(path) = stack.pop() {
+        let current = *path.last()?;
+        if current.id == to {
+            return Some(path);
+        }
+        if visited.contains(&current.id.as_str()) {
+            continue;
+        }
+        visited.push(&current.id);
+        for next in current.depends_on.iter().filter_map(|id| all.iter().find(|t| &t.id == id)) {
+            let mut extended = path.clone();
+            extended.push(next);
+            stack.push(extended);
+        }
+    }
+    None
+}
+
 /// A project tracked by agtx
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
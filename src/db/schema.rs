@@ -1,8 +1,10 @@
 use anyhow::{Context, Result};
 use rusqlite::{params, Connection};
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-use super::models::{Project, Task, TaskStatus};
+use super::migrations;
+use super::models::{AgentUsage, Project, QueuedCommand, Task, TaskEvent, TaskEventKind, TaskNote, TaskStatus};
 
 /// Database wrapper for SQLite operations
 pub struct Database {
@@ -12,29 +14,36 @@ pub struct Database {
 impl Database {
     /// Open or create a project database (stored centrally in config dir)
     pub fn open_project(project_path: &Path) -> Result<Self> {
-        let config_dir = directories::ProjectDirs::from("", "", "agtx")
-            .context("Could not determine config directory")?;
-
-        // Create a stable ID from the project path using a hash
-        let path_str = project_path.to_string_lossy();
-        let path_hash = Self::hash_path(&path_str);
-
-        let db_path = config_dir
-            .config_dir()
-            .join("projects")
-            .join(format!("{}.db", path_hash));
+        let db_path = Self::project_file(project_path, "db")?;
 
         // Ensure projects directory exists
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)
+        Self::open_project_at(&db_path)
+    }
+
+    /// Open or create a project database at an explicit path, migrating it to the latest schema
+    pub fn open_project_at(db_path: &Path) -> Result<Self> {
+        let mut conn = Connection::open(db_path)
             .with_context(|| format!("Failed to open database at {:?}", db_path))?;
+        migrations::migrate(&mut conn, migrations::PROJECT_MIGRATIONS)
+            .with_context(|| format!("Failed to migrate database at {:?}", db_path))?;
+        Ok(Self { conn })
+    }
+
+    /// A per-project file in the central projects directory, named after the hash of the
+    /// project path (`<hash>.<extension>`)
+    pub fn project_file(project_path: &Path, extension: &str) -> Result<PathBuf> {
+        let config_dir = directories::ProjectDirs::from("", "", "agtx")
+            .context("Could not determine config directory")?;
 
-        let db = Self { conn };
-        db.init_project_schema()?;
-        Ok(db)
+        // Create a stable ID from the project path using a hash
+        let path_str = project_path.to_string_lossy();
+        let path_hash = Self::hash_path(&path_str);
+
+        Ok(config_dir.config_dir().join("projects").join(format!("{}.{}", path_hash, extension)))
     }
 
     /// Create a stable hash from a path string for database filename
@@ -58,74 +67,16 @@ impl Database {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)
+        let mut conn = Connection::open(&db_path)
             .with_context(|| format!("Failed to open global database at {:?}", db_path))?;
-
-        let db = Self { conn };
-        db.init_global_schema()?;
-        Ok(db)
+        migrations::migrate(&mut conn, migrations::GLOBAL_MIGRATIONS)
+            .with_context(|| format!("Failed to migrate global database at {:?}", db_path))?;
+        Ok(Self { conn })
     }
 
-    fn init_project_schema(&self) -> Result<()> {
-        self.conn.execute_batch(
-            r#"
-            CREATE TABLE IF NOT EXISTS tasks (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT NOT NULL DEFAULT 'backlog',
-                agent TEXT NOT NULL,
-                project_id TEXT NOT NULL,
-                session_name TEXT,
-                worktree_path TEXT,
-                branch_name TEXT,
-                pr_number INTEGER,
-                pr_url TEXT,
-                plugin TEXT,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_tasks_status ON tasks(status);
-            CREATE INDEX IF NOT EXISTS idx_tasks_project ON tasks(project_id);
-            "#,
-        )?;
-
-        // Migration: add new columns if they don't exist
-        let _ = self.conn.execute("ALTER TABLE tasks ADD COLUMN branch_name TEXT", []);
-        let _ = self.conn.execute("ALTER TABLE tasks ADD COLUMN pr_number INTEGER", []);
-        let _ = self.conn.execute("ALTER TABLE tasks ADD COLUMN pr_url TEXT", []);
-        let _ = self.conn.execute("ALTER TABLE tasks ADD COLUMN plugin TEXT", []);
-
-        Ok(())
-    }
-
-    fn init_global_schema(&self) -> Result<()> {
-        self.conn.execute_batch(
-            r#"
-            CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                path TEXT NOT NULL UNIQUE,
-                github_url TEXT,
-                default_agent TEXT,
-                last_opened TEXT NOT NULL
-            );
-
-            CREATE TABLE IF NOT EXISTS running_agents (
-                session_name TEXT PRIMARY KEY,
-                project_id TEXT NOT NULL,
-                task_id TEXT NOT NULL,
-                agent_name TEXT NOT NULL,
-                started_at TEXT NOT NULL,
-                status TEXT NOT NULL,
-                FOREIGN KEY (project_id) REFERENCES projects(id)
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_running_project ON running_agents(project_id);
-            "#,
-        )?;
-        Ok(())
+    /// Schema version this database has been migrated to
+    pub fn schema_version(&self) -> Result<u32> {
+        migrations::schema_version(&self.conn)
     }
 
     // === Task Operations ===
@@ -133,8 +84,8 @@ impl Database {
     pub fn create_task(&self, task: &Task) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT INTO tasks (id, title, description, status, agent, project_id, session_name, worktree_path, branch_name, pr_number, pr_url, plugin, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            INSERT INTO tasks (id, title, description, status, agent, project_id, session_name, worktree_path, branch_name, pr_number, pr_url, plugin, created_at, updated_at, archived, tags, depends_on, deleted_at, agent_session_id, env, test_status, notes, last_exit_code, pinned, usage, subdir, from_ref, status_changed_at, slug)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)
             "#,
             params![
                 task.id,
@@ -151,12 +102,38 @@ impl Database {
                 task.plugin,
                 task.created_at.to_rfc3339(),
                 task.updated_at.to_rfc3339(),
+                task.archived,
+                list_to_column(&task.tags),
+                list_to_column(&task.depends_on),
+                task.deleted_at.map(|t| t.to_rfc3339()),
+                task.agent_session_id,
+                env_to_column(&task.env),
+                task.test_status,
+                notes_to_column(&task.notes),
+                task.last_exit_code,
+                task.pinned,
+                usage_to_column(task.usage.as_ref()),
+                task.subdir,
+                task.from_ref,
+                task.status_changed_at.map(|t| t.to_rfc3339()),
+                task.slug,
             ],
         )?;
+        self.add_task_event(&task.id, &TaskEvent::new(TaskEventKind::Created, format!("in {}", task.status.as_str())))?;
         Ok(())
     }
 
+    /// Save a task. Changes of status and moves in or out of the trash are added to its history.
     pub fn update_task(&self, task: &Task) -> Result<()> {
+        let previous: Option<(String, Option<String>)> = self
+            .conn
+            .query_row(
+                "SELECT status, deleted_at FROM tasks WHERE id = ?1",
+                params![task.id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .ok();
+
         self.conn.execute(
             r#"
             UPDATE tasks SET
@@ -170,7 +147,22 @@ impl Database {
                 pr_number = ?9,
                 pr_url = ?10,
                 plugin = ?11,
-                updated_at = ?12
+                updated_at = ?12,
+                archived = ?13,
+                tags = ?14,
+                depends_on = ?15,
+                deleted_at = ?16,
+                agent_session_id = ?17,
+                env = ?18,
+                test_status = ?19,
+                notes = ?20,
+                last_exit_code = ?21,
+                pinned = ?22,
+                usage = ?23,
+                subdir = ?24,
+                from_ref = ?25,
+                status_changed_at = ?26,
+                slug = ?27
             WHERE id = ?1
             "#,
             params![
@@ -186,17 +178,127 @@ impl Database {
                 task.pr_url,
                 task.plugin,
                 task.updated_at.to_rfc3339(),
+                task.archived,
+                list_to_column(&task.tags),
+                list_to_column(&task.depends_on),
+                task.deleted_at.map(|t| t.to_rfc3339()),
+                task.agent_session_id,
+                env_to_column(&task.env),
+                task.test_status,
+                notes_to_column(&task.notes),
+                task.last_exit_code,
+                task.pinned,
+                usage_to_column(task.usage.as_ref()),
+                task.subdir,
+                task.from_ref,
+                task.status_changed_at.map(|t| t.to_rfc3339()),
+                task.slug,
             ],
         )?;
+
+        if let Some((old_status, old_deleted_at)) = previous {
+            if old_status != task.status.as_str() {
+                self.conn.execute(
+                    "UPDATE tasks SET status_changed_at = ?2 WHERE id = ?1",
+                    params![task.id, chrono::Utc::now().to_rfc3339()],
+                )?;
+                let detail = format!("{} → {}", old_status, task.status.as_str());
+                self.add_task_event(&task.id, &TaskEvent::new(TaskEventKind::Moved, detail))?;
+            }
+            match (old_deleted_at.is_some(), task.deleted_at.is_some()) {
+                (false, true) => self.add_task_event(&task.id, &TaskEvent::new(TaskEventKind::Trashed, ""))?,
+                (true, false) => self.add_task_event(&task.id, &TaskEvent::new(TaskEventKind::Restored, ""))?,
+                _ => {}
+            }
+        }
         Ok(())
     }
 
+    /// Permanently remove a task (see `Task::deleted_at` for the recoverable trash)
     pub fn delete_task(&self, task_id: &str) -> Result<()> {
         self.conn
             .execute("DELETE FROM tasks WHERE id = ?1", params![task_id])?;
+        self.conn
+            .execute("DELETE FROM task_events WHERE task_id = ?1", params![task_id])?;
+        Ok(())
+    }
+
+    /// Save only a task's agent usage. The board polls usage while agents work, so this
+    /// leaves the rest of the row (and any edit made meanwhile elsewhere) alone.
+    pub fn set_task_usage(&self, task_id: &str, usage: &AgentUsage) -> Result<()> {
+        self.conn.execute(
+            "UPDATE tasks SET usage = ?2 WHERE id = ?1",
+            params![task_id, usage_to_column(Some(usage))],
+        )?;
+        Ok(())
+    }
+
+    // === Task History ===
+
+    /// Append an entry to a task's history
+    pub fn add_task_event(&self, task_id: &str, event: &TaskEvent) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO task_events (task_id, at, kind, detail) VALUES (?1, ?2, ?3, ?4)",
+            params![task_id, event.at.to_rfc3339(), event.kind.name(), event.detail],
+        )?;
         Ok(())
     }
 
+    /// A task's history, oldest first
+    pub fn get_task_events(&self, task_id: &str) -> Result<Vec<TaskEvent>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT at, kind, detail FROM task_events WHERE task_id = ?1 ORDER BY id")?;
+        let events = stmt
+            .query_map(params![task_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(at, kind, detail)| Self::event_from_columns(&at, &kind, detail))
+            .collect();
+        Ok(events)
+    }
+
+    /// Events of every task recorded after the one with id `after`, oldest first, as
+    /// (event id, task id, event). Pass `last_task_event_id` to skip what's already there.
+    pub fn task_events_since(&self, after: i64) -> Result<Vec<(i64, String, TaskEvent)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, task_id, at, kind, detail FROM task_events WHERE id > ?1 ORDER BY id")?;
+        let events = stmt
+            .query_map(params![after], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .filter_map(|(id, task_id, at, kind, detail)| {
+                Some((id, task_id, Self::event_from_columns(&at, &kind, detail)?))
+            })
+            .collect();
+        Ok(events)
+    }
+
+    /// Id of the newest event (0 when there are none)
+    pub fn last_task_event_id(&self) -> Result<i64> {
+        let id = self
+            .conn
+            .query_row("SELECT COALESCE(MAX(id), 0) FROM task_events", [], |row| row.get(0))?;
+        Ok(id)
+    }
+
+    fn event_from_columns(at: &str, kind: &str, detail: String) -> Option<TaskEvent> {
+        Some(TaskEvent {
+            at: chrono::DateTime::parse_from_rfc3339(at).ok()?.with_timezone(&chrono::Utc),
+            kind: TaskEventKind::from_name(kind)?,
+            detail,
+        })
+    }
+
     fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<Task> {
         Ok(Task {
             id: row.get("id")?,
@@ -212,6 +314,35 @@ impl Database {
             pr_number: row.get("pr_number").ok().flatten(),
             pr_url: row.get("pr_url").ok().flatten(),
             plugin: row.get("plugin").ok().flatten(),
+            archived: row.get::<_, Option<bool>>("archived").ok().flatten().unwrap_or(false),
+            tags: list_from_column(row.get::<_, Option<String>>("tags").ok().flatten()),
+            depends_on: list_from_column(row.get::<_, Option<String>>("depends_on").ok().flatten()),
+            deleted_at: row
+                .get::<_, Option<String>>("deleted_at")
+                .ok()
+                .flatten()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc)),
+            agent_session_id: row.get("agent_session_id").ok().flatten(),
+            env: env_from_column(row.get::<_, Option<String>>("env").ok().flatten()),
+            test_status: row.get("test_status").ok().flatten(),
+            last_exit_code: row.get("last_exit_code").ok().flatten(),
+            pinned: row.get::<_, Option<bool>>("pinned").ok().flatten().unwrap_or(false),
+            usage: row
+                .get::<_, Option<String>>("usage")
+                .ok()
+                .flatten()
+                .and_then(|v| serde_json::from_str(&v).ok()),
+            subdir: row.get("subdir").ok().flatten(),
+            from_ref: row.get("from_ref").ok().flatten(),
+            status_changed_at: row
+                .get::<_, Option<String>>("status_changed_at")
+                .ok()
+                .flatten()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc)),
+            slug: row.get("slug").ok().flatten(),
+            notes: notes_from_column(row.get::<_, Option<String>>("notes").ok().flatten()),
             created_at: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>("created_at")?)
                 .map(|dt| dt.with_timezone(&chrono::Utc))
                 .unwrap_or_else(|_| chrono::Utc::now()),
@@ -236,7 +367,7 @@ impl Database {
     pub fn get_tasks_by_status(&self, status: TaskStatus) -> Result<Vec<Task>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT * FROM tasks WHERE status = ?1 ORDER BY created_at")?;
+            .prepare("SELECT * FROM tasks WHERE status = ?1 AND deleted_at IS NULL ORDER BY created_at")?;
 
         let tasks = stmt
             .query_map(params![status.as_str()], Self::task_from_row)?
@@ -246,10 +377,48 @@ impl Database {
         Ok(tasks)
     }
 
+    /// Archive every Done task that isn't archived yet. Returns the number archived.
+    /// Only flips the flag - worktrees and windows were already cleaned up at Done.
+    pub fn archive_done_tasks(&self) -> Result<usize> {
+        let count = self.conn.execute(
+            "UPDATE tasks SET archived = 1, updated_at = ?2 WHERE status = ?1 AND archived = 0 AND deleted_at IS NULL",
+            params![TaskStatus::Done.as_str(), chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(count)
+    }
+
+    pub fn get_archived_tasks(&self) -> Result<Vec<Task>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM tasks WHERE archived = 1 AND deleted_at IS NULL ORDER BY created_at")?;
+
+        let tasks = stmt
+            .query_map([], Self::task_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tasks)
+    }
+
+    /// Every task on the board (archived included, trashed excluded)
     pub fn get_all_tasks(&self) -> Result<Vec<Task>> {
         let mut stmt = self
             .conn
-            .prepare("SELECT * FROM tasks ORDER BY created_at")?;
+            .prepare("SELECT * FROM tasks WHERE deleted_at IS NULL ORDER BY created_at")?;
+
+        let tasks = stmt
+            .query_map([], Self::task_from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tasks)
+    }
+
+    /// Tasks in the trash, most recently trashed first
+    pub fn get_trashed_tasks(&self) -> Result<Vec<Task>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT * FROM tasks WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC")?;
 
         let tasks = stmt
             .query_map([], Self::task_from_row)?
@@ -259,6 +428,36 @@ impl Database {
         Ok(tasks)
     }
 
+    // === Dispatch Queue ===
+
+    /// Replace the project's queued Running commands with `queue`, in dispatch order
+    pub fn save_dispatch_queue(&self, queue: &[QueuedCommand]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM dispatch_queue", [])?;
+        for (position, queued) in queue.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO dispatch_queue (task_id, position, target, command) VALUES (?1, ?2, ?3, ?4)",
+                params![queued.task_id, position as i64, queued.target, queued.command],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Queued Running commands saved by `save_dispatch_queue`, in dispatch order
+    pub fn get_dispatch_queue(&self) -> Result<Vec<QueuedCommand>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT task_id, target, command FROM dispatch_queue ORDER BY position")?;
+        let queue = stmt
+            .query_map([], |row| {
+                Ok(QueuedCommand { task_id: row.get(0)?, target: row.get(1)?, command: row.get(2)? })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(queue)
+    }
+
     // === Project Operations (for global db) ===
 
     pub fn upsert_project(&self, project: &Project) -> Result<()> {
@@ -310,3 +509,55 @@ impl Database {
         Ok(projects)
     }
 }
+
+/// Tags and dependency ids are stored as comma-separated lists (NULL when empty)
+fn list_to_column(items: &[String]) -> Option<String> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(items.join(","))
+    }
+}
+
+/// Env overrides are stored as a JSON object (values may contain commas)
+fn env_to_column(env: &BTreeMap<String, String>) -> Option<String> {
+    if env.is_empty() {
+        None
+    } else {
+        serde_json::to_string(env).ok()
+    }
+}
+
+fn env_from_column(value: Option<String>) -> BTreeMap<String, String> {
+    value
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default()
+}
+
+/// Notes are stored as a JSON array of `{at, text}`
+fn notes_to_column(notes: &[TaskNote]) -> Option<String> {
+    if notes.is_empty() {
+        None
+    } else {
+        serde_json::to_string(notes).ok()
+    }
+}
+
+fn usage_to_column(usage: Option<&AgentUsage>) -> Option<String> {
+    usage.and_then(|u| serde_json::to_string(u).ok())
+}
+
+fn notes_from_column(value: Option<String>) -> Vec<TaskNote> {
+    value
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default()
+}
+
+fn list_from_column(value: Option<String>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(',')
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
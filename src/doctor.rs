@@ -0,0 +1,174 @@
+//! `agtx doctor`: environment checks with a pass/fail line each.
+//!
+//! Every check is a small function returning a `CheckResult`, so new ones can be added to
+//! `run_checks` and tested on their own.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::{GlobalConfig, MergedConfig, Multiplexer, ProjectConfig};
+use crate::git;
+use crate::op_error::OpError;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not run because an earlier check it depends on failed
+    Skipped,
+}
+
+/// One line of the doctor report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Version found, path checked, or why it failed
+    pub detail: String,
+    /// A critical failure makes `agtx doctor` exit non-zero
+    pub critical: bool,
+}
+
+impl CheckResult {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Pass, detail: detail.into(), critical: true }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Fail, detail: detail.into(), critical: true }
+    }
+
+    fn skipped(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Skipped, detail: detail.into(), critical: true }
+    }
+}
+
+/// Check that `program` runs, reporting the first line of `program <version_arg>`
+pub fn check_binary(name: &str, program: &str, version_arg: &str) -> CheckResult {
+    match git::probe_version(program, version_arg) {
+        Ok(version) => CheckResult::pass(name, version),
+        Err(OpError::CommandNotFound { .. }) => CheckResult::fail(name, format!("`{}` not found on PATH", program)),
+        Err(e) => CheckResult::fail(name, e.to_string()),
+    }
+}
+
+/// The configured multiplexer is only required when agtx runs with agent windows; with
+/// `no_tmux` a failure is reported but not critical
+pub fn check_multiplexer(multiplexer: Multiplexer, no_tmux: bool) -> CheckResult {
+    let mut result = match (multiplexer.unsupported_reason(), multiplexer) {
+        (Some(reason), _) => CheckResult::fail(&format!("{} installed", multiplexer.program()), reason),
+        (None, Multiplexer::Tmux) => check_binary("tmux installed", "tmux", "-V"),
+        (None, Multiplexer::Zellij) => check_binary("zellij installed", "zellij", "--version"),
+    };
+    if no_tmux {
+        result.critical = false;
+        if result.status == CheckStatus::Fail {
+            result.detail.push_str(" (not needed: no_tmux is set)");
+        }
+    }
+    result
+}
+
+pub fn check_tmux(no_tmux: bool) -> CheckResult {
+    check_multiplexer(Multiplexer::Tmux, no_tmux)
+}
+
+pub fn check_git() -> CheckResult {
+    check_binary("git installed", "git", "--version")
+}
+
+pub fn check_git_repo(project_path: &Path) -> CheckResult {
+    let name = "project is a git repo";
+    if git::is_git_repo(project_path) {
+        CheckResult::pass(name, project_path.display().to_string())
+    } else {
+        CheckResult::fail(name, format!("{} is not inside a git repository", project_path.display()))
+    }
+}
+
+pub fn check_base_branch(project_path: &Path, base_branch: &str) -> CheckResult {
+    let name = "base branch exists";
+    if !git::is_git_repo(project_path) {
+        return CheckResult::skipped(name, "not a git repo");
+    }
+    let exists = Command::new("git")
+        .current_dir(project_path)
+        .args(["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", base_branch)])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false);
+    if exists {
+        CheckResult::pass(name, base_branch)
+    } else {
+        CheckResult::fail(
+            name,
+            format!("'{}' not found (set worktree.base_branch or base_branch in .agtx/config.toml)", base_branch),
+        )
+    }
+}
+
+/// Check that task worktrees can be created under the configured `worktree_dir`.
+/// If the directory doesn't exist yet, its nearest existing parent is probed instead
+/// (nothing is created).
+pub fn check_worktree_dir_writable(project_path: &Path, worktree_dir: &str) -> CheckResult {
+    let name = "worktree directory writable";
+    let base = git::worktree_base(project_path, worktree_dir);
+    let Some(existing) = base.ancestors().find(|p| p.is_dir()) else {
+        return CheckResult::fail(name, format!("no existing parent directory for {}", base.display()));
+    };
+    let probe = existing.join(format!(".agtx-doctor-{}", std::process::id()));
+    match std::fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            let detail = if existing == base {
+                base.display().to_string()
+            } else {
+                format!("{} (will be created)", base.display())
+            };
+            CheckResult::pass(name, detail)
+        }
+        Err(e) => CheckResult::fail(name, format!("cannot write to {}: {}", existing.display(), e)),
+    }
+}
+
+/// Run every check for a project
+pub fn run_checks(project_path: &Path, global: &GlobalConfig, project: &ProjectConfig) -> Vec<CheckResult> {
+    let merged = MergedConfig::merge(global, project);
+    let base_branch = merged
+        .base_branch
+        .or_else(|| git::default_branch(project_path).ok())
+        .unwrap_or_else(|| "main".to_string());
+    vec![
+        check_multiplexer(global.multiplexer, global.no_tmux),
+        check_git(),
+        check_git_repo(project_path),
+        check_base_branch(project_path, &base_branch),
+        check_worktree_dir_writable(project_path, &global.worktree.worktree_dir),
+    ]
+}
+
+/// Whether any critical check failed
+pub fn has_critical_failure(results: &[CheckResult]) -> bool {
+    results.iter().any(|r| r.critical && r.status == CheckStatus::Fail)
+}
+
+/// One line per check, e.g. `[PASS] git installed: git version 2.43.0`
+pub fn format_report(results: &[CheckResult]) -> String {
+    results
+        .iter()
+        .map(|r| {
+            let label = match r.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skipped => "SKIP",
+            };
+            if r.detail.is_empty() {
+                format!("[{}] {}", label, r.name)
+            } else {
+                format!("[{}] {}: {}", label, r.name, r.detail)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
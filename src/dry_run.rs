@@ -0,0 +1,399 @@
+//! Logging stand-ins for the git, tmux, provider and hook operations used by `--dry-run`.
+//!
+//! Mutating calls are recorded as shell commands instead of being executed.
+//! Read-only queries (diffs, status, pane capture) still go to the real
+//! implementations so the board renders the same as a normal run.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::git::{
+    self, CommitId, CommitSummary, GitOperations, GitProviderOperations, PullRequestState, RealGitOps,
+};
+use crate::hooks::HookRunner;
+use crate::op_error::OpResult;
+use crate::platform::shell_quote;
+use crate::tmux::{RealTmuxOps, TmuxOperations, AGENT_SERVER};
+
+/// Shared, ordered record of the commands a dry run would have executed
+#[derive(Debug, Clone, Default)]
+pub struct DryRunLog {
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+impl DryRunLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a command built from its arguments, quoting each for the shell
+    pub fn record<S: AsRef<str>>(&self, args: &[S]) {
+        let line = args
+            .iter()
+            .map(|a| shell_quote(a.as_ref()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.record_line(line);
+    }
+
+    /// Record a preformatted line (e.g. a `cd dir && ...` compound)
+    pub fn record_line(&self, line: impl Into<String>) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(line.into());
+        }
+    }
+
+    pub fn entries(&self) -> Vec<String> {
+        self.entries.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.lock().map(|e| e.is_empty()).unwrap_or(true)
+    }
+}
+
+fn path_str(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Tmux operations that record instead of touching the `agtx` server
+pub struct DryRunTmuxOps {
+    log: DryRunLog,
+    real: RealTmuxOps,
+}
+
+impl DryRunTmuxOps {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log, real: RealTmuxOps }
+    }
+
+    fn record_tmux(&self, args: &[&str]) {
+        let mut full = vec!["tmux", "-L", AGENT_SERVER];
+        full.extend_from_slice(args);
+        self.log.record(&full);
+    }
+}
+
+impl TmuxOperations for DryRunTmuxOps {
+    fn create_window(
+        &self,
+        session: &str,
+        window_name: &str,
+        working_dir: &str,
+        command: Option<String>,
+    ) -> OpResult<()> {
+        let mut args = vec!["new-window", "-d", "-t", session, "-n", window_name, "-c", working_dir];
+        let wrapped = command.map(|c| format!("{}; exec $SHELL", c));
+        if let Some(ref wrapped) = wrapped {
+            args.extend_from_slice(&["sh", "-c", wrapped]);
+        }
+        self.record_tmux(&args);
+        Ok(())
+    }
+
+    fn kill_window(&self, target: &str) -> OpResult<()> {
+        self.record_tmux(&["kill-window", "-t", target]);
+        Ok(())
+    }
+
+    fn window_exists(&self, target: &str) -> OpResult<bool> {
+        self.real.window_exists(target)
+    }
+
+    fn list_windows(&self, session: &str) -> OpResult<Vec<String>> {
+        self.real.list_windows(session)
+    }
+
+    fn send_keys(&self, target: &str, keys: &str) -> OpResult<()> {
+        self.record_tmux(&["send-keys", "-t", target, keys]);
+        self.record_tmux(&["send-keys", "-t", target, "Enter"]);
+        Ok(())
+    }
+
+    fn send_keys_literal(&self, target: &str, keys: &str) -> OpResult<()> {
+        self.record_tmux(&["send-keys", "-t", target, keys]);
+        Ok(())
+    }
+
+    fn send_interrupt(&self, target: &str) -> OpResult<()> {
+        self.record_tmux(&["send-keys", "-t", target, "C-c"]);
+        Ok(())
+    }
+
+    fn capture_pane(&self, target: &str) -> OpResult<String> {
+        self.real.capture_pane(target)
+    }
+
+    fn capture_pane_with_history(&self, target: &str, history_lines: i32) -> Vec<u8> {
+        self.real.capture_pane_with_history(target, history_lines)
+    }
+
+    fn get_cursor_info(&self, target: &str) -> Option<(usize, usize)> {
+        self.real.get_cursor_info(target)
+    }
+
+    fn resize_window(&self, target: &str, width: u16, height: u16) -> OpResult<()> {
+        // Resizing only affects presentation; skip it without cluttering the log
+        let _ = (target, width, height);
+        Ok(())
+    }
+
+    fn has_session(&self, session: &str) -> bool {
+        self.real.has_session(session)
+    }
+
+    fn create_session(&self, session: &str, working_dir: &str) -> OpResult<()> {
+        self.record_tmux(&["new-session", "-d", "-s", session, "-c", working_dir]);
+        Ok(())
+    }
+
+    fn attach(&self, target: &str) -> OpResult<()> {
+        // Attaching only looks at the window, so it's allowed in a dry run
+        self.real.attach(target)
+    }
+}
+
+/// Git operations that record instead of changing repositories or worktrees. Queries go to
+/// `real`, the ops a normal run would use, and recorded worktree paths follow its
+/// `worktree_dir`.
+pub struct DryRunGitOps {
+    log: DryRunLog,
+    real: RealGitOps,
+}
+
+impl DryRunGitOps {
+    pub fn new(log: DryRunLog, real: RealGitOps) -> Self {
+        Self { log, real }
+    }
+
+    fn record_git(&self, dir: &Path, args: &[&str]) {
+        let dir = path_str(dir);
+        let mut full = vec!["git", "-C", dir.as_str()];
+        full.extend_from_slice(args);
+        self.log.record(&full);
+    }
+}
+
+impl GitOperations for DryRunGitOps {
+    fn is_git_repo(&self, project_path: &Path) -> bool {
+        self.real.is_git_repo(project_path)
+    }
+
+    fn has_commits(&self, project_path: &Path) -> bool {
+        self.real.has_commits(project_path)
+    }
+
+    fn default_branch(&self, project_path: &Path) -> OpResult<String> {
+        self.real.default_branch(project_path)
+    }
+
+    fn ref_exists(&self, project_path: &Path, name: &str) -> OpResult<bool> {
+        self.real.ref_exists(project_path, name)
+    }
+
+    fn create_worktree(&self, project_path: &Path, task_slug: &str, base: &str) -> OpResult<String> {
+        let path = path_str(&git::worktree_path_in(project_path, &self.real.worktree_dir, task_slug));
+        let branch = format!("task/{}", task_slug);
+        self.record_git(project_path, &["worktree", "add", &path, "-b", &branch, base]);
+        Ok(path)
+    }
+
+    fn checkout_worktree(&self, project_path: &Path, task_slug: &str, branch: &str) -> OpResult<String> {
+        let path = path_str(&git::worktree_path_in(project_path, &self.real.worktree_dir, task_slug));
+        self.record_git(project_path, &["worktree", "add", &path, branch]);
+        Ok(path)
+    }
+
+    fn remove_worktree(&self, project_path: &Path, worktree_path: &str) -> OpResult<()> {
+        self.record_git(project_path, &["worktree", "remove", "--force", worktree_path]);
+        Ok(())
+    }
+
+    fn worktree_path(&self, project_path: &Path, task_slug: &str) -> PathBuf {
+        self.real.worktree_path(project_path, task_slug)
+    }
+
+    fn worktree_exists(&self, project_path: &Path, task_slug: &str) -> bool {
+        self.real.worktree_exists(project_path, task_slug)
+    }
+
+    fn list_worktrees(&self, project_path: &Path) -> OpResult<Vec<PathBuf>> {
+        self.real.list_worktrees(project_path)
+    }
+
+    fn delete_branch(&self, project_path: &Path, branch_name: &str) -> OpResult<()> {
+        self.record_git(project_path, &["branch", "-D", branch_name]);
+        Ok(())
+    }
+
+    fn diff(&self, worktree_path: &Path) -> String {
+        self.real.diff(worktree_path)
+    }
+
+    fn diff_cached(&self, worktree_path: &Path) -> String {
+        self.real.diff_cached(worktree_path)
+    }
+
+    fn list_untracked_files(&self, worktree_path: &Path) -> String {
+        self.real.list_untracked_files(worktree_path)
+    }
+
+    fn diff_untracked_file(&self, worktree_path: &Path, file: &str) -> String {
+        self.real.diff_untracked_file(worktree_path, file)
+    }
+
+    fn diff_stat_from_base(&self, worktree_path: &Path, base: &str) -> String {
+        self.real.diff_stat_from_base(worktree_path, base)
+    }
+
+    fn diff_stat_cached(&self, worktree_path: &Path) -> String {
+        self.real.diff_stat_cached(worktree_path)
+    }
+
+    fn diff_from_base(&self, worktree_path: &Path, base: &str) -> OpResult<String> {
+        self.real.diff_from_base(worktree_path, base)
+    }
+
+    fn add_all(&self, worktree_path: &Path) -> OpResult<()> {
+        self.record_git(worktree_path, &["add", "-A"]);
+        Ok(())
+    }
+
+    fn has_changes(&self, worktree_path: &Path) -> bool {
+        self.real.has_changes(worktree_path)
+    }
+
+    fn is_worktree_dirty(&self, worktree_path: &Path) -> OpResult<bool> {
+        self.real.is_worktree_dirty(worktree_path)
+    }
+
+    fn stash_worktree(&self, worktree_path: &Path, name: &str) -> OpResult<()> {
+        self.record_git(worktree_path, &["stash", "push", "--include-untracked", "-m", name]);
+        Ok(())
+    }
+
+    fn commit(&self, worktree_path: &Path, message: &str) -> OpResult<()> {
+        self.record_git(worktree_path, &["commit", "-m", message]);
+        Ok(())
+    }
+
+    fn commit_all(&self, worktree_path: &Path, message: &str) -> OpResult<Option<CommitId>> {
+        if !self.has_changes(worktree_path) {
+            return Ok(None);
+        }
+        self.add_all(worktree_path)?;
+        self.commit(worktree_path, message)?;
+        Ok(Some(CommitId("dry-run".to_string())))
+    }
+
+    fn recent_commits(&self, worktree_path: &Path, base: &str, n: usize) -> OpResult<Vec<CommitSummary>> {
+        self.real.recent_commits(worktree_path, base, n)
+    }
+
+    fn push(&self, worktree_path: &Path, branch: &str, set_upstream: bool) -> OpResult<()> {
+        let mut args = vec!["push"];
+        if set_upstream {
+            args.push("-u");
+        }
+        args.extend_from_slice(&["origin", branch]);
+        self.record_git(worktree_path, &args);
+        Ok(())
+    }
+
+    fn list_files(&self, project_path: &Path) -> Vec<String> {
+        self.real.list_files(project_path)
+    }
+
+    fn initialize_worktree(
+        &self,
+        project_path: &Path,
+        worktree_path: &Path,
+        copy_files: Option<String>,
+        init_script: Option<String>,
+        copy_dirs: Vec<String>,
+    ) -> Vec<String> {
+        let dirs = git::AGENT_CONFIG_DIRS.iter().map(|d| d.to_string()).chain(copy_dirs);
+        let files = copy_files
+            .iter()
+            .flat_map(|s| s.split(',').map(|f| f.trim().to_string()).collect::<Vec<_>>())
+            .filter(|f| !f.is_empty());
+
+        for entry in dirs.chain(files) {
+            let src = project_path.join(&entry);
+            if src.exists() {
+                self.log.record(&["cp", "-R", &path_str(&src), &path_str(&worktree_path.join(&entry))]);
+            }
+        }
+
+        if let Some(script) = init_script.filter(|s| !s.trim().is_empty()) {
+            self.log.record_line(format!(
+                "cd {} && sh -c {}",
+                shell_quote(&path_str(worktree_path)),
+                shell_quote(&script)
+            ));
+        }
+
+        Vec::new()
+    }
+}
+
+/// Provider operations that record `gh` calls instead of opening pull requests
+pub struct DryRunGitProviderOps {
+    log: DryRunLog,
+}
+
+impl DryRunGitProviderOps {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log }
+    }
+}
+
+impl GitProviderOperations for DryRunGitProviderOps {
+    fn get_pr_state(&self, _project_path: &Path, _pr_number: i32) -> Result<PullRequestState> {
+        Ok(PullRequestState::Unknown)
+    }
+
+    fn create_pr(
+        &self,
+        project_path: &Path,
+        title: &str,
+        body: &str,
+        head_branch: &str,
+        base_branch: &str,
+    ) -> Result<(i32, String)> {
+        let dir = path_str(project_path);
+        self.log.record_line(format!(
+            "cd {} && {}",
+            shell_quote(&dir),
+            ["gh", "pr", "create", "--title", title, "--body", body, "--head", head_branch, "--base", base_branch]
+                .iter()
+                .map(|a| shell_quote(a))
+                .collect::<Vec<_>>()
+                .join(" ")
+        ));
+        Ok((0, "(dry run)".to_string()))
+    }
+}
+
+/// Hook runner that records each hook instead of running it
+pub struct DryRunHookRunner {
+    log: DryRunLog,
+}
+
+impl DryRunHookRunner {
+    pub fn new(log: DryRunLog) -> Self {
+        Self { log }
+    }
+}
+
+impl HookRunner for DryRunHookRunner {
+    fn run(&self, command: &str, dir: &Path) -> OpResult<()> {
+        self.log.record_line(format!(
+            "cd {} && sh -c {}",
+            shell_quote(&path_str(dir)),
+            shell_quote(command)
+        ));
+        Ok(())
+    }
+}
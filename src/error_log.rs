@@ -0,0 +1,44 @@
+//! Append-only log of failed operations, alongside the error bar in the board.
+//!
+//! One line per failure: `<RFC3339 time>  <action>: <message>`. Logging is best effort; a log
+//! that can't be written never hides the error from the user.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `errors.log` in the agtx config directory, next to the databases
+pub fn default_path() -> Result<PathBuf> {
+    let dirs = directories::ProjectDirs::from("", "", "agtx")
+        .context("Could not determine config directory")?;
+    Ok(dirs.config_dir().join("errors.log"))
+}
+
+/// One log line (without the trailing newline). Newlines in the message are flattened so each
+/// failure stays on one line.
+pub fn format_entry(at: DateTime<Utc>, action: &str, message: &str) -> String {
+    let message = message.lines().map(str::trim).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+    format!("{}  {}: {}", at.to_rfc3339(), action, message)
+}
+
+/// Append a failure to the log at `path`, creating it (and its directory) if needed
+pub fn append(path: &Path, at: DateTime<Utc>, action: &str, message: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open error log {:?}", path))?;
+    writeln!(file, "{}", format_entry(at, action, message))?;
+    Ok(())
+}
+
+/// Append a failure to the default log, ignoring any error doing so
+pub fn log(action: &str, message: &str) {
+    if let Ok(path) = default_path() {
+        let _ = append(&path, Utc::now(), action, message);
+    }
+}
@@ -1,9 +1,11 @@
 mod operations;
 mod provider;
+mod timeout;
 mod worktree;
 
 pub use operations::*;
 pub use provider::{GitProviderOperations, PullRequestState, RealGitHubOps};
+pub use timeout::{is_timeout, run_with_timeout, GitOpClass, GitTimeouts};
 pub use worktree::*;
 
 #[cfg(feature = "test-mocks")]
@@ -15,6 +17,26 @@ use anyhow::{Context, Result};
 use std::path::Path;
 use std::process::Command;
 
+use crate::op_error::{OpError, OpResult};
+
+/// Run `git --version`: the startup check that git is installed. Returns the version line,
+/// or `OpError::CommandNotFound` when git isn't on PATH.
+pub fn probe_git() -> OpResult<String> {
+    probe_version("git", "--version")
+}
+
+/// Run `<program> <version_arg>` and return the first line it prints
+pub fn probe_version(program: &str, version_arg: &str) -> OpResult<String> {
+    let output = Command::new(program)
+        .arg(version_arg)
+        .output()
+        .map_err(|e| OpError::spawn(program, e))?;
+    if !output.status.success() {
+        return Err(OpError::failed(format!("{} {}", program, version_arg), &output));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).lines().next().unwrap_or("").trim().to_string())
+}
+
 /// Check if a path is inside a git repository
 pub fn is_git_repo(path: &Path) -> bool {
     Command::new("git")
@@ -25,6 +47,16 @@ pub fn is_git_repo(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Check if the repository has at least one commit (worktrees can't branch from an unborn HEAD)
+pub fn has_commits(path: &Path) -> bool {
+    Command::new("git")
+        .current_dir(path)
+        .args(["rev-parse", "--verify", "--quiet", "HEAD"])
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
 /// Get the root directory of the git repository
 pub fn repo_root(path: &Path) -> Result<std::path::PathBuf> {
     let output = Command::new("git")
@@ -73,22 +105,57 @@ pub fn diff_full(path: &Path, base: &str, target: &str) -> Result<String> {
     Ok(String::from_utf8_lossy(&output.stdout).to_string())
 }
 
-/// Merge a branch into the current branch
-pub fn merge_branch(path: &Path, branch: &str, message: &str) -> Result<()> {
-    let output = Command::new("git")
-        .current_dir(path)
-        .args(["merge", branch, "--no-ff", "-m", message])
-        .output()
+/// Result of `merge_branch`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeOutcome {
+    Merged,
+    /// The branch conflicts with the current branch in these files. The merge was aborted,
+    /// so the working tree is as it was before.
+    Conflict { files: Vec<String> },
+}
+
+/// Merge a branch into the current branch. A conflicting merge is aborted rather than left
+/// half done; other failures are errors.
+pub fn merge_branch(path: &Path, branch: &str, message: &str) -> Result<MergeOutcome> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(path).args(["merge", branch, "--no-ff", "-m", message]);
+    let output = run_with_timeout(&mut cmd, "merge", GitTimeouts::default().local)
         .context("Failed to merge branch")?;
 
-    if !output.status.success() {
+    if output.status.success() {
+        return Ok(MergeOutcome::Merged);
+    }
+
+    let files = conflicted_files(path)?;
+    if files.is_empty() {
         anyhow::bail!(
             "Merge failed: {}",
             String::from_utf8_lossy(&output.stderr)
         );
     }
+    let abort = Command::new("git")
+        .current_dir(path)
+        .args(["merge", "--abort"])
+        .output()
+        .context("Failed to abort conflicting merge")?;
+    if !abort.status.success() {
+        anyhow::bail!(
+            "Merge of {} conflicted and could not be aborted: {}",
+            branch,
+            String::from_utf8_lossy(&abort.stderr).trim()
+        );
+    }
+    Ok(MergeOutcome::Conflict { files })
+}
 
-    Ok(())
+/// Files with unresolved merge conflicts
+fn conflicted_files(path: &Path) -> Result<Vec<String>> {
+    let output = Command::new("git")
+        .current_dir(path)
+        .args(["diff", "--name-only", "--diff-filter=U"])
+        .output()
+        .context("Failed to list conflicted files")?;
+    Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect())
 }
 
 /// Delete a branch
@@ -1,25 +1,76 @@
 //! Traits for git operations to enable testing with mocks.
 
-use anyhow::Result;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{run_with_timeout, GitOpClass, GitTimeouts};
+use crate::op_error::{OpError, OpResult};
 
 #[cfg(feature = "test-mocks")]
 use mockall::automock;
 
+/// A commit hash created by agtx
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitId(pub String);
+
+impl CommitId {
+    /// Abbreviated hash for display
+    pub fn short(&self) -> &str {
+        &self.0[..self.0.len().min(8)]
+    }
+}
+
+impl std::fmt::Display for CommitId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// One line of a branch's log: abbreviated hash and subject
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub hash: String,
+    pub subject: String,
+}
+
 /// Operations for git worktree management
 #[cfg_attr(feature = "test-mocks", automock)]
 pub trait GitOperations: Send + Sync {
-    /// Create a worktree for a task
-    fn create_worktree(&self, project_path: &Path, task_slug: &str) -> Result<String>;
+    /// Check if a path is inside a git repository
+    fn is_git_repo(&self, project_path: &Path) -> bool;
+
+    /// Check if the repository has at least one commit
+    fn has_commits(&self, project_path: &Path) -> bool;
+
+    /// Default branch of the project: `origin/HEAD`'s target, falling back to `main`
+    /// (see `git::default_branch`)
+    fn default_branch(&self, project_path: &Path) -> OpResult<String>;
+
+    /// Whether `name` (a branch, tag or commit) resolves to a commit. A missing ref is
+    /// `Ok(false)`; errors are for git itself failing (e.g. not a repository).
+    fn ref_exists(&self, project_path: &Path, name: &str) -> OpResult<bool>;
+
+    /// Create a worktree for a task on a new branch from `base`
+    fn create_worktree(&self, project_path: &Path, task_slug: &str, base: &str) -> OpResult<String>;
+
+    /// Create a worktree for a task on its existing `branch` (see `git::checkout_worktree_in`)
+    fn checkout_worktree(&self, project_path: &Path, task_slug: &str, branch: &str) -> OpResult<String>;
 
     /// Remove a worktree
-    fn remove_worktree(&self, project_path: &Path, worktree_path: &str) -> Result<()>;
+    fn remove_worktree(&self, project_path: &Path, worktree_path: &str) -> OpResult<()>;
+
+    /// Where a task's worktree goes: `task_slug` under the configured `worktree_dir`
+    fn worktree_path(&self, project_path: &Path, task_slug: &str) -> PathBuf;
 
     /// Check if worktree exists
     fn worktree_exists(&self, project_path: &Path, task_slug: &str) -> bool;
 
+    /// Paths of the project's task worktrees that are still on disk, in one `git worktree list`
+    /// (the main checkout is not included)
+    fn list_worktrees(&self, project_path: &Path) -> OpResult<Vec<PathBuf>>;
+
     /// Delete a branch
-    fn delete_branch(&self, project_path: &Path, branch_name: &str) -> Result<()>;
+    fn delete_branch(&self, project_path: &Path, branch_name: &str) -> OpResult<()>;
 
     /// Get unstaged diff
     fn diff(&self, worktree_path: &Path) -> String;
@@ -33,20 +84,40 @@ pub trait GitOperations: Send + Sync {
     /// Get diff for untracked file (comparing to /dev/null)
     fn diff_untracked_file(&self, worktree_path: &Path, file: &str) -> String;
 
-    /// Get diff stats from main branch
-    fn diff_stat_from_main(&self, worktree_path: &Path) -> String;
+    /// Get diff stats against `base`
+    fn diff_stat_from_base(&self, worktree_path: &Path, base: &str) -> String;
+
+    /// `git diff --cached --stat`: what the next commit would contain
+    fn diff_stat_cached(&self, worktree_path: &Path) -> String;
+
+    /// Committed changes on the worktree's branch since it forked from `base` (`git diff base...HEAD`)
+    fn diff_from_base(&self, worktree_path: &Path, base: &str) -> OpResult<String>;
 
     /// Stage all changes
-    fn add_all(&self, worktree_path: &Path) -> Result<()>;
+    fn add_all(&self, worktree_path: &Path) -> OpResult<()>;
 
     /// Check if there are uncommitted changes (returns true if there are changes)
     fn has_changes(&self, worktree_path: &Path) -> bool;
 
+    /// Check for uncommitted or untracked changes, failing if git status can't be read.
+    /// A worktree that no longer exists on disk is reported clean.
+    fn is_worktree_dirty(&self, worktree_path: &Path) -> OpResult<bool>;
+
+    /// Stash all changes (including untracked files) under a named stash entry
+    fn stash_worktree(&self, worktree_path: &Path, name: &str) -> OpResult<()>;
+
     /// Commit with message
-    fn commit(&self, worktree_path: &Path, message: &str) -> Result<()>;
+    fn commit(&self, worktree_path: &Path, message: &str) -> OpResult<()>;
+
+    /// Stage everything and commit it. Returns `None` when the tree was already clean.
+    fn commit_all(&self, worktree_path: &Path, message: &str) -> OpResult<Option<CommitId>>;
+
+    /// The last `n` commits on a worktree's branch since it left `base`, newest first
+    /// (`git log -n <n> <base>..HEAD`). Empty when nothing was committed on the branch yet.
+    fn recent_commits(&self, worktree_path: &Path, base: &str, n: usize) -> OpResult<Vec<CommitSummary>>;
 
     /// Push branch to origin
-    fn push(&self, worktree_path: &Path, branch: &str, set_upstream: bool) -> Result<()>;
+    fn push(&self, worktree_path: &Path, branch: &str, set_upstream: bool) -> OpResult<()>;
 
     /// List all files (tracked + untracked, respects .gitignore)
     fn list_files(&self, project_path: &Path) -> Vec<String>;
@@ -63,32 +134,131 @@ pub trait GitOperations: Send + Sync {
     ) -> Vec<String>;
 }
 
-/// Real implementation using actual git commands
-pub struct RealGitOps;
+/// Real implementation using actual git commands.
+/// Commands that change the repository are killed if they exceed their class timeout.
+#[derive(Debug, Clone)]
+pub struct RealGitOps {
+    pub timeouts: GitTimeouts,
+    /// Where task worktrees are created (relative to the project root, or absolute)
+    pub worktree_dir: String,
+    /// Free space (in MB) a new worktree needs on that filesystem; None = don't check
+    pub min_free_mb: Option<u64>,
+}
+
+impl Default for RealGitOps {
+    fn default() -> Self {
+        Self::with_timeouts(GitTimeouts::default())
+    }
+}
+
+impl RealGitOps {
+    pub fn with_timeouts(timeouts: GitTimeouts) -> Self {
+        Self { timeouts, worktree_dir: super::DEFAULT_WORKTREE_DIR.to_string(), min_free_mb: None }
+    }
+
+    pub fn with_worktree_dir(mut self, worktree_dir: impl Into<String>) -> Self {
+        self.worktree_dir = worktree_dir.into();
+        self
+    }
+
+    pub fn with_min_free_mb(mut self, min_free_mb: Option<u64>) -> Self {
+        self.min_free_mb = min_free_mb;
+        self
+    }
+
+    /// Refuse a new worktree while its filesystem has less than `min_free_mb` free, before git
+    /// can half-create one on a full disk. A failed measurement is logged and doesn't block.
+    fn check_free_space(&self, project_path: &Path) -> OpResult<()> {
+        let Some(required_mb) = self.min_free_mb.filter(|mb| *mb > 0) else { return Ok(()) };
+        let base = super::worktree_base(project_path, &self.worktree_dir);
+        match crate::platform::available_space(&base) {
+            Ok(bytes) if bytes / (1024 * 1024) < required_mb => {
+                Err(OpError::LowDiskSpace { path: base, available_mb: bytes / (1024 * 1024), required_mb })
+            }
+            Ok(_) => Ok(()),
+            Err(e) => {
+                crate::error_log::log("Free space check", &format!("{:#}", e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Run `git <args>` in `dir` under the timeout for `class`
+    fn run(&self, dir: &Path, args: &[&str], class: GitOpClass) -> OpResult<std::process::Output> {
+        let op = args.iter().take(2).copied().collect::<Vec<_>>().join(" ");
+        let mut cmd = Command::new("git");
+        cmd.current_dir(dir).args(args);
+        run_with_timeout(&mut cmd, &op, self.timeouts.for_class(class))
+    }
+
+    /// Like `run`, but a non-zero exit is an `OpError::CommandFailed`
+    fn run_checked(&self, dir: &Path, args: &[&str], class: GitOpClass) -> OpResult<std::process::Output> {
+        let output = self.run(dir, args, class)?;
+        if !output.status.success() {
+            let op = args.iter().take(2).copied().collect::<Vec<_>>().join(" ");
+            return Err(OpError::failed(format!("git {}", op), &output));
+        }
+        Ok(output)
+    }
+}
 
 impl GitOperations for RealGitOps {
-    fn create_worktree(&self, project_path: &Path, task_slug: &str) -> Result<String> {
-        let path = super::create_worktree(project_path, task_slug)?;
+    fn is_git_repo(&self, project_path: &Path) -> bool {
+        super::is_git_repo(project_path)
+    }
+
+    fn has_commits(&self, project_path: &Path) -> bool {
+        super::has_commits(project_path)
+    }
+
+    fn default_branch(&self, project_path: &Path) -> OpResult<String> {
+        super::default_branch(project_path)
+    }
+
+    fn ref_exists(&self, project_path: &Path, name: &str) -> OpResult<bool> {
+        let commit = format!("{}^{{commit}}", name);
+        let output = self.run(project_path, &["rev-parse", "--verify", "--quiet", "--end-of-options", &commit], GitOpClass::Local)?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(OpError::failed("git rev-parse", &output)),
+        }
+    }
+
+    fn create_worktree(&self, project_path: &Path, task_slug: &str, base: &str) -> OpResult<String> {
+        self.check_free_space(project_path)?;
+        let path =
+            super::create_worktree_in(project_path, &self.worktree_dir, task_slug, base, self.timeouts.worktree)?;
         Ok(path.to_string_lossy().to_string())
     }
 
-    fn remove_worktree(&self, project_path: &Path, worktree_path: &str) -> Result<()> {
-        std::process::Command::new("git")
-            .current_dir(project_path)
-            .args(["worktree", "remove", "--force", worktree_path])
-            .output()?;
+    fn checkout_worktree(&self, project_path: &Path, task_slug: &str, branch: &str) -> OpResult<String> {
+        self.check_free_space(project_path)?;
+        let path =
+            super::checkout_worktree_in(project_path, &self.worktree_dir, task_slug, branch, self.timeouts.worktree)?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    fn remove_worktree(&self, project_path: &Path, worktree_path: &str) -> OpResult<()> {
+        self.run_checked(project_path, &["worktree", "remove", "--force", worktree_path], GitOpClass::Worktree)?;
         Ok(())
     }
 
+    fn worktree_path(&self, project_path: &Path, task_slug: &str) -> PathBuf {
+        super::worktree_path_in(project_path, &self.worktree_dir, task_slug)
+    }
+
     fn worktree_exists(&self, project_path: &Path, task_slug: &str) -> bool {
-        super::worktree_exists(project_path, task_slug)
+        self.worktree_path(project_path, task_slug).exists()
     }
 
-    fn delete_branch(&self, project_path: &Path, branch_name: &str) -> Result<()> {
-        std::process::Command::new("git")
-            .current_dir(project_path)
-            .args(["branch", "-D", branch_name])
-            .output()?;
+    fn list_worktrees(&self, project_path: &Path) -> OpResult<Vec<PathBuf>> {
+        let output = self.run_checked(project_path, &["worktree", "list", "--porcelain"], GitOpClass::Local)?;
+        Ok(super::parse_worktree_list(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn delete_branch(&self, project_path: &Path, branch_name: &str) -> OpResult<()> {
+        self.run(project_path, &["branch", "-D", branch_name], GitOpClass::Local)?;
         Ok(())
     }
 
@@ -128,20 +298,35 @@ impl GitOperations for RealGitOps {
             .unwrap_or_default()
     }
 
-    fn diff_stat_from_main(&self, worktree_path: &Path) -> String {
+    fn diff_stat_from_base(&self, worktree_path: &Path, base: &str) -> String {
         std::process::Command::new("git")
             .current_dir(worktree_path)
-            .args(["diff", "main", "--stat"])
+            .args(["diff", base, "--stat"])
             .output()
             .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
             .unwrap_or_default()
     }
 
-    fn add_all(&self, worktree_path: &Path) -> Result<()> {
+    fn diff_stat_cached(&self, worktree_path: &Path) -> String {
         std::process::Command::new("git")
             .current_dir(worktree_path)
-            .args(["add", "-A"])
-            .output()?;
+            .args(["diff", "--cached", "--stat"])
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+            .unwrap_or_default()
+    }
+
+    fn diff_from_base(&self, worktree_path: &Path, base: &str) -> OpResult<String> {
+        let range = format!("{}...HEAD", base);
+        let output = self.run(worktree_path, &["diff", &range], GitOpClass::Local)?;
+        if !output.status.success() {
+            return Err(OpError::failed(format!("git diff {}", range), &output));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn add_all(&self, worktree_path: &Path) -> OpResult<()> {
+        self.run(worktree_path, &["add", "-A"], GitOpClass::Local)?;
         Ok(())
     }
 
@@ -154,23 +339,78 @@ impl GitOperations for RealGitOps {
             .unwrap_or(false)
     }
 
-    fn commit(&self, worktree_path: &Path, message: &str) -> Result<()> {
+    fn is_worktree_dirty(&self, worktree_path: &Path) -> OpResult<bool> {
+        if !worktree_path.exists() {
+            return Ok(false);
+        }
         let output = std::process::Command::new("git")
             .current_dir(worktree_path)
-            .args(["commit", "-m", message])
-            .output()?;
+            .args(["status", "--porcelain"])
+            .output()
+            .map_err(|e| OpError::spawn("git", e))?;
+        if !output.status.success() {
+            return Err(OpError::failed("git status", &output));
+        }
+        Ok(!output.stdout.is_empty())
+    }
+
+    fn stash_worktree(&self, worktree_path: &Path, name: &str) -> OpResult<()> {
+        self.run_checked(worktree_path, &["stash", "push", "--include-untracked", "-m", name], GitOpClass::Local)?;
+        Ok(())
+    }
+
+    fn commit(&self, worktree_path: &Path, message: &str) -> OpResult<()> {
+        let output = self.run(worktree_path, &["commit", "-m", message], GitOpClass::Local)?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             // Only fail if it's not "nothing to commit"
             if !stderr.contains("nothing to commit") {
-                anyhow::bail!("Failed to commit changes: {}", stderr);
+                return Err(OpError::failed("git commit", &output));
             }
         }
         Ok(())
     }
 
-    fn push(&self, worktree_path: &Path, branch: &str, set_upstream: bool) -> Result<()> {
+    fn commit_all(&self, worktree_path: &Path, message: &str) -> OpResult<Option<CommitId>> {
+        if !self.has_changes(worktree_path) {
+            return Ok(None);
+        }
+        self.add_all(worktree_path)?;
+        self.commit(worktree_path, message)?;
+
+        let output = std::process::Command::new("git")
+            .current_dir(worktree_path)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .map_err(|e| OpError::spawn("git", e))?;
+        if !output.status.success() {
+            return Err(OpError::failed("git rev-parse HEAD", &output));
+        }
+        Ok(Some(CommitId(String::from_utf8_lossy(&output.stdout).trim().to_string())))
+    }
+
+    fn recent_commits(&self, worktree_path: &Path, base: &str, n: usize) -> OpResult<Vec<CommitSummary>> {
+        if !super::is_git_repo(worktree_path) {
+            return Err(OpError::NotAGitRepo { path: worktree_path.to_path_buf() });
+        }
+        let count = n.to_string();
+        let range = format!("{}..HEAD", base);
+        let output = self.run_checked(
+            worktree_path,
+            &["log", "-n", &count, "--format=%h%x09%s", &range, "--"],
+            GitOpClass::Local,
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let (hash, subject) = line.split_once('\t')?;
+                Some(CommitSummary { hash: hash.to_string(), subject: subject.to_string() })
+            })
+            .collect())
+    }
+
+    fn push(&self, worktree_path: &Path, branch: &str, set_upstream: bool) -> OpResult<()> {
         let mut args = vec!["push"];
         if set_upstream {
             args.push("-u");
@@ -178,15 +418,7 @@ impl GitOperations for RealGitOps {
         args.push("origin");
         args.push(branch);
 
-        let output = std::process::Command::new("git")
-            .current_dir(worktree_path)
-            .args(&args)
-            .output()?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            anyhow::bail!("Failed to push branch: {}", stderr);
-        }
+        self.run_checked(worktree_path, &args, GitOpClass::Network)?;
         Ok(())
     }
 
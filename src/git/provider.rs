@@ -24,7 +24,7 @@ pub trait GitProviderOperations: Send + Sync {
     /// Get the state of a pull/merge request
     fn get_pr_state(&self, project_path: &Path, pr_number: i32) -> Result<PullRequestState>;
 
-    /// Create a pull/merge request
+    /// Create a pull/merge request from `head_branch` into `base_branch`
     /// Returns (pr_number, pr_url)
     fn create_pr(
         &self,
@@ -32,6 +32,7 @@ pub trait GitProviderOperations: Send + Sync {
         title: &str,
         body: &str,
         head_branch: &str,
+        base_branch: &str,
     ) -> Result<(i32, String)>;
 }
 
@@ -67,6 +68,7 @@ impl GitProviderOperations for RealGitHubOps {
         title: &str,
         body: &str,
         head_branch: &str,
+        base_branch: &str,
     ) -> Result<(i32, String)> {
         let output = std::process::Command::new("gh")
             .current_dir(project_path)
@@ -75,6 +77,7 @@ impl GitProviderOperations for RealGitHubOps {
                 "--title", title,
                 "--body", body,
                 "--head", head_branch,
+                "--base", base_branch,
             ])
             .output()?;
 
@@ -0,0 +1,99 @@
+//! Running git with a deadline so a hung command (e.g. a credential prompt) can't freeze the TUI.
+
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::op_error::{OpError, OpResult};
+
+/// Classes of git operations, each with its own timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOpClass {
+    /// Commands that only touch the local repository (commit, stash, branch)
+    Local,
+    /// Creating and removing worktrees (checks out a full tree)
+    Worktree,
+    /// Commands that talk to a remote (push)
+    Network,
+}
+
+/// Timeouts per operation class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GitTimeouts {
+    pub local: Duration,
+    pub worktree: Duration,
+    pub network: Duration,
+}
+
+impl Default for GitTimeouts {
+    fn default() -> Self {
+        Self {
+            local: Duration::from_secs(30),
+            worktree: Duration::from_secs(120),
+            network: Duration::from_secs(120),
+        }
+    }
+}
+
+impl GitTimeouts {
+    pub fn for_class(&self, class: GitOpClass) -> Duration {
+        match class {
+            GitOpClass::Local => self.local,
+            GitOpClass::Worktree => self.worktree,
+            GitOpClass::Network => self.network,
+        }
+    }
+}
+
+/// How often a running command is checked for completion
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Run a command, killing it if it hasn't finished within `timeout`.
+/// Stdin is closed and git's terminal prompts are disabled so credential prompts fail fast
+/// instead of waiting on input the TUI will never send.
+pub fn run_with_timeout(cmd: &mut Command, op: &str, timeout: Duration) -> OpResult<Output> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .env("GIT_TERMINAL_PROMPT", "0");
+
+    let mut child = cmd.spawn().map_err(|e| OpError::spawn("git", e))?;
+
+    // Drain the pipes on threads so a chatty command can't block on a full pipe buffer
+    let stdout = child.stdout.take().map(read_on_thread);
+    let stderr = child.stderr.take().map(read_on_thread);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        let exited = child
+            .try_wait()
+            .map_err(|e| OpError::Io { context: format!("Failed to wait for git {}", op), source: e })?;
+        if let Some(status) = exited {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(OpError::Timeout { op: format!("git {}", op), after: timeout });
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(Output {
+        status,
+        stdout: stdout.map(|h| h.join().unwrap_or_default()).unwrap_or_default(),
+        stderr: stderr.map(|h| h.join().unwrap_or_default()).unwrap_or_default(),
+    })
+}
+
+fn read_on_thread<R: std::io::Read + Send + 'static>(mut pipe: R) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        buf
+    })
+}
+
+/// Whether an error (or anything in its context chain) is a git timeout
+pub fn is_timeout(err: &anyhow::Error) -> bool {
+    matches!(crate::op_error::op_error(err), Some(OpError::Timeout { .. }))
+}
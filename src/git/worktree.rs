@@ -1,17 +1,39 @@
 use anyhow::{Context, Result};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
+
+use crate::op_error::{OpError, OpResult};
+use crate::platform;
+
+/// Where task worktrees go when `worktree_dir` isn't configured, relative to the project root
+pub const DEFAULT_WORKTREE_DIR: &str = ".agtx/worktrees";
+
+/// Create a new git worktree for a task from the project's default branch
+pub fn create_worktree(project_path: &Path, task_slug: &str) -> OpResult<PathBuf> {
+    create_worktree_in(
+        project_path,
+        DEFAULT_WORKTREE_DIR,
+        task_slug,
+        &default_branch(project_path)?,
+        super::GitTimeouts::default().worktree,
+    )
+}
 
-/// Directory name for agtx data within a project
-const AGTX_DIR: &str = ".agtx";
-const WORKTREES_DIR: &str = "worktrees";
-
-/// Create a new git worktree for a task from the main branch
-pub fn create_worktree(project_path: &Path, task_slug: &str) -> Result<PathBuf> {
-    let worktree_path = project_path
-        .join(AGTX_DIR)
-        .join(WORKTREES_DIR)
-        .join(task_slug);
+/// Create a worktree under `worktree_dir` (see `worktree_base`) on a new branch from `base`,
+/// killing `git worktree add` if it runs longer than `timeout`
+pub fn create_worktree_in(
+    project_path: &Path,
+    worktree_dir: &str,
+    task_slug: &str,
+    base: &str,
+    timeout: Duration,
+) -> OpResult<PathBuf> {
+    let worktree_path = worktree_path_in(project_path, worktree_dir, task_slug);
+
+    if !super::is_git_repo(project_path) {
+        return Err(OpError::NotAGitRepo { path: project_path.to_path_buf() });
+    }
 
     // If worktree already exists and is valid, return it
     if worktree_path.exists() && worktree_path.join(".git").exists() {
@@ -25,13 +47,18 @@ pub fn create_worktree(project_path: &Path, task_slug: &str) -> Result<PathBuf>
 
     // Ensure parent directory exists
     if let Some(parent) = worktree_path.parent() {
-        std::fs::create_dir_all(parent)?;
+        std::fs::create_dir_all(parent).map_err(|e| OpError::Io {
+            context: format!("Failed to create worktree directory {}", parent.display()),
+            source: e,
+        })?;
     }
 
-    // Detect the main branch (main or master)
-    let main_branch = detect_main_branch(project_path)?;
+    // An unborn HEAD would make `worktree add` fail with "invalid reference"
+    if !super::has_commits(project_path) {
+        return Err(OpError::NoCommits { path: project_path.to_path_buf() });
+    }
 
-    // Create worktree with a new branch based on main
+    // Create worktree with a new branch based on the base branch
     let branch_name = format!("task/{}", task_slug);
 
     // First, try to delete the branch if it exists (from a previous failed attempt)
@@ -40,17 +67,58 @@ pub fn create_worktree(project_path: &Path, task_slug: &str) -> Result<PathBuf>
         .args(["branch", "-D", &branch_name])
         .output();
 
-    let output = Command::new("git")
-        .current_dir(project_path)
+    let mut cmd = Command::new("git");
+    cmd.current_dir(project_path)
         .args(["worktree", "add"])
         .arg(&worktree_path)
-        .args(["-b", &branch_name, &main_branch])
-        .output()
-        .context("Failed to create git worktree")?;
+        .args(["-b", &branch_name, base]);
+    let output = super::run_with_timeout(&mut cmd, "worktree add", timeout)?;
+
+    if !output.status.success() {
+        if String::from_utf8_lossy(&output.stderr).contains("already exists") {
+            return Err(OpError::WorktreeExists { path: worktree_path });
+        }
+        return Err(OpError::failed("git worktree add", &output));
+    }
+
+    Ok(worktree_path)
+}
+
+/// Create the worktree for a task on its existing `branch` (kept when the task went to Done),
+/// instead of a new branch from the base. An existing valid worktree is returned as is.
+pub fn checkout_worktree_in(
+    project_path: &Path,
+    worktree_dir: &str,
+    task_slug: &str,
+    branch: &str,
+    timeout: Duration,
+) -> OpResult<PathBuf> {
+    let worktree_path = worktree_path_in(project_path, worktree_dir, task_slug);
+
+    if !super::is_git_repo(project_path) {
+        return Err(OpError::NotAGitRepo { path: project_path.to_path_buf() });
+    }
+    if worktree_path.exists() && worktree_path.join(".git").exists() {
+        return Ok(worktree_path);
+    }
+    if worktree_path.exists() {
+        let _ = std::fs::remove_dir_all(&worktree_path);
+    }
+    if let Some(parent) = worktree_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| OpError::Io {
+            context: format!("Failed to create worktree directory {}", parent.display()),
+            source: e,
+        })?;
+    }
 
+    let mut cmd = Command::new("git");
+    cmd.current_dir(project_path)
+        .args(["worktree", "add"])
+        .arg(&worktree_path)
+        .arg(branch);
+    let output = super::run_with_timeout(&mut cmd, "worktree add", timeout)?;
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("Failed to create worktree: {}", stderr);
+        return Err(OpError::failed("git worktree add", &output));
     }
 
     Ok(worktree_path)
@@ -145,7 +213,7 @@ pub fn initialize_worktree(
     if let Some(script) = init_script {
         let script = script.trim();
         if !script.is_empty() {
-            match Command::new("sh").arg("-c").arg(script).current_dir(worktree_path).output() {
+            match platform::shell_command(script).current_dir(worktree_path).output() {
                 Ok(result) => {
                     if !result.status.success() {
                         let stderr = String::from_utf8_lossy(&result.stderr);
@@ -183,13 +251,53 @@ fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
 }
 
 /// Detect the main branch name (main or master)
-fn detect_main_branch(project_path: &Path) -> Result<String> {
+/// The branch new work is based on when `base_branch` isn't configured: whatever
+/// `origin/HEAD` points at, else `main`, `master` or the current branch. Needs no remote: a
+/// repository without `origin` (or offline) uses the local branches, and a detached HEAD
+/// without them gives `HEAD`, the current commit. Fails with `OpError::NoCommits` on an empty
+/// repository instead of returning a ref that can't be branched from.
+pub fn default_branch(project_path: &Path) -> OpResult<String> {
+    let output = Command::new("git")
+        .current_dir(project_path)
+        .args(["symbolic-ref", "--quiet", "refs/remotes/origin/HEAD"])
+        .output()
+        .map_err(|e| OpError::spawn("git", e))?;
+
+    if output.status.success() {
+        if let Some(branch) = parse_default_branch(&String::from_utf8_lossy(&output.stdout)) {
+            return Ok(branch);
+        }
+    }
+
+    detect_main_branch(project_path)
+}
+
+/// Branch name from `git symbolic-ref refs/remotes/origin/HEAD` output
+/// (`refs/remotes/origin/develop` → `develop`)
+pub fn parse_default_branch(symbolic_ref: &str) -> Option<String> {
+    let branch = symbolic_ref.trim().strip_prefix("refs/remotes/origin/")?;
+    (!branch.is_empty()).then(|| branch.to_string())
+}
+
+/// Linked worktree paths from `git worktree list --porcelain` output. The first entry (the
+/// main checkout) and entries git marks `prunable` (directory deleted) are skipped.
+pub fn parse_worktree_list(porcelain: &str) -> Vec<PathBuf> {
+    porcelain
+        .split("\n\n")
+        .skip(1)
+        .filter(|entry| !entry.lines().any(|line| line.starts_with("prunable")))
+        .filter_map(|entry| entry.lines().find_map(|line| line.strip_prefix("worktree ")))
+        .map(PathBuf::from)
+        .collect()
+}
+
+fn detect_main_branch(project_path: &Path) -> OpResult<String> {
     // Check if 'main' exists
     let output = Command::new("git")
         .current_dir(project_path)
         .args(["rev-parse", "--verify", "main"])
         .output()
-        .context("Failed to check for main branch")?;
+        .map_err(|e| OpError::spawn("git", e))?;
 
     if output.status.success() {
         return Ok("main".to_string());
@@ -200,28 +308,36 @@ fn detect_main_branch(project_path: &Path) -> Result<String> {
         .current_dir(project_path)
         .args(["rev-parse", "--verify", "master"])
         .output()
-        .context("Failed to check for master branch")?;
+        .map_err(|e| OpError::spawn("git", e))?;
 
     if output.status.success() {
         return Ok("master".to_string());
     }
 
-    // Fallback: get the current branch
+    if !super::is_git_repo(project_path) {
+        return Err(OpError::NotAGitRepo { path: project_path.to_path_buf() });
+    }
+    if !super::has_commits(project_path) {
+        return Err(OpError::NoCommits { path: project_path.to_path_buf() });
+    }
+
+    // Fallback: the current branch, or on a detached HEAD the current commit
     let output = Command::new("git")
         .current_dir(project_path)
-        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .args(["symbolic-ref", "--quiet", "--short", "HEAD"])
         .output()
-        .context("Failed to get current branch")?;
-
-    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .map_err(|e| OpError::spawn("git", e))?;
+    let branch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() && !branch.is_empty() {
+        Ok(branch)
+    } else {
+        Ok("HEAD".to_string())
+    }
 }
 
 /// Remove a git worktree
 pub fn remove_worktree(project_path: &Path, task_id: &str) -> Result<()> {
-    let worktree_path = project_path
-        .join(AGTX_DIR)
-        .join(WORKTREES_DIR)
-        .join(task_id);
+    let worktree_path = worktree_path(project_path, task_id);
 
     // Remove the worktree
     let output = Command::new("git")
@@ -243,15 +359,68 @@ pub fn remove_worktree(project_path: &Path, task_id: &str) -> Result<()> {
     Ok(())
 }
 
-/// Get the worktree path for a task
+/// Directory holding a project's task worktrees.
+/// A relative `worktree_dir` is resolved against the project root. An absolute one can be
+/// shared by several projects, so each project gets a subdirectory named after its root.
+pub fn worktree_base(project_path: &Path, worktree_dir: &str) -> PathBuf {
+    let dir = platform::native_path(worktree_dir);
+    if dir.is_absolute() {
+        let project_name = project_path
+            .file_name()
+            .map(|n| n.to_os_string())
+            .unwrap_or_else(|| "project".into());
+        dir.join(project_name)
+    } else {
+        project_path.join(dir)
+    }
+}
+
+/// Get the worktree path for a task under a configured `worktree_dir`
+pub fn worktree_path_in(project_path: &Path, worktree_dir: &str, task_id: &str) -> PathBuf {
+    worktree_base(project_path, worktree_dir).join(task_id)
+}
+
+/// Get the worktree path for a task in the default location
 pub fn worktree_path(project_path: &Path, task_id: &str) -> PathBuf {
-    project_path
-        .join(AGTX_DIR)
-        .join(WORKTREES_DIR)
-        .join(task_id)
+    worktree_path_in(project_path, DEFAULT_WORKTREE_DIR, task_id)
 }
 
 /// Check if a worktree exists for a task
 pub fn worktree_exists(project_path: &Path, task_id: &str) -> bool {
     worktree_path(project_path, task_id).exists()
 }
+
+/// Where in a worktree the agent's exit code is written when it exits (see `tmux::record_exit_code`)
+pub const AGENT_EXIT_CODE_FILE: &str = ".agtx/agent-exit-code";
+
+pub fn agent_exit_code_path(worktree_path: &Path) -> PathBuf {
+    worktree_path.join(platform::native_path(AGENT_EXIT_CODE_FILE))
+}
+
+/// Where in a worktree a prompt too long for the agent's command line is written
+/// (see `tmux::WindowSetup::agent_command`)
+pub const PROMPT_FILE: &str = ".agtx/prompt.md";
+
+pub fn prompt_file_path(worktree_path: &Path) -> PathBuf {
+    worktree_path.join(platform::native_path(PROMPT_FILE))
+}
+
+/// The exit code the last agent in this worktree wrote, or `None` while it's still running
+pub fn read_agent_exit_code(worktree_path: &Path) -> Option<i32> {
+    std::fs::read_to_string(agent_exit_code_path(worktree_path))
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+}
+
+/// Forget a previous agent's exit code before starting a new one, and make sure the
+/// directory for the next one exists
+pub fn clear_agent_exit_code(worktree_path: &Path) {
+    if !worktree_path.is_dir() {
+        return;
+    }
+    let path = agent_exit_code_path(worktree_path);
+    let _ = std::fs::remove_file(&path);
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+}
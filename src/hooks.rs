@@ -0,0 +1,108 @@
+//! User shell hooks run when a task changes column (`[hooks]` in config).
+//!
+//! Hooks run synchronously once a transition's built-in side effects are done. By default a
+//! failing hook is logged and the move goes ahead; with `strict = true` it blocks the move.
+
+use anyhow::Result;
+use std::path::Path;
+use std::process::Stdio;
+
+#[cfg(feature = "test-mocks")]
+use mockall::automock;
+
+use crate::config::HooksConfig;
+use crate::db::{Task, TaskStatus};
+use crate::error_log;
+use crate::op_error::{OpError, OpResult};
+use crate::platform::{self, shell_quote};
+
+/// Runs hook commands (mockable so transitions can be tested without a shell)
+#[cfg_attr(feature = "test-mocks", automock)]
+pub trait HookRunner: Send + Sync {
+    /// Run `command` in the platform shell (`sh -c`, see `platform::shell_command`) in `dir`
+    fn run(&self, command: &str, dir: &Path) -> OpResult<()>;
+}
+
+/// Runs hooks with the system shell
+pub struct ShellHookRunner;
+
+impl HookRunner for ShellHookRunner {
+    fn run(&self, command: &str, dir: &Path) -> OpResult<()> {
+        let output = platform::shell_command(command)
+            .current_dir(dir)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| OpError::spawn(platform::shell_program(), e))?;
+        if !output.status.success() {
+            return Err(OpError::failed(command, &output));
+        }
+        Ok(())
+    }
+}
+
+/// A hook that exited unsuccessfully (or couldn't be started)
+#[derive(Debug)]
+pub struct HookFailure {
+    pub name: String,
+    pub error: OpError,
+}
+
+/// Fill `{title}`, `{slug}`, `{worktree}` and `{status}` in a hook command. Values are
+/// shell-quoted, so templates use them bare: `notify-send {title}`.
+pub fn render_hook(template: &str, task: &Task, status: TaskStatus) -> String {
+    let slug = task
+        .branch_name
+        .as_deref()
+        .and_then(|b| b.strip_prefix("task/"))
+        .unwrap_or(&task.id);
+    template
+        .replace("{title}", &shell_quote(&task.title))
+        .replace("{slug}", &shell_quote(slug))
+        .replace("{worktree}", &shell_quote(task.worktree_path.as_deref().unwrap_or("")))
+        .replace("{status}", status.as_str())
+}
+
+/// Run the hooks configured for `from -> to`. Each runs in the task's worktree, or the project
+/// root once the worktree is gone. In strict mode nothing runs after the first failure.
+pub fn run_transition_hooks(
+    hooks: &HooksConfig,
+    runner: &dyn HookRunner,
+    task: &Task,
+    from: TaskStatus,
+    to: TaskStatus,
+    project_path: &Path,
+) -> Vec<HookFailure> {
+    let dir = task
+        .worktree_path
+        .as_deref()
+        .map(Path::new)
+        .filter(|p| p.is_dir())
+        .unwrap_or(project_path);
+    let mut failures = Vec::new();
+    for (name, template) in hooks.commands_for(from, to) {
+        if let Err(error) = runner.run(&render_hook(template, task, to), dir) {
+            failures.push(HookFailure { name, error });
+            if hooks.strict {
+                break;
+            }
+        }
+    }
+    failures
+}
+
+/// Decide what hook failures mean for the transition. Strict: the first failure is returned as
+/// an error and the move must not happen. Otherwise each failure goes to the error log and the
+/// failed hook names are returned for a warning.
+pub fn settle(failures: Vec<HookFailure>, strict: bool) -> Result<Vec<String>> {
+    if strict {
+        if let Some(failure) = failures.into_iter().next() {
+            return Err(anyhow::Error::new(failure.error)
+                .context(format!("Hook '{}' failed", failure.name)));
+        }
+        return Ok(Vec::new());
+    }
+    for failure in &failures {
+        error_log::log(&format!("Hook '{}' failed", failure.name), &failure.error.to_string());
+    }
+    Ok(failures.into_iter().map(|f| f.name).collect())
+}
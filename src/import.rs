@@ -0,0 +1,117 @@
+//! `agtx import <dir>`: one Backlog task per markdown file in a directory, e.g. an issue
+//! tracker's export. A file's first `# heading` is the title (its file name when it has none)
+//! and the rest is the description.
+//!
+//! Re-importing is safe: a file whose title matches a task already on the board (in any
+//! column, ignoring case) is skipped, and so is a second file with the same title.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::db::Task;
+
+/// A task read from one markdown file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub path: PathBuf,
+    pub title: String,
+    pub description: Option<String>,
+}
+
+/// Title and description of a markdown file named `file_stem`. The first `# ` heading is the
+/// title and is left out of the description; without one the file name is, with `-` and `_`
+/// read as spaces.
+pub fn parse_issue(file_stem: &str, content: &str) -> (String, Option<String>) {
+    let lines: Vec<&str> = content.lines().collect();
+    let heading = lines
+        .iter()
+        .position(|l| l.starts_with("# "))
+        .map(|i| (i, lines[i][2..].trim()))
+        .filter(|(_, title)| !title.is_empty());
+
+    let (title, body) = match heading {
+        Some((i, title)) => {
+            let rest: Vec<&str> = lines[..i].iter().chain(&lines[i + 1..]).copied().collect();
+            (title.to_string(), rest.join("\n"))
+        }
+        None => {
+            let title = file_stem.replace(['-', '_'], " ");
+            (title.split_whitespace().collect::<Vec<_>>().join(" "), content.to_string())
+        }
+    };
+    let body = body.trim();
+    (title, (!body.is_empty()).then(|| body.to_string()))
+}
+
+/// The `.md` files directly inside `dir`, by file name. Files that can't be read are returned
+/// as failures instead of stopping the import.
+pub fn read_issues(dir: &Path) -> Result<(Vec<Issue>, Vec<String>)> {
+    let entries = std::fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("md")))
+        .collect();
+    paths.sort();
+
+    let mut issues = Vec::new();
+    let mut failures = Vec::new();
+    for path in paths {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                let (title, description) = parse_issue(&stem, &content);
+                if title.is_empty() {
+                    failures.push(format!("{}: no title", path.display()));
+                } else {
+                    issues.push(Issue { path, title, description });
+                }
+            }
+            Err(e) => failures.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    Ok((issues, failures))
+}
+
+/// Which issues become tasks
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    pub create: Vec<Issue>,
+    /// Already on the board, or repeated within the import
+    pub skipped: Vec<Issue>,
+}
+
+/// Split issues into new ones and ones whose title a task (or an earlier issue) already has
+pub fn plan_import(issues: Vec<Issue>, existing: &[Task]) -> ImportPlan {
+    let mut titles: HashSet<String> = existing.iter().map(|t| t.title.trim().to_lowercase()).collect();
+    let mut plan = ImportPlan::default();
+    for issue in issues {
+        if titles.insert(issue.title.trim().to_lowercase()) {
+            plan.create.push(issue);
+        } else {
+            plan.skipped.push(issue);
+        }
+    }
+    plan
+}
+
+/// What an import did, for the report printed afterwards
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub created: Vec<String>,
+    pub skipped: Vec<String>,
+    /// Files that couldn't be imported, and why
+    pub failures: Vec<String>,
+}
+
+impl ImportSummary {
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("{} task(s) created", self.created.len()),
+            format!("{} skipped (already on the board)", self.skipped.len()),
+        ];
+        lines.extend(self.failures.iter().map(|f| format!("Failed: {}", f)));
+        lines
+    }
+}
@@ -1,10 +1,24 @@
+pub mod adopt;
 pub mod agent;
+pub mod api;
 pub mod config;
 pub mod db;
+pub mod doctor;
+pub mod dry_run;
+pub mod error_log;
 pub mod git;
+pub mod hooks;
+pub mod import;
+pub mod op_error;
+pub mod platform;
+pub mod scheduler;
 pub mod skills;
+pub mod slug;
+pub mod snapshot;
 pub mod tmux;
 pub mod tui;
+pub mod webhook;
+pub mod workflow;
 
 use std::path::PathBuf;
 
@@ -1,5 +1,6 @@
-use agtx::{agent, config::{self, GlobalConfig}, git, tui, AppMode};
-use anyhow::Result;
+use agtx::dry_run::{DryRunGitOps, DryRunGitProviderOps, DryRunHookRunner, DryRunLog, DryRunTmuxOps};
+use agtx::{agent, api, config::{self, GlobalConfig, ProjectConfig}, doctor, git, tui, AppMode};
+use anyhow::{Context, Result};
 use crossterm::{
     cursor,
     event::{self, Event, KeyCode},
@@ -8,13 +9,36 @@ use crossterm::{
 };
 use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Parse command line arguments
-    let args: Vec<String> = std::env::args().collect();
+    // Parse command line arguments: flags anywhere, plus an optional mode/path
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|a| a.as_str()) == Some("doctor") {
+        let project_path = match args.get(1) {
+            Some(path) => PathBuf::from(path),
+            None => std::env::current_dir()?,
+        };
+        std::process::exit(run_doctor(&project_path));
+    }
+    if args.first().map(|a| a.as_str()) == Some("serve") {
+        return run_serve(&args[1..]);
+    }
+    if args.first().map(|a| a.as_str()) == Some("snapshot") {
+        return run_snapshot(&args[1..]);
+    }
+    if args.first().map(|a| a.as_str()) == Some("import") {
+        return run_import(&args[1..]);
+    }
+    if args.first().map(|a| a.as_str()) == Some("adopt") {
+        return run_adopt(&args[1..]);
+    }
+    let dry_run = args.iter().any(|a| a == "--dry-run");
+    let no_tmux_flag = args.iter().any(|a| a == "--no-tmux");
+    let target = args.iter().find(|a| *a != "--dry-run" && *a != "--no-tmux");
 
-    let mode = match args.get(1).map(|s| s.as_str()) {
+    let mode = match target.map(|s| s.as_str()) {
         Some("-g") => AppMode::Dashboard,
         Some(".") => AppMode::Project(std::env::current_dir()?),
         Some(path) => AppMode::Project(PathBuf::from(path)),
@@ -57,13 +81,214 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Without the multiplexer, refuse to start unless no-tmux mode was chosen explicitly
+    let global = GlobalConfig::load().unwrap_or_default();
+    let no_tmux = no_tmux_flag || global.no_tmux;
+    if !no_tmux && !dry_run {
+        let check = doctor::check_multiplexer(global.multiplexer, false);
+        if check.status == doctor::CheckStatus::Fail {
+            eprintln!("agtx needs {} to run agents: {}", global.multiplexer.program(), check.detail);
+            eprintln!(
+                "Install it, or start with --no-tmux (or set no_tmux = true in the config) \
+                 to track tasks and worktrees without agent windows."
+            );
+            std::process::exit(1);
+        }
+    }
+
     // Initialize and run the app
-    let mut app = tui::App::new(mode)?;
-    app.run().await?;
+    if dry_run {
+        let log = DryRunLog::new();
+        {
+            let mut app = tui::App::with_ops(
+                mode,
+                Arc::new(DryRunTmuxOps::new(log.clone())),
+                Arc::new(DryRunGitOps::new(log.clone(), global.git_ops())),
+                Arc::new(DryRunGitProviderOps::new(log.clone())),
+                Arc::new(agent::RealAgentRegistry::new("claude")),
+                Arc::new(DryRunHookRunner::new(log.clone())),
+            )?;
+            app.run().await?;
+        }
+        // The app is dropped above, so the terminal is restored before printing
+        print_dry_run_log(&log);
+    } else {
+        let mut app = tui::App::new(mode, no_tmux)?;
+        app.run().await?;
+    }
+
+    Ok(())
+}
+
+/// `agtx serve [path] [--socket <file>] [--no-tmux] [--events]`: run the scripting API without
+/// the TUI, with `--events` also streaming task events to stdout
+fn run_serve(args: &[String]) -> Result<()> {
+    let mut socket = None;
+    let mut project = None;
+    let mut no_tmux = false;
+    let mut events = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--socket" => socket = Some(PathBuf::from(args.next().context("--socket needs a path")?)),
+            "--no-tmux" => no_tmux = true,
+            "--events" => events = true,
+            path => project = Some(PathBuf::from(path)),
+        }
+    }
+    let project = match project {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let project = project.canonicalize().with_context(|| format!("No such project: {}", project.display()))?;
+    let socket = socket.unwrap_or_else(|| api::default_socket_path(&project));
+
+    let no_tmux = no_tmux || GlobalConfig::load().unwrap_or_default().no_tmux;
+    let mut app = tui::App::headless(AppMode::Project(project), no_tmux)?;
+    if events {
+        app.stream_events()?;
+    }
+    app.serve(&socket)
+}
+
+/// `agtx snapshot save|restore <name> [path] [--no-tmux]` and `agtx snapshot list [path]`
+fn run_snapshot(args: &[String]) -> Result<()> {
+    const USAGE: &str = "usage: agtx snapshot save|restore <name> [path] [--no-tmux], or agtx snapshot list [path]";
+    let no_tmux = args.iter().any(|a| a == "--no-tmux");
+    let mut args = args.iter().filter(|a| *a != "--no-tmux");
+    let command = args.next().context(USAGE)?.clone();
+    let name = match command.as_str() {
+        "save" | "restore" => Some(args.next().context(USAGE)?.clone()),
+        "list" => None,
+        _ => anyhow::bail!(USAGE),
+    };
+    let project = match args.next() {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?,
+    };
+    let project = project.canonicalize().with_context(|| format!("No such project: {}", project.display()))?;
+
+    if command == "list" {
+        for name in agtx::snapshot::list(&project)? {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+    let name = name.unwrap_or_default();
+    let no_tmux = no_tmux || GlobalConfig::load().unwrap_or_default().no_tmux;
+    let mut app = tui::App::headless(AppMode::Project(project), no_tmux)?;
+    if command == "save" {
+        let path = app.save_snapshot(&name)?;
+        println!("Saved snapshot '{}' to {}", name, path.display());
+    } else {
+        let summary = app.restore_snapshot(&name)?;
+        println!("Restored snapshot '{}':", name);
+        for line in summary.lines() {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+fn run_import(args: &[String]) -> Result<()> {
+    const USAGE: &str = "usage: agtx import <dir> [path]";
+    let dir = PathBuf::from(args.first().context(USAGE)?);
+    let project = match args.get(1) {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?,
+    };
+    let project = project.canonicalize().with_context(|| format!("No such project: {}", project.display()))?;
+
+    // Importing only touches the database, so no multiplexer is needed
+    let mut app = tui::App::headless(AppMode::Project(project), true)?;
+    let summary = app.import_tasks(&dir)?;
+    println!("Imported {}:", dir.display());
+    for line in summary.lines() {
+        println!("  {}", line);
+    }
+    Ok(())
+}
+
+/// `agtx adopt <worktree> [path] [--window <name>] [--title <title>] [--no-tmux]` and
+/// `agtx adopt list [path]`
+fn run_adopt(args: &[String]) -> Result<()> {
+    const USAGE: &str = "usage: agtx adopt <worktree> [path] [--window <name>] [--title <title>] [--no-tmux], \
+                         or agtx adopt list [path]";
+    let mut window = None;
+    let mut title = None;
+    let mut no_tmux = false;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--window" => window = Some(args.next().context("--window needs a window name")?.clone()),
+            "--title" => title = Some(args.next().context("--title needs a title")?.clone()),
+            "--no-tmux" => no_tmux = true,
+            _ => positional.push(arg.clone()),
+        }
+    }
+    let mut positional = positional.into_iter();
+    let worktree = positional.next().context(USAGE)?;
+    let project = match positional.next() {
+        Some(path) => PathBuf::from(path),
+        None => std::env::current_dir()?,
+    };
+    let project = project.canonicalize().with_context(|| format!("No such project: {}", project.display()))?;
 
+    let no_tmux = no_tmux || GlobalConfig::load().unwrap_or_default().no_tmux;
+    if no_tmux && window.is_some() {
+        anyhow::bail!("--window needs the multiplexer; drop --no-tmux");
+    }
+    let mut app = tui::App::headless(AppMode::Project(project), no_tmux)?;
+    if worktree == "list" {
+        let (worktrees, windows) = app.adoption_candidates()?;
+        println!("Worktrees no task tracks:");
+        for path in &worktrees {
+            println!("  {}", path.display());
+        }
+        println!("Windows no task tracks:");
+        for name in &windows {
+            println!("  {}", name);
+        }
+        return Ok(());
+    }
+    let task = app.adopt(&worktree, window.as_deref(), title.as_deref())?;
+    println!("Adopted '{}' into Running", task.title);
+    println!("  worktree: {}", task.worktree_path.as_deref().unwrap_or_default());
+    if let Some(branch) = &task.branch_name {
+        println!("  branch: {}", branch);
+    }
+    if let Some(window) = &task.session_name {
+        println!("  window: {}", window);
+    }
     Ok(())
 }
 
+/// Print the environment checks for `agtx doctor`; returns the process exit code
+fn run_doctor(project_path: &std::path::Path) -> i32 {
+    let global = GlobalConfig::load().unwrap_or_default();
+    let project = ProjectConfig::load(project_path).unwrap_or_default();
+    let results = doctor::run_checks(project_path, &global, &project);
+    println!("{}", doctor::format_report(&results));
+    if doctor::has_critical_failure(&results) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Print the commands a dry run would have executed
+fn print_dry_run_log(log: &DryRunLog) {
+    if log.is_empty() {
+        println!("# agtx --dry-run: no git/tmux operations were issued");
+        return;
+    }
+    println!("# agtx --dry-run: operations that would have been executed");
+    for line in log.entries() {
+        println!("{}", line);
+    }
+}
+
 /// Migrate config from the old location (directories crate config_dir) to the new one (~/.config/agtx/).
 /// Returns true if migration was performed.
 fn migrate_old_config(new_path: &std::path::Path) -> bool {
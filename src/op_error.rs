@@ -0,0 +1,110 @@
+//! Typed errors for the git and tmux operations layer (`GitOperations`, `TmuxOperations`).
+//!
+//! Callers that just propagate can keep using `?` into `anyhow::Result`; callers that want to
+//! react to a particular failure match on the variant, or find it in a chain with `op_error`.
+
+use std::path::PathBuf;
+use std::process::Output;
+use std::time::Duration;
+
+pub type OpResult<T> = std::result::Result<T, OpError>;
+
+/// A failed git or tmux operation. `Display` is written for the error bar.
+#[derive(Debug, thiserror::Error)]
+pub enum OpError {
+    /// The program isn't installed (or isn't on PATH)
+    #[error("`{program}` was not found; is it installed and on PATH?")]
+    CommandNotFound { program: String },
+
+    /// The command ran and exited unsuccessfully (`code` is None when killed by a signal)
+    #[error("`{command}` failed{}{}", .code.map(|c| format!(" (exit {})", c)).unwrap_or_default(), stderr_suffix(.stderr))]
+    CommandFailed { command: String, code: Option<i32>, stderr: String },
+
+    /// The command ran past its deadline and was killed
+    #[error("{op} timed out after {}s and was stopped", .after.as_secs())]
+    Timeout { op: String, after: Duration },
+
+    /// The directory isn't inside a git repository
+    #[error("{} is not a git repository", .path.display())]
+    NotAGitRepo { path: PathBuf },
+
+    /// The repository has no commit yet, so there's nothing to branch a worktree from
+    #[error("{} has no commits yet; make a first commit before starting tasks", .path.display())]
+    NoCommits { path: PathBuf },
+
+    /// Something other than a worktree is in the way of a new one
+    #[error("a worktree or branch already exists at {}", .path.display())]
+    WorktreeExists { path: PathBuf },
+
+    /// The ref a worktree should branch from doesn't exist (e.g. a deleted feature branch)
+    #[error("base ref '{name}' not found")]
+    RefNotFound { name: String },
+
+    /// The filesystem worktrees go on has less free space than `worktree.min_free_mb`
+    #[error("only {available_mb} MB free at {}, below worktree.min_free_mb ({required_mb} MB)", .path.display())]
+    LowDiskSpace { path: PathBuf, available_mb: u64, required_mb: u64 },
+
+    /// The tmux window (or its session) is gone
+    #[error("tmux window '{target}' not found")]
+    WindowNotFound { target: String },
+
+    /// No tmux server is listening on the socket (nothing has started it yet)
+    #[error("tmux server '{server}' is not running")]
+    ServerNotRunning { server: String },
+
+    /// agtx runs without tmux (`--no-tmux`), so there are no agent windows
+    #[error("tmux is disabled (no-tmux mode), so tasks have no agent windows")]
+    TmuxDisabled,
+
+    /// Filesystem error around an operation (e.g. creating the worktree directory)
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+fn stderr_suffix(stderr: &str) -> String {
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", stderr)
+    }
+}
+
+impl OpError {
+    /// Error for a command that couldn't be started
+    pub fn spawn(program: &str, error: std::io::Error) -> Self {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            OpError::CommandNotFound { program: program.to_string() }
+        } else {
+            OpError::Io { context: format!("Failed to run {}", program), source: error }
+        }
+    }
+
+    /// Error for a command that ran and exited unsuccessfully
+    pub fn failed(command: impl Into<String>, output: &Output) -> Self {
+        OpError::CommandFailed {
+            command: command.into(),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+    }
+
+    /// Worth trying again later (the window may be recreated, a slow remote may answer)
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, OpError::WindowNotFound { .. } | OpError::Timeout { .. })
+    }
+
+    /// Nothing will work until the user fixes their environment
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, OpError::CommandNotFound { .. } | OpError::NotAGitRepo { .. })
+    }
+}
+
+/// The `OpError` in an error's context chain, if any
+pub fn op_error(err: &anyhow::Error) -> Option<&OpError> {
+    err.chain().find_map(|e| e.downcast_ref::<OpError>())
+}
@@ -0,0 +1,101 @@
+//! What differs between operating systems: the shell that runs configured commands
+//! (init scripts, hooks), path separators, free disk space, and which multiplexers can run.
+//!
+//! Everything that spawns a multiplexer goes through `TmuxOperations`, so a Windows backend
+//! (Windows Terminal tabs, or tmux inside WSL) is another implementation of that trait.
+//! Until one exists, tmux and zellij are reported as unsupported on Windows and agtx runs
+//! as if `--no-tmux` was given.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The program that runs configured shell snippets on this OS
+pub fn shell_program() -> &'static str {
+    if cfg!(windows) {
+        "powershell"
+    } else {
+        "sh"
+    }
+}
+
+/// A command running `script` in the platform shell: `sh -c` on Unix,
+/// `powershell -NoProfile -Command` on Windows
+pub fn shell_command(script: &str) -> Command {
+    let mut cmd = Command::new(shell_program());
+    if cfg!(windows) {
+        cmd.args(["-NoProfile", "-Command", script]);
+    } else {
+        cmd.args(["-c", script]);
+    }
+    cmd
+}
+
+/// Quote a word for POSIX sh. Words made only of safe characters are left bare.
+pub fn shell_quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// `s` as a PowerShell single-quoted string literal, where only `'` is special (doubled)
+pub fn powershell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// A `/`-separated path (from config or a constant like `.agtx/worktrees`) with this
+/// platform's separators
+pub fn native_path(path: &str) -> PathBuf {
+    Path::new(path).components().collect()
+}
+
+/// Bytes free (for unprivileged users) on the filesystem holding `path`. The path doesn't have
+/// to exist yet, e.g. a worktree directory: its nearest existing parent is measured. Runs
+/// `df -Pk` on Unix and asks .NET's `DriveInfo` through PowerShell on Windows.
+pub fn available_space(path: &Path) -> Result<u64> {
+    let existing = path.ancestors().find(|p| p.exists()).unwrap_or(path);
+    let output = if cfg!(windows) {
+        let script = format!(
+            "([System.IO.DriveInfo]::new({})).AvailableFreeSpace",
+            powershell_quote(&existing.to_string_lossy())
+        );
+        Command::new("powershell").args(["-NoProfile", "-Command", &script]).output()
+    } else {
+        Command::new("df").arg("-Pk").arg(existing).output()
+    }
+    .with_context(|| format!("Failed to check free space at {}", existing.display()))?;
+    if !output.status.success() {
+        bail!(
+            "Checking free space at {} failed: {}",
+            existing.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let available = if cfg!(windows) { stdout.trim().parse().ok() } else { parse_df_available(&stdout) };
+    available.with_context(|| format!("Unexpected free space output for {}: {}", existing.display(), stdout.trim()))
+}
+
+/// Bytes available in `df -Pk` output: the fourth column (1024-byte blocks) of the first line
+/// after the header
+pub fn parse_df_available(output: &str) -> Option<u64> {
+    let line = output.lines().nth(1)?;
+    let blocks: u64 = line.split_whitespace().nth(3)?.parse().ok()?;
+    blocks.checked_mul(1024)
+}
+
+/// Why `program` (a multiplexer) can't run on `os` (`std::env::consts::OS`), if it can't
+pub fn multiplexer_unsupported(program: &str, os: &str) -> Option<String> {
+    (os == "windows").then(|| {
+        format!(
+            "{} isn't supported on native Windows - run agtx inside WSL, or use --no-tmux to track tasks without agent windows",
+            program
+        )
+    })
+}
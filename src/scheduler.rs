@@ -0,0 +1,149 @@
+//! Global cap on how many agents are working at once.
+//!
+//! Moving a task into Running normally sends the agent its "start implementing" command
+//! right away. With a cap configured, the scheduler decides instead: the command is sent
+//! when a slot is free, otherwise it is queued until a task leaves Running.
+//!
+//! The queue lives here for the session; the app saves each project's part of it to the
+//! project database (`take_changed` says which) and `restore`s it when the project loads.
+
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use crate::tmux::TmuxOperations;
+
+/// A command waiting to be sent to a task's tmux window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDispatch {
+    pub task_id: String,
+    pub project: String,
+    /// tmux target (the task's session/window name)
+    pub target: String,
+    pub command: String,
+}
+
+/// What happened to a submitted dispatch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatchOutcome {
+    /// The command was sent to the agent
+    Sent,
+    /// The cap is reached; the command waits at this 1-based queue position
+    Queued(usize),
+}
+
+/// Owns the decision of when Running tasks get their command
+#[derive(Debug, Default)]
+pub struct DispatchScheduler {
+    /// Maximum agents working at once (0 = unlimited)
+    cap: usize,
+    /// Task id -> project of every task occupying a slot
+    active: HashMap<String, String>,
+    queue: VecDeque<PendingDispatch>,
+    /// Projects whose queued commands changed since the last `take_changed`
+    changed: BTreeSet<String>,
+}
+
+impl DispatchScheduler {
+    pub fn new(cap: usize) -> Self {
+        Self { cap, ..Self::default() }
+    }
+
+    pub fn cap(&self) -> usize {
+        self.cap
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.active.len()
+    }
+
+    pub fn queued_count(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_queued(&self, task_id: &str) -> bool {
+        self.queue.iter().any(|d| d.task_id == task_id)
+    }
+
+    /// The project's queued commands, in dispatch order
+    pub fn queued_for<'a>(&'a self, project: &'a str) -> impl Iterator<Item = &'a PendingDispatch> + 'a {
+        self.queue.iter().filter(move |d| d.project == project)
+    }
+
+    /// Projects whose queued commands changed (and need saving) since the last call
+    pub fn take_changed(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.changed).into_iter().collect()
+    }
+
+    /// Put back a command queued in an earlier session. Tasks the scheduler already knows
+    /// about (queued or holding a slot) are skipped. Call before `sync`, which would
+    /// otherwise count the task as already running.
+    pub fn restore(&mut self, dispatch: PendingDispatch) {
+        if !self.is_queued(&dispatch.task_id) && !self.active.contains_key(&dispatch.task_id) {
+            self.queue.push_back(dispatch);
+        }
+    }
+
+    fn has_free_slot(&self) -> bool {
+        self.cap == 0 || self.active.len() < self.cap
+    }
+
+    /// Change the cap (e.g. after a config reload). Raising it dispatches queued commands.
+    pub fn set_cap(&mut self, cap: usize, tmux: &dyn TmuxOperations) -> Vec<String> {
+        self.cap = cap;
+        self.dispatch_queued(tmux)
+    }
+
+    /// Send the command now if a slot is free, otherwise queue it
+    pub fn submit(&mut self, dispatch: PendingDispatch, tmux: &dyn TmuxOperations) -> Result<DispatchOutcome> {
+        // Re-submitting a task replaces its earlier entry
+        if self.is_queued(&dispatch.task_id) {
+            self.queue.retain(|d| d.task_id != dispatch.task_id);
+            self.changed.insert(dispatch.project.clone());
+        }
+        self.active.remove(&dispatch.task_id);
+
+        if !self.has_free_slot() {
+            self.changed.insert(dispatch.project.clone());
+            self.queue.push_back(dispatch);
+            return Ok(DispatchOutcome::Queued(self.queue.len()));
+        }
+        tmux.send_keys(&dispatch.target, &dispatch.command)?;
+        self.active.insert(dispatch.task_id, dispatch.project);
+        Ok(DispatchOutcome::Sent)
+    }
+
+    /// Reconcile with the tasks of `project` that are currently Running.
+    /// Tasks that left Running free their slot (or leave the queue); Running tasks the
+    /// scheduler didn't dispatch (resumed or started directly) take a slot. Queued commands
+    /// are then sent while slots are free. Returns the ids of tasks dispatched from the queue.
+    pub fn sync(&mut self, project: &str, running: &[&str], tmux: &dyn TmuxOperations) -> Vec<String> {
+        self.active.retain(|id, p| p != project || running.contains(&id.as_str()));
+        let queued = self.queue.len();
+        self.queue.retain(|d| d.project != project || running.contains(&d.task_id.as_str()));
+        if self.queue.len() != queued {
+            self.changed.insert(project.to_string());
+        }
+
+        for id in running {
+            if !self.active.contains_key(*id) && !self.is_queued(id) {
+                self.active.insert(id.to_string(), project.to_string());
+            }
+        }
+
+        self.dispatch_queued(tmux)
+    }
+
+    fn dispatch_queued(&mut self, tmux: &dyn TmuxOperations) -> Vec<String> {
+        let mut dispatched = Vec::new();
+        while self.has_free_slot() {
+            let Some(next) = self.queue.pop_front() else { break };
+            self.changed.insert(next.project.clone());
+            // A window that went away can't take the command; drop it rather than block the queue
+            if tmux.send_keys(&next.target, &next.command).is_ok() {
+                dispatched.push(next.task_id.clone());
+                self.active.insert(next.task_id, next.project);
+            }
+        }
+        dispatched
+    }
+}
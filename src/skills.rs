@@ -2,6 +2,8 @@
 /// Skills follow the Agent Skills spec (SKILL.md with YAML frontmatter + markdown).
 /// Content is loaded from .md files at compile time via include_str!().
 
+use crate::db::Task;
+
 pub const RESEARCH_SKILL: &str = include_str!("../skills/research.md");
 pub const PLAN_SKILL: &str = include_str!("../skills/plan.md");
 pub const EXECUTE_SKILL: &str = include_str!("../skills/execute.md");
@@ -29,6 +31,49 @@ pub const DEFAULT_PROMPT_RUNNING: &str =
     "Plan approved. Implement the changes described in .agtx/plan.md";
 pub const DEFAULT_PROMPT_REVIEW: &str =
     "Implementation complete. Review the changes.";
+/// Sent when a task goes straight from Backlog to Running, skipping the plan
+pub const DEFAULT_PROMPT_RUNNING_DIRECT: &str =
+    "Task: {task}\n\nPlease implement this task directly. No need to plan first - go ahead and make the changes.";
+
+/// Fill a prompt template's placeholders from a task:
+/// `{title}`, `{description}` (empty if none), `{task}` (title, blank line, description),
+/// `{task_id}` and `{worktree}`.
+/// An empty `worktree` leaves `{worktree}` in place, for prompts built before the worktree exists.
+pub fn render_prompt(template: &str, task: &Task, worktree: &str) -> String {
+    let description = task.description.as_deref().unwrap_or("");
+    let task_content = if description.is_empty() {
+        task.title.clone()
+    } else {
+        format!("{}\n\n{}", task.title, description)
+    };
+    let placeholders: [(&str, &str); 5] = [
+        ("{title}", &task.title),
+        ("{description}", description),
+        ("{task}", &task_content),
+        ("{task_id}", &task.id),
+        ("{worktree}", worktree),
+    ];
+
+    // Single pass, so placeholder-like text inside a task's title or description is kept as is
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) if !(*name == "{worktree}" && value.is_empty()) => {
+                rendered.push_str(value);
+                rest = &rest[name.len()..];
+            }
+            _ => {
+                rendered.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
 
 /// Agent-native command/skill directory paths.
 /// Returns (base_dir_relative_to_worktree, namespace_subdir) or None if agent has no native discovery.
@@ -0,0 +1,97 @@
+//! Task slugs (`abc12345-fix-login`): the name of a task's worktree directory, its
+//! `task/<slug>` branch and, through `window_name_template` (default `task-{slug}`), its
+//! window. They have to be valid in all three, so only ASCII letters, digits, `-` and `_`
+//! survive; everything else (spaces, `/`, `:`, `.`, non-ASCII letters) becomes a single `-`.
+
+use anyhow::Result;
+
+use crate::db::Task;
+
+/// Characters of the task id that start the slug
+pub const ID_PREFIX_LEN: usize = 8;
+
+/// Longest title part of a slug, in characters
+pub const TITLE_MAX_LEN: usize = 30;
+
+/// The slug of a task: the start of its id, then its title made safe for git and tmux
+pub fn slugify(id: &str, title: &str) -> String {
+    let id_prefix: String = clean(id).chars().take(ID_PREFIX_LEN).collect();
+    let title: String = clean(title).chars().take(TITLE_MAX_LEN).collect();
+    let title = title.trim_end_matches('-');
+    if title.is_empty() {
+        id_prefix
+    } else {
+        format!("{}-{}", id_prefix, title)
+    }
+}
+
+/// `slugify`, with `-2`, `-3`, ... appended while `exists` says the slug is taken (normally
+/// `GitOperations::worktree_exists`)
+pub fn unique_slug(id: &str, title: &str, exists: impl Fn(&str) -> bool) -> String {
+    with_free_suffix(&slugify(id, title), exists)
+}
+
+/// `base`, or `base-2`, `base-3`, ... : the first one `exists` says is free
+pub fn with_free_suffix(base: &str, exists: impl Fn(&str) -> bool) -> String {
+    if !exists(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !exists(candidate))
+        .expect("some suffix is free")
+}
+
+/// The window name when no `window_name_template` is configured
+pub const DEFAULT_WINDOW_TEMPLATE: &str = "task-{slug}";
+
+/// What a window name template can refer to
+pub const WINDOW_PLACEHOLDERS: [&str; 3] = ["{slug}", "{id}", "{title}"];
+
+/// The name of a task's window: `template` with `{slug}` (the task's slug, as its worktree is
+/// named), `{id}` (the start of its id) and `{title}` filled in, then cleaned like a slug so
+/// tmux never sees `:` or `.` (target separators) or spaces. Kill, attach and reconcile use the
+/// window recorded on the task, so everything that names a new window goes through here.
+pub fn window_name(template: &str, task: &Task, slug: &str) -> String {
+    let id: String = clean(&task.id).chars().take(ID_PREFIX_LEN).collect();
+    let title: String = clean(&task.title).chars().take(TITLE_MAX_LEN).collect();
+    let rendered = template
+        .replace("{slug}", slug)
+        .replace("{id}", &id)
+        .replace("{title}", title.trim_end_matches('-'));
+    let name = clean(&rendered);
+    if name.is_empty() {
+        slug.to_string()
+    } else {
+        name
+    }
+}
+
+/// Reject a window name template that's blank or uses a placeholder `window_name` doesn't know
+pub fn validate_window_template(template: &str) -> Result<()> {
+    if template.trim().is_empty() {
+        anyhow::bail!("window_name_template can't be empty");
+    }
+    let rest = WINDOW_PLACEHOLDERS.iter().fold(template.to_string(), |rest, p| rest.replace(p, ""));
+    if rest.contains('{') || rest.contains('}') {
+        anyhow::bail!(
+            "Unknown placeholder in window_name_template '{}' (use {})",
+            template,
+            WINDOW_PLACEHOLDERS.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Replace runs of anything but ASCII letters, digits and `_` with one `-`, trimmed at both ends
+fn clean(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            out.push(c);
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
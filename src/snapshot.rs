@@ -0,0 +1,192 @@
+//! Named saves of a project's board: every task with its status and fields, but not the
+//! worktrees or windows themselves. Snapshots live in `.agtx/snapshots/<name>.json`.
+//!
+//! Restoring is a diff: `plan_restore` works out which tasks to create, update or trash and
+//! which windows and worktrees no restored task refers to. `App::restore_snapshot` applies the
+//! plan, releasing resources under the same dirty-worktree and `[cleanup.delete]` rules as
+//! deleting a task, then brings back missing worktrees and windows for tasks restored into Running or Review.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::db::{Task, TaskId, TaskStatus};
+use crate::platform;
+
+/// Where snapshots are kept, relative to the project root
+pub const SNAPSHOT_DIR: &str = ".agtx/snapshots";
+
+/// The board of a project at one point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub tasks: Vec<Task>,
+}
+
+impl Snapshot {
+    pub fn new(name: &str, tasks: Vec<Task>) -> Self {
+        Self { name: name.to_string(), created_at: Utc::now(), tasks }
+    }
+}
+
+/// Snapshot names become file names: letters, digits, `-`, `_` and `.`, not starting with `.`
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("Snapshot name can't be empty");
+    }
+    if name.starts_with('.') || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        bail!("Invalid snapshot name '{}': use letters, digits, '-', '_' and '.'", name);
+    }
+    Ok(())
+}
+
+pub fn snapshot_path(project_path: &Path, name: &str) -> PathBuf {
+    project_path.join(platform::native_path(SNAPSHOT_DIR)).join(format!("{}.json", name))
+}
+
+/// Write a snapshot, replacing one with the same name
+pub fn save(project_path: &Path, snapshot: &Snapshot) -> Result<PathBuf> {
+    validate_name(&snapshot.name)?;
+    let path = snapshot_path(project_path, &snapshot.name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let json = serde_json::to_string_pretty(snapshot)?;
+    std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+pub fn load(project_path: &Path, name: &str) -> Result<Snapshot> {
+    validate_name(name)?;
+    let path = snapshot_path(project_path, name);
+    let json = std::fs::read_to_string(&path).with_context(|| format!("No snapshot named '{}'", name))?;
+    serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Names of the project's snapshots, sorted
+pub fn list(project_path: &Path) -> Result<Vec<String>> {
+    let dir = project_path.join(platform::native_path(SNAPSHOT_DIR));
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut names: Vec<String> = std::fs::read_dir(&dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            path.file_stem()?.to_str().map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// What restoring a snapshot changes
+#[derive(Debug, Clone, Default)]
+pub struct RestorePlan {
+    /// In the snapshot but not on the board
+    pub create: Vec<Task>,
+    /// On both, but changed since the snapshot
+    pub update: Vec<Task>,
+    /// On the board but not in the snapshot; these go to the trash
+    pub remove: Vec<TaskId>,
+    /// Current tasks holding a window or worktree no restored task refers to, reduced to just
+    /// those resources (the window or worktree that stays is cleared from the copy, and so is
+    /// a branch a restored task still uses, so `[cleanup.delete] branch` can't delete it)
+    pub release: Vec<Task>,
+}
+
+/// Diff a snapshot against the current tasks. Windows and worktrees stay when a restored task
+/// (other than a Done one) still refers to them; everything else a current task holds goes.
+pub fn plan_restore(snapshot: &[Task], current: &[Task]) -> RestorePlan {
+    let mut plan = RestorePlan::default();
+
+    for task in snapshot {
+        match current.iter().find(|t| t.id == task.id) {
+            None => plan.create.push(task.clone()),
+            Some(now) if !same_task(now, task) => plan.update.push(task.clone()),
+            Some(_) => {}
+        }
+    }
+    plan.remove = current
+        .iter()
+        .filter(|t| !snapshot.iter().any(|s| s.id == t.id))
+        .map(|t| t.id.clone())
+        .collect();
+
+    let kept: Vec<&Task> = snapshot.iter().filter(|t| t.status != TaskStatus::Done).collect();
+    let windows: HashSet<&str> = kept.iter().filter_map(|t| t.session_name.as_deref()).collect();
+    let worktrees: HashSet<&str> = kept.iter().filter_map(|t| t.worktree_path.as_deref()).collect();
+    let branches: HashSet<&str> = snapshot.iter().filter_map(|t| t.branch_name.as_deref()).collect();
+    for task in current {
+        let mut release = task.clone();
+        release.session_name = task.session_name.clone().filter(|w| !windows.contains(w.as_str()));
+        release.worktree_path = task.worktree_path.clone().filter(|w| !worktrees.contains(w.as_str()));
+        release.branch_name = task.branch_name.clone().filter(|b| !branches.contains(b.as_str()));
+        if release.session_name.is_some() || release.worktree_path.is_some() {
+            plan.release.push(release);
+        }
+    }
+    plan
+}
+
+fn same_task(a: &Task, b: &Task) -> bool {
+    serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+}
+
+/// What a restore did, for the report printed afterwards
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestoreSummary {
+    pub tasks_created: usize,
+    pub tasks_updated: usize,
+    /// Tasks moved to the trash because the snapshot doesn't have them
+    pub tasks_removed: usize,
+    pub windows_killed: usize,
+    pub worktrees_removed: usize,
+    /// Windows and worktrees left in place by the dirty-worktree or `[cleanup.delete]` policy,
+    /// and why
+    pub kept: Vec<String>,
+    /// Titles of tasks that got their worktree back
+    pub worktrees_created: Vec<String>,
+    /// Titles of tasks that got a window (and agent) back
+    pub windows_created: Vec<String>,
+    /// Resources that couldn't be brought back or cleaned up, and why
+    pub failures: Vec<String>,
+}
+
+impl RestoreSummary {
+    /// One line per kind of change, leaving out the ones that didn't happen
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let counts = [
+            (self.tasks_created, "task(s) created"),
+            (self.tasks_updated, "task(s) updated"),
+            (self.tasks_removed, "task(s) moved to the trash"),
+            (self.windows_killed, "window(s) closed"),
+            (self.worktrees_removed, "worktree(s) removed"),
+        ];
+        for (count, what) in counts {
+            if count > 0 {
+                lines.push(format!("{} {}", count, what));
+            }
+        }
+        if !self.worktrees_created.is_empty() {
+            lines.push(format!("Worktree recreated: {}", self.worktrees_created.join(", ")));
+        }
+        if !self.windows_created.is_empty() {
+            lines.push(format!("Window recreated: {}", self.windows_created.join(", ")));
+        }
+        lines.extend(self.kept.iter().map(|k| format!("Kept: {}", k)));
+        lines.extend(self.failures.iter().map(|f| format!("Failed: {}", f)));
+        if lines.is_empty() {
+            lines.push("Board already matches the snapshot".to_string());
+        }
+        lines
+    }
+}
@@ -0,0 +1,89 @@
+//! A bounded, rolling copy of what an agent has printed, built from repeated pane captures.
+//!
+//! A capture is the pane as it is now, so consecutive captures mostly repeat each other.
+//! `CaptureBuffer::push_capture` appends only the lines after the overlap with what it already
+//! holds, and the oldest lines are dropped once there are more than `max_lines`. That keeps
+//! memory flat however long an agent runs, and a push costs about one pass over the capture.
+
+use std::collections::VecDeque;
+
+/// Lines kept per task when `max_captured_lines` isn't configured
+pub const DEFAULT_MAX_CAPTURED_LINES: usize = 2000;
+
+/// The most recent `max_lines` lines of a pane, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaptureBuffer {
+    lines: VecDeque<String>,
+    max_lines: usize,
+}
+
+impl CaptureBuffer {
+    pub fn new(max_lines: usize) -> Self {
+        Self { lines: VecDeque::new(), max_lines }
+    }
+
+    pub fn max_lines(&self) -> usize {
+        self.max_lines
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Add one line, dropping the oldest when the buffer is full
+    pub fn push(&mut self, line: impl Into<String>) {
+        self.lines.push_back(line.into());
+        self.truncate(self.max_lines);
+    }
+
+    /// Add a pane capture: lines the buffer already ends with are skipped, and blank lines at
+    /// the bottom of the pane (unused space below the cursor) are ignored
+    pub fn push_capture(&mut self, capture: &str) {
+        let mut new: Vec<&str> = capture.lines().collect();
+        while new.last().is_some_and(|l| l.trim().is_empty()) {
+            new.pop();
+        }
+        let skip = self.overlap(&new);
+        for line in &new[skip..] {
+            self.push(*line);
+        }
+    }
+
+    /// Keep at most `max_lines` lines from now on, dropping the oldest ones
+    pub fn truncate(&mut self, max_lines: usize) {
+        self.max_lines = max_lines;
+        let excess = self.lines.len().saturating_sub(max_lines);
+        self.lines.drain(..excess);
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// The buffered lines joined with newlines
+    pub fn text(&self) -> String {
+        self.lines().collect::<Vec<_>>().join("\n")
+    }
+
+    /// How many leading lines of `capture` the buffer already ends with (the longest such run)
+    fn overlap(&self, capture: &[&str]) -> usize {
+        let Some(last) = self.lines.back() else { return 0 };
+        let longest = capture.len().min(self.lines.len());
+        (1..=longest)
+            .rev()
+            .filter(|&n| capture[n - 1] == last)
+            .find(|&n| {
+                let tail = self.lines.range(self.lines.len() - n..);
+                tail.zip(&capture[..n]).all(|(have, new)| have == new)
+            })
+            .unwrap_or(0)
+    }
+}
@@ -1,16 +1,159 @@
+mod capture;
 mod operations;
+mod zellij;
 
+pub use capture::{CaptureBuffer, DEFAULT_MAX_CAPTURED_LINES};
 pub use operations::*;
+pub use zellij::{split_target, zellij_key_bytes, ZellijOps};
 
 #[cfg(feature = "test-mocks")]
 pub use operations::MockTmuxOperations;
 
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::process::Command;
 
+use crate::config::{is_env_name, MergedConfig, PromptDelivery};
+use crate::db::Task;
+use crate::platform::shell_quote;
+use crate::slug;
+
 /// The tmux server name for agent sessions
 pub const AGENT_SERVER: &str = "agtx";
 
+/// Whether agtx itself runs in a client of the agtx tmux server.
+/// `$TMUX` is `<socket path>,<pid>,<session>`, and the socket is named after the server.
+pub fn inside_agent_server() -> bool {
+    std::env::var("TMUX").is_ok_and(|tmux| is_agent_server_socket(&tmux))
+}
+
+/// Whether a `$TMUX` value points at the agtx server's socket
+pub fn is_agent_server_socket(tmux_env: &str) -> bool {
+    let socket = tmux_env.split(',').next().unwrap_or("");
+    std::path::Path::new(socket).file_name().is_some_and(|name| name == AGENT_SERVER)
+}
+
+/// Append writing the command's exit status to `exit_file`, so it can be read after the
+/// agent exits (`git::read_agent_exit_code`)
+pub fn record_exit_code(command: &str, exit_file: &Path) -> String {
+    format!("{}; echo $? > {}", command, shell_quote(&exit_file.to_string_lossy()))
+}
+
+/// Prefix a window command with `export` lines for `env`, so both the agent and the shell left
+/// behind when it exits see them. Values are shell-quoted; names a shell couldn't export are
+/// skipped (see `config::validate_env`).
+pub fn with_env(command: &str, env: &BTreeMap<String, String>) -> String {
+    let exports: Vec<String> = env
+        .iter()
+        .filter(|(name, _)| is_env_name(name))
+        .map(|(name, value)| format!("export {}={};", name, shell_quote(value)))
+        .collect();
+    if exports.is_empty() {
+        command.to_string()
+    } else {
+        format!("{} {}", exports.join(" "), command)
+    }
+}
+
+/// Stands in for the prompt when an agent command is built for a prompt file
+const PROMPT_PLACEHOLDER: &str = "@agtx-prompt@";
+
+/// How a task window is named and prepared before its agent starts
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSetup {
+    /// Exported ahead of everything else
+    pub env: BTreeMap<String, String>,
+    /// Run once before the agent, in the same shell (e.g. `direnv allow`, `nvm use`)
+    pub startup_command: Option<String>,
+    /// `window_name_template`, rendered by `slug::window_name`
+    pub name_template: String,
+    /// Whether the agent's prompt goes on its command line or through a file
+    pub prompt_delivery: PromptDelivery,
+}
+
+impl Default for WindowSetup {
+    fn default() -> Self {
+        Self {
+            env: BTreeMap::new(),
+            startup_command: None,
+            name_template: slug::DEFAULT_WINDOW_TEMPLATE.to_string(),
+            prompt_delivery: PromptDelivery::default(),
+        }
+    }
+}
+
+impl WindowSetup {
+    /// How `task`'s window is prepared under `config`: its `[env]` with the task's overrides,
+    /// the startup command, the window name template and the prompt delivery
+    pub fn for_task(config: &MergedConfig, task: &Task) -> Self {
+        Self {
+            env: config.env_for(task),
+            startup_command: config.window_startup_command.clone(),
+            name_template: config.window_name_template.clone(),
+            prompt_delivery: config.prompt_delivery,
+        }
+    }
+
+    /// The name of the window for `task` whose worktree is `slug`
+    pub fn window_name(&self, task: &Task, slug: &str) -> String {
+        slug::window_name(&self.name_template, task, slug)
+    }
+
+    /// `window_name`, suffixed while `exists` says a window has that name. A template with
+    /// `{slug}` is unique already (slugs are), so `exists` is only asked for other templates,
+    /// e.g. `{title}` with two tasks of the same title.
+    pub fn unique_window_name(&self, task: &Task, slug: &str, exists: impl Fn(&str) -> bool) -> String {
+        let name = self.window_name(task, slug);
+        if self.name_template.contains("{slug}") {
+            return name;
+        }
+        slug::with_free_suffix(&name, exists)
+    }
+
+    /// The window command for `agent_command`: exports, then the startup command, then the
+    /// agent. With a startup command both run in an interactive `$SHELL`, so functions and
+    /// aliases from the user's rc files (`nvm use`) work; the window itself runs `sh -c`. The
+    /// agent starts even if the startup command fails, and ends up in the foreground once the
+    /// startup command has exited.
+    pub fn wrap(&self, agent_command: &str) -> String {
+        let command = match self.startup_command.as_deref().map(str::trim).filter(|c| !c.is_empty()) {
+            Some(startup) => {
+                format!("\"${{SHELL:-sh}}\" -ic {}", shell_quote(&format!("{}; {}", startup, agent_command)))
+            }
+            None => agent_command.to_string(),
+        };
+        with_env(&command, &self.env)
+    }
+
+    /// The agent command `build` makes for `prompt`. A prompt `prompt_delivery` says is too
+    /// long is written to the worktree's `git::PROMPT_FILE` instead, and the command reads it
+    /// back with `"$(cat …)"` when the window starts, so tmux never sees the text. If the file
+    /// can't be written, or `build` doesn't quote its prompt in single quotes, the prompt goes
+    /// inline after all.
+    pub fn agent_command(&self, prompt: &str, worktree: &Path, build: impl Fn(&str) -> String) -> String {
+        if !self.prompt_delivery.uses_file(prompt) {
+            return build(prompt);
+        }
+        let quoted_placeholder = format!("'{}'", PROMPT_PLACEHOLDER);
+        let command = build(PROMPT_PLACEHOLDER);
+        if !command.contains(&quoted_placeholder) {
+            return build(prompt);
+        }
+        let path = crate::git::prompt_file_path(worktree);
+        let written = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(&path, prompt));
+        if let Err(e) = written {
+            crate::error_log::log("Writing prompt file", &format!("{}: {}", path.display(), e));
+            return build(prompt);
+        }
+        let read_back = format!("\"$(cat {})\"", shell_quote(&path.to_string_lossy()));
+        command.replacen(&quoted_placeholder, &read_back, 1)
+    }
+}
+
 /// Spawn a new agent session in the agents tmux server
 pub fn spawn_session(
     session_name: &str,
@@ -1,36 +1,95 @@
 //! Traits for tmux operations to enable testing with mocks.
 
-use anyhow::Result;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::op_error::{OpError, OpResult};
 
 #[cfg(feature = "test-mocks")]
 use mockall::automock;
 
+/// Most text one `send-keys` carries. Longer text (a long task description sent to a running
+/// agent) goes in pieces, so no tmux command nears its size limit and the agent's input
+/// handling isn't handed one huge paste.
+pub const SEND_KEYS_CHUNK_BYTES: usize = 1024;
+
+/// Pause between the pieces of a long `send_keys`
+const SEND_KEYS_CHUNK_DELAY: Duration = Duration::from_millis(20);
+
+/// `keys` in pieces of at most `max_bytes`, split on char boundaries (a char longer than
+/// `max_bytes` gets a piece of its own)
+pub fn chunk_keys(keys: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = keys;
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// Type `keys` into a window, in `SEND_KEYS_CHUNK_BYTES` pieces when it's long
+fn send_text(target: &str, keys: &str) -> OpResult<()> {
+    let chunks = chunk_keys(keys, SEND_KEYS_CHUNK_BYTES);
+    if let [keys] = chunks.as_slice() {
+        run_tmux(&["send-keys", "-t", target, keys], Some(target))?;
+        return Ok(());
+    }
+    for (i, chunk) in chunks.into_iter().enumerate() {
+        if i > 0 {
+            std::thread::sleep(SEND_KEYS_CHUNK_DELAY);
+        }
+        // Literally, so a piece that happens to read "Enter" or "C-c" is typed, not pressed
+        run_tmux(&["send-keys", "-t", target, "-l", chunk], Some(target))?;
+    }
+    Ok(())
+}
+
 /// Operations for tmux window management
 #[cfg_attr(feature = "test-mocks", automock)]
 pub trait TmuxOperations: Send + Sync {
-    /// Create a new tmux window in a session with an optional command to run
+    /// Create a new tmux window in a session with an optional command to run.
+    /// If the tmux server isn't running, it's started (with `session`) first.
     fn create_window(
         &self,
         session: &str,
         window_name: &str,
         working_dir: &str,
         command: Option<String>,
-    ) -> Result<()>;
+    ) -> OpResult<()>;
 
     /// Kill a tmux window
-    fn kill_window(&self, target: &str) -> Result<()>;
+    fn kill_window(&self, target: &str) -> OpResult<()>;
 
     /// Check if a window exists
-    fn window_exists(&self, target: &str) -> Result<bool>;
+    fn window_exists(&self, target: &str) -> OpResult<bool>;
+
+    /// Names of the windows in a session, in one call; empty when the session doesn't exist
+    fn list_windows(&self, session: &str) -> OpResult<Vec<String>>;
 
     /// Send keys to a window (with Enter at the end)
-    fn send_keys(&self, target: &str, keys: &str) -> Result<()>;
+    fn send_keys(&self, target: &str, keys: &str) -> OpResult<()>;
 
     /// Send keys to a window without pressing Enter
-    fn send_keys_literal(&self, target: &str, keys: &str) -> Result<()>;
+    fn send_keys_literal(&self, target: &str, keys: &str) -> OpResult<()>;
+
+    /// Interrupt whatever runs in a window (Ctrl-C), leaving the window open
+    fn send_interrupt(&self, target: &str) -> OpResult<()>;
 
     /// Capture pane content
-    fn capture_pane(&self, target: &str) -> Result<String>;
+    fn capture_pane(&self, target: &str) -> OpResult<String>;
 
     /// Capture pane content with history (returns raw bytes for ANSI parsing)
     fn capture_pane_with_history(&self, target: &str, history_lines: i32) -> Vec<u8>;
@@ -39,18 +98,61 @@ pub trait TmuxOperations: Send + Sync {
     fn get_cursor_info(&self, target: &str) -> Option<(usize, usize)>;
 
     /// Resize a tmux window
-    fn resize_window(&self, target: &str, width: u16, height: u16) -> Result<()>;
+    fn resize_window(&self, target: &str, width: u16, height: u16) -> OpResult<()>;
 
     /// Check if a session exists
     fn has_session(&self, session: &str) -> bool;
 
     /// Create a new detached session
-    fn create_session(&self, session: &str, working_dir: &str) -> Result<()>;
+    fn create_session(&self, session: &str, working_dir: &str) -> OpResult<()>;
+
+    /// Attach the terminal to a window, blocking until the user detaches.
+    /// From a client already on the agtx server this switches the client instead.
+    fn attach(&self, target: &str) -> OpResult<()>;
+
+    /// Reuse a session if it exists, otherwise create it detached in `working_dir`.
+    /// Creating the first session also starts the tmux server, so this is safe to call anytime.
+    fn ensure_session(&self, session: &str, working_dir: &str) -> OpResult<()> {
+        if !self.has_session(session) {
+            self.create_session(session, working_dir)?;
+        }
+        Ok(())
+    }
 }
 
 /// Real implementation using actual tmux commands
 pub struct RealTmuxOps;
 
+/// Whether tmux's stderr says there is no server to talk to. Depending on the version and
+/// whether a stale socket is left behind, tmux reports either of these.
+pub fn is_server_not_running(stderr: &str) -> bool {
+    stderr.contains("no server running") || stderr.contains("error connecting to")
+}
+
+/// Run `tmux -L agtx <args>`, mapping failures to `OpError`.
+/// With a `target`, tmux's "can't find window/session" (or no server at all) becomes
+/// `WindowNotFound`; without one, a missing server is `ServerNotRunning`.
+fn run_tmux(args: &[&str], target: Option<&str>) -> OpResult<Output> {
+    let output = Command::new("tmux")
+        .args(["-L", super::AGENT_SERVER])
+        .args(args)
+        .output()
+        .map_err(|e| OpError::spawn("tmux", e))?;
+    if output.status.success() {
+        return Ok(output);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    match target {
+        Some(target) if stderr.contains("can't find") || is_server_not_running(&stderr) => {
+            Err(OpError::WindowNotFound { target: target.to_string() })
+        }
+        None if is_server_not_running(&stderr) => {
+            Err(OpError::ServerNotRunning { server: super::AGENT_SERVER.to_string() })
+        }
+        _ => Err(OpError::failed(format!("tmux {}", args.first().copied().unwrap_or_default()), &output)),
+    }
+}
+
 impl TmuxOperations for RealTmuxOps {
     fn create_window(
         &self,
@@ -58,74 +160,77 @@ impl TmuxOperations for RealTmuxOps {
         window_name: &str,
         working_dir: &str,
         command: Option<String>,
-    ) -> Result<()> {
-        let mut cmd = std::process::Command::new("tmux");
-        cmd.args(["-L", super::AGENT_SERVER])
-            .args(["new-window", "-d", "-t", session, "-n", window_name])
-            .args(["-c", working_dir]);
-
-        if let Some(ref shell_cmd) = command {
-            // Wrap command so it drops to a shell after the agent exits
-            let wrapped = format!("{}; exec $SHELL", shell_cmd);
-            cmd.args(["sh", "-c", &wrapped]);
-        }
+    ) -> OpResult<()> {
+        let mut args = vec!["new-window", "-d", "-t", session, "-n", window_name, "-c", working_dir];
 
-        let output = cmd.output()?;
+        // Wrap command so it drops to a shell after the agent exits
+        let wrapped = command.map(|shell_cmd| format!("{}; exec $SHELL", shell_cmd));
+        if let Some(ref wrapped) = wrapped {
+            args.extend(["sh", "-c", wrapped]);
+        }
 
-        if !output.status.success() {
-            anyhow::bail!("Failed to create tmux window");
+        match run_tmux(&args, None) {
+            // First window since the server went away: starting the session starts the server
+            Err(OpError::ServerNotRunning { .. }) => {
+                self.ensure_session(session, working_dir)?;
+                run_tmux(&args, None)?;
+            }
+            result => {
+                result?;
+            }
         }
         Ok(())
     }
 
-    fn kill_window(&self, target: &str) -> Result<()> {
-        std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["kill-window", "-t", target])
-            .output()?;
-        Ok(())
+    fn kill_window(&self, target: &str) -> OpResult<()> {
+        match run_tmux(&["kill-window", "-t", target], Some(target)) {
+            // Already gone is what we wanted
+            Ok(_) | Err(OpError::WindowNotFound { .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
     }
 
-    fn window_exists(&self, target: &str) -> Result<bool> {
-        let output = std::process::Command::new("tmux")
+    fn window_exists(&self, target: &str) -> OpResult<bool> {
+        let output = Command::new("tmux")
             .args(["-L", super::AGENT_SERVER])
             .args(["list-windows", "-t", target])
-            .output()?;
+            .output()
+            .map_err(|e| OpError::spawn("tmux", e))?;
         Ok(output.status.success())
     }
 
-    fn send_keys(&self, target: &str, keys: &str) -> Result<()> {
+    fn list_windows(&self, session: &str) -> OpResult<Vec<String>> {
+        match run_tmux(&["list-windows", "-t", session, "-F", "#{window_name}"], Some(session)) {
+            Ok(output) => Ok(String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect()),
+            Err(OpError::WindowNotFound { .. }) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn send_keys(&self, target: &str, keys: &str) -> OpResult<()> {
         // Send the text first
-        std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["send-keys", "-t", target, keys])
-            .output()?;
+        send_text(target, keys)?;
         // Send Enter separately (like the original implementation)
-        std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["send-keys", "-t", target, "Enter"])
-            .output()?;
+        run_tmux(&["send-keys", "-t", target, "Enter"], Some(target))?;
         Ok(())
     }
 
-    fn send_keys_literal(&self, target: &str, keys: &str) -> Result<()> {
-        std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["send-keys", "-t", target, keys])
-            .output()?;
+    fn send_keys_literal(&self, target: &str, keys: &str) -> OpResult<()> {
+        send_text(target, keys)
+    }
+
+    fn send_interrupt(&self, target: &str) -> OpResult<()> {
+        run_tmux(&["send-keys", "-t", target, "C-c"], Some(target))?;
         Ok(())
     }
 
-    fn capture_pane(&self, target: &str) -> Result<String> {
-        let output = std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["capture-pane", "-t", target, "-p"])
-            .output()?;
+    fn capture_pane(&self, target: &str) -> OpResult<String> {
+        let output = run_tmux(&["capture-pane", "-t", target, "-p"], Some(target))?;
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
     fn capture_pane_with_history(&self, target: &str, history_lines: i32) -> Vec<u8> {
-        std::process::Command::new("tmux")
+        Command::new("tmux")
             .args(["-L", super::AGENT_SERVER])
             .args(["capture-pane", "-t", target, "-p", "-e", "-J"])
             .args(["-S", &format!("-{}", history_lines)])
@@ -135,7 +240,7 @@ impl TmuxOperations for RealTmuxOps {
     }
 
     fn get_cursor_info(&self, target: &str) -> Option<(usize, usize)> {
-        let output = std::process::Command::new("tmux")
+        let output = Command::new("tmux")
             .args(["-L", super::AGENT_SERVER])
             .args(["display", "-p", "-t", target, "#{cursor_y} #{pane_height}"])
             .output()
@@ -153,18 +258,14 @@ impl TmuxOperations for RealTmuxOps {
         None
     }
 
-    fn resize_window(&self, target: &str, width: u16, height: u16) -> Result<()> {
-        std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["resize-window", "-t", target])
-            .args(["-x", &width.to_string()])
-            .args(["-y", &height.to_string()])
-            .output()?;
+    fn resize_window(&self, target: &str, width: u16, height: u16) -> OpResult<()> {
+        let (width, height) = (width.to_string(), height.to_string());
+        run_tmux(&["resize-window", "-t", target, "-x", &width, "-y", &height], Some(target))?;
         Ok(())
     }
 
     fn has_session(&self, session: &str) -> bool {
-        std::process::Command::new("tmux")
+        Command::new("tmux")
             .args(["-L", super::AGENT_SERVER])
             .args(["has-session", "-t", session])
             .output()
@@ -172,12 +273,203 @@ impl TmuxOperations for RealTmuxOps {
             .unwrap_or(false)
     }
 
-    fn create_session(&self, session: &str, working_dir: &str) -> Result<()> {
-        std::process::Command::new("tmux")
-            .args(["-L", super::AGENT_SERVER])
-            .args(["new-session", "-d", "-s", session])
-            .args(["-c", working_dir])
-            .output()?;
+    fn create_session(&self, session: &str, working_dir: &str) -> OpResult<()> {
+        run_tmux(&["new-session", "-d", "-s", session, "-c", working_dir], None)?;
+        Ok(())
+    }
+
+    fn attach(&self, target: &str) -> OpResult<()> {
+        let mut cmd = Command::new("tmux");
+        cmd.args(["-L", super::AGENT_SERVER]);
+        if super::inside_agent_server() {
+            cmd.args(["switch-client", "-t", target]);
+        } else {
+            // Inside another tmux server, TMUX would make this refuse to nest
+            cmd.env_remove("TMUX").args(["attach-session", "-t", target]);
+        }
+
+        let status = cmd.status().map_err(|e| OpError::spawn("tmux", e))?;
+        if !status.success() {
+            return Err(OpError::WindowNotFound { target: target.to_string() });
+        }
+        Ok(())
+    }
+}
+
+/// Stand-in used in no-tmux mode (`--no-tmux` / `no_tmux = true`): tasks still move through
+/// the board and get worktrees, but no agent windows are created. Window-changing calls succeed
+/// without doing anything; window lookups report `TmuxDisabled`, which callers treat like any
+/// other tmux error (e.g. no "window missing" badge).
+pub struct NoTmuxOps;
+
+impl TmuxOperations for NoTmuxOps {
+    fn create_window(
+        &self,
+        _session: &str,
+        _window_name: &str,
+        _working_dir: &str,
+        _command: Option<String>,
+    ) -> OpResult<()> {
+        Ok(())
+    }
+
+    fn kill_window(&self, _target: &str) -> OpResult<()> {
+        Ok(())
+    }
+
+    fn window_exists(&self, _target: &str) -> OpResult<bool> {
+        Err(OpError::TmuxDisabled)
+    }
+
+    fn list_windows(&self, _session: &str) -> OpResult<Vec<String>> {
+        Err(OpError::TmuxDisabled)
+    }
+
+    fn send_keys(&self, _target: &str, _keys: &str) -> OpResult<()> {
+        Ok(())
+    }
+
+    fn send_keys_literal(&self, _target: &str, _keys: &str) -> OpResult<()> {
         Ok(())
     }
+
+    fn send_interrupt(&self, _target: &str) -> OpResult<()> {
+        Err(OpError::TmuxDisabled)
+    }
+
+    fn capture_pane(&self, _target: &str) -> OpResult<String> {
+        Err(OpError::TmuxDisabled)
+    }
+
+    fn capture_pane_with_history(&self, _target: &str, _history_lines: i32) -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn get_cursor_info(&self, _target: &str) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn resize_window(&self, _target: &str, _width: u16, _height: u16) -> OpResult<()> {
+        Ok(())
+    }
+
+    fn has_session(&self, _session: &str) -> bool {
+        true
+    }
+
+    fn create_session(&self, _session: &str, _working_dir: &str) -> OpResult<()> {
+        Ok(())
+    }
+
+    fn attach(&self, _target: &str) -> OpResult<()> {
+        Err(OpError::TmuxDisabled)
+    }
+}
+
+/// Enforces a minimum interval between sends. Shared by every caller, so bursts of dispatches
+/// across tasks are spaced out; a zero interval never waits.
+#[derive(Debug, Default)]
+pub struct SendKeysGate {
+    min_interval: Duration,
+    last_sent: Mutex<Option<Instant>>,
+}
+
+impl SendKeysGate {
+    pub fn new(min_interval: Duration) -> Self {
+        Self { min_interval, last_sent: Mutex::new(None) }
+    }
+
+    /// Block until `min_interval` has passed since the previous send, then record this one.
+    /// The lock is held while waiting, so concurrent senders go one at a time.
+    pub fn wait(&self) {
+        if self.min_interval.is_zero() {
+            return;
+        }
+        let mut last_sent = self.last_sent.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(last) = *last_sent {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                std::thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        *last_sent = Some(Instant::now());
+    }
+}
+
+/// Wraps another backend so `send_keys` calls (prompts and commands sent to agents) respect a
+/// `SendKeysGate`. Single keystrokes forwarded with `send_keys_literal` aren't delayed.
+pub struct RateLimitedTmuxOps {
+    inner: Arc<dyn TmuxOperations>,
+    gate: SendKeysGate,
+}
+
+impl RateLimitedTmuxOps {
+    pub fn new(inner: Arc<dyn TmuxOperations>, min_interval: Duration) -> Self {
+        Self { inner, gate: SendKeysGate::new(min_interval) }
+    }
+}
+
+impl TmuxOperations for RateLimitedTmuxOps {
+    fn create_window(
+        &self,
+        session: &str,
+        window_name: &str,
+        working_dir: &str,
+        command: Option<String>,
+    ) -> OpResult<()> {
+        self.inner.create_window(session, window_name, working_dir, command)
+    }
+
+    fn kill_window(&self, target: &str) -> OpResult<()> {
+        self.inner.kill_window(target)
+    }
+
+    fn window_exists(&self, target: &str) -> OpResult<bool> {
+        self.inner.window_exists(target)
+    }
+
+    fn list_windows(&self, session: &str) -> OpResult<Vec<String>> {
+        self.inner.list_windows(session)
+    }
+
+    fn send_keys(&self, target: &str, keys: &str) -> OpResult<()> {
+        self.gate.wait();
+        self.inner.send_keys(target, keys)
+    }
+
+    fn send_keys_literal(&self, target: &str, keys: &str) -> OpResult<()> {
+        self.inner.send_keys_literal(target, keys)
+    }
+
+    fn send_interrupt(&self, target: &str) -> OpResult<()> {
+        self.inner.send_interrupt(target)
+    }
+
+    fn capture_pane(&self, target: &str) -> OpResult<String> {
+        self.inner.capture_pane(target)
+    }
+
+    fn capture_pane_with_history(&self, target: &str, history_lines: i32) -> Vec<u8> {
+        self.inner.capture_pane_with_history(target, history_lines)
+    }
+
+    fn get_cursor_info(&self, target: &str) -> Option<(usize, usize)> {
+        self.inner.get_cursor_info(target)
+    }
+
+    fn resize_window(&self, target: &str, width: u16, height: u16) -> OpResult<()> {
+        self.inner.resize_window(target, width, height)
+    }
+
+    fn has_session(&self, session: &str) -> bool {
+        self.inner.has_session(session)
+    }
+
+    fn create_session(&self, session: &str, working_dir: &str) -> OpResult<()> {
+        self.inner.create_session(session, working_dir)
+    }
+
+    fn attach(&self, target: &str) -> OpResult<()> {
+        self.inner.attach(target)
+    }
 }
@@ -0,0 +1,259 @@
+//! Zellij backend for `TmuxOperations` (`multiplexer = "zellij"`).
+//!
+//! Task windows map onto zellij like this:
+//!
+//! | agtx / tmux            | zellij                                              |
+//! |------------------------|-----------------------------------------------------|
+//! | session                | session (`zellij attach --create-background`)       |
+//! | window `session:name`  | tab named `name` in that session                    |
+//! | `send-keys text Enter` | `go-to-tab-name`, `write-chars text`, `write 13`    |
+//! | named keys (`Escape`)  | `write` with the key's bytes (see `zellij_key_bytes`) |
+//! | `capture-pane`         | `dump-screen` of the tab's focused pane (no colors) |
+//! | `kill-window`          | `go-to-tab-name`, `close-tab`                       |
+//!
+//! zellij actions apply to the focused tab, so every targeted call first switches to the tab.
+//! That moves the view of anyone attached to the session, and there is no cursor query or
+//! per-tab resize; those calls return `None`/do nothing.
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use crate::op_error::{OpError, OpResult};
+
+use super::TmuxOperations;
+
+/// Zellij implementation of the multiplexer operations
+pub struct ZellijOps;
+
+/// Split a `session:window` target into the zellij session and tab name
+pub fn split_target(target: &str) -> (&str, &str) {
+    target.split_once(':').unwrap_or((target, ""))
+}
+
+/// Bytes zellij should `write` for a tmux key name (as produced for `send_keys_literal`), or
+/// `None` for plain text that is typed with `write-chars`
+pub fn zellij_key_bytes(key: &str) -> Option<Vec<u8>> {
+    let bytes: &[u8] = match key {
+        "Enter" => b"\r",
+        "Escape" => b"\x1b",
+        "BSpace" => b"\x7f",
+        "Tab" => b"\t",
+        "Up" => b"\x1b[A",
+        "Down" => b"\x1b[B",
+        "Right" => b"\x1b[C",
+        "Left" => b"\x1b[D",
+        "Home" => b"\x1b[H",
+        "End" => b"\x1b[F",
+        "PageUp" => b"\x1b[5~",
+        "PageDown" => b"\x1b[6~",
+        "DC" => b"\x1b[3~",
+        "IC" => b"\x1b[2~",
+        "F1" => b"\x1bOP",
+        "F2" => b"\x1bOQ",
+        "F3" => b"\x1bOR",
+        "F4" => b"\x1bOS",
+        "F5" => b"\x1b[15~",
+        "F6" => b"\x1b[17~",
+        "F7" => b"\x1b[18~",
+        "F8" => b"\x1b[19~",
+        "F9" => b"\x1b[20~",
+        "F10" => b"\x1b[21~",
+        "F11" => b"\x1b[23~",
+        "F12" => b"\x1b[24~",
+        _ => return None,
+    };
+    Some(bytes.to_vec())
+}
+
+/// Run `zellij [--session <session>] <args>`, mapping failures to `OpError`.
+/// With a `target`, a missing session or tab becomes `WindowNotFound`.
+fn run_zellij(session: Option<&str>, args: &[&str], target: Option<&str>) -> OpResult<Output> {
+    let mut cmd = Command::new("zellij");
+    if let Some(session) = session {
+        cmd.args(["--session", session]);
+    }
+    let output = cmd.args(args).output().map_err(|e| OpError::spawn("zellij", e))?;
+    if output.status.success() {
+        return Ok(output);
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+    match target {
+        Some(target) if stderr.contains("not found") || stderr.contains("no active session") => {
+            Err(OpError::WindowNotFound { target: target.to_string() })
+        }
+        _ => Err(OpError::failed(format!("zellij {}", args.join(" ")), &output)),
+    }
+}
+
+/// Names of the tabs in a session, or `WindowNotFound` if the session is gone
+fn tab_names(session: &str, target: &str) -> OpResult<Vec<String>> {
+    let output = run_zellij(Some(session), &["action", "query-tab-names"], Some(target))?;
+    Ok(String::from_utf8_lossy(&output.stdout).lines().map(|l| l.trim().to_string()).collect())
+}
+
+/// Focus the tab a target names so the following action applies to it
+fn focus_tab(target: &str) -> OpResult<&str> {
+    let (session, tab) = split_target(target);
+    run_zellij(Some(session), &["action", "go-to-tab-name", tab], Some(target))?;
+    Ok(session)
+}
+
+/// Dump the focused pane of a target's tab (`--full` includes the scrollback)
+fn dump_screen(target: &str, full: bool) -> OpResult<String> {
+    let session = focus_tab(target)?;
+    let path: PathBuf = std::env::temp_dir().join(format!("agtx-zellij-{}.txt", std::process::id()));
+    let path_str = path.to_string_lossy().to_string();
+    let mut args = vec!["action", "dump-screen", path_str.as_str()];
+    if full {
+        args.push("--full");
+    }
+    run_zellij(Some(session), &args, Some(target))?;
+    let content = std::fs::read_to_string(&path).map_err(|e| OpError::Io {
+        context: format!("Failed to read zellij screen dump {}", path.display()),
+        source: e,
+    });
+    let _ = std::fs::remove_file(&path);
+    content
+}
+
+impl TmuxOperations for ZellijOps {
+    fn create_window(
+        &self,
+        session: &str,
+        window_name: &str,
+        working_dir: &str,
+        command: Option<String>,
+    ) -> OpResult<()> {
+        self.ensure_session(session, working_dir)?;
+        run_zellij(
+            Some(session),
+            &["action", "new-tab", "--name", window_name, "--cwd", working_dir],
+            None,
+        )?;
+        // A new tab starts the user's shell; type the command into it. The shell stays
+        // behind when the agent exits, like tmux's `; exec $SHELL` wrapper.
+        if let Some(command) = command {
+            self.send_keys(&format!("{}:{}", session, window_name), &command)?;
+        }
+        Ok(())
+    }
+
+    fn kill_window(&self, target: &str) -> OpResult<()> {
+        let session = match focus_tab(target) {
+            Ok(session) => session,
+            // Already gone is what we wanted
+            Err(OpError::WindowNotFound { .. }) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        run_zellij(Some(session), &["action", "close-tab"], Some(target))?;
+        Ok(())
+    }
+
+    fn window_exists(&self, target: &str) -> OpResult<bool> {
+        let (session, tab) = split_target(target);
+        if !self.has_session(session) {
+            return Ok(false);
+        }
+        match tab_names(session, target) {
+            Ok(names) => Ok(names.iter().any(|name| name == tab)),
+            Err(OpError::WindowNotFound { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn list_windows(&self, session: &str) -> OpResult<Vec<String>> {
+        if !self.has_session(session) {
+            return Ok(Vec::new());
+        }
+        match tab_names(session, session) {
+            Ok(names) => Ok(names),
+            Err(OpError::WindowNotFound { .. }) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn send_keys(&self, target: &str, keys: &str) -> OpResult<()> {
+        let session = focus_tab(target)?;
+        run_zellij(Some(session), &["action", "write-chars", keys], Some(target))?;
+        run_zellij(Some(session), &["action", "write", "13"], Some(target))?;
+        Ok(())
+    }
+
+    fn send_keys_literal(&self, target: &str, keys: &str) -> OpResult<()> {
+        let session = focus_tab(target)?;
+        match zellij_key_bytes(keys) {
+            Some(bytes) => {
+                let bytes: Vec<String> = bytes.iter().map(|b| b.to_string()).collect();
+                let mut args = vec!["action", "write"];
+                args.extend(bytes.iter().map(String::as_str));
+                run_zellij(Some(session), &args, Some(target))?;
+            }
+            None => {
+                run_zellij(Some(session), &["action", "write-chars", keys], Some(target))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn send_interrupt(&self, target: &str) -> OpResult<()> {
+        let session = focus_tab(target)?;
+        // ETX, what Ctrl-C sends
+        run_zellij(Some(session), &["action", "write", "3"], Some(target))?;
+        Ok(())
+    }
+
+    fn capture_pane(&self, target: &str) -> OpResult<String> {
+        dump_screen(target, false)
+    }
+
+    fn capture_pane_with_history(&self, target: &str, history_lines: i32) -> Vec<u8> {
+        let Ok(content) = dump_screen(target, true) else {
+            return Vec::new();
+        };
+        let lines: Vec<&str> = content.lines().collect();
+        let keep = usize::try_from(history_lines).unwrap_or(0).min(lines.len());
+        lines[lines.len() - keep..].join("\n").into_bytes()
+    }
+
+    fn get_cursor_info(&self, _target: &str) -> Option<(usize, usize)> {
+        // zellij has no cursor query for another pane
+        None
+    }
+
+    fn resize_window(&self, _target: &str, _width: u16, _height: u16) -> OpResult<()> {
+        // Tabs take the size of the attached client; there's nothing to resize
+        Ok(())
+    }
+
+    fn has_session(&self, session: &str) -> bool {
+        run_zellij(None, &["list-sessions", "--short", "--no-formatting"], None)
+            .map(|o| String::from_utf8_lossy(&o.stdout).lines().any(|l| l.trim() == session))
+            .unwrap_or(false)
+    }
+
+    fn create_session(&self, session: &str, working_dir: &str) -> OpResult<()> {
+        let output = Command::new("zellij")
+            .args(["attach", "--create-background", session])
+            .current_dir(working_dir)
+            .output()
+            .map_err(|e| OpError::spawn("zellij", e))?;
+        if !output.status.success() {
+            return Err(OpError::failed("zellij attach --create-background", &output));
+        }
+        Ok(())
+    }
+
+    fn attach(&self, target: &str) -> OpResult<()> {
+        let session = focus_tab(target)?;
+        // Inside zellij, ZELLIJ would make this refuse to nest
+        let status = Command::new("zellij")
+            .env_remove("ZELLIJ")
+            .args(["attach", session])
+            .status()
+            .map_err(|e| OpError::spawn("zellij", e))?;
+        if !status.success() {
+            return Err(OpError::WindowNotFound { target: target.to_string() });
+        }
+        Ok(())
+    }
+}
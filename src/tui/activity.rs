@@ -0,0 +1,133 @@
+//! Operations in flight, shown in the footer ("⠋ Creating worktree…") until they finish, so
+//! the board never looks hung while git or an agent is busy.
+//!
+//! Anything can report one: `ActivityReporter::start` returns a guard that reports the
+//! operation finished when it's dropped, whether that's on the main thread after a synchronous
+//! call or in a background thread. The main loop picks up the events with `Activities::drain`.
+//! Work that holds the main thread reports through `start_blocking`: nothing redraws until it's
+//! done, so its line is shown without a spinner that would only sit frozen.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Instant;
+
+/// Braille spinner, advanced once per refresh (`spinner_frame`)
+pub const SPINNER_FRAMES: &[&str] =
+    &["\u{280b}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283c}", "\u{2834}", "\u{2826}", "\u{2827}", "\u{2807}", "\u{280f}"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityEvent {
+    /// Id, label, and whether the footer animates while it runs
+    Started(u64, String, bool),
+    Finished(u64),
+}
+
+/// Hands out activity guards; cheap to clone into a background thread
+#[derive(Debug, Clone)]
+pub struct ActivityReporter {
+    tx: mpsc::Sender<ActivityEvent>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl ActivityReporter {
+    /// Report `label` as in flight until the returned guard is dropped
+    pub fn start(&self, label: impl Into<String>) -> ActivityGuard {
+        self.report(label.into(), true)
+    }
+
+    /// Like `start`, for work that blocks the thread drawing the UI
+    pub fn start_blocking(&self, label: impl Into<String>) -> ActivityGuard {
+        self.report(label.into(), false)
+    }
+
+    fn report(&self, label: String, animated: bool) -> ActivityGuard {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let _ = self.tx.send(ActivityEvent::Started(id, label, animated));
+        ActivityGuard { id, tx: self.tx.clone() }
+    }
+}
+
+/// One operation in flight; reports it finished on drop (including on an early return or panic)
+#[derive(Debug)]
+pub struct ActivityGuard {
+    id: u64,
+    tx: mpsc::Sender<ActivityEvent>,
+}
+
+impl Drop for ActivityGuard {
+    fn drop(&mut self) {
+        let _ = self.tx.send(ActivityEvent::Finished(self.id));
+    }
+}
+
+/// The operations in flight, oldest first, as last drained from the reporters
+#[derive(Debug)]
+pub struct Activities {
+    rx: mpsc::Receiver<ActivityEvent>,
+    reporter: ActivityReporter,
+    running: Vec<Running>,
+}
+
+#[derive(Debug)]
+struct Running {
+    id: u64,
+    label: String,
+    animated: bool,
+    started: Instant,
+}
+
+impl Default for Activities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Activities {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            rx,
+            reporter: ActivityReporter { tx, next_id: Arc::new(AtomicU64::new(0)) },
+            running: Vec::new(),
+        }
+    }
+
+    pub fn reporter(&self) -> ActivityReporter {
+        self.reporter.clone()
+    }
+
+    /// Apply the start/finish events reported since the last call
+    pub fn drain(&mut self) {
+        while let Ok(event) = self.rx.try_recv() {
+            match event {
+                ActivityEvent::Started(id, label, animated) => {
+                    self.running.push(Running { id, label, animated, started: Instant::now() })
+                }
+                ActivityEvent::Finished(id) => self.running.retain(|running| running.id != id),
+            }
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.running.is_empty()
+    }
+
+    /// Footer line for the oldest operation in flight, with how many others are running
+    pub fn status_line(&self, spinner_frame: usize) -> Option<String> {
+        let oldest = self.running.first()?;
+        let mut line = if oldest.animated {
+            let spinner = SPINNER_FRAMES[spinner_frame % SPINNER_FRAMES.len()];
+            format!("{} {}\u{2026}", spinner, oldest.label)
+        } else {
+            format!("{}\u{2026}", oldest.label)
+        };
+        let secs = oldest.started.elapsed().as_secs();
+        if oldest.animated && secs > 0 {
+            line.push_str(&format!(" ({}s)", secs));
+        }
+        if self.running.len() > 1 {
+            line.push_str(&format!(" (+{} more)", self.running.len() - 1));
+        }
+        Some(line)
+    }
+}
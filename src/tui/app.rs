@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossterm::{
     event::{self, Event, KeyCode, KeyEventKind},
     execute,
@@ -6,22 +6,42 @@ use crossterm::{
 };
 use ratatui::{prelude::*, widgets::*};
 use std::collections::{HashMap, HashSet};
-use std::io::{self, Stdout};
+use std::io::{self, BufRead, BufReader, Stdout, Write};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
 use std::time::Instant;
 
+use crate::adopt;
 use crate::agent::{self, AgentOperations};
-use crate::config::{GlobalConfig, MergedConfig, ProjectConfig, ThemeConfig, WorkflowPlugin};
-use crate::db::{Database, PhaseStatus, Task, TaskStatus};
-use crate::git::{self, GitOperations, GitProviderOperations, PullRequestState, RealGitHubOps, RealGitOps};
+use crate::api;
+use crate::config::{self, CleanupAction, CleanupPolicy, DirtyWorktreePolicy, GlobalConfig, HooksConfig, MergedConfig, Multiplexer, ProjectConfig, PromptTemplates, Template, Theme, ThemeConfig, WebhookConfig, WorkflowPlugin};
+use crate::db::{self, AgentUsage, Database, PhaseStatus, QueuedCommand, Task, TaskEvent, TaskEventKind, TaskStatus};
+use crate::scheduler::{DispatchOutcome, DispatchScheduler, PendingDispatch};
+use crate::git::{self, CommitSummary, GitOperations, GitProviderOperations, PullRequestState, RealGitHubOps};
+use crate::error_log;
+use crate::hooks::{self, HookRunner, ShellHookRunner};
+use crate::import;
+use crate::op_error::{OpError, OpResult};
+use crate::platform;
+use crate::slug;
+use crate::webhook;
+use crate::workflow;
+use crate::snapshot;
 use crate::skills;
-use crate::tmux::{self, RealTmuxOps, TmuxOperations};
+use crate::tmux::{record_exit_code, CaptureBuffer, NoTmuxOps, RateLimitedTmuxOps, RealTmuxOps, TmuxOperations, WindowSetup, ZellijOps};
 use crate::AppMode;
 
-use super::board::BoardState;
+use super::activity::{Activities, ActivityGuard, SPINNER_FRAMES};
+use super::board::{self, BoardState, BoardStats, NavDirection, Staleness};
+use super::keymap::{Action, KeyMap};
 use super::input::InputMode;
+use super::search::{self, fuzzy_score, MatchField, SearchMatch, SearchQuery};
 use super::shell_popup::{self, ShellPopup};
+use super::task_form::{self, FieldError, FormField, TaskForm};
+use super::ui_state::{self, UiState};
 
 /// Helper to convert hex color string to ratatui Color
 fn hex_to_color(hex: &str) -> Color {
@@ -38,15 +58,16 @@ fn build_footer_text(input_mode: InputMode, sidebar_focused: bool, selected_colu
                 " [j/k] navigate  [Enter] open  [l] board  [e] hide sidebar  [q] quit ".to_string()
             } else {
                 match selected_column {
-                    0 => " [o] new  [/] search  [Enter] open  [x] del  [d] diff  [R] research  [m] plan  [M] run  [e] sidebar  [q] quit".to_string(),
-                    1 => " [o] new  [/] search  [Enter] open  [x] del  [d] diff  [m] run  [e] sidebar  [q] quit".to_string(),
-                    2 | 3 => " [o] new  [/] search  [Enter] open  [x] del  [d] diff  [m] move  [r] move left  [e] sidebar  [q] quit".to_string(),
-                    _ => " [o] new  [/] search  [Enter] open  [x] del  [e] sidebar  [q] quit".to_string(),
+                    0 => " [o] new  [/] search  [Enter] open  [i] details  [x] del  [d] diff  [R] research  [m] plan  [M] run  [B] deps  [E] edit  [Space] select  [T] trash  [e] sidebar  [?] help  [q] quit".to_string(),
+                    1 => " [o] new  [/] search  [Enter] open  [i] details  [a] attach  [x] del  [d] diff  [m] run  [B] deps  [E] edit  [Space] select  [e] sidebar  [?] help  [q] quit".to_string(),
+                    2 | 3 => " [o] new  [/] search  [Enter] open  [i] details  [a] attach  [x] del  [d] diff  [D] branch diff  [m] move  [r] move left  [A] resume all  [E] edit  [Space] select  [e] sidebar  [?] help  [q] quit".to_string(),
+                    _ => " [o] new  [/] search  [Enter] open  [i] details  [x] del  [z] archive  [Z] archive all  [v] show archived  [T] trash  [e] sidebar  [?] help  [q] quit".to_string(),
                 }
             }
         }
         InputMode::InputTitle => " Enter task title... [Esc] cancel [Enter] next ".to_string(),
         InputMode::InputDescription => " Enter prompt for agent... [#] file search [Esc] cancel [\\+Enter] newline [Enter] save ".to_string(),
+        InputMode::NewTask => " New task... [Tab] next field [Enter] create [Esc] cancel ".to_string(),
     }
 }
 
@@ -61,6 +82,11 @@ const SHELL_POPUP_HEIGHT_PERCENT: u16 = 75; // Percentage of terminal height
 struct AppState {
     mode: AppMode,
     should_quit: bool,
+    /// Set by SIGINT (or Ctrl-C on the board): stop after the current operation
+    interrupted: Arc<AtomicBool>,
+    /// Hands-off mode: polling, reconciliation and queued dispatches stop until it's cleared.
+    /// Manual actions work as usual. Kept for the session only.
+    automation_paused: Arc<AtomicBool>,
     board: BoardState,
     input_mode: InputMode,
     input_buffer: String,
@@ -83,6 +109,8 @@ struct AppState {
     git_provider_ops: Arc<dyn GitProviderOperations>,
     // Agent registry (injectable for testing)
     agent_registry: Arc<dyn agent::AgentRegistry>,
+    // Runs `[hooks]` commands on status transitions (injectable for testing)
+    hook_runner: Arc<dyn HookRunner>,
     // Sidebar
     sidebar_visible: bool,
     sidebar_focused: bool,
@@ -118,12 +146,96 @@ struct AppState {
     review_confirm_popup: Option<ReviewConfirmPopup>,
     // Phase detection
     phase_status_cache: HashMap<String, (PhaseStatus, Instant)>,
+    // Task id -> (worktree has uncommitted changes, when it was checked)
+    worktree_status_cache: HashMap<String, (bool, Instant)>,
     spinner_frame: usize,
+    // Operations in flight (worktree creation, review tests), shown in the footer
+    activities: Activities,
     cached_plugin: Option<Option<WorkflowPlugin>>,
     // Transient warning message shown in footer (auto-clears after a few seconds)
     warning_message: Option<(String, Instant)>,
+    // Last failed operation, shown above the footer until dismissed with Esc
+    error_bar: Option<ErrorReport>,
     // Plugin selection popup
     plugin_select_popup: Option<PluginSelectPopup>,
+    // Board stats summary popup
+    stats_popup: Option<StatsPopup>,
+    help_popup: Option<HelpPopup>,
+    // Resolved keybindings for remappable board actions
+    keymap: KeyMap,
+    // Project picker popup
+    project_picker: Option<ProjectPickerPopup>,
+    // Why tasks can't be created in this project (not a git repo / no commits), if anything
+    repo_problem: Option<String>,
+    // Set at startup when `git --version` fails; blocks task creation like repo_problem
+    git_problem: Option<String>,
+    // Project's default branch when base_branch isn't configured (detected once per project)
+    detected_base_branch: Option<String>,
+    // Template picker shown before the new-task modal
+    template_picker: Option<TemplatePickerPopup>,
+    // Template chosen for the task being created (None = plain task)
+    pending_template: Option<Template>,
+    // New-task modal (input_mode is NewTask while it's open)
+    new_task_popup: Option<NewTaskPopup>,
+    // Blocked-by editor for the selected task
+    dependency_popup: Option<DependencyPopup>,
+    // Title/agent editor for the selected task
+    edit_task_popup: Option<EditTaskPopup>,
+    // Trash view (deleted tasks that can be restored or purged)
+    trash_popup: Option<TrashPopup>,
+    // Confirmation (with optional follow-up prompt) for resuming every Review task
+    resume_all_popup: Option<ResumeAllPopup>,
+    abort_popup: Option<AbortPopup>,
+    // Confirmation (with the reason to record) for moving a task straight to Done
+    abandon_popup: Option<AbandonPopup>,
+    // Decides when Running tasks get their command (max_concurrent_agents)
+    scheduler: DispatchScheduler,
+    // Running/Review tasks whose window or worktree is gone (found on startup/project switch)
+    resource_mismatches: HashMap<String, MissingResource>,
+    // Startup list of those tasks, with suggested fixes
+    reconcile_popup: Option<ReconcilePopup>,
+    // Live window/worktree presence of Running/Review tasks, for the card badges (task_id -> (presence, checked_at))
+    resource_presence_cache: HashMap<String, (ResourcePresence, Instant)>,
+    // Tasks whose `test_command` run (started on entering Review) hasn't finished
+    review_tests_running: HashSet<String>,
+    // Finished review test runs: (project path, task id, passed)
+    review_test_tx: mpsc::Sender<(PathBuf, String, bool)>,
+    review_test_rx: mpsc::Receiver<(PathBuf, String, bool)>,
+    // Task whose review tests start once its PR thread has saved it in Review
+    review_tests_after_pr: Option<String>,
+    // Newest task event already written to stdout by `agtx serve --events` (None = not streaming)
+    event_cursor: Option<i64>,
+    // Task detail view with notes
+    task_detail_popup: Option<TaskDetailPopup>,
+    // Worktrees created ahead of a bulk Backlog → Planning move, by task id
+    prepared_worktrees: HashMap<String, String>,
+    // Tasks whose worktree is being created in the background before they leave Backlog
+    worktrees_in_flight: HashSet<String>,
+    // Finished background worktrees: (project path, task id, worktree path)
+    worktree_tx: mpsc::Sender<(PathBuf, String, crate::op_error::OpResult<String>)>,
+    worktree_rx: mpsc::Receiver<(PathBuf, String, crate::op_error::OpResult<String>)>,
+    // Last time agent exit code files were read
+    agent_exit_checked_at: Instant,
+    // Last time agent windows were read for token usage
+    agent_usage_checked_at: Instant,
+    // Recent output of each active task's agent (at most max_captured_lines), by task id
+    captured_output: HashMap<String, CaptureBuffer>,
+}
+
+impl AppState {
+    /// tmux session task windows are created in (configured, or the project name)
+    fn tmux_session(&self) -> String {
+        self.config.tmux_session_for(&self.project_name)
+    }
+
+    /// Agent a task runs with: its own, or the project default for tasks saved without one
+    fn agent_for(&self, task: &Task) -> String {
+        if task.agent.is_empty() {
+            self.config.default_agent.clone()
+        } else {
+            task.agent.clone()
+        }
+    }
 }
 
 /// State for confirming move to Done
@@ -132,6 +244,8 @@ struct DoneConfirmPopup {
     task_id: String,
     pr_number: i32,
     pr_state: DoneConfirmPrState,
+    // What cleanup will destroy (window target / worktree path)
+    destroys: String,
 }
 
 #[derive(Debug, Clone)]
@@ -140,6 +254,7 @@ enum DoneConfirmPrState {
     Merged,
     Closed,
     Unknown,
+    NoPr,
 }
 
 /// State for PR creation status popup (loading/success/error)
@@ -170,7 +285,7 @@ struct DiffPopup {
 #[derive(Debug, Clone)]
 struct TaskSearchState {
     query: String,
-    matches: Vec<(String, String, TaskStatus)>, // (id, title, status)
+    matches: Vec<SearchMatch>,
     selected: usize,
 }
 
@@ -206,6 +321,7 @@ struct FileSearchState {
 struct DeleteConfirmPopup {
     task_id: String,
     task_title: String,
+    destroys: String,
 }
 
 /// State for asking if user wants to create PR when moving to Review
@@ -230,20 +346,182 @@ struct PluginOption {
     active: bool,        // Currently active for this project
 }
 
+/// State for the project picker popup
+#[derive(Debug, Clone)]
+struct ProjectPickerPopup {
+    query: String,
+    selected: usize, // Index into the filtered list
+}
+
+/// State for the task template picker popup
+#[derive(Debug, Clone)]
+struct TemplatePickerPopup {
+    selected: usize, // 0 = no template, otherwise index into config.templates + 1
+}
+
+/// State for editing which tasks the selected task depends on
+#[derive(Debug, Clone)]
+struct DependencyPopup {
+    task_id: String,
+    task_title: String,
+    candidates: Vec<String>, // Ids of the other (non-archived) tasks in the project
+    selected: usize,
+}
+
+/// State for the new-task modal. The focused text field is edited in `input_buffer` (so the
+/// prompt gets the file search); the other fields are kept in `form`.
+#[derive(Debug, Clone)]
+struct NewTaskPopup {
+    form: TaskForm,
+    focus: FormField,
+    errors: Vec<FieldError>, // From the last submit attempt
+}
+
+/// State for editing the selected task's title and agent in place
+#[derive(Debug, Clone)]
+struct EditTaskPopup {
+    task_id: String,
+    title: String,
+    agents: Vec<String>, // Available agents, plus the task's current one
+    agent_index: usize,
+    agent_editable: bool, // Only before the task's agent has been given work
+    editing_agent: bool,  // Focus: false = title, true = agent
+}
+
+/// State for the trash view
+#[derive(Debug, Clone)]
+struct TrashPopup {
+    tasks: Vec<Task>, // Trashed tasks, most recently deleted first
+    selected: usize,
+}
+
+/// State for resuming every Review task at once
+#[derive(Debug, Clone)]
+struct ResumeAllPopup {
+    count: usize,      // Review tasks that will be resumed
+    follow_up: String, // Optional prompt sent to each resumed task's window
+}
+
+/// State for interrupting a task's agent (`abort` action)
+#[derive(Debug, Clone)]
+struct AbortPopup {
+    task_id: String,
+    title: String,
+    follow_up: String, // Optional prompt sent once the agent has stopped
+}
+
+/// State for moving a task straight to Done (`abandon` action)
+#[derive(Debug, Clone)]
+struct AbandonPopup {
+    task_id: String,
+    title: String,
+    status: TaskStatus,
+    // What cleanup will destroy (window target / worktree path)
+    destroys: String,
+    reason: String, // Recorded in the task's history; required
+}
+
+/// State for the task detail view
+#[derive(Debug, Clone)]
+struct TaskDetailPopup {
+    task: Task,                          // Reloaded after each note change
+    events: Vec<TaskEvent>,              // History, oldest first
+    commits: Option<Vec<CommitSummary>>, // Latest on the worktree branch; None without a worktree
+    note_input: Option<String>,          // Some while a new note is being typed
+    highlight: Option<String>,           // Search text to highlight in the description and notes
+}
+
+/// What a Running/Review task is missing compared to what the board records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MissingResource {
+    Window,
+    Worktree,
+    WindowAndWorktree,
+}
+
+impl MissingResource {
+    /// Short card badge
+    fn badge(&self) -> &'static str {
+        match self {
+            MissingResource::Window => "⚠ no window",
+            MissingResource::Worktree => "⚠ no worktree",
+            MissingResource::WindowAndWorktree => "⚠ no window/worktree",
+        }
+    }
+
+    /// What the user can do about it; nothing is changed for them
+    fn suggestion(&self) -> &'static str {
+        match self {
+            MissingResource::Window => "tmux window was closed; the worktree is intact - move the task to Done to clean up, or reopen a window in the worktree",
+            MissingResource::Worktree => "worktree was deleted; the agent's window has no files - move the task to Done or delete it",
+            MissingResource::WindowAndWorktree => "window and worktree are both gone - move the task to Done or delete it",
+        }
+    }
+}
+
+/// State for the startup list of tasks whose resources are missing
+#[derive(Debug, Clone)]
+struct ReconcilePopup {
+    entries: Vec<(String, String, TaskStatus, MissingResource)>, // (id, title, status, mismatch)
+    selected: usize,
+}
+
+/// State for the board stats summary popup
+#[derive(Debug, Clone)]
+struct StatsPopup {
+    stats: BoardStats,
+    live_windows: usize,
+}
+
+/// The `?` key overlay, built from the keymap each time it's drawn
+#[derive(Debug, Default)]
+struct HelpPopup {
+    scroll: usize,
+    /// Furthest it can scroll, worked out when drawn (depends on the terminal height)
+    max_scroll: std::cell::Cell<usize>,
+}
+
 pub struct App {
-    terminal: Terminal,
+    /// `None` when running headless (`agtx serve`)
+    terminal: Option<Terminal>,
     state: AppState,
 }
 
 impl App {
-    pub fn new(mode: AppMode) -> Result<Self> {
-        Self::with_ops(
+    /// Task windows go to the configured `multiplexer`; with `no_tmux`, tasks get worktrees
+    /// but no agent windows (see `NoTmuxOps`)
+    pub fn new(mode: AppMode, no_tmux: bool) -> Result<Self> {
+        Self::with_real_ops(mode, no_tmux, true)
+    }
+
+    /// An app without a terminal, driven through `serve` instead of keys
+    pub fn headless(mode: AppMode, no_tmux: bool) -> Result<Self> {
+        Self::with_real_ops(mode, no_tmux, false)
+    }
+
+    fn with_real_ops(mode: AppMode, no_tmux: bool, interactive: bool) -> Result<Self> {
+        // Config errors are reported by with_ops; fall back to the defaults here
+        let global_config = GlobalConfig::load().unwrap_or_default();
+        let git_ops = global_config.git_ops();
+        let unsupported = global_config.multiplexer.unsupported_reason().filter(|_| !no_tmux);
+        let tmux_ops: Arc<dyn TmuxOperations> = match global_config.multiplexer {
+            _ if no_tmux || unsupported.is_some() => Arc::new(NoTmuxOps),
+            Multiplexer::Tmux => Arc::new(RealTmuxOps),
+            Multiplexer::Zellij => Arc::new(ZellijOps),
+        };
+        let mut app = Self::build(
             mode,
-            Arc::new(RealTmuxOps),
-            Arc::new(RealGitOps),
+            tmux_ops,
+            Arc::new(git_ops),
             Arc::new(RealGitHubOps),
             Arc::new(agent::RealAgentRegistry::new("claude")),
-        )
+            Arc::new(ShellHookRunner),
+            interactive,
+        )?;
+        if let Some(reason) = unsupported {
+            app.state.warning_message = Some((reason, Instant::now()));
+        }
+        Ok(app)
     }
 
     pub fn with_ops(
@@ -252,16 +530,53 @@ impl App {
         git_ops: Arc<dyn GitOperations>,
         git_provider_ops: Arc<dyn GitProviderOperations>,
         agent_registry: Arc<dyn agent::AgentRegistry>,
+        hook_runner: Arc<dyn HookRunner>,
     ) -> Result<Self> {
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen)?;
-        let backend = CrosstermBackend::new(stdout);
-        let terminal = Terminal::new(backend)?;
+        Self::build(mode, tmux_ops, git_ops, git_provider_ops, agent_registry, hook_runner, true)
+    }
 
-        // Load configs
+    fn build(
+        mode: AppMode,
+        tmux_ops: Arc<dyn TmuxOperations>,
+        git_ops: Arc<dyn GitOperations>,
+        git_provider_ops: Arc<dyn GitProviderOperations>,
+        agent_registry: Arc<dyn agent::AgentRegistry>,
+        hook_runner: Arc<dyn HookRunner>,
+        interactive: bool,
+    ) -> Result<Self> {
+        // Load configs (before terminal setup so config errors print normally)
         let global_config = GlobalConfig::load().unwrap_or_default();
+        let keymap = KeyMap::from_config(&global_config.keybindings)
+            .context("Invalid [keybindings] in global config")?;
+        Theme::resolve(&global_config.theme).context("Invalid [theme] in global config")?;
+        global_config.hooks.validate().context("Invalid [hooks] in global config")?;
+        config::validate_env(&global_config.env).context("Invalid [env] in global config")?;
+        config::validate_columns(&global_config.columns).context("Invalid [columns] in global config")?;
+        global_config.webhook.validate().context("Invalid [webhook] in global config")?;
+        global_config.cleanup.validate().context("Invalid [cleanup] in global config")?;
+        for template in &global_config.templates {
+            config::validate_env(&template.env)
+                .with_context(|| format!("Invalid env in template '{}'", template.name))?;
+        }
+        for view in &global_config.views {
+            view.filter.validate().with_context(|| format!("Invalid filter in view '{}'", view.name))?;
+        }
+        // Space out what's sent to agents when a burst of dispatches happens at once
+        let tmux_ops: Arc<dyn TmuxOperations> = match global_config.send_keys_interval_ms {
+            0 => tmux_ops,
+            ms => Arc::new(RateLimitedTmuxOps::new(tmux_ops, std::time::Duration::from_millis(ms))),
+        };
+
+        // Setup terminal
+        let terminal = if interactive {
+            enable_raw_mode()?;
+            let mut stdout = io::stdout();
+            execute!(stdout, EnterAlternateScreen)?;
+            Some(Terminal::new(CrosstermBackend::new(stdout))?)
+        } else {
+            None
+        };
+
         let global_db = Database::open_global()?;
 
         // Detect available agents
@@ -284,20 +599,29 @@ impl App {
                 let project = crate::db::Project::new(&name, canonical.to_string_lossy());
                 global_db.upsert_project(&project)?;
 
-                // Ensure tmux session exists for this project
-                ensure_project_tmux_session(&name, &canonical, tmux_ops.as_ref());
-
                 (Some(db), Some(canonical), name, project_config)
             }
         };
 
+        project_config.hooks.validate().context("Invalid [hooks] in project config")?;
         let config = MergedConfig::merge(&global_config, &project_config);
+        slug::validate_window_template(&config.window_name_template).context("Invalid window_name_template")?;
+
+        // Ensure the tmux session task windows go into exists (reused if already running)
+        if let Some(path) = &project_path {
+            let session = config.tmux_session_for(&project_name);
+            let _ = ensure_project_tmux_session(&session, path, tmux_ops.as_ref());
+        }
 
+        let (review_test_tx, review_test_rx) = mpsc::channel();
+        let (worktree_tx, worktree_rx) = mpsc::channel();
         let mut app = Self {
             terminal,
             state: AppState {
                 mode,
                 should_quit: false,
+                interrupted: Arc::new(AtomicBool::new(false)),
+                automation_paused: Arc::new(AtomicBool::new(false)),
                 board: BoardState::new(),
                 input_mode: InputMode::Normal,
                 input_buffer: String::new(),
@@ -314,6 +638,7 @@ impl App {
                 git_ops,
                 git_provider_ops,
                 agent_registry,
+                hook_runner,
                 sidebar_visible: true,
                 sidebar_focused: false,
                 projects: vec![],
@@ -333,10 +658,46 @@ impl App {
                 delete_confirm_popup: None,
                 review_confirm_popup: None,
                 phase_status_cache: HashMap::new(),
+                worktree_status_cache: HashMap::new(),
                 spinner_frame: 0,
+                activities: Activities::new(),
                 cached_plugin: None,
                 warning_message: None,
+                error_bar: None,
                 plugin_select_popup: None,
+                stats_popup: None,
+                help_popup: None,
+                keymap,
+                project_picker: None,
+                repo_problem: None,
+                git_problem: git_problem(&git::probe_git()),
+                detected_base_branch: None,
+                template_picker: None,
+                pending_template: None,
+                new_task_popup: None,
+                dependency_popup: None,
+                edit_task_popup: None,
+                trash_popup: None,
+                resume_all_popup: None,
+                abort_popup: None,
+                abandon_popup: None,
+                scheduler: DispatchScheduler::new(global_config.max_concurrent_agents),
+                resource_mismatches: HashMap::new(),
+                reconcile_popup: None,
+                resource_presence_cache: HashMap::new(),
+                review_tests_running: HashSet::new(),
+                review_test_tx,
+                review_test_rx,
+                review_tests_after_pr: None,
+                event_cursor: None,
+                task_detail_popup: None,
+                prepared_worktrees: HashMap::new(),
+                worktrees_in_flight: HashSet::new(),
+                worktree_tx,
+                worktree_rx,
+                agent_exit_checked_at: Instant::now(),
+                agent_usage_checked_at: Instant::now(),
+                captured_output: HashMap::new(),
             },
         };
 
@@ -346,8 +707,14 @@ impl App {
             app.state.project_path.as_deref(),
         ));
 
+        // Check the repo up front so task creation fails clearly instead of mid-workflow
+        app.check_repo();
+
         // Load tasks if in project mode
+        app.restore_dispatch_queue();
         app.refresh_tasks()?;
+        app.restore_ui_state();
+        app.reconcile_resources();
         // Load projects from global database
         app.refresh_projects()?;
 
@@ -355,7 +722,14 @@ impl App {
     }
 
     pub async fn run(&mut self) -> Result<()> {
+        watch_for_interrupt(Arc::clone(&self.state.interrupted));
         while !self.state.should_quit {
+            // A transition in progress has finished (or rolled back) by the time we get here
+            if self.state.interrupted.load(Ordering::SeqCst) {
+                self.state.should_quit = true;
+                break;
+            }
+            self.state.activities.drain();
             self.draw()?;
 
             // Check for PR generation completion
@@ -373,15 +747,29 @@ impl App {
             // Check for PR creation completion
             if let Some(ref rx) = self.state.pr_creation_rx {
                 if let Ok(result) = rx.try_recv() {
+                    let tests_task = self.state.review_tests_after_pr.take();
                     match result {
-                        Ok((_, pr_url)) => {
+                        Ok((pr_number, pr_url)) => {
+                            // pr_number is 0 when changes were pushed to an existing PR
+                            let pr_url_for_event = if pr_number == 0 {
+                                format!("pushed changes to {}", pr_url)
+                            } else {
+                                pr_url.clone()
+                            };
                             self.state.pr_status_popup = Some(PrStatusPopup {
                                 status: PrCreationStatus::Success,
                                 pr_url: Some(pr_url),
                                 error_message: None,
                             });
+                            if let Some(task_id) = tests_task {
+                                self.log_task_event(&task_id, TaskEventKind::PrOpened, pr_url_for_event);
+                                self.start_review_tests(&task_id)?;
+                            }
                         }
                         Err(err) => {
+                            if let Some(task_id) = tests_task {
+                                self.log_task_event(&task_id, TaskEventKind::Failed, err.clone());
+                            }
                             self.state.pr_status_popup = Some(PrStatusPopup {
                                 status: PrCreationStatus::Error,
                                 pr_url: None,
@@ -394,10 +782,32 @@ impl App {
                 }
             }
 
+            // Finish Backlog moves whose worktree was created in the background
+            while let Ok((project_path, task_id, result)) = self.state.worktree_rx.try_recv() {
+                self.finish_background_move(&project_path, &task_id, result)?;
+            }
+
+            // Record finished review test runs
+            while let Ok((project_path, task_id, passed)) = self.state.review_test_rx.try_recv() {
+                if let Err(e) = self.record_review_tests(&project_path, &task_id, passed) {
+                    self.report_error(&e);
+                }
+            }
+
             if event::poll(std::time::Duration::from_millis(100))? {
                 if let Event::Key(key) = event::read()? {
                     if key.kind == KeyEventKind::Press {
-                        self.handle_key(key)?;
+                        // Raw mode turns Ctrl-C into a key press instead of SIGINT (the shell
+                        // popup is the agent's terminal, so there it's left alone)
+                        if is_interrupt_key(&key) && self.state.shell_popup.is_none() {
+                            self.state.interrupted.store(true, Ordering::SeqCst);
+                            continue;
+                        }
+                        // A failed git/tmux operation is reported, not fatal; the board stays usable
+                        if let Err(e) = self.handle_key(key) {
+                            self.report_error(&e);
+                            self.refresh_tasks()?;
+                        }
                     }
                 }
             }
@@ -407,8 +817,14 @@ impl App {
                 popup.cached_content = capture_tmux_pane_with_history(&popup.window_name, 500, self.state.tmux_ops.as_ref());
             }
 
-            // Periodically refresh session status
-            self.refresh_sessions()?;
+            // Periodically refresh session status (not while automation is paused)
+            if !self.state.automation_paused.load(Ordering::SeqCst) {
+                self.refresh_sessions()?;
+                self.refresh_worktree_status();
+                self.refresh_resource_presence();
+                self.refresh_agent_exit_codes();
+                self.refresh_agent_usage();
+            }
 
             // Clear expired warning messages
             if let Some((_, created)) = &self.state.warning_message {
@@ -418,12 +834,43 @@ impl App {
             }
         }
 
+        self.finish_pr_creation();
+        self.save_ui_state();
         Ok(())
     }
 
+    /// On the way out, wait for a PR being created in the background: its thread saves the
+    /// task's PR fields and status, and exiting under it would leave the task in Running
+    /// with a PR open. The terminal is still in raw mode here, so the wait is shown in the footer.
+    fn finish_pr_creation(&mut self) {
+        let Some(rx) = self.state.pr_creation_rx.take() else { return };
+        let _activity = self.begin_activity("Waiting for the pull request to be created before exiting");
+        if rx.recv_timeout(SHUTDOWN_PR_WAIT).is_err() {
+            error_log::log("Shutting down", "Gave up waiting for pull request creation");
+        }
+    }
+
+    /// Remember the selection and scroll of the current project's board (best effort)
+    fn save_ui_state(&self) {
+        let Some(project_path) = &self.state.project_path else { return };
+        let saved = ui_state::path_for(project_path).and_then(|path| UiState::capture(&self.state.board).save(&path));
+        if let Err(e) = saved {
+            error_log::log("Saving board position", &format!("{:#}", e));
+        }
+    }
+
+    /// Put the board back where it was left in this project. Falls back to the top of the
+    /// board when there's nothing saved or the file can't be read.
+    fn restore_ui_state(&mut self) {
+        let Some(project_path) = &self.state.project_path else { return };
+        let state = ui_state::path_for(project_path).and_then(|path| UiState::load(&path)).unwrap_or_default();
+        state.apply(&mut self.state.board);
+    }
+
     fn draw(&mut self) -> Result<()> {
         let state = &self.state;
-        self.terminal.draw(|frame| {
+        let Some(terminal) = self.terminal.as_mut() else { return Ok(()) };
+        terminal.draw(|frame| {
             let area = frame.area();
 
             match &state.mode {
@@ -464,51 +911,66 @@ impl App {
             main_chunks[0]
         };
 
-        // Main layout: header, board, footer
+        // Main layout: header, board, error bar (only while there is an error), footer
+        let error_bar_height = if state.error_bar.is_some() { 1 } else { 0 };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(3), // Header
-                Constraint::Min(0),    // Board
-                Constraint::Length(3), // Footer
+                Constraint::Length(3),                // Header
+                Constraint::Min(0),                   // Board
+                Constraint::Length(error_bar_height), // Error bar
+                Constraint::Length(3),                // Footer
             ])
             .split(content_area);
 
         // Header
         let plugin_label = state.config.workflow_plugin.as_deref().unwrap_or("agtx");
         let left = Span::styled(format!(" {} ", state.project_name), Style::default().fg(Color::Cyan).bold());
+        let mut view_label = state.board.view.as_ref().map(|v| format!("[view: {}] ", v.name)).unwrap_or_default();
+        if let Some(agent) = &state.board.agent_filter {
+            view_label.push_str(&format!("[agent: {}] ", agent));
+        }
+        if state.config.fast_mode {
+            view_label.push_str(FAST_MODE_LABEL);
+        }
+        let paused_label = if state.automation_paused.load(Ordering::SeqCst) { AUTOMATION_PAUSED_LABEL } else { "" };
         let right_spans: Vec<Span> = vec![
             Span::styled(format!("{} ", plugin_label), Style::default().fg(hex_to_color(&state.config.theme.color_accent))),
             Span::styled("[P] ", Style::default().fg(hex_to_color(&state.config.theme.color_dimmed))),
             Span::styled("Plugins ", Style::default().fg(hex_to_color(&state.config.theme.color_dimmed))),
         ];
-        let left_len = state.project_name.len() + 2;
+        let left_len = state.project_name.len() + 2 + view_label.chars().count() + paused_label.chars().count();
         let right_len: usize = right_spans.iter().map(|s| s.content.len()).sum();
         let padding = (chunks[0].width as usize).saturating_sub(left_len + right_len + 2); // 2 for borders
-        let mut spans = vec![left, Span::raw(" ".repeat(padding))];
+        let mut spans = vec![
+            left,
+            Span::styled(view_label, Style::default().fg(hex_to_color(&state.config.theme.color_accent))),
+            Span::styled(paused_label, Style::default().fg(Color::Black).bg(Color::Yellow).bold()),
+            Span::raw(" ".repeat(padding)),
+        ];
         spans.extend(right_spans);
         let header = Paragraph::new(Line::from(spans))
             .block(Block::default().borders(Borders::ALL));
         frame.render_widget(header, chunks[0]);
 
-        // Board columns (5 columns: Backlog, Planning, Running, Review, Done)
+        // Board columns (5 columns: Backlog, Planning, Running, Review, Done); collapsed ones are thin bars
+        let now = chrono::Utc::now();
+        let collapsed = state.board.collapsed_flags();
         let columns = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
-            ])
+            .constraints(board::column_widths(chunks[1].width, &collapsed, state.board.selected_column).into_iter().map(Constraint::Length))
             .split(chunks[1]);
 
         for (i, status) in TaskStatus::columns().iter().enumerate() {
-            let tasks: Vec<&Task> = state.board.tasks.iter().filter(|t| t.status == *status).collect();
+            let tasks: Vec<&Task> = state.board.tasks_in_column(i);
 
             let is_selected_column = state.board.selected_column == i;
 
-            let title = format!(" {} ({}) ", status.as_str(), tasks.len());
+            let title = if *status == TaskStatus::Done && state.board.show_archived {
+                format!(" {} ({}, incl. archived) ", status.as_str(), tasks.len())
+            } else {
+                format!(" {} ({}) ", status.as_str(), tasks.len())
+            };
             let (border_style, title_style) = if is_selected_column {
                 (
                     Style::default().fg(hex_to_color(&state.config.theme.color_selected)),
@@ -517,25 +979,30 @@ impl App {
             } else {
                 (
                     Style::default().fg(hex_to_color(&state.config.theme.color_normal)),
-                    Style::default().fg(hex_to_color(&state.config.theme.color_column_header)),
+                    Style::default().fg(hex_to_color(state.config.theme.status_color(*status))),
                 )
             };
 
+            if collapsed[i] {
+                // Count on top, then the column name running down the bar
+                let mut lines = vec![Line::from(Span::styled(tasks.len().to_string(), title_style)), Line::raw("")];
+                lines.extend(status.as_str().chars().map(|c| Line::from(Span::styled(c.to_string(), title_style))));
+                let bar = Paragraph::new(lines)
+                    .alignment(ratatui::layout::Alignment::Center)
+                    .block(Block::default().borders(Borders::ALL).border_style(border_style));
+                frame.render_widget(bar, columns[i]);
+                continue;
+            }
+
             // Calculate card height (title + preview lines + borders)
             let card_height: u16 = 10; // 1 title + 7 preview lines + 2 borders
             let max_visible_cards = (columns[i].height.saturating_sub(2) / card_height) as usize;
 
-            // Calculate scroll offset to keep selected task visible
-            let scroll_offset = if is_selected_column && tasks.len() > max_visible_cards {
-                let selected = state.board.selected_row;
-                if selected >= max_visible_cards {
-                    selected - max_visible_cards + 1
-                } else {
-                    0
-                }
-            } else {
-                0
-            };
+            // Keep the column where it was scrolled, moving only to keep the selected task visible
+            let selected = is_selected_column.then_some(state.board.selected_row);
+            let scroll_offset =
+                board::scroll_offset(state.board.scroll_offset(i), selected, max_visible_cards, tasks.len());
+            state.board.set_scroll_offset(i, scroll_offset);
 
             // Check if we need a scrollbar
             let needs_scrollbar = tasks.len() > max_visible_cards;
@@ -571,7 +1038,23 @@ impl App {
                     break;
                 }
 
-                Self::draw_task_card(frame, task, card_area, is_selected, &state.config.theme, state.phase_status_cache.get(&task.id), state.spinner_frame);
+                let exit_badge = agent_exit_badge(task);
+                let badge = if matches!(task.status, TaskStatus::Backlog | TaskStatus::Planning)
+                    && db::is_blocked(task, &state.board.tasks)
+                {
+                    Some("🔒 blocked")
+                } else if task.status == TaskStatus::Running && state.scheduler.is_queued(&task.id) {
+                    Some("⏳ queued")
+                } else {
+                    state.resource_mismatches.get(&task.id).map(|m| m.badge())
+                        .or(exit_badge.as_deref())
+                        .or_else(|| review_test_badge(task, state.review_tests_running.contains(&task.id)))
+                };
+                let is_marked = state.board.marked.contains(&task.id);
+                let worktree_dirty = state.worktree_status_cache.get(&task.id).map(|(dirty, _)| *dirty);
+                let presence = state.resource_presence_cache.get(&task.id).map(|(presence, _)| *presence);
+                let staleness = board::staleness(task, now, &state.config.staleness);
+                Self::draw_task_card(frame, task, card_area, is_selected, is_marked, staleness, badge, worktree_dirty, presence, &state.config.theme, state.phase_status_cache.get(&task.id), state.spinner_frame);
             }
 
             // Draw scrollbar if needed
@@ -608,8 +1091,10 @@ impl App {
             }
         }
 
-        // Footer with help (or transient warning)
-        let (footer_text, footer_style) = if let Some((ref msg, created)) = state.warning_message {
+        // Footer with help (or an operation in flight, or a transient warning)
+        let (footer_text, footer_style) = if let Some(line) = state.activities.status_line(state.spinner_frame) {
+            (line, Style::default().fg(hex_to_color(&state.config.theme.color_working)))
+        } else if let Some((ref msg, created)) = state.warning_message {
             if created.elapsed() < std::time::Duration::from_secs(5) {
                 (msg.clone(), Style::default().fg(Color::Yellow))
             } else {
@@ -621,44 +1106,36 @@ impl App {
              Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)))
         };
 
+        if let Some(ref report) = state.error_bar {
+            let error_line = Line::from(vec![
+                Span::styled(format!(" \u{2717} {}: ", report.action), Style::default().fg(Color::White).bg(Color::Red).bold()),
+                Span::styled(format!("{} ", report.message), Style::default().fg(Color::White).bg(Color::Red)),
+                Span::styled(" [Esc] dismiss ", Style::default().fg(hex_to_color(&state.config.theme.color_dimmed))),
+            ]);
+            frame.render_widget(Paragraph::new(error_line), chunks[2]);
+        }
+
         let footer = Paragraph::new(footer_text.as_str())
             .style(footer_style)
             .block(Block::default().borders(Borders::ALL));
-        frame.render_widget(footer, chunks[2]);
+        frame.render_widget(footer, chunks[3]);
 
-        // Input overlay if in input mode
+        // Title/prompt input when editing a Backlog task
         if state.input_mode == InputMode::InputTitle || state.input_mode == InputMode::InputDescription {
             let input_area = centered_rect(50, 40, area);
             frame.render_widget(Clear, input_area);
 
-            let is_editing = state.editing_task_id.is_some();
-            let (title, label) = match state.input_mode {
-                InputMode::InputTitle => {
-                    if is_editing { (" Edit Task ", "Title: ") } else { (" New Task ", "Title: ") }
-                }
-                InputMode::InputDescription => {
-                    if is_editing { (" Edit Task ", "Prompt: ") } else { (" New Task ", "Prompt: ") }
-                }
-                _ => ("", ""),
-            };
-
-            // Show title if we're on description step
             // Insert cursor (█) at the correct position
             let (before_cursor, after_cursor) = state.input_buffer.split_at(
                 state.input_cursor.min(state.input_buffer.len())
             );
             let text_color = hex_to_color(&state.config.theme.color_text);
             let highlight_color = hex_to_color(&state.config.theme.color_accent);
+            // Show title if we're on description step
             let full_text = if state.input_mode == InputMode::InputDescription {
-                format!(
-                    "Title: {}\n\n{}{}█{}",
-                    state.pending_task_title,
-                    label,
-                    before_cursor,
-                    after_cursor
-                )
+                format!("Title: {}\n\nPrompt: {}█{}", state.pending_task_title, before_cursor, after_cursor)
             } else {
-                format!("{}{}█{}", label, before_cursor, after_cursor)
+                format!("Title: {}█{}", before_cursor, after_cursor)
             };
 
             let styled_text = if state.input_mode == InputMode::InputDescription && !state.highlighted_file_paths.is_empty() {
@@ -672,58 +1149,25 @@ impl App {
                 .wrap(Wrap { trim: false })
                 .block(
                     Block::default()
-                        .title(title)
+                        .title(" Edit Task ")
                         .borders(Borders::ALL)
                         .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_selected))),
                 );
             frame.render_widget(input, input_area);
 
-            // File search dropdown
             if let Some(ref search) = state.file_search {
-                if !search.matches.is_empty() {
-                    let dropdown_height = (search.matches.len() as u16 + 2).min(12);
-                    let dropdown_area = Rect {
-                        x: input_area.x + 2,
-                        y: input_area.y + input_area.height,
-                        width: input_area.width.saturating_sub(4),
-                        height: dropdown_height,
-                    };
-
-                    // Make sure dropdown doesn't go off screen
-                    let dropdown_area = if dropdown_area.y + dropdown_area.height > area.height {
-                        Rect {
-                            y: input_area.y.saturating_sub(dropdown_height),
-                            ..dropdown_area
-                        }
-                    } else {
-                        dropdown_area
-                    };
-
-                    frame.render_widget(Clear, dropdown_area);
+                Self::draw_file_search_dropdown(frame, search, input_area, area, &state.config.theme);
+            }
+        }
 
-                    let file_selected_color = hex_to_color(&state.config.theme.color_selected);
-                    let items: Vec<ListItem> = search.matches
-                        .iter()
-                        .enumerate()
-                        .map(|(i, path)| {
-                            let style = if i == search.selected {
-                                Style::default().bg(file_selected_color).fg(Color::Black)
-                            } else {
-                                Style::default().fg(Color::White)
-                            };
-                            ListItem::new(format!(" {} ", path)).style(style)
-                        })
-                        .collect();
+        // New-task modal
+        if let (InputMode::NewTask, Some(popup)) = (state.input_mode, &state.new_task_popup) {
+            let input_area = centered_rect(60, 60, area);
+            frame.render_widget(Clear, input_area);
+            Self::draw_new_task_popup(frame, state, popup, input_area);
 
-                    let list = List::new(items)
-                        .block(
-                            Block::default()
-                                .title(" Files [↑↓] select [Tab/Enter] insert [Esc] cancel ")
-                                .borders(Borders::ALL)
-                                .border_style(Style::default().fg(Color::Cyan)),
-                        );
-                    frame.render_widget(list, dropdown_area);
-                }
+            if let Some(ref search) = state.file_search {
+                Self::draw_file_search_dropdown(frame, search, input_area, area, &state.config.theme);
             }
         }
 
@@ -748,11 +1192,16 @@ impl App {
             let selected_color = hex_to_color(&state.config.theme.color_selected);
 
             // Search input
+            let title = if SearchQuery::parse(&search.query).everywhere {
+                " Search Tasks: titles, descriptions, notes "
+            } else {
+                " Search Tasks (+ to include descriptions and notes) "
+            };
             let input = Paragraph::new(format!(" 🔍 {}█", search.query))
                 .style(Style::default().fg(selected_color))
                 .block(
                     Block::default()
-                        .title(" Search Tasks ")
+                        .title(title)
                         .borders(Borders::ALL)
                         .border_style(Style::default().fg(selected_color)),
                 );
@@ -762,7 +1211,7 @@ impl App {
             let items: Vec<ListItem> = search.matches
                 .iter()
                 .enumerate()
-                .map(|(i, (_, title, status))| {
+                .map(|(i, found)| {
                     let is_selected = i == search.selected;
                     let style = if is_selected {
                         Style::default().bg(selected_color).fg(Color::Black)
@@ -770,15 +1219,20 @@ impl App {
                         Style::default().fg(Color::White)
                     };
 
-                    let status_icon = match status {
+                    let status_icon = match found.status {
                         TaskStatus::Backlog => "📋",
                         TaskStatus::Planning => "📝",
                         TaskStatus::Running => "⚡",
                         TaskStatus::Review => "👀",
                         TaskStatus::Done => "✅",
                     };
+                    // Say where the match was when it wasn't the title
+                    let field = match found.field {
+                        MatchField::Title => String::new(),
+                        field => format!(" [{}]", field.label()),
+                    };
 
-                    ListItem::new(format!(" {} {} ", status_icon, title)).style(style)
+                    ListItem::new(format!(" {} {}{} ", status_icon, found.title, field)).style(style)
                 })
                 .collect();
 
@@ -813,7 +1267,9 @@ impl App {
                     .as_millis() / 100) as usize % spinner_chars.len();
                 let spinner = spinner_chars[spinner_idx];
 
-                let agent_name = state.config.default_agent.clone();
+                let agent_name = state.board.tasks.iter()
+                    .find(|t| t.id == popup.task_id)
+                    .map_or_else(|| state.config.default_agent.clone(), |t| state.agent_for(t));
                 let loading_text = format!("{} Generating PR description with {}...", spinner, agent_name);
                 let loading = Paragraph::new(loading_text)
                     .style(Style::default().fg(Color::Cyan))
@@ -983,20 +1439,24 @@ impl App {
             let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 2 });
             let text = match popup.pr_state {
                 DoneConfirmPrState::Open => format!(
-                    "PR #{} is still open.\n\nAre you sure you want to move this task to Done?\n\nWorktree will be deleted, tmux coding session killed.\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
-                    popup.pr_number
+                    "PR #{} is still open.\n\nAre you sure you want to move this task to Done?\n\n{}\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
+                    popup.pr_number, popup.destroys
                 ),
                 DoneConfirmPrState::Merged => format!(
-                    "PR #{} was merged.\n\nWorktree will be deleted, tmux coding session killed.\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
-                    popup.pr_number
+                    "PR #{} was merged.\n\n{}\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
+                    popup.pr_number, popup.destroys
                 ),
                 DoneConfirmPrState::Closed => format!(
-                    "PR #{} was closed.\n\nWorktree will be deleted, tmux coding session killed.\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
-                    popup.pr_number
+                    "PR #{} was closed.\n\n{}\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
+                    popup.pr_number, popup.destroys
                 ),
                 DoneConfirmPrState::Unknown => format!(
-                    "PR #{} state unknown.\n\nAre you sure you want to move this task to Done?\n\nWorktree will be deleted, tmux coding session killed.\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
-                    popup.pr_number
+                    "PR #{} state unknown.\n\nAre you sure you want to move this task to Done?\n\n{}\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
+                    popup.pr_number, popup.destroys
+                ),
+                DoneConfirmPrState::NoPr => format!(
+                    "This task has no PR.\n\nAre you sure you want to move this task to Done?\n\n{}\nBranch kept locally.\n\n[y] Yes, move to Done    [n/Esc] Cancel",
+                    popup.destroys
                 ),
             };
             let content = Paragraph::new(text)
@@ -1012,15 +1472,15 @@ impl App {
             frame.render_widget(Clear, popup_area);
 
             let main_block = Block::default()
-                .title(" Delete Task? ")
+                .title(" Move Task to Trash? ")
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(Color::Red));
             frame.render_widget(main_block, popup_area);
 
             let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 2 });
             let text = format!(
-                "Are you sure you want to delete:\n\n\"{}\"\n\nThis will also remove the worktree and tmux session.\n\n[y] Yes, delete    [n/Esc] Cancel",
-                popup.task_title
+                "Move to trash:\n\n\"{}\"\n\n{}\nThe task itself can be restored from the trash [T].\n\n[y] Yes, move to trash    [n/Esc] Cancel",
+                popup.task_title, popup.destroys
             );
             let content = Paragraph::new(text)
                 .style(Style::default().fg(Color::White))
@@ -1097,2066 +1557,5894 @@ impl App {
             frame.render_widget(content, inner);
         }
 
-        // Git diff popup
-        if let Some(ref popup) = state.diff_popup {
-            let popup_area = centered_rect(80, 80, area);
+        // Dependency (blocked-by) editor popup
+        if let Some(ref popup) = state.dependency_popup {
+            let popup_area = centered_rect(60, 60, area);
             frame.render_widget(Clear, popup_area);
 
-            let popup_chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(1), // Title bar
-                    Constraint::Min(0),    // Diff content
-                    Constraint::Length(1), // Footer
-                ])
-                .split(popup_area);
-
-            // Title bar
-            let title = format!(" Diff: {} ", popup.task_title);
-            let title_bar = Paragraph::new(title)
-                .style(Style::default().fg(Color::Black).bg(hex_to_color(&state.config.theme.color_popup_header)));
-            frame.render_widget(title_bar, popup_chunks[0]);
+            let main_block = Block::default()
+                .title(format!(" Blocked By: {} ", popup.task_title))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-            // Diff content with syntax highlighting
-            let lines: Vec<Line> = popup.diff_content
-                .lines()
-                .skip(popup.scroll_offset)
-                .take(popup_chunks[1].height.saturating_sub(2) as usize)
-                .map(|line| {
-                    let style = if line.starts_with('+') && !line.starts_with("+++") {
-                        Style::default().fg(Color::Green)
-                    } else if line.starts_with('-') && !line.starts_with("---") {
-                        Style::default().fg(Color::Red)
-                    } else if line.starts_with("@@") {
-                        Style::default().fg(Color::Cyan)
-                    } else if line.starts_with("diff ") || line.starts_with("index ") {
-                        Style::default().fg(hex_to_color(&state.config.theme.color_selected))
-                    } else {
-                        Style::default().fg(Color::White)
-                    };
-                    Line::from(Span::styled(line, style))
-                })
-                .collect();
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let depends_on = state.board.tasks
+                .iter()
+                .find(|t| t.id == popup.task_id)
+                .map(|t| t.depends_on.clone())
+                .unwrap_or_default();
+            let mut lines: Vec<Line> = Vec::new();
+            if popup.candidates.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    "  No other tasks in this project",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+                )));
+            }
+            for (i, id) in popup.candidates.iter().enumerate() {
+                let Some(candidate) = state.board.tasks.iter().find(|t| &t.id == id) else { continue };
+                let is_selected = i == popup.selected;
+                let marker = if is_selected { "> " } else { "  " };
+                let check = if depends_on.contains(id) { "[x] " } else { "[ ] " };
+                let name_style = if is_selected {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text))
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(marker, name_style),
+                    Span::styled(check, name_style),
+                    Span::styled(candidate.title.as_str(), name_style),
+                    Span::styled(
+                        format!("  {}", candidate.status.as_str()),
+                        Style::default().fg(hex_to_color(&state.config.theme.color_description)),
+                    ),
+                ]));
+            }
 
-            let diff_content = Paragraph::new(lines)
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border))),
-                );
-            frame.render_widget(diff_content, popup_chunks[1]);
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  [Enter/Space] toggle  [↑/↓] move  [Esc] close",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
 
-            // Footer with scroll info
-            let total_lines = popup.diff_content.lines().count();
-            let footer_text = format!(
-                " [j/k] scroll  [d/u] page  [g/G] top/bottom  [q/Esc] close  ({}/{}) ",
-                popup.scroll_offset + 1,
-                total_lines
-            );
-            let footer = Paragraph::new(footer_text)
-                .style(Style::default().fg(Color::Black).bg(hex_to_color(&state.config.theme.color_dimmed)));
-            frame.render_widget(footer, popup_chunks[2]);
+            frame.render_widget(Paragraph::new(lines), inner);
         }
-    }
 
-    fn draw_shell_popup(popup: &ShellPopup, frame: &mut Frame, area: Rect, theme: &ThemeConfig) {
-        let popup_area = centered_rect_fixed_width(SHELL_POPUP_WIDTH, SHELL_POPUP_HEIGHT_PERCENT, area);
+        // Trash view popup
+        if let Some(ref popup) = state.trash_popup {
+            let popup_area = centered_rect(60, 60, area);
+            frame.render_widget(Clear, popup_area);
 
-        // Parse ANSI escape sequences for colors
-        let styled_lines = parse_ansi_to_lines(&popup.cached_content);
+            let main_block = Block::default()
+                .title(format!(" Trash ({}) ", popup.tasks.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        // Build colors from theme
-        let colors = shell_popup::ShellPopupColors {
-            border: hex_to_color(&theme.color_popup_border),
-            header_fg: Color::Black,
-            header_bg: hex_to_color(&theme.color_popup_header),
-            footer_fg: Color::Black,
-            footer_bg: hex_to_color(&theme.color_dimmed),
-        };
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let mut lines: Vec<Line> = Vec::new();
+            if popup.tasks.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    "  Trash is empty",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+                )));
+            }
+            for (i, task) in popup.tasks.iter().enumerate() {
+                let is_selected = i == popup.selected;
+                let marker = if is_selected { "> " } else { "  " };
+                let name_style = if is_selected {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text))
+                };
+                let deleted = task
+                    .deleted_at
+                    .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or_default();
+                lines.push(Line::from(vec![
+                    Span::styled(marker, name_style),
+                    Span::styled(task.title.as_str(), name_style),
+                    Span::styled(
+                        format!("  {} · deleted {}", task.status.as_str(), deleted),
+                        Style::default().fg(hex_to_color(&state.config.theme.color_description)),
+                    ),
+                ]));
+            }
 
-        shell_popup::render_shell_popup(popup, frame, popup_area, styled_lines, &colors);
-    }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  [Enter/r] restore to Backlog  [P] delete permanently  [↑/↓] move  [Esc] close",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
 
-    fn draw_task_card(frame: &mut Frame, task: &Task, area: Rect, is_selected: bool, theme: &ThemeConfig, phase_status: Option<&(PhaseStatus, Instant)>, spinner_frame: usize) {
-        let border_style = if is_selected {
-            Style::default().fg(hex_to_color(&theme.color_selected))
-        } else {
-            Style::default().fg(hex_to_color(&theme.color_normal))
-        };
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
 
-        let title_style = if is_selected {
-            Style::default().fg(hex_to_color(&theme.color_selected)).bold()
-        } else {
-            Style::default().fg(hex_to_color(&theme.color_text)).bold()
-        };
+        // Resume all Review tasks popup
+        if let Some(ref popup) = state.resume_all_popup {
+            let popup_area = centered_rect(50, 30, area);
+            frame.render_widget(Clear, popup_area);
 
-        // Truncate title to fit (char-safe for UTF-8)
-        let max_title_len = area.width.saturating_sub(4) as usize;
-        let title: String = if task.title.chars().count() > max_title_len {
-            let truncated: String = task.title.chars().take(max_title_len.saturating_sub(3)).collect();
-            format!("{}...", truncated)
-        } else {
-            task.title.clone()
-        };
+            let main_block = Block::default()
+                .title(" Resume All Review Tasks ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        let border_type = if is_selected {
-            BorderType::Thick
-        } else {
-            BorderType::Plain
-        };
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let lines = vec![
+                Line::from(Span::styled(
+                    format!("Move {} Review task(s) back to Running.", popup.count),
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text)),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(
+                        "Follow-up prompt (optional): ",
+                        Style::default().fg(hex_to_color(&state.config.theme.color_column_header)),
+                    ),
+                    Span::styled(
+                        format!("{}█", popup.follow_up),
+                        Style::default().fg(hex_to_color(&state.config.theme.color_selected)),
+                    ),
+                ]),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "  [Enter] resume all  [Esc] cancel",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+                )),
+            ];
+
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+        }
 
-        let card_block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(border_style)
-            .border_type(border_type);
-        let inner = card_block.inner(area);
-        frame.render_widget(card_block, area);
+        // Interrupt agent popup
+        if let Some(ref popup) = state.abort_popup {
+            let popup_area = centered_rect(50, 30, area);
+            frame.render_widget(Clear, popup_area);
 
-        // Title line with optional phase indicator
-        let show_indicator = matches!(task.status,
-            TaskStatus::Planning | TaskStatus::Running | TaskStatus::Review)
-            || (task.status == TaskStatus::Backlog && task.session_name.is_some());
+            let main_block = Block::default()
+                .title(" Interrupt Agent ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        if show_indicator {
-            const SPINNER_FRAMES: &[&str] = &["\u{280b}", "\u{2819}", "\u{2839}", "\u{2838}", "\u{283c}", "\u{2834}", "\u{2826}", "\u{2827}", "\u{2807}", "\u{280f}"];
-            let indicator = match phase_status {
-                Some((PhaseStatus::Ready, _)) => Span::styled("\u{2713} ", Style::default().fg(Color::Green)),
-                Some((PhaseStatus::Working, _)) => {
-                    let spinner = SPINNER_FRAMES[spinner_frame % SPINNER_FRAMES.len()];
-                    Span::styled(format!("{} ", spinner), Style::default().fg(Color::Yellow))
-                }
-                Some((PhaseStatus::Exited, _)) => Span::styled("\u{2717} ", Style::default().fg(Color::Red)),
-                None => Span::raw(""),
-            };
-            let title_spans = Line::from(vec![indicator, Span::styled(title, title_style)]);
-            let title_line = Paragraph::new(title_spans);
-            let title_area = Rect {
-                x: inner.x,
-                y: inner.y,
-                width: inner.width,
-                height: 1,
-            };
-            frame.render_widget(title_line, title_area);
-        } else {
-            let title_line = Paragraph::new(title).style(title_style);
-            let title_area = Rect {
-                x: inner.x,
-                y: inner.y,
-                width: inner.width,
-                height: 1,
-            };
-            frame.render_widget(title_line, title_area);
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let lines = vec![
+                Line::from(Span::styled(
+                    format!("Send Ctrl-C to the agent of '{}'. The task, window and worktree stay.", popup.title),
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text)),
+                )),
+                Line::from(""),
+                Line::from(vec![
+                    Span::styled(
+                        "New prompt (optional): ",
+                        Style::default().fg(hex_to_color(&state.config.theme.color_column_header)),
+                    ),
+                    Span::styled(
+                        format!("{}█", popup.follow_up),
+                        Style::default().fg(hex_to_color(&state.config.theme.color_selected)),
+                    ),
+                ]),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "  [Enter] interrupt  [Esc] cancel",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+                )),
+            ];
+
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
         }
 
-        // Preview area (below title) - always show description
-        if inner.height > 1 {
-            let preview_area = Rect {
-                x: inner.x,
-                y: inner.y + 1,
-                width: inner.width,
-                height: inner.height.saturating_sub(1),
-            };
+        // Move straight to Done popup
+        if let Some(ref popup) = state.abandon_popup {
+            let popup_area = centered_rect(50, 35, area);
+            frame.render_widget(Clear, popup_area);
+
+            let main_block = Block::default()
+                .title(" Move Straight to Done? ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_selected)));
+            frame.render_widget(main_block, popup_area);
 
-            // Show description or placeholder
-            let preview_text = task.description.as_deref().unwrap_or("No description");
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let text_style = Style::default().fg(hex_to_color(&state.config.theme.color_text));
+            let mut lines = vec![
+                Line::from(Span::styled(
+                    format!("Move '{}' from {} straight to Done.", popup.title, popup.status.as_str()),
+                    text_style,
+                )),
+                Line::from(""),
+            ];
+            lines.extend(popup.destroys.lines().map(|l| Line::from(Span::styled(l.to_string(), text_style))));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![
+                Span::styled(
+                    "Reason: ",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_column_header)),
+                ),
+                Span::styled(
+                    format!("{}█", popup.reason),
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)),
+                ),
+            ]));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  [Enter] move to Done  [Esc] cancel",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
 
-            // Truncate description to fit preview area
-            let max_chars = (preview_area.width as usize) * (preview_area.height as usize);
-            let truncated: String = if preview_text.chars().count() > max_chars {
-                format!("{}...", preview_text.chars().take(max_chars.saturating_sub(3)).collect::<String>())
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+        }
+
+        // Edit task (title/agent) popup
+        if let Some(ref popup) = state.edit_task_popup {
+            let popup_area = centered_rect(50, 30, area);
+            frame.render_widget(Clear, popup_area);
+
+            let main_block = Block::default()
+                .title(" Edit Task ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
+
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let field_style = |focused: bool| {
+                if focused {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text))
+                }
+            };
+            let label_style = Style::default().fg(hex_to_color(&state.config.theme.color_column_header));
+            let title_cursor = if popup.editing_agent { "" } else { "█" };
+            let agent = popup.agents.get(popup.agent_index).map(String::as_str).unwrap_or("");
+            let agent_text = if popup.agent_editable {
+                format!("◀ {} ▶", agent)
             } else {
-                preview_text.to_string()
+                format!("{} (locked once Running)", agent)
             };
 
-            let preview = Paragraph::new(truncated)
-                .style(Style::default().fg(hex_to_color(&theme.color_description)).italic())
-                .wrap(Wrap { trim: true });
-            frame.render_widget(preview, preview_area);
+            let lines = vec![
+                Line::from(vec![
+                    Span::styled(if popup.editing_agent { "  " } else { "> " }, field_style(!popup.editing_agent)),
+                    Span::styled("Title: ", label_style),
+                    Span::styled(format!("{}{}", popup.title, title_cursor), field_style(!popup.editing_agent)),
+                ]),
+                Line::from(vec![
+                    Span::styled(if popup.editing_agent { "> " } else { "  " }, field_style(popup.editing_agent)),
+                    Span::styled("Agent: ", label_style),
+                    Span::styled(agent_text, field_style(popup.editing_agent)),
+                ]),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "  [Tab] switch field  [←/→] change agent  [Enter] save  [Esc] cancel",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+                )),
+            ];
+
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
         }
-    }
 
-    fn draw_sidebar(state: &AppState, frame: &mut Frame, area: Rect) {
-        // Show projects from database
-        let current_path = state.project_path.as_ref().map(|p| p.to_string_lossy().to_string());
+        // Template picker popup
+        if let Some(ref popup) = state.template_picker {
+            let popup_area = centered_rect(50, 50, area);
+            frame.render_widget(Clear, popup_area);
 
-        let items: Vec<ListItem> = state
-            .projects
-            .iter()
-            .enumerate()
-            .map(|(i, project)| {
-                let is_selected = i == state.selected_project && state.sidebar_focused;
-                let is_current = current_path.as_ref() == Some(&project.path);
+            let main_block = Block::default()
+                .title(" New Task From Template ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-                let style = if is_selected {
-                    Style::default().bg(hex_to_color(&state.config.theme.color_selected)).fg(Color::Black)
-                } else if is_current {
-                    Style::default().fg(hex_to_color(&state.config.theme.color_selected))
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let mut lines: Vec<Line> = Vec::new();
+            let choices = std::iter::once(("none", "Plain task".to_string())).chain(
+                state.config.templates.iter().map(|t| {
+                    let mut detail = t.agent.clone().unwrap_or_default();
+                    if !t.tags.is_empty() {
+                        let tags = t.tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<_>>().join(" ");
+                        detail = if detail.is_empty() { tags } else { format!("{} {}", detail, tags) };
+                    }
+                    (t.name.as_str(), detail)
+                }),
+            );
+            for (i, (name, detail)) in choices.enumerate() {
+                let is_selected = i == popup.selected;
+                let marker = if is_selected { "> " } else { "  " };
+                let name_style = if is_selected {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)).add_modifier(Modifier::BOLD)
                 } else {
                     Style::default().fg(hex_to_color(&state.config.theme.color_text))
                 };
+                lines.push(Line::from(vec![
+                    Span::styled(marker, name_style),
+                    Span::styled(name, name_style),
+                    Span::styled(
+                        format!("  {}", detail),
+                        Style::default().fg(hex_to_color(&state.config.theme.color_description)),
+                    ),
+                ]));
+            }
 
-                let marker = if is_current { " ●" } else { "" };
-                ListItem::new(format!(" {}{}", project.name, marker)).style(style)
-            })
-            .collect();
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  [Enter] select  [↑/↓] move  [Esc] cancel",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
 
-        let title = format!(" 📁 Projects ({}) ", state.projects.len());
-        let border_color = if state.sidebar_focused {
-            hex_to_color(&state.config.theme.color_selected)
-        } else {
-            hex_to_color(&state.config.theme.color_normal)
-        };
-        let list = List::new(items).block(
-            Block::default()
-                .title(title)
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
+
+        // Project picker popup
+        if let Some(ref popup) = state.project_picker {
+            let popup_area = centered_rect(50, 60, area);
+            frame.render_widget(Clear, popup_area);
+
+            let main_block = Block::default()
+                .title(" Switch Project ")
                 .borders(Borders::ALL)
-                .border_style(Style::default().fg(border_color)),
-        );
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        frame.render_widget(list, area);
-    }
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let mut lines: Vec<Line> = vec![
+                Line::from(vec![
+                    Span::styled("> ", Style::default().fg(hex_to_color(&state.config.theme.color_accent))),
+                    Span::styled(popup.query.as_str(), Style::default().fg(hex_to_color(&state.config.theme.color_text))),
+                ]),
+                Line::from(""),
+            ];
+
+            let current_path = state.project_path.as_ref().map(|p| p.to_string_lossy().to_string());
+            let matches = filter_projects(&state.projects, &popup.query);
+            if matches.is_empty() {
+                lines.push(Line::from(Span::styled(
+                    "  No matching projects",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+                )));
+            }
+            for (i, idx) in matches.iter().enumerate() {
+                let project = &state.projects[*idx];
+                let is_selected = i == popup.selected;
+                let marker = if is_selected { "> " } else { "  " };
+                let current = if current_path.as_deref() == Some(project.path.as_str()) { " ✓" } else { "" };
+                let name_style = if is_selected {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text))
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(marker, name_style),
+                    Span::styled(project.name.as_str(), name_style),
+                    Span::styled(current, Style::default().fg(Color::Green)),
+                    Span::styled(
+                        format!("  {}", project.path),
+                        Style::default().fg(hex_to_color(&state.config.theme.color_description)),
+                    ),
+                ]));
+            }
 
-    fn draw_dashboard(state: &AppState, frame: &mut Frame, area: Rect) {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Min(0),
-                Constraint::Length(3),
-            ])
-            .split(area);
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  [Enter] switch  [↑/↓] select  [Esc] cancel",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
 
-        // Header
-        let header = Paragraph::new(" agtx Dashboard ")
-            .style(Style::default().fg(Color::Cyan).bold())
-            .block(Block::default().borders(Borders::ALL));
-        frame.render_widget(header, chunks[0]);
+            frame.render_widget(Paragraph::new(lines), inner);
+        }
 
-        // Content area split: message + options/project list
-        let content_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(5), // Message
-                Constraint::Min(0),    // Project list or options
-            ])
-            .split(chunks[1]);
+        // Board stats popup
+        if let Some(ref popup) = state.stats_popup {
+            let popup_area = centered_rect(40, 50, area);
+            frame.render_widget(Clear, popup_area);
 
-        let dimmed_color = hex_to_color(&state.config.theme.color_dimmed);
-        let selected_color = hex_to_color(&state.config.theme.color_selected);
+            let main_block = Block::default()
+                .title(" Board Stats ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        // Message
-        let message = Paragraph::new("\n  No project found in current directory.\n")
-            .style(Style::default().fg(selected_color))
-            .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(dimmed_color)));
-        frame.render_widget(message, content_chunks[0]);
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let label_style = Style::default().fg(hex_to_color(&state.config.theme.color_column_header));
+            let value_style = Style::default().fg(hex_to_color(&state.config.theme.color_text)).bold();
+            let stat_line = |label: String, value: String| {
+                Line::from(vec![
+                    Span::styled(format!("{:<20}", label), label_style),
+                    Span::styled(value, value_style),
+                ])
+            };
 
-        // Project list or options
-        if state.show_project_list && !state.projects.is_empty() {
-            // Show project list
-            let items: Vec<ListItem> = state
-                .projects
+            let mut lines: Vec<Line> = popup.stats.per_status
                 .iter()
-                .enumerate()
-                .map(|(i, project)| {
-                    let is_selected = i == state.selected_project;
-                    let style = if is_selected {
-                        Style::default().bg(dimmed_color).fg(Color::White)
-                    } else {
-                        Style::default()
-                    };
-                    ListItem::new(format!("  {}", project.name)).style(style)
-                })
+                .map(|(status, count)| stat_line(status.as_str().to_string(), count.to_string()))
                 .collect();
-
-            let list = List::new(items).block(
-                Block::default()
-                    .title(" Projects [j/k] navigate [Enter] open [Esc] back ")
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(selected_color)),
-            );
-            frame.render_widget(list, content_chunks[1]);
-        } else {
-            // Show options
-            let options = Paragraph::new(
-                "\n  [p] Open existing project\n  [n] Create new project in current directory\n",
-            )
-            .block(Block::default().title(" Options ").borders(Borders::ALL).border_style(Style::default().fg(dimmed_color)));
-            frame.render_widget(options, content_chunks[1]);
-        }
-
-        // Footer
-        let footer = Paragraph::new(" [p] projects  [n] new project  [q] quit ")
-            .style(Style::default().fg(dimmed_color))
-            .block(Block::default().borders(Borders::ALL));
-        frame.render_widget(footer, chunks[2]);
-    }
-
-    fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        // Handle PR status popup if open (loading/success/error)
-        if let Some(ref popup) = self.state.pr_status_popup {
-            // Only allow closing if not in Creating/Pushing state
-            if popup.status != PrCreationStatus::Creating && popup.status != PrCreationStatus::Pushing {
-                if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
-                    self.state.pr_status_popup = None;
+            lines.push(Line::from(""));
+            lines.push(stat_line("in flight".to_string(), popup.stats.in_flight().to_string()));
+            lines.push(stat_line("total".to_string(), popup.stats.total.to_string()));
+            lines.push(Line::from(""));
+            lines.push(stat_line("active worktrees".to_string(), popup.stats.active_worktrees.to_string()));
+            lines.push(stat_line(
+                "live tmux windows".to_string(),
+                format!("{} / {}", popup.live_windows, popup.stats.sessions),
+            ));
+            if !popup.stats.top_usage.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(stat_line("agent usage".to_string(), format_usage(&popup.stats.usage)));
+                for (title, usage) in &popup.stats.top_usage {
+                    let title: String = title.chars().take(18).collect();
+                    lines.push(stat_line(format!("  {}", title), format_usage(usage)));
                 }
             }
-            return Ok(());
-        }
-
-        // Handle Done confirmation popup if open
-        if self.state.done_confirm_popup.is_some() {
-            return self.handle_done_confirm_key(key);
-        }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "[Esc] close",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
 
-        // Handle Delete confirmation popup if open
-        if self.state.delete_confirm_popup.is_some() {
-            return self.handle_delete_confirm_key(key);
+            frame.render_widget(Paragraph::new(lines), inner);
         }
 
-        // Handle Review confirmation popup if open
-        if self.state.review_confirm_popup.is_some() {
-            return self.handle_review_confirm_key(key);
-        }
+        // Keyboard help
+        if let Some(ref popup) = state.help_popup {
+            let popup_area = centered_rect(60, 80, area);
+            frame.render_widget(Clear, popup_area);
+            let theme = &state.config.theme;
+            let block = Block::default()
+                .title(" Keys ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&theme.color_popup_border)));
+            let inner = block.inner(popup_area).inner(ratatui::layout::Margin { horizontal: 1, vertical: 0 });
+            frame.render_widget(block, popup_area);
 
-        // Handle diff popup if open
-        if self.state.diff_popup.is_some() {
-            return self.handle_diff_popup_key(key);
-        }
+            let header_style = Style::default().fg(hex_to_color(&theme.color_column_header)).bold();
+            let key_style = Style::default().fg(hex_to_color(&theme.color_accent));
+            let text_style = Style::default().fg(hex_to_color(&theme.color_text));
+            let mut lines: Vec<Line> = Vec::new();
+            for (category, entries) in state.keymap.help_sections() {
+                if !lines.is_empty() {
+                    lines.push(Line::from(""));
+                }
+                lines.push(Line::from(Span::styled(category.label(), header_style)));
+                for entry in entries {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("  {:<12}", entry.keys), key_style),
+                        Span::styled(entry.description, text_style),
+                    ]));
+                }
+            }
 
-        // Handle PR confirmation popup if open
-        if self.state.pr_confirm_popup.is_some() {
-            return self.handle_pr_confirm_key(key);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(1)])
+                .split(inner);
+            let max_scroll = lines.len().saturating_sub(chunks[0].height as usize);
+            popup.max_scroll.set(max_scroll);
+            let scroll = popup.scroll.min(max_scroll);
+            frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), chunks[0]);
+
+            let more = match (scroll > 0, scroll < max_scroll) {
+                (true, true) => "  ↑↓ more",
+                (false, true) => "  ↓ more",
+                (true, false) => "  ↑ more",
+                (false, false) => "",
+            };
+            frame.render_widget(
+                Paragraph::new(Span::styled(
+                    format!("[j/k] scroll  [Esc] close{}", more),
+                    Style::default().fg(hex_to_color(&theme.color_dimmed)),
+                )),
+                chunks[1],
+            );
         }
 
-        // Handle plugin selection popup if open
-        if self.state.plugin_select_popup.is_some() {
-            return self.handle_plugin_select_key(key);
-        }
+        // Task detail popup
+        if let Some(ref popup) = state.task_detail_popup {
+            let popup_area = centered_rect(70, 70, area);
+            frame.render_widget(Clear, popup_area);
 
-        // Handle task search popup if open
-        if self.state.task_search.is_some() {
-            return self.handle_task_search_key(key);
-        }
+            let main_block = Block::default()
+                .title(" Task Details ")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        // Handle shell popup if open
-        if self.state.shell_popup.is_some() {
-            return self.handle_shell_popup_key(key);
-        }
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let theme = &state.config.theme;
+            let label_style = Style::default().fg(hex_to_color(&theme.color_column_header));
+            let text_style = Style::default().fg(hex_to_color(&theme.color_text));
+            let dimmed_style = Style::default().fg(hex_to_color(&theme.color_dimmed));
+            let task = &popup.task;
+
+            let mut lines: Vec<Line> = vec![
+                Line::from(Span::styled(task.title.as_str(), text_style.bold())),
+                Line::from(""),
+            ];
+            for (label, value) in task_detail_fields(task) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("{:<10}", label), label_style),
+                    Span::styled(value, text_style),
+                ]));
+            }
 
-        // Handle based on mode (Dashboard vs Project)
-        match &self.state.mode {
-            AppMode::Dashboard => self.handle_dashboard_key(key.code),
-            AppMode::Project(_) => {
-                match self.state.input_mode {
-                    InputMode::Normal => self.handle_normal_key(key.code),
-                    InputMode::InputTitle => self.handle_title_input(key),
-                    InputMode::InputDescription => self.handle_description_input(key),
-                }
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Description", label_style)));
+            let highlight_style = Style::default().fg(hex_to_color(&theme.color_accent)).bold();
+            let highlighted = |text: &str| -> Vec<Span<'static>> {
+                search::highlight_segments(text, popup.highlight.as_deref().unwrap_or(""))
+                    .into_iter()
+                    .map(|(segment, matched)| Span::styled(segment, if matched { highlight_style } else { text_style }))
+                    .collect()
+            };
+            match task.description.as_deref().filter(|d| !d.trim().is_empty()) {
+                Some(description) => lines.extend(description.lines().map(|l| Line::from(highlighted(l)))),
+                None => lines.push(Line::from(Span::styled("No description", dimmed_style))),
             }
-        }
-    }
 
-    fn handle_done_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        if let Some(popup) = self.state.done_confirm_popup.clone() {
-            match key.code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
-                    // Confirmed - force move to Done
-                    self.state.done_confirm_popup = None;
-                    self.force_move_to_done(&popup.task_id)?;
+            if let Some(ref commits) = popup.commits {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("Commits", label_style)));
+                if commits.is_empty() {
+                    lines.push(Line::from(Span::styled("(no commits)", dimmed_style)));
                 }
-                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                    // Cancelled
-                    self.state.done_confirm_popup = None;
+                for commit in commits {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{}  ", commit.hash), dimmed_style),
+                        Span::styled(commit.subject.as_str(), text_style),
+                    ]));
                 }
-                _ => {}
             }
-        }
-        Ok(())
-    }
 
-    fn handle_delete_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        if let Some(popup) = self.state.delete_confirm_popup.clone() {
-            match key.code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
-                    // Confirmed - delete the task
-                    self.state.delete_confirm_popup = None;
-                    self.perform_delete_task(&popup.task_id)?;
-                }
-                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                    // Cancelled
-                    self.state.delete_confirm_popup = None;
-                }
-                _ => {}
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(format!("Notes ({})", task.notes.len()), label_style)));
+            if task.notes.is_empty() && popup.note_input.is_none() {
+                lines.push(Line::from(Span::styled("No notes yet", dimmed_style)));
+            }
+            for note in &task.notes {
+                let mut spans = vec![Span::styled(format!("{}  ", format_note_time(note.at)), dimmed_style)];
+                spans.extend(highlighted(&note.text));
+                lines.push(Line::from(spans));
+            }
+            if let Some(ref input) = popup.note_input {
+                lines.push(Line::from(vec![
+                    Span::styled("> ", Style::default().fg(hex_to_color(&theme.color_accent))),
+                    Span::styled(format!("{}█", input), text_style),
+                ]));
             }
-        }
-        Ok(())
-    }
 
-    fn handle_review_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        if let Some(popup) = self.state.review_confirm_popup.clone() {
-            match key.code {
-                KeyCode::Char('y') | KeyCode::Char('Y') => {
-                    // Yes - create PR and move to review
-                    self.state.review_confirm_popup = None;
-                    self.move_running_to_review_with_pr(&popup.task_id)?;
-                }
-                KeyCode::Char('n') | KeyCode::Char('N') => {
-                    // No - just move to review without PR
-                    self.state.review_confirm_popup = None;
-                    self.move_running_to_review_without_pr(&popup.task_id)?;
-                }
-                KeyCode::Esc => {
-                    // Cancelled - don't move
-                    self.state.review_confirm_popup = None;
+            if !popup.events.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("History", label_style)));
+                for event in &popup.events {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{}  ", format_note_time(event.at)), dimmed_style),
+                        Span::styled(format_event(event), text_style),
+                    ]));
                 }
-                _ => {}
             }
-        }
-        Ok(())
-    }
 
-    fn open_plugin_select_popup(&mut self) {
-        let current = self.state.config.workflow_plugin.as_deref().unwrap_or("");
-        let mut options = vec![PluginOption {
-            name: String::new(),
-            label: "agtx".to_string(),
-            description: "Built-in workflow with skills and prompts".to_string(),
-            active: current.is_empty(),
-        }];
-        for (name, desc, _content) in skills::BUNDLED_PLUGINS {
-            options.push(PluginOption {
-                name: name.to_string(),
-                label: name.to_string(),
-                description: desc.to_string(),
-                active: current == *name,
-            });
-        }
-        let selected = options.iter().position(|o| o.active).unwrap_or(0);
-        self.state.plugin_select_popup = Some(PluginSelectPopup { selected, options });
-    }
+            lines.push(Line::from(""));
+            let help = if popup.note_input.is_some() {
+                "[Enter] save note  [Esc] cancel"
+            } else {
+                "[n] add note  [x] delete last note  [Esc] close"
+            };
+            lines.push(Line::from(Span::styled(help, dimmed_style)));
 
-    fn handle_plugin_select_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        if let Some(ref mut popup) = self.state.plugin_select_popup {
-            match key.code {
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if popup.selected < popup.options.len().saturating_sub(1) {
-                        popup.selected += 1;
-                    }
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if popup.selected > 0 {
-                        popup.selected -= 1;
-                    }
-                }
-                KeyCode::Enter => {
-                    let name = popup.options[popup.selected].name.clone();
-                    self.state.plugin_select_popup = None;
-                    self.install_plugin(&name)?;
-                }
-                KeyCode::Esc => {
-                    self.state.plugin_select_popup = None;
-                }
-                _ => {}
-            }
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
         }
-        Ok(())
-    }
 
-    fn install_plugin(&mut self, plugin_name: &str) -> Result<()> {
-        let Some(project_path) = self.state.project_path.clone() else {
-            return Ok(());
-        };
+        // Startup reconciliation popup
+        if let Some(ref popup) = state.reconcile_popup {
+            let popup_area = centered_rect(70, 60, area);
+            frame.render_widget(Clear, popup_area);
 
-        // Load current project config
-        let mut project_config = ProjectConfig::load(&project_path).unwrap_or_default();
+            let main_block = Block::default()
+                .title(format!(" Out of Sync ({}) ", popup.entries.len()))
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border)));
+            frame.render_widget(main_block, popup_area);
 
-        if plugin_name.is_empty() {
-            // Clear plugin
-            project_config.workflow_plugin = None;
-        } else {
-            // Find bundled plugin content and write it
-            if let Some((_name, _desc, content)) = skills::BUNDLED_PLUGINS
-                .iter()
-                .find(|(n, _, _)| *n == plugin_name)
-            {
-                let plugin_dir = project_path
-                    .join(".agtx")
-                    .join("plugins")
-                    .join(plugin_name);
-                let _ = std::fs::create_dir_all(&plugin_dir);
-                let _ = std::fs::write(plugin_dir.join("plugin.toml"), content);
+            let inner = popup_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+            let mut lines: Vec<Line> = vec![
+                Line::from(Span::styled(
+                    "These tasks' tmux windows or worktrees no longer exist. Their status was left as is.",
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text)),
+                )),
+                Line::from(""),
+            ];
+            for (i, (_, title, status, mismatch)) in popup.entries.iter().enumerate() {
+                let is_selected = i == popup.selected;
+                let marker = if is_selected { "> " } else { "  " };
+                let name_style = if is_selected {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected)).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text))
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(marker, name_style),
+                    Span::styled(title.as_str(), name_style),
+                    Span::styled(
+                        format!("  {} · {}", status.as_str(), mismatch.badge()),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ]));
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", mismatch.suggestion()),
+                    Style::default().fg(hex_to_color(&state.config.theme.color_description)),
+                )));
             }
-            project_config.workflow_plugin = Some(plugin_name.to_string());
+
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                "  [Enter] select task  [↑/↓] move  [Esc] close",
+                Style::default().fg(hex_to_color(&state.config.theme.color_dimmed)),
+            )));
+
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
         }
 
-        // Save project config
-        project_config.save(&project_path)?;
-
-        // Refresh merged config and cached plugin
-        let global_config = GlobalConfig::load().unwrap_or_default();
-        self.state.config = MergedConfig::merge(&global_config, &project_config);
-        self.state.cached_plugin = Some(load_plugin_if_configured(
-            &self.state.config,
-            Some(&project_path),
-        ));
-
-        Ok(())
-    }
+        // Git diff popup
+        if let Some(ref popup) = state.diff_popup {
+            let popup_area = centered_rect(80, 80, area);
+            frame.render_widget(Clear, popup_area);
 
-    fn force_move_to_done(&mut self, task_id: &str) -> Result<()> {
-        if let (Some(db), Some(project_path)) = (&self.state.db, self.state.project_path.clone()) {
-            if let Some(mut task) = db.get_task(task_id)? {
-                cleanup_task_for_done(
-                    &mut task,
-                    &project_path,
-                    self.state.tmux_ops.as_ref(),
-                    self.state.git_ops.as_ref(),
-                );
-                db.update_task(&task)?;
-                self.refresh_tasks()?;
-            }
-        }
-        Ok(())
-    }
+            let popup_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1), // Title bar
+                    Constraint::Min(0),    // Diff content
+                    Constraint::Length(1), // Footer
+                ])
+                .split(popup_area);
 
-    fn move_running_to_review_with_pr(&mut self, task_id: &str) -> Result<()> {
-        if let Some(db) = &self.state.db {
-            if let Some(task) = db.get_task(task_id)? {
-                let task_title = task.title.clone();
-                let worktree_path = task.worktree_path.clone();
+            // Title bar
+            let title = format!(" Diff: {} ", popup.task_title);
+            let title_bar = Paragraph::new(title)
+                .style(Style::default().fg(Color::Black).bg(hex_to_color(&state.config.theme.color_popup_header)));
+            frame.render_widget(title_bar, popup_chunks[0]);
 
-                // Show popup immediately with loading state
-                self.state.pr_confirm_popup = Some(PrConfirmPopup {
-                    task_id: task_id.to_string(),
-                    pr_title: task_title.clone(),
-                    pr_body: String::new(),
-                    editing_title: true,
-                    generating: true,
-                });
+            // Diff content with syntax highlighting
+            let lines: Vec<Line> = popup.diff_content
+                .lines()
+                .skip(popup.scroll_offset)
+                .take(popup_chunks[1].height.saturating_sub(2) as usize)
+                .map(|line| {
+                    let style = if line.starts_with('+') && !line.starts_with("+++") {
+                        Style::default().fg(Color::Green)
+                    } else if line.starts_with('-') && !line.starts_with("---") {
+                        Style::default().fg(Color::Red)
+                    } else if line.starts_with("@@") {
+                        Style::default().fg(Color::Cyan)
+                    } else if line.starts_with("diff ") || line.starts_with("index ") {
+                        Style::default().fg(hex_to_color(&state.config.theme.color_selected))
+                    } else {
+                        Style::default().fg(Color::White)
+                    };
+                    Line::from(Span::styled(line, style))
+                })
+                .collect();
 
-                // Spawn background thread to generate PR description
-                let (tx, rx) = mpsc::channel();
-                self.state.pr_generation_rx = Some(rx);
+            let diff_content = Paragraph::new(lines)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(hex_to_color(&state.config.theme.color_popup_border))),
+                );
+            frame.render_widget(diff_content, popup_chunks[1]);
 
-                let title_for_thread = task_title.clone();
-                let worktree_for_thread = worktree_path.clone();
-                let git_ops = Arc::clone(&self.state.git_ops);
-                let agent_ops = self.state.agent_registry.get(&self.state.config.default_agent);
-                std::thread::spawn(move || {
-                    let (pr_title, pr_body) = generate_pr_description(
-                        &title_for_thread,
-                        worktree_for_thread.as_deref(),
-                        None,
-                        git_ops.as_ref(),
-                        agent_ops.as_ref(),
-                    );
-                    let _ = tx.send((pr_title, pr_body));
-                });
-            }
+            // Footer with scroll info
+            let total_lines = popup.diff_content.lines().count();
+            let footer_text = format!(
+                " [j/k] scroll  [d/u] page  [g/G] top/bottom  [q/Esc] close  ({}/{}) ",
+                popup.scroll_offset + 1,
+                total_lines
+            );
+            let footer = Paragraph::new(footer_text)
+                .style(Style::default().fg(Color::Black).bg(hex_to_color(&state.config.theme.color_dimmed)));
+            frame.render_widget(footer, popup_chunks[2]);
         }
-        Ok(())
     }
 
-    fn move_running_to_review_without_pr(&mut self, task_id: &str) -> Result<()> {
-        if let Some(db) = &self.state.db {
-            if let Some(mut task) = db.get_task(task_id)? {
-                task.status = TaskStatus::Review;
-                task.updated_at = chrono::Utc::now();
-                db.update_task(&task)?;
-                self.refresh_tasks()?;
+    fn draw_new_task_popup(frame: &mut Frame, state: &AppState, popup: &NewTaskPopup, input_area: Rect) {
+        let theme = &state.config.theme;
+        let title = match &state.pending_template {
+            Some(template) => format!(" New Task ({}) ", template.name),
+            None => " New Task ".to_string(),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(hex_to_color(&theme.color_selected)));
+        frame.render_widget(block, input_area);
+        let inner = input_area.inner(ratatui::layout::Margin { horizontal: 2, vertical: 1 });
+
+        let text_color = hex_to_color(&theme.color_text);
+        let dimmed = Style::default().fg(hex_to_color(&theme.color_dimmed));
+        let label_style = Style::default().fg(hex_to_color(&theme.color_column_header));
+        let field_style = |focused: bool| {
+            if focused {
+                Style::default().fg(hex_to_color(&theme.color_selected)).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(text_color)
             }
-        }
-        Ok(())
-    }
-
-    fn handle_pr_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        use crossterm::event::KeyModifiers;
+        };
 
-        if let Some(ref mut popup) = self.state.pr_confirm_popup {
-            match key.code {
-                KeyCode::Esc => {
-                    self.state.pr_confirm_popup = None;
+        let mut lines: Vec<Line> = Vec::new();
+        for field in FormField::ALL {
+            let focused = field == popup.focus;
+            // The focused text field lives in input_buffer; show it with the cursor
+            let value = if focused && field.is_text() {
+                let (before, after) = state.input_buffer.split_at(state.input_cursor.min(state.input_buffer.len()));
+                format!("{}█{}", before, after)
+            } else {
+                popup.form.value(field).to_string()
+            };
+            let mut spans = vec![
+                Span::styled(if focused { "> " } else { "  " }, field_style(focused)),
+                Span::styled(format!("{}: ", field.label()), label_style),
+            ];
+            match field {
+                FormField::Title => {
+                    if let Some(template) = state.pending_template.as_ref().filter(|t| !t.title_prefix.is_empty()) {
+                        spans.push(Span::styled(template.title_prefix.clone(), dimmed));
+                    }
+                    spans.push(Span::styled(value, field_style(focused)));
+                    lines.push(Line::from(spans));
                 }
-                KeyCode::Tab => {
-                    // Switch between title and body editing
-                    popup.editing_title = !popup.editing_title;
+                FormField::Description => {
+                    lines.push(Line::from(spans));
+                    if value.is_empty() {
+                        lines.push(Line::from(Span::styled("    (optional) [#] file search  [\\+Enter] newline", dimmed)));
+                    } else {
+                        let highlight_color = hex_to_color(&theme.color_accent);
+                        let text = build_highlighted_text(&value, &state.highlighted_file_paths, text_color, highlight_color);
+                        lines.extend(text.lines.into_iter().map(|line| {
+                            let mut spans = vec![Span::raw("    ")];
+                            spans.extend(line.spans);
+                            Line::from(spans)
+                        }));
+                    }
                 }
-                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    if !popup.generating {
-                        // Ctrl+s: Submit and create PR
-                        let task_id = popup.task_id.clone();
-                        let pr_title = popup.pr_title.clone();
-                        let pr_body = popup.pr_body.clone();
-                        self.state.pr_confirm_popup = None;
-                        self.create_pr_and_move_to_review_with_content(&task_id, &pr_title, &pr_body)?;
+                FormField::Agent => {
+                    spans.push(Span::styled(format!("‹ {} ›", value), field_style(focused)));
+                    if focused {
+                        spans.push(Span::styled("  [←/→] change", dimmed));
                     }
+                    lines.push(Line::from(spans));
                 }
-                KeyCode::Enter => {
-                    if popup.editing_title && !popup.generating {
-                        // Enter in title: move to body editing
-                        popup.editing_title = false;
-                    } else if !popup.generating {
-                        // Enter in body: add newline
-                        popup.pr_body.push('\n');
+                FormField::Tags => {
+                    if value.is_empty() {
+                        spans.push(Span::styled("(optional, comma-separated)", dimmed));
+                    } else {
+                        spans.push(Span::styled(value, field_style(focused)));
                     }
+                    lines.push(Line::from(spans));
                 }
-                KeyCode::Backspace => {
-                    if popup.editing_title {
-                        popup.pr_title.pop();
+                FormField::Subdir => {
+                    if value.is_empty() {
+                        spans.push(Span::styled("(optional, e.g. crates/api - the agent works there)", dimmed));
                     } else {
-                        popup.pr_body.pop();
+                        spans.push(Span::styled(value, field_style(focused)));
                     }
+                    lines.push(Line::from(spans));
                 }
-                KeyCode::Char(c) => {
-                    if popup.editing_title {
-                        popup.pr_title.push(c);
+                FormField::FromRef => {
+                    if value.is_empty() {
+                        spans.push(Span::styled("(optional, default: the base branch)", dimmed));
                     } else {
-                        popup.pr_body.push(c);
+                        spans.push(Span::styled(value, field_style(focused)));
                     }
+                    lines.push(Line::from(spans));
                 }
-                _ => {}
             }
+            for error in popup.errors.iter().filter(|e| e.field == field) {
+                lines.push(Line::from(Span::styled(format!("    ✗ {}", error.message), Style::default().fg(Color::Red))));
+            }
+            lines.push(Line::from(""));
         }
-        Ok(())
+        lines.push(Line::from(Span::styled(
+            "  [Tab/Shift+Tab] switch field  [Enter] create  [Esc] cancel",
+            dimmed,
+        )));
+
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
     }
 
-    fn create_pr_and_move_to_review_with_content(&mut self, task_id: &str, pr_title: &str, pr_body: &str) -> Result<()> {
-        if let (Some(db), Some(project_path)) = (&self.state.db, self.state.project_path.clone()) {
-            if let Some(mut task) = db.get_task(task_id)? {
-                // Keep tmux window open - session_name stays set for resume
+    /// File matches for `#`/`@` in a prompt, below the input box (or above it near the bottom)
+    fn draw_file_search_dropdown(frame: &mut Frame, search: &FileSearchState, input_area: Rect, area: Rect, theme: &Theme) {
+        if search.matches.is_empty() {
+            return;
+        }
+        let dropdown_height = (search.matches.len() as u16 + 2).min(12);
+        let dropdown_area = Rect {
+            x: input_area.x + 2,
+            y: input_area.y + input_area.height,
+            width: input_area.width.saturating_sub(4),
+            height: dropdown_height,
+        };
 
-                // Show loading popup
-                self.state.pr_status_popup = Some(PrStatusPopup {
-                    status: PrCreationStatus::Creating,
-                    pr_url: None,
-                    error_message: None,
-                });
+        // Make sure dropdown doesn't go off screen
+        let dropdown_area = if dropdown_area.y + dropdown_area.height > area.height {
+            Rect {
+                y: input_area.y.saturating_sub(dropdown_height),
+                ..dropdown_area
+            }
+        } else {
+            dropdown_area
+        };
 
-                // Clone data for background thread
-                let task_clone = task.clone();
-                let project_path_clone = project_path.clone();
-                let pr_title_clone = pr_title.to_string();
-                let pr_body_clone = pr_body.to_string();
-                let git_ops = Arc::clone(&self.state.git_ops);
-                let git_provider_ops = Arc::clone(&self.state.git_provider_ops);
-                let agent_ops = self.state.agent_registry.get(&self.state.config.default_agent);
+        frame.render_widget(Clear, dropdown_area);
 
-                // Create channel for result
-                let (tx, rx) = mpsc::channel();
-                self.state.pr_creation_rx = Some(rx);
+        let file_selected_color = hex_to_color(&theme.color_selected);
+        let items: Vec<ListItem> = search.matches
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let style = if i == search.selected {
+                    Style::default().bg(file_selected_color).fg(Color::Black)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                ListItem::new(format!(" {} ", path)).style(style)
+            })
+            .collect();
 
-                // Spawn background thread to create PR
-                std::thread::spawn(move || {
-                    let result = create_pr_with_content(
-                        &task_clone,
-                        &project_path_clone,
-                        &pr_title_clone,
-                        &pr_body_clone,
-                        git_ops.as_ref(),
-                        git_provider_ops.as_ref(),
-                        agent_ops.as_ref(),
-                    );
-                    match result {
-                        Ok((pr_number, pr_url)) => {
-                            // Update task in database from background thread
-                            // Keep session_name so popup can still be opened in Review
-                            if let Ok(db) = crate::db::Database::open_project(&project_path_clone) {
-                                let mut updated_task = task_clone;
-                                updated_task.pr_number = Some(pr_number);
-                                updated_task.pr_url = Some(pr_url.clone());
-                                updated_task.status = TaskStatus::Review;
-                                updated_task.updated_at = chrono::Utc::now();
-                                let _ = db.update_task(&updated_task);
-                            }
-                            let _ = tx.send(Ok((pr_number, pr_url)));
-                        }
-                        Err(e) => {
-                            let _ = tx.send(Err(e.to_string()));
-                        }
-                    }
-                });
-            }
-        }
-        Ok(())
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .title(" Files [↑↓] select [Tab/Enter] insert [Esc] cancel ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(Color::Cyan)),
+            );
+        frame.render_widget(list, dropdown_area);
     }
 
-    fn handle_task_search_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        use crossterm::event::KeyModifiers;
+    fn draw_shell_popup(popup: &ShellPopup, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let popup_area = centered_rect_fixed_width(SHELL_POPUP_WIDTH, SHELL_POPUP_HEIGHT_PERCENT, area);
 
-        let should_close = match key.code {
-            KeyCode::Esc => {
-                self.state.task_search = None;
-                true
+        // Parse ANSI escape sequences for colors
+        let styled_lines = parse_ansi_to_lines(&popup.cached_content);
+
+        // Build colors from theme
+        let colors = shell_popup::ShellPopupColors {
+            border: hex_to_color(&theme.color_popup_border),
+            header_fg: Color::Black,
+            header_bg: hex_to_color(&theme.color_popup_header),
+            footer_fg: Color::Black,
+            footer_bg: hex_to_color(&theme.color_dimmed),
+        };
+
+        shell_popup::render_shell_popup(popup, frame, popup_area, styled_lines, &colors);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn draw_task_card(frame: &mut Frame, task: &Task, area: Rect, is_selected: bool, is_marked: bool, staleness: Staleness, badge: Option<&str>, worktree_dirty: Option<bool>, presence: Option<ResourcePresence>, theme: &Theme, phase_status: Option<&(PhaseStatus, Instant)>, spinner_frame: usize) {
+        let border_style = if is_selected {
+            Style::default().fg(hex_to_color(&theme.color_selected))
+        } else if is_marked {
+            Style::default().fg(hex_to_color(&theme.color_accent))
+        } else {
+            match staleness {
+                Staleness::Fresh => Style::default().fg(hex_to_color(&theme.color_normal)),
+                Staleness::Stale => Style::default().fg(Color::Yellow),
+                Staleness::VeryStale => Style::default().fg(Color::Red),
             }
-            KeyCode::Enter => {
-                // Jump to selected task and open it
-                if let Some(ref search) = self.state.task_search {
-                    if let Some((task_id, _, status)) = search.matches.get(search.selected).cloned() {
-                        // Find column index for this status
-                        let col_idx = TaskStatus::columns().iter().position(|s| *s == status).unwrap_or(0);
-                        self.state.board.selected_column = col_idx;
-
-                        // Find row index for this task
-                        let tasks_in_col: Vec<_> = self.state.board.tasks.iter()
-                            .filter(|t| t.status == status)
-                            .collect();
-                        if let Some(row_idx) = tasks_in_col.iter().position(|t| t.id == task_id) {
-                            self.state.board.selected_row = row_idx;
-                        }
-                    }
-                }
-                self.state.task_search = None;
-                // Open the selected task (same as pressing Enter on a task)
-                self.open_selected_task()?;
-                true
+        };
+
+        let title_style = if is_selected {
+            Style::default().fg(hex_to_color(&theme.color_selected)).bold()
+        } else if task.archived {
+            Style::default().fg(hex_to_color(&theme.color_dimmed))
+        } else {
+            Style::default().fg(hex_to_color(&theme.color_text)).bold()
+        };
+
+        // Truncate title to fit (char-safe for UTF-8)
+        let max_title_len = area.width.saturating_sub(4) as usize;
+        let title: String = if task.title.chars().count() > max_title_len {
+            let truncated: String = task.title.chars().take(max_title_len.saturating_sub(3)).collect();
+            format!("{}...", truncated)
+        } else {
+            task.title.clone()
+        };
+        let title = if task.pinned { format!("📌 {}", title) } else { title };
+        let title = if is_marked { format!("● {}", title) } else { title };
+
+        let border_type = if is_selected {
+            BorderType::Thick
+        } else if is_marked {
+            BorderType::Double
+        } else {
+            BorderType::Plain
+        };
+
+        let card_block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .border_type(border_type);
+        let inner = card_block.inner(area);
+        frame.render_widget(card_block, area);
+
+        // Title line with optional phase indicator
+        let show_indicator = matches!(task.status,
+            TaskStatus::Planning | TaskStatus::Running | TaskStatus::Review)
+            || (task.status == TaskStatus::Backlog && task.session_name.is_some());
+
+        let mut title_spans = Vec::new();
+        if show_indicator {
+            let indicator = match phase_status {
+                Some((PhaseStatus::Ready, _)) => Span::styled("\u{2713} ", Style::default().fg(hex_to_color(&theme.color_ready))),
+                Some((PhaseStatus::Working, _)) => {
+                    let spinner = SPINNER_FRAMES[spinner_frame % SPINNER_FRAMES.len()];
+                    Span::styled(format!("{} ", spinner), Style::default().fg(hex_to_color(&theme.color_working)))
+                }
+                Some((PhaseStatus::Exited, _)) => Span::styled("\u{2717} ", Style::default().fg(hex_to_color(&theme.color_exited))),
+                None => Span::raw(""),
+            };
+            title_spans.push(indicator);
+        }
+        // Worktree marker: filled = uncommitted changes, hollow = clean, none = no worktree
+        match worktree_dirty {
+            Some(true) => title_spans.push(Span::styled("\u{25cf} ", Style::default().fg(hex_to_color(&theme.color_working)))),
+            Some(false) => title_spans.push(Span::styled("\u{25cb} ", Style::default().fg(hex_to_color(&theme.color_dimmed)))),
+            None => {}
+        }
+        // Live presence of the window (▣) and worktree (⎇): red once one has vanished
+        for (glyph, present) in presence_badges(presence) {
+            let color = if present { &theme.color_ready } else { &theme.color_exited };
+            title_spans.push(Span::styled(format!("{} ", glyph), Style::default().fg(hex_to_color(color))));
+        }
+        title_spans.push(Span::styled(title, title_style));
+        let title_line = Paragraph::new(Line::from(title_spans));
+        let title_area = Rect {
+            x: inner.x,
+            y: inner.y,
+            width: inner.width,
+            height: 1,
+        };
+        frame.render_widget(title_line, title_area);
+
+        // Preview area (below title) - always show description
+        if inner.height > 1 {
+            let preview_area = Rect {
+                x: inner.x,
+                y: inner.y + 1,
+                width: inner.width,
+                height: inner.height.saturating_sub(1),
+            };
+
+            // Show tags (if any) followed by the description or placeholder
+            let description = task.description.as_deref().unwrap_or("No description");
+            let mut preview_owned = if task.tags.is_empty() {
+                description.to_string()
+            } else {
+                let tags: Vec<String> = task.tags.iter().map(|t| format!("#{}", t)).collect();
+                format!("{} {}", tags.join(" "), description)
+            };
+            if let Some(badge) = badge {
+                preview_owned = format!("{} · {}", badge, preview_owned);
+            }
+            let preview_text = preview_owned.as_str();
+
+            // Truncate description to fit preview area
+            let max_chars = (preview_area.width as usize) * (preview_area.height as usize);
+            let truncated: String = if preview_text.chars().count() > max_chars {
+                format!("{}...", preview_text.chars().take(max_chars.saturating_sub(3)).collect::<String>())
+            } else {
+                preview_text.to_string()
+            };
+
+            let preview = Paragraph::new(truncated)
+                .style(Style::default().fg(hex_to_color(&theme.color_description)).italic())
+                .wrap(Wrap { trim: true });
+            frame.render_widget(preview, preview_area);
+        }
+    }
+
+    fn draw_sidebar(state: &AppState, frame: &mut Frame, area: Rect) {
+        // Show projects from database
+        let current_path = state.project_path.as_ref().map(|p| p.to_string_lossy().to_string());
+
+        let items: Vec<ListItem> = state
+            .projects
+            .iter()
+            .enumerate()
+            .map(|(i, project)| {
+                let is_selected = i == state.selected_project && state.sidebar_focused;
+                let is_current = current_path.as_ref() == Some(&project.path);
+
+                let style = if is_selected {
+                    Style::default().bg(hex_to_color(&state.config.theme.color_selected)).fg(Color::Black)
+                } else if is_current {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_selected))
+                } else {
+                    Style::default().fg(hex_to_color(&state.config.theme.color_text))
+                };
+
+                let marker = if is_current { " ●" } else { "" };
+                ListItem::new(format!(" {}{}", project.name, marker)).style(style)
+            })
+            .collect();
+
+        let title = format!(" 📁 Projects ({}) ", state.projects.len());
+        let border_color = if state.sidebar_focused {
+            hex_to_color(&state.config.theme.color_selected)
+        } else {
+            hex_to_color(&state.config.theme.color_normal)
+        };
+        let list = List::new(items).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color)),
+        );
+
+        frame.render_widget(list, area);
+    }
+
+    fn draw_dashboard(state: &AppState, frame: &mut Frame, area: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(area);
+
+        // Header
+        let header = Paragraph::new(" agtx Dashboard ")
+            .style(Style::default().fg(Color::Cyan).bold())
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(header, chunks[0]);
+
+        // Content area split: message + options/project list
+        let content_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(5), // Message
+                Constraint::Min(0),    // Project list or options
+            ])
+            .split(chunks[1]);
+
+        let dimmed_color = hex_to_color(&state.config.theme.color_dimmed);
+        let selected_color = hex_to_color(&state.config.theme.color_selected);
+
+        // Message
+        let message = Paragraph::new("\n  No project found in current directory.\n")
+            .style(Style::default().fg(selected_color))
+            .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(dimmed_color)));
+        frame.render_widget(message, content_chunks[0]);
+
+        // Project list or options
+        if state.show_project_list && !state.projects.is_empty() {
+            // Show project list
+            let items: Vec<ListItem> = state
+                .projects
+                .iter()
+                .enumerate()
+                .map(|(i, project)| {
+                    let is_selected = i == state.selected_project;
+                    let style = if is_selected {
+                        Style::default().bg(dimmed_color).fg(Color::White)
+                    } else {
+                        Style::default()
+                    };
+                    ListItem::new(format!("  {}", project.name)).style(style)
+                })
+                .collect();
+
+            let list = List::new(items).block(
+                Block::default()
+                    .title(" Projects [j/k] navigate [Enter] open [Esc] back ")
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(selected_color)),
+            );
+            frame.render_widget(list, content_chunks[1]);
+        } else {
+            // Show options
+            let options = Paragraph::new(
+                "\n  [p] Open existing project\n  [n] Create new project in current directory\n",
+            )
+            .block(Block::default().title(" Options ").borders(Borders::ALL).border_style(Style::default().fg(dimmed_color)));
+            frame.render_widget(options, content_chunks[1]);
+        }
+
+        // Footer
+        let footer = Paragraph::new(" [p] projects  [n] new project  [q] quit ")
+            .style(Style::default().fg(dimmed_color))
+            .block(Block::default().borders(Borders::ALL));
+        frame.render_widget(footer, chunks[2]);
+    }
+
+    fn handle_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        // Handle PR status popup if open (loading/success/error)
+        if let Some(ref popup) = self.state.pr_status_popup {
+            // Only allow closing if not in Creating/Pushing state
+            if popup.status != PrCreationStatus::Creating && popup.status != PrCreationStatus::Pushing {
+                if matches!(key.code, KeyCode::Enter | KeyCode::Esc) {
+                    self.state.pr_status_popup = None;
+                }
+            }
+            return Ok(());
+        }
+
+        // Handle Done confirmation popup if open
+        if self.state.done_confirm_popup.is_some() {
+            return self.handle_done_confirm_key(key);
+        }
+
+        // Handle Delete confirmation popup if open
+        if self.state.delete_confirm_popup.is_some() {
+            return self.handle_delete_confirm_key(key);
+        }
+
+        // Handle Review confirmation popup if open
+        if self.state.review_confirm_popup.is_some() {
+            return self.handle_review_confirm_key(key);
+        }
+
+        // Handle diff popup if open
+        if self.state.diff_popup.is_some() {
+            return self.handle_diff_popup_key(key);
+        }
+
+        // Handle PR confirmation popup if open
+        if self.state.pr_confirm_popup.is_some() {
+            return self.handle_pr_confirm_key(key);
+        }
+
+        // Handle plugin selection popup if open
+        if self.state.plugin_select_popup.is_some() {
+            return self.handle_plugin_select_key(key);
+        }
+
+        // Handle project picker popup if open
+        if self.state.project_picker.is_some() {
+            return self.handle_project_picker_key(key);
+        }
+
+        // Handle template picker popup if open
+        if self.state.template_picker.is_some() {
+            return self.handle_template_picker_key(key);
+        }
+
+        // Handle dependency editor popup if open
+        if self.state.dependency_popup.is_some() {
+            return self.handle_dependency_popup_key(key);
+        }
+
+        // Handle edit task popup if open
+        if self.state.edit_task_popup.is_some() {
+            return self.handle_edit_task_popup_key(key);
+        }
+
+        // Handle trash view if open
+        if self.state.trash_popup.is_some() {
+            return self.handle_trash_popup_key(key);
+        }
+
+        // Handle resume-all confirmation if open
+        if self.state.resume_all_popup.is_some() {
+            return self.handle_resume_all_popup_key(key);
+        }
+
+        if self.state.abort_popup.is_some() {
+            return self.handle_abort_popup_key(key);
+        }
+
+        if self.state.abandon_popup.is_some() {
+            return self.handle_abandon_popup_key(key);
+        }
+
+        // Handle board stats popup if open
+        if self.state.stats_popup.is_some() {
+            return self.handle_stats_popup_key(key);
+        }
+
+        // Handle keyboard help if open
+        if let Some(ref mut popup) = self.state.help_popup {
+            if !scroll_help(popup, key.code) {
+                self.state.help_popup = None;
+            }
+            return Ok(());
+        }
+
+        // Handle task detail view if open
+        if self.state.task_detail_popup.is_some() {
+            return self.handle_task_detail_key(key);
+        }
+
+        // Handle startup reconciliation list if open
+        if self.state.reconcile_popup.is_some() {
+            return self.handle_reconcile_popup_key(key);
+        }
+
+        // Handle task search popup if open
+        if self.state.task_search.is_some() {
+            return self.handle_task_search_key(key);
+        }
+
+        // Handle shell popup if open
+        if self.state.shell_popup.is_some() {
+            return self.handle_shell_popup_key(key);
+        }
+
+        // Handle based on mode (Dashboard vs Project)
+        match &self.state.mode {
+            AppMode::Dashboard => self.handle_dashboard_key(key.code),
+            AppMode::Project(_) => {
+                match self.state.input_mode {
+                    InputMode::Normal => self.handle_normal_key(key),
+                    InputMode::InputTitle => self.handle_title_input(key),
+                    InputMode::InputDescription => self.handle_description_input(key),
+                    InputMode::NewTask => self.handle_new_task_key(key),
+                }
+            }
+        }
+    }
+
+    fn handle_done_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        if let Some(popup) = self.state.done_confirm_popup.clone() {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    // Confirmed - force move to Done
+                    self.state.done_confirm_popup = None;
+                    self.force_move_to_done(&popup.task_id)?;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    // Cancelled
+                    self.state.done_confirm_popup = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_delete_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        if let Some(popup) = self.state.delete_confirm_popup.clone() {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    // Confirmed - delete the task
+                    self.state.delete_confirm_popup = None;
+                    self.perform_delete_task(&popup.task_id)?;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    // Cancelled
+                    self.state.delete_confirm_popup = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_review_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        if let Some(popup) = self.state.review_confirm_popup.clone() {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    // Yes - create PR and move to review
+                    self.state.review_confirm_popup = None;
+                    self.move_running_to_review_with_pr(&popup.task_id)?;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') => {
+                    // No - just move to review without PR
+                    self.state.review_confirm_popup = None;
+                    self.move_running_to_review_without_pr(&popup.task_id)?;
+                }
+                KeyCode::Esc => {
+                    // Cancelled - don't move
+                    self.state.review_confirm_popup = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn open_plugin_select_popup(&mut self) {
+        let current = self.state.config.workflow_plugin.as_deref().unwrap_or("");
+        let mut options = vec![PluginOption {
+            name: String::new(),
+            label: "agtx".to_string(),
+            description: "Built-in workflow with skills and prompts".to_string(),
+            active: current.is_empty(),
+        }];
+        for (name, desc, _content) in skills::BUNDLED_PLUGINS {
+            options.push(PluginOption {
+                name: name.to_string(),
+                label: name.to_string(),
+                description: desc.to_string(),
+                active: current == *name,
+            });
+        }
+        let selected = options.iter().position(|o| o.active).unwrap_or(0);
+        self.state.plugin_select_popup = Some(PluginSelectPopup { selected, options });
+    }
+
+    fn handle_plugin_select_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        if let Some(ref mut popup) = self.state.plugin_select_popup {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    if popup.selected < popup.options.len().saturating_sub(1) {
+                        popup.selected += 1;
+                    }
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    if popup.selected > 0 {
+                        popup.selected -= 1;
+                    }
+                }
+                KeyCode::Enter => {
+                    let name = popup.options[popup.selected].name.clone();
+                    self.state.plugin_select_popup = None;
+                    self.install_plugin(&name)?;
+                }
+                KeyCode::Esc => {
+                    self.state.plugin_select_popup = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn open_project_picker(&mut self) -> Result<()> {
+        self.refresh_projects()?;
+        let current = self.state.project_path.as_ref().map(|p| p.to_string_lossy().to_string());
+        let selected = self.state.projects
+            .iter()
+            .position(|p| Some(&p.path) == current.as_ref())
+            .unwrap_or(0);
+        self.state.project_picker = Some(ProjectPickerPopup { query: String::new(), selected });
+        Ok(())
+    }
+
+    fn handle_project_picker_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.project_picker else { return Ok(()) };
+        let match_count = filter_projects(&self.state.projects, &popup.query).len();
+        match key.code {
+            KeyCode::Esc => {
+                self.state.project_picker = None;
+            }
+            KeyCode::Down => {
+                popup.selected = (popup.selected + 1).min(match_count.saturating_sub(1));
+            }
+            KeyCode::Up => {
+                popup.selected = popup.selected.saturating_sub(1);
+            }
+            KeyCode::Backspace => {
+                popup.query.pop();
+                popup.selected = 0;
+            }
+            KeyCode::Char(c) => {
+                popup.query.push(c);
+                popup.selected = 0;
+            }
+            KeyCode::Enter => {
+                let picked = filter_projects(&self.state.projects, &popup.query)
+                    .get(popup.selected)
+                    .map(|idx| self.state.projects[*idx].clone());
+                self.state.project_picker = None;
+                if let Some(project) = picked {
+                    self.switch_to_project(&project)?;
+                    if let Some(idx) = self.state.projects.iter().position(|p| p.path == project.path) {
+                        self.state.selected_project = idx;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_template_picker_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.template_picker else { return Ok(()) };
+        let choice_count = self.state.config.templates.len() + 1;
+        match key.code {
+            KeyCode::Esc => {
+                self.state.template_picker = None;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.selected = (popup.selected + 1).min(choice_count - 1);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                popup.selected = popup.selected.saturating_sub(1);
+            }
+            KeyCode::Enter => {
+                let selected = popup.selected;
+                self.state.template_picker = None;
+                self.state.pending_template = selected
+                    .checked_sub(1)
+                    .and_then(|i| self.state.config.templates.get(i).cloned());
+                self.open_new_task_popup();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn open_edit_task_popup(&mut self) {
+        let Some(task) = self.state.board.selected_task() else { return };
+        let current_agent = self.state.agent_for(task);
+        let agents = agent_choices(&self.state.available_agents, &current_agent);
+        let agent_index = agents.iter().position(|a| *a == current_agent).unwrap_or(0);
+        self.state.edit_task_popup = Some(EditTaskPopup {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            agents,
+            agent_index,
+            agent_editable: agent_editable(task.status),
+            editing_agent: false,
+        });
+    }
+
+    fn handle_edit_task_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.edit_task_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc => self.state.edit_task_popup = None,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                popup.editing_agent = !popup.editing_agent;
+            }
+            KeyCode::Left | KeyCode::Right if popup.editing_agent => {
+                if !popup.agent_editable {
+                    self.state.warning_message = Some((AGENT_LOCKED_MESSAGE.to_string(), Instant::now()));
+                    return Ok(());
+                }
+                let len = popup.agents.len().max(1);
+                popup.agent_index = if key.code == KeyCode::Right {
+                    (popup.agent_index + 1) % len
+                } else {
+                    (popup.agent_index + len - 1) % len
+                };
+            }
+            KeyCode::Backspace if !popup.editing_agent => {
+                popup.title.pop();
+            }
+            KeyCode::Char(c) if !popup.editing_agent => popup.title.push(c),
+            KeyCode::Enter => {
+                let popup = popup.clone();
+                let Some(mut task) = self.state.board.tasks.iter().find(|t| t.id == popup.task_id).cloned() else {
+                    self.state.edit_task_popup = None;
+                    return Ok(());
+                };
+                let agent = popup.agents.get(popup.agent_index).cloned().unwrap_or_default();
+                if let Err(reason) = apply_task_edit(&mut task, &popup.title, &agent) {
+                    self.state.warning_message = Some((reason, Instant::now()));
+                    return Ok(());
+                }
+                if let Some(db) = &self.state.db {
+                    db.update_task(&task)?;
+                }
+                self.state.edit_task_popup = None;
+                self.refresh_tasks()?;
+                self.warn_on_title_conflict(&task);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn open_dependency_popup(&mut self) {
+        let Some(task) = self.state.board.selected_task() else { return };
+        if task.status == TaskStatus::Done {
+            return;
+        }
+        let candidates = self.state.board.tasks
+            .iter()
+            .filter(|t| t.id != task.id && !t.archived)
+            .map(|t| t.id.clone())
+            .collect();
+        self.state.dependency_popup = Some(DependencyPopup {
+            task_id: task.id.clone(),
+            task_title: task.title.clone(),
+            candidates,
+            selected: 0,
+        });
+    }
+
+    fn handle_dependency_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.dependency_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('B') => {
+                self.state.dependency_popup = None;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.selected = (popup.selected + 1).min(popup.candidates.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                popup.selected = popup.selected.saturating_sub(1);
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let Some(dependency_id) = popup.candidates.get(popup.selected).cloned() else {
+                    return Ok(());
+                };
+                let task_id = popup.task_id.clone();
+                self.toggle_dependency(&task_id, &dependency_id)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Add or remove a blocked-by relationship; a cycle is reported instead of saved
+    fn toggle_dependency(&mut self, task_id: &str, dependency_id: &str) -> Result<()> {
+        let all = self.state.board.tasks.clone();
+        let Some(mut task) = all.iter().find(|t| t.id == task_id).cloned() else {
+            return Ok(());
+        };
+        if let Some(pos) = task.depends_on.iter().position(|id| id == dependency_id) {
+            task.depends_on.remove(pos);
+        } else if let Err(e) = db::add_dependency(&mut task, dependency_id, &all) {
+            self.state.warning_message = Some((e.to_string(), Instant::now()));
+            return Ok(());
+        }
+        task.updated_at = chrono::Utc::now();
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.refresh_tasks()?;
+        Ok(())
+    }
+
+    /// If the task may not move into Running yet, show which tasks block it and return true
+    fn report_if_blocked(&mut self, task: &Task) -> bool {
+        let blockers: Vec<String> = db::blocking_tasks(task, &self.state.board.tasks)
+            .iter()
+            .map(|t| format!("'{}' ({})", t.title, t.status.as_str()))
+            .collect();
+        if blockers.is_empty() {
+            return false;
+        }
+        self.state.warning_message = Some((
+            format!("🔒 '{}' is blocked by {}", task.title, blockers.join(", ")),
+            Instant::now(),
+        ));
+        true
+    }
+
+    /// Open the new-task modal, prefilled from `pending_template` if one was picked
+    fn open_new_task_popup(&mut self) {
+        let form = TaskForm::new(self.state.pending_template.as_ref(), &self.state.config.default_agent);
+        self.state.input_buffer = form.title.clone();
+        self.state.input_cursor = self.state.input_buffer.len();
+        self.state.editing_task_id = None;
+        self.state.highlighted_file_paths.clear();
+        self.state.new_task_popup = Some(NewTaskPopup { form, focus: FormField::Title, errors: Vec::new() });
+        self.state.input_mode = InputMode::NewTask;
+    }
+
+    fn close_new_task_popup(&mut self) {
+        self.state.new_task_popup = None;
+        self.state.input_mode = InputMode::Normal;
+        self.state.input_buffer.clear();
+        self.state.input_cursor = 0;
+        self.state.pending_template = None;
+        self.state.highlighted_file_paths.clear();
+    }
+
+    /// Move the new-task modal's focus, swapping text fields in and out of `input_buffer`
+    fn focus_new_task_field(&mut self, field: FormField) {
+        let Some(popup) = self.state.new_task_popup.as_mut() else { return };
+        if popup.focus.is_text() {
+            *popup.form.value_mut(popup.focus) = std::mem::take(&mut self.state.input_buffer);
+        }
+        popup.focus = field;
+        self.state.input_buffer = if field.is_text() { popup.form.value(field).to_string() } else { String::new() };
+        self.state.input_cursor = self.state.input_buffer.len();
+    }
+
+    fn handle_new_task_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        // While the prompt's file search is open it gets every key
+        if self.state.file_search.is_some() {
+            return self.handle_description_input(key);
+        }
+        let Some(focus) = self.state.new_task_popup.as_ref().map(|p| p.focus) else {
+            self.state.input_mode = InputMode::Normal;
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => self.close_new_task_popup(),
+            KeyCode::Tab => self.focus_new_task_field(focus.next()),
+            KeyCode::BackTab => self.focus_new_task_field(focus.prev()),
+            // `\` + Enter starts a new line in the prompt
+            KeyCode::Enter if focus == FormField::Description && self.state.input_buffer.ends_with('\\') => {
+                return self.handle_description_input(key);
+            }
+            KeyCode::Enter => self.submit_new_task()?,
+            KeyCode::Left | KeyCode::Right if focus == FormField::Agent => {
+                if let Some(popup) = self.state.new_task_popup.as_mut() {
+                    let agents = agent_choices(&self.state.available_agents, &popup.form.agent);
+                    popup.form.agent = cycle_agent(&agents, &popup.form.agent, key.code == KeyCode::Right);
+                }
+            }
+            _ if focus == FormField::Agent => {}
+            _ if focus == FormField::Description => return self.handle_description_input(key),
+            _ => return self.handle_title_input(key),
+        }
+        Ok(())
+    }
+
+    /// Validate the new-task modal (`task_form::build_task`): create the task in Backlog,
+    /// or keep the modal open showing what's wrong
+    fn submit_new_task(&mut self) -> Result<()> {
+        let Some(focus) = self.state.new_task_popup.as_ref().map(|p| p.focus) else { return Ok(()) };
+        // Store the field being edited
+        self.focus_new_task_field(focus);
+        let Some(popup) = self.state.new_task_popup.as_mut() else { return Ok(()) };
+        match task_form::build_task(&popup.form, &self.state.project_name, self.state.pending_template.as_ref()) {
+            Ok(task) => {
+                if let Some(db) = &self.state.db {
+                    db.create_task(&task)?;
+                }
+                self.close_new_task_popup();
+                self.refresh_tasks()?;
+                self.warn_on_title_conflict(&task);
+            }
+            Err(errors) => {
+                let first = errors.first().map_or(focus, |e| e.field);
+                popup.errors = errors;
+                self.focus_new_task_field(first);
+            }
+        }
+        Ok(())
+    }
+
+    /// Warn (without blocking) when another open task in the project has the same title
+    fn warn_on_title_conflict(&mut self, task: &Task) {
+        let others = db::find_title_conflicts(&task.title, &self.state.board.tasks, &task.project_id)
+            .into_iter()
+            .filter(|id| id != &task.id)
+            .count();
+        if let Some(message) = title_conflict_warning(&task.title, others) {
+            self.state.warning_message = Some((message, Instant::now()));
+        }
+    }
+
+    fn open_stats_popup(&mut self) {
+        let tasks = &self.state.board.tasks;
+        self.state.stats_popup = Some(StatsPopup {
+            stats: board::board_stats(tasks),
+            live_windows: count_live_windows(tasks, self.state.tmux_ops.as_ref()),
+        });
+    }
+
+    fn handle_stats_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') | KeyCode::Enter) {
+            self.state.stats_popup = None;
+        }
+        Ok(())
+    }
+
+    /// Open the detail view for the selected task, optionally straight into typing a note
+    fn open_task_detail(&mut self, add_note: bool) {
+        let base_branch = self.base_branch();
+        if let Some(task) = self.state.board.selected_task() {
+            let events = self.state.db.as_ref()
+                .and_then(|db| db.get_task_events(&task.id).ok())
+                .unwrap_or_default();
+            let commits = task_recent_commits(task, &base_branch, self.state.git_ops.as_ref());
+            self.state.task_detail_popup = Some(TaskDetailPopup {
+                task: task.clone(),
+                events,
+                commits,
+                note_input: add_note.then(String::new),
+                highlight: None,
+            });
+        }
+    }
+
+    fn handle_task_detail_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.task_detail_popup else { return Ok(()) };
+
+        if let Some(ref mut input) = popup.note_input {
+            match key.code {
+                KeyCode::Esc => popup.note_input = None,
+                KeyCode::Enter => {
+                    let text = std::mem::take(input);
+                    popup.note_input = None;
+                    let task_id = popup.task.id.clone();
+                    self.update_task_notes(&task_id, |task| task.add_note(&text))?;
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => self.state.task_detail_popup = None,
+            KeyCode::Char('n') => popup.note_input = Some(String::new()),
+            KeyCode::Char('x') if !popup.task.notes.is_empty() => {
+                let task_id = popup.task.id.clone();
+                self.update_task_notes(&task_id, |task| {
+                    task.remove_last_note();
+                })?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Change a task's notes on the stored copy (so nothing else on the task is overwritten),
+    /// then show the result in the detail view
+    fn update_task_notes(&mut self, task_id: &str, change: impl FnOnce(&mut Task)) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        change(&mut task);
+        task.updated_at = chrono::Utc::now();
+        db.update_task(&task)?;
+        if let Some(ref mut popup) = self.state.task_detail_popup {
+            popup.task = task;
+        }
+        self.refresh_tasks()
+    }
+
+    fn handle_reconcile_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.reconcile_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.state.reconcile_popup = None,
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.selected = (popup.selected + 1).min(popup.entries.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => popup.selected = popup.selected.saturating_sub(1),
+            KeyCode::Enter => {
+                // Select the task on the board so it can be moved or deleted
+                if let Some((task_id, _, status, _)) = popup.entries.get(popup.selected).cloned() {
+                    let col_idx = TaskStatus::columns().iter().position(|s| *s == status).unwrap_or(0);
+                    self.state.board.selected_column = col_idx;
+                    if let Some(row_idx) = self.state.board.tasks_in_column(col_idx).iter().position(|t| t.id == task_id) {
+                        self.state.board.selected_row = row_idx;
+                    }
+                }
+                self.state.reconcile_popup = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn install_plugin(&mut self, plugin_name: &str) -> Result<()> {
+        let Some(project_path) = self.state.project_path.clone() else {
+            return Ok(());
+        };
+
+        // Load current project config
+        let mut project_config = ProjectConfig::load(&project_path).unwrap_or_default();
+
+        if plugin_name.is_empty() {
+            // Clear plugin
+            project_config.workflow_plugin = None;
+        } else {
+            // Find bundled plugin content and write it
+            if let Some((_name, _desc, content)) = skills::BUNDLED_PLUGINS
+                .iter()
+                .find(|(n, _, _)| *n == plugin_name)
+            {
+                let plugin_dir = project_path
+                    .join(".agtx")
+                    .join("plugins")
+                    .join(plugin_name);
+                let _ = std::fs::create_dir_all(&plugin_dir);
+                let _ = std::fs::write(plugin_dir.join("plugin.toml"), content);
+            }
+            project_config.workflow_plugin = Some(plugin_name.to_string());
+        }
+
+        // Save project config
+        project_config.save(&project_path)?;
+
+        // Refresh merged config and cached plugin
+        let global_config = GlobalConfig::load().unwrap_or_default();
+        self.state.config = MergedConfig::merge(&global_config, &project_config);
+        self.state.cached_plugin = Some(load_plugin_if_configured(
+            &self.state.config,
+            Some(&project_path),
+        ));
+
+        Ok(())
+    }
+
+    fn force_move_to_done(&mut self, task_id: &str) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        let Some(task) = db.get_task(task_id)? else { return Ok(()) };
+        self.finish_move_to_done(task)?;
+        Ok(())
+    }
+
+    /// Clean up a task per `[cleanup.done]`, run the hooks and save it in Done.
+    /// Returns false if the dirty-worktree policy stopped the move (a warning is shown).
+    fn finish_move_to_done(&mut self, mut task: Task) -> Result<bool> {
+        let Some(project_path) = self.state.project_path.clone() else { return Ok(false) };
+        let from = task.status;
+        if let Err(e) = cleanup_task_for_done(
+            &mut task,
+            &project_path,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+            self.state.config.dirty_worktree_policy,
+            self.state.config.cleanup.policy(CleanupAction::Done),
+        ) {
+            self.state.warning_message = Some((format!("Not moved to Done: {}", e), Instant::now()));
+            return Ok(false);
+        }
+        if let Err(e) = self.run_hooks(&task, from, TaskStatus::Done) {
+            // Blocked by a strict hook: the window and worktree are gone, the status stays
+            task.status = from;
+            if let Some(db) = &self.state.db {
+                db.update_task(&task)?;
+            }
+            self.refresh_tasks()?;
+            return Err(e);
+        }
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.post_webhook(&task, from);
+        self.refresh_tasks()?;
+        Ok(true)
+    }
+
+    fn move_running_to_review_with_pr(&mut self, task_id: &str) -> Result<()> {
+        if let Some(db) = &self.state.db {
+            if let Some(task) = db.get_task(task_id)? {
+                let task_title = task.title.clone();
+                let worktree_path = task.worktree_path.clone();
+
+                // Show popup immediately with loading state
+                self.state.pr_confirm_popup = Some(PrConfirmPopup {
+                    task_id: task_id.to_string(),
+                    pr_title: task_title.clone(),
+                    pr_body: String::new(),
+                    editing_title: true,
+                    generating: true,
+                });
+
+                // Spawn background thread to generate PR description
+                let (tx, rx) = mpsc::channel();
+                self.state.pr_generation_rx = Some(rx);
+
+                let title_for_thread = task_title.clone();
+                let worktree_for_thread = worktree_path.clone();
+                let base_branch = self.task_diff_base(&task).unwrap_or_else(|_| self.base_branch());
+                let git_ops = Arc::clone(&self.state.git_ops);
+                let agent_ops = self.state.agent_registry.get(&self.state.agent_for(&task));
+                std::thread::spawn(move || {
+                    let (pr_title, pr_body) = generate_pr_description(
+                        &title_for_thread,
+                        worktree_for_thread.as_deref(),
+                        None,
+                        &base_branch,
+                        git_ops.as_ref(),
+                        agent_ops.as_ref(),
+                    );
+                    let _ = tx.send((pr_title, pr_body));
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn move_running_to_review_without_pr(&mut self, task_id: &str) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        let from = task.status;
+        self.run_hooks(&task, from, TaskStatus::Review)?;
+        task.status = TaskStatus::Review;
+        task.updated_at = chrono::Utc::now();
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.post_webhook(&task, from);
+        self.start_review_tests(task_id)?;
+        self.refresh_tasks()?;
+        Ok(())
+    }
+
+    fn handle_pr_confirm_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        use crossterm::event::KeyModifiers;
+
+        if let Some(ref mut popup) = self.state.pr_confirm_popup {
+            match key.code {
+                KeyCode::Esc => {
+                    self.state.pr_confirm_popup = None;
+                }
+                KeyCode::Tab => {
+                    // Switch between title and body editing
+                    popup.editing_title = !popup.editing_title;
+                }
+                KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    if !popup.generating {
+                        // Ctrl+s: Submit and create PR
+                        let task_id = popup.task_id.clone();
+                        let pr_title = popup.pr_title.clone();
+                        let pr_body = popup.pr_body.clone();
+                        self.state.pr_confirm_popup = None;
+                        self.create_pr_and_move_to_review_with_content(&task_id, &pr_title, &pr_body)?;
+                    }
+                }
+                KeyCode::Enter => {
+                    if popup.editing_title && !popup.generating {
+                        // Enter in title: move to body editing
+                        popup.editing_title = false;
+                    } else if !popup.generating {
+                        // Enter in body: add newline
+                        popup.pr_body.push('\n');
+                    }
+                }
+                KeyCode::Backspace => {
+                    if popup.editing_title {
+                        popup.pr_title.pop();
+                    } else {
+                        popup.pr_body.pop();
+                    }
+                }
+                KeyCode::Char(c) => {
+                    if popup.editing_title {
+                        popup.pr_title.push(c);
+                    } else {
+                        popup.pr_body.push(c);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn create_pr_and_move_to_review_with_content(&mut self, task_id: &str, pr_title: &str, pr_body: &str) -> Result<()> {
+        if let (Some(db), Some(project_path)) = (&self.state.db, self.state.project_path.clone()) {
+            if let Some(mut task) = db.get_task(task_id)? {
+                // Keep tmux window open - session_name stays set for resume
+
+                // Show loading popup
+                self.state.pr_status_popup = Some(PrStatusPopup {
+                    status: PrCreationStatus::Creating,
+                    pr_url: None,
+                    error_message: None,
+                });
+
+                // Clone data for background thread
+                let task_clone = task.clone();
+                let project_path_clone = project_path.clone();
+                let pr_title_clone = pr_title.to_string();
+                let pr_body_clone = pr_body.to_string();
+                let base_branch = self.base_branch();
+                let git_ops = Arc::clone(&self.state.git_ops);
+                let git_provider_ops = Arc::clone(&self.state.git_provider_ops);
+                let agent_ops = self.state.agent_registry.get(&self.state.agent_for(&task));
+                let hook_runner = Arc::clone(&self.state.hook_runner);
+                let hooks_config = self.state.config.hooks.clone();
+                let webhook_config = self.state.config.webhook.clone();
+                let activity = self.state.activities.reporter().start(format!("Pushing and opening a PR for '{}'", task.title));
+
+                // Create channel for result
+                let (tx, rx) = mpsc::channel();
+                self.state.pr_creation_rx = Some(rx);
+                self.state.review_tests_after_pr = Some(task.id.clone());
+
+                // Spawn background thread to create PR
+                std::thread::spawn(move || {
+                    let _activity = activity;
+                    let result = create_pr_with_content(
+                        &task_clone,
+                        &project_path_clone,
+                        &base_branch,
+                        &pr_title_clone,
+                        &pr_body_clone,
+                        git_ops.as_ref(),
+                        git_provider_ops.as_ref(),
+                        agent_ops.as_ref(),
+                    );
+                    match result {
+                        Ok((pr_number, pr_url)) => {
+                            let mut updated_task = task_clone;
+                            updated_task.pr_number = Some(pr_number);
+                            updated_task.pr_url = Some(pr_url.clone());
+                            // Update task in database from background thread
+                            // Keep session_name so popup can still be opened in Review
+                            match save_review_from_thread(updated_task, &project_path_clone, &hooks_config, hook_runner.as_ref(), &webhook_config) {
+                                Ok(()) => {
+                                    let _ = tx.send(Ok((pr_number, pr_url)));
+                                }
+                                Err(e) => {
+                                    let _ = tx.send(Err(format!("PR created ({}), but {:#}", pr_url, e)));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Err(e.to_string()));
+                        }
+                    }
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_task_search_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        use crossterm::event::KeyModifiers;
+
+        let should_close = match key.code {
+            KeyCode::Esc => {
+                self.state.task_search = None;
+                true
+            }
+            KeyCode::Enter => {
+                // Jump to selected task and open it
+                let Some(search) = self.state.task_search.take() else { return Ok(()) };
+                let Some(found) = search.matches.get(search.selected).cloned() else { return Ok(()) };
+                // Find column index for this status
+                let col_idx = TaskStatus::columns().iter().position(|s| *s == found.status).unwrap_or(0);
+                self.state.board.selected_column = col_idx;
+
+                // Find row index for this task
+                let tasks_in_col = self.state.board.tasks_in_column(col_idx);
+                if let Some(row_idx) = tasks_in_col.iter().position(|t| t.id == found.task_id) {
+                    self.state.board.selected_row = row_idx;
+                }
+                if found.field == MatchField::Title {
+                    // Open the selected task (same as pressing Enter on a task)
+                    self.open_selected_task()?;
+                } else {
+                    // Matched in the description or notes: show them, with the match highlighted
+                    self.open_task_detail(false);
+                    if let Some(ref mut popup) = self.state.task_detail_popup {
+                        popup.highlight = Some(SearchQuery::parse(&search.query).text);
+                    }
+                }
+                true
+            }
+            KeyCode::Up | KeyCode::BackTab => {
+                if let Some(ref mut search) = self.state.task_search {
+                    if search.selected > 0 {
+                        search.selected -= 1;
+                    }
+                }
+                false
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                if let Some(ref mut search) = self.state.task_search {
+                    if search.selected < search.matches.len().saturating_sub(1) {
+                        search.selected += 1;
+                    }
+                }
+                false
+            }
+            KeyCode::Char('k') | KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(ref mut search) = self.state.task_search {
+                    if search.selected > 0 {
+                        search.selected -= 1;
+                    }
+                }
+                false
+            }
+            KeyCode::Char('j') | KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(ref mut search) = self.state.task_search {
+                    if search.selected < search.matches.len().saturating_sub(1) {
+                        search.selected += 1;
+                    }
+                }
+                false
+            }
+            KeyCode::Backspace => {
+                if let Some(ref mut search) = self.state.task_search {
+                    search.query.pop();
+                }
+                let query = self.state.task_search.as_ref().map(|s| s.query.clone()).unwrap_or_default();
+                let matches = self.get_all_task_matches(&query);
+                if let Some(ref mut search) = self.state.task_search {
+                    search.matches = matches;
+                    search.selected = 0;
+                }
+                false
+            }
+            KeyCode::Char(c) => {
+                if let Some(ref mut search) = self.state.task_search {
+                    search.query.push(c);
+                }
+                let query = self.state.task_search.as_ref().map(|s| s.query.clone()).unwrap_or_default();
+                let matches = self.get_all_task_matches(&query);
+                if let Some(ref mut search) = self.state.task_search {
+                    search.matches = matches;
+                    search.selected = 0;
+                }
+                false
+            }
+            _ => false,
+        };
+
+        if should_close {
+            self.state.task_search = None;
+        }
+
+        Ok(())
+    }
+
+    fn get_all_task_matches(&self, query: &str) -> Vec<SearchMatch> {
+        let tasks = board::visible_tasks(&self.state.board.tasks, self.state.board.show_archived);
+        search::search_tasks(&tasks, &SearchQuery::parse(query), 10)
+    }
+
+    fn handle_shell_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        use crossterm::event::KeyModifiers;
+
+        if let Some(ref mut popup) = self.state.shell_popup {
+            let window_name = popup.window_name.clone();
+            let has_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+            match key.code {
+                // Ctrl+q = close popup
+                KeyCode::Char('q') if has_ctrl => {
+                    self.state.shell_popup = None;
+                    // The agent likely changed files; re-check its worktree right away
+                    if let Some(task) = self.state.board.selected_task() {
+                        self.state.worktree_status_cache.remove(&task.id);
+                    }
+                }
+                // Scroll up with Ctrl+k or Ctrl+p or Ctrl+Up
+                KeyCode::Char('k') | KeyCode::Char('p') | KeyCode::Up if has_ctrl => {
+                    popup.scroll_up(5);
+                }
+                // Scroll down with Ctrl+j or Ctrl+n or Ctrl+Down
+                KeyCode::Char('j') | KeyCode::Char('n') | KeyCode::Down if has_ctrl => {
+                    popup.scroll_down(5);
+                }
+                // Page up with Ctrl+u or PageUp
+                KeyCode::Char('u') if has_ctrl => {
+                    popup.scroll_up(20);
+                }
+                KeyCode::PageUp => {
+                    popup.scroll_up(20);
+                }
+                // Page down with Ctrl+d or PageDown
+                KeyCode::Char('d') if has_ctrl => {
+                    popup.scroll_down(20);
+                }
+                KeyCode::PageDown => {
+                    popup.scroll_down(20);
+                }
+                // Ctrl+g = go to bottom (current)
+                KeyCode::Char('g') if has_ctrl => {
+                    popup.scroll_to_bottom();
+                }
+                _ => {
+                    // Forward all other keys to tmux window (including Esc)
+                    send_key_to_tmux(&window_name, key.code, self.state.tmux_ops.as_ref());
+                    // After sending a key, refresh content to show the result
+                    popup.cached_content = capture_tmux_pane_with_history(&window_name, 500, self.state.tmux_ops.as_ref());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// What a task's branch is compared against: its `from_ref`, else the base branch
+    /// (`task_base_ref`)
+    fn task_diff_base(&mut self, task: &Task) -> Result<String> {
+        let base_branch = self.base_branch();
+        let project_path = self.state.project_path.clone().unwrap_or_default();
+        task_base_ref(task, &base_branch, &project_path, self.state.git_ops.as_ref())
+    }
+
+    /// Preview the committed branch diff (`<base>...HEAD`) of a Running or Review task
+    fn show_branch_diff(&mut self) {
+        let has_branch = self
+            .state
+            .board
+            .selected_task()
+            .is_some_and(|task| matches!(task.status, TaskStatus::Running | TaskStatus::Review));
+        if !has_branch {
+            return;
+        }
+        let Some(task) = self.state.board.selected_task().cloned() else { return };
+        let (base, diff_content) = match self.task_diff_base(&task) {
+            Ok(base) => {
+                let content = branch_diff_content(&task, &base, self.state.git_ops.as_ref());
+                (base, content)
+            }
+            Err(e) => {
+                let base = task.from_ref.clone().unwrap_or_else(|| self.base_branch());
+                (base, format!("Could not diff: {:#}", e))
+            }
+        };
+        self.state.diff_popup = Some(DiffPopup {
+            task_title: format!("{} ({}...HEAD)", task.title, base),
+            diff_content,
+            scroll_offset: 0,
+        });
+    }
+
+    fn handle_diff_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        if let Some(ref mut popup) = self.state.diff_popup {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    self.state.diff_popup = None;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    popup.scroll_offset = popup.scroll_offset.saturating_add(1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    popup.scroll_offset = popup.scroll_offset.saturating_sub(1);
+                }
+                KeyCode::Char('d') | KeyCode::PageDown => {
+                    popup.scroll_offset = popup.scroll_offset.saturating_add(20);
+                }
+                KeyCode::Char('u') | KeyCode::PageUp => {
+                    popup.scroll_offset = popup.scroll_offset.saturating_sub(20);
+                }
+                KeyCode::Char('g') => {
+                    popup.scroll_offset = 0;
+                }
+                KeyCode::Char('G') => {
+                    // Go to end
+                    let line_count = popup.diff_content.lines().count();
+                    popup.scroll_offset = line_count.saturating_sub(10);
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_dashboard_key(&mut self, key: KeyCode) -> Result<()> {
+        if self.state.show_project_list {
+            match key {
+                KeyCode::Char('q') => self.state.should_quit = true,
+                KeyCode::Char('j') | KeyCode::Down => {
+                    if self.state.selected_project < self.state.projects.len().saturating_sub(1) {
+                        self.state.selected_project += 1;
+                    }
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    if self.state.selected_project > 0 {
+                        self.state.selected_project -= 1;
+                    }
+                }
+                KeyCode::Enter => {
+                    if let Some(project) = self.state.projects.get(self.state.selected_project).cloned() {
+                        self.switch_to_project(&project)?;
+                        self.state.mode = AppMode::Project(PathBuf::from(&project.path));
+                        self.state.sidebar_visible = false;
+                    }
+                }
+                KeyCode::Esc => {
+                    self.state.show_project_list = false;
+                }
+                _ => {}
+            }
+        } else {
+            match key {
+                KeyCode::Char('q') => self.state.should_quit = true,
+                KeyCode::Char('p') => {
+                    self.state.show_project_list = true;
+                }
+                KeyCode::Char('n') => {
+                    let current_dir = std::env::current_dir()?;
+                    if crate::git::is_git_repo(&current_dir) {
+                        let canonical = current_dir.canonicalize().unwrap_or(current_dir);
+                        let name = canonical
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let project = ProjectInfo {
+                            name: name.clone(),
+                            path: canonical.to_string_lossy().to_string(),
+                        };
+                        self.switch_to_project(&project)?;
+                        self.state.mode = AppMode::Project(canonical);
+                        self.state.sidebar_visible = false;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_normal_key(&mut self, key_event: crossterm::event::KeyEvent) -> Result<()> {
+        let key = key_event.code;
+
+        // Handle sidebar navigation if focused
+        if self.state.sidebar_focused && self.state.sidebar_visible {
+            match key {
+                KeyCode::Char('q') => self.state.should_quit = true,
+                KeyCode::Char('e') => {
+                    // Toggle sidebar visibility
+                    self.state.sidebar_visible = false;
+                    self.state.sidebar_focused = false;
+                }
+                KeyCode::Char('l') | KeyCode::Right | KeyCode::Esc => {
+                    // Move focus back to board
+                    self.state.sidebar_focused = false;
+                }
+                KeyCode::Char('j') | KeyCode::Down => {
+                    if self.state.selected_project < self.state.projects.len().saturating_sub(1) {
+                        self.state.selected_project += 1;
+                        // Switch to project immediately on cursor move
+                        if let Some(project) = self.state.projects.get(self.state.selected_project).cloned() {
+                            self.switch_to_project_keep_sidebar(&project)?;
+                        }
+                    }
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    if self.state.selected_project > 0 {
+                        self.state.selected_project -= 1;
+                        // Switch to project immediately on cursor move
+                        if let Some(project) = self.state.projects.get(self.state.selected_project).cloned() {
+                            self.switch_to_project_keep_sidebar(&project)?;
+                        }
+                    }
+                }
+                KeyCode::Enter => {
+                    // Enter focuses the board (sidebar stays visible)
+                    self.state.sidebar_focused = false;
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Remappable board actions
+        let selected_status = self.state.board.selected_task().map(|t| t.status);
+        if let Some(action) = self.state.keymap.resolve(&key_event, selected_status) {
+            return self.run_action(action);
+        }
+
+        // Handle board navigation
+        match key {
+            KeyCode::Char('q') => self.state.should_quit = true,
+            KeyCode::Char('e') => {
+                // Toggle sidebar visibility
+                self.state.sidebar_visible = !self.state.sidebar_visible;
+                if self.state.sidebar_visible {
+                    self.refresh_projects()?;
+                }
+            }
+            // Arrows always navigate; h/j/k/l come from the keymap (`select_*` actions)
+            KeyCode::Left => self.navigate(NavDirection::Left)?,
+            KeyCode::Right => self.navigate(NavDirection::Right)?,
+            KeyCode::Up => self.navigate(NavDirection::Up)?,
+            KeyCode::Down => self.navigate(NavDirection::Down)?,
+            KeyCode::Enter => {
+                if let Some(task) = self.state.board.selected_task() {
+                    if task.status == TaskStatus::Backlog && task.session_name.is_some() {
+                        // Backlog task with active research session — open tmux popup
+                        self.open_selected_task()?;
+                    } else if task.status == TaskStatus::Backlog {
+                        // Edit task
+                        self.state.editing_task_id = Some(task.id.clone());
+                        self.state.input_buffer = task.title.clone();
+                        self.state.input_cursor = self.state.input_buffer.len();
+                        self.state.pending_task_title.clear();
+                        self.state.input_mode = InputMode::InputTitle;
+                    } else if task.session_name.is_some() {
+                        // Open shell popup
+                        self.open_selected_task()?;
+                    }
+                }
+            }
+            KeyCode::Char('d') => self.show_task_diff()?,
+            KeyCode::Char('D') => self.show_branch_diff(),
+            KeyCode::Char('M') => {
+                if let Some(task_id) = self.state.board.selected_task().map(|t| t.id.clone()) {
+                    let result = self.move_backlog_to_running();
+                    self.record_failure(&task_id, result)?;
+                }
+            }
+            KeyCode::Char('R') => {
+                if let Some(task) = self.state.board.selected_task() {
+                    if task.status == TaskStatus::Backlog && task.session_name.is_none() {
+                        let task_id = task.id.clone();
+                        self.start_research(&task_id)?;
+                    }
+                }
+            }
+            KeyCode::Char('P') => {
+                // Open plugin selection popup
+                self.open_plugin_select_popup();
+            }
+            KeyCode::Char('S') => {
+                // Open board stats summary (recomputed each time it opens)
+                self.open_stats_popup();
+            }
+            KeyCode::Char('?') => self.state.help_popup = Some(HelpPopup::default()),
+            KeyCode::Char('p') => self.open_project_picker()?,
+            KeyCode::Char('B') => self.open_dependency_popup(),
+            KeyCode::Char('E') => self.open_edit_task_popup(),
+            KeyCode::Char('T') => self.open_trash_popup()?,
+            KeyCode::Char('A') => self.open_resume_all_popup(),
+            KeyCode::Char('i') => self.open_task_detail(false),
+            KeyCode::Char('n') => self.open_task_detail(true),
+            KeyCode::Char(' ') => self.state.board.toggle_mark_selected(),
+            // Esc dismisses the error bar first, then clears marks, then the active view and agent filter
+            KeyCode::Esc if self.state.error_bar.is_some() => self.state.error_bar = None,
+            KeyCode::Esc
+                if self.state.board.marked.is_empty()
+                    && (self.state.board.view.is_some() || self.state.board.agent_filter.is_some()) =>
+            {
+                self.state.board.view = None;
+                self.state.board.agent_filter = None;
+                self.state.board.clamp_row();
+            }
+            KeyCode::Esc => self.state.board.marked.clear(),
+            KeyCode::Char('z') => self.toggle_selected_archived()?,
+            KeyCode::Char('Z') => self.archive_all_done()?,
+            KeyCode::Char('v') => {
+                // Toggle archived task visibility
+                self.state.board.show_archived = !self.state.board.show_archived;
+                self.state.board.clamp_row();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Run a remappable board action (see `KeyMap`)
+    fn run_action(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::NewTask => {
+                if !self.repo_ready() {
+                    return Ok(());
+                }
+                self.state.pending_template = None;
+                if self.state.config.templates.is_empty() {
+                    self.open_new_task_popup();
+                } else {
+                    self.state.template_picker = Some(TemplatePickerPopup { selected: 0 });
+                }
+            }
+            Action::Delete => self.delete_selected_task()?,
+            Action::MoveForward if !self.state.board.marked.is_empty() => self.bulk_move_forward()?,
+            Action::MoveForward => self.move_task_right()?,
+            Action::Resume | Action::MoveBack => {
+                if let Some(task) = self.state.board.selected_task() {
+                    let task_id = task.id.clone();
+                    match (action, task.status) {
+                        // Move Review task back to Running (for PR changes)
+                        (Action::Resume, TaskStatus::Review) => {
+                            let result = self.move_review_to_running(&task_id);
+                            self.record_failure(&task_id, result)?;
+                        }
+                        // Move Running task back to Planning
+                        (Action::MoveBack, TaskStatus::Running) => {
+                            let result = self.move_running_to_planning(&task_id);
+                            self.record_failure(&task_id, result)?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Action::Search => {
+                self.state.task_search = Some(TaskSearchState {
+                    query: String::new(),
+                    matches: self.get_all_task_matches(""),
+                    selected: 0,
+                });
+            }
+            Action::Attach => self.attach_selected_task()?,
+            Action::CycleView => self.cycle_view(),
+            Action::CycleAgent => self.cycle_agent_filter(),
+            Action::TogglePin => self.toggle_selected_pinned()?,
+            Action::Duplicate => self.duplicate_selected_task()?,
+            Action::NextActionable => self.select_next_actionable(),
+            Action::Abort => self.open_abort_popup(),
+            Action::Abandon => self.open_abandon_popup(),
+            Action::ToggleAutomation => {
+                let message = toggle_automation(&self.state.automation_paused);
+                self.state.warning_message = Some((message, Instant::now()));
+                self.sync_scheduler();
+            }
+            Action::ToggleCollapse => {
+                let column = self.state.board.selected_column;
+                if !self.state.board.toggle_collapsed(column) {
+                    self.state.warning_message =
+                        Some(("The last expanded column can't be collapsed".to_string(), Instant::now()));
+                }
+            }
+            Action::SelectLeft => self.navigate(NavDirection::Left)?,
+            Action::SelectRight => self.navigate(NavDirection::Right)?,
+            Action::SelectUp => self.navigate(NavDirection::Up)?,
+            Action::SelectDown => self.navigate(NavDirection::Down)?,
+        }
+        Ok(())
+    }
+
+    /// Move the board selection, stepping left out of Backlog into the sidebar when it's shown
+    fn navigate(&mut self, dir: NavDirection) -> Result<()> {
+        if dir == NavDirection::Left && self.state.sidebar_visible && self.state.board.selected_column == 0 {
+            self.state.sidebar_focused = true;
+            self.refresh_projects()?;
+        } else {
+            self.state.board.move_selection(dir);
+        }
+        Ok(())
+    }
+
+    /// Switch the board to the next saved view, wrapping back to showing everything
+    fn cycle_view(&mut self) {
+        if self.state.config.views.is_empty() {
+            self.state.warning_message = Some(("No views configured ([[views]] in config)".to_string(), Instant::now()));
+            return;
+        }
+        let current = self.state.board.view.as_ref().map(|v| v.name.as_str());
+        let next = config::next_view(&self.state.config.views, current);
+        let message = match &next {
+            Some(view) => format!("View: {}", view.name),
+            None => "View cleared".to_string(),
+        };
+        self.state.board.view = next;
+        self.state.board.clamp_row();
+        self.state.warning_message = Some((message, Instant::now()));
+    }
+
+    /// Show only the next agent's tasks, wrapping back to all agents
+    fn cycle_agent_filter(&mut self) {
+        let next = board::next_agent_filter(&self.state.board.tasks, self.state.board.agent_filter.as_deref());
+        let message = match &next {
+            Some(agent) => format!("Agent: {}", agent),
+            None => "All agents".to_string(),
+        };
+        self.state.board.agent_filter = next;
+        self.state.board.clamp_row();
+        self.state.warning_message = Some((message, Instant::now()));
+    }
+
+    /// Hand the terminal to the selected task's tmux window; the board comes back on detach
+    fn attach_selected_task(&mut self) -> Result<()> {
+        let target = match attach_target(self.state.board.selected_task(), self.state.tmux_ops.as_ref()) {
+            Ok(target) => target,
+            Err(reason) => {
+                self.state.warning_message = Some((reason, Instant::now()));
+                return Ok(());
+            }
+        };
+
+        let Some(terminal) = self.terminal.as_mut() else { return Ok(()) };
+        disable_raw_mode()?;
+        execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        let attached = self.state.tmux_ops.attach(&target);
+        enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+        terminal.clear()?;
+
+        if let Err(e) = attached {
+            self.state.warning_message = Some((format!("{:#}", e), Instant::now()));
+        }
+        Ok(())
+    }
+
+    fn handle_title_input(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let has_alt = key.modifiers.contains(crossterm::event::KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Esc => {
+                self.state.input_mode = InputMode::Normal;
+                self.state.input_buffer.clear();
+                self.state.input_cursor = 0;
+                self.state.pending_task_title.clear();
+                self.state.editing_task_id = None;
+                self.state.pending_template = None;
+            }
+            KeyCode::Enter => {
+                if !self.state.input_buffer.is_empty() {
+                    // Save title and move to description input
+                    self.state.pending_task_title = self.state.input_buffer.clone();
+
+                    // Pre-fill the description of the task being edited
+                    self.state.input_buffer = match (&self.state.editing_task_id, &self.state.db) {
+                        (Some(task_id), Some(db)) => db
+                            .get_task(task_id)
+                            .ok()
+                            .flatten()
+                            .and_then(|task| task.description)
+                            .unwrap_or_default(),
+                        _ => String::new(),
+                    };
+
+                    self.state.input_cursor = self.state.input_buffer.len();
+                    self.state.input_mode = InputMode::InputDescription;
+                }
+            }
+            KeyCode::Left if has_alt => {
+                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
+            }
+            KeyCode::Right if has_alt => {
+                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
+            }
+            // macOS: Option+Left/Right sends Alt+b / Alt+f
+            KeyCode::Char('b') if has_alt => {
+                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
+            }
+            KeyCode::Char('f') if has_alt => {
+                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
+            }
+            KeyCode::Left => {
+                if self.state.input_cursor > 0 {
+                    self.state.input_cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                if self.state.input_cursor < self.state.input_buffer.len() {
+                    self.state.input_cursor += 1;
+                }
+            }
+            KeyCode::Home => {
+                self.state.input_cursor = 0;
+            }
+            KeyCode::End => {
+                self.state.input_cursor = self.state.input_buffer.len();
+            }
+            KeyCode::Backspace => {
+                if self.state.input_cursor > 0 {
+                    self.state.input_cursor -= 1;
+                    self.state.input_buffer.remove(self.state.input_cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if self.state.input_cursor < self.state.input_buffer.len() {
+                    self.state.input_buffer.remove(self.state.input_cursor);
+                }
+            }
+            KeyCode::Char(c) => {
+                self.state.input_buffer.insert(self.state.input_cursor, c);
+                self.state.input_cursor += 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_description_input(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        // Handle file search mode if active
+        if let Some(ref mut search) = self.state.file_search {
+            match key.code {
+                KeyCode::Esc => {
+                    // Cancel file search
+                    self.state.file_search = None;
+                }
+                KeyCode::Enter | KeyCode::Tab => {
+                    // Select current match
+                    if let Some(selected_file) = search.matches.get(search.selected).cloned() {
+                        // Replace trigger+pattern with the selected file path, preserving text after
+                        let pattern_end = search.start_pos + 1 + search.pattern.len(); // +1 for trigger char
+                        let suffix = self.state.input_buffer[pattern_end..].to_string();
+                        self.state.input_buffer.truncate(search.start_pos);
+                        self.state.input_buffer.push_str(&selected_file);
+                        self.state.input_cursor = self.state.input_buffer.len();
+                        self.state.input_buffer.push_str(&suffix);
+                        self.state.highlighted_file_paths.insert(selected_file);
+                    }
+                    self.state.file_search = None;
+                }
+                KeyCode::Up => {
+                    if search.selected > 0 {
+                        search.selected -= 1;
+                    }
+                }
+                KeyCode::Down => {
+                    if search.selected < search.matches.len().saturating_sub(1) {
+                        search.selected += 1;
+                    }
+                }
+                KeyCode::Char('k') | KeyCode::Char('p') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                    if search.selected > 0 {
+                        search.selected -= 1;
+                    }
+                }
+                KeyCode::Char('j') | KeyCode::Char('n') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
+                    if search.selected < search.matches.len().saturating_sub(1) {
+                        search.selected += 1;
+                    }
+                }
+                KeyCode::Backspace => {
+                    if search.pattern.is_empty() {
+                        // Cancel search if pattern is empty
+                        self.state.input_buffer.pop(); // Remove the trigger char
+                        self.state.input_cursor = self.state.input_cursor.saturating_sub(1);
+                        self.state.file_search = None;
+                    } else {
+                        search.pattern.pop();
+                        self.state.input_buffer.pop();
+                        self.state.input_cursor = self.state.input_cursor.saturating_sub(1);
+                        self.update_file_search_matches();
+                    }
+                }
+                KeyCode::Char(c) => {
+                    search.pattern.push(c);
+                    self.state.input_buffer.push(c);
+                    self.state.input_cursor += 1;
+                    self.update_file_search_matches();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                self.state.input_mode = InputMode::Normal;
+                self.state.input_buffer.clear();
+                self.state.input_cursor = 0;
+                self.state.pending_task_title.clear();
+                self.state.editing_task_id = None;
+                self.state.pending_template = None;
+                self.state.highlighted_file_paths.clear();
+            }
+            KeyCode::Enter => {
+                // Check if line ends with backslash for line continuation
+                if self.state.input_buffer.ends_with('\\') {
+                    // Remove backslash and insert newline
+                    self.state.input_buffer.pop();
+                    self.state.input_buffer.push('\n');
+                    self.state.input_cursor = self.state.input_buffer.len();
+                } else {
+                    // Save task (create or update)
+                    self.save_task()?;
+                    self.state.input_mode = InputMode::Normal;
+                    self.state.input_buffer.clear();
+                    self.state.input_cursor = 0;
+                    self.state.pending_task_title.clear();
+                    self.state.editing_task_id = None;
+                    self.state.pending_template = None;
+                    self.state.highlighted_file_paths.clear();
+                }
+            }
+            KeyCode::Left if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
+                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
+            }
+            KeyCode::Right if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
+                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
+            }
+            // macOS: Option+Left/Right sends Alt+b / Alt+f
+            KeyCode::Char('b') if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
+                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
+            }
+            KeyCode::Char('f') if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
+                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
+            }
+            KeyCode::Left => {
+                if self.state.input_cursor > 0 {
+                    self.state.input_cursor -= 1;
+                }
+            }
+            KeyCode::Right => {
+                if self.state.input_cursor < self.state.input_buffer.len() {
+                    self.state.input_cursor += 1;
+                }
+            }
+            KeyCode::Home => {
+                self.state.input_cursor = 0;
+            }
+            KeyCode::End => {
+                self.state.input_cursor = self.state.input_buffer.len();
             }
-            KeyCode::Up | KeyCode::BackTab => {
-                if let Some(ref mut search) = self.state.task_search {
-                    if search.selected > 0 {
-                        search.selected -= 1;
+            KeyCode::Backspace => {
+                if self.state.input_cursor > 0 {
+                    self.state.input_cursor -= 1;
+                    self.state.input_buffer.remove(self.state.input_cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if self.state.input_cursor < self.state.input_buffer.len() {
+                    self.state.input_buffer.remove(self.state.input_cursor);
+                }
+            }
+            KeyCode::Char('#') | KeyCode::Char('@') => {
+                // Start file search at cursor position
+                let trigger = if let KeyCode::Char(c) = key.code { c } else { '#' };
+                let start_pos = self.state.input_cursor;
+                self.state.input_buffer.insert(self.state.input_cursor, trigger);
+                self.state.input_cursor += 1;
+                self.state.file_search = Some(FileSearchState {
+                    pattern: String::new(),
+                    matches: vec![],
+                    selected: 0,
+                    start_pos,
+                    trigger_char: trigger,
+                });
+                self.update_file_search_matches();
+            }
+            KeyCode::Char(c) => {
+                self.state.input_buffer.insert(self.state.input_cursor, c);
+                self.state.input_cursor += 1;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn update_file_search_matches(&mut self) {
+        if let (Some(ref mut search), Some(ref project_path)) = (&mut self.state.file_search, &self.state.project_path) {
+            let pattern = &search.pattern;
+            search.matches = fuzzy_find_files(project_path, pattern, 10, self.state.git_ops.as_ref());
+            search.selected = 0;
+        }
+    }
+
+    /// Save the title and description of the task being edited (`editing_task_id`)
+    fn save_task(&mut self) -> Result<()> {
+        let (Some(db), Some(task_id)) = (&self.state.db, &self.state.editing_task_id) else { return Ok(()) };
+        if let Some(mut task) = db.get_task(task_id)? {
+            task.title = self.state.pending_task_title.clone();
+            task.description = if self.state.input_buffer.is_empty() {
+                None
+            } else {
+                Some(self.state.input_buffer.clone())
+            };
+            task.updated_at = chrono::Utc::now();
+            db.update_task(&task)?;
+        }
+        self.refresh_tasks()
+    }
+
+    fn delete_selected_task(&mut self) -> Result<()> {
+        if let Some(task) = self.state.board.selected_task().cloned() {
+            if !self.state.config.confirm_destructive {
+                return self.perform_delete_task(&task.id);
+            }
+            // Show confirmation popup
+            self.state.delete_confirm_popup = Some(DeleteConfirmPopup {
+                task_id: task.id.clone(),
+                task_title: task.title.clone(),
+                destroys: describe_destroyed_resources(&task, self.state.config.cleanup.policy(CleanupAction::Delete)),
+            });
+        }
+        Ok(())
+    }
+
+    fn perform_delete_task(&mut self, task_id: &str) -> Result<()> {
+        match self.trash_task(task_id) {
+            Ok(Some(task)) => {
+                self.state.warning_message = Some((
+                    format!("Moved '{}' to trash ([T] to restore)", task.title),
+                    Instant::now(),
+                ));
+                self.refresh_tasks()?;
+                self.state.board.clamp_row();
+            }
+            Ok(None) => {}
+            Err(e) => self.state.warning_message = Some((format!("{:#}", e), Instant::now())),
+        }
+        Ok(())
+    }
+
+    /// Remove a task's window and worktree, then move it to the trash.
+    /// `Ok(None)` if there's no such task (or no project open).
+    fn trash_task(&mut self, task_id: &str) -> Result<Option<Task>> {
+        let (Some(db), Some(project_path)) = (&self.state.db, &self.state.project_path) else {
+            return Ok(None);
+        };
+        let Some(mut task) = db.get_task(task_id)?.filter(|t| t.deleted_at.is_none()) else { return Ok(None) };
+        delete_task_resources(
+            &task,
+            project_path,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+            self.state.config.dirty_worktree_policy,
+            self.state.config.cleanup.policy(CleanupAction::Delete),
+        )
+        .context("Not deleted")?;
+        task.deleted_at = Some(chrono::Utc::now());
+        task.updated_at = chrono::Utc::now();
+        db.update_task(&task)?;
+        Ok(Some(task))
+    }
+
+    fn open_trash_popup(&mut self) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        self.state.trash_popup = Some(TrashPopup { tasks: db.get_trashed_tasks()?, selected: 0 });
+        Ok(())
+    }
+
+    fn handle_trash_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.trash_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('T') => {
+                self.state.trash_popup = None;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                popup.selected = (popup.selected + 1).min(popup.tasks.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                popup.selected = popup.selected.saturating_sub(1);
+            }
+            KeyCode::Enter | KeyCode::Char('r') => {
+                let Some(mut task) = popup.tasks.get(popup.selected).cloned() else { return Ok(()) };
+                task.restore_from_trash();
+                if let Some(db) = &self.state.db {
+                    db.update_task(&task)?;
+                }
+                self.state.warning_message = Some((format!("Restored '{}' to Backlog", task.title), Instant::now()));
+                self.refresh_tasks()?;
+                self.reload_trash_popup()?;
+            }
+            KeyCode::Char('P') => {
+                let Some(task) = popup.tasks.get(popup.selected).cloned() else { return Ok(()) };
+                if let Some(db) = &self.state.db {
+                    db.delete_task(&task.id)?;
+                }
+                self.state.warning_message = Some((format!("Permanently deleted '{}'", task.title), Instant::now()));
+                self.reload_trash_popup()?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Re-read the trash after a restore or purge, keeping the selection in range
+    fn reload_trash_popup(&mut self) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        let tasks = db.get_trashed_tasks()?;
+        if let Some(ref mut popup) = self.state.trash_popup {
+            popup.selected = popup.selected.min(tasks.len().saturating_sub(1));
+            popup.tasks = tasks;
+        }
+        Ok(())
+    }
+
+    /// Pin or unpin the selected task, keeping it selected as it moves within the column
+    fn toggle_selected_pinned(&mut self) -> Result<()> {
+        let Some(task) = self.state.board.selected_task() else { return Ok(()) };
+        let mut task = task.clone();
+        task.pinned = !task.pinned;
+        task.updated_at = chrono::Utc::now();
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.refresh_tasks()?;
+        if let Some((column, row)) = self.state.board.row_of(&task.id) {
+            self.state.board.selected_column = column;
+            self.state.board.selected_row = row;
+        }
+        Ok(())
+    }
+
+    /// Create a Backlog copy of the selected task (see `db::duplicate`) and select it
+    fn duplicate_selected_task(&mut self) -> Result<()> {
+        let Some(copy) = self.state.board.selected_task().map(db::duplicate) else { return Ok(()) };
+        if !self.repo_ready() {
+            return Ok(());
+        }
+        if let Some(db) = &self.state.db {
+            db.create_task(&copy)?;
+        }
+        self.refresh_tasks()?;
+        if let Some((column, row)) = self.state.board.row_of(&copy.id) {
+            self.state.board.selected_column = column;
+            self.state.board.selected_row = row;
+        }
+        Ok(())
+    }
+
+    /// Jump to the next task needing attention among those shown; pressing again from there
+    /// moves on to the next candidate
+    fn select_next_actionable(&mut self) {
+        let board = &self.state.board;
+        let mut current = board.selected_task().map(|t| t.id.clone());
+        // Ranked over every task so hidden dependencies still block, skipping the hidden ones
+        for _ in 0..board.tasks.len() {
+            let Some(id) = board::next_actionable_after(&board.tasks, current.as_deref()) else { break };
+            if let Some((column, row)) = board.row_of(&id) {
+                self.state.board.selected_column = column;
+                self.state.board.selected_row = row;
+                return;
+            }
+            current = Some(id);
+        }
+        self.state.warning_message = Some(("Nothing needs attention".to_string(), Instant::now()));
+    }
+
+    /// Archive (or unarchive, when archived tasks are shown) the selected Done task.
+    /// Purely a data change: Done tasks have no worktree or window left to clean up.
+    fn toggle_selected_archived(&mut self) -> Result<()> {
+        let Some(task) = self.state.board.selected_task() else { return Ok(()) };
+        if task.status != TaskStatus::Done {
+            self.state.warning_message = Some(("Only Done tasks can be archived".to_string(), Instant::now()));
+            return Ok(());
+        }
+        let mut task = task.clone();
+        task.archived = !task.archived;
+        task.updated_at = chrono::Utc::now();
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.refresh_tasks()?;
+        self.state.board.clamp_row();
+        Ok(())
+    }
+
+    fn archive_all_done(&mut self) -> Result<()> {
+        if let Some(db) = &self.state.db {
+            let count = db.archive_done_tasks()?;
+            self.state.warning_message = Some((format!("Archived {} Done task(s)", count), Instant::now()));
+        }
+        self.refresh_tasks()?;
+        self.state.board.clamp_row();
+        Ok(())
+    }
+
+    fn show_task_diff(&mut self) -> Result<()> {
+        if let Some(task) = self.state.board.selected_task() {
+            let diff_content = if let Some(worktree_path) = &task.worktree_path {
+                let mut exclude_prefixes: Vec<&str> = crate::git::AGENT_CONFIG_DIRS.to_vec();
+                let plugin = self.load_task_plugin(task);
+                let plugin_dirs: Vec<String> = plugin.map_or_else(Vec::new, |p| p.copy_dirs.clone());
+                let plugin_dir_refs: Vec<&str> = plugin_dirs.iter().map(|s| s.as_str()).collect();
+                exclude_prefixes.extend(plugin_dir_refs);
+                collect_task_diff(worktree_path, self.state.git_ops.as_ref(), &exclude_prefixes)
+            } else {
+                "(task has no worktree yet)".to_string()
+            };
+
+            self.state.diff_popup = Some(DiffPopup {
+                task_title: task.title.clone(),
+                diff_content,
+                scroll_offset: 0,
+            });
+        }
+        Ok(())
+    }
+
+    fn move_task_right(&mut self) -> Result<()> {
+        let Some(task) = self.state.board.selected_task().cloned() else {
+            return Ok(());
+        };
+        if self.state.worktrees_in_flight.contains(&task.id) {
+            self.state.warning_message = Some((format!("'{}' is still getting its worktree", task.title), Instant::now()));
+            return Ok(());
+        }
+        if self.start_worktree_in_background(&task) {
+            return Ok(());
+        }
+        if let Some(new_status) = next_status(task.status) {
+            self.move_task_forward(&task.id)
+                .with_context(|| format!("Moving '{}' to {}", task.title, new_status.as_str()))?;
+        }
+        Ok(())
+    }
+
+    /// Move every marked task one column to the right, exactly as `move_task_right` would.
+    /// Tasks that can't move are skipped and listed in the summary.
+    fn bulk_move_forward(&mut self) -> Result<()> {
+        let ids: Vec<String> = self.state.board.marked_tasks().iter().map(|t| t.id.clone()).collect();
+        self.prepare_worktrees(&ids);
+        let mut summary = BulkMoveSummary::default();
+        for id in ids {
+            let Some(task) = self.state.board.tasks.iter().find(|t| t.id == id).cloned() else {
+                continue;
+            };
+            // Only one confirmation popup can be open; later tasks have to be moved on their own
+            if self.confirmation_pending() && move_may_prompt(task.status) {
+                summary.skipped.push((task.title, "waiting on another confirmation".to_string()));
+                continue;
+            }
+            match self.move_task_forward(&id) {
+                Ok(MoveOutcome::Moved) => summary.moved += 1,
+                Ok(MoveOutcome::Prompted) => summary.prompted.push(task.title),
+                Ok(MoveOutcome::Skipped(reason)) => summary.skipped.push((task.title, reason)),
+                Err(e) => summary.skipped.push((task.title, e.to_string())),
+            }
+        }
+        self.discard_prepared_worktrees();
+        self.state.board.marked.clear();
+        self.state.warning_message = Some((summary.message(), Instant::now()));
+        Ok(())
+    }
+
+    /// Create the worktrees of the Backlog tasks among `ids` concurrently, for `advance_task` to
+    /// pick up. A task whose worktree fails here just goes through the normal serial path,
+    /// which retries it (parallel `git worktree add`s on one repository can trip over its locks).
+    fn prepare_worktrees(&mut self, ids: &[String]) {
+        let Some(project_path) = self.state.project_path.clone() else { return };
+        if self.state.repo_problem.is_some() {
+            return;
+        }
+        let slugs: Vec<(String, String)> = self
+            .state
+            .board
+            .tasks
+            .iter()
+            .filter(|t| ids.contains(&t.id) && t.status == TaskStatus::Backlog)
+            // Tasks branching from their own ref have it checked on the serial path
+            .filter(|t| t.from_ref.is_none())
+            // Tasks with a research window keep it and don't get a fresh worktree here
+            .filter(|t| {
+                !t.session_name.as_deref().is_some_and(|s| self.state.tmux_ops.window_exists(s).unwrap_or(false))
+            })
+            .map(|t| {
+                let slug = slug::unique_slug(&t.id, &t.title, |s| self.state.git_ops.worktree_exists(&project_path, s));
+                (t.id.clone(), slug)
+            })
+            .collect();
+        if slugs.len() < 2 {
+            return;
+        }
+        let base_branch = self.base_branch();
+        let _activity = self.begin_activity(format!("Creating {} worktrees", slugs.len()));
+        for (task_id, result) in create_worktrees_parallel(&project_path, &base_branch, &slugs, self.state.git_ops.as_ref()) {
+            match result {
+                Ok(path) => {
+                    self.state.prepared_worktrees.insert(task_id, path);
+                }
+                Err(e) => error_log::log("Parallel worktree creation failed, retrying serially", &e.to_string()),
+            }
+        }
+    }
+
+    /// Create the worktree of a Backlog task about to move on a worker thread, so the board
+    /// keeps drawing; `finish_background_move` makes the move once it's there. Returns false
+    /// when the move should just run now: the task isn't in Backlog, its move would be refused,
+    /// or it keeps a research window or branches from its own ref (checked on the serial path).
+    fn start_worktree_in_background(&mut self, task: &Task) -> bool {
+        let Some(project_path) = self.state.project_path.clone() else { return false };
+        if task.status != TaskStatus::Backlog
+            || task.from_ref.is_some()
+            || self.state.repo_problem.is_some()
+            || workflow::request_transition(task, TaskStatus::Planning).is_err()
+            || task.session_name.as_deref().is_some_and(|s| self.state.tmux_ops.window_exists(s).unwrap_or(false))
+        {
+            return false;
+        }
+        let slug = slug::unique_slug(&task.id, &task.title, |s| self.state.git_ops.worktree_exists(&project_path, s));
+        let base_branch = self.base_branch();
+        let git_ops = Arc::clone(&self.state.git_ops);
+        let tx = self.state.worktree_tx.clone();
+        let task_id = task.id.clone();
+        let activity = self.state.activities.reporter().start(format!("Creating worktree for '{}'", task.title));
+        self.state.worktrees_in_flight.insert(task.id.clone());
+        std::thread::spawn(move || {
+            let _activity = activity;
+            let result = git_ops.create_worktree(&project_path, &slug, &base_branch);
+            let _ = tx.send((project_path, task_id, result));
+        });
+        true
+    }
+
+    /// Make the move `start_worktree_in_background` started. A failed worktree is retried by
+    /// the move itself, which reports it; one for a project that's no longer open is removed.
+    fn finish_background_move(
+        &mut self,
+        project_path: &Path,
+        task_id: &str,
+        result: crate::op_error::OpResult<String>,
+    ) -> Result<()> {
+        self.state.worktrees_in_flight.remove(task_id);
+        if self.state.project_path.as_deref() != Some(project_path) {
+            if let Ok(worktree) = result {
+                discard_worktree(project_path, &worktree, self.state.git_ops.as_ref());
+            }
+            return Ok(());
+        }
+        match result {
+            Ok(worktree) => {
+                self.state.prepared_worktrees.insert(task_id.to_string(), worktree);
+            }
+            Err(e) => error_log::log("Background worktree creation failed, retrying", &e.to_string()),
+        }
+        let title = self.state.board.tasks.iter().find(|t| t.id == task_id).map(|t| t.title.clone());
+        let moved = self.move_task_forward(task_id);
+        // The task was deleted or moved meanwhile: nothing used the worktree
+        self.discard_prepared_worktrees();
+        if let Err(e) = moved.with_context(|| format!("Moving '{}' to Planning", title.unwrap_or_default())) {
+            self.report_error(&e);
+        }
+        self.refresh_tasks()
+    }
+
+    /// Remove prepared worktrees whose task didn't end up moving, so none are left behind
+    fn discard_prepared_worktrees(&mut self) {
+        let Some(project_path) = self.state.project_path.clone() else { return };
+        for worktree in std::mem::take(&mut self.state.prepared_worktrees).into_values() {
+            discard_worktree(&project_path, &worktree, self.state.git_ops.as_ref());
+        }
+    }
+
+    /// Run the `[hooks]` for a transition whose side effects are done. Failures are logged and
+    /// shown as a warning, or with `strict` returned so the caller leaves the task where it was.
+    /// Once the hooks let it through, the destination column's on-enter prompt is sent to the
+    /// task's window in the background. The caller posts the `[webhook]` after saving the move.
+    fn run_hooks(&mut self, task: &Task, from: TaskStatus, to: TaskStatus) -> Result<()> {
+        let Some(project_path) = self.state.project_path.clone() else { return Ok(()) };
+        let failed = run_transition_hooks(&self.state.config, self.state.hook_runner.as_ref(), &project_path, task, from, to)?;
+        self.report_failed_hooks(&task.id, &failed);
+        if let Some(start) = on_enter_start(&self.state.config, task, to) {
+            let tmux_ops = Arc::clone(&self.state.tmux_ops);
+            std::thread::spawn(move || send_phase_start(tmux_ops.as_ref(), &start));
+        }
+        Ok(())
+    }
+
+    /// Post the `[webhook]` for a move that's been saved, in the background
+    fn post_webhook(&self, task: &Task, from: TaskStatus) {
+        webhook::spawn_delivery(&self.state.config.webhook, webhook::Payload::new(task, from, task.status));
+    }
+
+    /// Log hooks that failed without `strict` in the task's history and warn about them
+    fn report_failed_hooks(&mut self, task_id: &str, failed: &[String]) {
+        for name in failed {
+            self.log_task_event(task_id, TaskEventKind::Failed, format!("hook '{}'", name));
+        }
+        if !failed.is_empty() {
+            self.state.warning_message = Some((
+                format!("Hook {} failed (see errors.log)", failed.join(", ")),
+                Instant::now(),
+            ));
+        }
+    }
+
+    /// Start the configured `test_command` in the worktree of a task that just entered Review.
+    /// It runs in the background and never holds up the move; `record_review_tests` stores the
+    /// result.
+    fn start_review_tests(&mut self, task_id: &str) -> Result<()> {
+        let Some(command) = self.state.config.test_command.clone() else { return Ok(()) };
+        let (Some(db), Some(project_path)) = (&self.state.db, self.state.project_path.clone()) else {
+            return Ok(());
+        };
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        let Some(worktree) = task.worktree_path.clone().filter(|wt| Path::new(wt).is_dir()) else {
+            return Ok(());
+        };
+        // The previous result no longer applies
+        task.test_status = None;
+        db.update_task(&task)?;
+        self.state.review_tests_running.insert(task.id.clone());
+
+        let runner = Arc::clone(&self.state.hook_runner);
+        let tx = self.state.review_test_tx.clone();
+        let activity = self.state.activities.reporter().start(format!("Running tests for '{}'", task.title));
+        std::thread::spawn(move || {
+            let _activity = activity;
+            let passed = run_review_tests(&command, Path::new(&worktree), runner.as_ref());
+            let _ = tx.send((project_path, task.id, passed));
+        });
+        Ok(())
+    }
+
+    /// Save a finished review test run on its task (which may be in another project by now)
+    fn record_review_tests(&mut self, project_path: &Path, task_id: &str, passed: bool) -> Result<()> {
+        self.state.review_tests_running.remove(task_id);
+        let db = Database::open_project(project_path)?;
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        task.test_status = Some(passed);
+        db.update_task(&task)?;
+        let result = if passed { "passed" } else { "failed" };
+        db.add_task_event(task_id, &TaskEvent::new(TaskEventKind::TestsRun, result))?;
+
+        let message = if passed {
+            format!("Tests passed for '{}'", task.title)
+        } else {
+            format!("Tests failed for '{}' (see errors.log)", task.title)
+        };
+        self.state.warning_message = Some((message, Instant::now()));
+        if self.state.project_path.as_deref() == Some(project_path) {
+            self.refresh_tasks()?;
+        }
+        Ok(())
+    }
+
+    /// Show `label` in the footer, drawn right away, for as long as the returned guard lives.
+    /// Work done synchronously on this thread would otherwise show nothing until it's finished;
+    /// the line has no spinner, since the footer can't redraw until the guard is dropped.
+    fn begin_activity(&mut self, label: impl Into<String>) -> ActivityGuard {
+        let guard = self.state.activities.reporter().start_blocking(label);
+        self.state.activities.drain();
+        if let Err(e) = self.draw() {
+            error_log::log("Drawing progress", &e.to_string());
+        }
+        guard
+    }
+
+    /// Show a failed operation in the error bar and append it to the error log
+    fn report_error(&mut self, error: &anyhow::Error) {
+        let report = ErrorReport::from_error(error);
+        error_log::log(&report.action, &report.message);
+        self.state.error_bar = Some(report);
+    }
+
+    /// Whether a popup is waiting on the user before a move can finish
+    fn confirmation_pending(&self) -> bool {
+        self.state.review_confirm_popup.is_some()
+            || self.state.done_confirm_popup.is_some()
+            || self.state.pr_status_popup.is_some()
+    }
+
+    /// Advance one task to the next column (see `advance_task`); a failure goes in its history
+    fn move_task_forward(&mut self, task_id: &str) -> Result<MoveOutcome> {
+        let result = self.advance_task(task_id);
+        self.record_failure(task_id, result)
+    }
+
+    /// Add a failed operation to the task's history and pass the result on
+    fn record_failure<T>(&self, task_id: &str, result: Result<T>) -> Result<T> {
+        if let Err(ref e) = result {
+            self.log_task_event(task_id, TaskEventKind::Failed, format!("{:#}", e));
+        }
+        result
+    }
+
+    /// Add an entry to a task's history. Best effort: history never blocks the board.
+    fn log_task_event(&self, task_id: &str, kind: TaskEventKind, detail: impl Into<String>) {
+        if let Some(db) = &self.state.db {
+            let _ = db.add_task_event(task_id, &TaskEvent::new(kind, detail));
+        }
+    }
+
+    /// Record the worktree and window a setup just created
+    fn log_setup_events(&self, task: &Task) {
+        if let Some(ref worktree) = task.worktree_path {
+            self.log_task_event(&task.id, TaskEventKind::WorktreeCreated, worktree.clone());
+        }
+        if let Some(ref target) = task.session_name {
+            self.log_task_event(&task.id, TaskEventKind::WindowCreated, target.clone());
+        }
+    }
+
+    /// Advance one task to the next column with `advance_task`, layering on what needs the
+    /// board: the blocked-task check, PR and Done confirmations, and the dispatch queue
+    fn advance_task(&mut self, task_id: &str) -> Result<MoveOutcome> {
+        // Clone task to avoid borrow issues
+        let (mut task, project_path) = match (
+            self.state.board.tasks.iter().find(|t| t.id == task_id).cloned(),
+            self.state.project_path.clone(),
+        ) {
+            (Some(t), Some(p)) => (t, p),
+            _ => return Ok(MoveOutcome::Skipped("task not found".to_string())),
+        };
+
+        let current_status = task.status;
+        let agent_name = self.state.agent_for(&task);
+        if current_status == TaskStatus::Backlog && !self.repo_ready() {
+            return Ok(MoveOutcome::Skipped(self.state.repo_problem.clone().unwrap_or_default()));
+        }
+        let Some(new_status) = next_status(current_status) else {
+            return Ok(MoveOutcome::Skipped("already Done".to_string()));
+        };
+        if let Err(e) = workflow::request_transition(&task, new_status) {
+            return Ok(MoveOutcome::Skipped(e.to_string()));
+        }
+
+        if new_status == TaskStatus::Running && self.report_if_blocked(&task) {
+            return Ok(MoveOutcome::Skipped("blocked by unfinished tasks".to_string()));
+        }
+
+        // When moving from Running to Review: send the review command, then ask about a PR,
+        // whose popup finishes the move
+        if current_status == TaskStatus::Running {
+            self.advance_on_board(&mut task, &project_path, false)?;
+            // Check if PR already exists (task was resumed from Review)
+            if task.pr_number.is_some() {
+                // PR already exists - just commit and push the new changes
+                self.state.pr_status_popup = Some(PrStatusPopup {
+                    status: PrCreationStatus::Pushing,
+                    pr_url: None,
+                    error_message: None,
+                });
+
+                let task_clone = task.clone();
+                let project_path_clone = project_path.clone();
+                let git_ops = Arc::clone(&self.state.git_ops);
+                let agent_ops = self.state.agent_registry.get(&agent_name);
+                let hook_runner = Arc::clone(&self.state.hook_runner);
+                let hooks_config = self.state.config.hooks.clone();
+                let webhook_config = self.state.config.webhook.clone();
+                let activity = self.state.activities.reporter().start(format!("Pushing '{}'", task.title));
+
+                let (tx, rx) = mpsc::channel();
+                self.state.pr_creation_rx = Some(rx);
+                self.state.review_tests_after_pr = Some(task.id.clone());
+
+                std::thread::spawn(move || {
+                    let _activity = activity;
+                    let result = push_changes_to_existing_pr(&task_clone, git_ops.as_ref(), agent_ops.as_ref());
+                    match result {
+                        Ok(pr_url) => {
+                            // Update task in database
+                            // Keep session_name so popup can still be opened in Review
+                            match save_review_from_thread(task_clone, &project_path_clone, &hooks_config, hook_runner.as_ref(), &webhook_config) {
+                                Ok(()) => {
+                                    let _ = tx.send(Ok((0, pr_url)));
+                                }
+                                Err(e) => {
+                                    let _ = tx.send(Err(format!("Changes pushed ({}), but {:#}", pr_url, e)));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Err(e.to_string()));
+                        }
                     }
-                }
-                false
+                });
+
+                // Keep tmux window open - session_name stays set for resume
+
+                return Ok(MoveOutcome::Prompted);
             }
-            KeyCode::Down | KeyCode::Tab => {
-                if let Some(ref mut search) = self.state.task_search {
-                    if search.selected < search.matches.len().saturating_sub(1) {
-                        search.selected += 1;
-                    }
-                }
-                false
+
+            // No PR yet - show confirmation popup asking if user wants to create PR
+            self.state.review_confirm_popup = Some(ReviewConfirmPopup {
+                task_id: task.id.clone(),
+                task_title: task.title.clone(),
+            });
+            return Ok(MoveOutcome::Prompted);
+        }
+
+        // When moving from Review to Done: Show confirmation with PR state
+        if current_status == TaskStatus::Review {
+            if let Some(pr_number) = task.pr_number {
+                let pr_state = self.state.git_provider_ops.get_pr_state(&project_path, pr_number)?;
+
+                let confirm_state = match pr_state {
+                    PullRequestState::Merged => DoneConfirmPrState::Merged,
+                    PullRequestState::Closed => DoneConfirmPrState::Closed,
+                    PullRequestState::Open => DoneConfirmPrState::Open,
+                    PullRequestState::Unknown => DoneConfirmPrState::Unknown,
+                };
+
+                self.state.done_confirm_popup = Some(DoneConfirmPopup {
+                    task_id: task.id.clone(),
+                    pr_number,
+                    pr_state: confirm_state,
+                    destroys: describe_destroyed_resources(&task, self.state.config.cleanup.policy(CleanupAction::Done)),
+                });
+                return Ok(MoveOutcome::Prompted);
             }
-            KeyCode::Char('k') | KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(ref mut search) = self.state.task_search {
-                    if search.selected > 0 {
-                        search.selected -= 1;
-                    }
-                }
-                false
+            // No PR - confirm before killing the window / removing the worktree
+            if needs_destroy_confirmation(&task, self.state.config.confirm_destructive, self.state.config.cleanup.policy(CleanupAction::Done)) {
+                self.state.done_confirm_popup = Some(DoneConfirmPopup {
+                    task_id: task.id.clone(),
+                    pr_number: 0,
+                    pr_state: DoneConfirmPrState::NoPr,
+                    destroys: describe_destroyed_resources(&task, self.state.config.cleanup.policy(CleanupAction::Done)),
+                });
+                return Ok(MoveOutcome::Prompted);
             }
-            KeyCode::Char('j') | KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(ref mut search) = self.state.task_search {
-                    if search.selected < search.matches.len().saturating_sub(1) {
-                        search.selected += 1;
-                    }
-                }
-                false
+        }
+
+        let _activity = (current_status == TaskStatus::Backlog).then(|| {
+            let label = if self.state.prepared_worktrees.contains_key(&task.id) {
+                format!("Setting up worktree for '{}'", task.title)
+            } else {
+                format!("Creating worktree for '{}'", task.title)
+            };
+            self.begin_activity(label)
+        });
+        if let Err(e) = self.advance_on_board(&mut task, &project_path, true) {
+            // Keep track of what the move set up (or tore down) before it stopped, but not the status
+            if let Some(db) = &self.state.db {
+                db.update_task(&task)?;
+            }
+            if current_status == TaskStatus::Review {
+                self.refresh_tasks()?;
+                self.state.warning_message = Some((format!("Not moved to Done: {:#}", e), Instant::now()));
+                return Ok(MoveOutcome::Skipped(format!("not moved to Done: {:#}", e)));
+            }
+            return Err(e);
+        }
+
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.post_webhook(&task, current_status);
+        self.refresh_tasks()?;
+        Ok(MoveOutcome::Moved)
+    }
+
+    /// Run `advance_task` with the board's `MoveHooks`, or with `finish` false only the side
+    /// effects (`move_side_effects`) when a popup saves the new status later. What the hooks
+    /// collected goes into the task's history and the footer.
+    fn advance_on_board(&mut self, task: &mut Task, project_path: &Path, finish: bool) -> Result<()> {
+        let agent_ops = self.state.agent_registry.get(&self.state.agent_for(task));
+        let worktree_before = task.worktree_path.clone();
+        let mut hooks = BoardMove {
+            tmux_ops: Arc::clone(&self.state.tmux_ops),
+            scheduler: &mut self.state.scheduler,
+            prepared: self.state.prepared_worktrees.remove(&task.id),
+            interrupted: Arc::clone(&self.state.interrupted),
+            project: self.state.project_name.clone(),
+            project_path,
+            config: &self.state.config,
+            detected_base_branch: &mut self.state.detected_base_branch,
+            hook_runner: self.state.hook_runner.as_ref(),
+            events: Vec::new(),
+            notices: Vec::new(),
+            failed_hooks: Vec::new(),
+        };
+        let git_ops = self.state.git_ops.as_ref();
+        let tmux_ops = self.state.tmux_ops.as_ref();
+        let result = if finish {
+            advance_task(task, project_path, git_ops, tmux_ops, agent_ops.as_ref(), &self.state.config, &mut hooks)
+        } else {
+            move_side_effects(task, project_path, git_ops, tmux_ops, agent_ops.as_ref(), &self.state.config, &mut hooks)
+                .map(|_| ())
+        };
+        let BoardMove { events, notices, failed_hooks, .. } = hooks;
+        self.save_dispatch_queues();
+
+        for (kind, detail) in events {
+            self.log_task_event(&task.id, kind, detail);
+        }
+        if let Some(notice) = notices.into_iter().last() {
+            self.state.warning_message = Some((notice, Instant::now()));
+        }
+        self.report_failed_hooks(&task.id, &failed_hooks);
+        if task.worktree_path.is_some() && task.worktree_path != worktree_before {
+            self.log_setup_events(task);
+            self.warn_if_subdir_missing(task);
+        }
+        result
+    }
+
+    /// Start a research session for a Backlog task (creates worktree, reused in planning)
+    fn start_research(&mut self, task_id: &str) -> Result<()> {
+        if !self.repo_ready() {
+            return Ok(());
+        }
+        let mut task = {
+            let Some(db) = &self.state.db else { return Ok(()) };
+            let Some(task) = db.get_task(task_id)? else { return Ok(()) };
+            task
+        };
+        let Some(project_path) = self.state.project_path.clone() else { return Ok(()) };
+        // Stamp plugin on task for research
+        task.plugin = self.state.config.workflow_plugin.clone();
+        let plugin = self.load_task_plugin(&task);
+        let agent_name = self.state.agent_for(&task);
+
+        let prompt = self.phase_prompt(&plugin, "research", &task, &agent_name);
+
+        // Create worktree + tmux window (same as planning, so it can be reused)
+        let base_branch = self.base_branch();
+        let window = WindowSetup::for_task(&self.state.config, &task);
+        let _activity = self.begin_activity(format!("Creating worktree for '{}'", task.title));
+        let session = self.state.tmux_session();
+        let setup = WorktreeSetup {
+            project_path: &project_path,
+            base_branch: &base_branch,
+            session: &session,
+            prompt: &prompt,
+            copy_files: self.state.config.copy_files.clone(),
+            init_script: self.state.config.init_script.clone(),
+            plugin: &plugin,
+            agent_name: &agent_name,
+            window: &window,
+        };
+        let target = setup_task_worktree(
+            &mut task,
+            &setup,
+            None,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+            self.state.agent_registry.get(&agent_name).as_ref(),
+            &self.state.interrupted,
+        )?;
+        self.log_setup_events(&task);
+        self.warn_if_subdir_missing(&task);
+
+        // Send research skill command + task prompt (with optional trigger polling), rendered
+        // now that the worktree path is known
+        let start = PhaseStart::for_phase(&plugin, &self.state.config.prompts, "research", &task, &agent_name, target);
+        let tmux_ops = Arc::clone(&self.state.tmux_ops);
+        std::thread::spawn(move || send_phase_start(tmux_ops.as_ref(), &start));
+
+        // Store session name — task stays in Backlog
+        task.updated_at = chrono::Utc::now();
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.refresh_tasks()?;
+
+        Ok(())
+    }
+
+    /// Move task directly from Backlog to Running (skip Planning)
+    fn move_backlog_to_running(&mut self) -> Result<()> {
+        let (mut task, project_path) = match (
+            self.state.board.selected_task().cloned(),
+            self.state.project_path.clone(),
+        ) {
+            (Some(t), Some(p)) => (t, p),
+            _ => return Ok(()),
+        };
+
+        if task.status != TaskStatus::Backlog {
+            return Ok(());
+        }
+        workflow::request_transition(&task, TaskStatus::Running)?;
+        if !self.repo_ready() || self.report_if_blocked(&task) {
+            return Ok(());
+        }
+        let agent_name = self.state.agent_for(&task);
+
+        // Build prompt - skip planning, go straight to implementation
+        let dispatch = self.state.config.should_dispatch(TaskStatus::Running);
+        let prompt = if dispatch {
+            self.state.config.prompts.wrap(with_scope_note(
+                skills::render_prompt(skills::DEFAULT_PROMPT_RUNNING_DIRECT, &task, ""),
+                &task,
+            ))
+        } else {
+            String::new()
+        };
+
+        // Stamp plugin on task
+        task.plugin = self.state.config.workflow_plugin.clone();
+        let plugin = self.load_task_plugin(&task);
+        let base_branch = self.base_branch();
+        let window = WindowSetup::for_task(&self.state.config, &task);
+        let _activity = self.begin_activity(format!("Creating worktree for '{}'", task.title));
+        let session = self.state.tmux_session();
+        let setup = WorktreeSetup {
+            project_path: &project_path,
+            base_branch: &base_branch,
+            session: &session,
+            prompt: &prompt,
+            copy_files: self.state.config.copy_files.clone(),
+            init_script: self.state.config.init_script.clone(),
+            plugin: &plugin,
+            agent_name: &agent_name,
+            window: &window,
+        };
+        let target = setup_task_worktree(
+            &mut task,
+            &setup,
+            None,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+            self.state.agent_registry.get(&agent_name).as_ref(),
+            &self.state.interrupted,
+        )?;
+        self.log_setup_events(&task);
+        self.warn_if_subdir_missing(&task);
+
+        // Wait for agent to be ready, then send execute skill command and task content
+        if dispatch {
+            let start = PhaseStart {
+                target,
+                skill_cmd: resolve_skill_command(&plugin, "running", &agent_name, &task_content(&task)),
+                prompt: prompt.clone(),
+                trigger: resolve_prompt_trigger(&plugin, "running"),
+                prefill: None,
+                wait_ready: true,
+            };
+            let tmux_ops = Arc::clone(&self.state.tmux_ops);
+            std::thread::spawn(move || send_phase_start(tmux_ops.as_ref(), &start));
+        }
+
+        if let Err(e) = self.run_hooks(&task, TaskStatus::Backlog, TaskStatus::Running) {
+            // Blocked by a strict hook: keep track of the new worktree and window
+            if let Some(db) = &self.state.db {
+                db.update_task(&task)?;
+            }
+            return Err(e);
+        }
+
+        task.status = TaskStatus::Running;
+        task.updated_at = chrono::Utc::now();
+
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.post_webhook(&task, TaskStatus::Backlog);
+        self.refresh_tasks()?;
+        Ok(())
+    }
+
+    /// Move task from Review back to Running (only allowed transition backwards)
+    /// The tmux window is normally still open from when it was in Running state; if it was
+    /// closed, the agent is restarted (see `restart_agent_window`)
+    fn move_review_to_running(&mut self, task_id: &str) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        if !resume_from_review(&mut task) {
+            return Ok(());
+        }
+        self.reopen_agent_window(&mut task, "")?;
+        self.run_hooks(&task, TaskStatus::Review, TaskStatus::Running)?;
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.post_webhook(&task, TaskStatus::Review);
+        self.refresh_tasks()?;
+        Ok(())
+    }
+
+    /// Normally a task resumed from Review still has its agent running in its window. If the
+    /// window was closed, bring the agent back, in its previous conversation when one was
+    /// recorded (see `restart_agent_window`), and send it a non-empty `follow_up` once it's
+    /// ready. Returns whether the window was reopened.
+    fn reopen_agent_window(&mut self, task: &mut Task, follow_up: &str) -> Result<bool> {
+        let window_alive = task.session_name.as_deref()
+            .is_some_and(|target| self.state.tmux_ops.window_exists(target).unwrap_or(false));
+        let Some(project_path) = self.state.project_path.clone().filter(|_| !window_alive) else {
+            return Ok(false);
+        };
+        let agent_name = self.state.agent_for(task);
+        let plugin = self.load_task_plugin(task);
+        let prompt = self.phase_prompt(&plugin, "running", task, &agent_name);
+        let window = WindowSetup::for_task(&self.state.config, task);
+        let resumed = restart_agent_window(
+            task,
+            &self.state.tmux_session(),
+            &project_path,
+            &prompt,
+            &window,
+            self.state.tmux_ops.as_ref(),
+            self.state.agent_registry.get(&agent_name).as_ref(),
+        )
+        .with_context(|| format!("Resuming '{}'", task.title))?;
+        let detail = if resumed { "reopened in the previous conversation" } else { "reopened with a new session" };
+        self.log_task_event(&task.id, TaskEventKind::WindowCreated, detail);
+        let message = if resumed {
+            format!("Reopened '{}' in its previous {} conversation", task.title, agent_name)
+        } else {
+            format!("Window was gone; started a new {} session for '{}'", agent_name, task.title)
+        };
+        self.state.warning_message = Some((message, Instant::now()));
+        self.state.resource_mismatches.remove(&task.id);
+        self.warn_if_subdir_missing(task);
+
+        if let Some(target) = task.session_name.clone().filter(|_| !follow_up.is_empty()) {
+            let start = PhaseStart { target, prompt: follow_up.to_string(), wait_ready: true, ..Default::default() };
+            let tmux_ops = Arc::clone(&self.state.tmux_ops);
+            std::thread::spawn(move || send_phase_start(tmux_ops.as_ref(), &start));
+        }
+        Ok(true)
+    }
+
+    fn open_resume_all_popup(&mut self) {
+        let count = self.state.board.tasks.iter().filter(|t| t.status == TaskStatus::Review).count();
+        if count == 0 {
+            self.state.warning_message = Some(("No tasks in Review to resume".to_string(), Instant::now()));
+            return;
+        }
+        self.state.resume_all_popup = Some(ResumeAllPopup { count, follow_up: String::new() });
+    }
+
+    fn handle_resume_all_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.resume_all_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc => self.state.resume_all_popup = None,
+            KeyCode::Backspace => {
+                popup.follow_up.pop();
+            }
+            KeyCode::Char(c) => popup.follow_up.push(c),
+            KeyCode::Enter => {
+                let follow_up = popup.follow_up.clone();
+                self.state.resume_all_popup = None;
+                self.resume_all_review_tasks(&follow_up)?;
             }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn open_abort_popup(&mut self) {
+        let Some(task) = self.state.board.selected_task() else { return };
+        if task.session_name.is_none() || task.status == TaskStatus::Done {
+            self.state.warning_message = Some(("This task has no agent window to interrupt".to_string(), Instant::now()));
+            return;
+        }
+        self.state.abort_popup =
+            Some(AbortPopup { task_id: task.id.clone(), title: task.title.clone(), follow_up: String::new() });
+    }
+
+    fn handle_abort_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.abort_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc => self.state.abort_popup = None,
             KeyCode::Backspace => {
-                if let Some(ref mut search) = self.state.task_search {
-                    search.query.pop();
-                }
-                let query = self.state.task_search.as_ref().map(|s| s.query.clone()).unwrap_or_default();
-                let matches = self.get_all_task_matches(&query);
-                if let Some(ref mut search) = self.state.task_search {
-                    search.matches = matches;
-                    search.selected = 0;
-                }
-                false
+                popup.follow_up.pop();
             }
-            KeyCode::Char(c) => {
-                if let Some(ref mut search) = self.state.task_search {
-                    search.query.push(c);
-                }
-                let query = self.state.task_search.as_ref().map(|s| s.query.clone()).unwrap_or_default();
-                let matches = self.get_all_task_matches(&query);
-                if let Some(ref mut search) = self.state.task_search {
-                    search.matches = matches;
-                    search.selected = 0;
-                }
-                false
+            KeyCode::Char(c) => popup.follow_up.push(c),
+            KeyCode::Enter => {
+                let popup = popup.clone();
+                self.state.abort_popup = None;
+                self.abort_task_agent(&popup.task_id, &popup.follow_up)?;
             }
-            _ => false,
-        };
+            _ => {}
+        }
+        Ok(())
+    }
 
-        if should_close {
-            self.state.task_search = None;
+    fn open_abandon_popup(&mut self) {
+        let Some(task) = self.state.board.selected_task() else { return };
+        if task.status == TaskStatus::Done {
+            self.state.warning_message = Some(("This task is already Done".to_string(), Instant::now()));
+            return;
         }
+        self.state.abandon_popup = Some(AbandonPopup {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            status: task.status,
+            destroys: describe_destroyed_resources(task, self.state.config.cleanup.policy(CleanupAction::Done)),
+            reason: String::new(),
+        });
+    }
 
+    fn handle_abandon_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
+        let Some(ref mut popup) = self.state.abandon_popup else { return Ok(()) };
+        match key.code {
+            KeyCode::Esc => self.state.abandon_popup = None,
+            KeyCode::Backspace => {
+                popup.reason.pop();
+            }
+            KeyCode::Char(c) => popup.reason.push(c),
+            // A jump past the normal flow is only recorded with a reason
+            KeyCode::Enter if popup.reason.trim().is_empty() => {}
+            KeyCode::Enter => {
+                let popup = popup.clone();
+                self.state.abandon_popup = None;
+                let result = self.abandon_task(&popup.task_id, &popup.reason);
+                self.record_failure(&popup.task_id, result)?;
+            }
+            _ => {}
+        }
         Ok(())
     }
 
-    fn get_all_task_matches(&self, query: &str) -> Vec<(String, String, TaskStatus)> {
-        let query_lower = query.to_lowercase();
+    /// Move a task straight to Done from whatever column it's in, skipping the columns between
+    /// (`next_status` still only ever advances one step). Only the window and worktree that
+    /// actually exist are cleaned up; the jump and its reason go in the task's history.
+    fn abandon_task(&mut self, task_id: &str, reason: &str) -> Result<()> {
+        let (Some(db), Some(project_path)) = (&self.state.db, self.state.project_path.clone()) else {
+            return Ok(());
+        };
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        if task.status == TaskStatus::Done {
+            return Ok(());
+        }
+        let from = task.status;
+        forget_missing_resources(&mut task, &project_path, self.state.tmux_ops.as_ref(), self.state.git_ops.as_ref());
+        if !self.finish_move_to_done(task.clone())? {
+            return Ok(());
+        }
+        self.log_task_event(&task.id, TaskEventKind::Abandoned, abandon_detail(from, reason));
+        self.state.resource_mismatches.remove(&task.id);
+        self.state.warning_message = Some((format!("Moved '{}' straight to Done", task.title), Instant::now()));
+        Ok(())
+    }
 
-        let mut matches: Vec<(String, String, TaskStatus, i32)> = self.state.board.tasks
-            .iter()
-            .filter_map(|task| {
-                let title_lower = task.title.to_lowercase();
-                let score = if query.is_empty() {
-                    1
-                } else {
-                    fuzzy_score(&title_lower, &query_lower)
-                };
+    /// Stop the agent of a task and optionally give it a new prompt. The task keeps its column,
+    /// window and worktree (unlike Done, which kills the window).
+    fn abort_task_agent(&mut self, task_id: &str, follow_up: &str) -> Result<()> {
+        let Some(task) = self.state.board.tasks.iter().find(|t| t.id == task_id).cloned() else { return Ok(()) };
+        let target = interrupt_task_agent(&task, self.state.tmux_ops.as_ref())?;
+        let follow_up = follow_up.trim().to_string();
+        self.log_task_event(&task.id, TaskEventKind::Interrupted, follow_up.clone());
+        let message = if follow_up.is_empty() {
+            format!("Interrupted the agent of '{}'", task.title)
+        } else {
+            // Give the agent a moment to get back to its input before typing into it
+            let tmux_ops = Arc::clone(&self.state.tmux_ops);
+            std::thread::spawn(move || {
+                std::thread::sleep(ABORT_SETTLE_DELAY);
+                let _ = tmux_ops.send_keys(&target, &follow_up);
+            });
+            format!("Interrupted the agent of '{}' and sent the new prompt", task.title)
+        };
+        self.state.warning_message = Some((message, Instant::now()));
+        Ok(())
+    }
 
-                if score > 0 {
-                    Some((task.id.clone(), task.title.clone(), task.status, score))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /// Send every Review task back to Running, optionally with a follow-up prompt
+    fn resume_all_review_tasks(&mut self, follow_up: &str) -> Result<()> {
+        let tasks = self.state.board.tasks.clone();
+        let tmux_ops = Arc::clone(&self.state.tmux_ops);
+        let trimmed = follow_up.trim();
+        let (resumed, prompted, unreachable) = resume_review_tasks(&tasks, trimmed, tmux_ops.as_ref(), |task| {
+            self.reopen_agent_window(task, trimmed)
+        });
+        let mut saved = 0;
+        let mut blocked = Vec::new();
+        for task in &resumed {
+            if let Err(e) = self.run_hooks(task, TaskStatus::Review, TaskStatus::Running) {
+                // Strict hook failed: this task stays in Review
+                let report = ErrorReport::from_error(&e);
+                error_log::log(&report.action, &report.message);
+                blocked.push(format!("'{}'", task.title));
+                continue;
+            }
+            if let Some(db) = &self.state.db {
+                db.update_task(task)?;
+            }
+            self.post_webhook(task, TaskStatus::Review);
+            saved += 1;
+        }
+        let mut message = format!("Resumed {} Review task(s)", saved);
+        if !follow_up.trim().is_empty() {
+            message.push_str(&format!(", follow-up sent to {}", prompted));
+        }
+        if !blocked.is_empty() {
+            message.push_str(&format!("; blocked by hooks: {}", blocked.join(", ")));
+        }
+        if !unreachable.is_empty() {
+            let titles: Vec<String> = unreachable
+                .iter()
+                .map(|(title, e)| {
+                    error_log::log(&format!("Resuming '{}'", title), &format!("{:#}", e));
+                    format!("'{}'", title)
+                })
+                .collect();
+            message.push_str(&format!("; agent could not be restarted: {}", titles.join(", ")));
+        }
+        self.state.warning_message = Some((message, Instant::now()));
+        self.refresh_tasks()?;
+        Ok(())
+    }
 
-        // Sort by score (higher is better)
-        matches.sort_by(|a, b| b.3.cmp(&a.3));
+    fn move_running_to_planning(&mut self, task_id: &str) -> Result<()> {
+        let Some(db) = &self.state.db else { return Ok(()) };
+        let Some(mut task) = db.get_task(task_id)? else { return Ok(()) };
+        if task.status != TaskStatus::Running {
+            return Ok(());
+        }
+        workflow::request_transition(&task, TaskStatus::Planning)?;
+        self.run_hooks(&task, TaskStatus::Running, TaskStatus::Planning)?;
 
-        matches.into_iter()
-            .take(10)
-            .map(|(id, title, status, _)| (id, title, status))
-            .collect()
+        // Just move the task back to Planning - the tmux window should still be open
+        task.status = TaskStatus::Planning;
+        task.updated_at = chrono::Utc::now();
+        if let Some(db) = &self.state.db {
+            db.update_task(&task)?;
+        }
+        self.post_webhook(&task, TaskStatus::Running);
+        self.refresh_tasks()?;
+        Ok(())
     }
 
-    fn handle_shell_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        use crossterm::event::KeyModifiers;
+    fn open_selected_task(&mut self) -> Result<()> {
+        if let Some(task) = self.state.board.selected_task() {
+            if let Some(window_name) = &task.session_name.clone() {
+                let mut popup = ShellPopup::new(task.title.clone(), window_name.clone());
 
-        if let Some(ref mut popup) = self.state.shell_popup {
-            let window_name = popup.window_name.clone();
-            let has_ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+                // Resize tmux window to match popup dimensions (uses same constants as draw_shell_popup)
+                if let Ok((_term_width, term_height)) = crossterm::terminal::size() {
+                    let pane_width = SHELL_POPUP_CONTENT_WIDTH;
+                    let popup_height = (term_height as u32 * SHELL_POPUP_HEIGHT_PERCENT as u32 / 100) as u16;
+                    let pane_height = popup_height.saturating_sub(4); // -4 for borders + header/footer
 
-            match key.code {
-                // Ctrl+q = close popup
-                KeyCode::Char('q') if has_ctrl => {
-                    self.state.shell_popup = None;
-                }
-                // Scroll up with Ctrl+k or Ctrl+p or Ctrl+Up
-                KeyCode::Char('k') | KeyCode::Char('p') | KeyCode::Up if has_ctrl => {
-                    popup.scroll_up(5);
-                }
-                // Scroll down with Ctrl+j or Ctrl+n or Ctrl+Down
-                KeyCode::Char('j') | KeyCode::Char('n') | KeyCode::Down if has_ctrl => {
-                    popup.scroll_down(5);
-                }
-                // Page up with Ctrl+u or PageUp
-                KeyCode::Char('u') if has_ctrl => {
-                    popup.scroll_up(20);
-                }
-                KeyCode::PageUp => {
-                    popup.scroll_up(20);
-                }
-                // Page down with Ctrl+d or PageDown
-                KeyCode::Char('d') if has_ctrl => {
-                    popup.scroll_down(20);
-                }
-                KeyCode::PageDown => {
-                    popup.scroll_down(20);
-                }
-                // Ctrl+g = go to bottom (current)
-                KeyCode::Char('g') if has_ctrl => {
-                    popup.scroll_to_bottom();
-                }
-                _ => {
-                    // Forward all other keys to tmux window (including Esc)
-                    send_key_to_tmux(&window_name, key.code, self.state.tmux_ops.as_ref());
-                    // After sending a key, refresh content to show the result
-                    popup.cached_content = capture_tmux_pane_with_history(&window_name, 500, self.state.tmux_ops.as_ref());
+                    let target = format!("{}:{}", self.state.tmux_session(), window_name);
+                    // TODO the resize should be done on target which is
+                    // session_name:window_name, but for some reason that doesn't work
+                    // doing tmux -L agtx resize-window -t session:window -x 30 -y 30 works
+                    let _ = self.state.tmux_ops.resize_window(&window_name, pane_width, pane_height);
+                    popup.last_pane_size = Some((pane_width, pane_height));
                 }
+
+                // Capture initial content
+                popup.cached_content = capture_tmux_pane_with_history(window_name, 500, self.state.tmux_ops.as_ref());
+
+                self.state.shell_popup = Some(popup);
             }
         }
         Ok(())
     }
 
-    fn handle_diff_popup_key(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        if let Some(ref mut popup) = self.state.diff_popup {
-            match key.code {
-                KeyCode::Esc | KeyCode::Char('q') => {
-                    self.state.diff_popup = None;
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    popup.scroll_offset = popup.scroll_offset.saturating_add(1);
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    popup.scroll_offset = popup.scroll_offset.saturating_sub(1);
-                }
-                KeyCode::Char('d') | KeyCode::PageDown => {
-                    popup.scroll_offset = popup.scroll_offset.saturating_add(20);
-                }
-                KeyCode::Char('u') | KeyCode::PageUp => {
-                    popup.scroll_offset = popup.scroll_offset.saturating_sub(20);
-                }
-                KeyCode::Char('g') => {
-                    popup.scroll_offset = 0;
-                }
-                KeyCode::Char('G') => {
-                    // Go to end
-                    let line_count = popup.diff_content.lines().count();
-                    popup.scroll_offset = line_count.saturating_sub(10);
-                }
-                _ => {}
-            }
+    /// Load the plugin that a specific task was created with.
+    /// Returns None for tasks created with agtx defaults (task.plugin is None).
+    /// The prompt for a task entering `phase`, with `{worktree}` filled once the task has one
+    fn phase_prompt(&self, plugin: &Option<WorkflowPlugin>, phase: &str, task: &Task, agent_name: &str) -> String {
+        let worktree = task.worktree_path.as_deref().unwrap_or("");
+        resolve_prompt(plugin, &self.state.config.prompts, phase, task, worktree, agent_name)
+    }
+
+    /// Footer warning (and error log entry) when the task's directory isn't in its worktree,
+    /// so its agent was started at the worktree root instead
+    fn warn_if_subdir_missing(&mut self, task: &Task) {
+        if let Some(message) = missing_subdir(task) {
+            error_log::log("Task directory", &message);
+            self.state.warning_message = Some((message, Instant::now()));
         }
-        Ok(())
     }
 
-    fn handle_dashboard_key(&mut self, key: KeyCode) -> Result<()> {
-        if self.state.show_project_list {
-            match key {
-                KeyCode::Char('q') => self.state.should_quit = true,
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if self.state.selected_project < self.state.projects.len().saturating_sub(1) {
-                        self.state.selected_project += 1;
-                    }
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if self.state.selected_project > 0 {
-                        self.state.selected_project -= 1;
-                    }
-                }
-                KeyCode::Enter => {
-                    if let Some(project) = self.state.projects.get(self.state.selected_project).cloned() {
-                        self.switch_to_project(&project)?;
-                        self.state.mode = AppMode::Project(PathBuf::from(&project.path));
-                        self.state.sidebar_visible = false;
-                    }
-                }
-                KeyCode::Esc => {
-                    self.state.show_project_list = false;
-                }
-                _ => {}
-            }
-        } else {
-            match key {
-                KeyCode::Char('q') => self.state.should_quit = true,
-                KeyCode::Char('p') => {
-                    self.state.show_project_list = true;
-                }
-                KeyCode::Char('n') => {
-                    let current_dir = std::env::current_dir()?;
-                    if crate::git::is_git_repo(&current_dir) {
-                        let canonical = current_dir.canonicalize().unwrap_or(current_dir);
-                        let name = canonical
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or("unknown")
-                            .to_string();
-                        let project = ProjectInfo {
-                            name: name.clone(),
-                            path: canonical.to_string_lossy().to_string(),
-                        };
-                        self.switch_to_project(&project)?;
-                        self.state.mode = AppMode::Project(canonical);
-                        self.state.sidebar_visible = false;
-                    }
-                }
-                _ => {}
-            }
+    fn load_task_plugin(&self, task: &Task) -> Option<WorkflowPlugin> {
+        load_plugin_for(task, &self.state.agent_for(task), self.state.project_path.as_deref())
+    }
+
+    /// Branch task worktrees are based on (configured, or detected and cached for the project)
+    fn base_branch(&mut self) -> String {
+        resolve_base_branch(
+            self.state.config.base_branch.as_deref(),
+            &mut self.state.detected_base_branch,
+            self.state.project_path.as_deref(),
+            self.state.git_ops.as_ref(),
+        )
+    }
+
+    /// Re-check whether the current project can host worktrees and show why not
+    fn check_repo(&mut self) {
+        // Without git every repo check fails; say that rather than "not a git repository"
+        self.state.repo_problem = self.state.git_problem.clone().or_else(|| {
+            self.state.project_path
+                .as_deref()
+                .and_then(|path| repo_problem(path, self.state.git_ops.as_ref()))
+        });
+        if let Some(problem) = &self.state.repo_problem {
+            self.state.warning_message = Some((problem.clone(), Instant::now()));
         }
-        Ok(())
     }
 
-    fn handle_normal_key(&mut self, key: KeyCode) -> Result<()> {
-        // Handle sidebar navigation if focused
-        if self.state.sidebar_focused && self.state.sidebar_visible {
-            match key {
-                KeyCode::Char('q') => self.state.should_quit = true,
-                KeyCode::Char('e') => {
-                    // Toggle sidebar visibility
-                    self.state.sidebar_visible = false;
-                    self.state.sidebar_focused = false;
-                }
-                KeyCode::Char('l') | KeyCode::Right | KeyCode::Esc => {
-                    // Move focus back to board
-                    self.state.sidebar_focused = false;
-                }
-                KeyCode::Char('j') | KeyCode::Down => {
-                    if self.state.selected_project < self.state.projects.len().saturating_sub(1) {
-                        self.state.selected_project += 1;
-                        // Switch to project immediately on cursor move
-                        if let Some(project) = self.state.projects.get(self.state.selected_project).cloned() {
-                            self.switch_to_project_keep_sidebar(&project)?;
-                        }
-                    }
-                }
-                KeyCode::Char('k') | KeyCode::Up => {
-                    if self.state.selected_project > 0 {
-                        self.state.selected_project -= 1;
-                        // Switch to project immediately on cursor move
-                        if let Some(project) = self.state.projects.get(self.state.selected_project).cloned() {
-                            self.switch_to_project_keep_sidebar(&project)?;
-                        }
-                    }
-                }
-                KeyCode::Enter => {
-                    // Enter focuses the board (sidebar stays visible)
-                    self.state.sidebar_focused = false;
-                }
-                _ => {}
+    /// Whether tasks can be created/started; surfaces the reason in the footer if not
+    fn repo_ready(&mut self) -> bool {
+        match &self.state.repo_problem {
+            Some(problem) => {
+                self.state.warning_message = Some((problem.clone(), Instant::now()));
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Compare Running/Review tasks with the windows and worktrees that actually exist and flag
+    /// the ones that don't match. Task status is never changed here; the user decides.
+    fn reconcile_resources(&mut self) {
+        let Some(project_path) = self.state.project_path.clone() else { return };
+        let mismatches = find_resource_mismatches(
+            &self.state.board.tasks,
+            &project_path,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+        );
+        let entries: Vec<_> = mismatches
+            .iter()
+            .filter_map(|(id, mismatch)| {
+                let task = self.state.board.tasks.iter().find(|t| &t.id == id)?;
+                Some((task.id.clone(), task.title.clone(), task.status, *mismatch))
+            })
+            .collect();
+        self.state.resource_mismatches = mismatches.into_iter().collect();
+        self.state.reconcile_popup = (!entries.is_empty()).then_some(ReconcilePopup { entries, selected: 0 });
+    }
+
+    /// Save the project's board as a named snapshot (`agtx snapshot save`)
+    pub fn save_snapshot(&self, name: &str) -> Result<PathBuf> {
+        let (Some(db), Some(project_path)) = (&self.state.db, &self.state.project_path) else {
+            anyhow::bail!("Snapshots need a project");
+        };
+        snapshot::save(project_path, &snapshot::Snapshot::new(name, db.get_all_tasks()?))
+    }
+
+    /// Every task that could hold a worktree or window, trashed ones included
+    fn tasks_with_resources(&self) -> Result<Vec<Task>> {
+        let Some(db) = &self.state.db else { anyhow::bail!("Adopting needs a project") };
+        let mut tasks = db.get_all_tasks()?;
+        tasks.extend(db.get_trashed_tasks()?);
+        Ok(tasks)
+    }
+
+    /// The project's worktrees in its `worktree_dir` and the windows of its session that no
+    /// task points at (`agtx adopt list`). Windows are empty when the multiplexer can't list them.
+    pub fn adoption_candidates(&self) -> Result<(Vec<PathBuf>, Vec<String>)> {
+        let Some(project_path) = &self.state.project_path else { anyhow::bail!("Adopting needs a project") };
+        let tasks = self.tasks_with_resources()?;
+        let mut worktrees = self.state.git_ops.list_worktrees(project_path).context("Failed to list worktrees")?;
+        // Only these can be adopted (see `Adoption::check`)
+        worktrees.retain(|path| adopt::in_worktree_dir(path, project_path, self.state.git_ops.as_ref()));
+        let session = self.state.tmux_session();
+        let windows = self.state.tmux_ops.list_windows(&session).unwrap_or_default();
+        Ok((adopt::untracked_worktrees(&worktrees, &tasks), adopt::untracked_windows(&session, &windows, &tasks)))
+    }
+
+    /// Track a worktree made by hand, and optionally the window its agent runs in, as a
+    /// Running task (`agtx adopt`). Neither is created; both are checked first.
+    pub fn adopt(&mut self, worktree: &str, window: Option<&str>, title: Option<&str>) -> Result<Task> {
+        let Some(project_path) = self.state.project_path.clone() else { anyhow::bail!("Adopting needs a project") };
+        if let Some(problem) = &self.state.repo_problem {
+            anyhow::bail!("{}", problem);
+        }
+        let adoption = adopt::Adoption::check(
+            worktree,
+            window,
+            &project_path,
+            &self.state.tmux_session(),
+            &self.tasks_with_resources()?,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+        )?;
+        let branch = git::current_branch(&adoption.worktree).ok();
+        let task = adoption.into_task(title, branch, &self.state.config.default_agent, &self.state.project_name);
+        let Some(db) = &self.state.db else { anyhow::bail!("Adopting needs a project") };
+        db.create_task(&task)?;
+        self.refresh_tasks()?;
+        Ok(task)
+    }
+
+    /// Create a Backlog task for each markdown file in `dir` (`agtx import`), skipping titles
+    /// already on the board
+    pub fn import_tasks(&mut self, dir: &Path) -> Result<import::ImportSummary> {
+        let Some(db) = &self.state.db else { anyhow::bail!("Importing needs a project") };
+        if let Some(problem) = &self.state.repo_problem {
+            anyhow::bail!("{}", problem);
+        }
+        let (issues, failures) = import::read_issues(dir)?;
+        let plan = import::plan_import(issues, &db.get_all_tasks()?);
+
+        let mut summary = import::ImportSummary { failures, ..Default::default() };
+        summary.skipped = plan.skipped.into_iter().map(|issue| issue.title).collect();
+        for issue in plan.create {
+            let mut task = Task::new(issue.title, self.state.config.default_agent.clone(), self.state.project_name.clone());
+            task.description = issue.description;
+            match db.create_task(&task) {
+                Ok(()) => summary.created.push(task.title),
+                Err(e) => summary.failures.push(format!("{}: {:#}", issue.path.display(), e)),
             }
-            return Ok(());
         }
+        self.refresh_tasks()?;
+        Ok(summary)
+    }
 
-        // Handle board navigation
-        match key {
-            KeyCode::Char('q') => self.state.should_quit = true,
-            KeyCode::Char('e') => {
-                // Toggle sidebar visibility
-                self.state.sidebar_visible = !self.state.sidebar_visible;
-                if self.state.sidebar_visible {
-                    self.refresh_projects()?;
+    /// Put the board back the way a snapshot recorded it (`agtx snapshot restore`): tasks are
+    /// created or updated to match and the rest trashed, windows and worktrees no restored task
+    /// refers to are cleaned up as deleting a task would (dirty worktrees and `[cleanup.delete]`
+    /// are respected), and Running/Review tasks get back the worktree and window they miss.
+    /// Kept resources and failures are reported in the summary instead of stopping the restore.
+    pub fn restore_snapshot(&mut self, name: &str) -> Result<snapshot::RestoreSummary> {
+        let Some(project_path) = self.state.project_path.clone() else {
+            anyhow::bail!("Snapshots need a project");
+        };
+        let saved = snapshot::load(&project_path, name)?;
+        let Some(db) = &self.state.db else { anyhow::bail!("Snapshots need a project") };
+        let plan = snapshot::plan_restore(&saved.tasks, &db.get_all_tasks()?);
+        let mut summary = snapshot::RestoreSummary::default();
+
+        let cleanup = self.state.config.cleanup.policy(CleanupAction::Delete);
+        for task in &plan.release {
+            let released = match delete_task_resources(
+                task,
+                &project_path,
+                self.state.tmux_ops.as_ref(),
+                self.state.git_ops.as_ref(),
+                self.state.config.dirty_worktree_policy,
+                cleanup,
+            ) {
+                Ok(released) => released,
+                Err(e) => {
+                    summary.kept.push(format!("'{}': {:#}", task.title, e));
+                    continue;
                 }
-            }
-            KeyCode::Char('h') | KeyCode::Left => {
-                // Move to sidebar only if visible AND in first column (Backlog)
-                if self.state.sidebar_visible && self.state.board.selected_column == 0 {
-                    self.state.sidebar_focused = true;
-                    self.refresh_projects()?;
-                } else {
-                    self.state.board.move_left();
+            };
+            if let Some(window) = &task.session_name {
+                match released.window {
+                    Some(Ok(())) => summary.windows_killed += 1,
+                    Some(Err(e)) => summary.failures.push(format!("window {}: {}", window, e)),
+                    None => summary.kept.push(format!("window {} ([cleanup.delete] keeps it)", window)),
                 }
             }
-            KeyCode::Char('l') | KeyCode::Right => self.state.board.move_right(),
-            KeyCode::Char('j') | KeyCode::Down => self.state.board.move_down(),
-            KeyCode::Char('k') | KeyCode::Up => self.state.board.move_up(),
-            KeyCode::Char('o') => {
-                // New task
-                self.state.input_mode = InputMode::InputTitle;
-                self.state.input_buffer.clear();
-                self.state.pending_task_title.clear();
-                self.state.editing_task_id = None;
-            }
-            KeyCode::Enter => {
-                if let Some(task) = self.state.board.selected_task() {
-                    if task.status == TaskStatus::Backlog && task.session_name.is_some() {
-                        // Backlog task with active research session — open tmux popup
-                        self.open_selected_task()?;
-                    } else if task.status == TaskStatus::Backlog {
-                        // Edit task
-                        self.state.editing_task_id = Some(task.id.clone());
-                        self.state.input_buffer = task.title.clone();
-                        self.state.input_cursor = self.state.input_buffer.len();
-                        self.state.pending_task_title.clear();
-                        self.state.input_mode = InputMode::InputTitle;
-                    } else if task.session_name.is_some() {
-                        // Open shell popup
-                        self.open_selected_task()?;
-                    }
+            if let Some(worktree) = &task.worktree_path {
+                match released.worktree {
+                    Some(Ok(())) => summary.worktrees_removed += 1,
+                    Some(Err(e)) => summary.failures.push(format!("worktree {}: {}", worktree, e)),
+                    None => summary.kept.push(format!("worktree {} ([cleanup.delete] keeps it)", worktree)),
                 }
             }
-            KeyCode::Char('x') => self.delete_selected_task()?,
-            KeyCode::Char('d') => self.show_task_diff()?,
-            KeyCode::Char('m') => self.move_task_right()?,
-            KeyCode::Char('M') => self.move_backlog_to_running()?,
-            KeyCode::Char('R') => {
-                if let Some(task) = self.state.board.selected_task() {
-                    if task.status == TaskStatus::Backlog && task.session_name.is_none() {
-                        let task_id = task.id.clone();
-                        self.start_research(&task_id)?;
-                    }
-                }
+        }
+
+        for id in &plan.remove {
+            let Some(mut task) = db.get_task(id)? else { continue };
+            task.deleted_at = Some(chrono::Utc::now());
+            task.updated_at = chrono::Utc::now();
+            db.update_task(&task)?;
+        }
+        for task in &plan.create {
+            // A task trashed since the snapshot still has its row
+            if db.get_task(&task.id)?.is_some() {
+                db.update_task(task)?;
+            } else {
+                db.create_task(task)?;
             }
-            KeyCode::Char('r') => {
-                if let Some(task) = self.state.board.selected_task() {
-                    let task_id = task.id.clone();
-                    match task.status {
-                        // Move Review task back to Running (for PR changes)
-                        TaskStatus::Review => self.move_review_to_running(&task_id)?,
-                        // Move Running task back to Planning
-                        TaskStatus::Running => self.move_running_to_planning(&task_id)?,
-                        _ => {}
+        }
+        for task in &plan.update {
+            db.update_task(task)?;
+        }
+        summary.tasks_created = plan.create.len();
+        summary.tasks_updated = plan.update.len();
+        summary.tasks_removed = plan.remove.len();
+        self.refresh_tasks()?;
+
+        let missing = find_resource_mismatches(
+            &self.state.board.tasks,
+            &project_path,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+        );
+        let base_branch = self.base_branch();
+        for (id, resource) in missing {
+            let Some(mut task) = self.state.board.tasks.iter().find(|t| t.id == id).cloned() else { continue };
+            let agent_name = self.state.agent_for(&task);
+            let plugin = self.load_task_plugin(&task);
+            let phase = if task.status == TaskStatus::Review { "review" } else { "running" };
+            let prompt = self.phase_prompt(&plugin, phase, &task, &agent_name);
+            let window = WindowSetup::for_task(&self.state.config, &task);
+            let _activity = self.begin_activity(format!("Restoring '{}'", task.title));
+            let restored = restore_task_resources(
+                &mut task,
+                resource,
+                &project_path,
+                &base_branch,
+                &self.state.tmux_session(),
+                &prompt,
+                self.state.config.copy_files.clone(),
+                self.state.config.init_script.clone(),
+                &plugin,
+                &window,
+                self.state.tmux_ops.as_ref(),
+                self.state.git_ops.as_ref(),
+                self.state.agent_registry.get(&agent_name).as_ref(),
+            );
+            match restored {
+                Ok(worktree_created) => {
+                    if worktree_created {
+                        summary.worktrees_created.push(task.title.clone());
+                    }
+                    summary.windows_created.push(task.title.clone());
+                    self.log_setup_events(&task);
+                    if let Some(db) = &self.state.db {
+                        db.update_task(&task)?;
                     }
                 }
+                Err(e) => summary.failures.push(format!("'{}': {:#}", task.title, e)),
             }
-            KeyCode::Char('/') => {
-                // Open task search
-                self.state.task_search = Some(TaskSearchState {
-                    query: String::new(),
-                    matches: self.get_all_task_matches(""),
-                    selected: 0,
-                });
-            }
-            KeyCode::Char('P') => {
-                // Open plugin selection popup
-                self.open_plugin_select_popup();
-            }
-            _ => {}
         }
-        Ok(())
+        self.refresh_tasks()?;
+        self.state.resource_mismatches.clear();
+        Ok(summary)
     }
 
-    fn handle_title_input(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        let has_alt = key.modifiers.contains(crossterm::event::KeyModifiers::ALT);
-        match key.code {
-            KeyCode::Esc => {
-                self.state.input_mode = InputMode::Normal;
-                self.state.input_buffer.clear();
-                self.state.input_cursor = 0;
-                self.state.pending_task_title.clear();
-                self.state.editing_task_id = None;
-            }
-            KeyCode::Enter => {
-                if !self.state.input_buffer.is_empty() {
-                    // Save title and move to description input
-                    self.state.pending_task_title = self.state.input_buffer.clone();
+    fn refresh_tasks(&mut self) -> Result<()> {
+        if let Some(db) = &self.state.db {
+            self.state.board.tasks = db.get_all_tasks()?;
+        }
+        // A flagged task that was moved on or deleted has nothing left to reconcile
+        let tasks = &self.state.board.tasks;
+        self.state.resource_mismatches.retain(|id, _| {
+            tasks.iter().any(|t| &t.id == id && matches!(t.status, TaskStatus::Running | TaskStatus::Review))
+        });
+        self.sync_scheduler();
+        Ok(())
+    }
 
-                    // If editing, pre-fill description
-                    if let Some(task_id) = &self.state.editing_task_id {
-                        if let Some(db) = &self.state.db {
-                            if let Ok(Some(task)) = db.get_task(task_id) {
-                                self.state.input_buffer = task.description.unwrap_or_default();
-                            } else {
-                                self.state.input_buffer.clear();
-                            }
-                        } else {
-                            self.state.input_buffer.clear();
-                        }
-                    } else {
-                        self.state.input_buffer.clear();
-                    }
+    /// Free agent slots for tasks that left Running and dispatch queued commands. Queued tasks
+    /// wait while automation is paused; resuming catches up.
+    fn sync_scheduler(&mut self) {
+        if self.state.automation_paused.load(Ordering::SeqCst) {
+            self.save_dispatch_queues();
+            return;
+        }
+        let running: Vec<&str> = self.state.board.tasks
+            .iter()
+            .filter(|t| t.status == TaskStatus::Running)
+            .map(|t| t.id.as_str())
+            .collect();
+        let dispatched = self.state.scheduler.sync(&self.state.project_name, &running, self.state.tmux_ops.as_ref());
+        self.save_dispatch_queues();
+        self.report_dispatched(&dispatched);
+    }
 
-                    self.state.input_cursor = self.state.input_buffer.len();
-                    self.state.input_mode = InputMode::InputDescription;
-                }
-            }
-            KeyCode::Left if has_alt => {
-                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
-            }
-            KeyCode::Right if has_alt => {
-                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
-            }
-            // macOS: Option+Left/Right sends Alt+b / Alt+f
-            KeyCode::Char('b') if has_alt => {
-                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
-            }
-            KeyCode::Char('f') if has_alt => {
-                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
-            }
-            KeyCode::Left => {
-                if self.state.input_cursor > 0 {
-                    self.state.input_cursor -= 1;
+    /// Queue the Running commands this project saved in an earlier session, so they're sent
+    /// once a slot frees up instead of being lost on quit
+    fn restore_dispatch_queue(&mut self) {
+        let Some(db) = &self.state.db else { return };
+        match db.get_dispatch_queue() {
+            Ok(queue) => {
+                for queued in queue {
+                    self.state.scheduler.restore(PendingDispatch {
+                        task_id: queued.task_id,
+                        project: self.state.project_name.clone(),
+                        target: queued.target,
+                        command: queued.command,
+                    });
                 }
             }
-            KeyCode::Right => {
-                if self.state.input_cursor < self.state.input_buffer.len() {
-                    self.state.input_cursor += 1;
+            Err(e) => error_log::log("Restoring queued dispatches", &format!("{:#}", e)),
+        }
+    }
+
+    /// Save the queued commands of every project whose queue changed. Other projects' queues
+    /// change when their commands are dispatched while this one is open.
+    fn save_dispatch_queues(&mut self) {
+        for project in self.state.scheduler.take_changed() {
+            let queue: Vec<QueuedCommand> = self.state.scheduler
+                .queued_for(&project)
+                .map(|d| QueuedCommand { task_id: d.task_id.clone(), target: d.target.clone(), command: d.command.clone() })
+                .collect();
+            let result = if project == self.state.project_name {
+                match &self.state.db {
+                    Some(db) => db.save_dispatch_queue(&queue),
+                    None => Ok(()),
                 }
+            } else {
+                self.state.global_db.get_all_projects().and_then(|projects| {
+                    match projects.iter().find(|p| p.name == project) {
+                        Some(p) => Database::open_project(Path::new(&p.path))?.save_dispatch_queue(&queue),
+                        None => Ok(()),
+                    }
+                })
+            };
+            if let Err(e) = result {
+                error_log::log("Saving queued dispatches", &format!("{}: {:#}", project, e));
             }
-            KeyCode::Home => {
-                self.state.input_cursor = 0;
-            }
-            KeyCode::End => {
-                self.state.input_cursor = self.state.input_buffer.len();
+        }
+    }
+
+    fn report_dispatched(&mut self, dispatched: &[String]) {
+        for id in dispatched {
+            self.log_task_event(id, TaskEventKind::AgentDispatched, "running command sent once a slot freed up");
+        }
+        let titles: Vec<String> = dispatched
+            .iter()
+            .filter_map(|id| self.state.board.tasks.iter().find(|t| &t.id == id))
+            .map(|t| format!("'{}'", t.title))
+            .collect();
+        if !titles.is_empty() {
+            self.state.warning_message = Some((format!("Agent slot free, started {}", titles.join(", ")), Instant::now()));
+        }
+    }
+
+    fn refresh_projects(&mut self) -> Result<()> {
+        // Load projects from global database
+        let db_projects = self.state.global_db.get_all_projects()?;
+
+        self.state.projects = db_projects
+            .into_iter()
+            .map(|p| ProjectInfo {
+                name: p.name,
+                path: p.path,
+            })
+            .collect();
+
+        // Sort alphabetically by name (case-insensitive)
+        self.state
+            .projects
+            .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+
+        // Find current project in list and select it
+        if let Some(project_path) = &self.state.project_path {
+            let current_path = project_path.to_string_lossy();
+            if let Some(pos) = self.state.projects.iter().position(|p| p.path == current_path) {
+                self.state.selected_project = pos;
             }
-            KeyCode::Backspace => {
-                if self.state.input_cursor > 0 {
-                    self.state.input_cursor -= 1;
-                    self.state.input_buffer.remove(self.state.input_cursor);
+        }
+
+        Ok(())
+    }
+
+    fn refresh_sessions(&mut self) -> Result<()> {
+        let now = Instant::now();
+        const CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(2);
+
+        let project_path = self.state.project_path.clone();
+
+        let tasks_to_check: Vec<_> = self.state.board.tasks.iter()
+            .filter(|t| {
+                matches!(t.status, TaskStatus::Planning | TaskStatus::Running | TaskStatus::Review)
+                || (t.status == TaskStatus::Backlog && t.session_name.is_some())
+            })
+            .filter(|t| t.worktree_path.is_some() || t.session_name.is_some())
+            .map(|t| (t.id.clone(), t.status, t.worktree_path.clone(), t.plugin.clone()))
+            .collect();
+
+        // Cache loaded plugins by name to avoid reloading from disk for each task
+        let mut plugin_cache: HashMap<Option<String>, Option<WorkflowPlugin>> = HashMap::new();
+
+        for (task_id, status, worktree_path, task_plugin) in tasks_to_check {
+            if let Some((_, timestamp)) = self.state.phase_status_cache.get(&task_id) {
+                if now.duration_since(*timestamp) < CACHE_TTL {
+                    continue;
                 }
             }
-            KeyCode::Delete => {
-                if self.state.input_cursor < self.state.input_buffer.len() {
-                    self.state.input_buffer.remove(self.state.input_cursor);
+
+            let phase_status = if status == TaskStatus::Backlog {
+                // Research artifact: check worktree first, then project root
+                let found = worktree_path.as_ref().map_or(false, |wt| {
+                    Path::new(wt).join(".agtx").join("research")
+                        .join(format!("{}.md", task_id)).exists()
+                }) || project_path.as_ref().map_or(false, |pp| {
+                    pp.join(".agtx").join("research")
+                        .join(format!("{}.md", task_id)).exists()
+                });
+                if found { PhaseStatus::Ready } else { PhaseStatus::Working }
+            } else if let Some(ref wt) = worktree_path {
+                let plugin = plugin_cache.entry(task_plugin.clone()).or_insert_with(|| {
+                    match &task_plugin {
+                        Some(name) => WorkflowPlugin::load(name, self.state.project_path.as_deref()).ok(),
+                        None => None,
+                    }
+                });
+                if phase_artifact_exists(wt, status, plugin) {
+                    PhaseStatus::Ready
+                } else {
+                    PhaseStatus::Working
                 }
-            }
-            KeyCode::Char(c) => {
-                self.state.input_buffer.insert(self.state.input_cursor, c);
-                self.state.input_cursor += 1;
-            }
-            _ => {}
+            } else {
+                PhaseStatus::Working
+            };
+
+            self.state.phase_status_cache.insert(task_id, (phase_status, now));
         }
+
+        self.state.spinner_frame = self.state.spinner_frame.wrapping_add(1);
         Ok(())
     }
 
-    fn handle_description_input(&mut self, key: crossterm::event::KeyEvent) -> Result<()> {
-        // Handle file search mode if active
-        if let Some(ref mut search) = self.state.file_search {
-            match key.code {
-                KeyCode::Esc => {
-                    // Cancel file search
-                    self.state.file_search = None;
-                }
-                KeyCode::Enter | KeyCode::Tab => {
-                    // Select current match
-                    if let Some(selected_file) = search.matches.get(search.selected).cloned() {
-                        // Replace trigger+pattern with the selected file path, preserving text after
-                        let pattern_end = search.start_pos + 1 + search.pattern.len(); // +1 for trigger char
-                        let suffix = self.state.input_buffer[pattern_end..].to_string();
-                        self.state.input_buffer.truncate(search.start_pos);
-                        self.state.input_buffer.push_str(&selected_file);
-                        self.state.input_cursor = self.state.input_buffer.len();
-                        self.state.input_buffer.push_str(&suffix);
-                        self.state.highlighted_file_paths.insert(selected_file);
-                    }
-                    self.state.file_search = None;
-                }
-                KeyCode::Up => {
-                    if search.selected > 0 {
-                        search.selected -= 1;
-                    }
-                }
-                KeyCode::Down => {
-                    if search.selected < search.matches.len().saturating_sub(1) {
-                        search.selected += 1;
-                    }
-                }
-                KeyCode::Char('k') | KeyCode::Char('p') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
-                    if search.selected > 0 {
-                        search.selected -= 1;
-                    }
-                }
-                KeyCode::Char('j') | KeyCode::Char('n') if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) => {
-                    if search.selected < search.matches.len().saturating_sub(1) {
-                        search.selected += 1;
-                    }
-                }
-                KeyCode::Backspace => {
-                    if search.pattern.is_empty() {
-                        // Cancel search if pattern is empty
-                        self.state.input_buffer.pop(); // Remove the trigger char
-                        self.state.input_cursor = self.state.input_cursor.saturating_sub(1);
-                        self.state.file_search = None;
-                    } else {
-                        search.pattern.pop();
-                        self.state.input_buffer.pop();
-                        self.state.input_cursor = self.state.input_cursor.saturating_sub(1);
-                        self.update_file_search_matches();
-                    }
-                }
-                KeyCode::Char(c) => {
-                    search.pattern.push(c);
-                    self.state.input_buffer.push(c);
-                    self.state.input_cursor += 1;
-                    self.update_file_search_matches();
-                }
-                _ => {}
-            }
-            return Ok(());
+    /// Pick up exit codes written by agents that exited since the last check (at most every
+    /// `AGENT_EXIT_CHECK_INTERVAL`). A nonzero code is shown as a warning and in the history.
+    fn refresh_agent_exit_codes(&mut self) {
+        if self.state.agent_exit_checked_at.elapsed() < AGENT_EXIT_CHECK_INTERVAL {
+            return;
         }
+        self.state.agent_exit_checked_at = Instant::now();
+        let exited: Vec<(Task, i32)> = self
+            .state
+            .board
+            .tasks
+            .iter()
+            .filter(|t| matches!(t.status, TaskStatus::Planning | TaskStatus::Running | TaskStatus::Review))
+            .filter(|t| t.last_exit_code.is_none())
+            .filter_map(|t| {
+                let worktree = t.worktree_path.as_deref()?;
+                git::read_agent_exit_code(Path::new(worktree)).map(|code| (t.clone(), code))
+            })
+            .collect();
 
-        match key.code {
-            KeyCode::Esc => {
-                self.state.input_mode = InputMode::Normal;
-                self.state.input_buffer.clear();
-                self.state.input_cursor = 0;
-                self.state.pending_task_title.clear();
-                self.state.editing_task_id = None;
-                self.state.highlighted_file_paths.clear();
-            }
-            KeyCode::Enter => {
-                // Check if line ends with backslash for line continuation
-                if self.state.input_buffer.ends_with('\\') {
-                    // Remove backslash and insert newline
-                    self.state.input_buffer.pop();
-                    self.state.input_buffer.push('\n');
-                    self.state.input_cursor = self.state.input_buffer.len();
-                } else {
-                    // Save task (create or update)
-                    self.save_task()?;
-                    self.state.input_mode = InputMode::Normal;
-                    self.state.input_buffer.clear();
-                    self.state.input_cursor = 0;
-                    self.state.pending_task_title.clear();
-                    self.state.editing_task_id = None;
-                    self.state.highlighted_file_paths.clear();
+        for (mut task, code) in exited {
+            task.last_exit_code = Some(code);
+            if let Some(db) = &self.state.db {
+                if let Err(e) = db.update_task(&task) {
+                    error_log::log("Failed to save agent exit code", &e.to_string());
                 }
             }
-            KeyCode::Left if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
-                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
-            }
-            KeyCode::Right if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
-                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
+            self.log_task_event(&task.id, TaskEventKind::AgentExited, format!("code {}", code));
+            if code != 0 {
+                self.state.warning_message =
+                    Some((format!("Agent for '{}' exited with code {}", task.title, code), Instant::now()));
             }
-            // macOS: Option+Left/Right sends Alt+b / Alt+f
-            KeyCode::Char('b') if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
-                self.state.input_cursor = word_boundary_left(&self.state.input_buffer, self.state.input_cursor);
+            if let Some(board_task) = self.state.board.tasks.iter_mut().find(|t| t.id == task.id) {
+                board_task.last_exit_code = Some(code);
             }
-            KeyCode::Char('f') if key.modifiers.contains(crossterm::event::KeyModifiers::ALT) => {
-                self.state.input_cursor = word_boundary_right(&self.state.input_buffer, self.state.input_cursor);
+        }
+    }
+
+    /// Read token usage from the windows of working tasks, at most every `AGENT_USAGE_CHECK_INTERVAL`,
+    /// and save readings that changed
+    fn refresh_agent_usage(&mut self) {
+        if self.state.agent_usage_checked_at.elapsed() < AGENT_USAGE_CHECK_INTERVAL {
+            return;
+        }
+        self.state.agent_usage_checked_at = Instant::now();
+        let state = &mut self.state;
+        let active: Vec<&Task> = state
+            .board
+            .tasks
+            .iter()
+            .filter(|t| matches!(t.status, TaskStatus::Planning | TaskStatus::Running | TaskStatus::Review))
+            .collect();
+        state.captured_output.retain(|id, _| active.iter().any(|t| &t.id == id));
+
+        let max_lines = state.config.max_captured_lines;
+        let mut updated: Vec<(String, AgentUsage)> = Vec::new();
+        for task in active {
+            let agent_ops = state.agent_registry.get(&task.agent);
+            let buffer = state.captured_output.entry(task.id.clone()).or_insert_with(|| CaptureBuffer::new(max_lines));
+            if let Some(usage) = capture_agent_usage(task, buffer, state.tmux_ops.as_ref(), agent_ops.as_ref()) {
+                updated.push((task.id.clone(), usage));
             }
-            KeyCode::Left => {
-                if self.state.input_cursor > 0 {
-                    self.state.input_cursor -= 1;
+        }
+
+        for (task_id, usage) in updated {
+            let Some(task) = self.state.board.tasks.iter_mut().find(|t| t.id == task_id) else { continue };
+            task.usage = Some(usage);
+            if let Some(db) = &self.state.db {
+                if let Err(e) = db.set_task_usage(&task_id, &usage) {
+                    error_log::log("Failed to save agent usage", &e.to_string());
                 }
             }
-            KeyCode::Right => {
-                if self.state.input_cursor < self.state.input_buffer.len() {
-                    self.state.input_cursor += 1;
+        }
+    }
+
+    /// Re-check worktrees for uncommitted changes, at most every `WORKTREE_STATUS_TTL` per task
+    fn refresh_worktree_status(&mut self) {
+        let now = Instant::now();
+        let tasks: Vec<Task> = self.state.board.tasks.iter().filter(|t| t.worktree_path.is_some()).cloned().collect();
+        self.state.worktree_status_cache.retain(|id, _| tasks.iter().any(|t| &t.id == id));
+
+        for task in tasks {
+            if let Some((_, checked)) = self.state.worktree_status_cache.get(&task.id) {
+                if now.duration_since(*checked) < WORKTREE_STATUS_TTL {
+                    continue;
                 }
             }
-            KeyCode::Home => {
-                self.state.input_cursor = 0;
+            match worktree_dirty_state(&task, self.state.git_ops.as_ref()) {
+                Some(dirty) => {
+                    self.state.worktree_status_cache.insert(task.id.clone(), (dirty, now));
+                }
+                None => {
+                    self.state.worktree_status_cache.remove(&task.id);
+                }
             }
-            KeyCode::End => {
-                self.state.input_cursor = self.state.input_buffer.len();
+        }
+    }
+
+    /// Re-check that Running/Review tasks still have their window and worktree, at most every
+    /// `RESOURCE_PRESENCE_TTL`. Once any task is due, the whole board is resolved against a
+    /// single sweep (see `sweep_resources`). Nothing is changed; drift only shows on the cards.
+    fn refresh_resource_presence(&mut self) {
+        let Some(project_path) = self.state.project_path.clone() else { return };
+        let now = Instant::now();
+        let tasks: Vec<&Task> = self.state.board.tasks
+            .iter()
+            .filter(|t| matches!(t.status, TaskStatus::Running | TaskStatus::Review))
+            .collect();
+        self.state.resource_presence_cache.retain(|id, _| tasks.iter().any(|t| &t.id == id));
+
+        let due = tasks.iter().any(|task| {
+            self.state
+                .resource_presence_cache
+                .get(&task.id)
+                .is_none_or(|(_, checked)| now.duration_since(*checked) >= RESOURCE_PRESENCE_TTL)
+        });
+        if !due {
+            return;
+        }
+        let sweep = sweep_resources(
+            &self.state.board.tasks,
+            &project_path,
+            self.state.tmux_ops.as_ref(),
+            self.state.git_ops.as_ref(),
+        );
+        for drift in reconcile_all(&self.state.board.tasks, &sweep.windows, &sweep.worktrees) {
+            self.state.resource_presence_cache.insert(drift.task_id, (drift.presence, now));
+        }
+    }
+
+    fn switch_to_project(&mut self, project: &ProjectInfo) -> Result<()> {
+        self.switch_to_project_keep_sidebar(project)?;
+        // Unfocus sidebar
+        self.state.sidebar_focused = false;
+        Ok(())
+    }
+
+    fn switch_to_project_keep_sidebar(&mut self, project: &ProjectInfo) -> Result<()> {
+        let project_path = PathBuf::from(&project.path);
+
+        // Check if project path exists
+        if !project_path.exists() {
+            // Skip non-existent projects silently
+            return Ok(());
+        }
+
+        self.save_ui_state();
+
+        // Update current project
+        self.state.project_name = project.name.clone();
+        self.state.project_path = Some(project_path.clone());
+
+        // Open project database (create if needed)
+        match Database::open_project(&project_path) {
+            Ok(db) => self.state.db = Some(db),
+            Err(_) => {
+                // If we can't open the db, skip this project
+                return Ok(());
             }
-            KeyCode::Backspace => {
-                if self.state.input_cursor > 0 {
-                    self.state.input_cursor -= 1;
-                    self.state.input_buffer.remove(self.state.input_cursor);
-                }
+        }
+
+        self.check_repo();
+
+        // Reload config and plugin so worktree base branch, copy files, init script
+        // and agent all come from the newly active project
+        let global_config = GlobalConfig::load().unwrap_or_default();
+        let project_config = ProjectConfig::load(&project_path).unwrap_or_default();
+        // Startup refuses a bad project `[hooks]`; here the switch goes ahead and says why
+        // the misnamed hooks will never run
+        if let Err(e) = project_config.hooks.validate() {
+            self.state.warning_message =
+                Some((format!("Invalid [hooks] in {}'s config: {:#}", project.name, e), Instant::now()));
+        }
+        self.state.config = MergedConfig::merge(&global_config, &project_config);
+        let dispatched = self.state.scheduler.set_cap(self.state.config.max_concurrent_agents, self.state.tmux_ops.as_ref());
+        self.save_dispatch_queues();
+        self.report_dispatched(&dispatched);
+        self.state.cached_plugin = Some(load_plugin_if_configured(
+            &self.state.config,
+            Some(&project_path),
+        ));
+        self.state.detected_base_branch = None;
+        self.state.phase_status_cache.clear();
+        self.state.worktree_status_cache.clear();
+        self.state.resource_presence_cache.clear();
+        self.state.board.marked.clear();
+        self.state.board.selected_row = 0;
+
+        // Update last_opened in global db
+        let proj = crate::db::Project::new(&project.name, &project.path);
+        let _ = self.state.global_db.upsert_project(&proj);
+
+        // Ensure tmux session exists
+        let _ = ensure_project_tmux_session(&self.state.tmux_session(), &project_path, self.state.tmux_ops.as_ref());
+
+        // Reload tasks for new project
+        self.restore_dispatch_queue();
+        self.refresh_tasks()?;
+        self.restore_ui_state();
+        self.reconcile_resources();
+
+        Ok(())
+    }
+
+    /// Serve the scripting API (`crate::api`) on a Unix socket until the process is stopped.
+    /// Requests run through the same methods the board's keys do, one at a time.
+    #[cfg(unix)]
+    pub fn serve(&mut self, socket_path: &Path) -> Result<()> {
+        if self.state.db.is_none() {
+            anyhow::bail!("agtx serve needs a project: run it inside a git repository or pass its path");
+        }
+        if socket_path.exists() {
+            // A socket nobody answers on is left over from a previous run
+            if UnixStream::connect(socket_path).is_ok() {
+                anyhow::bail!("Another agtx is already serving on {}", socket_path.display());
             }
-            KeyCode::Delete => {
-                if self.state.input_cursor < self.state.input_buffer.len() {
-                    self.state.input_buffer.remove(self.state.input_cursor);
+            std::fs::remove_file(socket_path)
+                .with_context(|| format!("Failed to remove stale socket {}", socket_path.display()))?;
+        }
+        if let Some(parent) = socket_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let listener = UnixListener::bind(socket_path)
+            .with_context(|| format!("Failed to listen on {}", socket_path.display()))?;
+        eprintln!("agtx: serving API version {} on {}", api::API_VERSION, socket_path.display());
+
+        // Accept without blocking so SIGINT is noticed between requests, never in the middle of one
+        watch_for_interrupt(Arc::clone(&self.state.interrupted));
+        listener.set_nonblocking(true).context("Failed to configure the socket")?;
+        while !self.state.interrupted.load(Ordering::SeqCst) {
+            self.emit_new_events();
+            let result = match listener.accept() {
+                Ok((stream, _)) => stream
+                    .set_nonblocking(false)
+                    .context("Failed to configure the connection")
+                    .and_then(|()| self.serve_connection(stream)),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(SERVE_POLL_INTERVAL);
+                    continue;
                 }
+                Err(e) => Err(e).context("Failed to accept a connection"),
+            };
+            // One client's broken connection doesn't stop the server
+            if let Err(e) = result {
+                eprintln!("agtx: {:#}", e);
             }
-            KeyCode::Char('#') | KeyCode::Char('@') => {
-                // Start file search at cursor position
-                let trigger = if let KeyCode::Char(c) = key.code { c } else { '#' };
-                let start_pos = self.state.input_cursor;
-                self.state.input_buffer.insert(self.state.input_cursor, trigger);
-                self.state.input_cursor += 1;
-                self.state.file_search = Some(FileSearchState {
-                    pattern: String::new(),
-                    matches: vec![],
-                    selected: 0,
-                    start_pos,
-                    trigger_char: trigger,
-                });
-                self.update_file_search_matches();
-            }
-            KeyCode::Char(c) => {
-                self.state.input_buffer.insert(self.state.input_cursor, c);
-                self.state.input_cursor += 1;
+        }
+        self.emit_new_events();
+        let _ = std::fs::remove_file(socket_path);
+        eprintln!("agtx: interrupted, stopped serving");
+        Ok(())
+    }
+
+    /// The API listens on a Unix socket, which this platform doesn't have
+    #[cfg(not(unix))]
+    pub fn serve(&mut self, _socket_path: &Path) -> Result<()> {
+        anyhow::bail!("agtx serve needs Unix sockets, which this platform doesn't have (run it inside WSL)")
+    }
+
+    /// Answer each request line on a connection until the client closes it
+    #[cfg(unix)]
+    fn serve_connection(&mut self, stream: UnixStream) -> Result<()> {
+        let reader = BufReader::new(stream.try_clone().context("Failed to clone the connection")?);
+        let mut writer = stream;
+        for line in reader.lines() {
+            let line = line.context("Failed to read a request")?;
+            if line.trim().is_empty() {
+                continue;
             }
-            _ => {}
+            let response = match api::parse_request(&line) {
+                Ok(request) => match self.handle_api_request(request) {
+                    Ok(result) => api::Response::ok(result),
+                    Err(e) => api::Response::error(format!("{:#}", e)),
+                },
+                Err(e) => api::Response::error(e),
+            };
+            writeln!(writer, "{}", response.to_line()).context("Failed to write a response")?;
+            self.emit_new_events();
         }
         Ok(())
     }
 
-    fn update_file_search_matches(&mut self) {
-        if let (Some(ref mut search), Some(ref project_path)) = (&mut self.state.file_search, &self.state.project_path) {
-            let pattern = &search.pattern;
-            search.matches = fuzzy_find_files(project_path, pattern, 10, self.state.git_ops.as_ref());
-            search.selected = 0;
+    /// Write task events to stdout as they're recorded while serving (`agtx serve --events`),
+    /// starting from now rather than replaying the project's history
+    pub fn stream_events(&mut self) -> Result<()> {
+        let db = self.state.db.as_ref().context("agtx serve --events needs a project")?;
+        self.state.event_cursor = Some(db.last_task_event_id()?);
+        Ok(())
+    }
+
+    /// Print the task events recorded since the last call, one JSON line each
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn emit_new_events(&mut self) {
+        let (Some(cursor), Some(db)) = (self.state.event_cursor, &self.state.db) else { return };
+        let events = match db.task_events_since(cursor) {
+            Ok(events) => events,
+            Err(e) => {
+                error_log::log("Streaming events", &e.to_string());
+                return;
+            }
+        };
+        let Some((last, _, _)) = events.last() else { return };
+        self.state.event_cursor = Some(*last);
+        let mut stdout = io::stdout().lock();
+        for (_, task_id, event) in events {
+            let _ = writeln!(stdout, "{}", api::EventLine::new(task_id, event).to_line());
         }
+        let _ = stdout.flush();
     }
 
-    fn save_task(&mut self) -> Result<()> {
-        if let Some(db) = &self.state.db {
-            if let Some(task_id) = &self.state.editing_task_id {
-                // Editing existing task
-                if let Some(mut task) = db.get_task(task_id)? {
-                    task.title = self.state.pending_task_title.clone();
-                    task.description = if self.state.input_buffer.is_empty() {
-                        None
-                    } else {
-                        Some(self.state.input_buffer.clone())
-                    };
-                    task.updated_at = chrono::Utc::now();
-                    db.update_task(&task)?;
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn handle_api_request(&mut self, request: api::Request) -> Result<serde_json::Value> {
+        // The board (or another client) may have changed tasks since the last request
+        self.refresh_tasks()?;
+        match request {
+            api::Request::ListTasks => {
+                let tasks: Vec<api::TaskInfo> = self.state.board.tasks.iter().map(api::TaskInfo::from).collect();
+                Ok(serde_json::to_value(tasks)?)
+            }
+            api::Request::CreateTask { title, description, agent } => {
+                let title = title.trim();
+                if title.is_empty() {
+                    anyhow::bail!("Task title can't be empty");
                 }
-            } else {
-                // Creating new task
-                let project_id = self.state.project_name.clone();
-                let agent = self.state.config.default_agent.clone();
-
-                let mut task = Task::new(&self.state.pending_task_title, agent, project_id);
-                if !self.state.input_buffer.is_empty() {
-                    task.description = Some(self.state.input_buffer.clone());
+                if let Some(problem) = &self.state.repo_problem {
+                    anyhow::bail!("{}", problem);
                 }
-                // Task starts in Backlog without tmux window
+                let Some(db) = &self.state.db else { anyhow::bail!("No project is open") };
+                let agent = agent.unwrap_or_else(|| self.state.config.default_agent.clone());
+                let mut task = Task::new(title, agent, self.state.project_name.clone());
+                task.description = description.filter(|d| !d.is_empty());
                 db.create_task(&task)?;
+                self.refresh_tasks()?;
+                Ok(serde_json::to_value(api::TaskInfo::from(&task))?)
             }
-            self.refresh_tasks()?;
+            api::Request::MoveTask { id, direction } => {
+                let Some(task) = self.state.board.tasks.iter().find(|t| t.id == id).cloned() else {
+                    anyhow::bail!("No task with id '{}'", id);
+                };
+                match direction {
+                    api::MoveDirection::Forward => match self.move_task_forward(&id)? {
+                        MoveOutcome::Moved => {}
+                        MoveOutcome::Prompted => {
+                            // Nobody is here to answer a popup; a push to an existing PR
+                            // carries on in the background
+                            self.state.pr_status_popup = None;
+                            let confirm = self.state.review_confirm_popup.take().is_some()
+                                | self.state.done_confirm_popup.take().is_some();
+                            if confirm {
+                                anyhow::bail!("Moving '{}' needs a confirmation; move it from the board", task.title);
+                            }
+                        }
+                        MoveOutcome::Skipped(reason) => anyhow::bail!("'{}' not moved: {}", task.title, reason),
+                    },
+                    api::MoveDirection::Back => {
+                        let Some(target) = workflow::resume_status(task.status) else {
+                            anyhow::bail!("Only Running and Review tasks move back ('{}' is {})", task.title, task.status.as_str());
+                        };
+                        workflow::request_transition(&task, target)?;
+                        let result = match target {
+                            TaskStatus::Planning => self.move_running_to_planning(&id),
+                            _ => self.move_review_to_running(&id),
+                        };
+                        self.record_failure(&id, result)?;
+                    }
+                }
+                self.refresh_tasks()?;
+                let task = self.state.board.tasks.iter().find(|t| t.id == id).unwrap_or(&task);
+                Ok(serde_json::to_value(api::TaskInfo::from(task))?)
+            }
+            api::Request::DeleteTask { id } => match self.trash_task(&id)? {
+                Some(_) => {
+                    self.refresh_tasks()?;
+                    Ok(serde_json::json!({ "id": id }))
+                }
+                None => anyhow::bail!("No task with id '{}'", id),
+            },
         }
-        Ok(())
     }
+}
 
-    fn delete_selected_task(&mut self) -> Result<()> {
-        if let Some(task) = self.state.board.selected_task().cloned() {
-            // Show confirmation popup
-            self.state.delete_confirm_popup = Some(DeleteConfirmPopup {
-                task_id: task.id.clone(),
-                task_title: task.title.clone(),
-            });
+impl Drop for App {
+    fn drop(&mut self) {
+        if let Some(terminal) = self.terminal.as_mut() {
+            let _ = disable_raw_mode();
+            let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
         }
-        Ok(())
     }
+}
 
-    fn perform_delete_task(&mut self, task_id: &str) -> Result<()> {
-        if let (Some(db), Some(project_path)) = (&self.state.db, &self.state.project_path) {
-            if let Some(task) = db.get_task(task_id)? {
-                delete_task_resources(
-                    &task,
-                    project_path,
-                    self.state.tmux_ops.as_ref(),
-                    self.state.git_ops.as_ref(),
-                );
-                db.delete_task(&task.id)?;
-                self.refresh_tasks()?;
-            }
-        }
-        Ok(())
+/// Ensure the tmux session for a project exists, creating it in the project root if needed
+fn ensure_project_tmux_session(session: &str, project_path: &Path, tmux_ops: &dyn TmuxOperations) -> Result<()> {
+    tmux_ops
+        .ensure_session(session, &project_path.to_string_lossy())
+        .with_context(|| format!("Failed to set up tmux session '{}'", session))
+}
+
+/// The configured base branch, else the project's default branch. Detection runs git, so its
+/// result is kept in `detected`; a failed detection (or no project) falls back to `main`.
+fn resolve_base_branch(
+    configured: Option<&str>,
+    detected: &mut Option<String>,
+    project_path: Option<&Path>,
+    git_ops: &dyn GitOperations,
+) -> String {
+    if let Some(branch) = configured {
+        return branch.to_string();
     }
+    detected
+        .get_or_insert_with(|| {
+            project_path
+                .and_then(|path| git_ops.default_branch(path).ok())
+                .unwrap_or_else(|| "main".to_string())
+        })
+        .clone()
+}
 
-    fn show_task_diff(&mut self) -> Result<()> {
-        if let Some(task) = self.state.board.selected_task() {
-            let diff_content = if let Some(worktree_path) = &task.worktree_path {
-                let mut exclude_prefixes: Vec<&str> = crate::git::AGENT_CONFIG_DIRS.to_vec();
-                let plugin = self.load_task_plugin(task);
-                let plugin_dirs: Vec<String> = plugin.map_or_else(Vec::new, |p| p.copy_dirs.clone());
-                let plugin_dir_refs: Vec<&str> = plugin_dirs.iter().map(|s| s.as_str()).collect();
-                exclude_prefixes.extend(plugin_dir_refs);
-                collect_task_diff(worktree_path, self.state.git_ops.as_ref(), &exclude_prefixes)
-            } else {
-                "(task has no worktree yet)".to_string()
-            };
+/// Why tasks can't be created because git itself doesn't run, or `None` if it does
+fn git_problem(probe: &OpResult<String>) -> Option<String> {
+    match probe {
+        Ok(_) => None,
+        Err(OpError::CommandNotFound { .. }) => Some(
+            "git not found in PATH - install git (https://git-scm.com/downloads) and restart agtx to create tasks"
+                .to_string(),
+        ),
+        Err(e) => Some(format!("git doesn't run ({}) - fix it and restart agtx to create tasks", e)),
+    }
+}
 
-            self.state.diff_popup = Some(DiffPopup {
-                task_title: task.title.clone(),
-                diff_content,
-                scroll_offset: 0,
-            });
-        }
-        Ok(())
+/// Describe why a project can't host task worktrees, or `None` if it can
+fn repo_problem(project_path: &Path, git_ops: &dyn GitOperations) -> Option<String> {
+    if !git_ops.is_git_repo(project_path) {
+        return Some(format!(
+            "{} is not a git repository - run `git init` to create tasks here",
+            project_path.display()
+        ));
     }
+    if !git_ops.has_commits(project_path) {
+        return Some("Repository has no commits yet - make an initial commit so worktrees can be created".to_string());
+    }
+    None
+}
 
-    fn move_task_right(&mut self) -> Result<()> {
-        // Clone task to avoid borrow issues
-        let (mut task, project_path) = match (
-            self.state.board.selected_task().cloned(),
-            self.state.project_path.clone(),
-        ) {
-            (Some(t), Some(p)) => (t, p),
-            _ => return Ok(()),
-        };
+const AGENT_LOCKED_MESSAGE: &str = "The agent can only be changed while the task is in Backlog or Planning";
 
-        let current_status = task.status;
-        let next_status = match current_status {
-            TaskStatus::Backlog => Some(TaskStatus::Planning),
-            TaskStatus::Planning => Some(TaskStatus::Running),
-            TaskStatus::Running => Some(TaskStatus::Review),
-            TaskStatus::Review => Some(TaskStatus::Done),
-            TaskStatus::Done => None,
-        };
+/// Whether a task in this status may still switch agents (no implementation command sent yet)
+fn agent_editable(status: TaskStatus) -> bool {
+    matches!(status, TaskStatus::Backlog | TaskStatus::Planning)
+}
 
-        if let Some(new_status) = next_status {
-            // Create worktree and tmux window when moving from Backlog to Planning
-            if current_status == TaskStatus::Backlog && new_status == TaskStatus::Planning {
-                // Stamp plugin on task if not already set (may be set from research)
-                if task.plugin.is_none() {
-                    task.plugin = self.state.config.workflow_plugin.clone();
-                }
-                let plugin = self.load_task_plugin(&task);
+/// Apply an in-place edit of title and agent. Status, worktree and tmux window are left alone.
+fn apply_task_edit(task: &mut Task, title: &str, agent: &str) -> std::result::Result<(), String> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err("Task title can't be empty".to_string());
+    }
+    let agent_changed = !agent.is_empty() && agent != task.agent;
+    if agent_changed && !agent_editable(task.status) {
+        return Err(AGENT_LOCKED_MESSAGE.to_string());
+    }
+    task.title = title.to_string();
+    if agent_changed {
+        task.agent = agent.to_string();
+    }
+    task.updated_at = chrono::Utc::now();
+    Ok(())
+}
 
-                let has_live_session = task.session_name.as_ref().map_or(false, |s| {
-                    self.state.tmux_ops.window_exists(s).unwrap_or(false)
-                });
+/// The tmux target to attach to for a task, or a message saying why there is none
+fn attach_target(task: Option<&Task>, tmux_ops: &dyn TmuxOperations) -> std::result::Result<String, String> {
+    let task = task.ok_or_else(|| "No task selected".to_string())?;
+    let target = task.session_name.as_ref().ok_or_else(|| {
+        format!("'{}' has no tmux window yet - move it to Planning to start its agent", task.title)
+    })?;
+    match tmux_ops.window_exists(target) {
+        Ok(true) => Ok(target.clone()),
+        Err(OpError::TmuxDisabled) => Err(OpError::TmuxDisabled.to_string()),
+        _ => Err(format!("tmux window for '{}' no longer exists", task.title)),
+    }
+}
 
-                if has_live_session {
-                    // Reuse existing session from research — just send planning command
-                    let target = task.session_name.clone().unwrap();
-                    let task_content = if let Some(desc) = &task.description {
-                        format!("{}\n\n{}", task.title, desc)
-                    } else {
-                        task.title.clone()
-                    };
-                    let skill_cmd = resolve_skill_command(&plugin, "planning", &self.state.config.default_agent, &task_content);
-                    let prompt = resolve_prompt(&plugin, "planning", &task_content, &task.id, &self.state.config.default_agent);
-
-                    let tmux_ops = Arc::clone(&self.state.tmux_ops);
-                    let task_content_clone = task_content.clone();
-                    std::thread::spawn(move || {
-                        if let Some(ref cmd) = skill_cmd {
-                            let _ = tmux_ops.send_keys(&target, cmd);
-                            std::thread::sleep(std::time::Duration::from_millis(500));
-                        }
-                        if !prompt.is_empty() {
-                            let _ = tmux_ops.send_keys(&target, &prompt);
-                        } else if skill_cmd.is_none() {
-                            // No command and no prompt (e.g. void plugin): prefill task in input
-                            let oneline = task_content_clone.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
-                            if !oneline.is_empty() {
-                                let _ = tmux_ops.send_keys_literal(&target, &oneline);
-                            }
-                        }
-                    });
-                } else {
-                    // No research session — create worktree + tmux window from scratch
-                    let task_content = if let Some(desc) = &task.description {
-                        format!("{}\n\n{}", task.title, desc)
-                    } else {
-                        task.title.clone()
-                    };
-                    let prompt = resolve_prompt(&plugin, "planning", &task_content, &task.id, &self.state.config.default_agent);
-
-                    let target = setup_task_worktree(
-                        &mut task,
-                        &project_path,
-                        &self.state.project_name,
-                        &prompt,
-                        self.state.config.copy_files.clone(),
-                        self.state.config.init_script.clone(),
-                        &plugin,
-                        &self.state.config.default_agent,
-                        self.state.tmux_ops.as_ref(),
-                        self.state.git_ops.as_ref(),
-                        self.state.agent_registry.get(&self.state.config.default_agent).as_ref(),
-                    )?;
-
-                    // Wait for agent to be ready, then send skill command and task content
-                    let target_clone = target.clone();
-                    let tmux_ops = Arc::clone(&self.state.tmux_ops);
-                    let skill_cmd = resolve_skill_command(&plugin, "planning", &self.state.config.default_agent, &task_content);
-                    let prompt_clone = prompt.clone();
-                    let prompt_trigger = resolve_prompt_trigger(&plugin, "planning");
-                    let task_content_clone = task_content.clone();
-                    std::thread::spawn(move || {
-                        if let Some(target) = wait_for_agent_ready(&tmux_ops, &target_clone) {
-                            if let Some(ref cmd) = skill_cmd {
-                                let _ = tmux_ops.send_keys(&target, cmd);
-                                std::thread::sleep(std::time::Duration::from_millis(500));
-                            }
-                            if !prompt_clone.is_empty() {
-                                if let Some(ref trigger) = prompt_trigger {
-                                    if wait_for_prompt_trigger(&tmux_ops, &target, trigger) {
-                                        std::thread::sleep(std::time::Duration::from_millis(500));
-                                        let _ = tmux_ops.send_keys(&target, &prompt_clone);
-                                    }
-                                } else {
-                                    let _ = tmux_ops.send_keys(&target, &prompt_clone);
-                                }
-                            } else if skill_cmd.is_none() {
-                                // No command and no prompt (e.g. void plugin): prefill task in input
-                                let oneline = task_content_clone.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
-                                if !oneline.is_empty() {
-                                    let _ = tmux_ops.send_keys_literal(&target, &oneline);
-                                }
-                            }
-                        }
-                    });
-                }
-            }
+/// Indices of projects whose name or path fuzzy-matches the query (best match first)
+fn filter_projects(projects: &[ProjectInfo], query: &str) -> Vec<usize> {
+    if query.is_empty() {
+        return (0..projects.len()).collect();
+    }
+    let query_lower = query.to_lowercase();
+    let mut scored: Vec<(usize, i32)> = projects
+        .iter()
+        .enumerate()
+        .filter_map(|(i, p)| {
+            let score = fuzzy_score(&p.name.to_lowercase(), &query_lower)
+                .max(fuzzy_score(&p.path.to_lowercase(), &query_lower));
+            (score > 0).then_some((i, score))
+        })
+        .collect();
+    scored.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
 
-            // When moving from Planning to Running, send skill command to agent
-            if current_status == TaskStatus::Planning && new_status == TaskStatus::Running {
-                if let Some(session_name) = &task.session_name {
-                    let plugin = self.load_task_plugin(&task);
-                    let task_content = if let Some(desc) = &task.description {
-                        format!("{}\n\n{}", task.title, desc)
-                    } else {
-                        task.title.clone()
-                    };
-                    let skill_cmd = resolve_skill_command(&plugin, "running", &self.state.config.default_agent, &task_content);
-                    if let Some(cmd) = skill_cmd {
-                        let _ = self.state.tmux_ops.send_keys(session_name, &cmd);
-                    } else {
-                        let msg = resolve_prompt(&plugin, "running", &task_content, &task.id, &self.state.config.default_agent);
-                        if !msg.is_empty() {
-                            let _ = self.state.tmux_ops.send_keys(session_name, &msg);
-                        }
-                    }
-                }
-            }
+/// Agent names to choose from: the installed agents, with `current` first if it isn't installed
+fn agent_choices(available: &[agent::Agent], current: &str) -> Vec<String> {
+    let mut agents: Vec<String> = available.iter().map(|a| a.name.clone()).collect();
+    if !current.is_empty() && !agents.iter().any(|a| a == current) {
+        agents.insert(0, current.to_string());
+    }
+    agents
+}
 
-            // When moving from Running to Review: send skill command, then ask about PR
-            if current_status == TaskStatus::Running && new_status == TaskStatus::Review {
-                // Send review skill command to agent
-                if let Some(session_name) = &task.session_name {
-                    let plugin = self.load_task_plugin(&task);
-                    let task_content = if let Some(desc) = &task.description {
-                        format!("{}\n\n{}", task.title, desc)
-                    } else {
-                        task.title.clone()
-                    };
-                    let skill_cmd = resolve_skill_command(&plugin, "review", &self.state.config.default_agent, &task_content);
-                    if let Some(cmd) = skill_cmd {
-                        let _ = self.state.tmux_ops.send_keys(session_name, &cmd);
-                    } else {
-                        let msg = resolve_prompt(&plugin, "review", &task_content, &task.id, &self.state.config.default_agent);
-                        if !msg.is_empty() {
-                            let _ = self.state.tmux_ops.send_keys(session_name, &msg);
-                        }
-                    }
-                }
-                // Check if PR already exists (task was resumed from Review)
-                if task.pr_number.is_some() {
-                    // PR already exists - just commit and push the new changes
-                    self.state.pr_status_popup = Some(PrStatusPopup {
-                        status: PrCreationStatus::Pushing,
-                        pr_url: None,
-                        error_message: None,
-                    });
+/// The agent after (or before) `current` in `agents`, wrapping around
+fn cycle_agent(agents: &[String], current: &str, forward: bool) -> String {
+    if agents.is_empty() {
+        return current.to_string();
+    }
+    let len = agents.len();
+    let index = agents.iter().position(|a| a == current).unwrap_or(0);
+    let next = if forward { (index + 1) % len } else { (index + len - 1) % len };
+    agents[next].clone()
+}
 
-                    let task_clone = task.clone();
-                    let project_path_clone = project_path.clone();
-                    let git_ops = Arc::clone(&self.state.git_ops);
-                    let agent_ops = self.state.agent_registry.get(&self.state.config.default_agent);
-
-                    let (tx, rx) = mpsc::channel();
-                    self.state.pr_creation_rx = Some(rx);
-
-                    std::thread::spawn(move || {
-                        let result = push_changes_to_existing_pr(&task_clone, git_ops.as_ref(), agent_ops.as_ref());
-                        match result {
-                            Ok(pr_url) => {
-                                // Update task in database
-                                // Keep session_name so popup can still be opened in Review
-                                if let Ok(db) = crate::db::Database::open_project(&project_path_clone) {
-                                    let mut updated_task = task_clone;
-                                    updated_task.status = TaskStatus::Review;
-                                    updated_task.updated_at = chrono::Utc::now();
-                                    let _ = db.update_task(&updated_task);
-                                }
-                                let _ = tx.send(Ok((0, pr_url)));
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Err(e.to_string()));
-                            }
-                        }
-                    });
+/// Apply a key to the help overlay; false when the key closes it
+fn scroll_help(popup: &mut HelpPopup, key: KeyCode) -> bool {
+    let max = popup.max_scroll.get();
+    match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('?') | KeyCode::Enter => return false,
+        KeyCode::Char('j') | KeyCode::Down => popup.scroll = (popup.scroll + 1).min(max),
+        KeyCode::Char('k') | KeyCode::Up => popup.scroll = popup.scroll.saturating_sub(1),
+        KeyCode::Char('d') | KeyCode::PageDown => popup.scroll = (popup.scroll + 10).min(max),
+        KeyCode::Char('u') | KeyCode::PageUp => popup.scroll = popup.scroll.saturating_sub(10),
+        KeyCode::Char('g') | KeyCode::Home => popup.scroll = 0,
+        KeyCode::Char('G') | KeyCode::End => popup.scroll = max,
+        _ => {}
+    }
+    true
+}
 
-                    // Keep tmux window open - session_name stays set for resume
+/// Token usage for the stats and detail popups: `12.3k in / 4.5k out, ~$0.25`
+fn format_usage(usage: &AgentUsage) -> String {
+    let tokens = format!(
+        "{} in / {} out",
+        agent::usage::format_count(usage.tokens_in),
+        agent::usage::format_count(usage.tokens_out)
+    );
+    match usage.cost_usd {
+        Some(cost) => format!("{}, ~${:.2}", tokens, cost),
+        None => tokens,
+    }
+}
 
-                    return Ok(());
-                }
+/// Count tasks whose recorded tmux window is still alive
+fn count_live_windows(tasks: &[Task], tmux_ops: &dyn TmuxOperations) -> usize {
+    tasks
+        .iter()
+        .filter_map(|t| t.session_name.as_deref())
+        .filter(|target| tmux_ops.window_exists(target).unwrap_or(false))
+        .count()
+}
 
-                // No PR yet - show confirmation popup asking if user wants to create PR
-                self.state.review_confirm_popup = Some(ReviewConfirmPopup {
-                    task_id: task.id.clone(),
-                    task_title: task.title.clone(),
-                });
-                return Ok(());
-            }
+/// Running/Review tasks whose recorded tmux window or worktree doesn't exist, in board order.
+/// A window that can't be checked (tmux errors) is given the benefit of the doubt.
+fn find_resource_mismatches(
+    tasks: &[Task],
+    project_path: &Path,
+    tmux_ops: &dyn TmuxOperations,
+    git_ops: &dyn GitOperations,
+) -> Vec<(String, MissingResource)> {
+    let sweep = sweep_resources(tasks, project_path, tmux_ops, git_ops);
+    reconcile_all(tasks, &sweep.windows, &sweep.worktrees)
+        .into_iter()
+        .filter_map(|drift| {
+            let mismatch = drift.presence.missing()?;
+            Some((drift.task_id, mismatch))
+        })
+        .collect()
+}
 
-            // When moving from Review to Done: Show confirmation with PR state
-            if current_status == TaskStatus::Review && new_status == TaskStatus::Done {
-                if let Some(pr_number) = task.pr_number {
-                    let pr_state = self.state.git_provider_ops.get_pr_state(&project_path, pr_number)?;
+/// What exists, looked up once for a whole board: the window names in each session a task
+/// records (None when the multiplexer couldn't list them) and the project's worktree
+/// directory names
+#[derive(Debug, Default)]
+struct ResourceSweep {
+    windows: HashMap<String, Option<HashSet<String>>>,
+    worktrees: HashSet<String>,
+}
 
-                    let confirm_state = match pr_state {
-                        PullRequestState::Merged => DoneConfirmPrState::Merged,
-                        PullRequestState::Closed => DoneConfirmPrState::Closed,
-                        PullRequestState::Open => DoneConfirmPrState::Open,
-                        PullRequestState::Unknown => DoneConfirmPrState::Unknown,
-                    };
+/// One `list_windows` per session and one `list_worktrees` for the project, instead of a
+/// lookup per task. If git can't list the worktrees, each task's directory is checked instead.
+fn sweep_resources(
+    tasks: &[Task],
+    project_path: &Path,
+    tmux_ops: &dyn TmuxOperations,
+    git_ops: &dyn GitOperations,
+) -> ResourceSweep {
+    let active: Vec<&Task> =
+        tasks.iter().filter(|t| matches!(t.status, TaskStatus::Running | TaskStatus::Review)).collect();
+    if active.is_empty() {
+        return ResourceSweep::default();
+    }
 
-                    self.state.done_confirm_popup = Some(DoneConfirmPopup {
-                        task_id: task.id.clone(),
-                        pr_number,
-                        pr_state: confirm_state,
-                    });
-                    return Ok(());
+    let mut windows = HashMap::new();
+    for task in &active {
+        let Some(target) = task.session_name.as_deref() else { continue };
+        let (session, _) = crate::tmux::split_target(target);
+        if !windows.contains_key(session) {
+            let names = tmux_ops.list_windows(session).ok().map(|names| names.into_iter().collect());
+            windows.insert(session.to_string(), names);
+        }
+    }
+
+    let worktrees = match git_ops.list_worktrees(project_path) {
+        Ok(paths) => paths
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str().map(str::to_string))
+            .collect(),
+        Err(e) => {
+            error_log::log("List worktrees", &e.to_string());
+            active
+                .iter()
+                .filter_map(|task| worktree_slug(task))
+                .filter(|slug| git_ops.worktree_exists(project_path, slug))
+                .map(str::to_string)
+                .collect()
+        }
+    };
+    ResourceSweep { windows, worktrees }
+}
+
+/// A Running/Review task's window and worktree as found by a sweep
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Drift {
+    task_id: String,
+    presence: ResourcePresence,
+}
+
+/// Resolve every Running/Review task against one sweep, in board order, without further
+/// tmux or git calls. A session missing from `windows` or listed as None leaves the task's
+/// window unknown.
+fn reconcile_all(
+    tasks: &[Task],
+    windows: &HashMap<String, Option<HashSet<String>>>,
+    worktrees: &HashSet<String>,
+) -> Vec<Drift> {
+    tasks
+        .iter()
+        .filter(|t| matches!(t.status, TaskStatus::Running | TaskStatus::Review))
+        .map(|task| {
+            let window = match task.session_name.as_deref() {
+                Some(target) => {
+                    let (session, window) = crate::tmux::split_target(target);
+                    windows.get(session).and_then(Option::as_ref).map(|names| names.contains(window))
                 }
-                // No PR - allow moving to Done directly (task might have been abandoned early)
-                // Cleanup resources (but don't set status yet - that's done below)
-                cleanup_task_for_done(
-                    &mut task,
-                    &project_path,
-                    self.state.tmux_ops.as_ref(),
-                    self.state.git_ops.as_ref(),
-                );
-            }
+                None => Some(false),
+            };
+            let worktree = worktree_slug(task).is_some_and(|slug| worktrees.contains(slug));
+            Drift { task_id: task.id.clone(), presence: ResourcePresence { window, worktree } }
+        })
+        .collect()
+}
 
-            task.status = new_status;
-            task.updated_at = chrono::Utc::now();
+/// The slug a task's worktree directory is named after
+fn worktree_slug(task: &Task) -> Option<&str> {
+    task.worktree_path.as_deref().and_then(|wt| Path::new(wt).file_name()).and_then(|slug| slug.to_str())
+}
 
-            if let Some(db) = &self.state.db {
-                db.update_task(&task)?;
-            }
+/// Whether a task's window and worktree exist right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ResourcePresence {
+    /// None when the multiplexer couldn't say (tmux disabled or erroring)
+    window: Option<bool>,
+    worktree: bool,
+}
+
+impl ResourcePresence {
+    /// What's gone; a window the multiplexer couldn't check counts as there
+    fn missing(&self) -> Option<MissingResource> {
+        match (self.window.unwrap_or(true), self.worktree) {
+            (true, true) => None,
+            (false, true) => Some(MissingResource::Window),
+            (true, false) => Some(MissingResource::Worktree),
+            (false, false) => Some(MissingResource::WindowAndWorktree),
         }
-        self.refresh_tasks()?;
-        Ok(())
     }
+}
 
-    /// Start a research session for a Backlog task (creates worktree, reused in planning)
-    fn start_research(&mut self, task_id: &str) -> Result<()> {
-        let mut task = {
-            let Some(db) = &self.state.db else { return Ok(()) };
-            let Some(task) = db.get_task(task_id)? else { return Ok(()) };
-            task
-        };
-        let Some(project_path) = self.state.project_path.clone() else { return Ok(()) };
-        // Stamp plugin on task for research
-        task.plugin = self.state.config.workflow_plugin.clone();
-        let plugin = self.load_task_plugin(&task);
-        let agent_name = self.state.config.default_agent.clone();
+/// The card badges for a task's cached presence: (glyph, present), window first. A window the
+/// multiplexer couldn't check gets no badge.
+fn presence_badges(presence: Option<ResourcePresence>) -> Vec<(&'static str, bool)> {
+    let Some(presence) = presence else { return Vec::new() };
+    let window = presence.window.map(|present| ("\u{25a3}", present));
+    window.into_iter().chain([("\u{2387}", presence.worktree)]).collect()
+}
 
-        let task_content = if let Some(desc) = &task.description {
-            format!("{}\n\n{}", task.title, desc)
-        } else {
-            task.title.clone()
+/// Drop the window and worktree a task still records but that are gone, so cleanup only
+/// touches what exists. A window the multiplexer couldn't check is kept.
+fn forget_missing_resources(task: &mut Task, project_path: &Path, tmux_ops: &dyn TmuxOperations, git_ops: &dyn GitOperations) {
+    let presence = resource_presence(task, project_path, tmux_ops, git_ops);
+    if presence.window == Some(false) {
+        task.session_name = None;
+    }
+    if !presence.worktree {
+        task.worktree_path = None;
+    }
+}
+
+/// Footer warning for a title `others` other open tasks share (None when there are none)
+fn title_conflict_warning(title: &str, others: usize) -> Option<String> {
+    match others {
+        0 => None,
+        1 => Some(format!("Another open task is also titled '{}'; consider renaming one", title)),
+        n => Some(format!("{} other open tasks are also titled '{}'; consider renaming them", n, title)),
+    }
+}
+
+/// History detail for a task moved straight to Done, e.g. `from running: superseded by #12`
+fn abandon_detail(from: TaskStatus, reason: &str) -> String {
+    format!("from {}: {}", from.as_str(), reason.trim())
+}
+
+/// The latest commits the task's branch added on top of what it branched from (its `from_ref`,
+/// else `base_branch`) for the detail view, or None when the task has no worktree or git can't
+/// read it
+fn task_recent_commits(task: &Task, base_branch: &str, git_ops: &dyn GitOperations) -> Option<Vec<CommitSummary>> {
+    let worktree = task.worktree_path.as_deref()?;
+    let base = task.from_ref.as_deref().unwrap_or(base_branch);
+    git_ops.recent_commits(Path::new(worktree), base, DETAIL_RECENT_COMMITS).ok()
+}
+
+/// Look up a task's window and worktree. Read-only: only asks tmux and git.
+fn resource_presence(
+    task: &Task,
+    project_path: &Path,
+    tmux_ops: &dyn TmuxOperations,
+    git_ops: &dyn GitOperations,
+) -> ResourcePresence {
+    let window = match task.session_name.as_deref() {
+        Some(target) => tmux_ops.window_exists(target).ok(),
+        None => Some(false),
+    };
+    let worktree = worktree_slug(task).is_some_and(|slug| git_ops.worktree_exists(project_path, slug));
+    ResourcePresence { window, worktree }
+}
+
+/// Bring back what a restored Running/Review task is missing. A missing worktree is checked out
+/// again on the task's branch, or created from `base_branch` when the branch is gone too; a
+/// window left open in the vanished worktree is closed. The agent then gets a new window,
+/// rejoining its recorded conversation when it can (see `restart_agent_window`).
+/// Returns whether the worktree had to be recreated.
+#[allow(clippy::too_many_arguments)]
+fn restore_task_resources(
+    task: &mut Task,
+    missing: MissingResource,
+    project_path: &Path,
+    base_branch: &str,
+    session: &str,
+    prompt: &str,
+    copy_files: Option<String>,
+    init_script: Option<String>,
+    plugin: &Option<WorkflowPlugin>,
+    window: &WindowSetup,
+    tmux_ops: &dyn TmuxOperations,
+    git_ops: &dyn GitOperations,
+    agent_ops: &dyn AgentOperations,
+) -> Result<bool> {
+    let worktree_missing = missing != MissingResource::Window;
+    if worktree_missing {
+        let slug = task
+            .worktree_path
+            .as_deref()
+            .and_then(|wt| Path::new(wt).file_name())
+            .and_then(|slug| slug.to_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| task.resource_slug());
+        let path = match task.branch_name.as_deref().map(|branch| git_ops.checkout_worktree(project_path, &slug, branch)) {
+            Some(Ok(path)) => path,
+            _ => {
+                let base = task_base_ref(task, base_branch, project_path, git_ops)?;
+                let path = git_ops
+                    .create_worktree(project_path, &slug, &base)
+                    .context("Failed to create worktree")?;
+                task.branch_name = Some(format!("task/{}", slug));
+                task.slug = Some(slug.clone());
+                path
+            }
         };
+        let copy_dirs = plugin.as_ref().map_or_else(Vec::new, |p| p.copy_dirs.clone());
+        for warning in git_ops.initialize_worktree(project_path, Path::new(&path), copy_files, init_script, copy_dirs) {
+            eprintln!("Worktree init: {}", warning);
+        }
+        task.worktree_path = Some(path);
+        if missing == MissingResource::Worktree {
+            if let Some(target) = &task.session_name {
+                let _ = tmux_ops.kill_window(target);
+            }
+        }
+    }
+    restart_agent_window(task, session, project_path, prompt, window, tmux_ops, agent_ops)?;
+    Ok(worktree_missing)
+}
+
+/// Whether moving a task to Done needs a yes/no confirmation first.
+/// Only tasks that still own a tmux window or worktree lose anything on cleanup.
+fn needs_destroy_confirmation(task: &Task, confirm_destructive: bool, cleanup: CleanupPolicy) -> bool {
+    confirm_destructive
+        && ((cleanup.kill_window && task.session_name.is_some())
+            || (cleanup.remove_worktree && task.worktree_path.is_some()))
+}
 
-        let prompt = resolve_prompt(&plugin, "research", &task_content, &task.id, &agent_name);
+/// Describe the resources cleanup will destroy, for confirmation popups
+fn describe_destroyed_resources(task: &Task, cleanup: CleanupPolicy) -> String {
+    let mut lines = Vec::new();
+    if let Some(session_name) = task.session_name.as_ref().filter(|_| cleanup.kill_window) {
+        lines.push(format!("tmux window {} will be killed.", session_name));
+    }
+    if let Some(worktree) = task.worktree_path.as_ref().filter(|_| cleanup.remove_worktree) {
+        lines.push(format!("Worktree {} will be removed.", worktree));
+        if let Some(branch) = task.branch_name.as_ref().filter(|_| cleanup.delete_branch) {
+            lines.push(format!("Branch {} will be deleted.", branch));
+        }
+    }
+    if lines.is_empty() {
+        "No worktree or tmux window to clean up.".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
 
-        // Create worktree + tmux window (same as planning, so it can be reused)
-        let target = setup_task_worktree(
-            &mut task,
-            &project_path,
-            &self.state.project_name,
-            &prompt,
-            self.state.config.copy_files.clone(),
-            self.state.config.init_script.clone(),
-            &plugin,
-            &agent_name,
-            self.state.tmux_ops.as_ref(),
-            self.state.git_ops.as_ref(),
-            self.state.agent_registry.get(&agent_name).as_ref(),
-        )?;
+/// Review → Running for follow-up work. The window, worktree and PR are kept as they are,
+/// so nothing is recreated. Returns false (and leaves the task alone) if it isn't in Review.
+fn resume_from_review(task: &mut Task) -> bool {
+    if task.status != TaskStatus::Review || workflow::request_transition(task, TaskStatus::Running).is_err() {
+        return false;
+    }
+    task.status = TaskStatus::Running;
+    task.updated_at = chrono::Utc::now();
+    true
+}
 
-        // Send research skill command + task prompt (with optional trigger polling)
-        let target_clone = target.clone();
-        let tmux_ops = Arc::clone(&self.state.tmux_ops);
-        let skill_cmd = resolve_skill_command(&plugin, "research", &agent_name, &task_content);
-        let prompt_clone = prompt.clone();
-        let prompt_trigger = resolve_prompt_trigger(&plugin, "research");
-        let task_content_clone = task_content.clone();
-        std::thread::spawn(move || {
-            if let Some(target) = wait_for_agent_ready(&tmux_ops, &target_clone) {
-                if let Some(ref cmd) = skill_cmd {
-                    let _ = tmux_ops.send_keys(&target, cmd);
-                    std::thread::sleep(std::time::Duration::from_millis(500));
+/// Resume every Review task in `tasks`. `reopen` brings back an agent whose window was closed
+/// (`App::reopen_agent_window`) and returns true when it did, having taken care of `follow_up`
+/// itself; otherwise a non-blank `follow_up` is typed into the task's window. Returns the
+/// updated tasks (for the caller to save), how many got the prompt, and the tasks left in
+/// Review because their agent couldn't be brought back.
+fn resume_review_tasks(
+    tasks: &[Task],
+    follow_up: &str,
+    tmux_ops: &dyn TmuxOperations,
+    mut reopen: impl FnMut(&mut Task) -> Result<bool>,
+) -> (Vec<Task>, usize, Vec<(String, anyhow::Error)>) {
+    let follow_up = follow_up.trim();
+    let mut resumed = Vec::new();
+    let mut prompted = 0;
+    let mut unreachable = Vec::new();
+    for task in tasks {
+        let mut task = task.clone();
+        if !resume_from_review(&mut task) {
+            continue;
+        }
+        match reopen(&mut task) {
+            Ok(true) => {
+                if !follow_up.is_empty() {
+                    prompted += 1;
                 }
-                if !prompt_clone.is_empty() {
-                    if let Some(ref trigger) = prompt_trigger {
-                        // Wait for the trigger text before sending the prompt
-                        if wait_for_prompt_trigger(&tmux_ops, &target, trigger) {
-                            std::thread::sleep(std::time::Duration::from_millis(500));
-                            let _ = tmux_ops.send_keys(&target, &prompt_clone);
-                        }
-                    } else {
-                        let _ = tmux_ops.send_keys(&target, &prompt_clone);
-                    }
-                } else if skill_cmd.is_none() {
-                    // No command and no prompt (e.g. void plugin): prefill task in input
-                    let oneline = task_content_clone.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
-                    if !oneline.is_empty() {
-                        let _ = tmux_ops.send_keys_literal(&target, &oneline);
+            }
+            Ok(false) => {
+                if let Some(window) = task.session_name.as_ref().filter(|_| !follow_up.is_empty()) {
+                    if tmux_ops.send_keys(window, follow_up).is_ok() {
+                        prompted += 1;
                     }
                 }
             }
-        });
-
-        // Store session name — task stays in Backlog
-        task.updated_at = chrono::Utc::now();
-        if let Some(db) = &self.state.db {
-            db.update_task(&task)?;
+            Err(e) => {
+                unreachable.push((task.title.clone(), e));
+                continue;
+            }
         }
-        self.refresh_tasks()?;
+        resumed.push(task);
+    }
+    (resumed, prompted, unreachable)
+}
 
-        Ok(())
+/// Column a task moves to on "move forward" (None once it's Done)
+fn next_status(status: TaskStatus) -> Option<TaskStatus> {
+    match status {
+        TaskStatus::Backlog => Some(TaskStatus::Planning),
+        TaskStatus::Planning => Some(TaskStatus::Running),
+        TaskStatus::Running => Some(TaskStatus::Review),
+        TaskStatus::Review => Some(TaskStatus::Done),
+        TaskStatus::Done => None,
     }
+}
 
-    /// Move task directly from Backlog to Running (skip Planning)
-    fn move_backlog_to_running(&mut self) -> Result<()> {
-        let (mut task, project_path) = match (
-            self.state.board.selected_task().cloned(),
-            self.state.project_path.clone(),
-        ) {
-            (Some(t), Some(p)) => (t, p),
-            _ => return Ok(()),
-        };
+/// Whether moving a task forward from `status` may stop at a confirmation popup
+/// (PR prompt on Running → Review, Done confirmation on Review → Done)
+fn move_may_prompt(status: TaskStatus) -> bool {
+    matches!(status, TaskStatus::Running | TaskStatus::Review)
+}
 
-        if task.status != TaskStatus::Backlog {
-            return Ok(());
-        }
+/// What a forward move did with a task
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MoveOutcome {
+    Moved,
+    /// Stopped at a confirmation popup (or a background push); finishes once the user answers
+    Prompted,
+    /// Not moved, with the reason
+    Skipped(String),
+}
 
-        // Build prompt - skip planning, go straight to implementation
-        let task_content = if let Some(desc) = &task.description {
-            format!("{}\n\n{}", task.title, desc)
+/// A failed operation for the error bar: what was being done and why it failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ErrorReport {
+    action: String,
+    message: String,
+}
+
+impl ErrorReport {
+    /// The outermost context names the action; the rest of the chain is the message.
+    /// Errors without context are reported as a generic failed operation.
+    fn from_error(error: &anyhow::Error) -> Self {
+        let mut chain = error.chain().map(|e| e.to_string());
+        let first = chain.next().unwrap_or_default();
+        let rest: Vec<String> = chain.collect();
+        if rest.is_empty() {
+            Self { action: "Operation failed".to_string(), message: first }
         } else {
-            task.title.clone()
-        };
-        let prompt = format!(
-            "Task: {}\n\nPlease implement this task directly. No need to plan first - go ahead and make the changes.",
-            task_content
-        );
+            Self { action: first, message: rest.join(": ") }
+        }
+    }
+}
 
-        // Stamp plugin on task
-        task.plugin = self.state.config.workflow_plugin.clone();
-        let plugin = self.load_task_plugin(&task);
-        let target = setup_task_worktree(
-            &mut task,
-            &project_path,
-            &self.state.project_name,
-            &prompt,
-            self.state.config.copy_files.clone(),
-            self.state.config.init_script.clone(),
-            &plugin,
-            &self.state.config.default_agent,
-            self.state.tmux_ops.as_ref(),
-            self.state.git_ops.as_ref(),
-            self.state.agent_registry.get(&self.state.config.default_agent).as_ref(),
-        )?;
+/// Most `git worktree add` processes a bulk move runs at once. Each takes the repository's
+/// worktree lock briefly, so a few overlap well while many would just queue on it.
+const WORKTREE_POOL_SIZE: usize = 4;
 
-        // Wait for agent to be ready, then send execute skill command and task content
-        let target_clone = target.clone();
-        let tmux_ops = Arc::clone(&self.state.tmux_ops);
-        let skill_cmd = resolve_skill_command(&plugin, "running", &self.state.config.default_agent, &task_content);
-        let prompt_clone = prompt.clone();
-        let prompt_trigger = resolve_prompt_trigger(&plugin, "running");
-        std::thread::spawn(move || {
-            if let Some(target) = wait_for_agent_ready(&tmux_ops, &target_clone) {
-                if let Some(ref cmd) = skill_cmd {
-                    let _ = tmux_ops.send_keys(&target, cmd);
-                    std::thread::sleep(std::time::Duration::from_millis(500));
-                }
-                if !prompt_clone.is_empty() {
-                    if let Some(ref trigger) = prompt_trigger {
-                        if wait_for_prompt_trigger(&tmux_ops, &target, trigger) {
-                            std::thread::sleep(std::time::Duration::from_millis(500));
-                            let _ = tmux_ops.send_keys(&target, &prompt_clone);
-                        }
-                    } else {
-                        let _ = tmux_ops.send_keys(&target, &prompt_clone);
-                    }
+/// Remove a worktree created ahead of a move that didn't happen, with its `task/<slug>` branch
+fn discard_worktree(project_path: &Path, worktree: &str, git_ops: &dyn GitOperations) {
+    let _ = git_ops.remove_worktree(project_path, worktree);
+    if let Some(slug) = Path::new(worktree).file_name().and_then(|s| s.to_str()) {
+        let _ = git_ops.delete_branch(project_path, &format!("task/{}", slug));
+    }
+}
+
+/// Create worktrees for `(task id, slug)` pairs concurrently, at most `WORKTREE_POOL_SIZE` at a
+/// time. Results come back in input order; one task's failure doesn't affect the others.
+fn create_worktrees_parallel(
+    project_path: &Path,
+    base_branch: &str,
+    slugs: &[(String, String)],
+    git_ops: &dyn GitOperations,
+) -> Vec<(String, crate::op_error::OpResult<String>)> {
+    let next = std::sync::atomic::AtomicUsize::new(0);
+    let results = std::sync::Mutex::new(Vec::with_capacity(slugs.len()));
+    std::thread::scope(|scope| {
+        for _ in 0..WORKTREE_POOL_SIZE.min(slugs.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let Some((task_id, slug)) = slugs.get(index) else { break };
+                let result = git_ops.create_worktree(project_path, slug, base_branch);
+                if let Ok(mut results) = results.lock() {
+                    results.push((index, task_id.clone(), result));
                 }
-            }
-        });
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap_or_default();
+    results.sort_by_key(|(index, _, _)| *index);
+    results.into_iter().map(|(_, task_id, result)| (task_id, result)).collect()
+}
 
-        task.status = TaskStatus::Running;
-        task.updated_at = chrono::Utc::now();
+/// Tally of a bulk move, shown in the footer when it's done
+#[derive(Debug, Default)]
+struct BulkMoveSummary {
+    moved: usize,
+    prompted: Vec<String>,          // Titles waiting on a popup
+    skipped: Vec<(String, String)>, // (title, reason)
+}
 
-        if let Some(db) = &self.state.db {
-            db.update_task(&task)?;
+impl BulkMoveSummary {
+    fn message(&self) -> String {
+        let mut parts = vec![format!("Moved {} task(s)", self.moved)];
+        if !self.prompted.is_empty() {
+            parts.push(format!("confirm '{}'", self.prompted.join("', '")));
         }
-        self.refresh_tasks()?;
-        Ok(())
+        if !self.skipped.is_empty() {
+            let skipped: Vec<String> = self
+                .skipped
+                .iter()
+                .map(|(title, reason)| format!("'{}' ({})", title, reason))
+                .collect();
+            parts.push(format!("skipped {}: {}", self.skipped.len(), skipped.join(", ")));
+        }
+        parts.join("; ")
     }
+}
 
-    /// Move task from Review back to Running (only allowed transition backwards)
-    /// The tmux window should still be open from when it was in Running state
-    fn move_review_to_running(&mut self, task_id: &str) -> Result<()> {
-        if let (Some(db), Some(_project_path)) = (&self.state.db, &self.state.project_path) {
-            if let Some(mut task) = db.get_task(task_id)? {
-                if task.status != TaskStatus::Review {
-                    return Ok(());
-                }
+/// Most changed files listed in a generated commit message; the rest are counted
+const COMMIT_MESSAGE_MAX_FILES: usize = 20;
+
+/// Commit message from the task title and `git diff --stat` output: the title, then the
+/// per-file lines (at most `COMMIT_MESSAGE_MAX_FILES`) and the totals line. An empty diff
+/// gives just the title.
+fn generate_commit_message(task: &Task, diff_summary: &str) -> String {
+    let title = task.title.lines().next().unwrap_or("").trim();
+    let title = if title.is_empty() { "Update task" } else { title };
+    let lines: Vec<&str> = diff_summary.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let Some((totals, files)) = lines.split_last() else {
+        return title.to_string();
+    };
+    // Without a totals line (e.g. a summary that isn't from --stat) every line is a file
+    let (totals, files) = if totals.contains("changed") { (Some(*totals), files) } else { (None, &lines[..]) };
 
-                // Just move the task back to Running - the tmux window should still be open
-                task.status = TaskStatus::Running;
-                task.updated_at = chrono::Utc::now();
-                db.update_task(&task)?;
-                self.refresh_tasks()?;
-            }
-        }
-        Ok(())
+    let mut body: Vec<String> = files.iter().take(COMMIT_MESSAGE_MAX_FILES).map(|f| f.to_string()).collect();
+    if files.len() > COMMIT_MESSAGE_MAX_FILES {
+        body.push(format!("... and {} more file(s)", files.len() - COMMIT_MESSAGE_MAX_FILES));
     }
+    body.extend(totals.map(str::to_string));
+    format!("{}\n\n{}", title, body.join("\n"))
+}
 
-    fn move_running_to_planning(&mut self, task_id: &str) -> Result<()> {
-        if let (Some(db), Some(_project_path)) = (&self.state.db, &self.state.project_path) {
-            if let Some(mut task) = db.get_task(task_id)? {
-                if task.status != TaskStatus::Running {
-                    return Ok(());
-                }
+/// Commit all worktree changes for a task entering Review, described by `generate_commit_message`.
+/// Returns `None` if the task has no worktree or nothing to commit.
+fn auto_commit_for_review(task: &Task, git_ops: &dyn GitOperations) -> Result<Option<crate::git::CommitId>> {
+    let Some(worktree) = &task.worktree_path else { return Ok(None) };
+    let worktree = Path::new(worktree);
+    // Stage first so new files are part of the summary
+    git_ops.add_all(worktree)?;
+    let message = generate_commit_message(task, &git_ops.diff_stat_cached(worktree));
+    Ok(git_ops.commit_all(worktree, &message)?)
+}
 
-                // Just move the task back to Planning - the tmux window should still be open
-                task.status = TaskStatus::Planning;
-                task.updated_at = chrono::Utc::now();
-                db.update_task(&task)?;
-                self.refresh_tasks()?;
-            }
+/// Apply the dirty-worktree policy before a task's worktree is removed.
+/// Errors (abort policy, or a failed status check/stash) mean the worktree must be kept.
+fn guard_dirty_worktree(task: &Task, git_ops: &dyn GitOperations, policy: DirtyWorktreePolicy) -> Result<()> {
+    let Some(worktree) = &task.worktree_path else { return Ok(()) };
+    if policy == DirtyWorktreePolicy::Force {
+        return Ok(());
+    }
+    if !git_ops.is_worktree_dirty(Path::new(worktree))? {
+        return Ok(());
+    }
+    match policy {
+        DirtyWorktreePolicy::Abort => anyhow::bail!(
+            "Worktree {} has uncommitted changes; commit them or set worktree.dirty_policy",
+            worktree
+        ),
+        DirtyWorktreePolicy::Stash => {
+            let name = format!("agtx: {} ({})", task.title, task.branch_name.as_deref().unwrap_or(&task.id));
+            git_ops.stash_worktree(Path::new(worktree), &name)?;
+            Ok(())
         }
-        Ok(())
+        DirtyWorktreePolicy::Force => Ok(()),
     }
+}
 
-    fn open_selected_task(&mut self) -> Result<()> {
-        if let Some(task) = self.state.board.selected_task() {
-            if let Some(window_name) = &task.session_name.clone() {
-                let mut popup = ShellPopup::new(task.title.clone(), window_name.clone());
-
-                // Resize tmux window to match popup dimensions (uses same constants as draw_shell_popup)
-                if let Ok((_term_width, term_height)) = crossterm::terminal::size() {
-                    let pane_width = SHELL_POPUP_CONTENT_WIDTH;
-                    let popup_height = (term_height as u32 * SHELL_POPUP_HEIGHT_PERCENT as u32 / 100) as u16;
-                    let pane_height = popup_height.saturating_sub(4); // -4 for borders + header/footer
+/// The parts of a move that differ between the board and a headless caller (see
+/// `advance_task`). The defaults send everything right away on the calling thread, which is
+/// what scripts and tests want.
+pub trait MoveHooks {
+    /// A worktree a bulk move already created for the task, used instead of a new one
+    fn prepared_worktree(&mut self, _task: &Task) -> Option<String> {
+        None
+    }
 
-                    let target = format!("{}:{}", self.state.project_name, window_name);
-                    // TODO the resize should be done on target which is
-                    // session_name:window_name, but for some reason that doesn't work
-                    // doing tmux -L agtx resize-window -t session:window -x 30 -y 30 works
-                    let _ = self.state.tmux_ops.resize_window(&window_name, pane_width, pane_height);
-                    popup.last_pane_size = Some((pane_width, pane_height));
-                }
+    /// Set when the user interrupts, so a worktree setup in progress is rolled back
+    fn interrupt_flag(&self) -> Option<&AtomicBool> {
+        None
+    }
 
-                // Capture initial content
-                popup.cached_content = capture_tmux_pane_with_history(window_name, 500, self.state.tmux_ops.as_ref());
+    /// The branch a new worktree starts from (the board reuses the default branch it detected)
+    fn base_branch(&mut self, config: &MergedConfig, project_path: &Path, git_ops: &dyn GitOperations) -> String {
+        resolve_base_branch(config.base_branch.as_deref(), &mut None, Some(project_path), git_ops)
+    }
 
-                self.state.shell_popup = Some(popup);
-            }
-        }
-        Ok(())
+    /// Send what starts a phase in the agent's window (the board does it in the background)
+    fn start_phase(&mut self, tmux_ops: &dyn TmuxOperations, start: PhaseStart) {
+        send_phase_start(tmux_ops, &start);
     }
 
-    /// Load the plugin that a specific task was created with.
-    /// Returns None for tasks created with agtx defaults (task.plugin is None).
-    fn load_task_plugin(&self, task: &Task) -> Option<WorkflowPlugin> {
-        let plugin = match &task.plugin {
-            Some(name) => WorkflowPlugin::load(name, self.state.project_path.as_deref()).ok(),
-            None => None,
-        };
-        if let Some(ref p) = plugin {
-            if !p.supports_agent(&self.state.config.default_agent) {
-                return None;
-            }
+    /// Send the Running command (the board queues it behind `max_concurrent_agents`)
+    fn dispatch_running(&mut self, task: &Task, target: &str, command: String, tmux_ops: &dyn TmuxOperations) {
+        if let Err(e) = tmux_ops.send_keys(target, &command) {
+            self.notice(format!("Sending the running command to '{}' failed: {}", task.title, e));
         }
-        plugin
     }
 
-    fn refresh_tasks(&mut self) -> Result<()> {
-        if let Some(db) = &self.state.db {
-            self.state.board.tasks = db.get_all_tasks()?;
-        }
+    /// Something worth telling the user that doesn't stop the move
+    fn notice(&mut self, _message: String) {}
+
+    /// Called once the side effects are done, before the status changes; an error (a `strict`
+    /// hook, say) leaves the task in its column with whatever the move set up
+    fn transition_allowed(&mut self, _task: &Task, _from: TaskStatus, _to: TaskStatus) -> Result<()> {
         Ok(())
     }
+}
 
-    fn refresh_projects(&mut self) -> Result<()> {
-        // Load projects from global database
-        let db_projects = self.state.global_db.get_all_projects()?;
+/// `MoveHooks` with every default
+pub struct DirectMove;
 
-        self.state.projects = db_projects
-            .into_iter()
-            .map(|p| ProjectInfo {
-                name: p.name,
-                path: p.path,
-            })
-            .collect();
+impl MoveHooks for DirectMove {}
 
-        // Sort alphabetically by name (case-insensitive)
-        self.state
-            .projects
-            .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+/// The board's `MoveHooks`: sends run in background threads, the Running command goes through
+/// the dispatch queue, and transition hooks run. Events and notices are collected for
+/// `App::advance_on_board` to record once the move returns.
+struct BoardMove<'a> {
+    tmux_ops: Arc<dyn TmuxOperations>,
+    scheduler: &'a mut DispatchScheduler,
+    prepared: Option<String>,
+    interrupted: Arc<AtomicBool>,
+    project: String,
+    project_path: &'a Path,
+    config: &'a MergedConfig,
+    detected_base_branch: &'a mut Option<String>,
+    hook_runner: &'a dyn HookRunner,
+    events: Vec<(TaskEventKind, String)>,
+    notices: Vec<String>,
+    failed_hooks: Vec<String>,
+}
 
-        // Find current project in list and select it
-        if let Some(project_path) = &self.state.project_path {
-            let current_path = project_path.to_string_lossy();
-            if let Some(pos) = self.state.projects.iter().position(|p| p.path == current_path) {
-                self.state.selected_project = pos;
-            }
-        }
+impl MoveHooks for BoardMove<'_> {
+    fn prepared_worktree(&mut self, _task: &Task) -> Option<String> {
+        self.prepared.take()
+    }
 
-        Ok(())
+    fn interrupt_flag(&self) -> Option<&AtomicBool> {
+        Some(&self.interrupted)
     }
 
-    fn refresh_sessions(&mut self) -> Result<()> {
-        let now = Instant::now();
-        const CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(2);
+    fn base_branch(&mut self, config: &MergedConfig, project_path: &Path, git_ops: &dyn GitOperations) -> String {
+        resolve_base_branch(config.base_branch.as_deref(), self.detected_base_branch, Some(project_path), git_ops)
+    }
 
-        let project_path = self.state.project_path.clone();
+    fn start_phase(&mut self, _tmux_ops: &dyn TmuxOperations, start: PhaseStart) {
+        let tmux_ops = Arc::clone(&self.tmux_ops);
+        std::thread::spawn(move || send_phase_start(tmux_ops.as_ref(), &start));
+    }
 
-        let tasks_to_check: Vec<_> = self.state.board.tasks.iter()
-            .filter(|t| {
-                matches!(t.status, TaskStatus::Planning | TaskStatus::Running | TaskStatus::Review)
-                || (t.status == TaskStatus::Backlog && t.session_name.is_some())
-            })
-            .filter(|t| t.worktree_path.is_some() || t.session_name.is_some())
-            .map(|t| (t.id.clone(), t.status, t.worktree_path.clone(), t.plugin.clone()))
-            .collect();
+    fn dispatch_running(&mut self, task: &Task, target: &str, command: String, tmux_ops: &dyn TmuxOperations) {
+        // The scheduler sends it now or queues it behind max_concurrent_agents
+        let dispatch = PendingDispatch {
+            task_id: task.id.clone(),
+            project: self.project.clone(),
+            target: target.to_string(),
+            command,
+        };
+        match self.scheduler.submit(dispatch, tmux_ops) {
+            Ok(DispatchOutcome::Sent) => {
+                self.events.push((TaskEventKind::AgentDispatched, "running command sent".to_string()));
+            }
+            Ok(DispatchOutcome::Queued(position)) => {
+                let detail = format!("queued for an agent slot (#{})", position);
+                self.events.push((TaskEventKind::AgentDispatched, detail));
+                self.notices.push(format!(
+                    "{} agents already working; '{}' queued (#{})",
+                    self.scheduler.active_count(),
+                    task.title,
+                    position
+                ));
+            }
+            Err(e) => self.events.push((TaskEventKind::Failed, format!("sending running command: {:#}", e))),
+        }
+    }
 
-        // Cache loaded plugins by name to avoid reloading from disk for each task
-        let mut plugin_cache: HashMap<Option<String>, Option<WorkflowPlugin>> = HashMap::new();
+    fn notice(&mut self, message: String) {
+        self.notices.push(message);
+    }
 
-        for (task_id, status, worktree_path, task_plugin) in tasks_to_check {
-            if let Some((_, timestamp)) = self.state.phase_status_cache.get(&task_id) {
-                if now.duration_since(*timestamp) < CACHE_TTL {
-                    continue;
-                }
-            }
+    fn transition_allowed(&mut self, task: &Task, from: TaskStatus, to: TaskStatus) -> Result<()> {
+        let failed = run_transition_hooks(self.config, self.hook_runner, self.project_path, task, from, to)?;
+        self.failed_hooks.extend(failed);
+        Ok(())
+    }
+}
 
-            let phase_status = if status == TaskStatus::Backlog {
-                // Research artifact: check worktree first, then project root
-                let found = worktree_path.as_ref().map_or(false, |wt| {
-                    Path::new(wt).join(".agtx").join("research")
-                        .join(format!("{}.md", task_id)).exists()
-                }) || project_path.as_ref().map_or(false, |pp| {
-                    pp.join(".agtx").join("research")
-                        .join(format!("{}.md", task_id)).exists()
-                });
-                if found { PhaseStatus::Ready } else { PhaseStatus::Working }
-            } else if let Some(ref wt) = worktree_path {
-                let plugin = plugin_cache.entry(task_plugin.clone()).or_insert_with(|| {
-                    match &task_plugin {
-                        Some(name) => WorkflowPlugin::load(name, self.state.project_path.as_deref()).ok(),
-                        None => None,
-                    }
-                });
-                if phase_artifact_exists(wt, status, plugin) {
-                    PhaseStatus::Ready
-                } else {
-                    PhaseStatus::Working
+/// Run the `[hooks]` for a transition whose side effects are done.
+/// Returns the hooks that failed without `strict`; with it, the first failure is the error.
+/// The `[webhook]` is up to the caller, once the new status is saved (`App::post_webhook`).
+fn run_transition_hooks(
+    config: &MergedConfig,
+    hook_runner: &dyn HookRunner,
+    project_path: &Path,
+    task: &Task,
+    from: TaskStatus,
+    to: TaskStatus,
+) -> Result<Vec<String>> {
+    let failures = hooks::run_transition_hooks(&config.hooks, hook_runner, task, from, to, project_path);
+    hooks::settle(failures, config.hooks.strict)
+}
+
+/// Move `task` to its next column with the side effects that move needs. The board runs every
+/// forward move through here (see `App::advance_task`):
+/// - Backlog → Planning creates the worktree and window, or reuses a research window that's
+///   still open, and starts the agent on the planning command and prompt
+/// - Planning → Running sends the running command (skill command or prompt) to its window
+/// - Running → Review commits leftover changes (with `auto_commit_on_review`) and sends the review command
+/// - Review → Done cleans up per `[cleanup.done]` and the dirty-worktree policy
+///
+/// Then `MoveHooks::transition_allowed` gets its say, the status changes and the destination
+/// column's `on_enter_prompt` is sent. Saving the task and dependency checks are up to the
+/// caller. On error the task keeps its status.
+pub fn advance_task(
+    task: &mut Task,
+    project_path: &Path,
+    git_ops: &dyn GitOperations,
+    tmux_ops: &dyn TmuxOperations,
+    agent_ops: &dyn AgentOperations,
+    config: &MergedConfig,
+    hooks: &mut dyn MoveHooks,
+) -> Result<()> {
+    let from = task.status;
+    let to = move_side_effects(task, project_path, git_ops, tmux_ops, agent_ops, config, hooks)?;
+    hooks.transition_allowed(task, from, to)?;
+    task.status = to;
+    task.updated_at = chrono::Utc::now();
+    if let Some(start) = on_enter_start(config, task, to) {
+        hooks.start_phase(tmux_ops, start);
+    }
+    Ok(())
+}
+
+/// The side effects of `advance_task` without the status change; returns the column the task
+/// is moving to. The board stops here for Running → Review, whose PR popup finishes the move.
+fn move_side_effects(
+    task: &mut Task,
+    project_path: &Path,
+    git_ops: &dyn GitOperations,
+    tmux_ops: &dyn TmuxOperations,
+    agent_ops: &dyn AgentOperations,
+    config: &MergedConfig,
+    hooks: &mut dyn MoveHooks,
+) -> Result<TaskStatus> {
+    let from = task.status;
+    let to = next_status(from).with_context(|| format!("'{}' is already Done", task.title))?;
+    workflow::request_transition(task, to)?;
+    let agent_name = if task.agent.is_empty() { config.default_agent.clone() } else { task.agent.clone() };
+    // Stamp the plugin on the task if not already set (it may be set from research)
+    if from == TaskStatus::Backlog && task.plugin.is_none() {
+        task.plugin = config.workflow_plugin.clone();
+    }
+    let plugin = load_plugin_for(task, &agent_name, Some(project_path));
+
+    match from {
+        TaskStatus::Backlog => {
+            // A column without dispatch gets the agent started idle, with nothing sent to it
+            let dispatch = config.should_dispatch(TaskStatus::Planning);
+            let research_window = task
+                .session_name
+                .clone()
+                .filter(|target| tmux_ops.window_exists(target).unwrap_or(false));
+            let fresh = research_window.is_none();
+            let target = match research_window {
+                Some(target) => target,
+                None => {
+                    let base_branch = hooks.base_branch(config, project_path, git_ops);
+                    let prompt = if dispatch {
+                        resolve_prompt(&plugin, &config.prompts, "planning", task, "", &agent_name)
+                    } else {
+                        String::new()
+                    };
+                    let window = WindowSetup::for_task(config, task);
+                    let session = config.tmux_session_for(&task.project_id);
+                    let setup = WorktreeSetup {
+                        project_path,
+                        base_branch: &base_branch,
+                        session: &session,
+                        prompt: &prompt,
+                        copy_files: config.copy_files.clone(),
+                        init_script: config.init_script.clone(),
+                        plugin: &plugin,
+                        agent_name: &agent_name,
+                        window: &window,
+                    };
+                    let prepared = hooks.prepared_worktree(task);
+                    let not_interrupted = AtomicBool::new(false);
+                    setup_task_worktree(
+                        task,
+                        &setup,
+                        prepared,
+                        tmux_ops,
+                        git_ops,
+                        agent_ops,
+                        hooks.interrupt_flag().unwrap_or(&not_interrupted),
+                    )?
                 }
-            } else {
-                PhaseStatus::Working
             };
-
-            self.state.phase_status_cache.insert(task_id, (phase_status, now));
+            if dispatch {
+                let mut start = PhaseStart::for_phase(&plugin, &config.prompts, "planning", task, &agent_name, target);
+                // A research window is already up and needs no wait
+                start.wait_ready = fresh;
+                if !fresh {
+                    start.trigger = None;
+                }
+                hooks.start_phase(tmux_ops, start);
+            }
         }
-
-        self.state.spinner_frame = self.state.spinner_frame.wrapping_add(1);
-        Ok(())
-    }
-
-    fn switch_to_project(&mut self, project: &ProjectInfo) -> Result<()> {
-        self.switch_to_project_keep_sidebar(project)?;
-        // Unfocus sidebar
-        self.state.sidebar_focused = false;
-        Ok(())
+        TaskStatus::Planning => {
+            let command = phase_command(&plugin, &config.prompts, "running", task, &agent_name);
+            let dispatch = !command.is_empty() && config.should_dispatch(to);
+            if let Some(target) = task.session_name.clone().filter(|_| dispatch) {
+                hooks.dispatch_running(task, &target, command, tmux_ops);
+            }
+        }
+        TaskStatus::Running => {
+            // Opt-in: snapshot whatever the agent left uncommitted
+            if config.auto_commit_on_review {
+                match auto_commit_for_review(task, git_ops) {
+                    Ok(Some(commit)) => hooks.notice(format!("Committed worktree changes ({})", commit.short())),
+                    Ok(None) => {}
+                    Err(e) => hooks.notice(format!("Auto-commit failed: {}", e)),
+                }
+            }
+            let command = phase_command(&plugin, &config.prompts, "review", task, &agent_name);
+            let dispatch = !command.is_empty() && config.should_dispatch(to);
+            if let Some(target) = task.session_name.as_deref().filter(|_| dispatch) {
+                if let Err(e) = tmux_ops.send_keys(target, &command) {
+                    hooks.notice(format!("Sending the review command to '{}' failed: {}", task.title, e));
+                }
+            }
+        }
+        TaskStatus::Review => {
+            cleanup_task_for_done(
+                task,
+                project_path,
+                tmux_ops,
+                git_ops,
+                config.dirty_worktree_policy,
+                config.cleanup.policy(CleanupAction::Done),
+            )?;
+        }
+        TaskStatus::Done => unreachable!("next_status(Done) is None"),
     }
+    Ok(to)
+}
 
-    fn switch_to_project_keep_sidebar(&mut self, project: &ProjectInfo) -> Result<()> {
-        let project_path = PathBuf::from(&project.path);
+/// What starts a phase in an agent's window: the skill command and/or prompt, or the task text
+/// to leave in the agent's input when there's neither (see `send_phase_start`)
+#[derive(Debug, Clone, Default)]
+pub struct PhaseStart {
+    pub target: String,
+    pub skill_cmd: Option<String>,
+    pub prompt: String,
+    /// Text the pane has to show before the prompt goes in (the plugin's `prompt_triggers`)
+    pub trigger: Option<String>,
+    /// Task text typed without Enter when there's no command or prompt (e.g. the void plugin)
+    pub prefill: Option<String>,
+    /// Wait for a freshly started agent to be ready first
+    pub wait_ready: bool,
+}
 
-        // Check if project path exists
-        if !project_path.exists() {
-            // Skip non-existent projects silently
-            return Ok(());
+impl PhaseStart {
+    /// The skill command, prompt and trigger for `phase`, sent once a fresh agent is ready
+    fn for_phase(
+        plugin: &Option<WorkflowPlugin>,
+        prompts: &PromptTemplates,
+        phase: &str,
+        task: &Task,
+        agent_name: &str,
+        target: String,
+    ) -> Self {
+        let content = task_content(task);
+        let worktree = task.worktree_path.as_deref().unwrap_or("");
+        let prefill = content.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect::<Vec<_>>().join(" ");
+        Self {
+            target,
+            skill_cmd: resolve_skill_command(plugin, phase, agent_name, &content),
+            prompt: resolve_prompt(plugin, prompts, phase, task, worktree, agent_name),
+            trigger: resolve_prompt_trigger(plugin, phase),
+            prefill: (!prefill.is_empty()).then_some(prefill),
+            wait_ready: true,
         }
+    }
+}
 
-        // Update current project
-        self.state.project_name = project.name.clone();
-        self.state.project_path = Some(project_path.clone());
-
-        // Open project database (create if needed)
-        match Database::open_project(&project_path) {
-            Ok(db) => self.state.db = Some(db),
-            Err(_) => {
-                // If we can't open the db, skip this project
-                return Ok(());
+/// Send a `PhaseStart`, blocking while the agent starts up or until the trigger shows
+fn send_phase_start(tmux_ops: &dyn TmuxOperations, start: &PhaseStart) {
+    let target = if start.wait_ready {
+        match wait_for_agent_ready(tmux_ops, &start.target) {
+            Some(target) => target,
+            None => return,
+        }
+    } else {
+        start.target.clone()
+    };
+    if let Some(ref cmd) = start.skill_cmd {
+        let _ = tmux_ops.send_keys(&target, cmd);
+        std::thread::sleep(std::time::Duration::from_millis(500));
+    }
+    if !start.prompt.is_empty() {
+        match start.trigger {
+            Some(ref trigger) => {
+                if wait_for_prompt_trigger(tmux_ops, &target, trigger) {
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                    let _ = tmux_ops.send_keys(&target, &start.prompt);
+                }
+            }
+            None => {
+                let _ = tmux_ops.send_keys(&target, &start.prompt);
             }
         }
-
-        // Update last_opened in global db
-        let proj = crate::db::Project::new(&project.name, &project.path);
-        let _ = self.state.global_db.upsert_project(&proj);
-
-        // Ensure tmux session exists
-        ensure_project_tmux_session(&project.name, &project_path, self.state.tmux_ops.as_ref());
-
-        // Reload tasks for new project
-        self.refresh_tasks()?;
-
-        Ok(())
+    } else if start.skill_cmd.is_none() {
+        if let Some(ref prefill) = start.prefill {
+            let _ = tmux_ops.send_keys_literal(&target, prefill);
+        }
     }
 }
 
-impl Drop for App {
-    fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+/// The task's title, with its description below when it has one
+fn task_content(task: &Task) -> String {
+    match &task.description {
+        Some(desc) => format!("{}\n\n{}", task.title, desc),
+        None => task.title.clone(),
     }
 }
 
-/// Ensure tmux session exists for a project
-fn ensure_project_tmux_session(project_name: &str, project_path: &Path, tmux_ops: &dyn TmuxOperations) {
-    if !tmux_ops.has_session(project_name) {
-        let _ = tmux_ops.create_session(project_name, &project_path.to_string_lossy());
+/// What starts a phase in a task's window: the plugin's (or agtx's) skill command for the agent
+/// followed by `skill_argument`, else the phase prompt. Empty when neither applies (e.g. the
+/// void plugin).
+fn phase_command(plugin: &Option<WorkflowPlugin>, prompts: &PromptTemplates, phase: &str, task: &Task, agent_name: &str) -> String {
+    let worktree = task.worktree_path.as_deref().unwrap_or("");
+    match resolve_skill_command(plugin, phase, agent_name, &task_content(task)) {
+        Some(cmd) => match skill_argument(prompts, phase, task, worktree) {
+            Some(argument) => format!("{} {}", cmd, argument),
+            None => cmd,
+        },
+        None => resolve_prompt(plugin, prompts, phase, task, worktree, agent_name),
     }
 }
 
-/// Generate a URL-safe slug from task ID and title
-fn generate_task_slug(task_id: &str, title: &str) -> String {
-    let title_slug: String = title
-        .chars()
-        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
-        .take(30)
+/// The text sent as a skill command's argument: the phase's configured `[prompts]` template and
+/// the project's prompt prefix and suffix, which would otherwise never reach an agent that
+/// advances by skill command. The plugin's and default prompts are left to the skill.
+fn skill_argument(prompts: &PromptTemplates, phase: &str, task: &Task, worktree: &str) -> Option<String> {
+    let template = match phase {
+        "research" => prompts.research.as_deref(),
+        "planning" | "planning_with_research" => prompts.planning.as_deref(),
+        "running" => prompts.running.as_deref(),
+        "review" => prompts.review.as_deref(),
+        _ => None,
+    };
+    let body = template
+        .filter(|t| !t.is_empty())
+        .map(|t| with_scope_note(skills::render_prompt(t, task, worktree), task));
+    let parts: Vec<&str> = [prompts.prefix.as_deref(), body.as_deref(), prompts.suffix.as_deref()]
+        .into_iter()
+        .flatten()
         .collect();
-    let title_slug = title_slug.trim_matches('-').to_string();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
 
-    // Add task ID prefix to ensure uniqueness
-    let id_prefix: String = task_id.chars().take(8).collect();
-    format!("{}-{}", id_prefix, title_slug)
+/// The task's workflow plugin, if it has one that loads and supports `agent_name`
+fn load_plugin_for(task: &Task, agent_name: &str, project_path: Option<&Path>) -> Option<WorkflowPlugin> {
+    let plugin = WorkflowPlugin::load(task.plugin.as_deref()?, project_path).ok()?;
+    plugin.supports_agent(agent_name).then_some(plugin)
 }
 
 /// Cleanup task resources (tmux window, git worktree) and mark as done
-/// Modifies the task in place, ready for database update
+/// Modifies the task in place, ready for database update. `cleanup` (`[cleanup.done]`) says
+/// what goes; by default the window and worktree do and the branch stays for reopening.
+/// Leaves the task untouched if the dirty-worktree policy refuses the removal.
 fn cleanup_task_for_done(
     task: &mut Task,
     project_path: &Path,
     tmux_ops: &dyn TmuxOperations,
     git_ops: &dyn GitOperations,
-) {
+    dirty_policy: DirtyWorktreePolicy,
+    cleanup: CleanupPolicy,
+) -> Result<()> {
+    if cleanup.remove_worktree {
+        guard_dirty_worktree(task, git_ops, dirty_policy)?;
+    }
+
     // Archive artifacts before removing worktree
-    if let Some(worktree) = &task.worktree_path {
+    if let Some(worktree) = task.worktree_path.as_ref().filter(|_| cleanup.remove_worktree) {
         let artifacts_dir = Path::new(worktree).join(".agtx");
         if artifacts_dir.exists() {
             let slug = task.branch_name.as_deref()
@@ -3176,53 +7464,110 @@ fn cleanup_task_for_done(
         }
     }
 
-    if let Some(session_name) = &task.session_name {
-        let _ = tmux_ops.kill_window(session_name);
+    if cleanup.kill_window {
+        if let Some(session_name) = task.session_name.take() {
+            let _ = tmux_ops.kill_window(&session_name);
+        }
     }
-    if let Some(worktree) = &task.worktree_path {
-        let _ = git_ops.remove_worktree(project_path, worktree);
+    if cleanup.remove_worktree {
+        if let Some(worktree) = task.worktree_path.take() {
+            let _ = git_ops.remove_worktree(project_path, &worktree);
+            if let Some(branch) = task.branch_name.as_ref().filter(|_| cleanup.delete_branch) {
+                let _ = git_ops.delete_branch(project_path, branch);
+                task.branch_name = None;
+            }
+        }
     }
-    // Keep the branch so task can be reopened later
-    task.session_name = None;
-    task.worktree_path = None;
     task.status = TaskStatus::Done;
     task.updated_at = chrono::Utc::now();
+    Ok(())
+}
+
+/// Finish Running -> Review from a PR thread: run the hooks, then save the task (in Review unless
+/// a strict hook failed, in which case the hook error is returned and the task stays in Running)
+fn save_review_from_thread(
+    mut task: Task,
+    project_path: &Path,
+    hooks_config: &HooksConfig,
+    hook_runner: &dyn HookRunner,
+    webhook_config: &WebhookConfig,
+) -> Result<()> {
+    let from = task.status;
+    let failures = hooks::run_transition_hooks(
+        hooks_config,
+        hook_runner,
+        &task,
+        from,
+        TaskStatus::Review,
+        project_path,
+    );
+    let result = hooks::settle(failures, hooks_config.strict).map(|_| ());
+    if result.is_ok() {
+        task.status = TaskStatus::Review;
+    }
+    task.updated_at = chrono::Utc::now();
+    let db = crate::db::Database::open_project(project_path)?;
+    db.update_task(&task)?;
+    if result.is_ok() {
+        webhook::spawn_delivery(webhook_config, webhook::Payload::new(&task, from, TaskStatus::Review));
+    }
+    result
+}
+
+/// Where and how `setup_task_worktree` sets up a task: the project, the session its window
+/// goes in, the agent and what it starts with
+struct WorktreeSetup<'a> {
+    project_path: &'a Path,
+    /// Branched from when the task has no `from_ref`
+    base_branch: &'a str,
+    session: &'a str,
+    /// Used only for agents without native skill invocation (fallback). For agents with
+    /// skill support, the agent starts with no prompt and the skill command is sent later
+    /// via send_keys (see the acceptance thread in move_task_right).
+    prompt: &'a str,
+    copy_files: Option<String>,
+    init_script: Option<String>,
+    plugin: &'a Option<WorkflowPlugin>,
+    agent_name: &'a str,
+    window: &'a WindowSetup,
 }
 
 /// Set up a worktree and tmux window for a task.
 /// Creates worktree, initializes it (copy files + init script), creates tmux window with agent.
 /// Updates task fields (session_name, worktree_path, branch_name) in place.
 /// Returns the tmux target string on success.
-///
-/// `prompt` is used only for agents without native skill invocation (fallback).
-/// For agents with skill support, the agent starts with no prompt and the skill command
-/// is sent later via send_keys (see the acceptance thread in move_task_right).
 fn setup_task_worktree(
     task: &mut Task,
-    project_path: &Path,
-    project_name: &str,
-    prompt: &str,
-    copy_files: Option<String>,
-    init_script: Option<String>,
-    plugin: &Option<WorkflowPlugin>,
-    agent_name: &str,
+    setup: &WorktreeSetup,
+    prepared_worktree: Option<String>,
     tmux_ops: &dyn TmuxOperations,
     git_ops: &dyn GitOperations,
     agent_ops: &dyn AgentOperations,
+    interrupted: &AtomicBool,
 ) -> Result<String> {
-    let unique_slug = generate_task_slug(&task.id, &task.title);
-    let window_name = format!("task-{}", unique_slug);
-    let target = format!("{}:{}", project_name, window_name);
-
-    // Create git worktree from main branch
-    let worktree_path_str = match git_ops.create_worktree(project_path, &unique_slug) {
-        Ok(path) => path,
-        Err(e) => {
-            eprintln!("Failed to create worktree: {}", e);
-            project_path.join(".agtx").join("worktrees").join(&unique_slug)
-                .to_string_lossy().to_string()
+    let WorktreeSetup { project_path, base_branch, session, prompt, plugin, agent_name, window, .. } = *setup;
+    // A prepared worktree already has its slug; otherwise pick one no worktree uses yet
+    let unique_slug = match prepared_worktree.as_deref().and_then(|wt| Path::new(wt).file_name()).and_then(|s| s.to_str()) {
+        Some(slug) => slug.to_string(),
+        None => slug::unique_slug(&task.id, &task.title, |s| git_ops.worktree_exists(project_path, s)),
+    };
+    let window_name = window.unique_window_name(task, &unique_slug, |name| {
+        tmux_ops.window_exists(&format!("{}:{}", session, name)).unwrap_or(false)
+    });
+    let target = format!("{}:{}", session, window_name);
+
+    // Create git worktree from the task's ref or the base branch (unless a bulk move already
+    // did). On failure nothing has been created and the caller leaves the task where it was.
+    let worktree_path_str = match prepared_worktree {
+        Some(path) => path,
+        None => {
+            let base = task_base_ref(task, base_branch, project_path, git_ops)?;
+            git_ops
+                .create_worktree(project_path, &unique_slug, &base)
+                .context("Failed to create worktree")?
         }
     };
+    let branch_name = format!("task/{}", unique_slug);
 
     // Initialize worktree: copy files and run init script
     let worktree_path = Path::new(&worktree_path_str);
@@ -3230,8 +7575,8 @@ fn setup_task_worktree(
     let init_warnings = git_ops.initialize_worktree(
         project_path,
         worktree_path,
-        copy_files,
-        init_script,
+        setup.copy_files.clone(),
+        setup.init_script.clone(),
         copy_dirs,
     );
     for warning in &init_warnings {
@@ -3250,14 +7595,82 @@ fn setup_task_worktree(
         let _ = std::fs::copy(&research_path, dst_dir.join("research.md"));
     }
 
+    // Agents that can resume a conversation start under a known id, recorded on the task
+    let agent_session_id = agent_ops.new_session_id();
+
+    // From here on a failure removes the worktree again, so no resources outlive the failed move.
+    // An interrupt that arrived while the worktree was being set up counts as one.
+    if interrupted.load(Ordering::SeqCst) {
+        let _ = git_ops.remove_worktree(project_path, &worktree_path_str);
+        let _ = git_ops.delete_branch(project_path, &branch_name);
+        anyhow::bail!("Interrupted before the agent window was opened; the task was left where it was");
+    }
+    if let Err(e) = start_agent_window(
+        session,
+        &window_name,
+        project_path,
+        &worktree_path_str,
+        task.subdir.as_deref(),
+        prompt,
+        plugin,
+        agent_name,
+        agent_session_id.as_deref(),
+        window,
+        tmux_ops,
+        agent_ops,
+    ) {
+        let _ = git_ops.remove_worktree(project_path, &worktree_path_str);
+        let _ = git_ops.delete_branch(project_path, &branch_name);
+        return Err(e);
+    }
+
+    task.session_name = Some(target.clone());
+    task.worktree_path = Some(worktree_path_str);
+    task.branch_name = Some(branch_name);
+    task.slug = Some(unique_slug);
+    task.last_exit_code = None;
+    task.agent_session_id = agent_session_id;
+
+    Ok(target)
+}
+
+/// The ref a task's new worktree branches from: its `from_ref`, or `base_branch`. Checked
+/// first, so a deleted branch is reported by name instead of as a failed `git worktree add`.
+fn task_base_ref(task: &Task, base_branch: &str, project_path: &Path, git_ops: &dyn GitOperations) -> Result<String> {
+    let base = task.from_ref.as_deref().unwrap_or(base_branch);
+    let exists = git_ops
+        .ref_exists(project_path, base)
+        .with_context(|| format!("Failed to look up base ref '{}'", base))?;
+    if !exists {
+        return Err(crate::op_error::OpError::RefNotFound { name: base.to_string() }.into());
+    }
+    Ok(base.to_string())
+}
+
+/// Run the plugin's init_script in a fresh worktree, then open the task's tmux window
+/// with the agent running in it (in `subdir` of the worktree, when the task has one)
+#[allow(clippy::too_many_arguments)]
+fn start_agent_window(
+    session: &str,
+    window_name: &str,
+    project_path: &Path,
+    worktree_path_str: &str,
+    subdir: Option<&str>,
+    prompt: &str,
+    plugin: &Option<WorkflowPlugin>,
+    agent_name: &str,
+    agent_session_id: Option<&str>,
+    window: &WindowSetup,
+    tmux_ops: &dyn TmuxOperations,
+    agent_ops: &dyn AgentOperations,
+) -> Result<()> {
     // Run plugin init_script (in addition to project init_script)
     // Supports {agent} placeholder for agent-specific initialization
     if let Some(ref p) = plugin {
         if let Some(ref script) = p.init_script {
             let script = script.replace("{agent}", agent_name);
-            let output = std::process::Command::new("sh")
-                .arg("-c").arg(&script)
-                .current_dir(&worktree_path_str)
+            let output = platform::shell_command(&script)
+                .current_dir(worktree_path_str)
                 .output();
             match output {
                 Ok(o) if !o.status.success() => {
@@ -3280,48 +7693,369 @@ fn setup_task_worktree(
     // Build the interactive command. For agents with skill/command support,
     // start with no prompt — the skill command and task content are sent via send_keys.
     let has_skill_support = resolve_skill_command(plugin, "planning", agent_name, "").is_some();
-    let agent_cmd = if has_skill_support {
-        agent_ops.build_interactive_command("")
+    let initial_prompt = if has_skill_support {
+        String::new()
     } else {
-        agent_ops.build_interactive_command(prompt)
+        // The prompt was rendered before the worktree existed
+        prompt.replace("{worktree}", worktree_path_str)
     };
+    let worktree_path = Path::new(worktree_path_str);
+    let agent_cmd = window.agent_command(&initial_prompt, worktree_path, |prompt| match agent_session_id {
+        Some(id) => agent_ops.build_interactive_command_in_session(prompt, id),
+        None => agent_ops.build_interactive_command(prompt),
+    });
+    git::clear_agent_exit_code(worktree_path);
+    let agent_cmd = record_exit_code(&agent_cmd, &git::agent_exit_code_path(worktree_path));
 
-    // Ensure project tmux session exists
-    ensure_project_tmux_session(project_name, project_path, tmux_ops);
+    // Ensure the tmux session exists (reusing it if the user already runs one)
+    ensure_project_tmux_session(session, project_path, tmux_ops)?;
 
     tmux_ops.create_window(
-        project_name,
-        &window_name,
-        &worktree_path_str,
-        Some(agent_cmd),
+        session,
+        window_name,
+        &task_working_dir(worktree_path_str, subdir),
+        Some(window.wrap(&agent_cmd)),
     )?;
+    Ok(())
+}
 
-    task.session_name = Some(target.clone());
-    task.worktree_path = Some(worktree_path_str);
-    task.branch_name = Some(format!("task/{}", unique_slug));
+/// Reopen the window of a task resumed from Review whose tmux window is gone. The agent rejoins
+/// its recorded conversation when it supports that; otherwise it starts fresh with `prompt`
+/// (under a new conversation id, if it can have one). Returns whether the conversation was resumed.
+fn restart_agent_window(
+    task: &mut Task,
+    session: &str,
+    project_path: &Path,
+    prompt: &str,
+    window: &WindowSetup,
+    tmux_ops: &dyn TmuxOperations,
+    agent_ops: &dyn AgentOperations,
+) -> Result<bool> {
+    let worktree = task
+        .worktree_path
+        .clone()
+        .filter(|wt| Path::new(wt).is_dir())
+        .with_context(|| format!("'{}' has no worktree to restart its agent in", task.title))?;
+    let window_name = task
+        .session_name
+        .as_deref()
+        .and_then(|target| target.split_once(':'))
+        .map(|(_, window)| window.to_string())
+        .unwrap_or_else(|| {
+            let slug = Path::new(&worktree)
+                .file_name()
+                .and_then(|s| s.to_str())
+                .map_or_else(|| task.resource_slug(), str::to_string);
+            window.window_name(task, &slug)
+        });
 
-    Ok(target)
+    let resume_command = task
+        .agent_session_id
+        .as_deref()
+        .and_then(|id| agent_ops.build_resume_command(id));
+    let resumed = resume_command.is_some();
+    let command = match resume_command {
+        Some(command) => command,
+        None => {
+            let session_id = agent_ops.new_session_id();
+            let command = window.agent_command(prompt, Path::new(&worktree), |prompt| match &session_id {
+                Some(id) => agent_ops.build_interactive_command_in_session(prompt, id),
+                None => agent_ops.build_interactive_command(prompt),
+            });
+            task.agent_session_id = session_id;
+            command
+        }
+    };
+
+    git::clear_agent_exit_code(Path::new(&worktree));
+    let command = record_exit_code(&command, &git::agent_exit_code_path(Path::new(&worktree)));
+
+    ensure_project_tmux_session(session, project_path, tmux_ops)?;
+    let working_dir = task_working_dir(&worktree, task.subdir.as_deref());
+    tmux_ops.create_window(session, &window_name, &working_dir, Some(window.wrap(&command)))?;
+    task.session_name = Some(format!("{}:{}", session, window_name));
+    task.last_exit_code = None;
+    Ok(resumed)
+}
+
+/// Where a task's agent works: `subdir` inside the worktree, or the worktree root when the
+/// task has none or it doesn't exist there (see `missing_subdir`)
+fn task_working_dir(worktree: &str, subdir: Option<&str>) -> String {
+    subdir
+        .map(|dir| Path::new(worktree).join(platform::native_path(dir)))
+        .filter(|path| path.is_dir())
+        .map_or_else(|| worktree.to_string(), |path| path.to_string_lossy().into_owned())
+}
+
+/// Why a task's directory can't be used: it has one and a worktree, but the worktree has no
+/// such directory (e.g. the base branch doesn't have it yet)
+fn missing_subdir(task: &Task) -> Option<String> {
+    let subdir = task.subdir.as_deref()?;
+    let worktree = task.worktree_path.as_deref()?;
+    (!Path::new(worktree).join(platform::native_path(subdir)).is_dir()).then(|| {
+        format!("'{}' has no directory '{}' in its worktree; its agent works at the worktree root", task.title, subdir)
+    })
+}
+
+/// A prompt with a note limiting the agent to the task's directory, when it has one
+fn with_scope_note(prompt: String, task: &Task) -> String {
+    match task.subdir.as_deref() {
+        Some(subdir) if !prompt.is_empty() => format!(
+            "{}\n\nScope: this task is limited to `{}` (your working directory). Leave files outside it alone unless the task needs them.",
+            prompt, subdir
+        ),
+        _ => prompt,
+    }
+}
+
+/// Run the review `test_command` in a worktree. A failure (or a command that couldn't start)
+/// is written to the error log.
+fn run_review_tests(command: &str, worktree: &Path, runner: &dyn HookRunner) -> bool {
+    match runner.run(command, worktree) {
+        Ok(()) => true,
+        Err(e) => {
+            error_log::log(&format!("Tests failed: {}", command), &e.to_string());
+            false
+        }
+    }
+}
+
+/// Label/value rows shown under the title in the task detail view (only fields that are set)
+fn task_detail_fields(task: &Task) -> Vec<(&'static str, String)> {
+    let mut fields = vec![("Status", task.status.as_str().to_string()), ("Agent", task.agent.clone())];
+    if let Some(ref branch) = task.branch_name {
+        fields.push(("Branch", branch.clone()));
+    }
+    if let Some(ref worktree) = task.worktree_path {
+        fields.push(("Worktree", worktree.clone()));
+    }
+    if let Some(ref subdir) = task.subdir {
+        fields.push(("Directory", subdir.clone()));
+    }
+    if let Some(ref from_ref) = task.from_ref {
+        fields.push(("From", from_ref.clone()));
+    }
+    if let Some(ref url) = task.pr_url {
+        fields.push(("PR", url.clone()));
+    }
+    if !task.tags.is_empty() {
+        fields.push(("Tags", task.tags.iter().map(|t| format!("#{}", t)).collect::<Vec<_>>().join(" ")));
+    }
+    if let Some(passed) = task.test_status {
+        fields.push(("Tests", if passed { "passed" } else { "failed" }.to_string()));
+    }
+    if let Some(ref usage) = task.usage {
+        fields.push(("Usage", format_usage(usage)));
+    }
+    fields.push(("Created", format_note_time(task.created_at)));
+    fields
+}
+
+/// One history line for the detail view, e.g. `worktree created  /repo/.agtx/worktrees/x`
+fn format_event(event: &TaskEvent) -> String {
+    let kind = event.kind.name().replace('_', " ");
+    if event.detail.is_empty() {
+        kind
+    } else {
+        format!("{}  {}", kind, event.detail)
+    }
+}
+
+/// Local time for the detail view, e.g. `2024-05-01 14:03`
+fn format_note_time(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string()
+}
+
+/// Card badge for an agent that exited with an error, e.g. `⚠ exit 1`
+fn agent_exit_badge(task: &Task) -> Option<String> {
+    if task.status == TaskStatus::Done {
+        return None;
+    }
+    task.last_exit_code.filter(|code| *code != 0).map(|code| format!("⚠ exit {}", code))
+}
+
+/// Card badge for a Review task's test run
+fn review_test_badge(task: &Task, running: bool) -> Option<&'static str> {
+    if task.status != TaskStatus::Review {
+        return None;
+    }
+    if running {
+        return Some("🧪 testing");
+    }
+    task.test_status.map(|passed| if passed { "✅ tests" } else { "❌ tests" })
 }
 
-/// Delete task resources: kill tmux window, remove worktree, delete branch
+/// What `delete_task_resources` tried to remove and how it went; `None` when the task had no
+/// such resource or `[cleanup.delete]` keeps it
+#[derive(Debug, Default)]
+struct ReleasedResources {
+    window: Option<crate::op_error::OpResult<()>>,
+    worktree: Option<crate::op_error::OpResult<()>>,
+}
+
+/// Delete task resources: kill tmux window, remove worktree, delete branch, each unless
+/// `cleanup` (`[cleanup.delete]`) keeps it. The branch goes only once its worktree is removed.
+/// Nothing is removed if the dirty-worktree policy refuses the removal.
 fn delete_task_resources(
     task: &Task,
     project_path: &Path,
     tmux_ops: &dyn TmuxOperations,
     git_ops: &dyn GitOperations,
-) {
+    dirty_policy: DirtyWorktreePolicy,
+    cleanup: CleanupPolicy,
+) -> Result<ReleasedResources> {
+    if cleanup.remove_worktree {
+        guard_dirty_worktree(task, git_ops, dirty_policy)?;
+    }
+    let mut released = ReleasedResources::default();
+
     // Kill tmux window if exists
-    if let Some(ref session_name) = task.session_name {
-        let _ = tmux_ops.kill_window(session_name);
+    if let Some(session_name) = task.session_name.as_ref().filter(|_| cleanup.kill_window) {
+        released.window = Some(tmux_ops.kill_window(session_name));
     }
 
     // Remove worktree and delete branch if exists
-    if task.worktree_path.is_some() {
-        if let Some(ref branch_name) = task.branch_name {
-            let slug = branch_name.strip_prefix("task/").unwrap_or(branch_name);
-            let _ = git_ops.remove_worktree(project_path, slug);
-            let _ = git_ops.delete_branch(project_path, branch_name);
+    if let Some(worktree) = task.worktree_path.as_deref().filter(|_| cleanup.remove_worktree) {
+        let removed = git_ops.remove_worktree(project_path, worktree);
+        if removed.is_ok() && cleanup.delete_branch {
+            if let Some(branch_name) = &task.branch_name {
+                let _ = git_ops.delete_branch(project_path, branch_name);
+            }
+        }
+        released.worktree = Some(removed);
+    }
+    Ok(released)
+}
+
+/// Pause between interrupting an agent and typing its new prompt
+const ABORT_SETTLE_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Interrupt the agent in a task's window; returns the window. Nothing else is touched.
+fn interrupt_task_agent(task: &Task, tmux_ops: &dyn TmuxOperations) -> Result<String> {
+    let target = task
+        .session_name
+        .clone()
+        .with_context(|| format!("'{}' has no agent window", task.title))?;
+    tmux_ops
+        .send_interrupt(&target)
+        .with_context(|| format!("Interrupting the agent of '{}'", task.title))?;
+    Ok(target)
+}
+
+/// How long quitting waits for a pull request still being created in the background
+const SHUTDOWN_PR_WAIT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// How often `agtx serve` checks for SIGINT while no client is connected
+#[cfg(unix)]
+const SERVE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Header badge while automation is paused
+const AUTOMATION_PAUSED_LABEL: &str = "⏸ AUTOMATION PAUSED ";
+
+/// Commits listed in the task detail view
+const DETAIL_RECENT_COMMITS: usize = 5;
+
+/// Header label while the project's `fast_mode` is on
+const FAST_MODE_LABEL: &str = "[fast] ";
+
+/// Flip hands-off mode and describe the new state for the footer
+fn toggle_automation(paused: &AtomicBool) -> String {
+    if paused.fetch_xor(true, Ordering::SeqCst) {
+        "Automation resumed".to_string()
+    } else {
+        "Automation paused: no status polling, reconciliation or queued dispatches until you press it again".to_string()
+    }
+}
+
+/// Set `interrupted` when the process gets SIGINT. The flag is only read between operations,
+/// so a transition that's under way finishes or rolls back before agtx exits.
+fn watch_for_interrupt(interrupted: Arc<AtomicBool>) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            interrupted.store(true, Ordering::SeqCst);
         }
+    });
+}
+
+/// Ctrl-C on the board, which raw mode delivers as a key instead of a signal
+fn is_interrupt_key(key: &crossterm::event::KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL)
+}
+
+/// How often agent windows are read for token usage
+const AGENT_USAGE_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// The usage a task's agent has printed, when it differs from what's recorded. The capture is
+/// added to `buffer` first, so a summary that scrolled off screen is still found while it's
+/// among the buffered lines (with a zero-line buffer only the pane as it is now is read).
+/// Nothing when the window can't be captured or no usage summary is found.
+fn capture_agent_usage(
+    task: &Task,
+    buffer: &mut CaptureBuffer,
+    tmux_ops: &dyn TmuxOperations,
+    agent_ops: &dyn AgentOperations,
+) -> Option<AgentUsage> {
+    let target = task.session_name.as_deref()?;
+    let output = tmux_ops.capture_pane(target).ok()?;
+    let usage = if buffer.max_lines() == 0 {
+        agent_ops.parse_usage(&output)?
+    } else {
+        buffer.push_capture(&output);
+        agent_ops.parse_usage(&buffer.text())?
+    };
+    (task.usage != Some(usage)).then_some(usage)
+}
+
+/// How often the agents' exit code files are read
+const AGENT_EXIT_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long a task's window/worktree presence is trusted before tmux and git are asked again
+const RESOURCE_PRESENCE_TTL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How long a worktree's dirty/clean state is trusted before git is asked again
+const WORKTREE_STATUS_TTL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Whether a task's worktree has uncommitted changes. None when there's no worktree to
+/// report on (Done tasks, not created yet, or git status failed).
+fn worktree_dirty_state(task: &Task, git_ops: &dyn GitOperations) -> Option<bool> {
+    if task.status == TaskStatus::Done {
+        return None;
+    }
+    let worktree = task.worktree_path.as_ref()?;
+    if !Path::new(worktree).exists() {
+        return None;
+    }
+    git_ops.is_worktree_dirty(Path::new(worktree)).ok()
+}
+
+/// Longest branch diff shown in the preview; the rest is cut off with a note
+const MAX_BRANCH_DIFF_LINES: usize = 5000;
+
+/// Keep the first `max_lines` lines of a diff, noting how much was left out
+fn truncate_diff(diff: &str, max_lines: usize) -> String {
+    let total = diff.lines().count();
+    if total <= max_lines {
+        return diff.to_string();
+    }
+    let mut kept: Vec<&str> = diff.lines().take(max_lines).collect();
+    let note = format!(
+        "... diff truncated: showing {} of {} lines (attach to the task to see the rest)",
+        max_lines, total
+    );
+    kept.push("");
+    kept.push(&note);
+    kept.join("\n")
+}
+
+/// Preview text for everything a task's branch changed since `base`
+fn branch_diff_content(task: &Task, base: &str, git_ops: &dyn GitOperations) -> String {
+    let Some(worktree) = &task.worktree_path else {
+        return "(task has no worktree yet)".to_string();
+    };
+    match git_ops.diff_from_base(Path::new(worktree), base) {
+        Ok(diff) if diff.trim().is_empty() => format!("(no committed changes since {})", base),
+        Ok(diff) => truncate_diff(&diff, MAX_BRANCH_DIFF_LINES),
+        Err(e) => format!("Could not diff against {}: {}", base, e),
     }
 }
 
@@ -3438,6 +8172,7 @@ pub(crate) fn generate_pr_description(
     task_title: &str,
     worktree_path: Option<&str>,
     _branch_name: Option<&str>,
+    base_branch: &str,
     git_ops: &dyn GitOperations,
     agent_ops: &dyn AgentOperations,
 ) -> (String, String) {
@@ -3448,8 +8183,7 @@ pub(crate) fn generate_pr_description(
     // Try to get git diff for context
     if let Some(worktree) = worktree_path {
         let worktree_path = Path::new(worktree);
-        // Get diff from main
-        let diff_stat = git_ops.diff_stat_from_main(worktree_path);
+        let diff_stat = git_ops.diff_stat_from_base(worktree_path, base_branch);
 
         if !diff_stat.is_empty() {
             default_body.push_str("## Changes\n```\n");
@@ -3473,10 +8207,12 @@ pub(crate) fn generate_pr_description(
     (default_title, default_body)
 }
 
-/// Create a PR with provided title and body, return (pr_number, pr_url)
+/// Create a PR into `base_branch` with provided title and body, return (pr_number, pr_url)
+#[allow(clippy::too_many_arguments)]
 fn create_pr_with_content(
     task: &Task,
     project_path: &Path,
+    base_branch: &str,
     pr_title: &str,
     pr_body: &str,
     git_ops: &dyn GitOperations,
@@ -3509,6 +8245,7 @@ fn create_pr_with_content(
         pr_title,
         pr_body,
         task.branch_name.as_deref().unwrap_or(""),
+        base_branch,
     )
 }
 
@@ -3825,71 +8562,37 @@ fn fuzzy_find_files(project_path: &Path, pattern: &str, max_results: usize, git_
     matches.into_iter().take(max_results).map(|(path, _)| path).collect()
 }
 
-/// Calculate fuzzy match score (higher is better, 0 means no match)
-fn fuzzy_score(haystack: &str, needle: &str) -> i32 {
-    if needle.is_empty() {
-        return 1;
-    }
-
-    let mut score = 0;
-    let mut needle_chars = needle.chars().peekable();
-    let mut prev_matched = false;
-    let mut prev_was_separator = true;
-
-    for c in haystack.chars() {
-        let is_separator = c == '/' || c == '_' || c == '-' || c == '.';
-
-        if let Some(&nc) = needle_chars.peek() {
-            if c == nc {
-                needle_chars.next();
-                score += 1;
-
-                // Bonus for matching after separator (start of word)
-                if prev_was_separator {
-                    score += 5;
-                }
-                // Bonus for consecutive matches
-                if prev_matched {
-                    score += 3;
-                }
-                prev_matched = true;
-            } else {
-                prev_matched = false;
-            }
-        }
-
-        prev_was_separator = is_separator;
-    }
-
-    // Only return score if all needle chars were found
-    if needle_chars.peek().is_none() {
-        score
-    } else {
-        0
-    }
-}
 
-/// Resolve the task prompt for a given phase transition, using plugin override or default.
+/// Resolve the task prompt for a given phase transition: the configured `[prompts]` template,
+/// then the plugin's, then the default, rendered with `skills::render_prompt`.
 /// This returns the task content message only — skill invocation is handled separately via send_keys.
 /// For agents without native skill invocation, a file-path fallback is appended.
-fn resolve_prompt(plugin: &Option<WorkflowPlugin>, phase: &str, task_content: &str, task_id: &str, agent_name: &str) -> String {
+fn resolve_prompt(
+    plugin: &Option<WorkflowPlugin>,
+    overrides: &PromptTemplates,
+    phase: &str,
+    task: &Task,
+    worktree: &str,
+    agent_name: &str,
+) -> String {
+    let plugin_prompts = plugin.as_ref().map(|p| &p.prompts);
     let template = match phase {
-        "research" => plugin.as_ref()
-            .and_then(|p| p.prompts.research.as_deref())
+        "research" => overrides.research.as_deref()
+            .or_else(|| plugin_prompts.and_then(|p| p.research.as_deref()))
             .unwrap_or(skills::DEFAULT_PROMPT_RESEARCH),
-        "planning" => plugin.as_ref()
-            .and_then(|p| p.prompts.planning.as_deref())
+        "planning" => overrides.planning.as_deref()
+            .or_else(|| plugin_prompts.and_then(|p| p.planning.as_deref()))
             .unwrap_or(skills::DEFAULT_PROMPT_PLANNING),
-        "planning_with_research" => plugin.as_ref()
-            .and_then(|p| p.prompts.planning.as_deref())
+        "planning_with_research" => overrides.planning.as_deref()
+            .or_else(|| plugin_prompts.and_then(|p| p.planning.as_deref()))
             .unwrap_or(skills::DEFAULT_PROMPT_PLANNING_WITH_RESEARCH),
-        "running" => plugin.as_ref()
-            .and_then(|p| p.prompts.running.as_deref())
+        "running" => overrides.running.as_deref()
+            .or_else(|| plugin_prompts.and_then(|p| p.running.as_deref()))
             .unwrap_or(skills::DEFAULT_PROMPT_RUNNING),
-        "review" => plugin.as_ref()
-            .and_then(|p| p.prompts.review.as_deref())
+        "review" => overrides.review.as_deref()
+            .or_else(|| plugin_prompts.and_then(|p| p.review.as_deref()))
             .unwrap_or(skills::DEFAULT_PROMPT_REVIEW),
-        _ => return task_content.to_string(),
+        _ => return skills::render_prompt("{task}", task, worktree),
     };
 
     // Explicit empty prompt means "send nothing" (e.g., void plugin)
@@ -3901,14 +8604,12 @@ fn resolve_prompt(plugin: &Option<WorkflowPlugin>, phase: &str, task_content: &s
     let skill_dir_name = skills::phase_to_skill_dir(phase);
     let skill_ref = skills::skill_reference(agent_name, skill_dir_name);
 
-    let result = template
-        .replace("{task}", task_content)
-        .replace("{task_id}", task_id);
+    let result = with_scope_note(skills::render_prompt(template, task, worktree), task);
 
     if skill_ref.is_empty() {
-        result
+        overrides.wrap(result)
     } else {
-        format!("{}\n\n{}", result, skill_ref)
+        overrides.wrap(format!("{}\n\n{}", result, skill_ref))
     }
 }
 
@@ -3960,7 +8661,7 @@ fn resolve_prompt_trigger(plugin: &Option<WorkflowPlugin>, phase: &str) -> Optio
 
 /// Wait for a specific text to appear in a tmux pane, then return.
 /// Returns true if the trigger was found, false if timed out.
-fn wait_for_prompt_trigger(tmux_ops: &Arc<dyn TmuxOperations>, target: &str, trigger: &str) -> bool {
+fn wait_for_prompt_trigger(tmux_ops: &dyn TmuxOperations, target: &str, trigger: &str) -> bool {
     let mut last_content = String::new();
     let mut stable_ticks = 0u32; // how many consecutive polls the content hasn't changed
 
@@ -4049,7 +8750,7 @@ fn glob_path_exists(pattern: &str) -> bool {
 /// Wait for an agent in a tmux pane to be ready for input.
 /// Handles both the bypass warning prompt (sends acceptance) and agents that skip it.
 /// Returns the target string if the agent became ready, None on timeout.
-fn wait_for_agent_ready(tmux_ops: &Arc<dyn TmuxOperations>, target: &str) -> Option<String> {
+fn wait_for_agent_ready(tmux_ops: &dyn TmuxOperations, target: &str) -> Option<String> {
     for _ in 0..50 {
         std::thread::sleep(std::time::Duration::from_millis(100));
 
@@ -4072,6 +8773,24 @@ fn wait_for_agent_ready(tmux_ops: &Arc<dyn TmuxOperations>, target: &str) -> Opt
     None
 }
 
+/// The window and rendered prompt to send when `task` enters `to`: only when the column has an
+/// `on_enter_prompt` and the task has a window to send it to
+fn on_enter_dispatch(config: &MergedConfig, task: &Task, to: TaskStatus) -> Option<(String, String)> {
+    if !config.should_dispatch(to) {
+        return None;
+    }
+    let template = config.on_enter_prompt(to)?;
+    let target = task.session_name.clone()?;
+    let worktree = task.worktree_path.as_deref().unwrap_or("");
+    Some((target, skills::render_prompt(template, task, worktree)))
+}
+
+/// `on_enter_dispatch` as a `PhaseStart`, sent once the agent is ready
+fn on_enter_start(config: &MergedConfig, task: &Task, to: TaskStatus) -> Option<PhaseStart> {
+    let (target, prompt) = on_enter_dispatch(config, task, to)?;
+    Some(PhaseStart { target, prompt, wait_ready: true, ..Default::default() })
+}
+
 /// Load workflow plugin if configured
 fn load_plugin_if_configured(config: &MergedConfig, project_path: Option<&Path>) -> Option<WorkflowPlugin> {
     config.workflow_plugin.as_ref()
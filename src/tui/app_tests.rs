@@ -8,6 +8,14 @@ use crate::agent::MockAgentOperations;
 use crate::git::{MockGitOperations, MockGitProviderOperations};
 #[cfg(feature = "test-mocks")]
 use crate::tmux::MockTmuxOperations;
+#[cfg(feature = "test-mocks")]
+use crate::op_error::OpError;
+
+/// A git/tmux command that exited with an error
+#[cfg(feature = "test-mocks")]
+fn command_failed(command: &str, stderr: &str) -> OpError {
+    OpError::CommandFailed { command: command.to_string(), code: Some(128), stderr: stderr.to_string() }
+}
 
 /// Test that generate_pr_description correctly combines git diff and agent-generated text
 #[test]
@@ -18,10 +26,10 @@ fn test_generate_pr_description_with_diff_and_agent() {
 
     // Setup: git returns a diff stat
     mock_git
-        .expect_diff_stat_from_main()
-        .withf(|path: &Path| path == Path::new("/tmp/worktree"))
+        .expect_diff_stat_from_base()
+        .withf(|path: &Path, base: &str| path == Path::new("/tmp/worktree") && base == "main")
         .times(1)
-        .returning(|_| " src/main.rs | 10 +++++++---\n 1 file changed".to_string());
+        .returning(|_, _| " src/main.rs | 10 +++++++---\n 1 file changed".to_string());
 
     // Setup: agent generates a description
     mock_agent
@@ -39,6 +47,7 @@ fn test_generate_pr_description_with_diff_and_agent() {
         "Add login feature",
         Some("/tmp/worktree"),
         None,
+        "main",
         &mock_git,
         &mock_agent,
     );
@@ -63,6 +72,7 @@ fn test_generate_pr_description_without_worktree() {
         "Simple task",
         None, // No worktree
         None,
+        "main",
         &mock_git,
         &mock_agent,
     );
@@ -80,8 +90,8 @@ fn test_generate_pr_description_with_empty_diff() {
 
     // Git returns empty diff (no changes from main)
     mock_git
-        .expect_diff_stat_from_main()
-        .returning(|_| String::new());
+        .expect_diff_stat_from_base()
+        .returning(|_, _| String::new());
 
     // Agent still generates description
     mock_agent
@@ -92,6 +102,7 @@ fn test_generate_pr_description_with_empty_diff() {
         "Update docs",
         Some("/tmp/worktree"),
         None,
+        "main",
         &mock_git,
         &mock_agent,
     );
@@ -109,8 +120,8 @@ fn test_generate_pr_description_agent_failure() {
     let mut mock_agent = MockAgentOperations::new();
 
     mock_git
-        .expect_diff_stat_from_main()
-        .returning(|_| " file.rs | 5 +++++\n".to_string());
+        .expect_diff_stat_from_base()
+        .returning(|_, _| " file.rs | 5 +++++\n".to_string());
 
     // Agent fails to generate
     mock_agent
@@ -121,6 +132,7 @@ fn test_generate_pr_description_agent_failure() {
         "Fix bug",
         Some("/tmp/worktree"),
         None,
+        "main",
         &mock_git,
         &mock_agent,
     );
@@ -135,22 +147,14 @@ fn test_generate_pr_description_agent_failure() {
 // Tests for ensure_project_tmux_session
 // =============================================================================
 
-/// Test that ensure_project_tmux_session creates session when it doesn't exist
+/// Test that ensure_project_tmux_session asks tmux for the session in the project root
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_ensure_project_tmux_session_creates_when_missing() {
+fn test_ensure_project_tmux_session_ensures_session() {
     let mut mock_tmux = MockTmuxOperations::new();
 
-    // Session doesn't exist
-    mock_tmux
-        .expect_has_session()
-        .with(mockall::predicate::eq("my-project"))
-        .times(1)
-        .returning(|_| false);
-
-    // Should create the session
     mock_tmux
-        .expect_create_session()
+        .expect_ensure_session()
         .with(
             mockall::predicate::eq("my-project"),
             mockall::predicate::eq("/home/user/project"),
@@ -158,1466 +162,3801 @@ fn test_ensure_project_tmux_session_creates_when_missing() {
         .times(1)
         .returning(|_, _| Ok(()));
 
-    ensure_project_tmux_session("my-project", Path::new("/home/user/project"), &mock_tmux);
+    ensure_project_tmux_session("my-project", Path::new("/home/user/project"), &mock_tmux).unwrap();
 }
 
-/// Test that ensure_project_tmux_session skips creation when session exists
+/// Test that a failure to create the session is reported
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_ensure_project_tmux_session_skips_when_exists() {
+fn test_ensure_project_tmux_session_reports_failure() {
     let mut mock_tmux = MockTmuxOperations::new();
 
-    // Session already exists
     mock_tmux
-        .expect_has_session()
-        .with(mockall::predicate::eq("existing-project"))
-        .times(1)
-        .returning(|_| true);
+        .expect_ensure_session()
+        .returning(|_, _| Err(command_failed("tmux new-session", "no server")));
+
+    let err = ensure_project_tmux_session("work", Path::new("/tmp/project"), &mock_tmux).unwrap_err();
+    assert!(format!("{:#}", err).contains("tmux session 'work'"));
+}
+
+/// Test setup_task_worktree creates the window in the configured session
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_uses_given_session() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, _, _| {
+        mock_tmux
+            .expect_ensure_session()
+            .with(mockall::predicate::eq("work"), mockall::predicate::always())
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_tmux
+            .expect_create_window()
+            .withf(|session, _, _, _| session == "work")
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+    });
 
-    // create_session should NOT be called
-    // (mockall will fail if unexpected calls are made)
+    let mut task = Task::new("Shared session", "claude", "project-1");
+    let window = WindowSetup::default();
+    let setup = WorktreeSetup { session: "work", ..worktree_setup(&window) };
+    let target =
+        setup_task_worktree(&mut task, &setup, None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false))
+            .unwrap();
 
-    ensure_project_tmux_session("existing-project", Path::new("/tmp/project"), &mock_tmux);
+    assert!(target.starts_with("work:task-"));
+    assert_eq!(task.session_name.as_deref(), Some(target.as_str()));
 }
 
 // =============================================================================
-// Tests for create_pr_with_content
+// Tests for attach_target
 // =============================================================================
 
-/// Test successful PR creation with changes
+/// Test that a task with a live window attaches to its session:window target
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_create_pr_with_content_success() {
-    let mut mock_git = MockGitOperations::new();
-    let mut mock_git_provider = MockGitProviderOperations::new();
-    let mut mock_agent = MockAgentOperations::new();
+fn test_attach_target_live_window() {
+    use crate::db::Task;
 
-    let task = Task {
-        id: "test-123".to_string(),
-        title: "Test task".to_string(),
-        description: None,
-        status: TaskStatus::Running,
-        agent: "claude".to_string(),
-        project_id: "proj-1".to_string(),
-        session_name: Some("test-session".to_string()),
-        worktree_path: Some("/tmp/worktree".to_string()),
-        branch_name: Some("feature/test".to_string()),
-        pr_number: None,
-        pr_url: None,
-        plugin: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-    };
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux
+        .expect_window_exists()
+        .with(mockall::predicate::eq("proj:task-fix"))
+        .returning(|_| Ok(true));
 
-    // Expect: add all files
-    mock_git
-        .expect_add_all()
-        .withf(|path: &Path| path == Path::new("/tmp/worktree"))
-        .times(1)
-        .returning(|_| Ok(()));
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.session_name = Some("proj:task-fix".to_string());
 
-    // Expect: check for changes
-    mock_git
-        .expect_has_changes()
-        .withf(|path: &Path| path == Path::new("/tmp/worktree"))
-        .times(1)
-        .returning(|_| true);
+    assert_eq!(attach_target(Some(&task), &mock_tmux), Ok("proj:task-fix".to_string()));
+}
 
-    // Expect: commit with co-author
-    mock_git
-        .expect_commit()
-        .withf(|path: &Path, msg: &str| {
-            path == Path::new("/tmp/worktree") && msg.contains("Test PR") && msg.contains("Co-Authored-By")
-        })
-        .times(1)
-        .returning(|_, _| Ok(()));
+/// Test that a window that went away is reported instead of attaching
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_attach_target_missing_window() {
+    use crate::db::Task;
 
-    // Expect: push with upstream
-    mock_git
-        .expect_push()
-        .withf(|path: &Path, branch: &str, set_upstream: &bool| {
-            path == Path::new("/tmp/worktree") && branch == "feature/test" && *set_upstream
-        })
-        .times(1)
-        .returning(|_, _, _| Ok(()));
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_window_exists().returning(|_| Ok(false));
 
-    // Agent co-author string
-    mock_agent
-        .expect_co_author_string()
-        .return_const("Claude <claude@anthropic.com>".to_string());
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.session_name = Some("proj:task-fix".to_string());
 
-    // Expect: create PR
-    mock_git_provider
-        .expect_create_pr()
-        .withf(|path: &Path, title: &str, body: &str, branch: &str| {
-            path == Path::new("/project") && title == "Test PR" && body == "Test body" && branch == "feature/test"
-        })
-        .times(1)
-        .returning(|_, _, _, _| Ok((42, "https://github.com/org/repo/pull/42".to_string())));
+    let reason = attach_target(Some(&task), &mock_tmux).unwrap_err();
+    assert!(reason.contains("no longer exists"));
+    assert!(reason.contains("Fix bug"));
+}
 
-    let result = create_pr_with_content(
-        &task,
-        Path::new("/project"),
-        "Test PR",
-        "Test body",
-        &mock_git,
-        &mock_git_provider,
-        &mock_agent,
-    );
+/// Test that no-tmux mode explains why there is nothing to attach to
+#[test]
+fn test_attach_target_no_tmux_mode() {
+    use crate::db::Task;
+    use crate::tmux::NoTmuxOps;
 
-    assert!(result.is_ok());
-    let (pr_number, pr_url) = result.unwrap();
-    assert_eq!(pr_number, 42);
-    assert_eq!(pr_url, "https://github.com/org/repo/pull/42");
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.session_name = Some("proj:task-fix".to_string());
+
+    let reason = attach_target(Some(&task), &NoTmuxOps).unwrap_err();
+    assert!(reason.contains("no-tmux mode"));
 }
 
-/// Test PR creation with no changes to commit
+/// Test that a Backlog task without a window is reported without asking tmux
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_create_pr_with_content_no_changes() {
-    let mut mock_git = MockGitOperations::new();
-    let mut mock_git_provider = MockGitProviderOperations::new();
-    let mock_agent = MockAgentOperations::new();
+fn test_attach_target_no_window_yet() {
+    use crate::db::Task;
 
-    let task = Task {
-        id: "test-123".to_string(),
-        title: "Test task".to_string(),
-        description: None,
-        status: TaskStatus::Running,
-        agent: "claude".to_string(),
-        project_id: "proj-1".to_string(),
-        session_name: Some("test-session".to_string()),
-        worktree_path: Some("/tmp/worktree".to_string()),
-        branch_name: Some("feature/test".to_string()),
-        pr_number: None,
-        pr_url: None,
-        plugin: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-    };
+    let mock_tmux = MockTmuxOperations::new();
+    let task = Task::new("Fix bug", "claude", "project-1");
 
-    mock_git
-        .expect_add_all()
-        .returning(|_| Ok(()));
+    let reason = attach_target(Some(&task), &mock_tmux).unwrap_err();
+    assert!(reason.contains("no tmux window yet"));
+    assert!(attach_target(None, &mock_tmux).is_err());
+}
 
-    // No changes to commit
-    mock_git
-        .expect_has_changes()
-        .returning(|_| false);
+// =============================================================================
+// Tests for apply_task_edit
+// =============================================================================
 
-    // commit should NOT be called (no expectation set)
+#[test]
+fn test_apply_task_edit_renames_and_switches_agent_in_backlog() {
+    let mut task = crate::db::Task::new("Fix tpyo", "claude", "project-1");
 
-    mock_git
-        .expect_push()
-        .returning(|_, _, _| Ok(()));
+    apply_task_edit(&mut task, "  Fix typo ", "codex").unwrap();
 
-    mock_git_provider
-        .expect_create_pr()
-        .returning(|_, _, _, _| Ok((1, "https://github.com/pr/1".to_string())));
+    assert_eq!(task.title, "Fix typo");
+    assert_eq!(task.agent, "codex");
+    assert_eq!(task.status, TaskStatus::Backlog);
+}
 
-    let result = create_pr_with_content(
-        &task,
-        Path::new("/project"),
-        "PR Title",
-        "PR Body",
-        &mock_git,
-        &mock_git_provider,
-        &mock_agent,
-    );
+#[test]
+fn test_apply_task_edit_agent_locked_once_running() {
+    let mut task = crate::db::Task::new("Fix bug", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.session_name = Some("proj:task-fix".to_string());
+
+    let reason = apply_task_edit(&mut task, "Fix bug", "codex").unwrap_err();
+    assert!(reason.contains("Backlog or Planning"));
+    assert_eq!(task.agent, "claude");
+
+    // The title can still be fixed; resources stay as they were
+    apply_task_edit(&mut task, "Fix the bug", "claude").unwrap();
+    assert_eq!(task.title, "Fix the bug");
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.session_name.as_deref(), Some("proj:task-fix"));
+}
 
-    assert!(result.is_ok());
+#[test]
+fn test_apply_task_edit_rejects_empty_title() {
+    let mut task = crate::db::Task::new("Keep me", "claude", "project-1");
+
+    assert!(apply_task_edit(&mut task, "   ", "claude").is_err());
+    assert_eq!(task.title, "Keep me");
 }
 
-/// Test PR creation failure on push
 #[test]
-#[cfg(feature = "test-mocks")]
-fn test_create_pr_with_content_push_failure() {
-    let mut mock_git = MockGitOperations::new();
-    let mock_git_provider = MockGitProviderOperations::new();
-    let mut mock_agent = MockAgentOperations::new();
+fn test_agent_editable_only_before_running() {
+    assert!(agent_editable(TaskStatus::Backlog));
+    assert!(agent_editable(TaskStatus::Planning));
+    assert!(!agent_editable(TaskStatus::Running));
+    assert!(!agent_editable(TaskStatus::Review));
+    assert!(!agent_editable(TaskStatus::Done));
+}
 
-    let task = Task {
-        id: "test-123".to_string(),
-        title: "Test task".to_string(),
-        description: None,
-        status: TaskStatus::Running,
-        agent: "claude".to_string(),
-        project_id: "proj-1".to_string(),
-        session_name: None,
-        worktree_path: Some("/tmp/worktree".to_string()),
-        branch_name: Some("feature/test".to_string()),
-        pr_number: None,
-        pr_url: None,
-        plugin: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-    };
+// =============================================================================
+// Tests for picking a new task's agent
+// =============================================================================
 
-    mock_git.expect_add_all().returning(|_| Ok(()));
-    mock_git.expect_has_changes().returning(|_| true);
-    mock_git.expect_commit().returning(|_, _| Ok(()));
-    mock_agent
-        .expect_co_author_string()
-        .return_const("Claude <claude@anthropic.com>".to_string());
+fn installed(names: &[&str]) -> Vec<agent::Agent> {
+    names.iter().map(|n| agent::Agent::new(n, n, "", "")).collect()
+}
 
-    // Push fails
-    mock_git
-        .expect_push()
-        .returning(|_, _, _| Err(anyhow::anyhow!("Permission denied")));
+#[test]
+fn test_agent_choices_adds_unavailable_current_first() {
+    let available = installed(&["claude", "codex"]);
 
-    let result = create_pr_with_content(
-        &task,
-        Path::new("/project"),
-        "PR",
-        "Body",
-        &mock_git,
-        &mock_git_provider,
-        &mock_agent,
-    );
+    assert_eq!(agent_choices(&available, "codex"), vec!["claude", "codex"]);
+    assert_eq!(agent_choices(&available, "aider"), vec!["aider", "claude", "codex"]);
+    assert_eq!(agent_choices(&available, ""), vec!["claude", "codex"]);
+}
 
-    assert!(result.is_err());
-    assert!(result.unwrap_err().to_string().contains("Permission denied"));
+#[test]
+fn test_cycle_agent_wraps_both_ways() {
+    let agents: Vec<String> = ["claude", "codex", "gemini"].iter().map(|s| s.to_string()).collect();
+
+    assert_eq!(cycle_agent(&agents, "claude", true), "codex");
+    assert_eq!(cycle_agent(&agents, "gemini", true), "claude");
+    assert_eq!(cycle_agent(&agents, "claude", false), "gemini");
+    assert_eq!(cycle_agent(&[], "claude", true), "claude");
 }
 
 // =============================================================================
-// Tests for push_changes_to_existing_pr
+// Tests for bulk moves
 // =============================================================================
 
-/// Test pushing changes to existing PR
 #[test]
-#[cfg(feature = "test-mocks")]
-fn test_push_changes_to_existing_pr_success() {
-    let mut mock_git = MockGitOperations::new();
-    let mut mock_agent = MockAgentOperations::new();
+fn test_next_status_follows_columns() {
+    assert_eq!(next_status(TaskStatus::Backlog), Some(TaskStatus::Planning));
+    assert_eq!(next_status(TaskStatus::Planning), Some(TaskStatus::Running));
+    assert_eq!(next_status(TaskStatus::Running), Some(TaskStatus::Review));
+    assert_eq!(next_status(TaskStatus::Review), Some(TaskStatus::Done));
+    assert_eq!(next_status(TaskStatus::Done), None);
+}
 
-    let task = Task {
-        id: "test-456".to_string(),
-        title: "Existing PR task".to_string(),
-        description: None,
-        status: TaskStatus::Review,
-        agent: "claude".to_string(),
-        project_id: "proj-1".to_string(),
-        session_name: Some("test-session".to_string()),
-        worktree_path: Some("/tmp/worktree".to_string()),
-        branch_name: Some("feature/existing".to_string()),
-        pr_number: Some(99),
-        pr_url: Some("https://github.com/org/repo/pull/99".to_string()),
-        plugin: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
+#[test]
+fn test_move_may_prompt_only_from_running_and_review() {
+    assert!(!move_may_prompt(TaskStatus::Backlog));
+    assert!(!move_may_prompt(TaskStatus::Planning));
+    assert!(move_may_prompt(TaskStatus::Running));
+    assert!(move_may_prompt(TaskStatus::Review));
+}
+
+#[test]
+fn test_bulk_move_summary_lists_skipped_with_reasons() {
+    let summary = BulkMoveSummary {
+        moved: 3,
+        prompted: vec!["Ship it".to_string()],
+        skipped: vec![
+            ("Old".to_string(), "already Done".to_string()),
+            ("Waits".to_string(), "blocked by unfinished tasks".to_string()),
+        ],
     };
 
-    mock_git.expect_add_all().returning(|_| Ok(()));
-    mock_git.expect_has_changes().returning(|_| true);
+    assert_eq!(
+        summary.message(),
+        "Moved 3 task(s); confirm 'Ship it'; skipped 2: 'Old' (already Done), 'Waits' (blocked by unfinished tasks)"
+    );
+}
 
-    // Commit message should include "Address review comments"
-    mock_git
-        .expect_commit()
-        .withf(|_: &Path, msg: &str| msg.contains("Address review comments"))
-        .returning(|_, _| Ok(()));
+#[test]
+fn test_bulk_move_summary_all_moved() {
+    let summary = BulkMoveSummary { moved: 2, ..Default::default() };
+    assert_eq!(summary.message(), "Moved 2 task(s)");
+}
 
-    // Push without setting upstream (false)
-    mock_git
-        .expect_push()
-        .withf(|_: &Path, branch: &str, set_upstream: &bool| {
-            branch == "feature/existing" && !*set_upstream
-        })
-        .returning(|_, _, _| Ok(()));
+/// Test that bulk worktree creation overlaps git calls, stays within the pool, and keeps
+/// per-task results (one failure doesn't affect the rest)
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_create_worktrees_parallel_bounded_and_per_task() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
-    mock_agent
-        .expect_co_author_string()
-        .return_const("Claude <claude@anthropic.com>".to_string());
+    let active = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let mut mock_git = MockGitOperations::new();
+    // One expectation per slug: mockall runs a single expectation's calls one at a time
+    for i in 0..10 {
+        let (active, peak) = (Arc::clone(&active), Arc::clone(&peak));
+        mock_git
+            .expect_create_worktree()
+            .with(
+                mockall::predicate::always(),
+                mockall::predicate::eq(format!("slug-{}", i)),
+                mockall::predicate::eq("main"),
+            )
+            .times(1)
+            .returning(move |_, slug, _| {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                std::thread::sleep(std::time::Duration::from_millis(20));
+                active.fetch_sub(1, Ordering::SeqCst);
+                if slug == "slug-3" {
+                    return Err(command_failed("git worktree add", "could not lock"));
+                }
+                Ok(format!("/project/.agtx/worktrees/{}", slug))
+            });
+    }
 
-    let result = push_changes_to_existing_pr(&task, &mock_git, &mock_agent);
+    let slugs: Vec<(String, String)> = (0..10).map(|i| (format!("task-{}", i), format!("slug-{}", i))).collect();
+    let results = create_worktrees_parallel(Path::new("/project"), "main", &slugs, &mock_git);
+
+    assert_eq!(results.len(), 10);
+    for (i, (task_id, result)) in results.iter().enumerate() {
+        assert_eq!(task_id, &format!("task-{}", i));
+        if i == 3 {
+            assert!(result.is_err());
+        } else {
+            assert_eq!(result.as_deref().unwrap(), format!("/project/.agtx/worktrees/slug-{}", i));
+        }
+    }
+    let peak = peak.load(Ordering::SeqCst);
+    assert!(peak > 1, "worktrees were created one at a time");
+    assert!(peak <= WORKTREE_POOL_SIZE);
+}
 
-    assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "https://github.com/org/repo/pull/99");
+/// Test that a worktree prepared by a bulk move is used instead of creating another
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_uses_prepared_worktree() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, mock_git, _| {
+        mock_git.expect_create_worktree().never();
+        mock_tmux
+            .expect_create_window()
+            .withf(|_, _, dir, _| dir == "/project/.agtx/worktrees/ready")
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+    });
+
+    let mut task = Task::new("Prepared", "claude", "project-1");
+    let window = WindowSetup::default();
+    setup_task_worktree(
+        &mut task,
+        &worktree_setup(&window),
+        Some("/project/.agtx/worktrees/ready".to_string()),
+        &mock_tmux,
+        &mock_git,
+        &mock_agent,
+        &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    assert_eq!(task.worktree_path.as_deref(), Some("/project/.agtx/worktrees/ready"));
 }
 
-/// Test pushing when no changes exist
+/// Test that a worktree created for a move that didn't happen goes with its branch, in the
+/// project it was created in
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_push_changes_to_existing_pr_no_changes() {
+fn test_discard_worktree_removes_it_and_its_branch() {
     let mut mock_git = MockGitOperations::new();
-    let mock_agent = MockAgentOperations::new();
+    mock_git
+        .expect_remove_worktree()
+        .with(mockall::predicate::eq(Path::new("/other")), mockall::predicate::eq("/other/.agtx/worktrees/fix-login"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+    mock_git
+        .expect_delete_branch()
+        .with(mockall::predicate::eq(Path::new("/other")), mockall::predicate::eq("task/fix-login"))
+        .times(1)
+        .returning(|_, _| Ok(()));
 
-    let task = Task {
-        id: "test-789".to_string(),
-        title: "Task with no changes".to_string(),
-        description: None,
-        status: TaskStatus::Review,
-        agent: "claude".to_string(),
-        project_id: "proj-1".to_string(),
-        session_name: None,
-        worktree_path: Some("/tmp/worktree".to_string()),
-        branch_name: Some("feature/no-changes".to_string()),
-        pr_number: Some(50),
-        pr_url: Some("https://github.com/org/repo/pull/50".to_string()),
-        plugin: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-    };
+    discard_worktree(Path::new("/other"), "/other/.agtx/worktrees/fix-login", &mock_git);
+}
 
-    mock_git.expect_add_all().returning(|_| Ok(()));
-    mock_git.expect_has_changes().returning(|_| false);
-    // No commit expected
-    mock_git.expect_push().returning(|_, _, _| Ok(()));
+#[test]
+fn test_agent_exit_badge_only_for_failures() {
+    let mut task = crate::db::Task::new("Task", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    assert_eq!(agent_exit_badge(&task), None);
 
-    let result = push_changes_to_existing_pr(&task, &mock_git, &mock_agent);
+    task.last_exit_code = Some(0);
+    assert_eq!(agent_exit_badge(&task), None);
 
-    assert!(result.is_ok());
+    task.last_exit_code = Some(2);
+    assert_eq!(agent_exit_badge(&task).as_deref(), Some("⚠ exit 2"));
+
+    task.status = TaskStatus::Done;
+    assert_eq!(agent_exit_badge(&task), None);
 }
 
-/// Test push with no existing PR URL
+// =============================================================================
+// Tests for resuming Review tasks
+// =============================================================================
+
+#[cfg(feature = "test-mocks")]
+fn review_task(title: &str, window: Option<&str>) -> crate::db::Task {
+    let mut task = crate::db::Task::new(title, "claude", "project-1");
+    task.status = TaskStatus::Review;
+    task.session_name = window.map(str::to_string);
+    task.worktree_path = Some(format!("/tmp/worktrees/{}", title));
+    task.branch_name = Some(format!("task/{}", title));
+    task.pr_number = Some(12);
+    task
+}
+
+/// Resuming keeps the window, worktree, branch and PR; no tmux or git calls are made
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_push_changes_to_existing_pr_no_url() {
-    let mut mock_git = MockGitOperations::new();
-    let mock_agent = MockAgentOperations::new();
+fn test_resume_from_review_does_not_recreate_resources() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_create_window().never();
+    mock_tmux.expect_send_keys().never();
+    let tasks = vec![review_task("a", Some("proj:task-a"))];
+
+    let (resumed, prompted, _) = resume_review_tasks(&tasks, "", &mock_tmux, |_| Ok(false));
+
+    assert_eq!(resumed.len(), 1);
+    assert_eq!(prompted, 0);
+    let task = &resumed[0];
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.session_name.as_deref(), Some("proj:task-a"));
+    assert_eq!(task.worktree_path.as_deref(), Some("/tmp/worktrees/a"));
+    assert_eq!(task.branch_name.as_deref(), Some("task/a"));
+    assert_eq!(task.pr_number, Some(12));
+}
 
-    let task = Task {
-        id: "test-abc".to_string(),
-        title: "Task without PR URL".to_string(),
-        description: None,
-        status: TaskStatus::Review,
-        agent: "claude".to_string(),
-        project_id: "proj-1".to_string(),
-        session_name: None,
-        worktree_path: Some("/tmp/worktree".to_string()),
-        branch_name: Some("feature/branch".to_string()),
-        pr_number: None,
-        pr_url: None, // No PR URL
-        plugin: None,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
-    };
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_resume_review_tasks_only_touches_review_and_sends_follow_up() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux
+        .expect_send_keys()
+        .with(mockall::predicate::eq("proj:task-a"), mockall::predicate::eq("Address the review comments"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+    mock_tmux
+        .expect_send_keys()
+        .with(mockall::predicate::eq("proj:task-b"), mockall::predicate::eq("Address the review comments"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+    let mut running = review_task("running", Some("proj:task-r"));
+    running.status = TaskStatus::Running;
+    let tasks = vec![
+        review_task("a", Some("proj:task-a")),
+        running,
+        review_task("b", Some("proj:task-b")),
+        // Window already gone: still resumed, just not prompted
+        review_task("c", None),
+    ];
+
+    let (resumed, prompted, _) =
+        resume_review_tasks(&tasks, "  Address the review comments ", &mock_tmux, |_| Ok(false));
+
+    let titles: Vec<&str> = resumed.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["a", "b", "c"]);
+    assert!(resumed.iter().all(|t| t.status == TaskStatus::Running));
+    assert_eq!(prompted, 2);
+}
 
-    mock_git.expect_add_all().returning(|_| Ok(()));
-    mock_git.expect_has_changes().returning(|_| false);
-    mock_git.expect_push().returning(|_, _, _| Ok(()));
+/// Test that a closed window goes through `reopen`, which sends the follow-up itself, and that
+/// a task whose agent can't be brought back stays in Review
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_resume_review_tasks_reopens_closed_windows() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux
+        .expect_send_keys()
+        .with(mockall::predicate::eq("proj:task-a"), mockall::predicate::eq("Rebase please"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+    let tasks = vec![
+        review_task("a", Some("proj:task-a")),
+        review_task("closed", Some("proj:task-closed")),
+        review_task("broken", None),
+    ];
+
+    let mut reopened = Vec::new();
+    let (resumed, prompted, unreachable) = resume_review_tasks(&tasks, "Rebase please", &mock_tmux, |task| {
+        reopened.push(task.title.clone());
+        match task.title.as_str() {
+            "a" => Ok(false),
+            "closed" => Ok(true),
+            _ => anyhow::bail!("no worktree"),
+        }
+    });
 
-    let result = push_changes_to_existing_pr(&task, &mock_git, &mock_agent);
+    assert_eq!(reopened, vec!["a", "closed", "broken"]);
+    let titles: Vec<&str> = resumed.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["a", "closed"]);
+    assert_eq!(prompted, 2);
+    assert_eq!(unreachable.len(), 1);
+    assert_eq!(unreachable[0].0, "broken");
+}
 
-    assert!(result.is_ok());
-    assert_eq!(result.unwrap(), "Changes pushed to existing PR");
+#[test]
+fn test_resume_from_review_ignores_other_columns() {
+    let mut task = crate::db::Task::new("Planning", "claude", "project-1");
+    task.status = TaskStatus::Planning;
+
+    assert!(!resume_from_review(&mut task));
+    assert_eq!(task.status, TaskStatus::Planning);
 }
 
 // =============================================================================
-// Tests for fuzzy_find_files
+// Tests for the branch diff preview
 // =============================================================================
 
-/// Test fuzzy file search with matching pattern
+#[test]
+fn test_truncate_diff_keeps_short_diffs() {
+    let diff = "+a\n-b\n";
+    assert_eq!(truncate_diff(diff, 10), diff);
+}
+
+#[test]
+fn test_truncate_diff_cuts_long_diffs_with_note() {
+    let diff: String = (0..10).map(|i| format!("+line {}\n", i)).collect();
+
+    let truncated = truncate_diff(&diff, 3);
+
+    let lines: Vec<&str> = truncated.lines().collect();
+    assert_eq!(&lines[..3], &["+line 0", "+line 1", "+line 2"]);
+    assert!(lines.last().unwrap().contains("showing 3 of 10 lines"));
+}
+
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_fuzzy_find_files_basic() {
+fn test_branch_diff_content_uses_configured_base() {
     let mut mock_git = MockGitOperations::new();
-
     mock_git
-        .expect_list_files()
-        .returning(|_| vec![
-            "src/main.rs".to_string(),
-            "src/lib.rs".to_string(),
-            "src/tui/app.rs".to_string(),
-            "src/tui/board.rs".to_string(),
-            "Cargo.toml".to_string(),
-        ]);
-
-    let results = fuzzy_find_files(Path::new("/project"), "app", 10, &mock_git);
+        .expect_diff_from_base()
+        .withf(|path, base| path == Path::new("/tmp/wt") && base == "develop")
+        .times(1)
+        .returning(|_, _| Ok("+added\n".to_string()));
+    let mut task = crate::db::Task::new("Task", "claude", "project-1");
+    task.worktree_path = Some("/tmp/wt".to_string());
 
-    assert!(!results.is_empty());
-    assert!(results.contains(&"src/tui/app.rs".to_string()));
+    assert_eq!(branch_diff_content(&task, "develop", &mock_git), "+added\n");
 }
 
-/// Test fuzzy file search with empty pattern returns first N files
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_fuzzy_find_files_empty_pattern() {
+fn test_branch_diff_content_reports_empty_and_errors() {
+    let mut task = crate::db::Task::new("Task", "claude", "project-1");
+    let mock_git = MockGitOperations::new();
+    assert_eq!(branch_diff_content(&task, "main", &mock_git), "(task has no worktree yet)");
+
+    task.worktree_path = Some("/tmp/wt".to_string());
     let mut mock_git = MockGitOperations::new();
+    mock_git.expect_diff_from_base().returning(|_, _| Ok(String::new()));
+    assert_eq!(branch_diff_content(&task, "main", &mock_git), "(no committed changes since main)");
 
+    let mut mock_git = MockGitOperations::new();
     mock_git
-        .expect_list_files()
-        .returning(|_| vec![
-            "a.rs".to_string(),
-            "b.rs".to_string(),
-            "c.rs".to_string(),
-            "d.rs".to_string(),
-            "e.rs".to_string(),
-        ]);
+        .expect_diff_from_base()
+        .returning(|_, _| Err(command_failed("git diff main...HEAD", "fatal: unknown revision")));
+    assert_eq!(
+        branch_diff_content(&task, "main", &mock_git),
+        "Could not diff against main: `git diff main...HEAD` failed (exit 128): fatal: unknown revision"
+    );
+}
 
-    let results = fuzzy_find_files(Path::new("/project"), "", 3, &mock_git);
+// =============================================================================
+// Tests for the error bar
+// =============================================================================
 
-    assert_eq!(results.len(), 3);
-    assert_eq!(results[0], "a.rs");
-    assert_eq!(results[1], "b.rs");
-    assert_eq!(results[2], "c.rs");
+#[test]
+fn test_error_report_splits_action_from_message() {
+    let err = anyhow::anyhow!("invalid reference: main")
+        .context("Failed to create worktree")
+        .context("Moving 'Add login' to planning");
+
+    let report = ErrorReport::from_error(&err);
+
+    assert_eq!(report.action, "Moving 'Add login' to planning");
+    assert_eq!(report.message, "Failed to create worktree: invalid reference: main");
 }
 
-/// Test fuzzy file search with no matches
+#[test]
+fn test_error_report_without_context() {
+    let report = ErrorReport::from_error(&anyhow::anyhow!("tmux not running"));
+
+    assert_eq!(report.action, "Operation failed");
+    assert_eq!(report.message, "tmux not running");
+}
+
+// =============================================================================
+// Tests for the worktree dirty/clean marker
+// =============================================================================
+
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_fuzzy_find_files_no_matches() {
-    let mut mock_git = MockGitOperations::new();
+fn test_worktree_dirty_state_reports_git_status() {
+    let worktree = std::env::temp_dir();
+    let mut task = crate::db::Task::new("Task", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.worktree_path = Some(worktree.to_string_lossy().to_string());
 
+    let mut mock_git = MockGitOperations::new();
     mock_git
-        .expect_list_files()
-        .returning(|_| vec!["main.rs".to_string(), "lib.rs".to_string()]);
-
-    let results = fuzzy_find_files(Path::new("/project"), "xyz123", 10, &mock_git);
+        .expect_is_worktree_dirty()
+        .withf(move |path| path == worktree.as_path())
+        .times(1)
+        .returning(|_| Ok(true));
+    assert_eq!(worktree_dirty_state(&task, &mock_git), Some(true));
 
-    assert!(results.is_empty());
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_worktree_dirty().returning(|_| Ok(false));
+    assert_eq!(worktree_dirty_state(&task, &mock_git), Some(false));
 }
 
-/// Test fuzzy file search with empty file list
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_fuzzy_find_files_empty_list() {
+fn test_worktree_dirty_state_none_without_worktree() {
     let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_worktree_dirty().never();
 
-    mock_git.expect_list_files().returning(|_| vec![]);
+    // Backlog task: no worktree yet
+    let mut task = crate::db::Task::new("Task", "claude", "project-1");
+    assert_eq!(worktree_dirty_state(&task, &mock_git), None);
 
-    let results = fuzzy_find_files(Path::new("/project"), "app", 10, &mock_git);
+    // Worktree path recorded but removed from disk
+    task.status = TaskStatus::Review;
+    task.worktree_path = Some("/nonexistent/agtx-worktree".to_string());
+    assert_eq!(worktree_dirty_state(&task, &mock_git), None);
 
-    assert!(results.is_empty());
+    // Done tasks have had their worktree cleaned up
+    task.status = TaskStatus::Done;
+    task.worktree_path = Some(std::env::temp_dir().to_string_lossy().to_string());
+    assert_eq!(worktree_dirty_state(&task, &mock_git), None);
 }
 
-/// Test fuzzy file search respects max_results
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_fuzzy_find_files_max_results() {
+fn test_worktree_dirty_state_none_on_git_error() {
+    let mut task = crate::db::Task::new("Task", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.worktree_path = Some(std::env::temp_dir().to_string_lossy().to_string());
     let mut mock_git = MockGitOperations::new();
-
     mock_git
-        .expect_list_files()
-        .returning(|_| vec![
-            "src/app1.rs".to_string(),
-            "src/app2.rs".to_string(),
-            "src/app3.rs".to_string(),
-            "src/app4.rs".to_string(),
-            "src/app5.rs".to_string(),
-        ]);
+        .expect_is_worktree_dirty()
+        .returning(|path| Err(OpError::NotAGitRepo { path: path.to_path_buf() }));
 
-    let results = fuzzy_find_files(Path::new("/project"), "app", 2, &mock_git);
-
-    assert_eq!(results.len(), 2);
+    assert_eq!(worktree_dirty_state(&task, &mock_git), None);
 }
 
 // =============================================================================
-// Tests for fuzzy_score
+// Tests for repo_problem
 // =============================================================================
 
-/// Test fuzzy score with exact match
+/// Test that a non-git directory is reported and commits aren't checked
 #[test]
-fn test_fuzzy_score_exact_match() {
-    let score = fuzzy_score("main.rs", "main.rs");
-    assert!(score > 0);
+#[cfg(feature = "test-mocks")]
+fn test_repo_problem_not_a_git_repo() {
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_git_repo().returning(|_| false);
+
+    let problem = repo_problem(Path::new("/tmp/plain-dir"), &mock_git).unwrap();
+    assert!(problem.contains("not a git repository"));
+    assert!(problem.contains("/tmp/plain-dir"));
 }
 
-/// Test fuzzy score with partial match
+/// Test that a repo without commits is reported
 #[test]
-fn test_fuzzy_score_partial_match() {
-    let score = fuzzy_score("src/main.rs", "main");
-    assert!(score > 0);
+#[cfg(feature = "test-mocks")]
+fn test_repo_problem_no_commits() {
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_git_repo().returning(|_| true);
+    mock_git.expect_has_commits().returning(|_| false);
+
+    let problem = repo_problem(Path::new("/tmp/new-repo"), &mock_git).unwrap();
+    assert!(problem.contains("no commits"));
 }
 
-/// Test fuzzy score with no match
+/// Test that a repo with history is ready
 #[test]
-fn test_fuzzy_score_no_match() {
-    let score = fuzzy_score("main.rs", "xyz");
-    assert_eq!(score, 0);
+#[cfg(feature = "test-mocks")]
+fn test_repo_problem_ready() {
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_git_repo().returning(|_| true);
+    mock_git.expect_has_commits().returning(|_| true);
+
+    assert!(repo_problem(Path::new("/tmp/repo"), &mock_git).is_none());
 }
 
-/// Test fuzzy score with empty needle
+/// Test that a configured base branch wins and detection runs once, then comes from the cache
 #[test]
-fn test_fuzzy_score_empty_needle() {
-    let score = fuzzy_score("main.rs", "");
-    assert_eq!(score, 1);
+#[cfg(feature = "test-mocks")]
+fn test_resolve_base_branch_detects_once() {
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_default_branch().times(1).returning(|_| Ok("develop".to_string()));
+    let mut detected = None;
+    let project = Some(Path::new("/tmp/repo"));
+
+    assert_eq!(resolve_base_branch(Some("trunk"), &mut detected, project, &mock_git), "trunk");
+    assert!(detected.is_none());
+    assert_eq!(resolve_base_branch(None, &mut detected, project, &mock_git), "develop");
+    assert_eq!(resolve_base_branch(None, &mut detected, project, &mock_git), "develop");
+    assert_eq!(detected.as_deref(), Some("develop"));
 }
 
-/// Test fuzzy score bonus for word start
+/// Test that a failed detection falls back to main
 #[test]
-fn test_fuzzy_score_word_boundary_bonus() {
-    // "app" at start of segment should score higher than in middle
-    let score_start = fuzzy_score("src/app.rs", "app");
-    let score_middle = fuzzy_score("src/myapp.rs", "app");
-    assert!(score_start > score_middle);
+#[cfg(feature = "test-mocks")]
+fn test_resolve_base_branch_falls_back_to_main() {
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_default_branch()
+        .returning(|_| Err(command_failed("git symbolic-ref", "fatal: not a git repository")));
+
+    let mut detected = None;
+    assert_eq!(resolve_base_branch(None, &mut detected, Some(Path::new("/tmp/x")), &mock_git), "main");
+    let mut detected = None;
+    assert_eq!(resolve_base_branch(None, &mut detected, None, &mock_git), "main");
 }
 
-/// Test fuzzy score bonus for consecutive matches
 #[test]
-fn test_fuzzy_score_consecutive_bonus() {
-    // Consecutive "main" should score higher than scattered chars within a word
-    let score_consecutive = fuzzy_score("main.rs", "main");
-    let score_scattered = fuzzy_score("myaweirdin.rs", "main");
-    assert!(score_consecutive > score_scattered);
+fn test_git_problem_from_probe() {
+    assert!(git_problem(&Ok("git version 2.43.0".to_string())).is_none());
+
+    let missing = git_problem(&Err(OpError::CommandNotFound { program: "git".to_string() })).unwrap();
+    assert!(missing.contains("git not found in PATH"));
+    assert!(missing.contains("install git"));
+
+    let broken = OpError::CommandFailed { command: "git --version".to_string(), code: Some(1), stderr: "boom".to_string() };
+    assert!(git_problem(&Err(broken)).unwrap().contains("boom"));
 }
 
 // =============================================================================
-// Tests for send_key_to_tmux
+// Tests for filter_projects
 // =============================================================================
 
-/// Test sending character key to tmux
-#[test]
-#[cfg(feature = "test-mocks")]
-fn test_send_key_to_tmux_char() {
-    let mut mock_tmux = MockTmuxOperations::new();
+fn project_info(name: &str, path: &str) -> ProjectInfo {
+    ProjectInfo { name: name.to_string(), path: path.to_string() }
+}
 
-    mock_tmux
-        .expect_send_keys_literal()
-        .with(
-            mockall::predicate::eq("test-window"),
-            mockall::predicate::eq("a"),
-        )
-        .times(1)
-        .returning(|_, _| Ok(()));
+#[test]
+fn test_filter_projects_empty_query_returns_all() {
+    let projects = vec![project_info("agtx", "/code/agtx"), project_info("web", "/code/web")];
+    assert_eq!(filter_projects(&projects, ""), vec![0, 1]);
+}
 
-    send_key_to_tmux("test-window", KeyCode::Char('a'), &mock_tmux);
+#[test]
+fn test_filter_projects_matches_name() {
+    let projects = vec![project_info("agtx", "/code/agtx"), project_info("website", "/srv/site")];
+    assert_eq!(filter_projects(&projects, "web"), vec![1]);
+}
+
+#[test]
+fn test_filter_projects_matches_path() {
+    let projects = vec![project_info("api", "/work/backend/api"), project_info("ui", "/work/frontend/ui")];
+    assert_eq!(filter_projects(&projects, "frontend"), vec![1]);
 }
 
-/// Test sending Enter key to tmux
+#[test]
+fn test_filter_projects_no_match() {
+    let projects = vec![project_info("agtx", "/code/agtx")];
+    assert!(filter_projects(&projects, "zzz").is_empty());
+}
+
+// =============================================================================
+// Tests for count_live_windows
+// =============================================================================
+
+/// Test that only tasks whose tmux window still exists are counted
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_send_key_to_tmux_enter() {
-    let mut mock_tmux = MockTmuxOperations::new();
+fn test_count_live_windows_skips_dead_and_missing() {
+    use crate::db::{Task, TaskStatus};
 
+    let mut mock_tmux = MockTmuxOperations::new();
     mock_tmux
-        .expect_send_keys_literal()
-        .with(
-            mockall::predicate::eq("test-window"),
-            mockall::predicate::eq("Enter"),
-        )
-        .times(1)
-        .returning(|_, _| Ok(()));
+        .expect_window_exists()
+        .returning(|target| Ok(target == "proj:task-alive"));
 
-    send_key_to_tmux("test-window", KeyCode::Enter, &mock_tmux);
+    let mut alive = Task::new("Alive", "claude", "proj");
+    alive.status = TaskStatus::Running;
+    alive.session_name = Some("proj:task-alive".to_string());
+
+    let mut dead = Task::new("Dead", "claude", "proj");
+    dead.status = TaskStatus::Review;
+    dead.session_name = Some("proj:task-dead".to_string());
+
+    // No session recorded - window_exists should not be consulted
+    let backlog = Task::new("Backlog", "claude", "proj");
+
+    let tasks = vec![alive, dead, backlog];
+    assert_eq!(count_live_windows(&tasks, &mock_tmux), 1);
 }
 
-/// Test sending special keys to tmux
+// =============================================================================
+// Tests for find_resource_mismatches
+// =============================================================================
+
+#[cfg(feature = "test-mocks")]
+fn active_task(title: &str, status: crate::db::TaskStatus, slug: &str) -> crate::db::Task {
+    let mut task = crate::db::Task::new(title, "claude", "proj");
+    task.status = status;
+    task.session_name = Some(format!("proj:task-{}", slug));
+    task.worktree_path = Some(format!("/repo/.agtx/worktrees/{}", slug));
+    task
+}
+
+/// Test that each kind of missing resource is flagged and healthy tasks are not
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_send_key_to_tmux_special_keys() {
-    let mut mock_tmux = MockTmuxOperations::new();
+fn test_find_resource_mismatches_flags_missing_window_and_worktree() {
+    use crate::db::TaskStatus;
 
-    // Test Escape
+    let mut mock_tmux = MockTmuxOperations::new();
     mock_tmux
-        .expect_send_keys_literal()
-        .with(mockall::predicate::eq("win"), mockall::predicate::eq("Escape"))
-        .returning(|_, _| Ok(()));
+        .expect_list_windows()
+        .withf(|session| session == "proj")
+        .times(1)
+        .returning(|_| Ok(vec!["task-ok".to_string(), "task-no-wt".to_string()]));
+    mock_tmux.expect_window_exists().never();
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_list_worktrees().times(1).returning(|_| {
+        Ok(vec![PathBuf::from("/repo/.agtx/worktrees/ok"), PathBuf::from("/repo/.agtx/worktrees/no-window")])
+    });
+    mock_git.expect_worktree_exists().never();
 
-    send_key_to_tmux("win", KeyCode::Esc, &mock_tmux);
+    let tasks = vec![
+        active_task("Ok", TaskStatus::Running, "ok"),
+        active_task("No window", TaskStatus::Running, "no-window"),
+        active_task("No worktree", TaskStatus::Review, "no-wt"),
+        active_task("Neither", TaskStatus::Review, "gone"),
+    ];
 
-    // Test Backspace
-    let mut mock_tmux2 = MockTmuxOperations::new();
-    mock_tmux2
-        .expect_send_keys_literal()
-        .with(mockall::predicate::eq("win"), mockall::predicate::eq("BSpace"))
-        .returning(|_, _| Ok(()));
+    let mismatches = find_resource_mismatches(&tasks, Path::new("/repo"), &mock_tmux, &mock_git);
 
-    send_key_to_tmux("win", KeyCode::Backspace, &mock_tmux2);
+    assert_eq!(
+        mismatches,
+        vec![
+            (tasks[1].id.clone(), MissingResource::Window),
+            (tasks[2].id.clone(), MissingResource::Worktree),
+            (tasks[3].id.clone(), MissingResource::WindowAndWorktree),
+        ]
+    );
 }
 
-/// Test sending function key to tmux
+/// Test that only Running/Review tasks are checked, and a tmux error isn't reported as a missing window
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_send_key_to_tmux_function_key() {
-    let mut mock_tmux = MockTmuxOperations::new();
+fn test_find_resource_mismatches_skips_other_columns_and_tmux_errors() {
+    use crate::db::TaskStatus;
 
+    let mut mock_tmux = MockTmuxOperations::new();
     mock_tmux
-        .expect_send_keys_literal()
-        .with(mockall::predicate::eq("win"), mockall::predicate::eq("F5"))
-        .returning(|_, _| Ok(()));
+        .expect_list_windows()
+        .times(1)
+        .returning(|_| Err(OpError::CommandNotFound { program: "tmux".to_string() }));
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_list_worktrees()
+        .times(1)
+        .returning(|_| Ok(vec![PathBuf::from("/repo/.agtx/worktrees/running")]));
 
-    send_key_to_tmux("win", KeyCode::F(5), &mock_tmux);
-}
+    let tasks = vec![
+        active_task("Planning", TaskStatus::Planning, "planning"),
+        active_task("Done", TaskStatus::Done, "done"),
+        active_task("Running", TaskStatus::Running, "running"),
+    ];
 
-// =============================================================================
-// Tests for capture_tmux_pane_with_history
-// =============================================================================
+    assert!(find_resource_mismatches(&tasks, Path::new("/repo"), &mock_tmux, &mock_git).is_empty());
+}
 
-/// Test capturing tmux pane content
+/// Test that a board with no Running/Review task makes no tmux or git call at all
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_capture_tmux_pane_with_history() {
-    let mut mock_tmux = MockTmuxOperations::new();
+fn test_find_resource_mismatches_skips_sweep_without_active_tasks() {
+    use crate::db::TaskStatus;
 
-    mock_tmux
-        .expect_capture_pane_with_history()
-        .with(mockall::predicate::eq("test-window"), mockall::predicate::eq(500))
-        .returning(|_, _| b"Line 1\nLine 2\nLine 3\n".to_vec());
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_list_windows().never();
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_list_worktrees().never();
 
-    mock_tmux
-        .expect_get_cursor_info()
-        .with(mockall::predicate::eq("test-window"))
-        .returning(|_| Some((2, 3))); // cursor at line 2, pane has 3 lines
+    let tasks = vec![active_task("Planning", TaskStatus::Planning, "planning")];
+    assert!(find_resource_mismatches(&tasks, Path::new("/repo"), &mock_tmux, &mock_git).is_empty());
+}
 
-    let content = capture_tmux_pane_with_history("test-window", 500, &mock_tmux);
+/// Test that a failed worktree listing falls back to checking each task's directory
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_sweep_resources_falls_back_when_worktree_list_fails() {
+    use crate::db::TaskStatus;
 
-    // Content should be trimmed to cursor position
-    assert!(!content.is_empty());
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_list_windows().returning(|_| Ok(vec![]));
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_list_worktrees()
+        .returning(|_| Err(OpError::CommandNotFound { program: "git".to_string() }));
+    mock_git.expect_worktree_exists().times(2).returning(|_, slug| slug == "kept");
+
+    let tasks = vec![
+        active_task("Kept", TaskStatus::Running, "kept"),
+        active_task("Gone", TaskStatus::Review, "gone"),
+    ];
+    let sweep = sweep_resources(&tasks, Path::new("/repo"), &mock_tmux, &mock_git);
+
+    assert_eq!(sweep.worktrees, HashSet::from(["kept".to_string()]));
+    assert_eq!(sweep.windows, HashMap::from([("proj".to_string(), Some(HashSet::new()))]));
 }
 
-// =============================================================================
-// Tests for centered_rect helpers (pure functions, no mocks needed)
-// =============================================================================
-
-/// Test centered_rect creates correct dimensions
+/// Test that live presence checks only look: no tmux or git call beyond the two lookups
 #[test]
-fn test_centered_rect() {
-    let area = Rect::new(0, 0, 100, 50);
-    let popup = centered_rect(50, 50, area);
+#[cfg(feature = "test-mocks")]
+fn test_resource_presence_only_looks_up() {
+    use crate::db::TaskStatus;
 
-    // Should be centered horizontally and vertically
-    assert!(popup.x > 0);
-    assert!(popup.y > 0);
-    assert!(popup.width < 100);
-    assert!(popup.height < 50);
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_window_exists().times(1).returning(|_| Ok(false));
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_worktree_exists()
+        .withf(|_, slug| slug == "fix")
+        .times(1)
+        .returning(|_, _| true);
+
+    let task = active_task("Fix", TaskStatus::Running, "fix");
+    let presence = resource_presence(&task, Path::new("/repo"), &mock_tmux, &mock_git);
+
+    assert_eq!(presence, ResourcePresence { window: Some(false), worktree: true });
+    assert_eq!(presence.missing(), Some(MissingResource::Window));
 }
 
-/// Test centered_rect_fixed_width creates correct dimensions
+/// Test resolving a board against one sweep: per session windows, unknown sessions, and
+/// tasks without a recorded window
 #[test]
-fn test_centered_rect_fixed_width() {
-    let area = Rect::new(0, 0, 100, 50);
-    let popup = centered_rect_fixed_width(40, 50, area);
+fn test_reconcile_all() {
+    use crate::db::{Task, TaskStatus};
+
+    let task = |title: &str, status: TaskStatus, session: Option<&str>, slug: &str| {
+        let mut task = Task::new(title, "claude", "proj");
+        task.status = status;
+        task.session_name = session.map(str::to_string);
+        task.worktree_path = Some(format!("/repo/.agtx/worktrees/{}", slug));
+        task
+    };
+    let tasks = vec![
+        task("Ok", TaskStatus::Running, Some("proj:task-ok"), "ok"),
+        task("Closed", TaskStatus::Review, Some("proj:task-closed"), "closed"),
+        task("Other session", TaskStatus::Running, Some("other:task-x"), "x"),
+        task("Unlisted", TaskStatus::Running, Some("unlisted:task-y"), "y"),
+        task("No window", TaskStatus::Review, None, "ok"),
+        task("Backlog", TaskStatus::Backlog, Some("proj:task-ok"), "ok"),
+    ];
+    let windows = HashMap::from([
+        ("proj".to_string(), Some(HashSet::from(["task-ok".to_string()]))),
+        ("other".to_string(), None),
+    ]);
+    let worktrees = HashSet::from(["ok".to_string(), "x".to_string()]);
+
+    let presence: Vec<_> = reconcile_all(&tasks, &windows, &worktrees)
+        .into_iter()
+        .map(|drift| (drift.task_id, drift.presence.window, drift.presence.worktree))
+        .collect();
 
-    // Width should be fixed at 40
-    assert_eq!(popup.width, 40);
-    // Should be centered
-    assert_eq!(popup.x, 30); // (100 - 40) / 2
+    assert_eq!(
+        presence,
+        vec![
+            (tasks[0].id.clone(), Some(true), true),
+            (tasks[1].id.clone(), Some(false), false),
+            (tasks[2].id.clone(), None, true),
+            (tasks[3].id.clone(), None, false),
+            (tasks[4].id.clone(), Some(false), true),
+        ]
+    );
 }
 
-/// Test centered_rect_fixed_width caps width to terminal size
+/// Test that a window and worktree that are gone are dropped before abandoning a task
 #[test]
-fn test_centered_rect_fixed_width_capped() {
-    let area = Rect::new(0, 0, 30, 50); // Small terminal
-    let popup = centered_rect_fixed_width(100, 50, area); // Request large width
+#[cfg(feature = "test-mocks")]
+fn test_forget_missing_resources() {
+    use crate::db::TaskStatus;
 
-    // Width should be capped
-    assert!(popup.width <= 30);
-}
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_window_exists().returning(|_| Ok(false));
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_worktree_exists().returning(|_, _| true);
 
-// =============================================================================
-// Tests for hex_to_color
-// =============================================================================
+    let mut task = active_task("Fix", TaskStatus::Planning, "fix");
+    forget_missing_resources(&mut task, Path::new("/repo"), &mock_tmux, &mock_git);
+    assert_eq!(task.session_name, None);
+    assert!(task.worktree_path.is_some());
 
-/// Test hex_to_color with valid hex
-#[test]
-fn test_hex_to_color_valid() {
-    let color = hex_to_color("#FF0000");
-    assert_eq!(color, Color::Rgb(255, 0, 0));
+    // A window tmux can't check is kept, so cleanup still tries to close it
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux
+        .expect_window_exists()
+        .returning(|_| Err(OpError::TmuxDisabled));
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_worktree_exists().returning(|_, _| false);
+
+    let mut task = active_task("Fix", TaskStatus::Running, "fix");
+    forget_missing_resources(&mut task, Path::new("/repo"), &mock_tmux, &mock_git);
+    assert!(task.session_name.is_some());
+    assert_eq!(task.worktree_path, None);
 }
 
-/// Test hex_to_color with invalid hex falls back to white
+/// Test that the detail view asks for the last five commits since the base (or the task's
+/// `from_ref`) only when the task has a worktree, and hides the list when git can't read it
 #[test]
-fn test_hex_to_color_invalid() {
-    let color = hex_to_color("invalid");
-    assert_eq!(color, Color::White);
-}
+#[cfg(feature = "test-mocks")]
+fn test_task_recent_commits() {
+    use crate::db::TaskStatus;
+    use crate::git::CommitSummary;
 
-// =============================================================================
-// Tests for generate_task_slug
-// =============================================================================
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_recent_commits()
+        .withf(|path, base, n| path == Path::new("/repo/.agtx/worktrees/fix") && base == "main" && *n == 5)
+        .times(1)
+        .returning(|_, _, _| Ok(vec![CommitSummary { hash: "abc1234".to_string(), subject: "Fix login".to_string() }]));
+    let mut task = active_task("Fix", TaskStatus::Running, "fix");
+    let commits = task_recent_commits(&task, "main", &mock_git).unwrap();
+    assert_eq!(commits[0].subject, "Fix login");
 
-/// Test generate_task_slug with normal title
-#[test]
-fn test_generate_task_slug_normal() {
-    let slug = generate_task_slug("12345678-abcd-efgh", "Add login feature");
-    assert!(slug.starts_with("12345678-"));
-    assert!(slug.contains("Add-login-feature"));
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_recent_commits()
+        .withf(|_, base, _| base == "release-1.2")
+        .times(1)
+        .returning(|_, _, _| Ok(vec![]));
+    task.from_ref = Some("release-1.2".to_string());
+    assert_eq!(task_recent_commits(&task, "main", &mock_git), Some(vec![]));
+    task.from_ref = None;
+
+    let mut backlog = crate::db::Task::new("Later", "claude", "proj");
+    backlog.worktree_path = None;
+    assert_eq!(task_recent_commits(&backlog, "main", &MockGitOperations::new()), None);
+
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_recent_commits()
+        .returning(|path, _, _| Err(OpError::NotAGitRepo { path: path.to_path_buf() }));
+    assert_eq!(task_recent_commits(&task, "main", &mock_git), None);
 }
 
-/// Test generate_task_slug with special characters
+/// Test the footer warning for tasks sharing a title
 #[test]
-fn test_generate_task_slug_special_chars() {
-    let slug = generate_task_slug("abc12345", "Fix bug #123 (urgent!)");
-    assert!(slug.starts_with("abc12345-"));
-    // Special chars should be replaced with dashes
-    assert!(!slug.contains("#"));
-    assert!(!slug.contains("("));
-    assert!(!slug.contains("!"));
+fn test_title_conflict_warning() {
+    assert_eq!(title_conflict_warning("Fix login", 0), None);
+    assert_eq!(
+        title_conflict_warning("Fix login", 1).as_deref(),
+        Some("Another open task is also titled 'Fix login'; consider renaming one")
+    );
+    assert_eq!(
+        title_conflict_warning("Fix login", 2).as_deref(),
+        Some("2 other open tasks are also titled 'Fix login'; consider renaming them")
+    );
 }
 
-/// Test generate_task_slug truncates long titles
+/// Test the history detail recorded for a task moved straight to Done
 #[test]
-fn test_generate_task_slug_long_title() {
-    let long_title = "This is a very long task title that should be truncated to thirty characters";
-    let slug = generate_task_slug("abcd1234", long_title);
-    // 8 char id prefix + "-" + max 30 chars = max 39 chars
-    assert!(slug.len() <= 39);
+fn test_abandon_detail() {
+    use crate::db::TaskStatus;
+
+    assert_eq!(abandon_detail(TaskStatus::Running, "  superseded by #12 "), "from running: superseded by #12");
+    assert_eq!(abandon_detail(TaskStatus::Backlog, "not needed"), "from backlog: not needed");
 }
 
-/// Test generate_task_slug with empty title
+/// Test that a window tmux can't check gets no badge, while the worktree still does
 #[test]
-fn test_generate_task_slug_empty_title() {
-    let slug = generate_task_slug("12345678", "");
-    assert_eq!(slug, "12345678-");
+fn test_presence_badges() {
+    assert!(presence_badges(None).is_empty());
+    assert_eq!(
+        presence_badges(Some(ResourcePresence { window: Some(true), worktree: false })),
+        vec![("\u{25a3}", true), ("\u{2387}", false)]
+    );
+    assert_eq!(
+        presence_badges(Some(ResourcePresence { window: None, worktree: true })),
+        vec![("\u{2387}", true)]
+    );
+    assert_eq!(ResourcePresence { window: None, worktree: true }.missing(), None);
 }
 
 // =============================================================================
-// Tests for cleanup_task_for_done
+// Tests for review test runs
 // =============================================================================
 
-/// Test cleanup_task_for_done cleans up resources
+/// Test that the test command runs in the worktree and its exit status becomes the result
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_cleanup_task_for_done_with_resources() {
-    use crate::db::Task;
-
-    let mut mock_tmux = MockTmuxOperations::new();
-    let mut mock_git = MockGitOperations::new();
+fn test_run_review_tests_reports_pass_and_fail() {
+    use crate::hooks::MockHookRunner;
 
-    mock_tmux
-        .expect_kill_window()
-        .with(mockall::predicate::eq("project:task-window"))
-        .times(1)
-        .returning(|_| Ok(()));
-
-    mock_git
-        .expect_remove_worktree()
-        .with(
-            mockall::predicate::eq(Path::new("/project")),
-            mockall::predicate::eq("/tmp/worktree"),
-        )
+    let mut runner = MockHookRunner::new();
+    runner
+        .expect_run()
+        .withf(|command, dir| command == "cargo test" && dir == Path::new("/repo/wt"))
         .times(1)
         .returning(|_, _| Ok(()));
+    assert!(run_review_tests("cargo test", Path::new("/repo/wt"), &runner));
 
-    let mut task = Task::new("Test task", "claude", "project-1");
-    task.session_name = Some("project:task-window".to_string());
-    task.worktree_path = Some("/tmp/worktree".to_string());
-    task.status = TaskStatus::Review;
+    let mut runner = MockHookRunner::new();
+    runner
+        .expect_run()
+        .returning(|command, _| Err(command_failed(command, "test result: FAILED")));
+    assert!(!run_review_tests("cargo test", Path::new("/repo/wt"), &runner));
+}
 
-    cleanup_task_for_done(
-        &mut task,
-        Path::new("/project"),
-        &mock_tmux,
-        &mock_git,
-    );
+/// Test that the card shows the test state only for Review tasks
+#[test]
+fn test_review_test_badge() {
+    use crate::db::{Task, TaskStatus};
 
-    assert!(task.session_name.is_none());
-    assert!(task.worktree_path.is_none());
-    assert_eq!(task.status, TaskStatus::Done);
+    let mut task = Task::new("Task", "claude", "proj");
+    task.status = TaskStatus::Review;
+    assert_eq!(review_test_badge(&task, false), None);
+    assert_eq!(review_test_badge(&task, true), Some("🧪 testing"));
+    task.test_status = Some(true);
+    assert_eq!(review_test_badge(&task, false), Some("✅ tests"));
+    task.test_status = Some(false);
+    assert_eq!(review_test_badge(&task, false), Some("❌ tests"));
+
+    task.status = TaskStatus::Running;
+    assert_eq!(review_test_badge(&task, true), None);
 }
 
-/// Test cleanup_task_for_done handles missing resources gracefully
+// =============================================================================
+// Tests for the task detail view
+// =============================================================================
+
+/// Test that the detail view lists the fields that are set, in order
 #[test]
-#[cfg(feature = "test-mocks")]
-fn test_cleanup_task_for_done_no_resources() {
-    use crate::db::Task;
+fn test_task_detail_fields_only_set_values() {
+    use crate::db::{Task, TaskStatus};
 
-    let mock_tmux = MockTmuxOperations::new();
-    let mock_git = MockGitOperations::new();
-    // No expectations - functions should not be called
+    let task = Task::new("Task", "codex", "proj");
+    let labels: Vec<&str> = task_detail_fields(&task).into_iter().map(|(label, _)| label).collect();
+    assert_eq!(labels, vec!["Status", "Agent", "Created"]);
 
-    let mut task = Task::new("Test task", "claude", "project-1");
-    // No session_name or worktree_path set
+    let mut task = Task::new("Task", "claude", "proj");
+    task.status = TaskStatus::Review;
+    task.branch_name = Some("task/abc".to_string());
+    task.worktree_path = Some("/repo/.agtx/worktrees/abc".to_string());
+    task.pr_url = Some("https://github.com/o/r/pull/7".to_string());
+    task.tags = vec!["bug".to_string(), "ui".to_string()];
+    task.test_status = Some(false);
+    let fields = task_detail_fields(&task);
+    assert_eq!(fields[0], ("Status", "review".to_string()));
+    assert!(fields.contains(&("Tags", "#bug #ui".to_string())));
+    assert!(fields.contains(&("Tests", "failed".to_string())));
+    assert!(fields.contains(&("PR", "https://github.com/o/r/pull/7".to_string())));
+
+    task.usage = Some(crate::db::AgentUsage { tokens_in: 1_500, tokens_out: 200, cost_usd: None });
+    assert!(task_detail_fields(&task).contains(&("Usage", "1.5k in / 200 out".to_string())));
+}
 
-    cleanup_task_for_done(
-        &mut task,
-        Path::new("/project"),
-        &mock_tmux,
-        &mock_git,
-    );
+/// Test how history entries read in the detail view
+#[test]
+fn test_format_event() {
+    use crate::db::{TaskEvent, TaskEventKind};
 
-    assert_eq!(task.status, TaskStatus::Done);
+    let event = TaskEvent::new(TaskEventKind::WorktreeCreated, "/repo/.agtx/worktrees/x");
+    assert_eq!(format_event(&event), "worktree created  /repo/.agtx/worktrees/x");
+    assert_eq!(format_event(&TaskEvent::new(TaskEventKind::Trashed, "")), "trashed");
 }
 
 // =============================================================================
-// Tests for delete_task_resources
+// Tests for create_pr_with_content
 // =============================================================================
 
-/// Test delete_task_resources cleans up all resources
+/// Test successful PR creation with changes
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_delete_task_resources_full_cleanup() {
-    use crate::db::Task;
-
-    let mut mock_tmux = MockTmuxOperations::new();
+fn test_create_pr_with_content_success() {
     let mut mock_git = MockGitOperations::new();
+    let mut mock_git_provider = MockGitProviderOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
 
-    mock_tmux
-        .expect_kill_window()
-        .with(mockall::predicate::eq("project:task-window"))
+    let task = Task {
+        id: "test-123".to_string(),
+        title: "Test task".to_string(),
+        description: None,
+        status: TaskStatus::Running,
+        agent: "claude".to_string(),
+        project_id: "proj-1".to_string(),
+        session_name: Some("test-session".to_string()),
+        worktree_path: Some("/tmp/worktree".to_string()),
+        branch_name: Some("feature/test".to_string()),
+        pr_number: None,
+        pr_url: None,
+        plugin: None,
+        archived: false,
+        tags: Vec::new(),
+        depends_on: Vec::new(),
+        deleted_at: None,
+        agent_session_id: None,
+        env: Default::default(),
+        test_status: None,
+        notes: Vec::new(),
+        last_exit_code: None,
+        pinned: false,
+        usage: None,
+        subdir: None,
+        from_ref: None,
+        status_changed_at: None,
+        slug: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    // Expect: add all files
+    mock_git
+        .expect_add_all()
+        .withf(|path: &Path| path == Path::new("/tmp/worktree"))
         .times(1)
         .returning(|_| Ok(()));
 
+    // Expect: check for changes
     mock_git
-        .expect_remove_worktree()
+        .expect_has_changes()
+        .withf(|path: &Path| path == Path::new("/tmp/worktree"))
         .times(1)
-        .returning(|_, _| Ok(()));
+        .returning(|_| true);
 
+    // Expect: commit with co-author
     mock_git
-        .expect_delete_branch()
-        .with(
-            mockall::predicate::eq(Path::new("/project")),
-            mockall::predicate::eq("task/abc-feature"),
-        )
+        .expect_commit()
+        .withf(|path: &Path, msg: &str| {
+            path == Path::new("/tmp/worktree") && msg.contains("Test PR") && msg.contains("Co-Authored-By")
+        })
         .times(1)
         .returning(|_, _| Ok(()));
 
-    let mut task = Task::new("Feature task", "claude", "project-1");
-    task.session_name = Some("project:task-window".to_string());
-    task.worktree_path = Some("/tmp/worktree".to_string());
-    task.branch_name = Some("task/abc-feature".to_string());
+    // Expect: push with upstream
+    mock_git
+        .expect_push()
+        .withf(|path: &Path, branch: &str, set_upstream: &bool| {
+            path == Path::new("/tmp/worktree") && branch == "feature/test" && *set_upstream
+        })
+        .times(1)
+        .returning(|_, _, _| Ok(()));
 
-    delete_task_resources(
+    // Agent co-author string
+    mock_agent
+        .expect_co_author_string()
+        .return_const("Claude <claude@anthropic.com>".to_string());
+
+    // Expect: create PR
+    mock_git_provider
+        .expect_create_pr()
+        .withf(|path: &Path, title: &str, body: &str, branch: &str, base: &str| {
+            path == Path::new("/project") && title == "Test PR" && body == "Test body" && branch == "feature/test" && base == "develop"
+        })
+        .times(1)
+        .returning(|_, _, _, _, _| Ok((42, "https://github.com/org/repo/pull/42".to_string())));
+
+    let result = create_pr_with_content(
         &task,
         Path::new("/project"),
-        &mock_tmux,
+        "develop",
+        "Test PR",
+        "Test body",
         &mock_git,
+        &mock_git_provider,
+        &mock_agent,
     );
+
+    assert!(result.is_ok());
+    let (pr_number, pr_url) = result.unwrap();
+    assert_eq!(pr_number, 42);
+    assert_eq!(pr_url, "https://github.com/org/repo/pull/42");
 }
 
-/// Test delete_task_resources handles task without resources
+/// Test PR creation with no changes to commit
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_delete_task_resources_no_resources() {
-    use crate::db::Task;
+fn test_create_pr_with_content_no_changes() {
+    let mut mock_git = MockGitOperations::new();
+    let mut mock_git_provider = MockGitProviderOperations::new();
+    let mock_agent = MockAgentOperations::new();
 
-    let mock_tmux = MockTmuxOperations::new();
-    let mock_git = MockGitOperations::new();
-    // No expectations - nothing should be called
+    let task = Task {
+        id: "test-123".to_string(),
+        title: "Test task".to_string(),
+        description: None,
+        status: TaskStatus::Running,
+        agent: "claude".to_string(),
+        project_id: "proj-1".to_string(),
+        session_name: Some("test-session".to_string()),
+        worktree_path: Some("/tmp/worktree".to_string()),
+        branch_name: Some("feature/test".to_string()),
+        pr_number: None,
+        pr_url: None,
+        plugin: None,
+        archived: false,
+        tags: Vec::new(),
+        depends_on: Vec::new(),
+        deleted_at: None,
+        agent_session_id: None,
+        env: Default::default(),
+        test_status: None,
+        notes: Vec::new(),
+        last_exit_code: None,
+        pinned: false,
+        usage: None,
+        subdir: None,
+        from_ref: None,
+        status_changed_at: None,
+        slug: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
 
-    let task = Task::new("Simple task", "claude", "project-1");
-    // No session_name, worktree_path, or branch_name
+    mock_git
+        .expect_add_all()
+        .returning(|_| Ok(()));
 
-    delete_task_resources(
+    // No changes to commit
+    mock_git
+        .expect_has_changes()
+        .returning(|_| false);
+
+    // commit should NOT be called (no expectation set)
+
+    mock_git
+        .expect_push()
+        .returning(|_, _, _| Ok(()));
+
+    mock_git_provider
+        .expect_create_pr()
+        .returning(|_, _, _, _, _| Ok((1, "https://github.com/pr/1".to_string())));
+
+    let result = create_pr_with_content(
         &task,
         Path::new("/project"),
-        &mock_tmux,
+        "develop",
+        "PR Title",
+        "PR Body",
         &mock_git,
+        &mock_git_provider,
+        &mock_agent,
     );
-}
 
-// =============================================================================
-// Tests for collect_task_diff
-// =============================================================================
+    assert!(result.is_ok());
+}
 
-/// Test collect_task_diff with all types of changes
+/// Test PR creation failure on push
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_collect_task_diff_all_changes() {
+fn test_create_pr_with_content_push_failure() {
     let mut mock_git = MockGitOperations::new();
+    let mock_git_provider = MockGitProviderOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
 
-    mock_git
-        .expect_diff()
-        .returning(|_| "diff --git a/file.rs\n-old\n+new".to_string());
-
-    mock_git
-        .expect_diff_cached()
-        .returning(|_| "diff --git a/staged.rs\n+added".to_string());
+    let task = Task {
+        id: "test-123".to_string(),
+        title: "Test task".to_string(),
+        description: None,
+        status: TaskStatus::Running,
+        agent: "claude".to_string(),
+        project_id: "proj-1".to_string(),
+        session_name: None,
+        worktree_path: Some("/tmp/worktree".to_string()),
+        branch_name: Some("feature/test".to_string()),
+        pr_number: None,
+        pr_url: None,
+        plugin: None,
+        archived: false,
+        tags: Vec::new(),
+        depends_on: Vec::new(),
+        deleted_at: None,
+        agent_session_id: None,
+        env: Default::default(),
+        test_status: None,
+        notes: Vec::new(),
+        last_exit_code: None,
+        pinned: false,
+        usage: None,
+        subdir: None,
+        from_ref: None,
+        status_changed_at: None,
+        slug: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
 
-    mock_git
-        .expect_list_untracked_files()
-        .returning(|_| "new_file.rs\n".to_string());
+    mock_git.expect_add_all().returning(|_| Ok(()));
+    mock_git.expect_has_changes().returning(|_| true);
+    mock_git.expect_commit().returning(|_, _| Ok(()));
+    mock_agent
+        .expect_co_author_string()
+        .return_const("Claude <claude@anthropic.com>".to_string());
 
+    // Push fails
     mock_git
-        .expect_diff_untracked_file()
-        .returning(|_, _| "+++ new_file.rs\n+content".to_string());
+        .expect_push()
+        .returning(|_, _, _| Err(command_failed("git push", "Permission denied")));
 
-    let result = collect_task_diff("/tmp/worktree", &mock_git, &[]);
+    let result = create_pr_with_content(
+        &task,
+        Path::new("/project"),
+        "develop",
+        "PR",
+        "Body",
+        &mock_git,
+        &mock_git_provider,
+        &mock_agent,
+    );
 
-    assert!(result.contains("Unstaged Changes"));
-    assert!(result.contains("Staged Changes"));
-    assert!(result.contains("Untracked Files"));
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("Permission denied"));
 }
 
-/// Test collect_task_diff with no changes
+// =============================================================================
+// Tests for push_changes_to_existing_pr
+// =============================================================================
+
+/// Test pushing changes to existing PR
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_collect_task_diff_no_changes() {
+fn test_push_changes_to_existing_pr_success() {
     let mut mock_git = MockGitOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
 
-    mock_git.expect_diff().returning(|_| String::new());
-    mock_git.expect_diff_cached().returning(|_| String::new());
-    mock_git.expect_list_untracked_files().returning(|_| String::new());
-
-    let result = collect_task_diff("/tmp/worktree", &mock_git, &[]);
+    let task = Task {
+        id: "test-456".to_string(),
+        title: "Existing PR task".to_string(),
+        description: None,
+        status: TaskStatus::Review,
+        agent: "claude".to_string(),
+        project_id: "proj-1".to_string(),
+        session_name: Some("test-session".to_string()),
+        worktree_path: Some("/tmp/worktree".to_string()),
+        branch_name: Some("feature/existing".to_string()),
+        pr_number: Some(99),
+        pr_url: Some("https://github.com/org/repo/pull/99".to_string()),
+        plugin: None,
+        archived: false,
+        tags: Vec::new(),
+        depends_on: Vec::new(),
+        deleted_at: None,
+        agent_session_id: None,
+        env: Default::default(),
+        test_status: None,
+        notes: Vec::new(),
+        last_exit_code: None,
+        pinned: false,
+        usage: None,
+        subdir: None,
+        from_ref: None,
+        status_changed_at: None,
+        slug: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
 
-    assert!(result.contains("(no changes)"));
-    assert!(result.contains("/tmp/worktree"));
-}
+    mock_git.expect_add_all().returning(|_| Ok(()));
+    mock_git.expect_has_changes().returning(|_| true);
 
-/// Test collect_task_diff with only unstaged changes
-#[test]
-#[cfg(feature = "test-mocks")]
-fn test_collect_task_diff_only_unstaged() {
-    let mut mock_git = MockGitOperations::new();
+    // Commit message should include "Address review comments"
+    mock_git
+        .expect_commit()
+        .withf(|_: &Path, msg: &str| msg.contains("Address review comments"))
+        .returning(|_, _| Ok(()));
 
+    // Push without setting upstream (false)
     mock_git
-        .expect_diff()
-        .returning(|_| "diff --git a/modified.rs".to_string());
+        .expect_push()
+        .withf(|_: &Path, branch: &str, set_upstream: &bool| {
+            branch == "feature/existing" && !*set_upstream
+        })
+        .returning(|_, _, _| Ok(()));
 
-    mock_git.expect_diff_cached().returning(|_| String::new());
-    mock_git.expect_list_untracked_files().returning(|_| String::new());
+    mock_agent
+        .expect_co_author_string()
+        .return_const("Claude <claude@anthropic.com>".to_string());
 
-    let result = collect_task_diff("/tmp/worktree", &mock_git, &[]);
+    let result = push_changes_to_existing_pr(&task, &mock_git, &mock_agent);
 
-    assert!(result.contains("Unstaged Changes"));
-    assert!(!result.contains("Staged Changes"));
-    assert!(!result.contains("Untracked Files"));
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "https://github.com/org/repo/pull/99");
 }
 
-// =============================================================================
-// Tests for build_highlighted_text
-// =============================================================================
+/// Test pushing when no changes exist
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_push_changes_to_existing_pr_no_changes() {
+    let mut mock_git = MockGitOperations::new();
+    let mock_agent = MockAgentOperations::new();
+
+    let task = Task {
+        id: "test-789".to_string(),
+        title: "Task with no changes".to_string(),
+        description: None,
+        status: TaskStatus::Review,
+        agent: "claude".to_string(),
+        project_id: "proj-1".to_string(),
+        session_name: None,
+        worktree_path: Some("/tmp/worktree".to_string()),
+        branch_name: Some("feature/no-changes".to_string()),
+        pr_number: Some(50),
+        pr_url: Some("https://github.com/org/repo/pull/50".to_string()),
+        plugin: None,
+        archived: false,
+        tags: Vec::new(),
+        depends_on: Vec::new(),
+        deleted_at: None,
+        agent_session_id: None,
+        env: Default::default(),
+        test_status: None,
+        notes: Vec::new(),
+        last_exit_code: None,
+        pinned: false,
+        usage: None,
+        subdir: None,
+        from_ref: None,
+        status_changed_at: None,
+        slug: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    mock_git.expect_add_all().returning(|_| Ok(()));
+    mock_git.expect_has_changes().returning(|_| false);
+    // No commit expected
+    mock_git.expect_push().returning(|_, _, _| Ok(()));
+
+    let result = push_changes_to_existing_pr(&task, &mock_git, &mock_agent);
+
+    assert!(result.is_ok());
+}
+
+/// Test push with no existing PR URL
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_push_changes_to_existing_pr_no_url() {
+    let mut mock_git = MockGitOperations::new();
+    let mock_agent = MockAgentOperations::new();
+
+    let task = Task {
+        id: "test-abc".to_string(),
+        title: "Task without PR URL".to_string(),
+        description: None,
+        status: TaskStatus::Review,
+        agent: "claude".to_string(),
+        project_id: "proj-1".to_string(),
+        session_name: None,
+        worktree_path: Some("/tmp/worktree".to_string()),
+        branch_name: Some("feature/branch".to_string()),
+        pr_number: None,
+        pr_url: None, // No PR URL
+        plugin: None,
+        archived: false,
+        tags: Vec::new(),
+        depends_on: Vec::new(),
+        deleted_at: None,
+        agent_session_id: None,
+        env: Default::default(),
+        test_status: None,
+        notes: Vec::new(),
+        last_exit_code: None,
+        pinned: false,
+        usage: None,
+        subdir: None,
+        from_ref: None,
+        status_changed_at: None,
+        slug: None,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+
+    mock_git.expect_add_all().returning(|_| Ok(()));
+    mock_git.expect_has_changes().returning(|_| false);
+    mock_git.expect_push().returning(|_, _, _| Ok(()));
+
+    let result = push_changes_to_existing_pr(&task, &mock_git, &mock_agent);
+
+    assert!(result.is_ok());
+    assert_eq!(result.unwrap(), "Changes pushed to existing PR");
+}
+
+// =============================================================================
+// Tests for fuzzy_find_files
+// =============================================================================
+
+/// Test fuzzy file search with matching pattern
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_fuzzy_find_files_basic() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_list_files()
+        .returning(|_| vec![
+            "src/main.rs".to_string(),
+            "src/lib.rs".to_string(),
+            "src/tui/app.rs".to_string(),
+            "src/tui/board.rs".to_string(),
+            "Cargo.toml".to_string(),
+        ]);
+
+    let results = fuzzy_find_files(Path::new("/project"), "app", 10, &mock_git);
+
+    assert!(!results.is_empty());
+    assert!(results.contains(&"src/tui/app.rs".to_string()));
+}
+
+/// Test fuzzy file search with empty pattern returns first N files
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_fuzzy_find_files_empty_pattern() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_list_files()
+        .returning(|_| vec![
+            "a.rs".to_string(),
+            "b.rs".to_string(),
+            "c.rs".to_string(),
+            "d.rs".to_string(),
+            "e.rs".to_string(),
+        ]);
+
+    let results = fuzzy_find_files(Path::new("/project"), "", 3, &mock_git);
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0], "a.rs");
+    assert_eq!(results[1], "b.rs");
+    assert_eq!(results[2], "c.rs");
+}
+
+/// Test fuzzy file search with no matches
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_fuzzy_find_files_no_matches() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_list_files()
+        .returning(|_| vec!["main.rs".to_string(), "lib.rs".to_string()]);
+
+    let results = fuzzy_find_files(Path::new("/project"), "xyz123", 10, &mock_git);
+
+    assert!(results.is_empty());
+}
+
+/// Test fuzzy file search with empty file list
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_fuzzy_find_files_empty_list() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git.expect_list_files().returning(|_| vec![]);
+
+    let results = fuzzy_find_files(Path::new("/project"), "app", 10, &mock_git);
+
+    assert!(results.is_empty());
+}
+
+/// Test fuzzy file search respects max_results
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_fuzzy_find_files_max_results() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_list_files()
+        .returning(|_| vec![
+            "src/app1.rs".to_string(),
+            "src/app2.rs".to_string(),
+            "src/app3.rs".to_string(),
+            "src/app4.rs".to_string(),
+            "src/app5.rs".to_string(),
+        ]);
+
+    let results = fuzzy_find_files(Path::new("/project"), "app", 2, &mock_git);
+
+    assert_eq!(results.len(), 2);
+}
+
+// =============================================================================
+// Tests for fuzzy_score
+// =============================================================================
+
+/// Test fuzzy score with exact match
+#[test]
+fn test_fuzzy_score_exact_match() {
+    let score = fuzzy_score("main.rs", "main.rs");
+    assert!(score > 0);
+}
+
+/// Test fuzzy score with partial match
+#[test]
+fn test_fuzzy_score_partial_match() {
+    let score = fuzzy_score("src/main.rs", "main");
+    assert!(score > 0);
+}
+
+/// Test fuzzy score with no match
+#[test]
+fn test_fuzzy_score_no_match() {
+    let score = fuzzy_score("main.rs", "xyz");
+    assert_eq!(score, 0);
+}
+
+/// Test fuzzy score with empty needle
+#[test]
+fn test_fuzzy_score_empty_needle() {
+    let score = fuzzy_score("main.rs", "");
+    assert_eq!(score, 1);
+}
+
+/// Test fuzzy score bonus for word start
+#[test]
+fn test_fuzzy_score_word_boundary_bonus() {
+    // "app" at start of segment should score higher than in middle
+    let score_start = fuzzy_score("src/app.rs", "app");
+    let score_middle = fuzzy_score("src/myapp.rs", "app");
+    assert!(score_start > score_middle);
+}
+
+/// Test fuzzy score bonus for consecutive matches
+#[test]
+fn test_fuzzy_score_consecutive_bonus() {
+    // Consecutive "main" should score higher than scattered chars within a word
+    let score_consecutive = fuzzy_score("main.rs", "main");
+    let score_scattered = fuzzy_score("myaweirdin.rs", "main");
+    assert!(score_consecutive > score_scattered);
+}
+
+// =============================================================================
+// Tests for send_key_to_tmux
+// =============================================================================
+
+/// Test sending character key to tmux
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_send_key_to_tmux_char() {
+    let mut mock_tmux = MockTmuxOperations::new();
+
+    mock_tmux
+        .expect_send_keys_literal()
+        .with(
+            mockall::predicate::eq("test-window"),
+            mockall::predicate::eq("a"),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    send_key_to_tmux("test-window", KeyCode::Char('a'), &mock_tmux);
+}
+
+/// Test sending Enter key to tmux
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_send_key_to_tmux_enter() {
+    let mut mock_tmux = MockTmuxOperations::new();
+
+    mock_tmux
+        .expect_send_keys_literal()
+        .with(
+            mockall::predicate::eq("test-window"),
+            mockall::predicate::eq("Enter"),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    send_key_to_tmux("test-window", KeyCode::Enter, &mock_tmux);
+}
+
+/// Test sending special keys to tmux
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_send_key_to_tmux_special_keys() {
+    let mut mock_tmux = MockTmuxOperations::new();
+
+    // Test Escape
+    mock_tmux
+        .expect_send_keys_literal()
+        .with(mockall::predicate::eq("win"), mockall::predicate::eq("Escape"))
+        .returning(|_, _| Ok(()));
+
+    send_key_to_tmux("win", KeyCode::Esc, &mock_tmux);
+
+    // Test Backspace
+    let mut mock_tmux2 = MockTmuxOperations::new();
+    mock_tmux2
+        .expect_send_keys_literal()
+        .with(mockall::predicate::eq("win"), mockall::predicate::eq("BSpace"))
+        .returning(|_, _| Ok(()));
+
+    send_key_to_tmux("win", KeyCode::Backspace, &mock_tmux2);
+}
+
+/// Test sending function key to tmux
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_send_key_to_tmux_function_key() {
+    let mut mock_tmux = MockTmuxOperations::new();
+
+    mock_tmux
+        .expect_send_keys_literal()
+        .with(mockall::predicate::eq("win"), mockall::predicate::eq("F5"))
+        .returning(|_, _| Ok(()));
+
+    send_key_to_tmux("win", KeyCode::F(5), &mock_tmux);
+}
+
+// =============================================================================
+// Tests for capture_tmux_pane_with_history
+// =============================================================================
+
+/// Test capturing tmux pane content
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_capture_tmux_pane_with_history() {
+    let mut mock_tmux = MockTmuxOperations::new();
+
+    mock_tmux
+        .expect_capture_pane_with_history()
+        .with(mockall::predicate::eq("test-window"), mockall::predicate::eq(500))
+        .returning(|_, _| b"Line 1\nLine 2\nLine 3\n".to_vec());
+
+    mock_tmux
+        .expect_get_cursor_info()
+        .with(mockall::predicate::eq("test-window"))
+        .returning(|_| Some((2, 3))); // cursor at line 2, pane has 3 lines
+
+    let content = capture_tmux_pane_with_history("test-window", 500, &mock_tmux);
+
+    // Content should be trimmed to cursor position
+    assert!(!content.is_empty());
+}
+
+// =============================================================================
+// Tests for centered_rect helpers (pure functions, no mocks needed)
+// =============================================================================
+
+/// Test centered_rect creates correct dimensions
+#[test]
+fn test_centered_rect() {
+    let area = Rect::new(0, 0, 100, 50);
+    let popup = centered_rect(50, 50, area);
+
+    // Should be centered horizontally and vertically
+    assert!(popup.x > 0);
+    assert!(popup.y > 0);
+    assert!(popup.width < 100);
+    assert!(popup.height < 50);
+}
+
+/// Test centered_rect_fixed_width creates correct dimensions
+#[test]
+fn test_centered_rect_fixed_width() {
+    let area = Rect::new(0, 0, 100, 50);
+    let popup = centered_rect_fixed_width(40, 50, area);
+
+    // Width should be fixed at 40
+    assert_eq!(popup.width, 40);
+    // Should be centered
+    assert_eq!(popup.x, 30); // (100 - 40) / 2
+}
+
+/// Test centered_rect_fixed_width caps width to terminal size
+#[test]
+fn test_centered_rect_fixed_width_capped() {
+    let area = Rect::new(0, 0, 30, 50); // Small terminal
+    let popup = centered_rect_fixed_width(100, 50, area); // Request large width
+
+    // Width should be capped
+    assert!(popup.width <= 30);
+}
+
+// =============================================================================
+// Tests for hex_to_color
+// =============================================================================
+
+/// Test hex_to_color with valid hex
+#[test]
+fn test_hex_to_color_valid() {
+    let color = hex_to_color("#FF0000");
+    assert_eq!(color, Color::Rgb(255, 0, 0));
+}
+
+/// Test hex_to_color with invalid hex falls back to white
+#[test]
+fn test_hex_to_color_invalid() {
+    let color = hex_to_color("invalid");
+    assert_eq!(color, Color::White);
+}
+
+// =============================================================================
+// Tests for cleanup_task_for_done
+// =============================================================================
+
+/// Test cleanup_task_for_done cleans up resources
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_with_resources() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_is_worktree_dirty()
+        .with(mockall::predicate::eq(Path::new("/tmp/worktree")))
+        .times(1)
+        .returning(|_| Ok(false));
+
+    mock_tmux
+        .expect_kill_window()
+        .with(mockall::predicate::eq("project:task-window"))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    mock_git
+        .expect_remove_worktree()
+        .with(
+            mockall::predicate::eq(Path::new("/project")),
+            mockall::predicate::eq("/tmp/worktree"),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.status = TaskStatus::Review;
+
+    cleanup_task_for_done(
+        &mut task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Abort,
+        CleanupAction::Done.default_policy(),
+    )
+    .unwrap();
+
+    assert!(task.session_name.is_none());
+    assert!(task.worktree_path.is_none());
+    assert_eq!(task.status, TaskStatus::Done);
+}
+
+/// Test cleanup_task_for_done handles missing resources gracefully
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_no_resources() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mock_git = MockGitOperations::new();
+    // No expectations - functions should not be called
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    // No session_name or worktree_path set
+
+    cleanup_task_for_done(
+        &mut task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Abort,
+        CleanupAction::Done.default_policy(),
+    )
+    .unwrap();
+
+    assert_eq!(task.status, TaskStatus::Done);
+}
+
+// =============================================================================
+// Tests for auto_commit_for_review
+// =============================================================================
+
+/// Test that the worktree is committed with a message built from the title and the staged diff
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_auto_commit_for_review_commits_worktree() {
+    use crate::db::Task;
+    use crate::git::CommitId;
+
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_add_all().times(1).returning(|_| Ok(()));
+    mock_git
+        .expect_diff_stat_cached()
+        .returning(|_| " src/login.rs | 40 ++++\n 1 file changed, 40 insertions(+)\n".to_string());
+    mock_git
+        .expect_commit_all()
+        .withf(|path: &Path, msg: &str| {
+            path == Path::new("/tmp/worktree")
+                && msg == "Add login page\n\nsrc/login.rs | 40 ++++\n1 file changed, 40 insertions(+)"
+        })
+        .times(1)
+        .returning(|_, _| Ok(Some(CommitId("abc123def456".to_string()))));
+
+    let mut task = Task::new("Add login page", "claude", "project-1");
+    task.worktree_path = Some("/tmp/worktree".to_string());
+
+    let commit = auto_commit_for_review(&task, &mock_git).unwrap();
+    assert_eq!(commit.unwrap().short(), "abc123de");
+}
+
+/// Test that a task without a worktree never touches git
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_auto_commit_for_review_no_worktree() {
+    use crate::db::Task;
+
+    let mock_git = MockGitOperations::new();
+    let task = Task::new("No worktree", "claude", "project-1");
+
+    assert!(auto_commit_for_review(&task, &mock_git).unwrap().is_none());
+}
+
+#[test]
+fn test_generate_commit_message_empty_diff_is_title() {
+    let task = crate::db::Task::new("Fix the parser", "claude", "project-1");
+    assert_eq!(generate_commit_message(&task, ""), "Fix the parser");
+    assert_eq!(generate_commit_message(&task, "  \n"), "Fix the parser");
+}
+
+#[test]
+fn test_generate_commit_message_lists_files_and_totals() {
+    let task = crate::db::Task::new("Fix the parser", "claude", "project-1");
+    let stat = " src/parser.rs | 12 +++++++-----\n tests/parser.rs |  4 ++++\n 2 files changed, 11 insertions(+), 5 deletions(-)\n";
+    assert_eq!(
+        generate_commit_message(&task, stat),
+        "Fix the parser\n\nsrc/parser.rs | 12 +++++++-----\ntests/parser.rs |  4 ++++\n2 files changed, 11 insertions(+), 5 deletions(-)"
+    );
+}
+
+#[test]
+fn test_generate_commit_message_truncates_long_file_lists() {
+    let task = crate::db::Task::new("Rename everything", "claude", "project-1");
+    let mut stat: String = (0..30).map(|i| format!(" src/file{}.rs | 2 +-\n", i)).collect();
+    stat.push_str(" 30 files changed, 30 insertions(+), 30 deletions(-)\n");
+
+    let message = generate_commit_message(&task, &stat);
+    let lines: Vec<&str> = message.lines().collect();
+    assert_eq!(lines[0], "Rename everything");
+    assert_eq!(lines.len(), 2 + COMMIT_MESSAGE_MAX_FILES + 2);
+    assert!(message.contains("src/file19.rs"));
+    assert!(!message.contains("src/file20.rs"));
+    assert!(message.contains("... and 10 more file(s)"));
+    assert!(message.ends_with("30 files changed, 30 insertions(+), 30 deletions(-)"));
+}
+
+// =============================================================================
+// Tests for needs_destroy_confirmation / describe_destroyed_resources
+// =============================================================================
+
+#[test]
+fn test_needs_destroy_confirmation_with_resources() {
+    use crate::db::Task;
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    assert!(needs_destroy_confirmation(&task, true, CleanupAction::Done.default_policy()));
+
+    task.session_name = None;
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    assert!(needs_destroy_confirmation(&task, true, CleanupAction::Done.default_policy()));
+}
+
+#[test]
+fn test_needs_destroy_confirmation_nothing_to_destroy() {
+    use crate::db::Task;
+
+    let task = Task::new("Test task", "claude", "project-1");
+    assert!(!needs_destroy_confirmation(&task, true, CleanupAction::Done.default_policy()));
+}
+
+#[test]
+fn test_needs_destroy_confirmation_disabled() {
+    use crate::db::Task;
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    assert!(!needs_destroy_confirmation(&task, false, CleanupAction::Done.default_policy()));
+}
+
+#[test]
+fn test_destroy_confirmation_follows_cleanup_policy() {
+    use crate::db::Task;
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.branch_name = Some("task/abc-test".to_string());
+
+    let keep_worktree = CleanupPolicy { remove_worktree: false, ..CleanupAction::Done.default_policy() };
+    assert!(!needs_destroy_confirmation(&task, true, keep_worktree));
+    assert!(describe_destroyed_resources(&task, keep_worktree).contains("No worktree"));
+
+    let text = describe_destroyed_resources(&task, CleanupAction::Delete.default_policy());
+    assert!(text.contains("/tmp/worktree"));
+    assert!(text.contains("Branch task/abc-test will be deleted."));
+}
+
+#[test]
+fn test_describe_destroyed_resources_names_targets() {
+    use crate::db::Task;
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+
+    let text = describe_destroyed_resources(&task, CleanupAction::Done.default_policy());
+    assert!(text.contains("project:task-window"));
+    assert!(text.contains("/tmp/worktree"));
+
+    let empty = Task::new("Empty", "claude", "project-1");
+    assert!(describe_destroyed_resources(&empty, CleanupAction::Done.default_policy()).contains("No worktree"));
+}
+
+/// Test that a dirty worktree aborts Done cleanup without touching anything
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_dirty_abort() {
+    use crate::db::Task;
+
+    // No kill_window / remove_worktree expectations - they must not be called
+    let mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_is_worktree_dirty()
+        .times(1)
+        .returning(|_| Ok(true));
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.status = TaskStatus::Review;
+
+    let result = cleanup_task_for_done(
+        &mut task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Abort,
+        CleanupAction::Done.default_policy(),
+    );
+
+    assert!(result.unwrap_err().to_string().contains("uncommitted changes"));
+    assert_eq!(task.status, TaskStatus::Review);
+    assert!(task.worktree_path.is_some());
+    assert!(task.session_name.is_some());
+}
+
+/// Test that a dirty worktree is stashed under a named entry before removal
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_dirty_stash() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    let mut seq = mockall::Sequence::new();
+
+    mock_git
+        .expect_is_worktree_dirty()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_| Ok(true));
+    mock_git
+        .expect_stash_worktree()
+        .withf(|path: &Path, name: &str| path == Path::new("/tmp/worktree") && name.contains("Stash me"))
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| Ok(()));
+    mock_git
+        .expect_remove_worktree()
+        .times(1)
+        .in_sequence(&mut seq)
+        .returning(|_, _| Ok(()));
+    mock_tmux
+        .expect_kill_window()
+        .times(1)
+        .returning(|_| Ok(()));
+
+    let mut task = Task::new("Stash me", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+
+    cleanup_task_for_done(
+        &mut task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Stash,
+        CleanupAction::Done.default_policy(),
+    )
+    .unwrap();
+
+    assert_eq!(task.status, TaskStatus::Done);
+}
+
+/// Test that the force policy skips the dirty check entirely
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_force_skips_dirty_check() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    // No is_worktree_dirty expectation - calling it would panic
+    mock_git
+        .expect_remove_worktree()
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.worktree_path = Some("/tmp/worktree".to_string());
+
+    cleanup_task_for_done(
+        &mut task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Force,
+        CleanupAction::Done.default_policy(),
+    )
+    .unwrap();
+
+    assert!(task.worktree_path.is_none());
+}
+
+/// Test that a failed status check is treated as a refusal, not as clean
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_dirty_check_error() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    mock_git
+        .expect_is_worktree_dirty()
+        .returning(|_| Err(command_failed("git status", "index.lock exists")));
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.worktree_path = Some("/tmp/worktree".to_string());
+
+    let result = cleanup_task_for_done(
+        &mut task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Stash,
+        CleanupAction::Done.default_policy(),
+    );
+
+    assert!(result.is_err());
+    assert!(task.worktree_path.is_some());
+}
+
+// =============================================================================
+// Tests for delete_task_resources
+// =============================================================================
+
+/// Test delete_task_resources cleans up all resources
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_delete_task_resources_full_cleanup() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_is_worktree_dirty()
+        .times(1)
+        .returning(|_| Ok(false));
+
+    mock_tmux
+        .expect_kill_window()
+        .with(mockall::predicate::eq("project:task-window"))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    // The worktree's own path, not its slug (which git would resolve against the project root)
+    mock_git
+        .expect_remove_worktree()
+        .with(mockall::predicate::eq(Path::new("/project")), mockall::predicate::eq("/tmp/worktree"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    mock_git
+        .expect_delete_branch()
+        .with(
+            mockall::predicate::eq(Path::new("/project")),
+            mockall::predicate::eq("task/abc-feature"),
+        )
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let mut task = Task::new("Feature task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.branch_name = Some("task/abc-feature".to_string());
+
+    let released = delete_task_resources(
+        &task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Abort,
+        CleanupAction::Delete.default_policy(),
+    )
+    .unwrap();
+    assert!(matches!(released.window, Some(Ok(()))));
+    assert!(matches!(released.worktree, Some(Ok(()))));
+}
+
+/// A worktree git fails to remove is reported, and its branch is left alone
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_delete_task_resources_reports_failed_removal() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_worktree_dirty().returning(|_| Ok(false));
+    mock_git
+        .expect_remove_worktree()
+        .returning(|_, _| Err(command_failed("git worktree", "fatal: '/tmp/worktree' is not a working tree")));
+    mock_git.expect_delete_branch().never();
+
+    let mut task = Task::new("Feature task", "claude", "project-1");
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.branch_name = Some("task/abc-feature".to_string());
+
+    let released = delete_task_resources(
+        &task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Abort,
+        CleanupAction::Delete.default_policy(),
+    )
+    .unwrap();
+    assert!(released.window.is_none());
+    assert!(released.worktree.unwrap().unwrap_err().to_string().contains("not a working tree"));
+}
+
+/// Test delete_task_resources handles task without resources
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_delete_task_resources_no_resources() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mock_git = MockGitOperations::new();
+    // No expectations - nothing should be called
+
+    let task = Task::new("Simple task", "claude", "project-1");
+    // No session_name, worktree_path, or branch_name
+
+    delete_task_resources(
+        &task,
+        Path::new("/project"),
+        &mock_tmux,
+        &mock_git,
+        DirtyWorktreePolicy::Abort,
+        CleanupAction::Delete.default_policy(),
+    )
+    .unwrap();
+}
+
+/// `[cleanup.done] worktree = false`: the window goes, the worktree and branch stay
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_keeps_worktree() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    mock_tmux.expect_kill_window().times(1).returning(|_| Ok(()));
+    mock_git.expect_is_worktree_dirty().never();
+    mock_git.expect_remove_worktree().never();
+    mock_git.expect_delete_branch().never();
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.branch_name = Some("task/abc-test".to_string());
+
+    let policy = CleanupPolicy { remove_worktree: false, ..CleanupAction::Done.default_policy() };
+    cleanup_task_for_done(&mut task, Path::new("/project"), &mock_tmux, &mock_git, DirtyWorktreePolicy::Abort, policy)
+        .unwrap();
+
+    assert!(task.session_name.is_none());
+    assert_eq!(task.worktree_path.as_deref(), Some("/tmp/worktree"));
+    assert_eq!(task.branch_name.as_deref(), Some("task/abc-test"));
+    assert_eq!(task.status, TaskStatus::Done);
+}
+
+/// `[cleanup.done] branch = true` deletes the branch with the worktree
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_cleanup_task_for_done_deletes_branch() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    mock_git.expect_is_worktree_dirty().returning(|_| Ok(false));
+    mock_git.expect_remove_worktree().times(1).returning(|_, _| Ok(()));
+    mock_git
+        .expect_delete_branch()
+        .with(mockall::predicate::eq(Path::new("/project")), mockall::predicate::eq("task/abc-test"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.branch_name = Some("task/abc-test".to_string());
+
+    let policy = CleanupAction::Delete.default_policy();
+    cleanup_task_for_done(&mut task, Path::new("/project"), &mock_tmux, &mock_git, DirtyWorktreePolicy::Abort, policy)
+        .unwrap();
+
+    assert!(task.worktree_path.is_none());
+    assert!(task.branch_name.is_none());
+}
+
+/// `[cleanup.delete] branch = false` removes the worktree but keeps the branch
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_delete_task_resources_keeps_branch() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    mock_tmux.expect_kill_window().times(1).returning(|_| Ok(()));
+    mock_git.expect_is_worktree_dirty().returning(|_| Ok(false));
+    mock_git.expect_remove_worktree().times(1).returning(|_, _| Ok(()));
+    mock_git.expect_delete_branch().never();
+
+    let mut task = Task::new("Feature task", "claude", "project-1");
+    task.session_name = Some("project:task-window".to_string());
+    task.worktree_path = Some("/tmp/worktree".to_string());
+    task.branch_name = Some("task/abc-feature".to_string());
+
+    let policy = CleanupPolicy { delete_branch: false, ..CleanupAction::Delete.default_policy() };
+    delete_task_resources(&task, Path::new("/project"), &mock_tmux, &mock_git, DirtyWorktreePolicy::Abort, policy)
+        .unwrap();
+}
+
+// =============================================================================
+// Tests for collect_task_diff
+// =============================================================================
+
+/// Test collect_task_diff with all types of changes
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_collect_task_diff_all_changes() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_diff()
+        .returning(|_| "diff --git a/file.rs\n-old\n+new".to_string());
+
+    mock_git
+        .expect_diff_cached()
+        .returning(|_| "diff --git a/staged.rs\n+added".to_string());
+
+    mock_git
+        .expect_list_untracked_files()
+        .returning(|_| "new_file.rs\n".to_string());
+
+    mock_git
+        .expect_diff_untracked_file()
+        .returning(|_, _| "+++ new_file.rs\n+content".to_string());
+
+    let result = collect_task_diff("/tmp/worktree", &mock_git, &[]);
+
+    assert!(result.contains("Unstaged Changes"));
+    assert!(result.contains("Staged Changes"));
+    assert!(result.contains("Untracked Files"));
+}
+
+/// Test collect_task_diff with no changes
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_collect_task_diff_no_changes() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git.expect_diff().returning(|_| String::new());
+    mock_git.expect_diff_cached().returning(|_| String::new());
+    mock_git.expect_list_untracked_files().returning(|_| String::new());
+
+    let result = collect_task_diff("/tmp/worktree", &mock_git, &[]);
+
+    assert!(result.contains("(no changes)"));
+    assert!(result.contains("/tmp/worktree"));
+}
+
+/// Test collect_task_diff with only unstaged changes
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_collect_task_diff_only_unstaged() {
+    let mut mock_git = MockGitOperations::new();
+
+    mock_git
+        .expect_diff()
+        .returning(|_| "diff --git a/modified.rs".to_string());
+
+    mock_git.expect_diff_cached().returning(|_| String::new());
+    mock_git.expect_list_untracked_files().returning(|_| String::new());
+
+    let result = collect_task_diff("/tmp/worktree", &mock_git, &[]);
+
+    assert!(result.contains("Unstaged Changes"));
+    assert!(!result.contains("Staged Changes"));
+    assert!(!result.contains("Untracked Files"));
+}
+
+// =============================================================================
+// Tests for build_highlighted_text
+// =============================================================================
+
+/// Test build_highlighted_text with no file paths produces plain text
+#[test]
+fn test_build_highlighted_text_no_paths() {
+    let paths = HashSet::new();
+    let text = build_highlighted_text("hello world", &paths, Color::White, Color::Cyan);
+    let lines: Vec<&Line> = text.lines.iter().collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].spans.len(), 1);
+    assert_eq!(lines[0].spans[0].content, "hello world");
+}
+
+/// Test build_highlighted_text highlights a single file path
+#[test]
+fn test_build_highlighted_text_single_path() {
+    let mut paths = HashSet::new();
+    paths.insert("src/main.rs".to_string());
+    let text = build_highlighted_text(
+        "Please edit src/main.rs for me",
+        &paths,
+        Color::White,
+        Color::Cyan,
+    );
+    let lines: Vec<&Line> = text.lines.iter().collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].spans.len(), 3);
+    assert_eq!(lines[0].spans[0].content, "Please edit ");
+    assert_eq!(lines[0].spans[1].content, "src/main.rs");
+    assert_eq!(lines[0].spans[2].content, " for me");
+    // The highlighted span should be bold
+    assert!(lines[0].spans[1].style.add_modifier.contains(Modifier::BOLD));
+}
+
+/// Test build_highlighted_text with multiple file paths on one line
+#[test]
+fn test_build_highlighted_text_multiple_paths() {
+    let mut paths = HashSet::new();
+    paths.insert("a.rs".to_string());
+    paths.insert("b.rs".to_string());
+    let text = build_highlighted_text("fix a.rs and b.rs", &paths, Color::White, Color::Cyan);
+    let lines: Vec<&Line> = text.lines.iter().collect();
+    assert_eq!(lines.len(), 1);
+    // Should be: "fix " | "a.rs" | " and " | "b.rs"
+    assert_eq!(lines[0].spans.len(), 4);
+    assert_eq!(lines[0].spans[1].content, "a.rs");
+    assert_eq!(lines[0].spans[3].content, "b.rs");
+}
+
+/// Test build_highlighted_text with multiline input
+#[test]
+fn test_build_highlighted_text_multiline() {
+    let mut paths = HashSet::new();
+    paths.insert("app.rs".to_string());
+    let text = build_highlighted_text("line1\nfix app.rs\nline3", &paths, Color::White, Color::Cyan);
+    let lines: Vec<&Line> = text.lines.iter().collect();
+    assert_eq!(lines.len(), 3);
+    // First line: no highlight
+    assert_eq!(lines[0].spans.len(), 1);
+    assert_eq!(lines[0].spans[0].content, "line1");
+    // Second line: has highlight
+    assert_eq!(lines[1].spans.len(), 2);
+    assert_eq!(lines[1].spans[0].content, "fix ");
+    assert_eq!(lines[1].spans[1].content, "app.rs");
+    // Third line: no highlight
+    assert_eq!(lines[2].spans.len(), 1);
+    assert_eq!(lines[2].spans[0].content, "line3");
+}
+
+/// Test build_highlighted_text when path is at the start of line
+#[test]
+fn test_build_highlighted_text_path_at_start() {
+    let mut paths = HashSet::new();
+    paths.insert("src/lib.rs".to_string());
+    let text = build_highlighted_text("src/lib.rs is important", &paths, Color::White, Color::Cyan);
+    let lines: Vec<&Line> = text.lines.iter().collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].spans.len(), 2);
+    assert_eq!(lines[0].spans[0].content, "src/lib.rs");
+    assert_eq!(lines[0].spans[1].content, " is important");
+}
+
+/// Test build_highlighted_text when path is the entire line
+#[test]
+fn test_build_highlighted_text_path_is_entire_line() {
+    let mut paths = HashSet::new();
+    paths.insert("Cargo.toml".to_string());
+    let text = build_highlighted_text("Cargo.toml", &paths, Color::White, Color::Cyan);
+    let lines: Vec<&Line> = text.lines.iter().collect();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].spans.len(), 1);
+    assert_eq!(lines[0].spans[0].content, "Cargo.toml");
+    assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+}
+
+// =============================================================================
+// Tests for word_boundary_left / word_boundary_right
+// =============================================================================
+
+/// Test word_boundary_left from end of string
+#[test]
+fn test_word_boundary_left_from_end() {
+    assert_eq!(word_boundary_left("hello world", 11), 6);
+}
+
+/// Test word_boundary_left skips to previous word
+#[test]
+fn test_word_boundary_left_between_words() {
+    assert_eq!(word_boundary_left("hello world", 6), 0);
+}
+
+/// Test word_boundary_left from middle of word
+#[test]
+fn test_word_boundary_left_mid_word() {
+    assert_eq!(word_boundary_left("hello world", 8), 6);
+}
+
+/// Test word_boundary_left at start stays at 0
+#[test]
+fn test_word_boundary_left_at_start() {
+    assert_eq!(word_boundary_left("hello", 0), 0);
+}
+
+/// Test word_boundary_left with multiple spaces
+#[test]
+fn test_word_boundary_left_multiple_spaces() {
+    assert_eq!(word_boundary_left("hello   world", 13), 8);
+}
+
+/// Test word_boundary_left with path separators
+#[test]
+fn test_word_boundary_left_path() {
+    // From end of "src/main.rs", should jump back over "rs"
+    assert_eq!(word_boundary_left("src/main.rs", 11), 9);
+}
+
+/// Test word_boundary_right from start of string
+#[test]
+fn test_word_boundary_right_from_start() {
+    assert_eq!(word_boundary_right("hello world", 0), 6);
+}
+
+/// Test word_boundary_right from space between words
+#[test]
+fn test_word_boundary_right_from_space() {
+    assert_eq!(word_boundary_right("hello world", 5), 6);
+}
+
+/// Test word_boundary_right from middle of word
+#[test]
+fn test_word_boundary_right_mid_word() {
+    assert_eq!(word_boundary_right("hello world", 3), 6);
+}
+
+/// Test word_boundary_right at end stays at end
+#[test]
+fn test_word_boundary_right_at_end() {
+    assert_eq!(word_boundary_right("hello", 5), 5);
+}
+
+/// Test word_boundary_right with multiple spaces
+#[test]
+fn test_word_boundary_right_multiple_spaces() {
+    assert_eq!(word_boundary_right("hello   world", 0), 8);
+}
+
+/// Test word_boundary_right with path separators
+#[test]
+fn test_word_boundary_right_path() {
+    // From start of "src/main.rs", should jump over "src" then the separator
+    assert_eq!(word_boundary_right("src/main.rs", 0), 4);
+}
+
+/// Test word_boundary_left with empty string
+#[test]
+fn test_word_boundary_left_empty() {
+    assert_eq!(word_boundary_left("", 0), 0);
+}
+
+/// Test word_boundary_right with empty string
+#[test]
+fn test_word_boundary_right_empty() {
+    assert_eq!(word_boundary_right("", 0), 0);
+}
+
+/// Test word_boundary roundtrip: jumping right then left returns close to start
+#[test]
+fn test_word_boundary_roundtrip() {
+    let s = "hello world foo";
+    let pos = word_boundary_right(s, 0); // -> 6 (start of "world")
+    let pos = word_boundary_right(s, pos); // -> 12 (start of "foo")
+    let pos = word_boundary_left(s, pos); // -> 6 (start of "world")
+    let pos = word_boundary_left(s, pos); // -> 0 (start of "hello")
+    assert_eq!(pos, 0);
+}
+
+// =============================================================================
+// Tests for build_footer_text
+// =============================================================================
+
+#[test]
+fn test_footer_text_mentions_help() {
+    for column in 0..5 {
+        assert!(build_footer_text(InputMode::Normal, false, column).contains("[?] help"));
+    }
+}
+
+/// The help overlay scrolls within what the last draw allowed and closes on Esc, q and ?
+#[test]
+fn test_scroll_help() {
+    let mut popup = HelpPopup::default();
+    popup.max_scroll.set(3);
+
+    assert!(scroll_help(&mut popup, KeyCode::Char('j')));
+    assert_eq!(popup.scroll, 1);
+    assert!(scroll_help(&mut popup, KeyCode::PageDown));
+    assert_eq!(popup.scroll, 3);
+    assert!(scroll_help(&mut popup, KeyCode::Up));
+    assert_eq!(popup.scroll, 2);
+    assert!(scroll_help(&mut popup, KeyCode::Char('g')));
+    assert_eq!(popup.scroll, 0);
+    assert!(scroll_help(&mut popup, KeyCode::Char('k')));
+    assert_eq!(popup.scroll, 0);
+
+    for key in [KeyCode::Esc, KeyCode::Char('q'), KeyCode::Char('?')] {
+        assert!(!scroll_help(&mut popup, key));
+    }
+}
 
-/// Test build_highlighted_text with no file paths produces plain text
 #[test]
-fn test_build_highlighted_text_no_paths() {
-    let paths = HashSet::new();
-    let text = build_highlighted_text("hello world", &paths, Color::White, Color::Cyan);
-    let lines: Vec<&Line> = text.lines.iter().collect();
-    assert_eq!(lines.len(), 1);
-    assert_eq!(lines[0].spans.len(), 1);
-    assert_eq!(lines[0].spans[0].content, "hello world");
+fn test_footer_text_sidebar_focused() {
+    let text = build_footer_text(InputMode::Normal, true, 0);
+    assert!(text.contains("[j/k] navigate"));
+    assert!(text.contains("[e] hide sidebar"));
+    assert!(!text.contains("[o] new"));
 }
 
-/// Test build_highlighted_text highlights a single file path
 #[test]
-fn test_build_highlighted_text_single_path() {
-    let mut paths = HashSet::new();
-    paths.insert("src/main.rs".to_string());
-    let text = build_highlighted_text(
-        "Please edit src/main.rs for me",
-        &paths,
-        Color::White,
-        Color::Cyan,
-    );
-    let lines: Vec<&Line> = text.lines.iter().collect();
-    assert_eq!(lines.len(), 1);
-    assert_eq!(lines[0].spans.len(), 3);
-    assert_eq!(lines[0].spans[0].content, "Please edit ");
-    assert_eq!(lines[0].spans[1].content, "src/main.rs");
-    assert_eq!(lines[0].spans[2].content, " for me");
-    // The highlighted span should be bold
-    assert!(lines[0].spans[1].style.add_modifier.contains(Modifier::BOLD));
+fn test_footer_text_backlog_column() {
+    let text = build_footer_text(InputMode::Normal, false, 0);
+    assert!(text.contains("[M] run"));
+    assert!(text.contains("[m] plan"));
+    assert!(!text.contains("[r] move left"));
 }
 
-/// Test build_highlighted_text with multiple file paths on one line
 #[test]
-fn test_build_highlighted_text_multiple_paths() {
-    let mut paths = HashSet::new();
-    paths.insert("a.rs".to_string());
-    paths.insert("b.rs".to_string());
-    let text = build_highlighted_text("fix a.rs and b.rs", &paths, Color::White, Color::Cyan);
-    let lines: Vec<&Line> = text.lines.iter().collect();
-    assert_eq!(lines.len(), 1);
-    // Should be: "fix " | "a.rs" | " and " | "b.rs"
-    assert_eq!(lines[0].spans.len(), 4);
-    assert_eq!(lines[0].spans[1].content, "a.rs");
-    assert_eq!(lines[0].spans[3].content, "b.rs");
+fn test_footer_text_planning_column() {
+    let text = build_footer_text(InputMode::Normal, false, 1);
+    assert!(text.contains("[m] run"));
+    assert!(!text.contains("[M] run"));
+    assert!(!text.contains("[r] move left"));
 }
 
-/// Test build_highlighted_text with multiline input
 #[test]
-fn test_build_highlighted_text_multiline() {
-    let mut paths = HashSet::new();
-    paths.insert("app.rs".to_string());
-    let text = build_highlighted_text("line1\nfix app.rs\nline3", &paths, Color::White, Color::Cyan);
-    let lines: Vec<&Line> = text.lines.iter().collect();
-    assert_eq!(lines.len(), 3);
-    // First line: no highlight
-    assert_eq!(lines[0].spans.len(), 1);
-    assert_eq!(lines[0].spans[0].content, "line1");
-    // Second line: has highlight
-    assert_eq!(lines[1].spans.len(), 2);
-    assert_eq!(lines[1].spans[0].content, "fix ");
-    assert_eq!(lines[1].spans[1].content, "app.rs");
-    // Third line: no highlight
-    assert_eq!(lines[2].spans.len(), 1);
-    assert_eq!(lines[2].spans[0].content, "line3");
+fn test_footer_text_running_column() {
+    let text = build_footer_text(InputMode::Normal, false, 2);
+    assert!(text.contains("[r] move left"));
+    assert!(text.contains("[m] move"));
+    assert!(text.contains("[a] attach"));
 }
 
-/// Test build_highlighted_text when path is at the start of line
 #[test]
-fn test_build_highlighted_text_path_at_start() {
-    let mut paths = HashSet::new();
-    paths.insert("src/lib.rs".to_string());
-    let text = build_highlighted_text("src/lib.rs is important", &paths, Color::White, Color::Cyan);
-    let lines: Vec<&Line> = text.lines.iter().collect();
-    assert_eq!(lines.len(), 1);
-    assert_eq!(lines[0].spans.len(), 2);
-    assert_eq!(lines[0].spans[0].content, "src/lib.rs");
-    assert_eq!(lines[0].spans[1].content, " is important");
+fn test_footer_text_review_column() {
+    let text = build_footer_text(InputMode::Normal, false, 3);
+    assert!(text.contains("[r] move left"));
+    assert!(text.contains("[m] move"));
 }
 
-/// Test build_highlighted_text when path is the entire line
 #[test]
-fn test_build_highlighted_text_path_is_entire_line() {
-    let mut paths = HashSet::new();
-    paths.insert("Cargo.toml".to_string());
-    let text = build_highlighted_text("Cargo.toml", &paths, Color::White, Color::Cyan);
-    let lines: Vec<&Line> = text.lines.iter().collect();
-    assert_eq!(lines.len(), 1);
-    assert_eq!(lines[0].spans.len(), 1);
-    assert_eq!(lines[0].spans[0].content, "Cargo.toml");
-    assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+fn test_footer_text_done_column() {
+    let text = build_footer_text(InputMode::Normal, false, 4);
+    assert!(!text.contains("[m] move"));
+    assert!(!text.contains("[r]"));
+    assert!(!text.contains("[d] diff"));
+}
+
+#[test]
+fn test_footer_text_input_title() {
+    let text = build_footer_text(InputMode::InputTitle, false, 0);
+    assert!(text.contains("Enter task title"));
+    assert!(text.contains("[Esc] cancel"));
+}
+
+#[test]
+fn test_footer_text_input_description() {
+    let text = build_footer_text(InputMode::InputDescription, false, 0);
+    assert!(text.contains("[#] file search"));
+    assert!(text.contains("[\\+Enter] newline"));
+}
+
+#[test]
+fn test_footer_text_new_task() {
+    let text = build_footer_text(InputMode::NewTask, false, 0);
+    assert!(text.contains("[Tab] next field"));
+    assert!(text.contains("[Enter] create"));
 }
 
 // =============================================================================
-// Tests for word_boundary_left / word_boundary_right
+// Tests for setup_task_worktree
 // =============================================================================
 
-/// Test word_boundary_left from end of string
+/// Mocks for `setup_task_worktree`. `expect` adds what the test checks first, so those
+/// expectations win; any other call takes the happy path: no slug or window name is taken,
+/// the base ref exists, worktrees go under /project/.agtx/worktrees, windows open and the
+/// agent has no session ids.
+#[cfg(feature = "test-mocks")]
+fn worktree_mocks(
+    expect: impl FnOnce(&mut MockTmuxOperations, &mut MockGitOperations, &mut MockAgentOperations),
+) -> (MockTmuxOperations, MockGitOperations, MockAgentOperations) {
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_git = MockGitOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
+    expect(&mut mock_tmux, &mut mock_git, &mut mock_agent);
+
+    mock_git.expect_worktree_exists().returning(|_, _| false);
+    mock_git.expect_ref_exists().returning(|_, _| Ok(true));
+    mock_git
+        .expect_create_worktree()
+        .returning(|_, slug, _| Ok(format!("/project/.agtx/worktrees/{}", slug)));
+    mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
+    mock_agent.expect_new_session_id().returning(|| None);
+    mock_agent.expect_build_interactive_command().returning(|prompt| format!("claude '{}'", prompt));
+    mock_tmux.expect_window_exists().returning(|_| Ok(false));
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux.expect_create_window().returning(|_, _, _, _| Ok(()));
+    (mock_tmux, mock_git, mock_agent)
+}
+
+/// A claude task of /project branching from main, its window in session "my-project"
+#[cfg(feature = "test-mocks")]
+fn worktree_setup(window: &WindowSetup) -> WorktreeSetup<'_> {
+    WorktreeSetup {
+        project_path: Path::new("/project"),
+        base_branch: "main",
+        session: "my-project",
+        prompt: "go",
+        copy_files: None,
+        init_script: None,
+        plugin: &None,
+        agent_name: "claude",
+        window,
+    }
+}
+
+/// Test setup_task_worktree creates worktree, initializes it, and creates tmux window
 #[test]
-fn test_word_boundary_left_from_end() {
-    assert_eq!(word_boundary_left("hello world", 11), 6);
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_success() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|_, _, _| {});
+
+    let mut task = Task::new("Add login feature", "claude", "project-1");
+    task.status = TaskStatus::Backlog;
+    let window = WindowSetup::default();
+    let result = setup_task_worktree(
+        &mut task,
+        &worktree_setup(&window),
+        None,
+        &mock_tmux,
+        &mock_git,
+        &mock_agent,
+        &AtomicBool::new(false),
+    );
+
+    assert!(result.is_ok());
+    let target = result.unwrap();
+    assert!(target.starts_with("my-project:task-"));
+    assert!(task.session_name.is_some());
+    assert!(task.worktree_path.is_some());
+    assert!(task.branch_name.is_some());
+    assert!(task.branch_name.as_ref().unwrap().starts_with("task/"));
+}
+
+/// A slug whose worktree is already taken gets a numbered suffix, used for the branch and window too
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_avoids_taken_slug() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|_, mock_git, _| {
+        mock_git.expect_worktree_exists().returning(|_, slug| !slug.ends_with("-2"));
+        mock_git
+            .expect_create_worktree()
+            .withf(|_, slug, _| slug.ends_with("-fix-thing-other-2"))
+            .times(1)
+            .returning(|_, slug, _| Ok(format!("/project/.agtx/worktrees/{}", slug)));
+    });
+
+    let mut task = Task::new("fix: thing / other", "claude", "project-1");
+    let window = WindowSetup::default();
+    let target = setup_task_worktree(
+        &mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    assert!(target.ends_with("-fix-thing-other-2"));
+    assert!(task.branch_name.unwrap().ends_with("-fix-thing-other-2"));
+}
+
+/// The window is named by the configured template, and the task records that name
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_uses_window_name_template() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, _, _| {
+        mock_tmux
+            .expect_create_window()
+            .withf(|_, window, _, _| window == "agtx-fix-thing")
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+    });
+
+    let window = WindowSetup { name_template: "agtx:{title}".to_string(), ..Default::default() };
+    let mut task = Task::new("fix thing", "claude", "project-1");
+    let target = setup_task_worktree(
+        &mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    assert_eq!(target, "my-project:agtx-fix-thing");
+    assert_eq!(task.session_name.as_deref(), Some("my-project:agtx-fix-thing"));
+}
+
+/// Another task's window with the same name (same title, template without `{slug}`) gets a suffix
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_suffixes_taken_window_name() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, _, _| {
+        mock_tmux.expect_window_exists().returning(|target| Ok(target == "my-project:agtx-fix-thing"));
+        mock_tmux
+            .expect_create_window()
+            .withf(|_, window, _, _| window == "agtx-fix-thing-2")
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+    });
+
+    let window = WindowSetup { name_template: "agtx:{title}".to_string(), ..Default::default() };
+    let mut task = Task::new("fix thing", "claude", "project-1");
+    let target = setup_task_worktree(
+        &mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    assert_eq!(target, "my-project:agtx-fix-thing-2");
+}
+
+/// A task branching from a deleted ref fails with the ref's name before any worktree is made
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_missing_base_ref() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|_, mock_git, _| {
+        mock_git
+            .expect_ref_exists()
+            .withf(|_, name| name == "feature/gone")
+            .returning(|_, _| Ok(false));
+        mock_git.expect_create_worktree().never();
+    });
+
+    let mut task = Task::new("Follow-up", "claude", "project-1");
+    task.from_ref = Some("feature/gone".to_string());
+    let window = WindowSetup::default();
+    let err = setup_task_worktree(
+        &mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false),
+    )
+    .unwrap_err();
+
+    assert_eq!(format!("{:#}", err), "base ref 'feature/gone' not found");
+    assert!(task.worktree_path.is_none());
+}
+
+/// The worktree branches from the task's ref instead of the base branch
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_branches_from_task_ref() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|_, mock_git, _| {
+        mock_git
+            .expect_create_worktree()
+            .withf(|_, _, base| base == "release/2.0")
+            .times(1)
+            .returning(|_, slug, _| Ok(format!("/project/.agtx/worktrees/{}", slug)));
+    });
+
+    let mut task = Task::new("Backport", "claude", "project-1");
+    task.from_ref = Some("release/2.0".to_string());
+    let window = WindowSetup::default();
+    setup_task_worktree(
+        &mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    assert!(task.worktree_path.is_some());
+}
+
+/// A task with a directory gets its window opened there, not at the worktree root
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_opens_window_in_subdir() {
+    use crate::db::Task;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    let worktree = dir.path().join("fix-thing");
+    std::fs::create_dir_all(worktree.join("crates").join("api")).unwrap();
+    let worktree_str = worktree.to_string_lossy().into_owned();
+    let expected_dir = worktree.join("crates").join("api").to_string_lossy().into_owned();
+
+    let created = worktree_str.clone();
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, mock_git, _| {
+        mock_git.expect_create_worktree().returning(move |_, _, _| Ok(created.clone()));
+        mock_tmux
+            .expect_create_window()
+            .withf(move |_, _, working_dir, _| working_dir == expected_dir)
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+    });
+
+    let mut task = Task::new("fix thing", "claude", "project-1");
+    task.subdir = Some("crates/api".to_string());
+    let window = WindowSetup::default();
+    setup_task_worktree(
+        &mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false),
+    )
+    .unwrap();
+
+    assert_eq!(task.worktree_path.as_deref(), Some(worktree_str.as_str()));
+    assert!(missing_subdir(&task).is_none());
+}
+
+#[test]
+fn test_task_working_dir_falls_back_to_root() {
+    use crate::db::Task;
+
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("web")).unwrap();
+    let root = dir.path().to_string_lossy().into_owned();
+
+    assert_eq!(task_working_dir(&root, None), root);
+    assert_eq!(task_working_dir(&root, Some("web")), dir.path().join("web").to_string_lossy());
+    assert_eq!(task_working_dir(&root, Some("docs")), root);
+
+    let mut task = Task::new("Docs", "claude", "p");
+    task.subdir = Some("docs".to_string());
+    // No worktree yet: nothing to check
+    assert!(missing_subdir(&task).is_none());
+    task.worktree_path = Some(root);
+    assert!(missing_subdir(&task).unwrap().contains("no directory 'docs'"));
+}
+
+#[test]
+fn test_with_scope_note() {
+    use crate::db::Task;
+
+    let mut task = Task::new("Fix API", "claude", "p");
+    assert_eq!(with_scope_note("Do it".to_string(), &task), "Do it");
+
+    task.subdir = Some("crates/api".to_string());
+    let prompt = with_scope_note("Do it".to_string(), &task);
+    assert!(prompt.starts_with("Do it\n\nScope:"));
+    assert!(prompt.contains("`crates/api`"));
+    // An empty prompt means "send nothing" and stays that way
+    assert_eq!(with_scope_note(String::new(), &task), "");
+}
+
+/// Test setup_task_worktree sets correct task fields
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_sets_task_fields() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|_, _, mock_agent| {
+        mock_agent
+            .expect_new_session_id()
+            .returning(|| Some("sess-1".to_string()));
+        mock_agent
+            .expect_build_interactive_command_in_session()
+            .with(mockall::predicate::always(), mockall::predicate::eq("sess-1"))
+            .returning(|prompt, id| format!("claude --session-id {} '{}'", id, prompt));
+    });
+
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    let window = WindowSetup::default();
+    let setup = WorktreeSetup {
+        prompt: "fix the bug",
+        copy_files: Some("CLAUDE.md".to_string()),
+        init_script: Some("./init.sh".to_string()),
+        ..worktree_setup(&window)
+    };
+    let target =
+        setup_task_worktree(&mut task, &setup, None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false))
+            .unwrap();
+
+    // session_name should be the returned target
+    assert_eq!(task.session_name.as_ref().unwrap(), &target);
+    // worktree_path should contain the slug
+    assert!(task.worktree_path.as_ref().unwrap().contains(".agtx/worktrees/"));
+    // branch_name should be task/{slug}
+    let slug = &task.branch_name.as_ref().unwrap()["task/".len()..];
+    assert!(task.worktree_path.as_ref().unwrap().ends_with(slug));
+    // the slug is stored, so renaming the task later doesn't rename its resources
+    assert_eq!(task.slug.as_deref(), Some(slug));
+    // the agent's session id is recorded so the conversation can be resumed
+    assert_eq!(task.agent_session_id.as_deref(), Some("sess-1"));
 }
 
-/// Test word_boundary_left skips to previous word
+/// Configured env is exported and the startup command run in the window before the agent
 #[test]
-fn test_word_boundary_left_between_words() {
-    assert_eq!(word_boundary_left("hello world", 6), 0);
-}
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_prepares_window_before_agent() {
+    use crate::db::Task;
 
-/// Test word_boundary_left from middle of word
-#[test]
-fn test_word_boundary_left_mid_word() {
-    assert_eq!(word_boundary_left("hello world", 8), 6);
-}
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, _, mock_agent| {
+        mock_agent
+            .expect_build_interactive_command()
+            .returning(|_| "claude --dangerously-skip-permissions".to_string());
+        mock_tmux
+            .expect_create_window()
+            .withf(|_, _, _, command| {
+                command.as_deref().is_some_and(|c| {
+                    c.starts_with("export FOO=bar; export GREETING='hello world'; \"${SHELL:-sh}\" -ic 'direnv allow; claude --dangerously-skip-permissions; echo $? > /project/")
+                        && c.ends_with("/.agtx/agent-exit-code'")
+                })
+            })
+            .times(1)
+            .returning(|_, _, _, _| Ok(()));
+    });
 
-/// Test word_boundary_left at start stays at 0
-#[test]
-fn test_word_boundary_left_at_start() {
-    assert_eq!(word_boundary_left("hello", 0), 0);
-}
+    let mut config = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    config.env.insert("FOO".to_string(), "staging".to_string());
+    config.window_startup_command = Some("direnv allow".to_string());
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.env.insert("FOO".to_string(), "bar".to_string());
+    task.env.insert("GREETING".to_string(), "hello world".to_string());
+    let window = WindowSetup::for_task(&config, &task);
 
-/// Test word_boundary_left with multiple spaces
-#[test]
-fn test_word_boundary_left_multiple_spaces() {
-    assert_eq!(word_boundary_left("hello   world", 13), 8);
+    let setup = WorktreeSetup { prompt: "", ..worktree_setup(&window) };
+    setup_task_worktree(&mut task, &setup, None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false))
+        .unwrap();
 }
 
-/// Test word_boundary_left with path separators
+/// Test a failed worktree creation aborts the setup so the task stays where it was
 #[test]
-fn test_word_boundary_left_path() {
-    // From end of "src/main.rs", should jump back over "rs"
-    assert_eq!(word_boundary_left("src/main.rs", 11), 9);
-}
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_worktree_creation_fails() {
+    use crate::db::Task;
 
-/// Test word_boundary_right from start of string
-#[test]
-fn test_word_boundary_right_from_start() {
-    assert_eq!(word_boundary_right("hello world", 0), 6);
-}
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, mock_git, _| {
+        mock_git
+            .expect_create_worktree()
+            .returning(|_, _, _| Err(command_failed("git worktree add", "fatal: invalid reference: main")));
+        mock_git.expect_initialize_worktree().never();
+        mock_tmux.expect_create_window().never();
+    });
 
-/// Test word_boundary_right from space between words
-#[test]
-fn test_word_boundary_right_from_space() {
-    assert_eq!(word_boundary_right("hello world", 5), 6);
-}
+    let mut task = Task::new("Test task", "claude", "project-1");
+    let window = WindowSetup::default();
+    let result = setup_task_worktree(
+        &mut task,
+        &worktree_setup(&window),
+        None,
+        &mock_tmux,
+        &mock_git,
+        &mock_agent,
+        &AtomicBool::new(false),
+    );
 
-/// Test word_boundary_right from middle of word
-#[test]
-fn test_word_boundary_right_mid_word() {
-    assert_eq!(word_boundary_right("hello world", 3), 6);
+    let err = result.unwrap_err();
+    assert_eq!(
+        format!("{:#}", err),
+        "Failed to create worktree: `git worktree add` failed (exit 128): fatal: invalid reference: main"
+    );
+    assert!(task.worktree_path.is_none());
+    assert!(task.session_name.is_none());
 }
 
-/// Test word_boundary_right at end stays at end
+/// Test a timed-out (killed) worktree creation aborts instead of falling back
 #[test]
-fn test_word_boundary_right_at_end() {
-    assert_eq!(word_boundary_right("hello", 5), 5);
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_timeout_aborts() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, mock_git, _| {
+        // Slow git: the worktree add was killed after the configured timeout
+        mock_git.expect_create_worktree().returning(|_, _, _| {
+            Err(OpError::Timeout {
+                op: "git worktree add".to_string(),
+                after: std::time::Duration::from_secs(120),
+            })
+        });
+        mock_git.expect_initialize_worktree().never();
+        mock_tmux.expect_create_window().never();
+    });
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    let window = WindowSetup::default();
+    let result = setup_task_worktree(
+        &mut task,
+        &worktree_setup(&window),
+        None,
+        &mock_tmux,
+        &mock_git,
+        &mock_agent,
+        &AtomicBool::new(false),
+    );
+
+    let err = result.unwrap_err();
+    assert!(crate::git::is_timeout(&err));
+    assert!(format!("{:#}", err).contains("timed out after 120s"));
+    assert!(task.worktree_path.is_none());
 }
 
-/// Test word_boundary_right with multiple spaces
+/// Test setup_task_worktree fails when tmux window creation fails
 #[test]
-fn test_word_boundary_right_multiple_spaces() {
-    assert_eq!(word_boundary_right("hello   world", 0), 8);
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_tmux_window_fails() {
+    use crate::db::Task;
+
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, mock_git, _| {
+        // Tmux window creation fails
+        mock_tmux
+            .expect_create_window()
+            .returning(|_, _, _, _| Err(command_failed("tmux new-window", "tmux not running")));
+
+        // The worktree and branch just created are removed again
+        mock_git
+            .expect_remove_worktree()
+            .withf(|_, path| path.starts_with("/project/.agtx/worktrees/"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_git
+            .expect_delete_branch()
+            .withf(|_, branch| branch.starts_with("task/"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+    });
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    let window = WindowSetup::default();
+    let result = setup_task_worktree(
+        &mut task,
+        &worktree_setup(&window),
+        None,
+        &mock_tmux,
+        &mock_git,
+        &mock_agent,
+        &AtomicBool::new(false),
+    );
+
+    // Should propagate the error and leave the task without resources
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("tmux not running"));
+    assert!(task.worktree_path.is_none());
+    assert!(task.branch_name.is_none());
 }
 
-/// Test word_boundary_right with path separators
+/// An interrupt while the worktree is being created rolls it back before any window opens,
+/// so the task keeps its pre-transition status and fields
 #[test]
-fn test_word_boundary_right_path() {
-    // From start of "src/main.rs", should jump over "src" then the separator
-    assert_eq!(word_boundary_right("src/main.rs", 0), 4);
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_interrupted_rolls_back() {
+    use crate::db::Task;
+
+    let interrupted = Arc::new(AtomicBool::new(false));
+    // SIGINT arrives while git is creating the worktree
+    let flag = Arc::clone(&interrupted);
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, mock_git, _| {
+        mock_git.expect_create_worktree().times(1).returning(move |_, slug, _| {
+            flag.store(true, Ordering::SeqCst);
+            Ok(format!("/project/.agtx/worktrees/{}", slug))
+        });
+        mock_git
+            .expect_remove_worktree()
+            .withf(|_, path| path.starts_with("/project/.agtx/worktrees/"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_git
+            .expect_delete_branch()
+            .withf(|_, branch| branch.starts_with("task/"))
+            .times(1)
+            .returning(|_, _| Ok(()));
+        mock_tmux.expect_create_window().never();
+    });
+
+    let mut task = Task::new("Test task", "claude", "project-1");
+    task.status = TaskStatus::Planning;
+    let before = task.clone();
+
+    let window = WindowSetup::default();
+    let result =
+        setup_task_worktree(&mut task, &worktree_setup(&window), None, &mock_tmux, &mock_git, &mock_agent, &interrupted);
+
+    assert!(result.unwrap_err().to_string().contains("Interrupted"));
+    assert_eq!(task.status, before.status);
+    assert!(task.session_name.is_none());
+    assert!(task.worktree_path.is_none());
+    assert!(task.branch_name.is_none());
 }
 
-/// Test word_boundary_left with empty string
 #[test]
-fn test_word_boundary_left_empty() {
-    assert_eq!(word_boundary_left("", 0), 0);
+fn test_is_interrupt_key() {
+    use crossterm::event::{KeyEvent, KeyModifiers};
+
+    assert!(is_interrupt_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)));
+    assert!(!is_interrupt_key(&KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE)));
+    assert!(!is_interrupt_key(&KeyEvent::new(KeyCode::Char('x'), KeyModifiers::CONTROL)));
 }
 
-/// Test word_boundary_right with empty string
 #[test]
-fn test_word_boundary_right_empty() {
-    assert_eq!(word_boundary_right("", 0), 0);
+fn test_toggle_automation() {
+    let paused = AtomicBool::new(false);
+    assert!(toggle_automation(&paused).starts_with("Automation paused"));
+    assert!(paused.load(Ordering::SeqCst));
+    assert_eq!(toggle_automation(&paused), "Automation resumed");
+    assert!(!paused.load(Ordering::SeqCst));
 }
 
-/// Test word_boundary roundtrip: jumping right then left returns close to start
+/// Test setup_task_worktree creates tmux session when missing
 #[test]
-fn test_word_boundary_roundtrip() {
-    let s = "hello world foo";
-    let pos = word_boundary_right(s, 0); // -> 6 (start of "world")
-    let pos = word_boundary_right(s, pos); // -> 12 (start of "foo")
-    let pos = word_boundary_left(s, pos); // -> 6 (start of "world")
-    let pos = word_boundary_left(s, pos); // -> 0 (start of "hello")
-    assert_eq!(pos, 0);
-}
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_creates_session_when_missing() {
+    use crate::db::Task;
 
-// =============================================================================
-// Tests for build_footer_text
-// =============================================================================
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|mock_tmux, _, _| {
+        // Session doesn't exist yet; it is created in the project root
+        mock_tmux
+            .expect_ensure_session()
+            .with(
+                mockall::predicate::eq("my-project"),
+                mockall::predicate::eq("/project"),
+            )
+            .times(1)
+            .returning(|_, _| Ok(()));
+    });
 
-#[test]
-fn test_footer_text_sidebar_focused() {
-    let text = build_footer_text(InputMode::Normal, true, 0);
-    assert!(text.contains("[j/k] navigate"));
-    assert!(text.contains("[e] hide sidebar"));
-    assert!(!text.contains("[o] new"));
-}
+    let mut task = Task::new("New task", "claude", "project-1");
+    let window = WindowSetup::default();
+    let result = setup_task_worktree(
+        &mut task,
+        &worktree_setup(&window),
+        None,
+        &mock_tmux,
+        &mock_git,
+        &mock_agent,
+        &AtomicBool::new(false),
+    );
 
-#[test]
-fn test_footer_text_backlog_column() {
-    let text = build_footer_text(InputMode::Normal, false, 0);
-    assert!(text.contains("[M] run"));
-    assert!(text.contains("[m] plan"));
-    assert!(!text.contains("[r] move left"));
+    assert!(result.is_ok());
 }
 
+/// Test setup_task_worktree passes copy_files and init_script to initialize_worktree
 #[test]
-fn test_footer_text_planning_column() {
-    let text = build_footer_text(InputMode::Normal, false, 1);
-    assert!(text.contains("[m] run"));
-    assert!(!text.contains("[M] run"));
-    assert!(!text.contains("[r] move left"));
-}
+#[cfg(feature = "test-mocks")]
+fn test_setup_task_worktree_passes_init_config() {
+    use crate::db::Task;
 
-#[test]
-fn test_footer_text_running_column() {
-    let text = build_footer_text(InputMode::Normal, false, 2);
-    assert!(text.contains("[r] move left"));
-    assert!(text.contains("[m] move"));
+    let (mock_tmux, mock_git, mock_agent) = worktree_mocks(|_, mock_git, _| {
+        // Verify copy_files and init_script are passed through
+        mock_git
+            .expect_initialize_worktree()
+            .withf(|_, _, copy_files, init_script, _copy_dirs| {
+                copy_files.as_deref() == Some("CLAUDE.md,.env")
+                    && init_script.as_deref() == Some("./setup.sh")
+            })
+            .times(1)
+            .returning(|_, _, _, _, _| vec!["warning: .env not found".to_string()]);
+    });
+
+    let mut task = Task::new("Task with config", "claude", "project-1");
+    let window = WindowSetup::default();
+    let setup = WorktreeSetup {
+        copy_files: Some("CLAUDE.md,.env".to_string()),
+        init_script: Some("./setup.sh".to_string()),
+        ..worktree_setup(&window)
+    };
+    let result =
+        setup_task_worktree(&mut task, &setup, None, &mock_tmux, &mock_git, &mock_agent, &AtomicBool::new(false));
+
+    assert!(result.is_ok());
 }
 
+/// Reopening a task whose window is gone resumes its recorded agent conversation
 #[test]
-fn test_footer_text_review_column() {
-    let text = build_footer_text(InputMode::Normal, false, 3);
-    assert!(text.contains("[r] move left"));
-    assert!(text.contains("[m] move"));
+#[cfg(feature = "test-mocks")]
+fn test_restart_agent_window_resumes_recorded_session() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+    let worktree = dir.path().to_string_lossy().to_string();
+    let expected = format!("claude --resume sess-1; echo $? > {}/.agtx/agent-exit-code", worktree);
+
+    mock_agent
+        .expect_build_resume_command()
+        .with(mockall::predicate::eq("sess-1"))
+        .returning(|id| Some(format!("claude --resume {}", id)));
+    mock_agent.expect_new_session_id().never();
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux
+        .expect_create_window()
+        .withf(move |session, window, _, command| {
+            session == "my-project" && window == "task-old" && command.as_deref() == Some(expected.as_str())
+        })
+        .returning(|_, _, _, _| Ok(()));
+
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.worktree_path = Some(worktree);
+    task.session_name = Some("my-project:task-old".to_string());
+    task.agent_session_id = Some("sess-1".to_string());
+
+    let resumed = restart_agent_window(
+        &mut task, "my-project", Path::new("/project"), "go on", &WindowSetup::default(), &mock_tmux, &mock_agent,
+    )
+    .unwrap();
+
+    assert!(resumed);
+    assert_eq!(task.session_name.as_deref(), Some("my-project:task-old"));
+    assert_eq!(task.agent_session_id.as_deref(), Some("sess-1"));
 }
 
+/// Without a recorded conversation the agent starts fresh (in a new session when supported)
 #[test]
-fn test_footer_text_done_column() {
-    let text = build_footer_text(InputMode::Normal, false, 4);
-    assert!(!text.contains("[m] move"));
-    assert!(!text.contains("[r]"));
-    assert!(!text.contains("[d] diff"));
+#[cfg(feature = "test-mocks")]
+fn test_restart_agent_window_starts_fresh_without_session() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+    let worktree = dir.path().to_string_lossy().to_string();
+
+    mock_agent.expect_build_resume_command().never();
+    mock_agent
+        .expect_new_session_id()
+        .returning(|| Some("sess-2".to_string()));
+    mock_agent
+        .expect_build_interactive_command_in_session()
+        .returning(|prompt, id| format!("claude --session-id {} '{}'", id, prompt));
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux
+        .expect_create_window()
+        .withf(|_, _, _, command| {
+            command.as_deref().is_some_and(|c| c.starts_with("claude --session-id sess-2 'go on'; echo $? > "))
+        })
+        .returning(|_, _, _, _| Ok(()));
+
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.worktree_path = Some(worktree);
+
+    let resumed = restart_agent_window(
+        &mut task, "my-project", Path::new("/project"), "go on", &WindowSetup::default(), &mock_tmux, &mock_agent,
+    )
+    .unwrap();
+
+    assert!(!resumed);
+    assert!(task.session_name.as_deref().unwrap().starts_with("my-project:task-"));
+    assert_eq!(task.agent_session_id.as_deref(), Some("sess-2"));
 }
 
+/// A task whose worktree is gone can't get its agent back
 #[test]
-fn test_footer_text_input_title() {
-    let text = build_footer_text(InputMode::InputTitle, false, 0);
-    assert!(text.contains("Enter task title"));
-    assert!(text.contains("[Esc] cancel"));
+#[cfg(feature = "test-mocks")]
+fn test_restart_agent_window_requires_worktree() {
+    use crate::db::Task;
+
+    let mock_tmux = MockTmuxOperations::new();
+    let mock_agent = MockAgentOperations::new();
+
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.worktree_path = Some("/nonexistent/agtx/worktree".to_string());
+    task.agent_session_id = Some("sess-1".to_string());
+
+    let err = restart_agent_window(
+        &mut task, "my-project", Path::new("/project"), "go on", &WindowSetup::default(), &mock_tmux, &mock_agent,
+    )
+    .unwrap_err();
+
+    assert!(err.to_string().contains("has no worktree"));
+    assert!(task.session_name.is_none());
 }
 
+/// A multi-kilobyte prompt reaches the restarted agent through the prompt file, not tmux
 #[test]
-fn test_footer_text_input_description() {
-    let text = build_footer_text(InputMode::InputDescription, false, 0);
-    assert!(text.contains("[#] file search"));
-    assert!(text.contains("[\\+Enter] newline"));
-}
+#[cfg(feature = "test-mocks")]
+fn test_restart_agent_window_sends_long_prompt_through_file() {
+    use crate::db::Task;
 
-// =============================================================================
-// Tests for setup_task_worktree
-// =============================================================================
+    let mut mock_tmux = MockTmuxOperations::new();
+    let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+    let worktree = dir.path().to_string_lossy().to_string();
+    let prompt = format!("Fix bug\n\n{}", "Steps to reproduce: log in twice. ".repeat(300));
+    let prompt_file = git::prompt_file_path(dir.path());
+    let read_back = format!("claude \"$(cat {})\"; echo $? > ", prompt_file.display());
+
+    mock_agent.expect_build_resume_command().never();
+    mock_agent.expect_new_session_id().returning(|| None);
+    mock_agent
+        .expect_build_interactive_command()
+        .returning(|prompt| format!("claude '{}'", prompt));
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux
+        .expect_create_window()
+        .withf(move |_, _, _, command| command.as_deref().is_some_and(|c| c.starts_with(&read_back)))
+        .returning(|_, _, _, _| Ok(()));
 
-/// Test setup_task_worktree creates worktree, initializes it, and creates tmux window
+    let mut task = Task::new("Fix bug", "claude", "project-1");
+    task.worktree_path = Some(worktree);
+
+    restart_agent_window(
+        &mut task, "my-project", Path::new("/project"), &prompt, &WindowSetup::default(), &mock_tmux, &mock_agent,
+    )
+    .unwrap();
+
+    assert!(prompt.len() > 8 * 1024);
+    assert_eq!(std::fs::read_to_string(prompt_file).unwrap(), prompt);
+}
+
+/// A restored task whose worktree is gone gets it back on its own branch, and the window left
+/// in the vanished directory is replaced
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_setup_task_worktree_success() {
+fn test_restore_task_resources_checks_out_existing_branch() {
     use crate::db::Task;
 
     let mut mock_tmux = MockTmuxOperations::new();
     let mut mock_git = MockGitOperations::new();
     let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+    let worktree = dir.path().to_string_lossy().to_string();
+    let restored = worktree.clone();
 
-    // Expect worktree creation
-    mock_git
-        .expect_create_worktree()
-        .returning(|_, slug| Ok(format!("/project/.agtx/worktrees/{}", slug)));
-
-    // Expect worktree initialization
     mock_git
-        .expect_initialize_worktree()
-        .returning(|_, _, _, _, _| vec![]);
-
-    // Expect agent command building
-    mock_agent
-        .expect_build_interactive_command()
-        .returning(|prompt| format!("claude --dangerously-skip-permissions '{}'", prompt));
-
-    // Expect tmux session check and window creation
+        .expect_checkout_worktree()
+        .withf(|_, slug, branch| slug == "abc-fix" && branch == "task/abc-fix")
+        .returning(move |_, _, _| Ok(restored.clone()));
+    mock_git.expect_create_worktree().never();
+    mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
     mock_tmux
-        .expect_has_session()
-        .returning(|_| true);
-
-    mock_tmux
-        .expect_create_window()
-        .returning(|_, _, _, _| Ok(()));
-
-    let mut task = Task::new("Add login feature", "claude", "project-1");
-    task.status = TaskStatus::Backlog;
-
-    let result = setup_task_worktree(
-        &mut task,
-        Path::new("/project"),
-        "my-project",
-        "implement this",
-        None,
-        None,
-        &None,
-        "claude",
-        &mock_tmux,
-        &mock_git,
-        &mock_agent,
-    );
-
-    assert!(result.is_ok());
-    let target = result.unwrap();
-    assert!(target.starts_with("my-project:task-"));
-    assert!(task.session_name.is_some());
-    assert!(task.worktree_path.is_some());
-    assert!(task.branch_name.is_some());
-    assert!(task.branch_name.as_ref().unwrap().starts_with("task/"));
+        .expect_kill_window()
+        .with(mockall::predicate::eq("my-project:task-abc-fix"))
+        .times(1)
+        .returning(|_| Ok(()));
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux.expect_create_window().times(1).returning(|_, _, _, _| Ok(()));
+    mock_agent.expect_build_resume_command().returning(|id| Some(format!("claude --resume {}", id)));
+
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.worktree_path = Some("/old/.agtx/worktrees/abc-fix".to_string());
+    task.branch_name = Some("task/abc-fix".to_string());
+    task.session_name = Some("my-project:task-abc-fix".to_string());
+    task.agent_session_id = Some("sess-1".to_string());
+
+    let recreated = restore_task_resources(
+        &mut task, MissingResource::Worktree, Path::new("/project"), "main", "my-project", "go on",
+        None, None, &None, &WindowSetup::default(), &mock_tmux, &mock_git, &mock_agent,
+    )
+    .unwrap();
+
+    assert!(recreated);
+    assert_eq!(task.worktree_path.as_deref(), Some(worktree.as_str()));
+    assert_eq!(task.branch_name.as_deref(), Some("task/abc-fix"));
+    assert_eq!(task.session_name.as_deref(), Some("my-project:task-abc-fix"));
 }
 
-/// Test setup_task_worktree sets correct task fields
+/// A renamed task whose worktree record is gone is restored under its stored slug, not one
+/// derived from the new title
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_setup_task_worktree_sets_task_fields() {
+fn test_restore_task_resources_uses_stored_slug_after_rename() {
     use crate::db::Task;
 
     let mut mock_tmux = MockTmuxOperations::new();
     let mut mock_git = MockGitOperations::new();
     let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+    let restored = dir.path().to_string_lossy().to_string();
 
     mock_git
-        .expect_create_worktree()
-        .returning(|_, slug| Ok(format!("/project/.agtx/worktrees/{}", slug)));
-    mock_git
-        .expect_initialize_worktree()
-        .returning(|_, _, _, _, _| vec![]);
-    mock_agent
-        .expect_build_interactive_command()
-        .returning(|prompt| format!("claude '{}'", prompt));
-    mock_tmux.expect_has_session().returning(|_| true);
+        .expect_checkout_worktree()
+        .withf(|_, slug, branch| slug == "abc-fix" && branch == "task/abc-fix")
+        .times(1)
+        .returning(move |_, _, _| Ok(restored.clone()));
+    mock_git.expect_create_worktree().never();
+    mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
     mock_tmux.expect_create_window().returning(|_, _, _, _| Ok(()));
+    mock_agent.expect_new_session_id().returning(|| None);
+    mock_agent.expect_build_interactive_command().returning(|prompt| format!("claude '{}'", prompt));
 
-    let mut task = Task::new("Fix bug", "claude", "project-1");
+    let mut task = Task::new("A completely different title", "claude", "project-1");
+    task.status = TaskStatus::Review;
+    task.branch_name = Some("task/abc-fix".to_string());
+    task.slug = Some("abc-fix".to_string());
 
-    let target = setup_task_worktree(
-        &mut task,
-        Path::new("/project"),
-        "my-project",
-        "fix the bug",
-        Some("CLAUDE.md".to_string()),
-        Some("./init.sh".to_string()),
-        &None,
-        "claude",
-        &mock_tmux,
-        &mock_git,
-        &mock_agent,
-    ).unwrap();
+    restore_task_resources(
+        &mut task, MissingResource::WindowAndWorktree, Path::new("/project"), "main", "my-project", "go on",
+        None, None, &None, &WindowSetup::default(), &mock_tmux, &mock_git, &mock_agent,
+    )
+    .unwrap();
 
-    // session_name should be the returned target
-    assert_eq!(task.session_name.as_ref().unwrap(), &target);
-    // worktree_path should contain the slug
-    assert!(task.worktree_path.as_ref().unwrap().contains(".agtx/worktrees/"));
-    // branch_name should be task/{slug}
-    let slug = &task.branch_name.as_ref().unwrap()["task/".len()..];
-    assert!(task.worktree_path.as_ref().unwrap().ends_with(slug));
+    assert_eq!(task.slug.as_deref(), Some("abc-fix"));
 }
 
-/// Test setup_task_worktree handles worktree creation failure gracefully
+/// Without its branch the worktree starts over from the base branch
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_setup_task_worktree_worktree_creation_fails() {
+fn test_restore_task_resources_falls_back_to_base_branch() {
     use crate::db::Task;
 
     let mut mock_tmux = MockTmuxOperations::new();
     let mut mock_git = MockGitOperations::new();
     let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+    let worktree = dir.path().to_string_lossy().to_string();
 
-    // Worktree creation fails
     mock_git
-        .expect_create_worktree()
-        .returning(|_, _| Err(anyhow::anyhow!("worktree already exists")));
-
-    // Should still initialize and create window with fallback path
+        .expect_checkout_worktree()
+        .returning(|path, _, _| Err(OpError::NotAGitRepo { path: path.to_path_buf() }));
+    mock_git.expect_ref_exists().returning(|_, _| Ok(true));
     mock_git
-        .expect_initialize_worktree()
-        .returning(|_, _, _, _, _| vec![]);
-    mock_agent
-        .expect_build_interactive_command()
-        .returning(|prompt| format!("claude '{}'", prompt));
-    mock_tmux.expect_has_session().returning(|_| true);
-    mock_tmux.expect_create_window().returning(|_, _, _, _| Ok(()));
-
-    let mut task = Task::new("Test task", "claude", "project-1");
+        .expect_create_worktree()
+        .withf(|_, slug, base| slug == "abc-fix" && base == "develop")
+        .returning(move |_, _, _| Ok(worktree.clone()));
+    mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
+    mock_tmux.expect_kill_window().never();
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux.expect_create_window().times(1).returning(|_, _, _, _| Ok(()));
+    mock_agent.expect_new_session_id().returning(|| None);
+    mock_agent.expect_build_interactive_command().returning(|prompt| format!("claude '{}'", prompt));
+
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.status = TaskStatus::Review;
+    task.worktree_path = Some("/old/.agtx/worktrees/abc-fix".to_string());
+    task.branch_name = Some("task/abc-fix".to_string());
 
-    let result = setup_task_worktree(
-        &mut task,
-        Path::new("/project"),
-        "my-project",
-        "do something",
-        None,
-        None,
-        &None,
-        "claude",
-        &mock_tmux,
-        &mock_git,
-        &mock_agent,
-    );
+    let recreated = restore_task_resources(
+        &mut task, MissingResource::WindowAndWorktree, Path::new("/project"), "develop", "my-project", "review",
+        None, None, &None, &WindowSetup::default(), &mock_tmux, &mock_git, &mock_agent,
+    )
+    .unwrap();
 
-    // Should succeed despite worktree creation failure (uses fallback path)
-    assert!(result.is_ok());
-    assert!(task.worktree_path.is_some());
-    assert!(task.worktree_path.as_ref().unwrap().contains(".agtx/worktrees/"));
+    assert!(recreated);
+    assert_eq!(task.branch_name.as_deref(), Some("task/abc-fix"));
+    assert!(task.session_name.is_some());
 }
 
-/// Test setup_task_worktree fails when tmux window creation fails
+/// A restored task that only lost its window keeps its worktree untouched
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_setup_task_worktree_tmux_window_fails() {
+fn test_restore_task_resources_window_only() {
     use crate::db::Task;
 
     let mut mock_tmux = MockTmuxOperations::new();
     let mut mock_git = MockGitOperations::new();
     let mut mock_agent = MockAgentOperations::new();
+    let dir = tempfile::TempDir::new().unwrap();
+
+    mock_git.expect_checkout_worktree().never();
+    mock_git.expect_create_worktree().never();
+    mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+    mock_tmux.expect_create_window().times(1).returning(|_, _, _, _| Ok(()));
+    mock_agent.expect_build_resume_command().returning(|id| Some(format!("claude --resume {}", id)));
+
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.worktree_path = Some(dir.path().to_string_lossy().to_string());
+    task.agent_session_id = Some("sess-1".to_string());
+
+    let recreated = restore_task_resources(
+        &mut task, MissingResource::Window, Path::new("/project"), "main", "my-project", "go on",
+        None, None, &None, &WindowSetup::default(), &mock_tmux, &mock_git, &mock_agent,
+    )
+    .unwrap();
+
+    assert!(!recreated);
+    assert!(task.session_name.is_some());
+}
 
-    mock_git
-        .expect_create_worktree()
-        .returning(|_, slug| Ok(format!("/project/.agtx/worktrees/{}", slug)));
-    mock_git
-        .expect_initialize_worktree()
-        .returning(|_, _, _, _, _| vec![]);
-    mock_agent
-        .expect_build_interactive_command()
-        .returning(|prompt| format!("claude '{}'", prompt));
-    mock_tmux.expect_has_session().returning(|_| true);
+/// Aborting sends Ctrl-C to the task's window and nothing else
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_interrupt_task_agent_sends_interrupt() {
+    use crate::db::Task;
 
-    // Tmux window creation fails
+    let mut mock_tmux = MockTmuxOperations::new();
     mock_tmux
-        .expect_create_window()
-        .returning(|_, _, _, _| Err(anyhow::anyhow!("tmux not running")));
+        .expect_send_interrupt()
+        .withf(|target| target == "proj:task-1")
+        .times(1)
+        .returning(|_| Ok(()));
+    mock_tmux.expect_kill_window().never();
+    mock_tmux.expect_send_keys().never();
 
-    let mut task = Task::new("Test task", "claude", "project-1");
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.session_name = Some("proj:task-1".to_string());
 
-    let result = setup_task_worktree(
-        &mut task,
-        Path::new("/project"),
-        "my-project",
-        "do something",
-        None,
-        None,
-        &None,
-        "claude",
-        &mock_tmux,
-        &mock_git,
-        &mock_agent,
-    );
+    assert_eq!(interrupt_task_agent(&task, &mock_tmux).unwrap(), "proj:task-1");
+    assert_eq!(task.status, TaskStatus::Running);
+}
 
-    // Should propagate the error
-    assert!(result.is_err());
-    assert!(result.unwrap_err().to_string().contains("tmux not running"));
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_interrupt_task_agent_without_window() {
+    use crate::db::Task;
+
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_interrupt().never();
+
+    let task = Task::new("Fix", "claude", "project-1");
+    let err = interrupt_task_agent(&task, &mock_tmux).unwrap_err();
+    assert!(err.to_string().contains("no agent window"));
 }
 
-/// Test setup_task_worktree creates tmux session when missing
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_setup_task_worktree_creates_session_when_missing() {
+fn test_interrupt_task_agent_reports_tmux_error() {
     use crate::db::Task;
 
     let mut mock_tmux = MockTmuxOperations::new();
-    let mut mock_git = MockGitOperations::new();
-    let mut mock_agent = MockAgentOperations::new();
+    mock_tmux.expect_send_interrupt().returning(|_| Err(OpError::TmuxDisabled));
 
-    mock_git
-        .expect_create_worktree()
-        .returning(|_, slug| Ok(format!("/project/.agtx/worktrees/{}", slug)));
-    mock_git
-        .expect_initialize_worktree()
-        .returning(|_, _, _, _, _| vec![]);
-    mock_agent
-        .expect_build_interactive_command()
-        .returning(|prompt| format!("claude '{}'", prompt));
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.session_name = Some("proj:task-1".to_string());
+    let err = interrupt_task_agent(&task, &mock_tmux).unwrap_err();
+    assert!(format!("{:#}", err).contains("Interrupting the agent of 'Fix'"));
+}
 
-    // Session doesn't exist yet
-    mock_tmux
-        .expect_has_session()
-        .returning(|_| false);
-    mock_tmux
-        .expect_create_session()
-        .returning(|_, _| Ok(()));
-    mock_tmux
-        .expect_create_window()
-        .returning(|_, _, _, _| Ok(()));
+/// A usage summary on screen that differs from the recorded one is returned
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_capture_agent_usage_new_reading() {
+    use crate::db::{AgentUsage, Task};
 
-    let mut task = Task::new("New task", "claude", "project-1");
+    let reading = AgentUsage { tokens_in: 2_000, tokens_out: 300, cost_usd: Some(0.05) };
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux
+        .expect_capture_pane()
+        .withf(|target| target == "proj:task-1")
+        .returning(|_| Ok("Total cost: $0.05".to_string()));
+    let mut mock_agent = MockAgentOperations::new();
+    mock_agent.expect_parse_usage().returning(move |_| Some(reading));
 
-    let result = setup_task_worktree(
-        &mut task,
-        Path::new("/project"),
-        "my-project",
-        "do work",
-        None,
-        None,
-        &None,
-        "claude",
-        &mock_tmux,
-        &mock_git,
-        &mock_agent,
-    );
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.session_name = Some("proj:task-1".to_string());
+    let mut buffer = CaptureBuffer::new(100);
+    assert_eq!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent), Some(reading));
 
-    assert!(result.is_ok());
+    // The same reading again isn't an update
+    task.usage = Some(reading);
+    assert_eq!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent), None);
 }
 
-/// Test setup_task_worktree passes copy_files and init_script to initialize_worktree
+/// Unparseable output or a window that can't be read records nothing
 #[test]
 #[cfg(feature = "test-mocks")]
-fn test_setup_task_worktree_passes_init_config() {
+fn test_capture_agent_usage_records_nothing_on_failure() {
     use crate::db::Task;
 
     let mut mock_tmux = MockTmuxOperations::new();
-    let mut mock_git = MockGitOperations::new();
+    mock_tmux.expect_capture_pane().returning(|_| Ok("✻ Thinking…".to_string()));
     let mut mock_agent = MockAgentOperations::new();
+    mock_agent.expect_parse_usage().returning(|_| None);
 
-    mock_git
-        .expect_create_worktree()
-        .returning(|_, slug| Ok(format!("/project/.agtx/worktrees/{}", slug)));
+    let mut task = Task::new("Fix", "codex", "project-1");
+    task.session_name = Some("proj:task-1".to_string());
+    let mut buffer = CaptureBuffer::new(100);
+    assert_eq!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent), None);
 
-    // Verify copy_files and init_script are passed through
-    mock_git
-        .expect_initialize_worktree()
-        .withf(|_, _, copy_files, init_script, _copy_dirs| {
-            copy_files.as_deref() == Some("CLAUDE.md,.env")
-                && init_script.as_deref() == Some("./setup.sh")
-        })
-        .returning(|_, _, _, _, _| vec!["warning: .env not found".to_string()]);
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_capture_pane().returning(|_| Err(OpError::TmuxDisabled));
+    let mut mock_agent = MockAgentOperations::new();
+    mock_agent.expect_parse_usage().never();
+    assert_eq!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent), None);
 
-    mock_agent
-        .expect_build_interactive_command()
-        .returning(|prompt| format!("claude '{}'", prompt));
-    mock_tmux.expect_has_session().returning(|_| true);
-    mock_tmux.expect_create_window().returning(|_, _, _, _| Ok(()));
+    // No window, no capture
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_capture_pane().never();
+    task.session_name = None;
+    assert_eq!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent), None);
+}
 
-    let mut task = Task::new("Task with config", "claude", "project-1");
+/// A summary that has scrolled off screen is still read from the buffered output
+#[test]
+#[cfg(feature = "test-mocks")]
+fn test_capture_agent_usage_reads_buffered_output() {
+    use crate::db::{AgentUsage, Task};
 
-    let result = setup_task_worktree(
-        &mut task,
-        Path::new("/project"),
-        "my-project",
-        "implement feature",
-        Some("CLAUDE.md,.env".to_string()),
-        Some("./setup.sh".to_string()),
-        &None,
-        "claude",
-        &mock_tmux,
-        &mock_git,
-        &mock_agent,
-    );
+    let screens = std::sync::Mutex::new(vec!["Total cost: $0.05\nDone".to_string(), "Done\n> next".to_string()]);
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_capture_pane().returning(move |_| Ok(screens.lock().unwrap().remove(0)));
+    let mut mock_agent = MockAgentOperations::new();
+    mock_agent
+        .expect_parse_usage()
+        .returning(|output| output.contains("Total cost").then_some(AgentUsage { cost_usd: Some(0.05), ..Default::default() }));
+
+    let mut task = Task::new("Fix", "claude", "project-1");
+    task.session_name = Some("proj:task-1".to_string());
+    let mut buffer = CaptureBuffer::new(100);
+    assert!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent).is_some());
+    task.usage = None;
+    assert!(capture_agent_usage(&task, &mut buffer, &mock_tmux, &mock_agent).is_some());
+    assert_eq!(buffer.text(), "Total cost: $0.05\nDone\n> next");
+}
 
-    assert!(result.is_ok());
+#[test]
+fn test_format_usage() {
+    use crate::db::AgentUsage;
+
+    let usage = AgentUsage { tokens_in: 12_300, tokens_out: 4_500, cost_usd: Some(0.254) };
+    assert_eq!(format_usage(&usage), "12.3k in / 4.5k out, ~$0.25");
+    let usage = AgentUsage { tokens_in: 800, tokens_out: 20, cost_usd: None };
+    assert_eq!(format_usage(&usage), "800 in / 20 out");
 }
 
 // ── Agent-Native Skill Discovery Tests ──────────────────────────────────────
@@ -1766,10 +4105,77 @@ fn test_transform_skill_frontmatter_no_agtx() {
     assert_eq!(output, input);
 }
 
+/// A task whose `{task}` renders as `title`
+fn prompt_task(title: &str, id: &str) -> Task {
+    let mut task = Task::new(title, "claude", "project-1");
+    task.id = id.to_string();
+    task
+}
+
+#[test]
+fn test_render_prompt_placeholders() {
+    let mut task = prompt_task("Add login", "task-123");
+    task.description = Some("Use OAuth".to_string());
+
+    let rendered = skills::render_prompt("{title}|{description}|{task}|{task_id}|{worktree}|{other}", &task, "/tmp/wt");
+
+    assert_eq!(rendered, "Add login|Use OAuth|Add login\n\nUse OAuth|task-123|/tmp/wt|{other}");
+}
+
+#[test]
+fn test_render_prompt_without_description_or_worktree() {
+    let task = prompt_task("Add login", "task-123");
+
+    let rendered = skills::render_prompt("Task: {task} [{description}] in {worktree}", &task, "");
+
+    // {worktree} stays for setup_task_worktree to fill once the worktree exists
+    assert_eq!(rendered, "Task: Add login [] in {worktree}");
+}
+
+#[test]
+fn test_render_prompt_does_not_expand_placeholders_inside_task_text() {
+    let task = prompt_task("Document the {title} placeholder", "task-123");
+
+    assert_eq!(skills::render_prompt("{title}", &task, "/tmp/wt"), "Document the {title} placeholder");
+}
+
+/// A column's on-enter prompt goes to the task's window, with placeholders filled in
+#[test]
+fn test_on_enter_dispatch() {
+    let global: GlobalConfig =
+        toml::from_str("[columns.review]\non_enter_prompt = \"check {title} in {worktree}\"").unwrap();
+    let config = MergedConfig::merge(&global, &ProjectConfig::default());
+    let mut task = prompt_task("Fix login", "task-1");
+    task.session_name = Some("proj:task-fix".to_string());
+    task.worktree_path = Some("/wt/fix".to_string());
+
+    assert_eq!(
+        on_enter_dispatch(&config, &task, TaskStatus::Review),
+        Some(("proj:task-fix".to_string(), "check Fix login in /wt/fix".to_string()))
+    );
+    // No prompt for the column, or no window to send it to
+    assert_eq!(on_enter_dispatch(&config, &task, TaskStatus::Running), None);
+    task.session_name = None;
+    assert_eq!(on_enter_dispatch(&config, &task, TaskStatus::Review), None);
+}
+
+#[test]
+fn test_on_enter_dispatch_skips_columns_without_dispatch() {
+    let global: GlobalConfig = toml::from_str(
+        "[columns.review]\non_enter_prompt = \"check {title}\"\ndispatch_agent = false",
+    )
+    .unwrap();
+    let config = MergedConfig::merge(&global, &ProjectConfig::default());
+    let mut task = prompt_task("Fix login", "task-1");
+    task.session_name = Some("proj:task-fix".to_string());
+
+    assert_eq!(on_enter_dispatch(&config, &task, TaskStatus::Review), None);
+}
+
 #[test]
 fn test_resolve_prompt_claude_no_skill_ref() {
     // Claude has skill invocation support — prompt should NOT contain skill reference
-    let prompt = resolve_prompt(&None, "planning", "my task", "task-123", "claude");
+    let prompt = resolve_prompt(&None, &PromptTemplates::default(), "planning", &prompt_task("my task", "task-123"), "", "claude");
     assert!(prompt.contains("my task"));
     assert!(!prompt.contains("/agtx:plan"));
     assert!(!prompt.contains("SKILL.md"));
@@ -1778,14 +4184,14 @@ fn test_resolve_prompt_claude_no_skill_ref() {
 #[test]
 fn test_resolve_prompt_copilot_has_file_path() {
     // Copilot has no skill invocation — prompt should contain file-path reference
-    let prompt = resolve_prompt(&None, "planning", "my task", "task-123", "copilot");
+    let prompt = resolve_prompt(&None, &PromptTemplates::default(), "planning", &prompt_task("my task", "task-123"), "", "copilot");
     assert!(prompt.contains("my task"));
     assert!(prompt.contains(".agtx/skills/agtx-plan/SKILL.md"));
 }
 
 #[test]
 fn test_resolve_prompt_research_has_task_id() {
-    let prompt = resolve_prompt(&None, "research", "my task", "abc-123", "claude");
+    let prompt = resolve_prompt(&None, &PromptTemplates::default(), "research", &prompt_task("my task", "abc-123"), "", "claude");
     assert!(prompt.contains("abc-123"));
     // Claude should NOT have skill ref in prompt (sent via send_keys)
     assert!(!prompt.contains("/agtx:research"));
@@ -1793,18 +4199,141 @@ fn test_resolve_prompt_research_has_task_id() {
 
 #[test]
 fn test_resolve_prompt_running_phase() {
-    let prompt = resolve_prompt(&None, "running", "my task", "task-123", "claude");
+    let prompt = resolve_prompt(&None, &PromptTemplates::default(), "running", &prompt_task("my task", "task-123"), "", "claude");
     assert!(prompt.contains("Plan approved"));
     assert!(!prompt.contains("SKILL.md"));
 }
 
 #[test]
 fn test_resolve_prompt_review_phase() {
-    let prompt = resolve_prompt(&None, "review", "my task", "task-123", "copilot");
+    let prompt = resolve_prompt(&None, &PromptTemplates::default(), "review", &prompt_task("my task", "task-123"), "", "copilot");
     assert!(prompt.contains("Implementation complete"));
     assert!(prompt.contains(".agtx/skills/agtx-review/SKILL.md"));
 }
 
+#[test]
+fn test_resolve_prompt_config_template_overrides_plugin_and_default() {
+    use crate::config::WorkflowPlugin;
+    let plugin: WorkflowPlugin = toml::from_str(r#"
+        name = "gsd"
+        [prompts]
+        planning = "Plugin plan: {task}"
+        running = "Plugin run"
+    "#).unwrap();
+    let overrides = PromptTemplates {
+        planning: Some("Plan '{title}' in {worktree}: {description}".to_string()),
+        ..Default::default()
+    };
+    let mut task = prompt_task("Add login", "task-123");
+    task.description = Some("Use OAuth".to_string());
+
+    let plugin = Some(plugin);
+    let planning = resolve_prompt(&plugin, &overrides, "planning", &task, "/tmp/wt", "claude");
+    assert_eq!(planning, "Plan 'Add login' in /tmp/wt: Use OAuth");
+
+    // Phases without a configured template fall back to the plugin, then the default
+    assert_eq!(resolve_prompt(&plugin, &overrides, "running", &task, "/tmp/wt", "claude"), "Plugin run");
+    assert_eq!(
+        resolve_prompt(&None, &overrides, "review", &task, "/tmp/wt", "claude"),
+        skills::DEFAULT_PROMPT_REVIEW
+    );
+}
+
+#[test]
+fn test_resolve_prompt_wraps_templates_in_prefix_and_suffix() {
+    use crate::config::WorkflowPlugin;
+    let plugin: WorkflowPlugin = toml::from_str(r#"
+        name = "void"
+        [prompts]
+        planning = ""
+        running = "Plugin run {title}"
+    "#).unwrap();
+    let prompts = PromptTemplates {
+        planning: Some("Plan {title}".to_string()),
+        prefix: Some("House style: run clippy.".to_string()),
+        suffix: Some("Commit when done.".to_string()),
+        ..Default::default()
+    };
+    let task = prompt_task("Add login", "task-123");
+
+    assert_eq!(
+        resolve_prompt(&None, &prompts, "planning", &task, "", "claude"),
+        "House style: run clippy.\n\nPlan Add login\n\nCommit when done."
+    );
+    let plugin = Some(plugin);
+    assert_eq!(
+        resolve_prompt(&plugin, &prompts, "running", &task, "", "claude"),
+        "House style: run clippy.\n\nPlugin run Add login\n\nCommit when done."
+    );
+    // A phase that sends nothing still sends nothing
+    let prompts = PromptTemplates { planning: None, ..prompts };
+    assert_eq!(resolve_prompt(&plugin, &prompts, "planning", &task, "", "claude"), "");
+}
+
+/// Test that the prefix and suffix reach agents that advance by skill command
+#[test]
+fn test_prefix_and_suffix_follow_skill_commands() {
+    let prompts = PromptTemplates {
+        prefix: Some("House style: run clippy.".to_string()),
+        suffix: Some("Commit when done.".to_string()),
+        ..Default::default()
+    };
+    let task = prompt_task("Add login", "task-123");
+
+    assert_eq!(
+        phase_command(&None, &prompts, "running", &task, "claude"),
+        "/agtx:execute House style: run clippy.\n\nCommit when done."
+    );
+    assert_eq!(
+        phase_command(&None, &prompts, "review", &task, "codex"),
+        "$agtx-review House style: run clippy.\n\nCommit when done."
+    );
+    // Nothing to add: the skill command goes out alone
+    assert_eq!(phase_command(&None, &PromptTemplates::default(), "running", &task, "claude"), "/agtx:execute");
+    // Without a skill command the phase prompt is sent, wrapped
+    let command = phase_command(&None, &prompts, "running", &task, "copilot");
+    assert!(command.starts_with("House style: run clippy.\n\n"));
+    assert!(command.ends_with("\n\nCommit when done."));
+
+    let start = PhaseStart::for_phase(&None, &prompts, "planning", &task, "gemini", "proj:task".to_string());
+    assert_eq!(start.skill_cmd.as_deref(), Some("/agtx:plan"));
+    assert!(start.prompt.starts_with("House style: run clippy.\n\n"));
+}
+
+/// Test that a configured `[prompts]` template isn't shadowed by the phase's skill command
+#[test]
+fn test_configured_prompt_follows_skill_command() {
+    let prompts = PromptTemplates {
+        running: Some("Implement {title}; keep the diff small.".to_string()),
+        suffix: Some("Commit when done.".to_string()),
+        ..Default::default()
+    };
+    let task = prompt_task("Add login", "task-123");
+
+    assert_eq!(
+        phase_command(&None, &prompts, "running", &task, "claude"),
+        "/agtx:execute Implement Add login; keep the diff small.\n\nCommit when done."
+    );
+    // Other phases only carry the suffix
+    assert_eq!(phase_command(&None, &prompts, "review", &task, "claude"), "/agtx:review Commit when done.");
+    // An empty template adds nothing
+    let prompts = PromptTemplates { running: Some(String::new()), ..Default::default() };
+    assert_eq!(phase_command(&None, &prompts, "running", &task, "claude"), "/agtx:execute");
+}
+
+#[test]
+fn test_resolve_prompt_defaults_match_builtin_prompts() {
+    let mut task = prompt_task("Add login", "task-123");
+    task.description = Some("Use OAuth".to_string());
+    let none = PromptTemplates::default();
+
+    assert_eq!(resolve_prompt(&None, &none, "planning", &task, "", "claude"), "Task: Add login\n\nUse OAuth");
+    assert_eq!(
+        resolve_prompt(&None, &none, "running", &task, "", "claude"),
+        "Plan approved. Implement the changes described in .agtx/plan.md"
+    );
+}
+
 #[test]
 fn test_resolve_skill_command_no_plugin() {
     // No plugin: falls back to agent-native skill invocation
@@ -2167,7 +4696,7 @@ fn test_resolve_prompt_empty_for_gsd_planning() {
         [artifacts]
     "#;
     let plugin: WorkflowPlugin = toml::from_str(plugin_toml).unwrap();
-    let prompt = resolve_prompt(&Some(plugin), "planning", "my task content", "task-123", "claude");
+    let prompt = resolve_prompt(&Some(plugin), &PromptTemplates::default(), "planning", &prompt_task("my task content", "task-123"), "", "claude");
     assert!(prompt.is_empty());
 }
 
@@ -2183,7 +4712,7 @@ fn test_resolve_prompt_research_with_task() {
         [artifacts]
     "#;
     let plugin: WorkflowPlugin = toml::from_str(plugin_toml).unwrap();
-    let prompt = resolve_prompt(&Some(plugin), "research", "add tests", "task-123", "claude");
+    let prompt = resolve_prompt(&Some(plugin), &PromptTemplates::default(), "research", &prompt_task("add tests", "task-123"), "", "claude");
     assert_eq!(prompt, "Task: add tests");
 }
 
@@ -1,4 +1,10 @@
-use crate::db::{Task, TaskStatus};
+use std::cell::Cell;
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+
+use crate::config::{StalenessConfig, TaskFilter, View};
+use crate::db::{is_blocked, AgentUsage, Task, TaskId, TaskStatus};
 
 /// State for the kanban board view
 #[derive(Debug)]
@@ -6,6 +12,19 @@ pub struct BoardState {
     pub tasks: Vec<Task>,
     pub selected_column: usize,
     pub selected_row: usize,
+    /// Include archived tasks in the columns
+    pub show_archived: bool,
+    /// Ids of tasks marked (Space) for a bulk move
+    pub marked: HashSet<String>,
+    /// Saved view limiting which tasks are shown (`None` = all)
+    pub view: Option<View>,
+    /// Only show tasks of this agent (`None` = all agents), on top of the view
+    pub agent_filter: Option<String>,
+    /// Columns drawn as a thin bar (name and count only), kept for the session
+    pub collapsed: HashSet<usize>,
+    /// First shown row of each column. Cells because drawing, which keeps the selection in
+    /// view, only borrows the state.
+    scroll: Vec<Cell<usize>>,
 }
 
 impl BoardState {
@@ -14,18 +33,52 @@ impl BoardState {
             tasks: vec![],
             selected_column: 0,
             selected_row: 0,
+            show_archived: false,
+            marked: HashSet::new(),
+            view: None,
+            agent_filter: None,
+            collapsed: HashSet::new(),
+            scroll: vec![Cell::new(0); TaskStatus::columns().len()],
         }
     }
 
-    /// Get tasks in a specific column
+    pub fn scroll_offset(&self, column: usize) -> usize {
+        self.scroll.get(column).map_or(0, Cell::get)
+    }
+
+    pub fn set_scroll_offset(&self, column: usize, offset: usize) {
+        if let Some(cell) = self.scroll.get(column) {
+            cell.set(offset);
+        }
+    }
+
+    /// Get tasks in a specific column, in display order (see `sort_column`)
     pub fn tasks_in_column(&self, column: usize) -> Vec<&Task> {
         let status = TaskStatus::columns().get(column).copied();
         match status {
-            Some(s) => self.tasks.iter().filter(|t| t.status == s).collect(),
+            Some(s) => {
+                let agent = self.agent_filter.clone().map(TaskFilter::Agent);
+                let mut tasks: Vec<&Task> = visible_tasks(&self.tasks, self.show_archived)
+                    .into_iter()
+                    .filter(|t| t.status == s)
+                    .filter(|t| self.view.as_ref().is_none_or(|v| v.filter.matches(t)))
+                    .filter(|t| agent.as_ref().is_none_or(|f| f.matches(t)))
+                    .collect();
+                sort_column(&mut tasks);
+                tasks
+            }
             None => vec![],
         }
     }
 
+    /// Row of a task in its column, if it's shown
+    pub fn row_of(&self, task_id: &str) -> Option<(usize, usize)> {
+        (0..TaskStatus::columns().len()).find_map(|column| {
+            let row = self.tasks_in_column(column).iter().position(|t| t.id == task_id)?;
+            Some((column, row))
+        })
+    }
+
     /// Get the currently selected task (immutable)
     pub fn selected_task(&self) -> Option<&Task> {
         let column_tasks = self.tasks_in_column(self.selected_column);
@@ -34,54 +87,79 @@ impl BoardState {
 
     /// Get the currently selected task (mutable)
     pub fn selected_task_mut(&mut self) -> Option<&mut Task> {
-        let status = TaskStatus::columns()
-            .get(self.selected_column)
-            .copied()?;
+        let id = self.selected_task()?.id.clone();
+        self.tasks.iter_mut().find(|t| t.id == id)
+    }
 
-        let mut matching_indices: Vec<usize> = self
-            .tasks
-            .iter()
-            .enumerate()
-            .filter(|(_, t)| t.status == status)
-            .map(|(i, _)| i)
-            .collect();
+    /// Collapse or expand a column. The last expanded column can't be collapsed; returns
+    /// whether anything changed.
+    pub fn toggle_collapsed(&mut self, column: usize) -> bool {
+        if column >= TaskStatus::columns().len() {
+            return false;
+        }
+        if self.collapsed.remove(&column) {
+            return true;
+        }
+        if self.collapsed.len() + 1 >= TaskStatus::columns().len() {
+            return false;
+        }
+        self.collapsed.insert(column)
+    }
+
+    /// Collapsed flag per column, in `TaskStatus::columns()` order
+    pub fn collapsed_flags(&self) -> Vec<bool> {
+        (0..TaskStatus::columns().len()).map(|c| self.collapsed.contains(&c)).collect()
+    }
+
+    /// Mark or unmark the selected task for a bulk move
+    pub fn toggle_mark_selected(&mut self) {
+        let Some(id) = self.selected_task().map(|t| t.id.clone()) else { return };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+    }
+
+    /// Marked tasks still visible on the board, in board order (column by column)
+    pub fn marked_tasks(&self) -> Vec<&Task> {
+        (0..TaskStatus::columns().len())
+            .flat_map(|column| self.tasks_in_column(column))
+            .filter(|t| self.marked.contains(&t.id))
+            .collect()
+    }
 
-        matching_indices.get(self.selected_row).and_then(|&idx| self.tasks.get_mut(idx))
+    /// Move the selection one step (see `move_selection`). A collapsed column the selection
+    /// moves into is expanded, so the selected card is always on screen.
+    pub fn move_selection(&mut self, dir: NavDirection) {
+        let selection = move_selection(self, dir);
+        if selection.column != self.selected_column {
+            self.collapsed.remove(&selection.column);
+        }
+        self.selected_column = selection.column;
+        self.selected_row = selection.row;
     }
 
     /// Move selection left
     pub fn move_left(&mut self) {
-        if self.selected_column > 0 {
-            self.selected_column -= 1;
-            self.clamp_row();
-        }
+        self.move_selection(NavDirection::Left);
     }
 
     /// Move selection right
     pub fn move_right(&mut self) {
-        if self.selected_column < TaskStatus::columns().len() - 1 {
-            self.selected_column += 1;
-            self.clamp_row();
-        }
+        self.move_selection(NavDirection::Right);
     }
 
     /// Move selection up
     pub fn move_up(&mut self) {
-        if self.selected_row > 0 {
-            self.selected_row -= 1;
-        }
+        self.move_selection(NavDirection::Up);
     }
 
     /// Move selection down
     pub fn move_down(&mut self) {
-        let column_count = self.tasks_in_column(self.selected_column).len();
-        if self.selected_row < column_count.saturating_sub(1) {
-            self.selected_row += 1;
-        }
+        self.move_selection(NavDirection::Down);
     }
 
     /// Ensure selected_row is valid for current column
-    fn clamp_row(&mut self) {
+    pub fn clamp_row(&mut self) {
         let column_count = self.tasks_in_column(self.selected_column).len();
         if column_count == 0 {
             self.selected_row = 0;
@@ -96,3 +174,268 @@ impl Default for BoardState {
         Self::new()
     }
 }
+
+/// First row to show in a column of `len` cards with room for `visible`: the previous offset,
+/// clamped to the end of the column and moved just enough to keep `selected` in view
+pub fn scroll_offset(previous: usize, selected: Option<usize>, visible: usize, len: usize) -> usize {
+    if visible == 0 || len <= visible {
+        return 0;
+    }
+    let offset = previous.min(len - visible);
+    match selected {
+        Some(row) if row < offset => row,
+        Some(row) if row >= offset + visible => row + 1 - visible,
+        _ => offset,
+    }
+}
+
+/// A step of board navigation (`h/j/k/l` or the arrow keys)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A column/row position on the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub column: usize,
+    pub row: usize,
+}
+
+/// Where the selection lands after one step. Moving between columns keeps the row, or the
+/// last task when the new column is shorter; edges and empty columns stop the step.
+pub fn move_selection(board: &BoardState, dir: NavDirection) -> Selection {
+    let (column, row) = (board.selected_column, board.selected_row);
+    let last_column = TaskStatus::columns().len() - 1;
+    let column_len = |c: usize| board.tasks_in_column(c).len();
+    match dir {
+        NavDirection::Left | NavDirection::Right => {
+            let target = match dir {
+                NavDirection::Left => column.saturating_sub(1),
+                _ => (column + 1).min(last_column),
+            };
+            Selection { column: target, row: row.min(column_len(target).saturating_sub(1)) }
+        }
+        NavDirection::Up => Selection { column, row: row.saturating_sub(1) },
+        NavDirection::Down => Selection { column, row: (row + 1).min(column_len(column).saturating_sub(1)) },
+    }
+}
+
+/// Width of a collapsed column: borders plus room for the count
+pub const COLLAPSED_COLUMN_WIDTH: u16 = 5;
+
+/// Widths of the board columns for a board `total_width` wide. Collapsed columns get
+/// `COLLAPSED_COLUMN_WIDTH` (less if the board is narrower than that); the rest is split
+/// evenly between expanded columns, with leftover cells going to the `focused` column (to the
+/// leftmost expanded ones when it's collapsed).
+pub fn column_widths(total_width: u16, collapsed: &[bool], focused: usize) -> Vec<u16> {
+    let collapsed_count = collapsed.iter().filter(|c| **c).count() as u16;
+    let expanded_count = collapsed.len() as u16 - collapsed_count;
+    let bar_width = if expanded_count == 0 {
+        total_width / collapsed_count.max(1)
+    } else {
+        COLLAPSED_COLUMN_WIDTH.min(total_width / collapsed_count.max(1))
+    };
+    let remaining = total_width.saturating_sub(bar_width * collapsed_count);
+    let (share, mut extra) = match expanded_count {
+        0 => (0, 0),
+        n => (remaining / n, remaining % n),
+    };
+    let focused_extra = if collapsed.get(focused) == Some(&false) { std::mem::take(&mut extra) } else { 0 };
+    collapsed
+        .iter()
+        .enumerate()
+        .map(|(column, &is_collapsed)| {
+            if is_collapsed {
+                bar_width
+            } else if column == focused {
+                share + focused_extra
+            } else if extra > 0 {
+                extra -= 1;
+                share + 1
+            } else {
+                share
+            }
+        })
+        .collect()
+}
+
+/// Order a column for display: pinned tasks first, otherwise the order tasks were loaded in
+/// (creation time). The sort is stable, so pinning never reshuffles the rest.
+pub fn sort_column(tasks: &mut [&Task]) {
+    tasks.sort_by_key(|t| !t.pinned);
+}
+
+/// Tasks shown on the board: archived tasks are hidden unless `show_archived` is set
+pub fn visible_tasks(tasks: &[Task], show_archived: bool) -> Vec<&Task> {
+    tasks.iter().filter(|t| show_archived || !t.archived).collect()
+}
+
+/// Most tasks listed by usage in the stats popup
+pub const STATS_TOP_USAGE: usize = 5;
+
+/// Summary numbers for the board stats popup
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BoardStats {
+    /// Task count per column, in `TaskStatus::columns()` order
+    pub per_status: Vec<(TaskStatus, usize)>,
+    /// Total number of tasks on the board
+    pub total: usize,
+    /// Tasks that currently own a git worktree
+    pub active_worktrees: usize,
+    /// Tasks with a tmux window recorded (the window may no longer be alive)
+    pub sessions: usize,
+    /// Agent usage summed over every task that has some (cost only over those that show one)
+    pub usage: AgentUsage,
+    /// Tasks with recorded usage, most tokens first, at most `STATS_TOP_USAGE`
+    pub top_usage: Vec<(String, AgentUsage)>,
+}
+
+impl BoardStats {
+    /// Number of tasks in a given column
+    pub fn count(&self, status: TaskStatus) -> usize {
+        self.per_status
+            .iter()
+            .find(|(s, _)| *s == status)
+            .map_or(0, |(_, n)| *n)
+    }
+
+    /// Tasks that have been started but are not finished (Planning, Running, Review)
+    pub fn in_flight(&self) -> usize {
+        self.count(TaskStatus::Planning) + self.count(TaskStatus::Running) + self.count(TaskStatus::Review)
+    }
+}
+
+/// Compute board statistics from a list of tasks.
+/// Pure function so it can be tested without a terminal or tmux.
+pub fn board_stats(tasks: &[Task]) -> BoardStats {
+    let per_status = TaskStatus::columns()
+        .iter()
+        .map(|status| (*status, tasks.iter().filter(|t| t.status == *status).count()))
+        .collect();
+
+    let mut with_usage: Vec<(&Task, AgentUsage)> = tasks.iter().filter_map(|t| Some((t, t.usage?))).collect();
+    let usage = with_usage.iter().fold(AgentUsage::default(), |total, (_, u)| AgentUsage {
+        tokens_in: total.tokens_in + u.tokens_in,
+        tokens_out: total.tokens_out + u.tokens_out,
+        cost_usd: match (total.cost_usd, u.cost_usd) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        },
+    });
+    with_usage.sort_by_key(|(_, u)| std::cmp::Reverse(u.tokens()));
+
+    BoardStats {
+        per_status,
+        total: tasks.len(),
+        active_worktrees: tasks.iter().filter(|t| t.worktree_path.is_some()).count(),
+        sessions: tasks.iter().filter(|t| t.session_name.is_some()).count(),
+        usage,
+        top_usage: with_usage
+            .into_iter()
+            .take(STATS_TOP_USAGE)
+            .map(|(t, u)| (t.title.clone(), u))
+            .collect(),
+    }
+}
+
+/// How long a task has sat in its current column, as a card tint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Staleness {
+    Fresh,
+    /// Past `stale_hours`: yellow
+    Stale,
+    /// Past `very_stale_hours`: red
+    VeryStale,
+}
+
+/// Staleness of a task at `now`, from when it entered its column (its creation, if it never
+/// moved). Backlog and Done are queues and archives rather than work in progress, so their
+/// tasks are always fresh, as is everything when tints are turned off.
+pub fn staleness(task: &Task, now: DateTime<Utc>, config: &StalenessConfig) -> Staleness {
+    if !config.enabled || task.archived || matches!(task.status, TaskStatus::Backlog | TaskStatus::Done) {
+        return Staleness::Fresh;
+    }
+    let since = task.status_changed_at.unwrap_or(task.created_at);
+    let hours = (now - since).num_hours().max(0) as u64;
+    if hours >= config.very_stale_hours.max(config.stale_hours) {
+        Staleness::VeryStale
+    } else if hours >= config.stale_hours {
+        Staleness::Stale
+    } else {
+        Staleness::Fresh
+    }
+}
+
+/// How urgently a task needs the user, most urgent first (see `next_actionable`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Actionability {
+    /// The agent exited with a non-zero code
+    Errored,
+    /// The agent exited cleanly and the task is waiting to be advanced
+    Finished,
+    /// Waiting for review
+    Review,
+    /// In Backlog with nothing blocking it, waiting to be started
+    Waiting,
+}
+
+/// Whether (and how urgently) a task needs the user. Tasks whose agent is still working,
+/// blocked Backlog tasks, Done tasks and archived tasks don't.
+pub fn actionability(task: &Task, tasks: &[Task]) -> Option<Actionability> {
+    if task.archived || task.status == TaskStatus::Done {
+        return None;
+    }
+    match (task.status, task.last_exit_code) {
+        (_, Some(code)) if code != 0 => Some(Actionability::Errored),
+        (TaskStatus::Planning | TaskStatus::Running, Some(_)) => Some(Actionability::Finished),
+        (TaskStatus::Review, _) => Some(Actionability::Review),
+        (TaskStatus::Backlog, _) if !is_blocked(task, tasks) => Some(Actionability::Waiting),
+        _ => None,
+    }
+}
+
+/// Tasks needing attention, most urgent first; ties go to the task untouched the longest
+pub fn actionable_tasks(tasks: &[Task]) -> Vec<&Task> {
+    let mut ranked: Vec<(Actionability, &Task)> =
+        tasks.iter().filter_map(|t| actionability(t, tasks).map(|a| (a, t))).collect();
+    ranked.sort_by_key(|(a, t)| (*a, t.updated_at));
+    ranked.into_iter().map(|(_, t)| t).collect()
+}
+
+/// The task needing attention most (see `Actionability`)
+pub fn next_actionable(tasks: &[Task]) -> Option<TaskId> {
+    next_actionable_after(tasks, None)
+}
+
+/// The actionable task after `current` in `actionable_tasks` order, wrapping around, so
+/// repeated jumps cycle through every candidate. Starts from the top when `current` isn't one.
+pub fn next_actionable_after(tasks: &[Task], current: Option<&str>) -> Option<TaskId> {
+    let ranked = actionable_tasks(tasks);
+    let start = current
+        .and_then(|id| ranked.iter().position(|t| t.id == id))
+        .map_or(0, |i| i + 1);
+    ranked.get(start % ranked.len().max(1)).map(|t| t.id.clone())
+}
+
+/// Agents tasks on the board are assigned to, sorted and without duplicates
+pub fn board_agents(tasks: &[Task]) -> Vec<&str> {
+    let mut agents: Vec<&str> = tasks.iter().map(|t| t.agent.as_str()).filter(|a| !a.is_empty()).collect();
+    agents.sort_unstable();
+    agents.dedup();
+    agents
+}
+
+/// The agent filter after `current` when cycling: all agents → first → ... → last → all agents.
+/// A filter for an agent no task has any more goes back to all agents.
+pub fn next_agent_filter(tasks: &[Task], current: Option<&str>) -> Option<String> {
+    let agents = board_agents(tasks);
+    let next = match current {
+        None => 0,
+        Some(agent) => agents.iter().position(|a| *a == agent).map_or(agents.len(), |i| i + 1),
+    };
+    agents.get(next).map(|a| a.to_string())
+}
@@ -7,6 +7,8 @@ pub enum InputMode {
     InputTitle,
     /// Entering task description/prompt
     InputDescription,
+    /// Filling in the new-task modal (see `task_form`)
+    NewTask,
 }
 
 impl Default for InputMode {
@@ -0,0 +1,561 @@
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::db::TaskStatus;
+
+/// Board actions that can be remapped via the `[keybindings]` config section
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Advance the selected task to the next column
+    MoveForward,
+    /// Move a Running task back to Planning
+    MoveBack,
+    /// Move a Review task back to Running
+    Resume,
+    /// Delete the selected task
+    Delete,
+    /// Create a new task
+    NewTask,
+    /// Open the task search popup
+    Search,
+    /// Attach the terminal to the selected task's tmux window
+    Attach,
+    /// Switch to the next saved view (`[[views]]`)
+    CycleView,
+    /// Show only the tasks of the next agent on the board, wrapping back to all agents
+    CycleAgent,
+    /// Collapse or expand the selected column
+    ToggleCollapse,
+    /// Pin the selected task to the top of its column, or unpin it
+    TogglePin,
+    /// Copy the selected task into a new Backlog task
+    Duplicate,
+    /// Select the next task needing attention (see `board::next_actionable`)
+    NextActionable,
+    /// Interrupt the selected task's agent, keeping the task, window and worktree
+    Abort,
+    /// Pause or resume everything agtx does on its own (watchers, reconciliation, the queue)
+    ToggleAutomation,
+    /// Move the selected task straight to Done from any column, after a confirmation
+    Abandon,
+    /// Select the column to the left (arrow keys always work too)
+    SelectLeft,
+    /// Select the column to the right
+    SelectRight,
+    /// Select the task above
+    SelectUp,
+    /// Select the task below
+    SelectDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 20] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::Resume,
+        Action::Delete,
+        Action::NewTask,
+        Action::Search,
+        Action::Attach,
+        Action::CycleView,
+        Action::CycleAgent,
+        Action::ToggleCollapse,
+        Action::TogglePin,
+        Action::Duplicate,
+        Action::NextActionable,
+        Action::Abort,
+        Action::ToggleAutomation,
+        Action::Abandon,
+        Action::SelectLeft,
+        Action::SelectRight,
+        Action::SelectUp,
+        Action::SelectDown,
+    ];
+
+    /// Config name of the action (as used in `[keybindings]`)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "move_forward",
+            Action::MoveBack => "move_back",
+            Action::Resume => "resume",
+            Action::Delete => "delete",
+            Action::NewTask => "new_task",
+            Action::Search => "search",
+            Action::Attach => "attach",
+            Action::CycleView => "cycle_view",
+            Action::CycleAgent => "cycle_agent",
+            Action::ToggleCollapse => "toggle_collapse",
+            Action::TogglePin => "toggle_pin",
+            Action::Duplicate => "duplicate",
+            Action::NextActionable => "next_actionable",
+            Action::Abort => "abort",
+            Action::ToggleAutomation => "toggle_automation",
+            Action::Abandon => "abandon",
+            Action::SelectLeft => "select_left",
+            Action::SelectRight => "select_right",
+            Action::SelectUp => "select_up",
+            Action::SelectDown => "select_down",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    /// Key the action gets under a preset before `[keybindings]` overrides
+    fn preset_key(&self, preset: Preset) -> KeyBinding {
+        match (preset, self.arrow_key()) {
+            (Preset::Arrows, Some(code)) => KeyBinding { code, modifiers: KeyModifiers::NONE },
+            _ => self.default_key(),
+        }
+    }
+
+    /// Arrow key that always triggers a selection action
+    fn arrow_key(&self) -> Option<KeyCode> {
+        match self {
+            Action::SelectLeft => Some(KeyCode::Left),
+            Action::SelectRight => Some(KeyCode::Right),
+            Action::SelectUp => Some(KeyCode::Up),
+            Action::SelectDown => Some(KeyCode::Down),
+            _ => None,
+        }
+    }
+
+    /// Default key, matching the bindings the board has always used
+    fn default_key(&self) -> KeyBinding {
+        KeyBinding::char(match self {
+            Action::MoveForward => 'm',
+            Action::MoveBack => 'r',
+            Action::Resume => 'r',
+            Action::Delete => 'x',
+            Action::NewTask => 'o',
+            Action::Search => '/',
+            Action::Attach => 'a',
+            Action::CycleView => 'V',
+            Action::CycleAgent => 'G',
+            Action::ToggleCollapse => 'c',
+            Action::TogglePin => 'f',
+            Action::Duplicate => 'y',
+            Action::NextActionable => 'g',
+            Action::Abort => 'C',
+            Action::ToggleAutomation => 'H',
+            Action::Abandon => 'X',
+            Action::SelectLeft => 'h',
+            Action::SelectRight => 'l',
+            Action::SelectUp => 'k',
+            Action::SelectDown => 'j',
+        })
+    }
+
+    /// Help overlay section the action is listed in
+    pub fn category(&self) -> HelpCategory {
+        match self {
+            Action::SelectLeft | Action::SelectRight | Action::SelectUp | Action::SelectDown => HelpCategory::Navigation,
+            Action::NextActionable | Action::Search => HelpCategory::Navigation,
+            Action::MoveForward | Action::MoveBack | Action::Resume | Action::Abort | Action::Abandon => {
+                HelpCategory::Transitions
+            }
+            Action::NewTask | Action::Delete | Action::Attach | Action::TogglePin | Action::Duplicate => {
+                HelpCategory::TaskOps
+            }
+            Action::CycleView | Action::CycleAgent | Action::ToggleCollapse | Action::ToggleAutomation => {
+                HelpCategory::Board
+            }
+        }
+    }
+
+    /// What the action does, as shown in the help overlay
+    pub fn help(&self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move task to the next column",
+            Action::MoveBack => "Move Running task back to Planning",
+            Action::Resume => "Move Review task back to Running",
+            Action::Delete => "Delete task",
+            Action::NewTask => "New task",
+            Action::Search => "Search tasks (+ also searches descriptions and notes)",
+            Action::Attach => "Attach to the task's window",
+            Action::CycleView => "Next saved view",
+            Action::CycleAgent => "Show one agent's tasks (cycles, then all agents)",
+            Action::ToggleCollapse => "Collapse or expand column",
+            Action::TogglePin => "Pin or unpin task",
+            Action::Duplicate => "Duplicate task",
+            Action::NextActionable => "Next task needing attention",
+            Action::Abort => "Interrupt the agent",
+            Action::ToggleAutomation => "Pause or resume automation (hands off)",
+            Action::Abandon => "Move task straight to Done, with a reason",
+            Action::SelectLeft => "Column left",
+            Action::SelectRight => "Column right",
+            Action::SelectUp => "Task above",
+            Action::SelectDown => "Task below",
+        }
+    }
+
+    /// Column the action is limited to, if any.
+    /// Actions limited to different columns may share a key (e.g. `r` for both
+    /// `resume` on Review and `move_back` on Running).
+    pub fn only_for(&self) -> Option<TaskStatus> {
+        match self {
+            Action::MoveBack => Some(TaskStatus::Running),
+            Action::Resume => Some(TaskStatus::Review),
+            _ => None,
+        }
+    }
+}
+
+/// Base bindings `[keybindings]` overrides apply on top of (`preset = "..."`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// `h/j/k/l` move the selection, alongside the arrow keys
+    #[default]
+    Vim,
+    /// Only the arrow keys move the selection, leaving `h/j/k/l` free to bind
+    Arrows,
+}
+
+impl Preset {
+    pub const NAMES: [&'static str; 2] = ["vim", "arrows"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "vim" => Some(Preset::Vim),
+            "arrows" => Some(Preset::Arrows),
+            _ => None,
+        }
+    }
+}
+
+/// Sections of the help overlay, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpCategory {
+    Navigation,
+    Transitions,
+    TaskOps,
+    Board,
+}
+
+impl HelpCategory {
+    pub const ALL: [HelpCategory; 4] =
+        [HelpCategory::Navigation, HelpCategory::Transitions, HelpCategory::TaskOps, HelpCategory::Board];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            HelpCategory::Navigation => "Navigation",
+            HelpCategory::Transitions => "Transitions",
+            HelpCategory::TaskOps => "Task operations",
+            HelpCategory::Board => "Board",
+        }
+    }
+}
+
+/// One line of the help overlay: the key(s) and what they do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    pub keys: String,
+    pub description: &'static str,
+}
+
+/// A single key, optionally with Ctrl/Alt modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn char(c: char) -> Self {
+        Self { code: KeyCode::Char(c), modifiers: KeyModifiers::NONE }
+    }
+
+    /// Parse a key spec such as `m`, `M`, `/`, `enter`, `ctrl+d` or `alt+left`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            bail!("empty key");
+        }
+
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = spec;
+        // A lone "+" is a key, so only split on "+" when something follows it
+        while let Some((prefix, tail)) = rest.split_once('+').filter(|(_, t)| !t.is_empty()) {
+            match prefix.to_ascii_lowercase().as_str() {
+                "ctrl" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                "shift" => modifiers |= KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier '{}'", prefix),
+            }
+            rest = tail;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match rest.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => bail!("unknown key '{}'", rest),
+                },
+            },
+        };
+
+        // Shift on a character key is expressed by the character itself ("M", not "shift+m")
+        if let KeyCode::Char(c) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                modifiers.remove(KeyModifiers::SHIFT);
+                return Ok(Self { code: KeyCode::Char(c.to_ascii_uppercase()), modifiers });
+            }
+        }
+
+        Ok(Self { code, modifiers })
+    }
+
+    /// Whether a key event from the terminal triggers this binding
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        self.code == key.code && self.modifiers == normalize_modifiers(key.code, key.modifiers)
+    }
+}
+
+/// Terminals report SHIFT alongside uppercase characters; the character already encodes it
+fn normalize_modifiers(code: KeyCode, modifiers: KeyModifiers) -> KeyModifiers {
+    match code {
+        KeyCode::Char(_) => modifiers - KeyModifiers::SHIFT,
+        _ => modifiers,
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::Enter => write!(f, "Enter"),
+            KeyCode::Esc => write!(f, "Esc"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Keys the board handles itself that remappable actions may not take over
+const BUILTIN_KEYS: &[KeyCode] = &[
+    KeyCode::Char('q'),
+    KeyCode::Char('e'),
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Enter,
+    KeyCode::Char('d'),
+    KeyCode::Char('i'),
+    KeyCode::Char('n'),
+    KeyCode::Char('M'),
+    KeyCode::Char('R'),
+    KeyCode::Char('P'),
+    KeyCode::Char('S'),
+    KeyCode::Char('z'),
+    KeyCode::Char('Z'),
+    KeyCode::Char('v'),
+    KeyCode::Char('p'),
+    KeyCode::Char('B'),
+    KeyCode::Char('E'),
+    KeyCode::Char('T'),
+    KeyCode::Char('A'),
+    KeyCode::Char('D'),
+    KeyCode::Char(' '),
+    KeyCode::Char('?'),
+    KeyCode::Esc,
+];
+
+/// Help for the board's fixed keys (`BUILTIN_KEYS` and the detail keys), listed after the
+/// remappable actions of the same section
+const BUILTIN_HELP: &[(HelpCategory, &str, &str)] = &[
+    (HelpCategory::Navigation, "p", "Switch project"),
+    (HelpCategory::Navigation, "e", "Toggle project sidebar"),
+    (HelpCategory::Transitions, "M", "Run Backlog task directly (skip planning)"),
+    (HelpCategory::Transitions, "R", "Research Backlog task"),
+    (HelpCategory::Transitions, "A", "Resume all agents"),
+    (HelpCategory::TaskOps, "Enter", "Open task window / edit Backlog task"),
+    (HelpCategory::TaskOps, "i", "Task details"),
+    (HelpCategory::TaskOps, "n", "Add note"),
+    (HelpCategory::TaskOps, "E", "Edit task"),
+    (HelpCategory::TaskOps, "B", "Dependencies"),
+    (HelpCategory::TaskOps, "d", "Uncommitted diff"),
+    (HelpCategory::TaskOps, "D", "Branch diff"),
+    (HelpCategory::TaskOps, "Space", "Mark task for a bulk move"),
+    (HelpCategory::TaskOps, "z", "Archive or unarchive Done task"),
+    (HelpCategory::TaskOps, "T", "Trash"),
+    (HelpCategory::Board, "v", "Show or hide archived tasks"),
+    (HelpCategory::Board, "Z", "Archive all Done tasks"),
+    (HelpCategory::Board, "S", "Board stats"),
+    (HelpCategory::Board, "P", "Workflow plugin"),
+    (HelpCategory::Board, "Esc", "Clear error, marks, then view and agent filter"),
+    (HelpCategory::Board, "?", "This help"),
+    (HelpCategory::Board, "q / Ctrl+c", "Quit"),
+];
+
+/// Resolved keybindings for the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: HashMap<Action, KeyBinding>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        Self::with_preset(Preset::default())
+    }
+}
+
+/// `[keybindings]` entry that picks the preset rather than binding an action
+const PRESET_KEY: &str = "preset";
+
+impl KeyMap {
+    /// Keymap with every action on its preset key
+    pub fn with_preset(preset: Preset) -> Self {
+        Self {
+            bindings: Action::ALL.iter().map(|a| (*a, a.preset_key(preset))).collect(),
+        }
+    }
+
+    /// Build a keymap from the `[keybindings]` config section, starting from the preset
+    /// (`vim` unless `preset` says otherwise). Errors name the offending action so a bad
+    /// config can be fixed before startup.
+    pub fn from_config(overrides: &HashMap<String, String>) -> Result<Self> {
+        let preset = match overrides.get(PRESET_KEY) {
+            Some(name) => Preset::from_name(name).with_context(|| {
+                format!("Unknown keybinding preset '{}' (expected one of: {})", name, Preset::NAMES.join(", "))
+            })?,
+            None => Preset::default(),
+        };
+        let mut keymap = Self::with_preset(preset);
+
+        // Sort so errors are reported deterministically
+        let mut entries: Vec<_> = overrides.iter().filter(|(name, _)| name.as_str() != PRESET_KEY).collect();
+        entries.sort();
+
+        for (name, spec) in entries {
+            let action = Action::from_name(name).with_context(|| {
+                format!(
+                    "Unknown keybinding action '{}' (expected one of: {})",
+                    name,
+                    Action::ALL.iter().map(|a| a.name()).collect::<Vec<_>>().join(", ")
+                )
+            })?;
+            let binding = KeyBinding::parse(spec)
+                .with_context(|| format!("Invalid key '{}' for keybinding '{}'", spec, name))?;
+            keymap.bindings.insert(action, binding);
+        }
+
+        keymap.validate()?;
+        Ok(keymap)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for action in Action::ALL {
+            let binding = self.binding(action);
+            // Selection actions may sit on their own arrow key (the `arrows` preset)
+            let own_arrow = action.arrow_key() == Some(binding.code);
+            if binding.modifiers.is_empty() && BUILTIN_KEYS.contains(&binding.code) && !own_arrow {
+                bail!(
+                    "Keybinding '{}' = '{}' conflicts with a built-in board key",
+                    action.name(),
+                    binding
+                );
+            }
+        }
+
+        for (i, a) in Action::ALL.iter().enumerate() {
+            for b in &Action::ALL[i + 1..] {
+                if self.binding(*a) != self.binding(*b) {
+                    continue;
+                }
+                let disjoint = matches!((a.only_for(), b.only_for()), (Some(x), Some(y)) if x != y);
+                if !disjoint {
+                    bail!(
+                        "Keybinding '{}' conflicts with '{}': both are bound to '{}'",
+                        b.name(),
+                        a.name(),
+                        self.binding(*a)
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Key bound to an action
+    pub fn binding(&self, action: Action) -> KeyBinding {
+        self.bindings.get(&action).copied().unwrap_or_else(|| action.default_key())
+    }
+
+    /// Keys that trigger an action as the help overlay shows them: the bound key, plus the
+    /// arrow key selection actions always answer to
+    pub fn help_keys(&self, action: Action) -> String {
+        let binding = self.binding(action);
+        match action.arrow_key() {
+            Some(arrow) if arrow != binding.code || !binding.modifiers.is_empty() => {
+                format!("{} / {}", binding, KeyBinding { code: arrow, modifiers: KeyModifiers::NONE })
+            }
+            _ => binding.to_string(),
+        }
+    }
+
+    /// The help overlay: every action with its current key(s), then the fixed board keys,
+    /// grouped by `HelpCategory`
+    pub fn help_sections(&self) -> Vec<(HelpCategory, Vec<HelpEntry>)> {
+        HelpCategory::ALL
+            .iter()
+            .map(|category| {
+                let actions = Action::ALL
+                    .iter()
+                    .filter(|a| a.category() == *category)
+                    .map(|a| HelpEntry { keys: self.help_keys(*a), description: a.help() });
+                let builtin = BUILTIN_HELP
+                    .iter()
+                    .filter(|(c, _, _)| c == category)
+                    .map(|(_, keys, description)| HelpEntry { keys: keys.to_string(), description });
+                (*category, actions.chain(builtin).collect())
+            })
+            .collect()
+    }
+
+    /// Resolve a key press to an action, given the status of the selected task.
+    /// When several actions share the key, the one applicable to that status wins.
+    pub fn resolve(&self, key: &KeyEvent, selected: Option<TaskStatus>) -> Option<Action> {
+        let mut matching = Action::ALL.iter().copied().filter(|a| self.binding(*a).matches(key));
+        let first = matching.next()?;
+        if first.only_for().is_none() || first.only_for() == selected {
+            return Some(first);
+        }
+        matching.find(|a| a.only_for() == selected).or(Some(first))
+    }
+}
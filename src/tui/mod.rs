@@ -1,7 +1,12 @@
+pub mod activity;
 mod app;
 pub mod board;
 mod input;
+pub mod keymap;
+pub mod search;
 pub mod shell_popup;
+pub mod task_form;
+pub mod ui_state;
 
-pub use app::App;
+pub use app::{advance_task, App, DirectMove, MoveHooks, PhaseStart};
 pub use shell_popup::ShellPopup;
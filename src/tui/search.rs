@@ -0,0 +1,164 @@
+//! Task search (`/`). Titles are matched fuzzily; a query starting with `+` also looks for
+//! the text in descriptions and notes (plain case-insensitive substring, since nearly any
+//! short query fuzzy-matches a long description).
+
+use crate::db::{Task, TaskId, TaskStatus};
+
+/// Leading character that extends a query to descriptions and notes
+pub const EVERYWHERE_PREFIX: char = '+';
+
+/// Where a task matched, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchField {
+    Title,
+    Description,
+    Notes,
+}
+
+impl MatchField {
+    pub fn label(&self) -> &'static str {
+        match self {
+            MatchField::Title => "title",
+            MatchField::Description => "description",
+            MatchField::Notes => "notes",
+        }
+    }
+}
+
+/// What was typed in the search prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Lowercased, without the prefix
+    pub text: String,
+    /// Also match descriptions and notes
+    pub everywhere: bool,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        match input.strip_prefix(EVERYWHERE_PREFIX) {
+            Some(rest) => Self { text: rest.trim().to_lowercase(), everywhere: true },
+            None => Self { text: input.to_lowercase(), everywhere: false },
+        }
+    }
+}
+
+/// A task found by a search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    pub task_id: TaskId,
+    pub title: String,
+    pub status: TaskStatus,
+    pub field: MatchField,
+}
+
+/// Fuzzy title match: score, or None. An empty query matches everything.
+pub fn match_title(task: &Task, text: &str) -> Option<i32> {
+    Some(fuzzy_score(&task.title.to_lowercase(), text)).filter(|score| *score > 0)
+}
+
+/// Whether `haystack` contains `needle` (already lowercased), ignoring case
+pub fn contains_text(haystack: &str, needle: &str) -> bool {
+    !needle.is_empty() && haystack.to_lowercase().contains(needle)
+}
+
+/// The best field of `task` that matches, with its score. Descriptions and notes are only
+/// looked at for `everywhere` queries, after the title.
+pub fn match_task(task: &Task, query: &SearchQuery) -> Option<(MatchField, i32)> {
+    if let Some(score) = match_title(task, &query.text) {
+        return Some((MatchField::Title, score));
+    }
+    if !query.everywhere {
+        return None;
+    }
+    if task.description.as_deref().is_some_and(|d| contains_text(d, &query.text)) {
+        return Some((MatchField::Description, 1));
+    }
+    if task.notes.iter().any(|n| contains_text(&n.text, &query.text)) {
+        return Some((MatchField::Notes, 1));
+    }
+    None
+}
+
+/// Up to `limit` matching tasks: title matches first (best score first), then description,
+/// then note matches, otherwise in the given order
+pub fn search_tasks(tasks: &[&Task], query: &SearchQuery, limit: usize) -> Vec<SearchMatch> {
+    let mut found: Vec<(&Task, MatchField, i32)> = tasks
+        .iter()
+        .filter_map(|task| match_task(task, query).map(|(field, score)| (*task, field, score)))
+        .collect();
+    found.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)));
+    found
+        .into_iter()
+        .take(limit)
+        .map(|(task, field, _)| SearchMatch { task_id: task.id.clone(), title: task.title.clone(), status: task.status, field })
+        .collect()
+}
+
+/// Split `text` into (segment, matched) pieces around each case-insensitive occurrence of
+/// `needle`, for highlighting. No needle (or no occurrence) gives the whole text unmatched.
+pub fn highlight_segments(text: &str, needle: &str) -> Vec<(String, bool)> {
+    let lower = text.to_lowercase();
+    // Lowercasing can change byte lengths outside ASCII; don't risk slicing mid-character
+    if needle.is_empty() || lower.len() != text.len() {
+        return vec![(text.to_string(), false)];
+    }
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while let Some(found) = lower[start..].find(needle) {
+        let at = start + found;
+        if at > start {
+            segments.push((text[start..at].to_string(), false));
+        }
+        segments.push((text[at..at + needle.len()].to_string(), true));
+        start = at + needle.len();
+    }
+    if start < text.len() || segments.is_empty() {
+        segments.push((text[start..].to_string(), false));
+    }
+    segments
+}
+
+/// Calculate fuzzy match score (higher is better, 0 means no match)
+pub fn fuzzy_score(haystack: &str, needle: &str) -> i32 {
+    if needle.is_empty() {
+        return 1;
+    }
+
+    let mut score = 0;
+    let mut needle_chars = needle.chars().peekable();
+    let mut prev_matched = false;
+    let mut prev_was_separator = true;
+
+    for c in haystack.chars() {
+        let is_separator = c == '/' || c == '_' || c == '-' || c == '.';
+
+        if let Some(&nc) = needle_chars.peek() {
+            if c == nc {
+                needle_chars.next();
+                score += 1;
+
+                // Bonus for matching after separator (start of word)
+                if prev_was_separator {
+                    score += 5;
+                }
+                // Bonus for consecutive matches
+                if prev_matched {
+                    score += 3;
+                }
+                prev_matched = true;
+            } else {
+                prev_matched = false;
+            }
+        }
+
+        prev_was_separator = is_separator;
+    }
+
+    // Only return score if all needle chars were found
+    if needle_chars.peek().is_none() {
+        score
+    } else {
+        0
+    }
+}
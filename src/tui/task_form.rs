@@ -0,0 +1,212 @@
+//! Fields and validation of the new-task modal
+
+use crate::config::Template;
+use crate::db::Task;
+
+/// A field of the new-task modal, in Tab order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormField {
+    Title,
+    Description,
+    Agent,
+    Tags,
+    Subdir,
+    FromRef,
+}
+
+impl FormField {
+    pub const ALL: [FormField; 6] = [
+        FormField::Title,
+        FormField::Description,
+        FormField::Agent,
+        FormField::Tags,
+        FormField::Subdir,
+        FormField::FromRef,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FormField::Title => "Title",
+            FormField::Description => "Prompt",
+            FormField::Agent => "Agent",
+            FormField::Tags => "Tags",
+            FormField::Subdir => "Directory",
+            FormField::FromRef => "Branch from",
+        }
+    }
+
+    /// The field Tab moves to (wrapping around)
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// The field Shift+Tab moves to (wrapping around)
+    pub fn prev(self) -> Self {
+        let i = Self::ALL.iter().position(|f| *f == self).unwrap_or(0);
+        Self::ALL[(i + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether the field is free text (the agent is picked from a list instead)
+    pub fn is_text(&self) -> bool {
+        !matches!(self, FormField::Agent)
+    }
+}
+
+/// What has been entered in the new-task modal
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskForm {
+    pub title: String,
+    pub description: String,
+    pub agent: String,
+    /// Comma- or space-separated, with an optional leading `#` on each tag
+    pub tags: String,
+    /// Where in the worktree the agent works, relative to its root; blank for the root
+    pub subdir: String,
+    /// Branch, tag or commit to branch from; blank for the base branch
+    pub from_ref: String,
+}
+
+impl TaskForm {
+    /// Start from a template (its description, agent and tags), or blank with `default_agent`
+    pub fn new(template: Option<&Template>, default_agent: &str) -> Self {
+        Self {
+            title: String::new(),
+            description: template.and_then(|t| t.description.clone()).unwrap_or_default(),
+            agent: template
+                .and_then(|t| t.agent.clone())
+                .filter(|a| !a.is_empty())
+                .unwrap_or_else(|| default_agent.to_string()),
+            tags: template.map(|t| t.tags.join(", ")).unwrap_or_default(),
+            subdir: String::new(),
+            from_ref: String::new(),
+        }
+    }
+
+    pub fn value(&self, field: FormField) -> &str {
+        match field {
+            FormField::Title => &self.title,
+            FormField::Description => &self.description,
+            FormField::Agent => &self.agent,
+            FormField::Tags => &self.tags,
+            FormField::Subdir => &self.subdir,
+            FormField::FromRef => &self.from_ref,
+        }
+    }
+
+    pub fn value_mut(&mut self, field: FormField) -> &mut String {
+        match field {
+            FormField::Title => &mut self.title,
+            FormField::Description => &mut self.description,
+            FormField::Agent => &mut self.agent,
+            FormField::Tags => &mut self.tags,
+            FormField::Subdir => &mut self.subdir,
+            FormField::FromRef => &mut self.from_ref,
+        }
+    }
+}
+
+/// Why a field can't be submitted as is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: FormField,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: FormField, message: impl Into<String>) -> Self {
+        Self { field, message: message.into() }
+    }
+}
+
+/// Split the tags field into tags: commas and whitespace separate them, a leading `#` is
+/// dropped and repeats are ignored. A bare `#` is rejected.
+pub fn parse_tags(input: &str) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = Vec::new();
+    for raw in input.split(|c: char| c == ',' || c.is_whitespace()).filter(|t| !t.is_empty()) {
+        let tag = raw.trim_start_matches('#');
+        if tag.is_empty() {
+            return Err(format!("'{}' isn't a tag", raw));
+        }
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    Ok(tags)
+}
+
+/// The directory field as a `/`-separated path relative to the worktree root: a leading `./`
+/// and trailing `/` are dropped, and blank (or `.`) means the root. Absolute paths and `..`
+/// are rejected, since the agent has to stay inside its worktree.
+pub fn parse_subdir(input: &str) -> Result<Option<String>, String> {
+    let input = input.trim();
+    if input.starts_with('/') || input.starts_with('\\') || input.contains(':') {
+        return Err(format!("'{}' must be relative to the worktree", input));
+    }
+    let mut parts = Vec::new();
+    for part in input.split(['/', '\\']).filter(|p| !p.is_empty() && *p != ".") {
+        if part == ".." {
+            return Err(format!("'{}' leaves the worktree", input));
+        }
+        parts.push(part);
+    }
+    Ok((!parts.is_empty()).then(|| parts.join("/")))
+}
+
+/// The branch-from field: blank means the base branch. Whether the ref exists is only known
+/// to git, so that's checked when the worktree is created; here it just has to be one word
+/// that git wouldn't read as an option.
+pub fn parse_from_ref(input: &str) -> Result<Option<String>, String> {
+    let input = input.trim();
+    if input.chars().any(char::is_whitespace) {
+        return Err(format!("'{}' isn't a single branch or commit", input));
+    }
+    if input.starts_with('-') {
+        return Err(format!("'{}' can't start with '-'", input));
+    }
+    Ok((!input.is_empty()).then(|| input.to_string()))
+}
+
+/// Validate the form and build the Backlog task it describes (through `Task::from_template`
+/// when a template was picked, for its title prefix and env). Every invalid field is
+/// reported, in Tab order.
+pub fn build_task(form: &TaskForm, project_id: &str, template: Option<&Template>) -> Result<Task, Vec<FieldError>> {
+    let mut errors = Vec::new();
+
+    let title = form.title.trim();
+    if title.is_empty() {
+        errors.push(FieldError::new(FormField::Title, "Title can't be empty"));
+    }
+    let agent = form.agent.trim();
+    if agent.is_empty() {
+        errors.push(FieldError::new(FormField::Agent, "Pick an agent"));
+    }
+    let tags = parse_tags(&form.tags).unwrap_or_else(|message| {
+        errors.push(FieldError::new(FormField::Tags, message));
+        Vec::new()
+    });
+    let subdir = parse_subdir(&form.subdir).unwrap_or_else(|message| {
+        errors.push(FieldError::new(FormField::Subdir, message));
+        None
+    });
+    let from_ref = parse_from_ref(&form.from_ref).unwrap_or_else(|message| {
+        errors.push(FieldError::new(FormField::FromRef, message));
+        None
+    });
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut task = match template {
+        Some(template) => Task::from_template(template, title),
+        None => Task::new(title, "", ""),
+    };
+    task.agent = agent.to_string();
+    task.project_id = project_id.to_string();
+    // The prompt field started from the template's description, so what's there now wins
+    task.description = Some(form.description.clone()).filter(|d| !d.trim().is_empty());
+    task.tags = tags;
+    task.subdir = subdir;
+    task.from_ref = from_ref;
+    Ok(task)
+}
@@ -0,0 +1,79 @@
+//! Where the board was left: the selected task and how far each column was scrolled.
+//! Kept in a small JSON file next to the project's database (not in it), so exports and
+//! snapshots only ever carry task data.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::board::BoardState;
+use crate::db::{Database, TaskId, TaskStatus};
+
+/// Selection and scroll of a project's board between runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiState {
+    pub selected_task: Option<TaskId>,
+    /// Used when the selected task is gone (deleted, or hidden by a view)
+    #[serde(default)]
+    pub selected_column: usize,
+    /// Scroll offset per column, keyed by status name
+    #[serde(default)]
+    pub scroll: BTreeMap<String, usize>,
+}
+
+impl UiState {
+    pub fn capture(board: &BoardState) -> Self {
+        let scroll = TaskStatus::columns()
+            .iter()
+            .enumerate()
+            .map(|(column, status)| (status.as_str().to_string(), board.scroll_offset(column)))
+            .filter(|(_, offset)| *offset > 0)
+            .collect();
+        Self {
+            selected_task: board.selected_task().map(|t| t.id.clone()),
+            selected_column: board.selected_column,
+            scroll,
+        }
+    }
+
+    /// Put the board back where it was. A task that no longer exists leaves the selection at
+    /// the top of its old column; offsets past the end are clamped when the board is drawn.
+    pub fn apply(&self, board: &mut BoardState) {
+        match self.selected_task.as_deref().and_then(|id| board.row_of(id)) {
+            Some((column, row)) => {
+                board.selected_column = column;
+                board.selected_row = row;
+            }
+            None => {
+                board.selected_column = self.selected_column.min(TaskStatus::columns().len() - 1);
+                board.selected_row = 0;
+            }
+        }
+        for (column, status) in TaskStatus::columns().iter().enumerate() {
+            board.set_scroll_offset(column, self.scroll.get(status.as_str()).copied().unwrap_or(0));
+        }
+    }
+
+    /// A missing file is a fresh board; an unreadable one is reported
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The UI state file of a project: `<hash>.ui.json` beside its `<hash>.db`
+pub fn path_for(project_path: &Path) -> Result<PathBuf> {
+    Database::project_file(project_path, "ui.json")
+}
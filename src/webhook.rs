@@ -0,0 +1,129 @@
+//! Outbound HTTP webhook (`[webhook]` in config): a JSON POST for every column change, for
+//! dashboards that would rather not be driven by shell hooks.
+//!
+//! Requests go through `curl` on a background thread once the transition's hooks have let it
+//! through. A failed request is retried once, then written to the error log; the board never
+//! waits on it.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+#[cfg(feature = "test-mocks")]
+use mockall::automock;
+
+use crate::config::WebhookConfig;
+use crate::db::{Task, TaskId, TaskStatus};
+use crate::error_log;
+use crate::op_error::{OpError, OpResult};
+
+/// Longest a single request may take, connection included
+pub const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause before the one retry
+pub const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// What's posted for a transition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Payload {
+    pub task_id: TaskId,
+    pub title: String,
+    /// Status names as in hooks and config: `backlog`, `planning`, `running`, `review`, `done`
+    pub old_status: String,
+    pub new_status: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Payload {
+    pub fn new(task: &Task, from: TaskStatus, to: TaskStatus) -> Self {
+        Self {
+            task_id: task.id.clone(),
+            title: task.title.clone(),
+            old_status: from.as_str().to_string(),
+            new_status: to.as_str().to_string(),
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// Sends one webhook request (mockable so delivery can be tested without a server)
+#[cfg_attr(feature = "test-mocks", automock)]
+pub trait WebhookSender: Send + Sync {
+    /// POST `body` as JSON to `url`, with `header` added when given
+    fn post(&self, url: &str, header: Option<String>, body: &str) -> OpResult<()>;
+}
+
+/// Posts with `curl`, failing on HTTP errors and after `WEBHOOK_TIMEOUT`
+pub struct CurlWebhookSender;
+
+impl WebhookSender for CurlWebhookSender {
+    fn post(&self, url: &str, header: Option<String>, body: &str) -> OpResult<()> {
+        let mut cmd = Command::new("curl");
+        cmd.args(["--silent", "--show-error", "--fail", "--max-time"])
+            .arg(WEBHOOK_TIMEOUT.as_secs().to_string())
+            .args(["-X", "POST", "-H", "Content-Type: application/json"]);
+        // The url, auth header and body go through stdin so none of them shows up in `ps`
+        cmd.args(["--config", "-"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd.spawn().map_err(|e| OpError::spawn("curl", e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(curl_config(url, header.as_deref(), body).as_bytes());
+        }
+        let output = child.wait_with_output().map_err(|e| OpError::spawn("curl", e))?;
+        if !output.status.success() {
+            return Err(OpError::failed(format!("curl POST {}", url), &output));
+        }
+        Ok(())
+    }
+}
+
+/// A curl config file (`curl --config`) with the request's url, header and body
+pub fn curl_config(url: &str, header: Option<&str>, body: &str) -> String {
+    let mut config = format!("url = {}\n", curl_quote(url));
+    if let Some(header) = header {
+        config.push_str(&format!("header = {}\n", curl_quote(header)));
+    }
+    config.push_str(&format!("data-binary = {}\n", curl_quote(body)));
+    config
+}
+
+/// A double-quoted curl config value, with the escapes curl reads back
+fn curl_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// Post `payload` to the configured url, retrying once after `RETRY_DELAY`. Does nothing
+/// when no url is set.
+pub fn deliver(config: &WebhookConfig, sender: &dyn WebhookSender, payload: &Payload) -> OpResult<()> {
+    let Some(url) = config.url() else { return Ok(()) };
+    let body = serde_json::to_string(payload).expect("payload serializes");
+    let header = config.auth_header.clone();
+    sender.post(url, header.clone(), &body).or_else(|_| {
+        std::thread::sleep(RETRY_DELAY);
+        sender.post(url, header, &body)
+    })
+}
+
+/// `deliver` on a background thread, logging the failure if the retry fails too
+pub fn spawn_delivery(config: &WebhookConfig, payload: Payload) {
+    if config.url().is_none() {
+        return;
+    }
+    let config = config.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = deliver(&config, &CurlWebhookSender, &payload) {
+            error_log::log(&format!("Webhook for '{}' failed", payload.title), &e.to_string());
+        }
+    });
+}
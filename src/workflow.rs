@@ -0,0 +1,62 @@
+//! Which column changes a task may make.
+//!
+//! Every move, from a board key, a bulk move, the `agtx serve` API or `tui::advance_task`,
+//! asks `request_transition` first, so no caller can put a task somewhere the workflow
+//! doesn't lead (e.g. Backlog straight to Done). Moving straight to Done with a reason
+//! (the `abandon` action) is the one deliberate exception and doesn't go through here.
+
+use crate::db::{Task, TaskStatus};
+
+/// A column change the workflow doesn't allow. `Display` is written for the footer and for
+/// API error responses.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TransitionError {
+    #[error("'{title}' is already in {}", .status.as_str())]
+    AlreadyThere { title: String, status: TaskStatus },
+
+    #[error(
+        "'{title}' can't move from {} to {}: tasks move one column forward (Backlog may skip to \
+         Running), or back from Running to Planning and from Review to Running",
+        .from.as_str(),
+        .to.as_str()
+    )]
+    NotAllowed { title: String, from: TaskStatus, to: TaskStatus },
+}
+
+/// One column to the right, or Backlog straight to Running (`move_backlog_to_running`)
+pub fn is_valid_forward_transition(from: TaskStatus, to: TaskStatus) -> bool {
+    matches!(
+        (from, to),
+        (TaskStatus::Backlog, TaskStatus::Planning)
+            | (TaskStatus::Backlog, TaskStatus::Running)
+            | (TaskStatus::Planning, TaskStatus::Running)
+            | (TaskStatus::Running, TaskStatus::Review)
+            | (TaskStatus::Review, TaskStatus::Done)
+    )
+}
+
+/// Back for more work: Running → Planning, or Review → Running
+pub fn is_valid_resume_transition(from: TaskStatus, to: TaskStatus) -> bool {
+    resume_status(from) == Some(to)
+}
+
+/// Where a task goes when it's sent back (`move_back`/`resume`), if it can be
+pub fn resume_status(status: TaskStatus) -> Option<TaskStatus> {
+    match status {
+        TaskStatus::Running => Some(TaskStatus::Planning),
+        TaskStatus::Review => Some(TaskStatus::Running),
+        _ => None,
+    }
+}
+
+/// Check a move of `task` to `target` before anything is changed
+pub fn request_transition(task: &Task, target: TaskStatus) -> Result<(), TransitionError> {
+    let from = task.status;
+    if from == target {
+        return Err(TransitionError::AlreadyThere { title: task.title.clone(), status: from });
+    }
+    if is_valid_forward_transition(from, target) || is_valid_resume_transition(from, target) {
+        return Ok(());
+    }
+    Err(TransitionError::NotAllowed { title: task.title.clone(), from, to: target })
+}
@@ -0,0 +1,80 @@
+use agtx::tui::activity::{Activities, SPINNER_FRAMES};
+
+#[test]
+fn test_idle_has_no_status_line() {
+    let mut activities = Activities::new();
+    activities.drain();
+    assert!(!activities.is_busy());
+    assert_eq!(activities.status_line(0), None);
+}
+
+#[test]
+fn test_guard_reports_start_and_finish() {
+    let mut activities = Activities::new();
+    let guard = activities.reporter().start("Creating worktree for 'Fix login'");
+
+    // Nothing shows until the events are drained
+    assert!(!activities.is_busy());
+    activities.drain();
+    assert_eq!(
+        activities.status_line(0).as_deref(),
+        Some("\u{280b} Creating worktree for 'Fix login'\u{2026}")
+    );
+
+    drop(guard);
+    activities.drain();
+    assert!(!activities.is_busy());
+    assert_eq!(activities.status_line(0), None);
+}
+
+#[test]
+fn test_status_line_shows_oldest_and_counts_the_rest() {
+    let mut activities = Activities::new();
+    let reporter = activities.reporter();
+    let first = reporter.start("Creating 3 worktrees");
+    let _second = reporter.start("Running tests for 'Fix login'");
+    activities.drain();
+
+    let line = activities.status_line(1).unwrap();
+    assert!(line.starts_with(&format!("{} Creating 3 worktrees", SPINNER_FRAMES[1])), "{}", line);
+    assert!(line.ends_with("(+1 more)"), "{}", line);
+
+    drop(first);
+    activities.drain();
+    let line = activities.status_line(SPINNER_FRAMES.len()).unwrap();
+    assert_eq!(line, format!("{} Running tests for 'Fix login'\u{2026}", SPINNER_FRAMES[0]));
+}
+
+#[test]
+fn test_blocking_activity_has_no_spinner() {
+    let mut activities = Activities::new();
+    let reporter = activities.reporter();
+    let _blocking = reporter.start_blocking("Creating worktree for 'Fix login'");
+    let _background = reporter.start("Running tests for 'Fix login'");
+    activities.drain();
+
+    // The frame can't advance while the main thread is busy, so none is drawn
+    assert_eq!(activities.status_line(3).as_deref(), Some("Creating worktree for 'Fix login'\u{2026} (+1 more)"));
+}
+
+#[test]
+fn test_guard_finishes_from_background_thread() {
+    let mut activities = Activities::new();
+    let guard = activities.reporter().start("Running tests for 'Fix login'");
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _activity = guard;
+        let _ = done_tx.send(());
+    });
+
+    done_rx.recv().unwrap();
+    // The guard is dropped right after the send; wait for the thread to let go of it
+    for _ in 0..100 {
+        activities.drain();
+        if !activities.is_busy() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(!activities.is_busy());
+}
@@ -0,0 +1,213 @@
+use agtx::adopt::{self, Adoption};
+use agtx::db::{Task, TaskStatus};
+use std::path::PathBuf;
+
+fn running(title: &str, worktree: &str, window: &str) -> Task {
+    let mut task = Task::new(title, "claude", "project-1");
+    task.status = TaskStatus::Running;
+    task.worktree_path = Some(worktree.to_string());
+    task.session_name = Some(window.to_string());
+    task
+}
+
+// === Candidate Tests ===
+
+#[test]
+fn test_untracked_worktrees_skips_tracked() {
+    let worktrees = vec![PathBuf::from("/proj/.agtx/worktrees/mine"), PathBuf::from("/proj/.agtx/worktrees/manual")];
+    let tasks = vec![running("Mine", "/proj/.agtx/worktrees/mine", "proj:task-mine")];
+
+    assert_eq!(adopt::untracked_worktrees(&worktrees, &tasks), vec![PathBuf::from("/proj/.agtx/worktrees/manual")]);
+}
+
+#[test]
+fn test_untracked_windows_skips_tracked() {
+    let windows = vec!["task-mine".to_string(), "hand-made".to_string()];
+    let tasks = vec![running("Mine", "/proj/.agtx/worktrees/mine", "proj:task-mine")];
+
+    assert_eq!(adopt::untracked_windows("proj", &windows, &tasks), vec!["hand-made".to_string()]);
+    // A window of the same name in another session is someone else's
+    assert_eq!(adopt::untracked_windows("other", &windows, &tasks).len(), 2);
+}
+
+#[test]
+fn test_find_worktree_by_path_or_name() {
+    let worktrees = vec![PathBuf::from("/proj/.agtx/worktrees/fix-login"), PathBuf::from("/elsewhere/spike")];
+
+    assert_eq!(adopt::find_worktree(&worktrees, "/elsewhere/spike"), Some(PathBuf::from("/elsewhere/spike")));
+    assert_eq!(adopt::find_worktree(&worktrees, "fix-login"), Some(PathBuf::from("/proj/.agtx/worktrees/fix-login")));
+    assert_eq!(adopt::find_worktree(&worktrees, "nope"), None);
+}
+
+#[test]
+fn test_window_target() {
+    assert_eq!(adopt::window_target("proj", "hand-made"), "proj:hand-made");
+    assert_eq!(adopt::window_target("proj", "other:agent"), "other:agent");
+}
+
+#[test]
+fn test_title_from_slug() {
+    assert_eq!(adopt::title_from_slug("fix-login_page"), "fix login page");
+    assert_eq!(adopt::title_from_slug("spike"), "spike");
+}
+
+// === Task Tests ===
+
+#[test]
+fn test_into_task_points_at_resources() {
+    let adoption = Adoption {
+        worktree: PathBuf::from("/proj/.agtx/worktrees/fix-login"),
+        window: Some("proj:hand-made".to_string()),
+    };
+    let task = adoption.into_task(None, Some("fix-login".to_string()), "codex", "project-1");
+
+    assert_eq!(task.status, TaskStatus::Running);
+    assert_eq!(task.title, "fix login");
+    assert_eq!(task.agent, "codex");
+    assert_eq!(task.worktree_path.as_deref(), Some("/proj/.agtx/worktrees/fix-login"));
+    assert_eq!(task.branch_name.as_deref(), Some("fix-login"));
+    assert_eq!(task.session_name.as_deref(), Some("proj:hand-made"));
+    assert_eq!(task.slug.as_deref(), Some("fix-login"));
+}
+
+#[test]
+fn test_into_task_title_and_detached_head() {
+    let adoption = Adoption { worktree: PathBuf::from("/elsewhere/spike"), window: None };
+    let task = adoption.into_task(Some("  Try the new parser "), Some("HEAD".to_string()), "claude", "project-1");
+
+    assert_eq!(task.title, "Try the new parser");
+    assert_eq!(task.branch_name, None);
+    assert_eq!(task.session_name, None);
+}
+
+// === Check Tests ===
+
+#[cfg(feature = "test-mocks")]
+mod check {
+    use super::*;
+    use agtx::git::MockGitOperations;
+    use agtx::op_error::OpError;
+    use agtx::tmux::MockTmuxOperations;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    /// Git listing `worktrees`, with `worktree_dir` the directory they are in
+    fn git_listing(worktrees: Vec<PathBuf>) -> MockGitOperations {
+        let dir = worktrees[0].parent().unwrap().to_path_buf();
+        let mut git = MockGitOperations::new();
+        git.expect_list_worktrees().returning(move |_| Ok(worktrees.clone()));
+        git.expect_worktree_path().returning(move |_, slug| dir.join(slug));
+        git.expect_create_worktree().never();
+        git
+    }
+
+    fn tmux_listing(windows: Vec<&'static str>) -> MockTmuxOperations {
+        let mut tmux = MockTmuxOperations::new();
+        tmux.expect_list_windows()
+            .withf(|session| session == "proj")
+            .returning(move |_| Ok(windows.iter().map(|w| w.to_string()).collect()));
+        tmux.expect_create_window().never();
+        tmux
+    }
+
+    #[test]
+    fn test_check_adopts_worktree_and_window() {
+        let dir = TempDir::new().unwrap();
+        let worktree = dir.path().join("manual");
+        std::fs::create_dir(&worktree).unwrap();
+        let git = git_listing(vec![worktree.clone()]);
+        let tmux = tmux_listing(vec!["hand-made"]);
+
+        let adoption =
+            Adoption::check("manual", Some("hand-made"), Path::new("/proj"), "proj", &[], &tmux, &git).unwrap();
+
+        assert_eq!(adoption.worktree, worktree);
+        assert_eq!(adoption.window.as_deref(), Some("proj:hand-made"));
+    }
+
+    #[test]
+    fn test_check_without_window_skips_multiplexer() {
+        let dir = TempDir::new().unwrap();
+        let git = git_listing(vec![dir.path().to_path_buf()]);
+        let mut tmux = MockTmuxOperations::new();
+        tmux.expect_list_windows().never();
+
+        let adoption =
+            Adoption::check(dir.path().to_str().unwrap(), None, Path::new("/proj"), "proj", &[], &tmux, &git).unwrap();
+
+        assert_eq!(adoption.window, None);
+    }
+
+    #[test]
+    fn test_check_rejects_unknown_or_missing_worktree() {
+        let dir = TempDir::new().unwrap();
+        let gone = dir.path().join("gone");
+        let git = git_listing(vec![gone]);
+        let tmux = tmux_listing(vec![]);
+
+        let err = Adoption::check("other", None, Path::new("/proj"), "proj", &[], &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("not a worktree"));
+        let err = Adoption::check("gone", None, Path::new("/proj"), "proj", &[], &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_check_rejects_missing_window() {
+        let dir = TempDir::new().unwrap();
+        let git = git_listing(vec![dir.path().to_path_buf()]);
+        let tmux = tmux_listing(vec!["task-mine"]);
+
+        let name = dir.path().file_name().unwrap().to_str().unwrap().to_string();
+        let err = Adoption::check(&name, Some("hand-made"), Path::new("/proj"), "proj", &[], &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("No window 'hand-made'"));
+    }
+
+    #[test]
+    fn test_check_rejects_resources_a_task_has() {
+        let dir = TempDir::new().unwrap();
+        let tracked = dir.path().join("tracked");
+        let manual = dir.path().join("manual");
+        std::fs::create_dir(&tracked).unwrap();
+        std::fs::create_dir(&manual).unwrap();
+        let git = git_listing(vec![tracked.clone(), manual]);
+        let tmux = tmux_listing(vec!["task-mine", "hand-made"]);
+        let tasks = vec![running("Mine", tracked.to_str().unwrap(), "proj:task-mine")];
+
+        let err = Adoption::check("tracked", None, Path::new("/proj"), "proj", &tasks, &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("already belongs to 'Mine'"));
+        let err =
+            Adoption::check("manual", Some("task-mine"), Path::new("/proj"), "proj", &tasks, &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("already belongs to 'Mine'"));
+    }
+
+    /// Tasks look for their worktree under worktree_dir, so one made elsewhere can't be adopted
+    #[test]
+    fn test_check_rejects_worktree_outside_worktree_dir() {
+        let dir = TempDir::new().unwrap();
+        let outside = dir.path().join("spike");
+        std::fs::create_dir(&outside).unwrap();
+        let mut git = MockGitOperations::new();
+        git.expect_list_worktrees().returning({
+            let outside = outside.clone();
+            move |_| Ok(vec![outside.clone()])
+        });
+        git.expect_worktree_path().returning(|_, slug| Path::new("/proj/.agtx/worktrees").join(slug));
+        let tmux = tmux_listing(vec![]);
+
+        let err = Adoption::check("spike", None, Path::new("/proj"), "proj", &[], &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("outside the worktree directory"), "{}", err);
+        assert!(!adopt::in_worktree_dir(&outside, Path::new("/proj"), &git));
+    }
+
+    #[test]
+    fn test_check_reports_multiplexer_errors() {
+        let dir = TempDir::new().unwrap();
+        let git = git_listing(vec![dir.path().to_path_buf()]);
+        let mut tmux = MockTmuxOperations::new();
+        tmux.expect_list_windows().returning(|_| Err(OpError::TmuxDisabled));
+
+        let name = dir.path().file_name().unwrap().to_str().unwrap().to_string();
+        let err = Adoption::check(&name, Some("hand-made"), Path::new("/proj"), "proj", &[], &tmux, &git).unwrap_err();
+        assert!(err.to_string().contains("Failed to list windows"));
+    }
+}
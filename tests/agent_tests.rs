@@ -1,4 +1,4 @@
-use agtx::agent::parse_agent_selection;
+use agtx::agent::{get_agent, parse_agent_selection};
 
 #[test]
 fn test_parse_agent_selection_empty_defaults_to_first() {
@@ -40,3 +40,33 @@ fn test_parse_agent_selection_single_agent() {
     assert_eq!(parse_agent_selection("2", 1), None);
     assert_eq!(parse_agent_selection("", 1), Some(0));
 }
+
+#[test]
+fn test_claude_starts_and_resumes_a_named_session() {
+    let claude = get_agent("claude").unwrap();
+    assert!(claude.supports_session_resume());
+    assert_eq!(
+        claude.build_interactive_command_in_session("fix it", "sess-1"),
+        "claude --session-id sess-1 --dangerously-skip-permissions 'fix it'"
+    );
+    assert_eq!(
+        claude.build_resume_command("sess-1").as_deref(),
+        Some("claude --dangerously-skip-permissions --resume sess-1")
+    );
+    // Resuming starts the agent the way it's normally started
+    assert_eq!(
+        claude.build_resume_command("sess-1").unwrap(),
+        format!("{} --resume sess-1", claude.build_interactive_command(""))
+    );
+}
+
+#[test]
+fn test_agents_without_resume_ignore_session_id() {
+    let codex = get_agent("codex").unwrap();
+    assert!(!codex.supports_session_resume());
+    assert_eq!(
+        codex.build_interactive_command_in_session("fix it", "sess-1"),
+        codex.build_interactive_command("fix it")
+    );
+    assert_eq!(codex.build_resume_command("sess-1"), None);
+}
@@ -0,0 +1,123 @@
+use agtx::api::{default_socket_path, parse_request, EventLine, MoveDirection, Request, Response, TaskInfo, API_VERSION};
+use agtx::db::{Task, TaskEvent, TaskEventKind, TaskStatus};
+use std::path::Path;
+
+// === Request Parsing Tests ===
+
+#[test]
+fn test_parse_list_tasks() {
+    assert_eq!(parse_request(r#"{"version": 1, "command": "list_tasks"}"#), Ok(Request::ListTasks));
+}
+
+#[test]
+fn test_parse_create_task_optional_fields() {
+    assert_eq!(
+        parse_request(r#"{"version": 1, "command": "create_task", "title": "Fix login"}"#),
+        Ok(Request::CreateTask { title: "Fix login".to_string(), description: None, agent: None })
+    );
+    assert_eq!(
+        parse_request(r#"{"version": 1, "command": "create_task", "title": "T", "description": "D", "agent": "codex"}"#),
+        Ok(Request::CreateTask {
+            title: "T".to_string(),
+            description: Some("D".to_string()),
+            agent: Some("codex".to_string()),
+        })
+    );
+}
+
+#[test]
+fn test_parse_move_task_direction_defaults_forward() {
+    assert_eq!(
+        parse_request(r#"{"version": 1, "command": "move_task", "id": "abc"}"#),
+        Ok(Request::MoveTask { id: "abc".to_string(), direction: MoveDirection::Forward })
+    );
+    assert_eq!(
+        parse_request(r#"{"version": 1, "command": "move_task", "id": "abc", "direction": "back"}"#),
+        Ok(Request::MoveTask { id: "abc".to_string(), direction: MoveDirection::Back })
+    );
+}
+
+#[test]
+fn test_parse_delete_task() {
+    assert_eq!(
+        parse_request(r#"{"version": 1, "command": "delete_task", "id": "abc"}"#),
+        Ok(Request::DeleteTask { id: "abc".to_string() })
+    );
+}
+
+#[test]
+fn test_parse_rejects_other_versions() {
+    let err = parse_request(r#"{"version": 2, "command": "list_tasks"}"#).unwrap_err();
+    assert!(err.contains("unsupported API version 2"));
+
+    let err = parse_request(r#"{"command": "list_tasks"}"#).unwrap_err();
+    assert!(err.contains("missing \"version\""));
+
+    let err = parse_request(r#"{"version": "1", "command": "list_tasks"}"#).unwrap_err();
+    assert!(err.contains("must be a number"));
+}
+
+#[test]
+fn test_parse_rejects_bad_requests() {
+    assert!(parse_request("not json").unwrap_err().contains("invalid JSON"));
+    assert!(parse_request("[1]").unwrap_err().contains("JSON object"));
+    assert!(parse_request(r#"{"version": 1, "command": "fly"}"#).unwrap_err().contains("fly"));
+    // Missing required field
+    assert!(parse_request(r#"{"version": 1, "command": "move_task"}"#).unwrap_err().contains("id"));
+    // Typos in field names are caught rather than ignored
+    assert!(parse_request(r#"{"version": 1, "command": "delete_task", "id": "a", "force": true}"#).is_err());
+}
+
+// === Response Tests ===
+
+#[test]
+fn test_response_lines() {
+    let ok = Response::ok(serde_json::json!({ "id": "abc" })).to_line();
+    assert_eq!(ok, format!(r#"{{"version":{},"ok":true,"result":{{"id":"abc"}}}}"#, API_VERSION));
+    assert!(!ok.contains('\n'));
+
+    let err = Response::error("No task with id 'x'").to_line();
+    assert_eq!(err, format!(r#"{{"version":{},"ok":false,"error":"No task with id 'x'"}}"#, API_VERSION));
+    let parsed: Response = serde_json::from_str(&err).unwrap();
+    assert!(!parsed.ok);
+    assert!(parsed.result.is_none());
+}
+
+#[test]
+fn test_event_lines() {
+    let event = TaskEvent::new(TaskEventKind::Failed, "tmux window 'x' not found");
+    let line = EventLine::new("abc", event.clone()).to_line();
+    assert!(!line.contains('\n'));
+
+    let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(value["version"], API_VERSION);
+    assert_eq!(value["task_id"], "abc");
+    assert_eq!(value["kind"], "failed");
+    assert_eq!(value["detail"], "tmux window 'x' not found");
+
+    let parsed: EventLine = serde_json::from_str(&line).unwrap();
+    assert_eq!(parsed, EventLine::new("abc", event));
+}
+
+#[test]
+fn test_task_info_from_task() {
+    let mut task = Task::new("Fix login", "claude", "proj");
+    task.status = TaskStatus::Running;
+    task.tags = vec!["bug".to_string()];
+    task.worktree_path = Some("/wt".to_string());
+
+    let info = TaskInfo::from(&task);
+    assert_eq!(info.id, task.id);
+    assert_eq!(info.status, "running");
+    assert_eq!(info.tags, vec!["bug"]);
+    assert_eq!(info.worktree_path.as_deref(), Some("/wt"));
+
+    let json = serde_json::to_value(&info).unwrap();
+    assert_eq!(json["status"], "running");
+    assert_eq!(json["agent"], "claude");
+}
+
+#[test]
+fn test_default_socket_path() {
+    assert_eq!(default_socket_path(Path::new("/repo")), Path::new("/repo/.agtx/api.sock"));
+}
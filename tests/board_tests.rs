@@ -1,5 +1,10 @@
-use agtx::db::{Task, TaskStatus};
-use agtx::tui::board::BoardState;
+use agtx::config::{StalenessConfig, TaskFilter, View};
+use agtx::db::{AgentUsage, Task, TaskStatus};
+use agtx::tui::board::{
+    actionability, board_agents, board_stats, column_widths, move_selection, next_actionable, next_actionable_after, next_agent_filter, scroll_offset,
+    sort_column, staleness, visible_tasks,
+    Actionability, BoardState, NavDirection, Selection, Staleness,
+};
 
 fn create_test_task(title: &str, status: TaskStatus) -> Task {
     let mut task = Task::new(title, "claude", "test-project");
@@ -223,3 +228,523 @@ fn test_selected_task_mut() {
 
     assert_eq!(board.tasks[0].title, "Modified Task");
 }
+
+#[test]
+fn test_move_selection_is_pure() {
+    let mut board = BoardState::new();
+    board.tasks = vec![
+        create_test_task("B1", TaskStatus::Backlog),
+        create_test_task("B2", TaskStatus::Backlog),
+        create_test_task("B3", TaskStatus::Backlog),
+        create_test_task("P1", TaskStatus::Planning),
+        create_test_task("P2", TaskStatus::Planning),
+    ];
+    board.selected_row = 1;
+
+    // The board itself doesn't move
+    assert_eq!(move_selection(&board, NavDirection::Down), Selection { column: 0, row: 2 });
+    assert_eq!(move_selection(&board, NavDirection::Up), Selection { column: 0, row: 0 });
+    assert_eq!(move_selection(&board, NavDirection::Left), Selection { column: 0, row: 1 });
+    // Row 1 exists in Planning, so it's kept
+    assert_eq!(move_selection(&board, NavDirection::Right), Selection { column: 1, row: 1 });
+    assert_eq!((board.selected_column, board.selected_row), (0, 1));
+
+    // From the bottom of Backlog, Planning's last task is the closest row
+    board.selected_row = 2;
+    assert_eq!(move_selection(&board, NavDirection::Right), Selection { column: 1, row: 1 });
+    assert_eq!(move_selection(&board, NavDirection::Down), Selection { column: 0, row: 2 });
+
+    board.selected_column = 4;
+    board.selected_row = 0;
+    assert_eq!(move_selection(&board, NavDirection::Right), Selection { column: 4, row: 0 });
+}
+
+// === Archived Task Tests ===
+
+#[test]
+fn test_visible_tasks_hides_archived() {
+    let mut archived = create_test_task("Old", TaskStatus::Done);
+    archived.archived = true;
+    let tasks = vec![create_test_task("Current", TaskStatus::Done), archived];
+
+    let visible = visible_tasks(&tasks, false);
+    assert_eq!(visible.len(), 1);
+    assert_eq!(visible[0].title, "Current");
+
+    assert_eq!(visible_tasks(&tasks, true).len(), 2);
+}
+
+#[test]
+fn test_tasks_in_column_respects_show_archived() {
+    let mut board = BoardState::new();
+    let mut archived = create_test_task("Old", TaskStatus::Done);
+    archived.archived = true;
+    board.tasks = vec![create_test_task("Current", TaskStatus::Done), archived];
+
+    assert_eq!(board.tasks_in_column(4).len(), 1);
+
+    board.show_archived = true;
+    assert_eq!(board.tasks_in_column(4).len(), 2);
+}
+
+#[test]
+fn test_selected_task_skips_archived() {
+    let mut board = BoardState::new();
+    let mut archived = create_test_task("Old", TaskStatus::Done);
+    archived.archived = true;
+    board.tasks = vec![archived, create_test_task("Current", TaskStatus::Done)];
+    board.selected_column = 4;
+    board.selected_row = 0;
+
+    assert_eq!(board.selected_task().unwrap().title, "Current");
+    assert_eq!(board.selected_task_mut().unwrap().title, "Current");
+}
+
+// === Multi-select Tests ===
+
+#[test]
+fn test_toggle_mark_selected() {
+    let mut board = BoardState::new();
+    board.tasks = vec![create_test_task("A", TaskStatus::Backlog)];
+
+    board.toggle_mark_selected();
+    assert!(board.marked.contains(&board.tasks[0].id));
+
+    board.toggle_mark_selected();
+    assert!(board.marked.is_empty());
+}
+
+#[test]
+fn test_toggle_mark_on_empty_column_does_nothing() {
+    let mut board = BoardState::new();
+    board.toggle_mark_selected();
+    assert!(board.marked.is_empty());
+}
+
+#[test]
+fn test_marked_tasks_in_board_order() {
+    let mut board = BoardState::new();
+    board.tasks = vec![
+        create_test_task("Review", TaskStatus::Review),
+        create_test_task("Backlog 1", TaskStatus::Backlog),
+        create_test_task("Unmarked", TaskStatus::Backlog),
+        create_test_task("Backlog 2", TaskStatus::Backlog),
+    ];
+    for i in [0, 1, 3] {
+        let id = board.tasks[i].id.clone();
+        board.marked.insert(id);
+    }
+    // A mark whose task is gone (e.g. deleted) is ignored
+    board.marked.insert("gone".to_string());
+
+    let titles: Vec<&str> = board.marked_tasks().iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Backlog 1", "Backlog 2", "Review"]);
+}
+
+// === View Tests ===
+
+#[test]
+fn test_view_filters_columns_and_selection() {
+    let mut board = BoardState::new();
+    board.tasks = vec![
+        create_test_task("Plain", TaskStatus::Backlog),
+        create_test_task("Tagged", TaskStatus::Backlog),
+    ];
+    board.tasks[1].tags = vec!["urgent".to_string()];
+    board.view = Some(View { name: "urgent".into(), filter: TaskFilter::Tag("urgent".into()) });
+
+    let titles: Vec<&str> = board.tasks_in_column(0).iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Tagged"]);
+    assert_eq!(board.selected_task().unwrap().title, "Tagged");
+    board.selected_task_mut().unwrap().title = "Renamed".to_string();
+    assert_eq!(board.tasks[1].title, "Renamed");
+
+    board.view = None;
+    assert_eq!(board.tasks_in_column(0).len(), 2);
+}
+
+// === Agent Filter Tests ===
+
+#[test]
+fn test_agent_filter_combines_with_view() {
+    let mut board = BoardState::new();
+    board.tasks = vec![
+        create_test_task("Claude", TaskStatus::Backlog),
+        create_test_task("Aider", TaskStatus::Backlog),
+        create_test_task("Aider urgent", TaskStatus::Backlog),
+    ];
+    board.tasks[1].agent = "aider".to_string();
+    board.tasks[2].agent = "aider".to_string();
+    board.tasks[2].tags = vec!["urgent".to_string()];
+
+    board.agent_filter = Some("aider".to_string());
+    let titles: Vec<&str> = board.tasks_in_column(0).iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Aider", "Aider urgent"]);
+
+    board.view = Some(View { name: "urgent".into(), filter: TaskFilter::Tag("urgent".into()) });
+    let titles: Vec<&str> = board.tasks_in_column(0).iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Aider urgent"]);
+
+    board.view = None;
+    board.agent_filter = None;
+    assert_eq!(board.tasks_in_column(0).len(), 3);
+}
+
+#[test]
+fn test_next_agent_filter_cycles_through_board_agents() {
+    let mut tasks = vec![
+        create_test_task("A", TaskStatus::Backlog),
+        create_test_task("B", TaskStatus::Running),
+        create_test_task("C", TaskStatus::Done),
+    ];
+    tasks[1].agent = "aider".to_string();
+    tasks[2].agent = "codex".to_string();
+
+    assert_eq!(board_agents(&tasks), vec!["aider", "claude", "codex"]);
+    assert_eq!(next_agent_filter(&tasks, None).as_deref(), Some("aider"));
+    assert_eq!(next_agent_filter(&tasks, Some("aider")).as_deref(), Some("claude"));
+    assert_eq!(next_agent_filter(&tasks, Some("codex")), None);
+    // An agent no task uses any more goes back to all agents
+    assert_eq!(next_agent_filter(&tasks, Some("gemini")), None);
+    assert_eq!(next_agent_filter(&[], None), None);
+}
+
+// === Pinned Task Tests ===
+
+#[test]
+fn test_sort_column_pinned_first_stable() {
+    let a = create_test_task("A", TaskStatus::Backlog);
+    let mut b = create_test_task("B", TaskStatus::Backlog);
+    let c = create_test_task("C", TaskStatus::Backlog);
+    let mut d = create_test_task("D", TaskStatus::Backlog);
+    b.pinned = true;
+    d.pinned = true;
+
+    let mut column = vec![&a, &b, &c, &d];
+    sort_column(&mut column);
+    let titles: Vec<&str> = column.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["B", "D", "A", "C"]);
+}
+
+#[test]
+fn test_pinned_task_leads_its_column() {
+    let mut board = BoardState::new();
+    board.tasks = vec![
+        create_test_task("Old", TaskStatus::Backlog),
+        create_test_task("Pinned", TaskStatus::Backlog),
+        create_test_task("Elsewhere", TaskStatus::Running),
+    ];
+    board.tasks[1].pinned = true;
+
+    assert_eq!(board.selected_task().unwrap().title, "Pinned");
+    assert_eq!(board.tasks_in_column(0)[1].title, "Old");
+    let id = board.tasks[0].id.clone();
+    assert_eq!(board.row_of(&id), Some((0, 1)));
+    let id = board.tasks[2].id.clone();
+    assert_eq!(board.row_of(&id), Some((2, 0)));
+    assert_eq!(board.row_of("gone"), None);
+}
+
+// === Collapsed Column Tests ===
+
+#[test]
+fn test_toggle_collapsed_keeps_one_column_expanded() {
+    let mut board = BoardState::new();
+    for column in 0..4 {
+        assert!(board.toggle_collapsed(column));
+    }
+    // Done is the only expanded column left
+    assert!(!board.toggle_collapsed(4));
+    assert_eq!(board.collapsed_flags(), vec![true, true, true, true, false]);
+
+    assert!(board.toggle_collapsed(0));
+    assert!(!board.collapsed.contains(&0));
+    assert!(!board.toggle_collapsed(99));
+}
+
+#[test]
+fn test_column_widths_all_expanded() {
+    assert_eq!(column_widths(100, &[false; 5], 0), vec![20; 5]);
+    // Leftover cells go to the focused column
+    assert_eq!(column_widths(83, &[false; 5], 2), vec![16, 16, 19, 16, 16]);
+    // ...or to the leftmost expanded ones when it's collapsed
+    assert_eq!(column_widths(83, &[false, false, false, false, true], 4), vec![20, 20, 19, 19, 5]);
+}
+
+#[test]
+fn test_column_widths_collapsed_columns_free_space() {
+    let widths = column_widths(80, &[true, false, false, true, false], 4);
+    assert_eq!(widths, vec![5, 23, 23, 5, 24]);
+    assert_eq!(widths.iter().sum::<u16>(), 80);
+}
+
+#[test]
+fn test_column_widths_focused_column_gets_the_rest() {
+    for width in [40u16, 80, 200] {
+        let widths = column_widths(width, &[true, true, false, true, true], 2);
+        assert_eq!(widths[2], width - 4 * 5);
+        assert_eq!(widths.iter().sum::<u16>(), width);
+    }
+}
+
+#[test]
+fn test_column_widths_narrow_terminal() {
+    // Too narrow for the bars: they shrink rather than overflow
+    let widths = column_widths(12, &[true, true, true, true, false], 4);
+    assert_eq!(widths, vec![3, 3, 3, 3, 0]);
+    assert!(widths.iter().sum::<u16>() <= 12);
+    assert_eq!(column_widths(0, &[false; 5], 0), vec![0; 5]);
+}
+
+#[test]
+fn test_moving_into_a_collapsed_column_expands_it() {
+    let mut board = BoardState::new();
+    board.toggle_collapsed(1);
+    board.toggle_collapsed(3);
+
+    board.move_right();
+    assert_eq!(board.selected_column, 1);
+    assert_eq!(board.collapsed_flags(), vec![false, false, false, true, false]);
+
+    // Collapsing the selected column keeps it collapsed until the selection comes back
+    board.toggle_collapsed(1);
+    board.move_up();
+    assert!(board.collapsed.contains(&1));
+    board.move_right();
+    board.move_left();
+    assert!(!board.collapsed.contains(&1));
+}
+
+// === Next Actionable Tests ===
+
+fn running_exited(title: &str, code: i32) -> Task {
+    let mut task = create_test_task(title, TaskStatus::Running);
+    task.last_exit_code = Some(code);
+    task
+}
+
+#[test]
+fn test_actionability_ranks() {
+    let tasks = vec![
+        running_exited("failed", 1),
+        running_exited("finished", 0),
+        create_test_task("review", TaskStatus::Review),
+        create_test_task("backlog", TaskStatus::Backlog),
+        create_test_task("working", TaskStatus::Running),
+        create_test_task("done", TaskStatus::Done),
+    ];
+    let ranks: Vec<Option<Actionability>> = tasks.iter().map(|t| actionability(t, &tasks)).collect();
+    assert_eq!(
+        ranks,
+        vec![
+            Some(Actionability::Errored),
+            Some(Actionability::Finished),
+            Some(Actionability::Review),
+            Some(Actionability::Waiting),
+            None,
+            None,
+        ]
+    );
+}
+
+#[test]
+fn test_actionability_blocked_backlog_is_not_waiting() {
+    let dep = create_test_task("dep", TaskStatus::Running);
+    let mut blocked = create_test_task("blocked", TaskStatus::Backlog);
+    blocked.depends_on = vec![dep.id.clone()];
+    let tasks = vec![dep, blocked];
+    assert_eq!(actionability(&tasks[1], &tasks), None);
+}
+
+#[test]
+fn test_next_actionable_prefers_errored() {
+    let tasks = vec![
+        create_test_task("review", TaskStatus::Review),
+        running_exited("finished", 0),
+        running_exited("failed", 2),
+    ];
+    assert_eq!(next_actionable(&tasks), Some(tasks[2].id.clone()));
+}
+
+#[test]
+fn test_next_actionable_oldest_first_within_rank() {
+    let mut newer = create_test_task("newer", TaskStatus::Review);
+    let mut older = create_test_task("older", TaskStatus::Review);
+    newer.updated_at = chrono::Utc::now();
+    older.updated_at = newer.updated_at - chrono::Duration::hours(1);
+    let tasks = vec![newer, older];
+    assert_eq!(next_actionable(&tasks), Some(tasks[1].id.clone()));
+}
+
+#[test]
+fn test_next_actionable_none() {
+    let tasks = vec![create_test_task("working", TaskStatus::Running), create_test_task("done", TaskStatus::Done)];
+    assert_eq!(next_actionable(&tasks), None);
+}
+
+#[test]
+fn test_next_actionable_after_cycles() {
+    let tasks = vec![running_exited("failed", 1), create_test_task("review", TaskStatus::Review)];
+    let first = next_actionable_after(&tasks, None).unwrap();
+    let second = next_actionable_after(&tasks, Some(&first)).unwrap();
+    let third = next_actionable_after(&tasks, Some(&second)).unwrap();
+    assert_eq!(first, tasks[0].id);
+    assert_eq!(second, tasks[1].id);
+    assert_eq!(third, first);
+}
+
+#[test]
+fn test_next_actionable_after_non_candidate_starts_at_top() {
+    let tasks = vec![create_test_task("working", TaskStatus::Running), create_test_task("review", TaskStatus::Review)];
+    assert_eq!(next_actionable_after(&tasks, Some(&tasks[0].id)), Some(tasks[1].id.clone()));
+}
+
+// === Staleness Tests ===
+
+#[test]
+fn test_staleness_thresholds() {
+    let config = StalenessConfig::default();
+    let mut task = create_test_task("Waiting", TaskStatus::Review);
+    let entered = task.created_at;
+    task.status_changed_at = Some(entered);
+
+    assert_eq!(staleness(&task, entered + chrono::Duration::hours(2), &config), Staleness::Fresh);
+    assert_eq!(staleness(&task, entered + chrono::Duration::hours(24), &config), Staleness::Stale);
+    assert_eq!(staleness(&task, entered + chrono::Duration::hours(80), &config), Staleness::VeryStale);
+
+    // Counted from the last column change, not from creation
+    task.status_changed_at = Some(entered + chrono::Duration::hours(70));
+    assert_eq!(staleness(&task, entered + chrono::Duration::hours(80), &config), Staleness::Fresh);
+}
+
+#[test]
+fn test_staleness_off_for_queues_and_when_disabled() {
+    let later = chrono::Utc::now() + chrono::Duration::days(30);
+    let config = StalenessConfig::default();
+    assert_eq!(staleness(&create_test_task("Idea", TaskStatus::Backlog), later, &config), Staleness::Fresh);
+    assert_eq!(staleness(&create_test_task("Shipped", TaskStatus::Done), later, &config), Staleness::Fresh);
+
+    let running = create_test_task("Old", TaskStatus::Running);
+    assert_eq!(staleness(&running, later, &config), Staleness::VeryStale);
+    let off = StalenessConfig { enabled: false, ..config };
+    assert_eq!(staleness(&running, later, &off), Staleness::Fresh);
+}
+
+// === Scroll Tests ===
+
+#[test]
+fn test_scroll_offset_fits_without_scrolling() {
+    assert_eq!(scroll_offset(3, Some(1), 5, 4), 0);
+    assert_eq!(scroll_offset(3, None, 0, 4), 0);
+}
+
+#[test]
+fn test_scroll_offset_keeps_previous_while_selection_visible() {
+    assert_eq!(scroll_offset(4, Some(5), 3, 10), 4);
+    assert_eq!(scroll_offset(4, None, 3, 10), 4);
+}
+
+#[test]
+fn test_scroll_offset_follows_selection() {
+    // Below the window: scroll just enough to show it at the bottom
+    assert_eq!(scroll_offset(0, Some(6), 3, 10), 4);
+    // Above the window: it becomes the first row
+    assert_eq!(scroll_offset(5, Some(2), 3, 10), 2);
+}
+
+#[test]
+fn test_scroll_offset_clamped_to_column_end() {
+    assert_eq!(scroll_offset(20, None, 3, 10), 7);
+}
+
+#[test]
+fn test_board_scroll_offset_per_column() {
+    let board = BoardState::new();
+    assert_eq!(board.scroll_offset(2), 0);
+
+    board.set_scroll_offset(2, 4);
+    assert_eq!(board.scroll_offset(2), 4);
+    assert_eq!(board.scroll_offset(0), 0);
+
+    // Columns that don't exist are ignored
+    board.set_scroll_offset(99, 1);
+    assert_eq!(board.scroll_offset(99), 0);
+}
+
+// === BoardStats Tests ===
+
+#[test]
+fn test_board_stats_empty() {
+    let stats = board_stats(&[]);
+
+    assert_eq!(stats.total, 0);
+    assert_eq!(stats.active_worktrees, 0);
+    assert_eq!(stats.sessions, 0);
+    assert_eq!(stats.per_status.len(), TaskStatus::columns().len());
+    assert!(stats.per_status.iter().all(|(_, count)| *count == 0));
+}
+
+#[test]
+fn test_board_stats_counts_per_column() {
+    let tasks = vec![
+        create_test_task("Task 1", TaskStatus::Backlog),
+        create_test_task("Task 2", TaskStatus::Backlog),
+        create_test_task("Task 3", TaskStatus::Planning),
+        create_test_task("Task 4", TaskStatus::Running),
+        create_test_task("Task 5", TaskStatus::Done),
+    ];
+
+    let stats = board_stats(&tasks);
+
+    assert_eq!(stats.total, 5);
+    assert_eq!(stats.count(TaskStatus::Backlog), 2);
+    assert_eq!(stats.count(TaskStatus::Planning), 1);
+    assert_eq!(stats.count(TaskStatus::Running), 1);
+    assert_eq!(stats.count(TaskStatus::Review), 0);
+    assert_eq!(stats.count(TaskStatus::Done), 1);
+    assert_eq!(stats.in_flight(), 2);
+}
+
+#[test]
+fn test_board_stats_worktrees_and_sessions() {
+    let mut running = create_test_task("Running", TaskStatus::Running);
+    running.worktree_path = Some("/tmp/wt/running".to_string());
+    running.session_name = Some("proj:task-running".to_string());
+
+    let mut review = create_test_task("Review", TaskStatus::Review);
+    review.worktree_path = Some("/tmp/wt/review".to_string());
+
+    let backlog = create_test_task("Backlog", TaskStatus::Backlog);
+
+    let stats = board_stats(&[running, review, backlog]);
+
+    assert_eq!(stats.active_worktrees, 2);
+    assert_eq!(stats.sessions, 1);
+}
+
+#[test]
+fn test_board_stats_usage_totals() {
+    let mut cheap = create_test_task("Cheap", TaskStatus::Running);
+    cheap.usage = Some(AgentUsage { tokens_in: 1_000, tokens_out: 200, cost_usd: Some(0.01) });
+    let mut costly = create_test_task("Costly", TaskStatus::Review);
+    costly.usage = Some(AgentUsage { tokens_in: 50_000, tokens_out: 8_000, cost_usd: Some(1.5) });
+    // Usage without a cost still counts its tokens
+    let mut codex = create_test_task("Codex", TaskStatus::Done);
+    codex.usage = Some(AgentUsage { tokens_in: 5_000, tokens_out: 500, cost_usd: None });
+    let idle = create_test_task("Idle", TaskStatus::Backlog);
+
+    let stats = board_stats(&[cheap, costly, codex, idle]);
+
+    assert_eq!(stats.usage.tokens_in, 56_000);
+    assert_eq!(stats.usage.tokens_out, 8_700);
+    assert!((stats.usage.cost_usd.unwrap() - 1.51).abs() < 1e-9);
+    let titles: Vec<&str> = stats.top_usage.iter().map(|(t, _)| t.as_str()).collect();
+    assert_eq!(titles, vec!["Costly", "Codex", "Cheap"]);
+}
+
+#[test]
+fn test_board_stats_no_usage() {
+    let stats = board_stats(&[create_test_task("Idle", TaskStatus::Backlog)]);
+    assert_eq!(stats.usage, AgentUsage::default());
+    assert!(stats.top_usage.is_empty());
+}
@@ -1,7 +1,10 @@
 use agtx::config::{
-    determine_first_run_action, FirstRunAction, GlobalConfig, MergedConfig, ProjectConfig,
-    ThemeConfig, WorktreeConfig,
+    determine_first_run_action, CleanupAction, CleanupPolicy, validate_columns, validate_env, DirtyWorktreePolicy, FirstRunAction, GlobalConfig, HooksConfig, MergedConfig, Multiplexer,
+    ProjectConfig, PromptDelivery, PromptTemplates, TaskFilter, Theme, ThemeConfig, View, WebhookConfig, WorktreeConfig, THEME_NAMES,
 };
+use agtx::config::{apply_view, next_view};
+use agtx::db::{Task, TaskStatus};
+use agtx::tmux::WindowSetup;
 
 // === ThemeConfig Tests ===
 
@@ -30,19 +33,82 @@ fn test_parse_hex_invalid() {
 }
 
 #[test]
-fn test_theme_config_default() {
-    let theme = ThemeConfig::default();
+fn test_builtin_themes_have_valid_colors() {
+    for name in THEME_NAMES {
+        let theme = Theme::builtin(name).unwrap();
 
-    // Verify all default colors are valid hex
-    assert!(ThemeConfig::parse_hex(&theme.color_selected).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_normal).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_dimmed).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_text).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_accent).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_description).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_column_header).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_popup_border).is_some());
-    assert!(ThemeConfig::parse_hex(&theme.color_popup_header).is_some());
+        // Verify every color of every palette is valid hex
+        for color in [
+            &theme.color_selected,
+            &theme.color_normal,
+            &theme.color_dimmed,
+            &theme.color_text,
+            &theme.color_accent,
+            &theme.color_description,
+            &theme.color_column_header,
+            &theme.color_popup_border,
+            &theme.color_popup_header,
+            &theme.color_backlog,
+            &theme.color_planning,
+            &theme.color_running,
+            &theme.color_review,
+            &theme.color_done,
+            &theme.color_ready,
+            &theme.color_working,
+            &theme.color_exited,
+        ] {
+            assert!(ThemeConfig::parse_hex(color).is_some(), "{}: {}", name, color);
+        }
+    }
+}
+
+#[test]
+fn test_theme_resolve_default() {
+    let theme = Theme::resolve(&ThemeConfig::default()).unwrap();
+    assert_eq!(theme, Theme::default());
+    assert_eq!(theme.color_selected, "#ead49a");
+}
+
+#[test]
+fn test_theme_resolve_named_with_overrides() {
+    let config: ThemeConfig = toml::from_str(
+        r##"
+        name = "high-contrast"
+        color_selected = "#FF8800"
+        "##,
+    )
+    .unwrap();
+
+    let theme = Theme::resolve(&config).unwrap();
+    assert_eq!(theme.color_selected, "#FF8800");
+    assert_eq!(theme.color_text, Theme::builtin("high-contrast").unwrap().color_text);
+}
+
+#[test]
+fn test_theme_resolve_unknown_name_lists_valid_names() {
+    let config = ThemeConfig { name: Some("neon".to_string()), ..Default::default() };
+
+    let err = Theme::resolve(&config).unwrap_err().to_string();
+    assert!(err.contains("neon"));
+    assert!(err.contains("default, high-contrast, solarized"));
+}
+
+#[test]
+fn test_theme_status_color() {
+    let theme = Theme::builtin("solarized").unwrap();
+    assert_eq!(theme.status_color(TaskStatus::Running), theme.color_running);
+    assert_eq!(theme.status_color(TaskStatus::Done), theme.color_done);
+    assert_ne!(theme.status_color(TaskStatus::Planning), theme.status_color(TaskStatus::Review));
+}
+
+#[test]
+fn test_global_config_theme_shorthand() {
+    let config: GlobalConfig = toml::from_str(r#"theme = "solarized""#).unwrap();
+    assert_eq!(config.theme.name.as_deref(), Some("solarized"));
+
+    let config: GlobalConfig = toml::from_str("[theme]\nname = \"high-contrast\"").unwrap();
+    assert_eq!(config.theme.name.as_deref(), Some("high-contrast"));
+    assert_eq!(MergedConfig::merge(&config, &ProjectConfig::default()).theme, Theme::builtin("high-contrast").unwrap());
 }
 
 // === GlobalConfig Tests ===
@@ -54,7 +120,95 @@ fn test_global_config_default() {
     assert_eq!(config.default_agent, "claude");
     assert!(config.worktree.enabled);
     assert!(config.worktree.auto_cleanup);
-    assert_eq!(config.worktree.base_branch, "main");
+    assert!(config.worktree.base_branch.is_none());
+    assert!(config.keybindings.is_empty());
+    assert!(config.confirm_destructive);
+}
+
+#[test]
+fn test_global_config_confirm_destructive_opt_out() {
+    let config: GlobalConfig = toml::from_str("confirm_destructive = false").unwrap();
+    assert!(!config.confirm_destructive);
+
+    let merged = MergedConfig::merge(&config, &ProjectConfig::default());
+    assert!(!merged.confirm_destructive);
+}
+
+#[test]
+fn test_global_config_parses_keybindings() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+        [keybindings]
+        move_forward = "n"
+        delete = "ctrl+x"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.keybindings.get("move_forward").map(String::as_str), Some("n"));
+    assert_eq!(config.keybindings.get("delete").map(String::as_str), Some("ctrl+x"));
+}
+
+#[test]
+fn test_global_config_max_concurrent_agents() {
+    assert_eq!(GlobalConfig::default().max_concurrent_agents, 0);
+
+    let config: GlobalConfig = toml::from_str("max_concurrent_agents = 2").unwrap();
+    let merged = MergedConfig::merge(&config, &ProjectConfig::default());
+    assert_eq!(merged.max_concurrent_agents, 2);
+}
+
+#[test]
+fn test_global_config_git_timeouts() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+        [git_timeouts]
+        network_secs = 300
+        "#,
+    )
+    .unwrap();
+
+    let timeouts = config.git_timeouts.to_timeouts();
+    assert_eq!(timeouts.network, std::time::Duration::from_secs(300));
+    // Unset classes keep their defaults
+    assert_eq!(timeouts.local, agtx::git::GitTimeouts::default().local);
+    assert_eq!(timeouts.worktree, agtx::git::GitTimeouts::default().worktree);
+}
+
+#[test]
+fn test_global_config_parses_templates() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+        [[templates]]
+        name = "bugfix"
+        title_prefix = "fix: "
+        description = "Steps to reproduce:"
+        agent = "codex"
+        tags = ["bug"]
+
+        [[templates]]
+        name = "feature"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.templates.len(), 2);
+    assert_eq!(config.templates[0].name, "bugfix");
+    assert_eq!(config.templates[0].title_prefix, "fix: ");
+    assert_eq!(config.templates[0].agent.as_deref(), Some("codex"));
+    assert_eq!(config.templates[0].tags, vec!["bug".to_string()]);
+    assert_eq!(config.templates[1].title_prefix, "");
+    assert!(config.templates[1].description.is_none());
+
+    let merged = MergedConfig::merge(&config, &ProjectConfig::default());
+    assert_eq!(merged.templates, config.templates);
+}
+
+#[test]
+fn test_global_config_templates_default_empty() {
+    let config = GlobalConfig::default();
+    assert!(config.templates.is_empty());
+    assert!(!toml::to_string(&config).unwrap().contains("templates"));
 }
 
 // === WorktreeConfig Tests ===
@@ -65,7 +219,46 @@ fn test_worktree_config_default() {
 
     assert!(config.enabled);
     assert!(config.auto_cleanup);
-    assert_eq!(config.base_branch, "main");
+    assert!(config.base_branch.is_none());
+    assert_eq!(config.dirty_policy, DirtyWorktreePolicy::Abort);
+    assert_eq!(config.worktree_dir, ".agtx/worktrees");
+    assert_eq!(config.min_free_mb, None);
+}
+
+#[test]
+fn test_worktree_config_min_free_mb_parse() {
+    let config: WorktreeConfig = toml::from_str("min_free_mb = 2048").unwrap();
+    assert_eq!(config.min_free_mb, Some(2048));
+}
+
+#[test]
+fn test_worktree_config_worktree_dir_parse() {
+    let config: WorktreeConfig = toml::from_str(r#"worktree_dir = "/mnt/fast/worktrees""#).unwrap();
+    assert_eq!(config.worktree_dir, "/mnt/fast/worktrees");
+
+    let config: WorktreeConfig = toml::from_str("enabled = true").unwrap();
+    assert_eq!(config.worktree_dir, ".agtx/worktrees");
+}
+
+#[test]
+fn test_worktree_config_base_branch_parse() {
+    let config: WorktreeConfig = toml::from_str(r#"base_branch = "trunk""#).unwrap();
+    assert_eq!(config.base_branch.as_deref(), Some("trunk"));
+
+    let global = GlobalConfig { worktree: config, ..Default::default() };
+    let merged = MergedConfig::merge(&global, &ProjectConfig::default());
+    assert_eq!(merged.base_branch.as_deref(), Some("trunk"));
+}
+
+#[test]
+fn test_worktree_config_dirty_policy_parse() {
+    let config: WorktreeConfig = toml::from_str(r#"dirty_policy = "stash""#).unwrap();
+    assert_eq!(config.dirty_policy, DirtyWorktreePolicy::Stash);
+
+    let config: WorktreeConfig = toml::from_str(r#"dirty_policy = "force""#).unwrap();
+    assert_eq!(config.dirty_policy, DirtyWorktreePolicy::Force);
+
+    assert!(toml::from_str::<WorktreeConfig>(r#"dirty_policy = "yolo""#).is_err());
 }
 
 // === ProjectConfig Tests ===
@@ -91,7 +284,7 @@ fn test_merged_config_uses_global_defaults() {
     let merged = MergedConfig::merge(&global, &project);
 
     assert_eq!(merged.default_agent, "claude");
-    assert_eq!(merged.base_branch, "main");
+    assert!(merged.base_branch.is_none());
     assert!(merged.worktree_enabled);
     assert!(merged.auto_cleanup);
     assert!(merged.copy_files.is_none());
@@ -108,15 +301,523 @@ fn test_merged_config_project_overrides() {
         copy_files: Some(".env, .env.local".to_string()),
         init_script: Some("npm install".to_string()),
         workflow_plugin: None,
+        auto_commit_on_review: Some(true),
+        fast_mode: false,
+        confirm_destructive: None,
+        tmux_session: None,
+        prompts: Default::default(),
+        prompt_prefix: None,
+        prompt_suffix: None,
+        hooks: Default::default(),
+        env: Default::default(),
+        window_startup_command: None,
+        window_name_template: None,
+        test_command: None,
+        columns: Default::default(),
+        webhook: Default::default(),
     };
 
     let merged = MergedConfig::merge(&global, &project);
 
     assert_eq!(merged.default_agent, "codex");
-    assert_eq!(merged.base_branch, "develop");
+    assert_eq!(merged.base_branch.as_deref(), Some("develop"));
     assert_eq!(merged.github_url, Some("https://github.com/user/repo".to_string()));
     assert_eq!(merged.copy_files, Some(".env, .env.local".to_string()));
     assert_eq!(merged.init_script, Some("npm install".to_string()));
+    assert!(merged.auto_commit_on_review);
+}
+
+#[test]
+fn test_merged_config_auto_commit_defaults_off() {
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    assert!(!merged.auto_commit_on_review);
+
+    let mut global = GlobalConfig::default();
+    global.worktree.auto_commit_on_review = true;
+    let merged = MergedConfig::merge(&global, &ProjectConfig::default());
+    assert!(merged.auto_commit_on_review);
+}
+
+#[test]
+fn test_merged_config_fast_mode_flips_defaults() {
+    let fast = ProjectConfig { fast_mode: true, ..Default::default() };
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &fast);
+    assert!(merged.fast_mode);
+    assert!(!merged.confirm_destructive);
+    assert!(merged.auto_commit_on_review);
+
+    let safe = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    assert!(!safe.fast_mode);
+    assert!(safe.confirm_destructive);
+    assert!(!safe.auto_commit_on_review);
+}
+
+#[test]
+fn test_merged_config_fast_mode_keeps_explicit_project_settings() {
+    let project: ProjectConfig = toml::from_str(
+        "fast_mode = true\nconfirm_destructive = true\nauto_commit_on_review = false\n",
+    )
+    .unwrap();
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &project);
+    assert!(merged.confirm_destructive);
+    assert!(!merged.auto_commit_on_review);
+
+    // Without fast mode a project can still turn confirmations off on its own
+    let project = ProjectConfig { confirm_destructive: Some(false), ..Default::default() };
+    assert!(!MergedConfig::merge(&GlobalConfig::default(), &project).confirm_destructive);
+}
+
+#[test]
+fn test_merged_config_tmux_session() {
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    assert_eq!(merged.tmux_session_for("myproject"), "myproject");
+
+    let global: GlobalConfig = toml::from_str(r#"tmux_session = "work""#).unwrap();
+    let merged = MergedConfig::merge(&global, &ProjectConfig::default());
+    assert_eq!(merged.tmux_session_for("myproject"), "work");
+
+    let project = ProjectConfig { tmux_session: Some("side".to_string()), ..Default::default() };
+    assert_eq!(MergedConfig::merge(&global, &project).tmux_session_for("myproject"), "side");
+
+    // A blank name falls back to the per-project session
+    let project = ProjectConfig { tmux_session: Some("  ".to_string()), ..Default::default() };
+    assert_eq!(MergedConfig::merge(&global, &project).tmux_session_for("myproject"), "myproject");
+}
+
+#[test]
+fn test_merged_config_prompt_prefix_and_suffix() {
+    let project: ProjectConfig = toml::from_str(
+        r#"
+        prompt_prefix = "Follow CONVENTIONS.md."
+        prompt_suffix = "  "
+        "#,
+    )
+    .unwrap();
+
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &project);
+
+    assert_eq!(merged.prompts.prefix.as_deref(), Some("Follow CONVENTIONS.md."));
+    // Blank counts as unset
+    assert_eq!(merged.prompts.suffix, None);
+    assert_eq!(merged.prompts.wrap("Plan it".to_string()), "Follow CONVENTIONS.md.\n\nPlan it");
+
+    // Empty by default, so prompts go out unchanged
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    assert_eq!(merged.prompts.wrap("Plan it".to_string()), "Plan it");
+}
+
+#[test]
+fn test_prompt_wrap_keeps_empty_prompt_empty() {
+    let prompts = PromptTemplates {
+        prefix: Some("Be brief.".to_string()),
+        suffix: Some("Run cargo clippy.".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(prompts.wrap("Fix it".to_string()), "Be brief.\n\nFix it\n\nRun cargo clippy.");
+    assert_eq!(prompts.wrap(String::new()), "");
+}
+
+#[test]
+fn test_merged_config_prompts_project_over_global() {
+    let global: GlobalConfig = toml::from_str(
+        r#"
+        [prompts]
+        planning = "Plan {title}"
+        review = "Review {worktree}"
+        "#,
+    )
+    .unwrap();
+    let project: ProjectConfig = toml::from_str(
+        r#"
+        [prompts]
+        planning = "Project plan {task}"
+        "#,
+    )
+    .unwrap();
+
+    let merged = MergedConfig::merge(&global, &project);
+
+    assert_eq!(merged.prompts.planning.as_deref(), Some("Project plan {task}"));
+    assert_eq!(merged.prompts.review.as_deref(), Some("Review {worktree}"));
+    assert_eq!(merged.prompts.running, None);
+}
+
+#[test]
+fn test_prompt_templates_omitted_when_unset() {
+    let content = toml::to_string_pretty(&GlobalConfig::default()).unwrap();
+    assert!(!content.contains("[prompts]"));
+    assert!(PromptTemplates::default().is_empty());
+}
+
+#[test]
+fn test_hooks_parse_and_order_specific_first() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+        [hooks]
+        strict = true
+        to_done = "notify-send {title}"
+        review_to_done = "./scripts/archive.sh {slug}"
+        "#,
+    )
+    .unwrap();
+
+    assert!(config.hooks.strict);
+    assert!(config.hooks.validate().is_ok());
+    assert_eq!(
+        config.hooks.commands_for(TaskStatus::Review, TaskStatus::Done),
+        vec![
+            ("review_to_done".to_string(), "./scripts/archive.sh {slug}"),
+            ("to_done".to_string(), "notify-send {title}"),
+        ]
+    );
+    assert!(config.hooks.commands_for(TaskStatus::Running, TaskStatus::Review).is_empty());
+}
+
+#[test]
+fn test_hooks_reject_unknown_names() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+        [hooks]
+        runing_to_review = "make lint"
+        "#,
+    )
+    .unwrap();
+
+    let err = config.hooks.validate().unwrap_err();
+    assert!(err.to_string().contains("runing_to_review"));
+}
+
+#[test]
+fn test_merged_config_hooks_project_over_global() {
+    let global: GlobalConfig = toml::from_str(
+        r#"
+        [hooks]
+        to_done = "global-done"
+        to_review = "global-review"
+        "#,
+    )
+    .unwrap();
+    let project: ProjectConfig = toml::from_str(
+        r#"
+        [hooks]
+        strict = true
+        to_review = "make lint"
+        "#,
+    )
+    .unwrap();
+
+    let hooks = MergedConfig::merge(&global, &project).hooks;
+
+    assert!(hooks.strict);
+    assert_eq!(hooks.commands.get("to_review").map(String::as_str), Some("make lint"));
+    assert_eq!(hooks.commands.get("to_done").map(String::as_str), Some("global-done"));
+    assert!(!toml::to_string_pretty(&GlobalConfig::default()).unwrap().contains("[hooks]"));
+    assert!(HooksConfig::default().is_empty());
+}
+
+#[test]
+fn test_merged_config_env_layers_global_project_task() {
+    let global: GlobalConfig = toml::from_str(
+        r#"
+        [env]
+        PROJECT_ENV = "dev"
+        API_URL = "https://api.example.com"
+        "#,
+    )
+    .unwrap();
+    let project: ProjectConfig = toml::from_str(
+        r#"
+        [env]
+        PROJECT_ENV = "staging"
+        "#,
+    )
+    .unwrap();
+    let merged = MergedConfig::merge(&global, &project);
+    let mut task = Task::new("Task", "claude", "proj");
+    task.env.insert("API_URL".to_string(), "http://localhost:8080".to_string());
+
+    let env = merged.env_for(&task);
+
+    assert_eq!(env.get("PROJECT_ENV").map(String::as_str), Some("staging"));
+    assert_eq!(env.get("API_URL").map(String::as_str), Some("http://localhost:8080"));
+    assert_eq!(merged.env.get("API_URL").map(String::as_str), Some("https://api.example.com"));
+    assert!(!toml::to_string_pretty(&GlobalConfig::default()).unwrap().contains("[env]"));
+}
+
+#[test]
+fn test_env_rejects_names_the_shell_cannot_export() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+        [env]
+        FOO = "bar"
+        _PRIVATE = "1"
+        "#,
+    )
+    .unwrap();
+    assert!(validate_env(&config.env).is_ok());
+
+    for bad in ["1FOO", "MY-VAR", "A B", ""] {
+        let env = [(bad.to_string(), "x".to_string())].into_iter().collect();
+        assert!(validate_env(&env).is_err(), "{:?} should be rejected", bad);
+    }
+}
+
+#[test]
+fn test_merged_config_window_name_template() {
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    assert_eq!(merged.window_name_template, "task-{slug}");
+
+    let global: GlobalConfig = toml::from_str(r#"window_name_template = "agtx:{title}""#).unwrap();
+    let merged = MergedConfig::merge(&global, &ProjectConfig::default());
+    assert_eq!(merged.window_name_template, "agtx:{title}");
+
+    // A project template wins; an empty one keeps the global
+    let project: ProjectConfig = toml::from_str(r#"window_name_template = "{id}""#).unwrap();
+    assert_eq!(MergedConfig::merge(&global, &project).window_name_template, "{id}");
+    let project: ProjectConfig = toml::from_str(r#"window_name_template = """#).unwrap();
+    let merged = MergedConfig::merge(&global, &project);
+    assert_eq!(merged.window_name_template, "agtx:{title}");
+    let task = Task::new("Task", "claude", "proj");
+    assert_eq!(WindowSetup::for_task(&merged, &task).name_template, "agtx:{title}");
+}
+
+#[test]
+fn test_merged_config_window_startup_command() {
+    let global: GlobalConfig = toml::from_str(r#"window_startup_command = "direnv allow""#).unwrap();
+    let merged = MergedConfig::merge(&global, &ProjectConfig::default());
+    assert_eq!(merged.window_startup_command.as_deref(), Some("direnv allow"));
+
+    // A project can switch it off with an empty string
+    let project: ProjectConfig = toml::from_str(r#"window_startup_command = """#).unwrap();
+    assert_eq!(MergedConfig::merge(&global, &project).window_startup_command, None);
+
+    let project: ProjectConfig = toml::from_str(r#"window_startup_command = "nvm use""#).unwrap();
+    let task = Task::new("Task", "claude", "proj");
+    let setup = WindowSetup::for_task(&MergedConfig::merge(&global, &project), &task);
+    assert_eq!(setup.startup_command.as_deref(), Some("nvm use"));
+}
+
+#[test]
+fn test_merged_config_test_command_is_opt_in() {
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    assert_eq!(merged.test_command, None);
+
+    let global: GlobalConfig = toml::from_str(r#"test_command = "cargo test""#).unwrap();
+    let project: ProjectConfig = toml::from_str(r#"test_command = "npm test""#).unwrap();
+    assert_eq!(MergedConfig::merge(&global, &project).test_command.as_deref(), Some("npm test"));
+
+    let project: ProjectConfig = toml::from_str(r#"test_command = " ""#).unwrap();
+    assert_eq!(MergedConfig::merge(&global, &project).test_command, None);
+}
+
+#[test]
+fn test_column_on_enter_prompt() {
+    let global: GlobalConfig = toml::from_str(
+        r#"
+        [columns.review]
+        on_enter_prompt = "run through the test plan"
+
+        [columns.running]
+        on_enter_prompt = "  "
+        "#,
+    )
+    .unwrap();
+    assert!(validate_columns(&global.columns).is_ok());
+
+    let merged = MergedConfig::merge(&global, &ProjectConfig::default());
+    assert_eq!(merged.on_enter_prompt(TaskStatus::Review), Some("run through the test plan"));
+    assert_eq!(merged.on_enter_prompt(TaskStatus::Running), None);
+    assert_eq!(merged.on_enter_prompt(TaskStatus::Planning), None);
+}
+
+#[test]
+fn test_should_dispatch_defaults_to_every_column() {
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default());
+    for status in TaskStatus::columns() {
+        assert!(merged.should_dispatch(*status), "{:?}", status);
+    }
+
+    let project: ProjectConfig = toml::from_str(
+        r#"
+        [columns.planning]
+        dispatch_agent = false
+
+        [columns.running]
+        dispatch_agent = true
+        "#,
+    )
+    .unwrap();
+    assert!(validate_columns(&project.columns).is_ok());
+    let merged = MergedConfig::merge(&GlobalConfig::default(), &project);
+    assert!(!merged.should_dispatch(TaskStatus::Planning));
+    assert!(merged.should_dispatch(TaskStatus::Running));
+    assert!(merged.should_dispatch(TaskStatus::Review));
+}
+
+#[test]
+fn test_column_on_enter_prompt_project_overrides() {
+    let global: GlobalConfig = toml::from_str("[columns.review]\non_enter_prompt = \"global\"").unwrap();
+    let project: ProjectConfig = toml::from_str("[columns.review]\non_enter_prompt = \"project\"").unwrap();
+    assert_eq!(MergedConfig::merge(&global, &project).on_enter_prompt(TaskStatus::Review), Some("project"));
+}
+
+#[test]
+fn test_validate_columns_rejects_unknown_column() {
+    let global: GlobalConfig = toml::from_str("[columns.qa]\non_enter_prompt = \"test it\"").unwrap();
+    let err = validate_columns(&global.columns).unwrap_err();
+    assert!(err.to_string().contains("'qa'"));
+}
+
+#[test]
+fn test_webhook_config_parse() {
+    let global: GlobalConfig = toml::from_str(
+        r#"
+        [webhook]
+        url = "https://dash.example.com/agtx"
+        auth_header = "Authorization: Bearer abc"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(global.webhook.url(), Some("https://dash.example.com/agtx"));
+    assert_eq!(global.webhook.auth_header.as_deref(), Some("Authorization: Bearer abc"));
+    assert!(global.webhook.validate().is_ok());
+}
+
+#[test]
+fn test_webhook_config_off_by_default() {
+    assert_eq!(GlobalConfig::default().webhook.url(), None);
+    assert!(!toml::to_string(&GlobalConfig::default()).unwrap().contains("webhook"));
+    let blank = WebhookConfig { url: Some("  ".to_string()), auth_header: None };
+    assert_eq!(blank.url(), None);
+}
+
+#[test]
+fn test_webhook_config_validate() {
+    let bad_url = WebhookConfig { url: Some("ftp://x".to_string()), auth_header: None };
+    assert!(bad_url.validate().is_err());
+    let bad_header = WebhookConfig {
+        url: Some("http://localhost:8080".to_string()),
+        auth_header: Some("Bearer abc".to_string()),
+    };
+    assert!(bad_header.validate().is_err());
+}
+
+#[test]
+fn test_webhook_project_overrides_global() {
+    let global: GlobalConfig = toml::from_str("[webhook]\nurl = \"https://global\"").unwrap();
+    assert_eq!(MergedConfig::merge(&global, &ProjectConfig::default()).webhook.url(), Some("https://global"));
+
+    let project: ProjectConfig = toml::from_str("[webhook]\nurl = \"https://project\"").unwrap();
+    assert_eq!(MergedConfig::merge(&global, &project).webhook.url(), Some("https://project"));
+}
+
+#[test]
+fn test_cleanup_defaults_match_full_cleanup() {
+    let config = GlobalConfig::default().cleanup;
+    assert_eq!(
+        config.policy(CleanupAction::Done),
+        CleanupPolicy { kill_window: true, remove_worktree: true, delete_branch: false }
+    );
+    assert_eq!(
+        config.policy(CleanupAction::Delete),
+        CleanupPolicy { kill_window: true, remove_worktree: true, delete_branch: true }
+    );
+    assert!(!toml::to_string(&GlobalConfig::default()).unwrap().contains("[cleanup"));
+}
+
+#[test]
+fn test_cleanup_overrides_per_action() {
+    let global: GlobalConfig = toml::from_str(
+        r#"
+        [cleanup.done]
+        worktree = false
+
+        [cleanup.delete]
+        branch = false
+        "#,
+    )
+    .unwrap();
+    assert!(global.cleanup.validate().is_ok());
+    assert_eq!(
+        global.cleanup.policy(CleanupAction::Done),
+        CleanupPolicy { kill_window: true, remove_worktree: false, delete_branch: false }
+    );
+    assert_eq!(
+        global.cleanup.policy(CleanupAction::Delete),
+        CleanupPolicy { kill_window: true, remove_worktree: true, delete_branch: false }
+    );
+    assert_eq!(MergedConfig::merge(&global, &ProjectConfig::default()).cleanup, global.cleanup);
+}
+
+#[test]
+fn test_cleanup_rejects_branch_without_worktree() {
+    let global: GlobalConfig = toml::from_str("[cleanup.delete]\nworktree = false").unwrap();
+    let err = global.cleanup.validate().unwrap_err();
+    assert!(err.to_string().contains("[cleanup.delete]"));
+}
+
+#[test]
+fn test_global_config_no_tmux_is_opt_in() {
+    assert!(!GlobalConfig::default().no_tmux);
+    let config: GlobalConfig = toml::from_str("no_tmux = true").unwrap();
+    assert!(config.no_tmux);
+    assert!(!toml::to_string(&GlobalConfig::default()).unwrap().contains("no_tmux"));
+}
+
+#[test]
+fn test_global_config_multiplexer() {
+    assert_eq!(GlobalConfig::default().multiplexer, Multiplexer::Tmux);
+    let config: GlobalConfig = toml::from_str(r#"multiplexer = "zellij""#).unwrap();
+    assert_eq!(config.multiplexer, Multiplexer::Zellij);
+    assert_eq!(config.multiplexer.program(), "zellij");
+    assert!(toml::from_str::<GlobalConfig>(r#"multiplexer = "screen""#).is_err());
+}
+
+#[test]
+fn test_global_config_prompt_delivery() {
+    assert_eq!(GlobalConfig::default().prompt_delivery, PromptDelivery::Auto);
+    let config: GlobalConfig = toml::from_str(r#"prompt_delivery = "file""#).unwrap();
+    assert_eq!(config.prompt_delivery, PromptDelivery::File);
+    let merged = MergedConfig::merge(&config, &ProjectConfig::default());
+    let task = Task::new("Fix login", "claude", "proj");
+    assert_eq!(WindowSetup::for_task(&merged, &task).prompt_delivery, PromptDelivery::File);
+    assert!(toml::from_str::<GlobalConfig>(r#"prompt_delivery = "pipe""#).is_err());
+
+    let long = "x".repeat(PromptDelivery::LONG_PROMPT_BYTES + 1);
+    assert!(!PromptDelivery::Auto.uses_file("short"));
+    assert!(PromptDelivery::Auto.uses_file(&long));
+    assert!(PromptDelivery::File.uses_file("short"));
+    // Nothing to write for an agent started without a prompt
+    assert!(!PromptDelivery::File.uses_file(""));
+    assert!(!PromptDelivery::Inline.uses_file(&long));
+}
+
+#[test]
+fn test_global_config_send_keys_interval_defaults_to_no_limit() {
+    assert_eq!(GlobalConfig::default().send_keys_interval_ms, 0);
+    let config: GlobalConfig = toml::from_str("send_keys_interval_ms = 250").unwrap();
+    assert_eq!(config.send_keys_interval_ms, 250);
+}
+
+#[test]
+fn test_global_config_staleness() {
+    let config: GlobalConfig = toml::from_str("").unwrap();
+    assert_eq!(config.staleness, agtx::config::StalenessConfig::default());
+    assert!(config.staleness.enabled);
+
+    let config: GlobalConfig = toml::from_str("[staleness]\nenabled = false\nstale_hours = 4").unwrap();
+    let merged = MergedConfig::merge(&config, &ProjectConfig::default());
+    assert!(!merged.staleness.enabled);
+    assert_eq!(merged.staleness.stale_hours, 4);
+    assert_eq!(merged.staleness.very_stale_hours, 72);
+}
+
+#[test]
+fn test_global_config_max_captured_lines() {
+    assert_eq!(GlobalConfig::default().max_captured_lines, agtx::tmux::DEFAULT_MAX_CAPTURED_LINES);
+    let config: GlobalConfig = toml::from_str("max_captured_lines = 500").unwrap();
+    let merged = MergedConfig::merge(&config, &ProjectConfig::default());
+    assert_eq!(merged.max_captured_lines, 500);
 }
 
 // === FirstRunAction Tests ===
@@ -170,3 +871,88 @@ fn test_first_run_new_user_prompt() {
         FirstRunAction::NewUserPrompt,
     );
 }
+
+// === View Tests ===
+
+fn view_task(title: &str, status: TaskStatus, tags: &[&str]) -> Task {
+    let mut task = Task::new(title, "claude", "proj");
+    task.status = status;
+    task.tags = tags.iter().map(|t| t.to_string()).collect();
+    task
+}
+
+fn view(name: &str) -> View {
+    View { name: name.to_string(), filter: TaskFilter::All(vec![]) }
+}
+
+#[test]
+fn test_views_parse_from_toml() {
+    let config: GlobalConfig = toml::from_str(
+        r#"
+[[views]]
+name = "urgent work"
+filter = { all = [{ tag = "urgent" }, { status = "running" }] }
+
+[[views]]
+name = "not codex"
+filter = { not = { agent = "codex" } }
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.views.len(), 2);
+    assert_eq!(
+        config.views[0].filter,
+        TaskFilter::All(vec![TaskFilter::Tag("urgent".into()), TaskFilter::Status("running".into())])
+    );
+    assert_eq!(config.views[1].filter, TaskFilter::Not(Box::new(TaskFilter::Agent("codex".into()))));
+}
+
+#[test]
+fn test_views_default_empty_and_not_serialized() {
+    let config = GlobalConfig::default();
+    assert!(config.views.is_empty());
+    assert!(!toml::to_string(&config).unwrap().contains("views"));
+}
+
+#[test]
+fn test_apply_view_composes_filters() {
+    let tasks = vec![
+        view_task("Fix login", TaskStatus::Running, &["urgent"]),
+        view_task("Fix logout", TaskStatus::Backlog, &["urgent"]),
+        view_task("Docs", TaskStatus::Running, &[]),
+    ];
+    let urgent_running = View {
+        name: "u".into(),
+        filter: TaskFilter::All(vec![TaskFilter::Tag("#Urgent".into()), TaskFilter::Status("Running".into())]),
+    };
+    let titles: Vec<&str> = apply_view(&urgent_running, &tasks).iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Fix login"]);
+
+    let fix_or_docs = View {
+        name: "f".into(),
+        filter: TaskFilter::Any(vec![TaskFilter::Text("LOGOUT".into()), TaskFilter::Not(Box::new(TaskFilter::Tag("urgent".into())))]),
+    };
+    let titles: Vec<&str> = apply_view(&fix_or_docs, &tasks).iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, vec!["Fix logout", "Docs"]);
+}
+
+#[test]
+fn test_next_view_cycles_through_none() {
+    let views = vec![view("a"), view("b")];
+    assert_eq!(next_view(&views, None).unwrap().name, "a");
+    assert_eq!(next_view(&views, Some("a")).unwrap().name, "b");
+    assert!(next_view(&views, Some("b")).is_none());
+    // A view removed from config starts over from none
+    assert!(next_view(&views, Some("gone")).is_none());
+    assert!(next_view(&[], None).is_none());
+}
+
+#[test]
+fn test_view_filter_validate_rejects_unknown_status() {
+    let ok = TaskFilter::Any(vec![TaskFilter::Status("review".into()), TaskFilter::Tag("x".into())]);
+    assert!(ok.validate().is_ok());
+
+    let bad = TaskFilter::Not(Box::new(TaskFilter::Status("doing".into())));
+    assert!(bad.validate().unwrap_err().to_string().contains("doing"));
+}
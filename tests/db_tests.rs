@@ -1,4 +1,5 @@
-use agtx::db::{Task, TaskStatus, Project};
+use agtx::config::Template;
+use agtx::db::{add_dependency, blocking_tasks, duplicate, find_title_conflicts, is_blocked, Task, TaskStatus, Project};
 
 // === TaskStatus Tests ===
 
@@ -59,6 +60,261 @@ fn test_task_new() {
     assert!(task.branch_name.is_none());
     assert!(task.pr_number.is_none());
     assert!(task.pr_url.is_none());
+    assert!(!task.archived);
+    assert!(task.tags.is_empty());
+}
+
+#[test]
+fn test_task_from_template() {
+    let template = Template {
+        name: "bugfix".to_string(),
+        title_prefix: "fix: ".to_string(),
+        description: Some("Steps to reproduce:".to_string()),
+        agent: Some("codex".to_string()),
+        tags: vec!["bug".to_string()],
+        env: [("RUST_BACKTRACE".to_string(), "1".to_string())].into_iter().collect(),
+    };
+
+    let task = Task::from_template(&template, "crash on startup");
+
+    assert_eq!(task.title, "fix: crash on startup");
+    assert_eq!(task.description.as_deref(), Some("Steps to reproduce:"));
+    assert_eq!(task.agent, "codex");
+    assert_eq!(task.tags, vec!["bug".to_string()]);
+    assert_eq!(task.env.get("RUST_BACKTRACE").map(String::as_str), Some("1"));
+    assert_eq!(task.status, TaskStatus::Backlog);
+}
+
+#[test]
+fn test_duplicate_copies_content_not_resources() {
+    let mut task = Task::new("Add login", "codex", "proj");
+    task.description = Some("Use OAuth".to_string());
+    task.tags = vec!["auth".to_string()];
+    task.env.insert("RUST_LOG".to_string(), "debug".to_string());
+    task.status = TaskStatus::Review;
+    task.session_name = Some("proj:task-add-login".to_string());
+    task.worktree_path = Some("/wt/add-login".to_string());
+    task.branch_name = Some("task/add-login".to_string());
+    task.pr_url = Some("https://github.com/o/r/pull/1".to_string());
+    task.pinned = true;
+    task.add_note("waiting on review");
+    task.created_at = chrono::Utc::now() - chrono::Duration::days(3);
+
+    let copy = duplicate(&task);
+
+    assert_ne!(copy.id, task.id);
+    assert_eq!(copy.title, "Add login (copy)");
+    assert_eq!(copy.description.as_deref(), Some("Use OAuth"));
+    assert_eq!(copy.agent, "codex");
+    assert_eq!(copy.project_id, "proj");
+    assert_eq!(copy.tags, vec!["auth".to_string()]);
+    assert_eq!(copy.env.get("RUST_LOG").map(String::as_str), Some("debug"));
+    assert_eq!(copy.status, TaskStatus::Backlog);
+    assert!(copy.session_name.is_none());
+    assert!(copy.worktree_path.is_none());
+    assert!(copy.branch_name.is_none());
+    assert!(copy.pr_url.is_none());
+    assert!(!copy.pinned);
+    assert!(copy.notes.is_empty());
+    assert!(copy.created_at > task.created_at);
+}
+
+/// Every field a user sets is copied. Destructuring without `..` makes a new `Task` field fail
+/// to compile here until it's sorted into copied or fresh.
+#[test]
+fn test_duplicate_copies_every_user_set_field() {
+    let mut task = Task::new("Add login", "codex", "proj");
+    task.description = Some("Use OAuth".to_string());
+    task.plugin = Some("gsd".to_string());
+    task.tags = vec!["auth".to_string()];
+    task.env.insert("RUST_LOG".to_string(), "debug".to_string());
+    task.subdir = Some("crates/api".to_string());
+    task.from_ref = Some("release-1.2".to_string());
+
+    let copy = duplicate(&task);
+    let Task {
+        // Copied
+        title,
+        description,
+        agent,
+        project_id,
+        plugin,
+        tags,
+        env,
+        subdir,
+        from_ref,
+        // Fresh: identity, progress, resources and history
+        id: _,
+        status: _,
+        session_name: _,
+        worktree_path: _,
+        branch_name: _,
+        pr_number: _,
+        pr_url: _,
+        archived: _,
+        depends_on: _,
+        deleted_at: _,
+        agent_session_id: _,
+        test_status: _,
+        notes: _,
+        last_exit_code: _,
+        pinned: _,
+        usage: _,
+        status_changed_at: _,
+        slug: _,
+        created_at: _,
+        updated_at: _,
+    } = copy;
+
+    assert_eq!(title, "Add login (copy)");
+    assert_eq!(description, task.description);
+    assert_eq!(agent, task.agent);
+    assert_eq!(project_id, task.project_id);
+    assert_eq!(plugin, task.plugin);
+    assert_eq!(tags, task.tags);
+    assert_eq!(env, task.env);
+    assert_eq!(subdir, task.subdir);
+    assert_eq!(from_ref, task.from_ref);
+}
+
+#[test]
+fn test_task_from_empty_template_matches_plain_task() {
+    let template = Template { name: "plain".to_string(), ..Default::default() };
+
+    let task = Task::from_template(&template, "Do the thing");
+
+    assert_eq!(task.title, "Do the thing");
+    assert!(task.description.is_none());
+    assert!(task.agent.is_empty());
+    assert!(task.tags.is_empty());
+}
+
+#[test]
+fn test_restore_from_trash_returns_to_backlog_without_resources() {
+    let mut task = Task::new("Trashed", "claude", "proj");
+    task.status = TaskStatus::Review;
+    task.session_name = Some("proj:task-1".to_string());
+    task.worktree_path = Some("/tmp/wt".to_string());
+    task.branch_name = Some("task/trashed".to_string());
+    task.slug = Some("trashed".to_string());
+    task.pr_number = Some(3);
+    task.agent_session_id = Some("sess-1".to_string());
+    task.tags = vec!["ui".to_string()];
+    task.description = Some("keep me".to_string());
+    task.deleted_at = Some(chrono::Utc::now());
+    assert!(task.is_trashed());
+
+    task.restore_from_trash();
+
+    assert!(!task.is_trashed());
+    assert_eq!(task.status, TaskStatus::Backlog);
+    assert!(task.session_name.is_none());
+    assert!(task.worktree_path.is_none());
+    assert!(task.branch_name.is_none());
+    assert!(task.slug.is_none());
+    assert!(task.pr_number.is_none());
+    assert!(task.agent_session_id.is_none());
+    assert_eq!(task.tags, vec!["ui".to_string()]);
+    assert_eq!(task.description.as_deref(), Some("keep me"));
+}
+
+#[test]
+fn test_add_note_stamps_time_and_skips_blank() {
+    let mut task = Task::new("Noted", "claude", "proj");
+    let before = chrono::Utc::now();
+    task.add_note("  check the retry logic  ");
+    task.add_note("   ");
+    task.add_note("asked for a smaller diff");
+
+    assert_eq!(task.notes.len(), 2);
+    assert_eq!(task.notes[0].text, "check the retry logic");
+    assert!(task.notes[0].at >= before);
+    assert_eq!(task.notes[1].text, "asked for a smaller diff");
+    assert!(task.description.is_none());
+}
+
+#[test]
+fn test_remove_last_note() {
+    let mut task = Task::new("Noted", "claude", "proj");
+    assert!(task.remove_last_note().is_none());
+    task.add_note("first");
+    task.add_note("second");
+
+    assert_eq!(task.remove_last_note().map(|n| n.text), Some("second".to_string()));
+    assert_eq!(task.notes.len(), 1);
+    assert_eq!(task.notes[0].text, "first");
+}
+
+// === Dependency Tests ===
+
+fn task_with_status(title: &str, status: TaskStatus) -> Task {
+    let mut task = Task::new(title, "claude", "proj");
+    task.status = status;
+    task
+}
+
+#[test]
+fn test_is_blocked_until_dependency_done() {
+    let mut dep = task_with_status("Schema", TaskStatus::Running);
+    let mut task = task_with_status("API", TaskStatus::Planning);
+    task.depends_on.push(dep.id.clone());
+
+    let all = vec![dep.clone(), task.clone()];
+    assert!(is_blocked(&task, &all));
+    assert_eq!(blocking_tasks(&task, &all)[0].title, "Schema");
+
+    dep.status = TaskStatus::Done;
+    let all = vec![dep, task.clone()];
+    assert!(!is_blocked(&task, &all));
+}
+
+#[test]
+fn test_is_blocked_ignores_deleted_dependencies() {
+    let mut task = task_with_status("API", TaskStatus::Backlog);
+    task.depends_on.push("gone".to_string());
+
+    assert!(!is_blocked(&task, &[task.clone()]));
+}
+
+#[test]
+fn test_add_dependency() {
+    let dep = task_with_status("Schema", TaskStatus::Backlog);
+    let mut task = task_with_status("API", TaskStatus::Backlog);
+    let all = vec![dep.clone(), task.clone()];
+
+    add_dependency(&mut task, &dep.id, &all).unwrap();
+    // Adding the same dependency twice is a no-op
+    add_dependency(&mut task, &dep.id, &all).unwrap();
+
+    assert_eq!(task.depends_on, vec![dep.id]);
+}
+
+#[test]
+fn test_add_dependency_rejects_self_and_unknown() {
+    let mut task = task_with_status("API", TaskStatus::Backlog);
+    let all = vec![task.clone()];
+    let id = task.id.clone();
+
+    assert!(add_dependency(&mut task, &id, &all).is_err());
+    assert!(add_dependency(&mut task, "missing", &all).is_err());
+    assert!(task.depends_on.is_empty());
+}
+
+#[test]
+fn test_add_dependency_rejects_cycle() {
+    let a = task_with_status("A", TaskStatus::Backlog);
+    let mut b = task_with_status("B", TaskStatus::Backlog);
+    let mut c = task_with_status("C", TaskStatus::Backlog);
+    b.depends_on.push(a.id.clone());
+    c.depends_on.push(b.id.clone());
+    let all = vec![a.clone(), b.clone(), c.clone()];
+
+    // A -> C would close A -> C -> B -> A
+    let mut a = a;
+    let err = add_dependency(&mut a, &c.id, &all).unwrap_err();
+    assert!(err.to_string().contains("cycle"));
+    assert!(err.to_string().contains("A → C → B → A"));
+    assert!(a.depends_on.is_empty());
 }
 
 #[test]
@@ -84,6 +340,21 @@ fn test_task_generate_session_name_special_chars() {
     assert!(!session_name.contains("!"));
 }
 
+#[test]
+fn test_find_title_conflicts() {
+    let open = Task::new("Fix login", "claude", "proj");
+    let mut done = Task::new("Fix login", "claude", "proj");
+    done.status = TaskStatus::Done;
+    let mut trashed = Task::new("Fix login", "claude", "proj");
+    trashed.deleted_at = Some(chrono::Utc::now());
+    let other_project = Task::new("Fix login", "claude", "other");
+    let other_title = Task::new("Fix logout", "claude", "proj");
+    let tasks = vec![open.clone(), done, trashed, other_project, other_title];
+
+    assert_eq!(find_title_conflicts("  fix LOGIN ", &tasks, "proj"), vec![open.id]);
+    assert!(find_title_conflicts("Add signup", &tasks, "proj").is_empty());
+}
+
 #[test]
 fn test_task_unique_ids() {
     let task1 = Task::new("Task 1", "claude", "proj");
@@ -112,3 +383,500 @@ fn test_project_unique_ids() {
 
     assert_ne!(project1.id, project2.id);
 }
+
+// === Migration Tests ===
+
+use agtx::db::migrations::{self, Migration, Step, PROJECT_MIGRATIONS};
+use agtx::db::Database;
+use rusqlite::Connection;
+
+/// The tasks table as the first versioned schema created it
+fn create_v1_project_db(path: &std::path::Path) -> Connection {
+    let conn = Connection::open(path).unwrap();
+    conn.execute_batch(
+        r#"
+        CREATE TABLE tasks (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            description TEXT,
+            status TEXT NOT NULL DEFAULT 'backlog',
+            agent TEXT NOT NULL,
+            project_id TEXT NOT NULL,
+            session_name TEXT,
+            worktree_path TEXT,
+            branch_name TEXT,
+            pr_number INTEGER,
+            pr_url TEXT,
+            plugin TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        INSERT INTO tasks (id, title, description, status, agent, project_id, branch_name, pr_number, created_at, updated_at)
+        VALUES ('t1', 'Old task', 'from v1', 'review', 'claude', 'proj', 'task/old', 7,
+                '2024-01-01T00:00:00Z', '2024-01-02T00:00:00Z');
+        PRAGMA user_version = 1;
+        "#,
+    )
+    .unwrap();
+    conn
+}
+
+#[test]
+fn test_migrate_v1_to_latest_preserves_tasks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.db");
+    drop(create_v1_project_db(&path));
+
+    let db = Database::open_project_at(&path).unwrap();
+
+    assert_eq!(db.schema_version().unwrap(), migrations::latest_version(PROJECT_MIGRATIONS));
+    let tasks = db.get_all_tasks().unwrap();
+    assert_eq!(tasks.len(), 1);
+    let task = &tasks[0];
+    assert_eq!(task.id, "t1");
+    assert_eq!(task.title, "Old task");
+    assert_eq!(task.description.as_deref(), Some("from v1"));
+    assert_eq!(task.status, TaskStatus::Review);
+    assert_eq!(task.branch_name.as_deref(), Some("task/old"));
+    // Backfilled from the branch, so the slug survives title edits
+    assert_eq!(task.slug.as_deref(), Some("old"));
+    assert_eq!(task.pr_number, Some(7));
+    assert!(!task.archived);
+    assert!(task.tags.is_empty());
+    assert!(task.depends_on.is_empty());
+}
+
+#[test]
+fn test_migrate_unversioned_db_with_ad_hoc_columns() {
+    // Before versioning, columns were added with unconditional ALTERs and user_version stayed 0
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.db");
+    let conn = create_v1_project_db(&path);
+    conn.execute_batch(
+        "ALTER TABLE tasks ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+         ALTER TABLE tasks ADD COLUMN tags TEXT;
+         UPDATE tasks SET tags = 'ui', archived = 1;
+         PRAGMA user_version = 0;",
+    )
+    .unwrap();
+    drop(conn);
+
+    let db = Database::open_project_at(&path).unwrap();
+
+    assert_eq!(db.schema_version().unwrap(), migrations::latest_version(PROJECT_MIGRATIONS));
+    let tasks = db.get_archived_tasks().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].tags, vec!["ui".to_string()]);
+}
+
+#[test]
+fn test_open_new_db_is_latest_and_reopen_is_noop() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.db");
+
+    let db = Database::open_project_at(&path).unwrap();
+    let mut task = Task::new("Fresh", "claude", "proj");
+    task.tags = vec!["x".to_string()];
+    db.create_task(&task).unwrap();
+    drop(db);
+
+    let db = Database::open_project_at(&path).unwrap();
+    assert_eq!(db.schema_version().unwrap(), migrations::latest_version(PROJECT_MIGRATIONS));
+    assert_eq!(db.get_all_tasks().unwrap().len(), 1);
+}
+
+#[test]
+fn test_failed_migration_rolls_back() {
+    const STEPS: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "create",
+            steps: &[Step::Sql("CREATE TABLE notes (id TEXT PRIMARY KEY);")],
+        },
+        Migration {
+            version: 2,
+            description: "broken",
+            steps: &[Step::Sql("ALTER TABLE missing ADD COLUMN x TEXT;")],
+        },
+    ];
+    let mut conn = Connection::open_in_memory().unwrap();
+
+    let err = migrations::migrate(&mut conn, STEPS).unwrap_err();
+
+    assert!(format!("{:#}", err).contains("Migration to v2 (broken) failed"));
+    assert_eq!(migrations::schema_version(&conn).unwrap(), 0);
+    let notes: i64 = conn
+        .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'notes'", [], |r| r.get(0))
+        .unwrap();
+    assert_eq!(notes, 0, "v1 must be rolled back along with v2");
+}
+
+#[test]
+fn test_newer_schema_is_refused() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("PRAGMA user_version = 99;").unwrap();
+
+    let err = migrations::migrate(&mut conn, PROJECT_MIGRATIONS).unwrap_err();
+
+    assert!(err.to_string().contains("newer than this agtx supports"));
+}
+
+#[test]
+fn test_agent_session_id_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Resume me", "claude", "proj");
+    db.create_task(&task).unwrap();
+    assert!(db.get_task(&task.id).unwrap().unwrap().agent_session_id.is_none());
+
+    task.agent_session_id = Some("0b6f5c1e-session".to_string());
+    db.update_task(&task).unwrap();
+
+    let loaded = db.get_task(&task.id).unwrap().unwrap();
+    assert_eq!(loaded.agent_session_id.as_deref(), Some("0b6f5c1e-session"));
+}
+
+#[test]
+fn test_task_env_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("With env", "claude", "proj");
+    task.env.insert("PROJECT_ENV".to_string(), "staging".to_string());
+    task.env.insert("LIST".to_string(), "a,b".to_string());
+    db.create_task(&task).unwrap();
+
+    let loaded = db.get_task(&task.id).unwrap().unwrap();
+    assert_eq!(loaded.env, task.env);
+
+    task.env.clear();
+    db.update_task(&task).unwrap();
+    assert!(db.get_task(&task.id).unwrap().unwrap().env.is_empty());
+}
+
+#[test]
+fn test_task_notes_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Noted", "claude", "proj");
+    task.add_note("needs a second look, see \"retry\", line 40");
+    db.create_task(&task).unwrap();
+    task.add_note("looks good now");
+    db.update_task(&task).unwrap();
+
+    let loaded = db.get_task(&task.id).unwrap().unwrap();
+    assert_eq!(loaded.notes, task.notes);
+}
+
+#[test]
+fn test_task_test_status_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Tested", "claude", "proj");
+    db.create_task(&task).unwrap();
+    assert_eq!(db.get_task(&task.id).unwrap().unwrap().test_status, None);
+
+    for status in [Some(false), Some(true), None] {
+        task.test_status = status;
+        db.update_task(&task).unwrap();
+        assert_eq!(db.get_task(&task.id).unwrap().unwrap().test_status, status);
+    }
+}
+
+#[test]
+fn test_task_last_exit_code_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Exited", "claude", "proj");
+    db.create_task(&task).unwrap();
+    assert_eq!(db.get_task(&task.id).unwrap().unwrap().last_exit_code, None);
+
+    task.last_exit_code = Some(137);
+    db.update_task(&task).unwrap();
+    assert_eq!(db.get_task(&task.id).unwrap().unwrap().last_exit_code, Some(137));
+}
+
+#[test]
+fn test_task_pinned_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Pin me", "claude", "proj");
+    db.create_task(&task).unwrap();
+    assert!(!db.get_task(&task.id).unwrap().unwrap().pinned);
+
+    task.pinned = true;
+    db.update_task(&task).unwrap();
+    assert!(db.get_task(&task.id).unwrap().unwrap().pinned);
+}
+
+#[test]
+fn test_task_usage_roundtrip() {
+    use agtx::db::AgentUsage;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Costly", "claude", "proj");
+    db.create_task(&task).unwrap();
+    assert_eq!(db.get_task(&task.id).unwrap().unwrap().usage, None);
+
+    let usage = AgentUsage { tokens_in: 12_000, tokens_out: 3_400, cost_usd: Some(0.42) };
+    task.usage = Some(usage);
+    db.update_task(&task).unwrap();
+    assert_eq!(db.get_task(&task.id).unwrap().unwrap().usage, Some(usage));
+}
+
+/// Saving usage touches only the usage column, so an edit saved meanwhile survives
+#[test]
+fn test_set_task_usage_leaves_rest_of_row() {
+    use agtx::db::AgentUsage;
+
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Costly", "claude", "proj");
+    db.create_task(&task).unwrap();
+    let stale = task.clone();
+    task.title = "Renamed".to_string();
+    db.update_task(&task).unwrap();
+
+    let usage = AgentUsage { tokens_in: 500, tokens_out: 20, cost_usd: None };
+    db.set_task_usage(&stale.id, &usage).unwrap();
+
+    let saved = db.get_task(&task.id).unwrap().unwrap();
+    assert_eq!(saved.usage, Some(usage));
+    assert_eq!(saved.title, "Renamed");
+    assert_eq!(saved.updated_at, task.updated_at);
+}
+
+#[test]
+fn test_status_change_is_timestamped() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Move me", "claude", "proj");
+    db.create_task(&task).unwrap();
+    assert!(db.get_task(&task.id).unwrap().unwrap().status_changed_at.is_none());
+
+    // Saving without a status change leaves it alone
+    task.title = "Renamed".to_string();
+    db.update_task(&task).unwrap();
+    assert!(db.get_task(&task.id).unwrap().unwrap().status_changed_at.is_none());
+
+    let before = chrono::Utc::now() - chrono::Duration::seconds(1);
+    task.status = TaskStatus::Review;
+    db.update_task(&task).unwrap();
+    let changed = db.get_task(&task.id).unwrap().unwrap().status_changed_at.unwrap();
+    assert!(changed >= before);
+}
+
+#[test]
+fn test_task_subdir_and_from_ref_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Fix API", "claude", "proj");
+    task.subdir = Some("crates/api".to_string());
+    db.create_task(&task).unwrap();
+    assert_eq!(db.get_task(&task.id).unwrap().unwrap().subdir.as_deref(), Some("crates/api"));
+
+    task.subdir = None;
+    task.from_ref = Some("release/2.0".to_string());
+    db.update_task(&task).unwrap();
+    let saved = db.get_task(&task.id).unwrap().unwrap();
+    assert_eq!(saved.subdir, None);
+    assert_eq!(saved.from_ref.as_deref(), Some("release/2.0"));
+}
+
+#[test]
+fn test_task_slug_survives_title_edit() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Fix login", "claude", "proj");
+    db.create_task(&task).unwrap();
+    // No resources yet: the slug follows the title
+    assert!(task.resource_slug().ends_with("Fix-login"));
+
+    task.slug = Some("abc-fix-login".to_string());
+    task.title = "Rework the whole auth flow".to_string();
+    db.update_task(&task).unwrap();
+    let saved = db.get_task(&task.id).unwrap().unwrap();
+    assert_eq!(saved.slug.as_deref(), Some("abc-fix-login"));
+    assert_eq!(saved.resource_slug(), "abc-fix-login");
+}
+
+// === Trash Tests ===
+
+#[test]
+fn test_trashed_tasks_leave_board_but_keep_row() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let keep = Task::new("Keep", "claude", "proj");
+    let mut trashed = Task::new("Trash me", "claude", "proj");
+    db.create_task(&keep).unwrap();
+    db.create_task(&trashed).unwrap();
+
+    trashed.deleted_at = Some(chrono::Utc::now());
+    db.update_task(&trashed).unwrap();
+
+    let board: Vec<String> = db.get_all_tasks().unwrap().into_iter().map(|t| t.title).collect();
+    assert_eq!(board, vec!["Keep".to_string()]);
+    assert!(db.get_tasks_by_status(TaskStatus::Backlog).unwrap().iter().all(|t| t.id != trashed.id));
+    let trash = db.get_trashed_tasks().unwrap();
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].id, trashed.id);
+    assert!(trash[0].deleted_at.is_some());
+}
+
+#[test]
+fn test_restore_and_purge_trashed_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut restored = Task::new("Restore", "claude", "proj");
+    restored.deleted_at = Some(chrono::Utc::now());
+    let mut purged = Task::new("Purge", "claude", "proj");
+    purged.deleted_at = Some(chrono::Utc::now());
+    db.create_task(&restored).unwrap();
+    db.create_task(&purged).unwrap();
+
+    restored.restore_from_trash();
+    db.update_task(&restored).unwrap();
+    db.delete_task(&purged.id).unwrap();
+
+    assert!(db.get_trashed_tasks().unwrap().is_empty());
+    assert!(db.get_task(&purged.id).unwrap().is_none());
+    let board = db.get_all_tasks().unwrap();
+    assert_eq!(board.len(), 1);
+    assert_eq!(board[0].id, restored.id);
+    assert_eq!(board[0].status, TaskStatus::Backlog);
+}
+
+// === Task History Tests ===
+
+use agtx::db::{TaskEvent, TaskEventKind};
+
+#[test]
+fn test_task_event_kind_roundtrip() {
+    let kinds = [
+        TaskEventKind::Created,
+        TaskEventKind::Moved,
+        TaskEventKind::WorktreeCreated,
+        TaskEventKind::WindowCreated,
+        TaskEventKind::AgentDispatched,
+        TaskEventKind::PrOpened,
+        TaskEventKind::TestsRun,
+        TaskEventKind::AgentExited,
+        TaskEventKind::Failed,
+        TaskEventKind::Trashed,
+        TaskEventKind::Restored,
+        TaskEventKind::Interrupted,
+        TaskEventKind::Abandoned,
+    ];
+    for kind in kinds {
+        assert_eq!(TaskEventKind::from_name(kind.name()), Some(kind));
+    }
+    assert_eq!(TaskEventKind::from_name("exploded"), None);
+}
+
+#[test]
+fn test_status_changes_and_trash_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let mut task = Task::new("Tracked", "claude", "proj");
+    db.create_task(&task).unwrap();
+
+    task.title = "Tracked (renamed)".to_string();
+    db.update_task(&task).unwrap();
+    task.status = TaskStatus::Planning;
+    db.update_task(&task).unwrap();
+    task.deleted_at = Some(chrono::Utc::now());
+    db.update_task(&task).unwrap();
+    task.restore_from_trash();
+    db.update_task(&task).unwrap();
+
+    let history: Vec<(TaskEventKind, String)> = db
+        .get_task_events(&task.id)
+        .unwrap()
+        .into_iter()
+        .map(|e| (e.kind, e.detail))
+        .collect();
+    assert_eq!(
+        history,
+        vec![
+            (TaskEventKind::Created, "in backlog".to_string()),
+            (TaskEventKind::Moved, "backlog → planning".to_string()),
+            (TaskEventKind::Trashed, String::new()),
+            (TaskEventKind::Moved, "planning → backlog".to_string()),
+            (TaskEventKind::Restored, String::new()),
+        ]
+    );
+}
+
+#[test]
+fn test_task_events_keep_order_and_go_with_the_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    let task = Task::new("Tracked", "claude", "proj");
+    let other = Task::new("Other", "claude", "proj");
+    db.create_task(&task).unwrap();
+    db.create_task(&other).unwrap();
+
+    db.add_task_event(&task.id, &TaskEvent::new(TaskEventKind::WorktreeCreated, "/repo/.agtx/worktrees/t")).unwrap();
+    db.add_task_event(&task.id, &TaskEvent::new(TaskEventKind::Failed, "tmux window 'x' not found")).unwrap();
+
+    let kinds: Vec<TaskEventKind> = db.get_task_events(&task.id).unwrap().into_iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, vec![TaskEventKind::Created, TaskEventKind::WorktreeCreated, TaskEventKind::Failed]);
+    assert_eq!(db.get_task_events(&other.id).unwrap().len(), 1);
+
+    db.delete_task(&task.id).unwrap();
+    assert!(db.get_task_events(&task.id).unwrap().is_empty());
+    assert_eq!(db.get_task_events(&other.id).unwrap().len(), 1);
+}
+
+#[test]
+fn test_task_events_since_covers_every_task() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open_project_at(&dir.path().join("project.db")).unwrap();
+    assert_eq!(db.last_task_event_id().unwrap(), 0);
+
+    let mut task = Task::new("Tracked", "claude", "proj");
+    db.create_task(&task).unwrap();
+    let cursor = db.last_task_event_id().unwrap();
+    assert!(cursor > 0);
+    assert!(db.task_events_since(cursor).unwrap().is_empty());
+
+    let other = Task::new("Other", "claude", "proj");
+    db.create_task(&other).unwrap();
+    task.status = TaskStatus::Planning;
+    db.update_task(&task).unwrap();
+
+    let events = db.task_events_since(cursor).unwrap();
+    let seen: Vec<(&str, TaskEventKind)> = events.iter().map(|(_, id, e)| (id.as_str(), e.kind)).collect();
+    assert_eq!(seen, vec![(other.id.as_str(), TaskEventKind::Created), (task.id.as_str(), TaskEventKind::Moved)]);
+    assert_eq!(events[1].2.detail, "backlog → planning");
+    // Ids increase, and the last one is where the next poll starts
+    assert!(events[0].0 < events[1].0);
+    assert_eq!(db.last_task_event_id().unwrap(), events[1].0);
+    assert!(db.task_events_since(events[1].0).unwrap().is_empty());
+}
+
+// === Dispatch Queue Tests ===
+
+#[test]
+fn test_dispatch_queue_roundtrip_keeps_order() {
+    use agtx::db::QueuedCommand;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("project.db");
+    let queued = |id: &str| QueuedCommand {
+        task_id: id.to_string(),
+        target: format!("proj:{}", id),
+        command: "/agtx:execute".to_string(),
+    };
+
+    let db = Database::open_project_at(&path).unwrap();
+    assert!(db.get_dispatch_queue().unwrap().is_empty());
+    db.save_dispatch_queue(&[queued("b"), queued("a")]).unwrap();
+    drop(db);
+
+    // Survives reopening, and saving again replaces what was there
+    let db = Database::open_project_at(&path).unwrap();
+    assert_eq!(db.get_dispatch_queue().unwrap(), vec![queued("b"), queued("a")]);
+    db.save_dispatch_queue(&[queued("a")]).unwrap();
+    assert_eq!(db.get_dispatch_queue().unwrap(), vec![queued("a")]);
+}
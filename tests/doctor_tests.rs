@@ -0,0 +1,169 @@
+use agtx::config::{GlobalConfig, Multiplexer, ProjectConfig};
+use agtx::doctor::{
+    check_base_branch, check_binary, check_git_repo, check_multiplexer, check_tmux, check_worktree_dir_writable, format_report,
+    has_critical_failure, run_checks, CheckResult, CheckStatus,
+};
+use std::process::Command;
+use tempfile::TempDir;
+
+fn setup_git_repo(branch: &str) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    for args in [
+        vec!["init", "-b", branch],
+        vec!["config", "user.email", "test@test.com"],
+        vec!["config", "user.name", "Test User"],
+    ] {
+        Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(&args)
+            .output()
+            .expect("Failed to run git");
+    }
+    std::fs::write(temp_dir.path().join("README.md"), "# Test").unwrap();
+    for args in [vec!["add", "-A"], vec!["commit", "-m", "Initial commit"]] {
+        Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(&args)
+            .output()
+            .expect("Failed to run git");
+    }
+    temp_dir
+}
+
+fn result(status: CheckStatus, critical: bool) -> CheckResult {
+    CheckResult { name: "check".to_string(), status, detail: String::new(), critical }
+}
+
+// === Individual Check Tests ===
+
+#[test]
+fn test_check_binary_reports_version() {
+    let result = check_binary("git installed", "git", "--version");
+    assert_eq!(result.status, CheckStatus::Pass);
+    assert!(result.detail.starts_with("git version"));
+}
+
+#[test]
+fn test_check_binary_missing_program_fails() {
+    let result = check_binary("missing", "agtx-definitely-not-installed", "--version");
+    assert_eq!(result.status, CheckStatus::Fail);
+    assert!(result.detail.contains("not found on PATH"));
+}
+
+#[test]
+fn test_check_tmux_not_critical_in_no_tmux_mode() {
+    assert!(check_tmux(false).critical);
+    assert!(!check_tmux(true).critical);
+}
+
+#[test]
+fn test_check_multiplexer_uses_configured_program() {
+    assert_eq!(check_multiplexer(Multiplexer::Tmux, false).name, "tmux installed");
+    assert_eq!(check_multiplexer(Multiplexer::Zellij, false).name, "zellij installed");
+}
+
+#[test]
+fn test_check_git_repo() {
+    let repo = setup_git_repo("main");
+    assert_eq!(check_git_repo(repo.path()).status, CheckStatus::Pass);
+
+    let plain = TempDir::new().unwrap();
+    assert_eq!(check_git_repo(plain.path()).status, CheckStatus::Fail);
+}
+
+#[test]
+fn test_check_base_branch() {
+    let repo = setup_git_repo("master");
+    assert_eq!(check_base_branch(repo.path(), "master").status, CheckStatus::Pass);
+
+    let missing = check_base_branch(repo.path(), "main");
+    assert_eq!(missing.status, CheckStatus::Fail);
+    assert!(missing.detail.contains("'main' not found"));
+}
+
+#[test]
+fn test_check_base_branch_skipped_outside_repo() {
+    let plain = TempDir::new().unwrap();
+    assert_eq!(check_base_branch(plain.path(), "main").status, CheckStatus::Skipped);
+}
+
+#[test]
+fn test_check_worktree_dir_writable_does_not_create_dir() {
+    let project = TempDir::new().unwrap();
+
+    let result = check_worktree_dir_writable(project.path(), ".agtx/worktrees");
+
+    assert_eq!(result.status, CheckStatus::Pass);
+    assert!(result.detail.contains("will be created"));
+    assert!(!project.path().join(".agtx").exists());
+}
+
+#[test]
+fn test_check_worktree_dir_existing() {
+    let project = TempDir::new().unwrap();
+    std::fs::create_dir_all(project.path().join("wt")).unwrap();
+
+    let result = check_worktree_dir_writable(project.path(), "wt");
+
+    assert_eq!(result.status, CheckStatus::Pass);
+    assert!(!result.detail.contains("will be created"));
+    // The probe file is cleaned up
+    assert_eq!(std::fs::read_dir(project.path().join("wt")).unwrap().count(), 0);
+}
+
+// === Report Tests ===
+
+#[test]
+fn test_has_critical_failure() {
+    assert!(!has_critical_failure(&[result(CheckStatus::Pass, true), result(CheckStatus::Skipped, true)]));
+    assert!(has_critical_failure(&[result(CheckStatus::Pass, true), result(CheckStatus::Fail, true)]));
+    assert!(!has_critical_failure(&[result(CheckStatus::Fail, false)]));
+}
+
+#[test]
+fn test_format_report() {
+    let results = vec![
+        CheckResult {
+            name: "git installed".to_string(),
+            status: CheckStatus::Pass,
+            detail: "git version 2.43.0".to_string(),
+            critical: true,
+        },
+        result(CheckStatus::Skipped, true),
+    ];
+
+    assert_eq!(format_report(&results), "[PASS] git installed: git version 2.43.0\n[SKIP] check");
+}
+
+#[test]
+fn test_run_checks_uses_configured_base_branch() {
+    let repo = setup_git_repo("develop");
+    let project = ProjectConfig { base_branch: Some("develop".to_string()), ..Default::default() };
+
+    let results = run_checks(repo.path(), &GlobalConfig::default(), &project);
+
+    let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "tmux installed",
+            "git installed",
+            "project is a git repo",
+            "base branch exists",
+            "worktree directory writable",
+        ]
+    );
+    let base = results.iter().find(|r| r.name == "base branch exists").unwrap();
+    assert_eq!(base.status, CheckStatus::Pass);
+}
+
+#[test]
+fn test_run_checks_detects_base_branch_when_unset() {
+    let repo = setup_git_repo("master");
+
+    let results = run_checks(repo.path(), &GlobalConfig::default(), &ProjectConfig::default());
+
+    let base = results.iter().find(|r| r.name == "base branch exists").unwrap();
+    assert_eq!(base.status, CheckStatus::Pass);
+    assert_eq!(base.detail, "master");
+}
@@ -0,0 +1,226 @@
+use agtx::dry_run::{DryRunGitOps, DryRunGitProviderOps, DryRunHookRunner, DryRunLog, DryRunTmuxOps};
+use agtx::hooks::HookRunner;
+use agtx::git::{GitOperations, GitProviderOperations, RealGitOps};
+use agtx::platform::shell_quote;
+use agtx::tmux::TmuxOperations;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn setup_git_repo() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    for args in [
+        vec!["init"],
+        vec!["config", "user.email", "test@test.com"],
+        vec!["config", "user.name", "Test User"],
+    ] {
+        Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(&args)
+            .output()
+            .expect("Failed to run git");
+    }
+    std::fs::write(temp_dir.path().join("README.md"), "# Test").unwrap();
+    for args in [vec!["add", "-A"], vec!["commit", "-m", "Initial commit"]] {
+        Command::new("git")
+            .current_dir(temp_dir.path())
+            .args(&args)
+            .output()
+            .expect("Failed to run git");
+    }
+    temp_dir
+}
+
+fn commit_count(path: &Path) -> String {
+    let output = Command::new("git")
+        .current_dir(path)
+        .args(["rev-list", "--count", "HEAD"])
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+// === DryRunTmuxOps Tests ===
+
+#[test]
+fn test_dry_run_tmux_records_create_window() {
+    let log = DryRunLog::new();
+    let tmux = DryRunTmuxOps::new(log.clone());
+
+    tmux.create_window("proj", "task-1", "/tmp/wt", Some("claude".to_string())).unwrap();
+
+    assert_eq!(
+        log.entries(),
+        vec!["tmux -L agtx new-window -d -t proj -n task-1 -c /tmp/wt sh -c 'claude; exec $SHELL'"]
+    );
+}
+
+#[test]
+fn test_dry_run_tmux_records_send_keys_and_kill() {
+    let log = DryRunLog::new();
+    let tmux = DryRunTmuxOps::new(log.clone());
+
+    tmux.send_keys("proj:task-1", "/agtx:plan").unwrap();
+    tmux.kill_window("proj:task-1").unwrap();
+
+    assert_eq!(
+        log.entries(),
+        vec![
+            "tmux -L agtx send-keys -t proj:task-1 /agtx:plan",
+            "tmux -L agtx send-keys -t proj:task-1 Enter",
+            "tmux -L agtx kill-window -t proj:task-1",
+        ]
+    );
+}
+
+#[test]
+fn test_dry_run_tmux_records_send_interrupt() {
+    let log = DryRunLog::new();
+    let tmux = DryRunTmuxOps::new(log.clone());
+
+    tmux.send_interrupt("proj:task-1").unwrap();
+
+    assert_eq!(log.entries(), vec!["tmux -L agtx send-keys -t proj:task-1 C-c"]);
+}
+
+#[test]
+fn test_dry_run_tmux_ensure_session_records_missing_session() {
+    let log = DryRunLog::new();
+    let tmux = DryRunTmuxOps::new(log.clone());
+
+    tmux.ensure_session("agtx-dry-run-no-such-session", "/tmp/proj").unwrap();
+
+    assert_eq!(
+        log.entries(),
+        vec!["tmux -L agtx new-session -d -s agtx-dry-run-no-such-session -c /tmp/proj"]
+    );
+}
+
+// === DryRunGitOps Tests ===
+
+#[test]
+fn test_dry_run_create_worktree_has_no_side_effects() {
+    let repo = setup_git_repo();
+    let log = DryRunLog::new();
+    let git = DryRunGitOps::new(log.clone(), RealGitOps::default());
+
+    let path = git.create_worktree(repo.path(), "my-task", "develop").unwrap();
+
+    assert!(path.ends_with(".agtx/worktrees/my-task"));
+    assert!(!Path::new(&path).exists());
+    assert_eq!(log.entries().len(), 1);
+    assert!(log.entries()[0].contains("worktree add"));
+    assert!(log.entries()[0].contains("-b task/my-task develop"));
+}
+
+#[test]
+fn test_dry_run_worktree_paths_follow_configured_dir() {
+    let log = DryRunLog::new();
+    let git = DryRunGitOps::new(log.clone(), RealGitOps::default().with_worktree_dir("/tmp/agtx-wt"));
+
+    let path = git.create_worktree(Path::new("/proj"), "my-task", "main").unwrap();
+    assert_eq!(path, "/tmp/agtx-wt/proj/my-task");
+    let path = git.checkout_worktree(Path::new("/proj"), "other", "task/other").unwrap();
+    assert_eq!(path, "/tmp/agtx-wt/proj/other");
+    assert_eq!(
+        log.entries(),
+        vec![
+            "git -C /proj worktree add /tmp/agtx-wt/proj/my-task -b task/my-task main",
+            "git -C /proj worktree add /tmp/agtx-wt/proj/other task/other",
+        ]
+    );
+}
+
+#[test]
+fn test_dry_run_commit_all_leaves_repo_untouched() {
+    let repo = setup_git_repo();
+    std::fs::write(repo.path().join("new.txt"), "change").unwrap();
+    let log = DryRunLog::new();
+    let git = DryRunGitOps::new(log.clone(), RealGitOps::default());
+
+    let commit = git.commit_all(repo.path(), "WIP: task").unwrap();
+
+    assert!(commit.is_some());
+    assert_eq!(commit_count(repo.path()), "1");
+    assert!(git.has_changes(repo.path()));
+    let entries = log.entries();
+    assert!(entries[0].ends_with("add -A"));
+    assert!(entries[1].ends_with("commit -m 'WIP: task'"));
+}
+
+#[test]
+fn test_dry_run_commit_all_clean_tree_records_nothing() {
+    let repo = setup_git_repo();
+    let log = DryRunLog::new();
+    let git = DryRunGitOps::new(log.clone(), RealGitOps::default());
+
+    assert!(git.commit_all(repo.path(), "WIP").unwrap().is_none());
+    assert!(log.is_empty());
+}
+
+#[test]
+fn test_dry_run_remove_worktree_and_branch() {
+    let log = DryRunLog::new();
+    let git = DryRunGitOps::new(log.clone(), RealGitOps::default());
+
+    git.remove_worktree(Path::new("/proj"), "/proj/.agtx/worktrees/t").unwrap();
+    git.delete_branch(Path::new("/proj"), "task/t").unwrap();
+
+    assert_eq!(
+        log.entries(),
+        vec![
+            "git -C /proj worktree remove --force /proj/.agtx/worktrees/t",
+            "git -C /proj branch -D task/t",
+        ]
+    );
+}
+
+#[test]
+fn test_dry_run_initialize_worktree_records_init_script() {
+    let log = DryRunLog::new();
+    let git = DryRunGitOps::new(log.clone(), RealGitOps::default());
+
+    let warnings = git.initialize_worktree(
+        Path::new("/nonexistent/proj"),
+        Path::new("/nonexistent/wt"),
+        None,
+        Some("npm install".to_string()),
+        vec![],
+    );
+
+    assert!(warnings.is_empty());
+    assert_eq!(log.entries(), vec!["cd /nonexistent/wt && sh -c 'npm install'"]);
+}
+
+// === DryRunGitProviderOps Tests ===
+
+#[test]
+fn test_dry_run_create_pr_records_gh_command() {
+    let log = DryRunLog::new();
+    let provider = DryRunGitProviderOps::new(log.clone());
+
+    let (number, _) = provider.create_pr(Path::new("/proj"), "Add feature", "Body", "task/t", "develop").unwrap();
+
+    assert_eq!(number, 0);
+    assert_eq!(
+        log.entries(),
+        vec!["cd /proj && gh pr create --title 'Add feature' --body Body --head task/t --base develop"]
+    );
+}
+
+// === DryRunHookRunner Tests ===
+
+#[test]
+fn test_dry_run_hook_is_recorded_not_run() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = DryRunLog::new();
+    let runner = DryRunHookRunner::new(log.clone());
+
+    runner.run("touch marker", temp_dir.path()).unwrap();
+
+    assert!(!temp_dir.path().join("marker").exists());
+    assert_eq!(
+        log.entries(),
+        vec![format!("cd {} && sh -c 'touch marker'", shell_quote(&temp_dir.path().to_string_lossy()))]
+    );
+}
@@ -0,0 +1,33 @@
+use agtx::error_log::{append, format_entry};
+use chrono::{TimeZone, Utc};
+use tempfile::TempDir;
+
+// === Error Log Tests ===
+
+#[test]
+fn test_format_entry() {
+    let at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 30, 0).unwrap();
+
+    let line = format_entry(at, "Moving 'Add login' to planning", "Failed to create worktree:\n  fatal: invalid reference");
+
+    assert_eq!(
+        line,
+        "2026-03-01T12:30:00+00:00  Moving 'Add login' to planning: Failed to create worktree: fatal: invalid reference"
+    );
+}
+
+#[test]
+fn test_append_creates_log_and_appends_lines() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("logs").join("errors.log");
+    let at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 30, 0).unwrap();
+
+    append(&path, at, "first", "one").unwrap();
+    append(&path, at, "second", "two").unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("first: one"));
+    assert!(lines[1].ends_with("second: two"));
+}
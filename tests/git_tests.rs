@@ -1,6 +1,8 @@
-use agtx::git;
-use std::path::PathBuf;
+use agtx::git::{self, GitOperations, GitTimeouts, RealGitOps};
+use agtx::op_error::OpError;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
 
 // =============================================================================
@@ -37,6 +39,31 @@ fn test_worktree_path_nested_project() {
     );
 }
 
+#[test]
+fn test_worktree_path_in_relative_dir() {
+    let project = PathBuf::from("/home/user/project");
+    let path = git::worktree_path_in(&project, "../wt", "task-123");
+    assert_eq!(path, PathBuf::from("/home/user/project/../wt/task-123"));
+}
+
+#[test]
+fn test_worktree_path_in_absolute_dir_is_per_project() {
+    let path = git::worktree_path_in(&PathBuf::from("/home/user/project"), "/fast/wt", "task-123");
+    assert_eq!(path, PathBuf::from("/fast/wt/project/task-123"));
+
+    let other = git::worktree_path_in(&PathBuf::from("/home/user/other"), "/fast/wt", "task-123");
+    assert_ne!(path, other);
+}
+
+#[test]
+fn test_worktree_path_in_default_dir_matches_worktree_path() {
+    let project = PathBuf::from("/home/user/project");
+    assert_eq!(
+        git::worktree_path_in(&project, git::DEFAULT_WORKTREE_DIR, "t"),
+        git::worktree_path(&project, "t")
+    );
+}
+
 #[test]
 fn test_worktree_exists_false_for_nonexistent() {
     let temp_dir = TempDir::new().unwrap();
@@ -108,6 +135,25 @@ fn test_is_git_repo_false() {
     assert!(!git::is_git_repo(temp_dir.path()));
 }
 
+#[test]
+fn test_has_commits_true() {
+    let temp_dir = setup_git_repo();
+    assert!(git::has_commits(temp_dir.path()));
+}
+
+#[test]
+fn test_has_commits_false_for_unborn_head() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["init"])
+        .output()
+        .expect("Failed to init git repo");
+
+    assert!(git::is_git_repo(temp_dir.path()));
+    assert!(!git::has_commits(temp_dir.path()));
+}
+
 #[test]
 fn test_repo_root() {
     let temp_dir = setup_git_repo();
@@ -125,6 +171,81 @@ fn test_current_branch() {
     assert_eq!(branch, "main");
 }
 
+#[test]
+fn test_parse_default_branch() {
+    assert_eq!(git::parse_default_branch("refs/remotes/origin/main\n"), Some("main".to_string()));
+    assert_eq!(git::parse_default_branch("refs/remotes/origin/release/2.x"), Some("release/2.x".to_string()));
+    assert_eq!(git::parse_default_branch("refs/remotes/origin/"), None);
+    assert_eq!(git::parse_default_branch("refs/heads/main"), None);
+    assert_eq!(git::parse_default_branch(""), None);
+}
+
+#[test]
+fn test_parse_worktree_list() {
+    let porcelain = "worktree /repo\nHEAD abc\nbranch refs/heads/main\n\n\
+                     worktree /repo/.agtx/worktrees/fix\nHEAD def\nbranch refs/heads/task/fix\n\n\
+                     worktree /repo/.agtx/worktrees/gone\nHEAD 123\ndetached\nprunable gitdir file points to non-existent location\n\n\
+                     worktree /repo/.agtx/worktrees/locked\nHEAD 456\nbranch refs/heads/task/locked\nlocked\n";
+    assert_eq!(
+        git::parse_worktree_list(porcelain),
+        vec![PathBuf::from("/repo/.agtx/worktrees/fix"), PathBuf::from("/repo/.agtx/worktrees/locked")]
+    );
+    assert!(git::parse_worktree_list("worktree /repo\nHEAD abc\nbranch refs/heads/main\n").is_empty());
+    assert!(git::parse_worktree_list("").is_empty());
+}
+
+#[test]
+fn test_default_branch_follows_origin_head() {
+    let temp_dir = setup_git_repo();
+    assert_eq!(git::default_branch(temp_dir.path()).unwrap(), "main");
+
+    Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["symbolic-ref", "refs/remotes/origin/HEAD", "refs/remotes/origin/develop"])
+        .output()
+        .unwrap();
+    assert_eq!(git::default_branch(temp_dir.path()).unwrap(), "develop");
+}
+
+#[test]
+fn test_default_branch_without_remote_uses_current_branch() {
+    let temp_dir = setup_git_repo();
+    // No origin, and neither main nor master
+    git(temp_dir.path(), &["branch", "-M", "trunk"]);
+
+    assert_eq!(git::default_branch(temp_dir.path()).unwrap(), "trunk");
+}
+
+#[test]
+fn test_detached_head_branches_from_current_commit() {
+    let temp_dir = setup_git_repo();
+    std::fs::write(temp_dir.path().join("second.txt"), "2").unwrap();
+    git(temp_dir.path(), &["add", "."]);
+    git(temp_dir.path(), &["commit", "-m", "Second"]);
+    git(temp_dir.path(), &["checkout", "--detach", "HEAD~1"]);
+    git(temp_dir.path(), &["branch", "-M", "main", "old-main"]);
+
+    let base = git::default_branch(temp_dir.path()).unwrap();
+    assert_eq!(base, "HEAD");
+
+    let path = git::create_worktree_in(temp_dir.path(), "wt", "detached", &base, Duration::from_secs(60)).unwrap();
+    assert!(path.join("README.md").exists());
+    assert!(!path.join("second.txt").exists());
+}
+
+#[test]
+fn test_empty_repo_reports_no_commits() {
+    let temp_dir = TempDir::new().unwrap();
+    git(temp_dir.path(), &["init"]);
+
+    let err = git::default_branch(temp_dir.path()).unwrap_err();
+    assert!(matches!(err, agtx::op_error::OpError::NoCommits { .. }), "{}", err);
+
+    let err = git::create_worktree_in(temp_dir.path(), "wt", "first", "HEAD", Duration::from_secs(60)).unwrap_err();
+    assert!(err.to_string().contains("has no commits yet"), "{}", err);
+    assert!(!temp_dir.path().join("wt").join("first").exists());
+}
+
 #[test]
 fn test_create_and_remove_worktree() {
     let temp_dir = setup_git_repo();
@@ -156,6 +277,90 @@ fn test_create_worktree_idempotent() {
     assert!(path1.exists());
 }
 
+#[test]
+fn test_create_worktree_in_absolute_dir_creates_missing_parents() {
+    let temp_dir = setup_git_repo();
+    let outside = TempDir::new().unwrap();
+    let worktree_dir = outside.path().join("not/yet/there");
+
+    let ops = RealGitOps::default().with_worktree_dir(worktree_dir.to_string_lossy());
+    let path = PathBuf::from(ops.create_worktree(temp_dir.path(), "fast-disk", "main").unwrap());
+
+    assert!(path.starts_with(&worktree_dir));
+    assert!(path.join(".git").exists());
+    assert!(ops.worktree_exists(temp_dir.path(), "fast-disk"));
+    assert!(!git::worktree_exists(temp_dir.path(), "fast-disk"));
+
+    ops.remove_worktree(temp_dir.path(), &path.to_string_lossy()).unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_create_worktree_refused_below_min_free_space() {
+    let temp_dir = setup_git_repo();
+    let ops = RealGitOps::default().with_min_free_mb(Some(u64::MAX));
+
+    let err = ops.create_worktree(temp_dir.path(), "full-disk", "main").unwrap_err();
+
+    assert!(matches!(err, agtx::op_error::OpError::LowDiskSpace { .. }), "{}", err);
+    assert!(!ops.worktree_exists(temp_dir.path(), "full-disk"));
+
+    // A limit the disk meets: created as usual
+    let ops = RealGitOps::default().with_min_free_mb(Some(1));
+    assert!(ops.create_worktree(temp_dir.path(), "fits", "main").is_ok());
+}
+
+#[test]
+fn test_create_worktree_in_relative_dir() {
+    let temp_dir = setup_git_repo();
+
+    let path = git::create_worktree_in(temp_dir.path(), "wt", "rel-task", "main", Duration::from_secs(60)).unwrap();
+
+    assert_eq!(path, temp_dir.path().join("wt").join("rel-task"));
+    assert!(path.join(".git").exists());
+}
+
+#[test]
+fn test_checkout_worktree_keeps_existing_branch() {
+    let temp_dir = setup_git_repo();
+    let ops = RealGitOps::default();
+    let path = ops.create_worktree(temp_dir.path(), "kept", "main").unwrap();
+    std::fs::write(Path::new(&path).join("work.txt"), "done").unwrap();
+    ops.commit_all(Path::new(&path), "work").unwrap();
+    ops.remove_worktree(temp_dir.path(), &path).unwrap();
+
+    let restored = ops.checkout_worktree(temp_dir.path(), "kept", "task/kept").unwrap();
+
+    assert_eq!(restored, path);
+    assert!(Path::new(&restored).join("work.txt").exists());
+}
+
+#[test]
+fn test_ref_exists() {
+    let temp_dir = setup_git_repo();
+    let ops = RealGitOps::default();
+    Command::new("git").current_dir(temp_dir.path()).args(["tag", "v1"]).output().unwrap();
+
+    assert!(ops.ref_exists(temp_dir.path(), "main").unwrap());
+    assert!(ops.ref_exists(temp_dir.path(), "v1").unwrap());
+    assert!(ops.ref_exists(temp_dir.path(), "HEAD").unwrap());
+    assert!(!ops.ref_exists(temp_dir.path(), "feature/deleted").unwrap());
+}
+
+#[test]
+fn test_ref_exists_outside_a_repo_is_an_error() {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(RealGitOps::default().ref_exists(temp_dir.path(), "main").is_err());
+}
+
+#[test]
+fn test_checkout_worktree_missing_branch_fails() {
+    let temp_dir = setup_git_repo();
+    let ops = RealGitOps::default();
+
+    assert!(ops.checkout_worktree(temp_dir.path(), "gone", "task/gone").is_err());
+}
+
 // =============================================================================
 // Error case tests
 // =============================================================================
@@ -224,7 +429,9 @@ fn test_create_worktree_on_non_git_directory() {
 
     let result = git::create_worktree(temp_dir.path(), "should-fail");
 
-    assert!(result.is_err());
+    assert!(matches!(result, Err(OpError::NotAGitRepo { .. })));
+    // Nothing is created in a directory that isn't a repository
+    assert!(!temp_dir.path().join(".agtx").exists());
 }
 
 #[test]
@@ -287,6 +494,25 @@ fn test_create_multiple_worktrees() {
     assert!(!path3.exists());
 }
 
+#[test]
+fn test_list_worktrees_skips_main_checkout_and_deleted_directories() {
+    let temp_dir = setup_git_repo();
+    let ops = RealGitOps::default();
+    assert!(ops.list_worktrees(temp_dir.path()).unwrap().is_empty());
+
+    git::create_worktree(temp_dir.path(), "task-1").unwrap();
+    let gone = git::create_worktree(temp_dir.path(), "task-2").unwrap();
+    std::fs::remove_dir_all(&gone).unwrap();
+
+    let names: Vec<_> = ops
+        .list_worktrees(temp_dir.path())
+        .unwrap()
+        .iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["task-1"]);
+}
+
 #[test]
 fn test_worktree_with_uncommitted_changes() {
     let temp_dir = setup_git_repo();
@@ -302,6 +528,140 @@ fn test_worktree_with_uncommitted_changes() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_commit_all_clean_tree_returns_none() {
+    let temp_dir = setup_git_repo();
+    let worktree_path = git::create_worktree(temp_dir.path(), "clean-task").unwrap();
+
+    let commit = RealGitOps::default().commit_all(&worktree_path, "nothing here").unwrap();
+    assert!(commit.is_none());
+}
+
+#[test]
+fn test_recent_commits_newest_first_and_capped() {
+    let temp_dir = setup_git_repo();
+    let base = git::current_branch(temp_dir.path()).unwrap();
+    let worktree_path = git::create_worktree(temp_dir.path(), "log-task").unwrap();
+    let ops = RealGitOps::default();
+    for i in 1..=3 {
+        std::fs::write(worktree_path.join(format!("file{}.txt", i)), "x").unwrap();
+        ops.commit_all(&worktree_path, &format!("Step {}", i)).unwrap();
+    }
+
+    let commits = ops.recent_commits(&worktree_path, &base, 2).unwrap();
+    let subjects: Vec<_> = commits.iter().map(|c| c.subject.as_str()).collect();
+    assert_eq!(subjects, vec!["Step 3", "Step 2"]);
+    assert!(commits.iter().all(|c| c.hash.len() >= 7));
+}
+
+/// A fresh worktree has the base's history but no commits of its own
+#[test]
+fn test_recent_commits_empty_on_fresh_worktree() {
+    let temp_dir = setup_git_repo();
+    let base = git::current_branch(temp_dir.path()).unwrap();
+    let worktree_path = git::create_worktree(temp_dir.path(), "fresh-task").unwrap();
+    assert!(RealGitOps::default().recent_commits(&worktree_path, &base, 5).unwrap().is_empty());
+
+    let not_a_repo = TempDir::new().unwrap();
+    assert!(RealGitOps::default().recent_commits(not_a_repo.path(), &base, 5).is_err());
+}
+
+#[test]
+fn test_commit_all_commits_uncommitted_changes() {
+    let temp_dir = setup_git_repo();
+    let worktree_path = git::create_worktree(temp_dir.path(), "dirty-commit").unwrap();
+    std::fs::write(worktree_path.join("new_file.txt"), "agent output").unwrap();
+
+    let commit = RealGitOps::default()
+        .commit_all(&worktree_path, "Add feature")
+        .unwrap()
+        .expect("expected a commit");
+    assert_eq!(commit.0.len(), 40);
+    assert!(!RealGitOps::default().has_changes(&worktree_path));
+
+    let log = Command::new("git")
+        .current_dir(&worktree_path)
+        .args(["log", "-1", "--format=%H %s"])
+        .output()
+        .unwrap();
+    let log = String::from_utf8_lossy(&log.stdout);
+    assert_eq!(log.trim(), format!("{} Add feature", commit));
+}
+#[test]
+fn test_is_worktree_dirty() {
+    let temp_dir = setup_git_repo();
+    let worktree_path = git::create_worktree(temp_dir.path(), "dirty-check").unwrap();
+
+    assert!(!RealGitOps::default().is_worktree_dirty(&worktree_path).unwrap());
+
+    std::fs::write(worktree_path.join("untracked.txt"), "wip").unwrap();
+    assert!(RealGitOps::default().is_worktree_dirty(&worktree_path).unwrap());
+}
+
+#[test]
+fn test_is_worktree_dirty_missing_path_is_clean() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing = temp_dir.path().join("gone");
+    assert!(!RealGitOps::default().is_worktree_dirty(&missing).unwrap());
+}
+
+#[test]
+fn test_is_worktree_dirty_non_git_directory_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(RealGitOps::default().is_worktree_dirty(temp_dir.path()).is_err());
+}
+
+#[test]
+fn test_stash_worktree_keeps_changes_in_named_stash() {
+    let temp_dir = setup_git_repo();
+    let worktree_path = git::create_worktree(temp_dir.path(), "stash-task").unwrap();
+    std::fs::write(worktree_path.join("wip.txt"), "half done").unwrap();
+
+    RealGitOps::default().stash_worktree(&worktree_path, "agtx: stash-task").unwrap();
+    assert!(!RealGitOps::default().is_worktree_dirty(&worktree_path).unwrap());
+
+    let list = Command::new("git")
+        .current_dir(temp_dir.path())
+        .args(["stash", "list"])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&list.stdout).contains("agtx: stash-task"));
+}
+
+fn current_branch(path: &Path) -> String {
+    let output = Command::new("git")
+        .current_dir(path)
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn test_diff_from_base_shows_only_committed_branch_changes() {
+    let temp_dir = setup_git_repo();
+    let base = current_branch(temp_dir.path());
+    let worktree_path = git::create_worktree(temp_dir.path(), "diff-task").unwrap();
+    std::fs::write(worktree_path.join("feature.txt"), "agent output\n").unwrap();
+    RealGitOps::default().commit_all(&worktree_path, "Add feature").unwrap();
+    // Uncommitted work isn't part of the branch diff
+    std::fs::write(worktree_path.join("scratch.txt"), "wip\n").unwrap();
+
+    let diff = RealGitOps::default().diff_from_base(&worktree_path, &base).unwrap();
+
+    assert!(diff.contains("feature.txt"));
+    assert!(diff.contains("+agent output"));
+    assert!(!diff.contains("scratch.txt"));
+}
+
+#[test]
+fn test_diff_from_base_unknown_base_errors() {
+    let temp_dir = setup_git_repo();
+    let worktree_path = git::create_worktree(temp_dir.path(), "diff-missing").unwrap();
+
+    let err = RealGitOps::default().diff_from_base(&worktree_path, "no-such-branch").unwrap_err();
+    assert!(err.to_string().contains("no-such-branch...HEAD"));
+}
 
 // =============================================================================
 // initialize_worktree tests
@@ -471,3 +831,165 @@ fn test_initialize_worktree_copy_directory_supported() {
     let content = std::fs::read_to_string(worktree_path.join("config").join("app.toml")).unwrap();
     assert_eq!(content, "key = 1");
 }
+
+// =============================================================================
+// Agent exit code tests
+// =============================================================================
+
+#[test]
+fn test_agent_exit_code_written_by_wrapped_command() {
+    let worktree = TempDir::new().unwrap();
+    git::clear_agent_exit_code(worktree.path());
+    assert_eq!(git::read_agent_exit_code(worktree.path()), None);
+
+    let command = agtx::tmux::record_exit_code("sh -c 'exit 3'", &git::agent_exit_code_path(worktree.path()));
+    let status = Command::new("sh").args(["-c", &command]).status().unwrap();
+    assert!(status.success());
+
+    assert_eq!(git::read_agent_exit_code(worktree.path()), Some(3));
+}
+
+#[test]
+fn test_clear_agent_exit_code_forgets_previous_agent() {
+    let worktree = TempDir::new().unwrap();
+    let path = git::agent_exit_code_path(worktree.path());
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, "0\n").unwrap();
+    assert_eq!(git::read_agent_exit_code(worktree.path()), Some(0));
+
+    git::clear_agent_exit_code(worktree.path());
+    assert_eq!(git::read_agent_exit_code(worktree.path()), None);
+}
+
+#[test]
+fn test_clear_agent_exit_code_ignores_missing_worktree() {
+    let worktree = TempDir::new().unwrap();
+    let missing = worktree.path().join("gone");
+    git::clear_agent_exit_code(&missing);
+    assert!(!missing.exists());
+}
+
+// =============================================================================
+// Timeout tests
+// =============================================================================
+
+#[test]
+fn test_run_with_timeout_returns_output() {
+    let mut cmd = Command::new("git");
+    cmd.args(["--version"]);
+
+    let output = git::run_with_timeout(&mut cmd, "version", Duration::from_secs(10)).unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("git version"));
+}
+
+#[test]
+fn test_run_with_timeout_kills_slow_command() {
+    let mut cmd = Command::new("sleep");
+    cmd.arg("10");
+
+    let started = Instant::now();
+    let err = git::run_with_timeout(&mut cmd, "push", Duration::from_millis(200)).unwrap_err();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    match err {
+        OpError::Timeout { ref op, after } => {
+            assert_eq!(op, "git push");
+            assert_eq!(after, Duration::from_millis(200));
+        }
+        ref other => panic!("expected a timeout error, got {:?}", other),
+    }
+    assert!(git::is_timeout(&err.into()));
+}
+
+#[test]
+fn test_is_timeout_false_for_other_errors() {
+    assert!(!git::is_timeout(&anyhow::anyhow!("Failed to push branch")));
+}
+
+#[test]
+fn test_real_git_ops_commit_times_out() {
+    let temp_dir = setup_git_repo();
+    std::fs::write(temp_dir.path().join("file.txt"), "change").unwrap();
+    // A pre-commit hook that hangs stands in for a stuck git command
+    let hook = temp_dir.path().join(".git").join("hooks").join("pre-commit");
+    std::fs::write(&hook, "#!/bin/sh\nsleep 10\n").unwrap();
+    Command::new("chmod").arg("+x").arg(&hook).output().unwrap();
+
+    let ops = RealGitOps::with_timeouts(GitTimeouts {
+        local: Duration::from_millis(300),
+        ..GitTimeouts::default()
+    });
+    ops.add_all(temp_dir.path()).unwrap();
+    let err = ops.commit(temp_dir.path(), "hangs").unwrap_err();
+
+    assert!(matches!(err, OpError::Timeout { .. }));
+}
+
+// === Merge Tests ===
+
+fn git(dir: &Path, args: &[&str]) {
+    let output = Command::new("git").current_dir(dir).args(args).output().unwrap();
+    assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+}
+
+/// A repo on `main` with a `feature` branch that changed README.md
+fn setup_feature_branch() -> TempDir {
+    let repo = setup_git_repo();
+    git(repo.path(), &["checkout", "-q", "-b", "feature"]);
+    std::fs::write(repo.path().join("README.md"), "# Feature").unwrap();
+    git(repo.path(), &["commit", "-qam", "feature change"]);
+    git(repo.path(), &["checkout", "-q", "main"]);
+    repo
+}
+
+#[test]
+fn test_merge_branch_merged() {
+    let repo = setup_feature_branch();
+
+    let outcome = git::merge_branch(repo.path(), "feature", "Merge feature").unwrap();
+
+    assert_eq!(outcome, git::MergeOutcome::Merged);
+    assert_eq!(std::fs::read_to_string(repo.path().join("README.md")).unwrap(), "# Feature");
+}
+
+#[test]
+fn test_merge_branch_conflict_is_aborted() {
+    let repo = setup_feature_branch();
+    std::fs::write(repo.path().join("README.md"), "# Main").unwrap();
+    git(repo.path(), &["commit", "-qam", "main change"]);
+
+    let outcome = git::merge_branch(repo.path(), "feature", "Merge feature").unwrap();
+
+    assert_eq!(outcome, git::MergeOutcome::Conflict { files: vec!["README.md".to_string()] });
+    // Nothing is left half merged
+    assert!(!repo.path().join(".git/MERGE_HEAD").exists());
+    assert_eq!(std::fs::read_to_string(repo.path().join("README.md")).unwrap(), "# Main");
+}
+
+#[test]
+fn test_merge_branch_unknown_branch_is_error() {
+    let repo = setup_git_repo();
+    let err = git::merge_branch(repo.path(), "no-such-branch", "Merge").unwrap_err();
+    assert!(err.to_string().contains("Merge failed"));
+}
+
+// === Probe Tests ===
+
+#[test]
+fn test_probe_git_reports_version() {
+    assert!(git::probe_git().unwrap().starts_with("git version"));
+}
+
+#[test]
+fn test_probe_version_missing_program_is_command_not_found() {
+    let err = git::probe_version("agtx-definitely-not-installed", "--version").unwrap_err();
+    assert!(matches!(err, OpError::CommandNotFound { ref program } if program == "agtx-definitely-not-installed"));
+}
+
+#[test]
+fn test_probe_version_failing_program_is_command_failed() {
+    let err = git::probe_version("git", "--no-such-flag").unwrap_err();
+    assert!(matches!(err, OpError::CommandFailed { .. }));
+}
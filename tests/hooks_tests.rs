@@ -0,0 +1,194 @@
+//! Tests for status transition hooks
+//!
+//! The runner tests need mocks: cargo test --features test-mocks
+
+use agtx::db::{Task, TaskStatus};
+use agtx::hooks::{self, HookRunner, ShellHookRunner};
+use agtx::op_error::OpError;
+use tempfile::TempDir;
+
+fn review_task() -> Task {
+    let mut task = Task::new("Fix it's login", "claude", "proj");
+    task.status = TaskStatus::Review;
+    task.branch_name = Some("task/abc123-fix-login".to_string());
+    task.worktree_path = Some("/repo/.agtx/worktrees/abc123-fix-login".to_string());
+    task
+}
+
+// === Render Tests ===
+
+#[test]
+fn test_render_hook_quotes_placeholders() {
+    let rendered = hooks::render_hook("notify {title} {slug} {worktree} {status}", &review_task(), TaskStatus::Done);
+
+    assert_eq!(
+        rendered,
+        r"notify 'Fix it'\''s login' abc123-fix-login /repo/.agtx/worktrees/abc123-fix-login done"
+    );
+}
+
+#[test]
+fn test_render_hook_without_worktree_or_branch() {
+    let task = Task::new("Plain", "claude", "proj");
+
+    let rendered = hooks::render_hook("echo {slug} [{worktree}]", &task, TaskStatus::Planning);
+
+    assert_eq!(rendered, format!("echo {} ['']", task.id));
+}
+
+// === ShellHookRunner Tests ===
+
+#[test]
+fn test_shell_runner_runs_in_dir() {
+    let dir = TempDir::new().unwrap();
+
+    ShellHookRunner.run("echo ran > marker", dir.path()).unwrap();
+
+    assert!(dir.path().join("marker").exists());
+}
+
+#[test]
+fn test_shell_runner_reports_exit_code_and_stderr() {
+    let dir = TempDir::new().unwrap();
+
+    let err = ShellHookRunner.run("echo lint failed >&2; exit 2", dir.path()).unwrap_err();
+
+    match err {
+        OpError::CommandFailed { code, stderr, .. } => {
+            assert_eq!(code, Some(2));
+            assert_eq!(stderr, "lint failed");
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+}
+
+// === Transition Tests ===
+
+#[test]
+fn test_settle_non_strict_returns_failed_names() {
+    let failures = vec![hooks::HookFailure {
+        name: "to_done".to_string(),
+        error: OpError::CommandNotFound { program: "sh".to_string() },
+    }];
+
+    assert_eq!(hooks::settle(failures, false).unwrap(), vec!["to_done".to_string()]);
+}
+
+#[test]
+fn test_settle_strict_blocks_with_hook_name() {
+    let failures = vec![hooks::HookFailure {
+        name: "running_to_review".to_string(),
+        error: OpError::CommandFailed { command: "make lint".to_string(), code: Some(1), stderr: String::new() },
+    }];
+
+    let err = hooks::settle(failures, true).unwrap_err();
+
+    assert_eq!(format!("{:#}", err), "Hook 'running_to_review' failed: `make lint` failed (exit 1)");
+}
+
+#[cfg(feature = "test-mocks")]
+mod runner {
+    use super::*;
+    use agtx::config::HooksConfig;
+    use agtx::hooks::MockHookRunner;
+    use std::path::Path;
+    use mockall::predicate::*;
+    use mockall::Sequence;
+
+    fn hooks_config(strict: bool, hooks: &[(&str, &str)]) -> HooksConfig {
+        HooksConfig {
+            strict,
+            commands: hooks.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_runs_specific_then_destination_hooks_in_project_root() {
+        let config = hooks_config(false, &[("to_done", "echo done {title}"), ("review_to_done", "echo {slug}")]);
+        let mut seq = Sequence::new();
+        let mut runner = MockHookRunner::new();
+        // The worktree doesn't exist on disk, so hooks run in the project root
+        runner
+            .expect_run()
+            .with(eq("echo abc123-fix-login"), eq(Path::new("/repo")))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+        runner
+            .expect_run()
+            .with(eq("echo done 'Fix it'\\''s login'"), eq(Path::new("/repo")))
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _| Ok(()));
+
+        let failures = hooks::run_transition_hooks(
+            &config,
+            &runner,
+            &review_task(),
+            TaskStatus::Review,
+            TaskStatus::Done,
+            Path::new("/repo"),
+        );
+
+        assert!(failures.is_empty());
+    }
+
+    #[test]
+    fn test_non_strict_runs_every_hook_and_collects_failures() {
+        let config = hooks_config(false, &[("to_done", "b"), ("review_to_done", "a")]);
+        let mut runner = MockHookRunner::new();
+        runner.expect_run().times(2).returning(|cmd, _| {
+            Err(OpError::CommandFailed { command: cmd.to_string(), code: Some(1), stderr: String::new() })
+        });
+
+        let failures = hooks::run_transition_hooks(
+            &config,
+            &runner,
+            &review_task(),
+            TaskStatus::Review,
+            TaskStatus::Done,
+            Path::new("/repo"),
+        );
+
+        let names: Vec<_> = failures.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["review_to_done", "to_done"]);
+    }
+
+    #[test]
+    fn test_strict_stops_at_first_failure() {
+        let config = hooks_config(true, &[("to_done", "b"), ("review_to_done", "a")]);
+        let mut runner = MockHookRunner::new();
+        runner.expect_run().times(1).returning(|cmd, _| {
+            Err(OpError::CommandFailed { command: cmd.to_string(), code: Some(1), stderr: String::new() })
+        });
+
+        let failures = hooks::run_transition_hooks(
+            &config,
+            &runner,
+            &review_task(),
+            TaskStatus::Review,
+            TaskStatus::Done,
+            Path::new("/repo"),
+        );
+
+        assert_eq!(failures.len(), 1);
+        assert!(hooks::settle(failures, true).is_err());
+    }
+
+    #[test]
+    fn test_no_hooks_for_transition_runs_nothing() {
+        let config = hooks_config(true, &[("to_done", "b")]);
+        let runner = MockHookRunner::new();
+
+        let failures = hooks::run_transition_hooks(
+            &config,
+            &runner,
+            &review_task(),
+            TaskStatus::Running,
+            TaskStatus::Review,
+            Path::new("/repo"),
+        );
+
+        assert!(failures.is_empty());
+    }
+}
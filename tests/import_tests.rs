@@ -0,0 +1,118 @@
+use agtx::db::{Task, TaskStatus};
+use agtx::import::{parse_issue, plan_import, read_issues, ImportSummary, Issue};
+use std::path::PathBuf;
+
+fn issue(title: &str) -> Issue {
+    Issue { path: PathBuf::from(format!("/issues/{}.md", title)), title: title.to_string(), description: None }
+}
+
+// === parse_issue Tests ===
+
+#[test]
+fn test_parse_issue_heading_is_title() {
+    let (title, description) = parse_issue("123-login", "# Fix login\n\nUsers can't log in.\n");
+    assert_eq!(title, "Fix login");
+    assert_eq!(description.as_deref(), Some("Users can't log in."));
+}
+
+#[test]
+fn test_parse_issue_text_before_heading_is_kept() {
+    let (title, description) = parse_issue("x", "Reported by QA\n# Crash on save\nSteps...\n## Notes\nmore");
+    assert_eq!(title, "Crash on save");
+    assert_eq!(description.as_deref(), Some("Reported by QA\nSteps...\n## Notes\nmore"));
+}
+
+#[test]
+fn test_parse_issue_file_name_without_heading() {
+    let (title, description) = parse_issue("fix-the_login--bug", "## Details\nBody");
+    assert_eq!(title, "fix the login bug");
+    assert_eq!(description.as_deref(), Some("## Details\nBody"));
+}
+
+#[test]
+fn test_parse_issue_empty_body() {
+    let (title, description) = parse_issue("empty", "# Only a title\n\n");
+    assert_eq!(title, "Only a title");
+    assert_eq!(description, None);
+
+    let (title, description) = parse_issue("blank-file", "");
+    assert_eq!(title, "blank file");
+    assert_eq!(description, None);
+}
+
+// === read_issues Tests ===
+
+#[test]
+fn test_read_issues_only_markdown_sorted() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("b.md"), "# Second").unwrap();
+    std::fs::write(dir.path().join("a.MD"), "# First\nbody").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "# Not an issue").unwrap();
+    std::fs::create_dir(dir.path().join("sub.md")).unwrap();
+
+    let (issues, failures) = read_issues(dir.path()).unwrap();
+    let titles: Vec<&str> = issues.iter().map(|i| i.title.as_str()).collect();
+    assert_eq!(titles, vec!["First", "Second"]);
+    assert_eq!(issues[0].description.as_deref(), Some("body"));
+    assert!(failures.is_empty());
+}
+
+#[test]
+fn test_read_issues_missing_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let err = read_issues(&dir.path().join("nope")).unwrap_err();
+    assert!(err.to_string().contains("Failed to read"));
+}
+
+#[test]
+fn test_read_issues_reports_unreadable_files() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("bad.md"), [0xff, 0xfe, 0x00]).unwrap();
+    std::fs::write(dir.path().join("good.md"), "# Good").unwrap();
+
+    let (issues, failures) = read_issues(dir.path()).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].contains("bad.md"));
+}
+
+// === plan_import Tests ===
+
+#[test]
+fn test_plan_import_skips_existing_titles() {
+    let mut done = Task::new("Fix login", "claude", "proj");
+    done.status = TaskStatus::Done;
+    let plan = plan_import(vec![issue("fix LOGIN "), issue("Add search")], &[done]);
+
+    assert_eq!(plan.create.iter().map(|i| i.title.as_str()).collect::<Vec<_>>(), vec!["Add search"]);
+    assert_eq!(plan.skipped.len(), 1);
+}
+
+#[test]
+fn test_plan_import_skips_repeats_within_import() {
+    let plan = plan_import(vec![issue("Same"), issue("same")], &[]);
+    assert_eq!(plan.create.len(), 1);
+    assert_eq!(plan.skipped.len(), 1);
+}
+
+#[test]
+fn test_plan_import_is_idempotent() {
+    let first = plan_import(vec![issue("A"), issue("B")], &[]);
+    let board: Vec<Task> = first.create.iter().map(|i| Task::new(i.title.clone(), "claude", "proj")).collect();
+    let second = plan_import(vec![issue("A"), issue("B")], &board);
+    assert!(second.create.is_empty());
+    assert_eq!(second.skipped.len(), 2);
+}
+
+#[test]
+fn test_import_summary_lines() {
+    let summary = ImportSummary {
+        created: vec!["A".to_string(), "B".to_string()],
+        skipped: vec!["C".to_string()],
+        failures: vec!["bad.md: no title".to_string()],
+    };
+    assert_eq!(
+        summary.lines(),
+        vec!["2 task(s) created", "1 skipped (already on the board)", "Failed: bad.md: no title"]
+    );
+}
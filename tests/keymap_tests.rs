@@ -0,0 +1,254 @@
+use agtx::db::TaskStatus;
+use agtx::tui::keymap::{Action, HelpCategory, KeyBinding, KeyMap, Preset};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+fn overrides(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+// === KeyBinding Parsing Tests ===
+
+#[test]
+fn test_parse_single_char() {
+    assert_eq!(KeyBinding::parse("m").unwrap(), KeyBinding::char('m'));
+    assert_eq!(KeyBinding::parse("/").unwrap(), KeyBinding::char('/'));
+    assert_eq!(KeyBinding::parse("+").unwrap(), KeyBinding::char('+'));
+}
+
+#[test]
+fn test_parse_named_keys() {
+    assert_eq!(KeyBinding::parse("enter").unwrap().code, KeyCode::Enter);
+    assert_eq!(KeyBinding::parse("Esc").unwrap().code, KeyCode::Esc);
+    assert_eq!(KeyBinding::parse("space").unwrap().code, KeyCode::Char(' '));
+    assert_eq!(KeyBinding::parse("f5").unwrap().code, KeyCode::F(5));
+}
+
+#[test]
+fn test_parse_modifiers() {
+    let binding = KeyBinding::parse("ctrl+d").unwrap();
+    assert_eq!(binding.code, KeyCode::Char('d'));
+    assert_eq!(binding.modifiers, KeyModifiers::CONTROL);
+
+    // shift on a character is folded into the uppercase character
+    assert_eq!(KeyBinding::parse("shift+n").unwrap(), KeyBinding::char('N'));
+}
+
+#[test]
+fn test_parse_invalid() {
+    assert!(KeyBinding::parse("").is_err());
+    assert!(KeyBinding::parse("nope").is_err());
+    assert!(KeyBinding::parse("f13").is_err());
+    assert!(KeyBinding::parse("hyper+x").is_err());
+}
+
+#[test]
+fn test_uppercase_matches_with_shift_reported() {
+    let binding = KeyBinding::char('N');
+    assert!(binding.matches(&KeyEvent::new(KeyCode::Char('N'), KeyModifiers::SHIFT)));
+    assert!(binding.matches(&key(KeyCode::Char('N'))));
+    assert!(!binding.matches(&key(KeyCode::Char('n'))));
+}
+
+// === KeyMap Tests ===
+
+#[test]
+fn test_default_keymap_matches_builtin_keys() {
+    let keymap = KeyMap::default();
+
+    assert_eq!(keymap.binding(Action::MoveForward), KeyBinding::char('m'));
+    assert_eq!(keymap.binding(Action::Resume), KeyBinding::char('r'));
+    assert_eq!(keymap.binding(Action::MoveBack), KeyBinding::char('r'));
+    assert_eq!(keymap.binding(Action::Delete), KeyBinding::char('x'));
+    assert_eq!(keymap.binding(Action::NewTask), KeyBinding::char('o'));
+    assert_eq!(keymap.binding(Action::Search), KeyBinding::char('/'));
+    assert_eq!(keymap.binding(Action::Attach), KeyBinding::char('a'));
+    assert_eq!(keymap.binding(Action::TogglePin), KeyBinding::char('f'));
+    assert_eq!(keymap.binding(Action::Duplicate), KeyBinding::char('y'));
+    assert_eq!(keymap.binding(Action::NextActionable), KeyBinding::char('g'));
+    assert_eq!(keymap.binding(Action::Abort), KeyBinding::char('C'));
+    assert_eq!(keymap.binding(Action::ToggleAutomation), KeyBinding::char('H'));
+    assert_eq!(keymap.binding(Action::Abandon), KeyBinding::char('X'));
+    assert_eq!(keymap.binding(Action::CycleAgent), KeyBinding::char('G'));
+    assert_eq!(keymap.binding(Action::SelectLeft), KeyBinding::char('h'));
+    assert_eq!(keymap.binding(Action::SelectDown), KeyBinding::char('j'));
+    assert_eq!(keymap.binding(Action::SelectUp), KeyBinding::char('k'));
+    assert_eq!(keymap.binding(Action::SelectRight), KeyBinding::char('l'));
+    assert_eq!(KeyMap::from_config(&HashMap::new()).unwrap(), keymap);
+}
+
+#[test]
+fn test_resolve_shared_key_by_column() {
+    let keymap = KeyMap::default();
+    let r = key(KeyCode::Char('r'));
+
+    assert_eq!(keymap.resolve(&r, Some(TaskStatus::Review)), Some(Action::Resume));
+    assert_eq!(keymap.resolve(&r, Some(TaskStatus::Running)), Some(Action::MoveBack));
+}
+
+#[test]
+fn test_resolve_unbound_key() {
+    let keymap = KeyMap::default();
+    assert_eq!(keymap.resolve(&key(KeyCode::Char('z')), None), None);
+}
+
+#[test]
+fn test_override_replaces_default() {
+    let keymap = KeyMap::from_config(&overrides(&[("move_forward", "u")])).unwrap();
+
+    assert_eq!(keymap.resolve(&key(KeyCode::Char('u')), None), Some(Action::MoveForward));
+    assert_eq!(keymap.resolve(&key(KeyCode::Char('m')), None), None);
+}
+
+#[test]
+fn test_unknown_action_is_error() {
+    let err = KeyMap::from_config(&overrides(&[("fly_away", "f")])).unwrap_err();
+    assert!(format!("{:#}", err).contains("fly_away"));
+}
+
+#[test]
+fn test_invalid_key_names_action() {
+    let err = KeyMap::from_config(&overrides(&[("delete", "bogus")])).unwrap_err();
+    assert!(format!("{:#}", err).contains("delete"));
+}
+
+#[test]
+fn test_conflicting_bindings_is_error() {
+    let err = KeyMap::from_config(&overrides(&[("search", "x")])).unwrap_err();
+    let msg = format!("{:#}", err);
+    assert!(msg.contains("search"));
+    assert!(msg.contains("delete"));
+}
+
+#[test]
+fn test_binding_builtin_key_is_error() {
+    let err = KeyMap::from_config(&overrides(&[("delete", "q")])).unwrap_err();
+    assert!(format!("{:#}", err).contains("delete"));
+
+    // With a modifier the key is free
+    assert!(KeyMap::from_config(&overrides(&[("delete", "ctrl+q")])).is_ok());
+}
+
+// === Preset Tests ===
+
+#[test]
+fn test_vim_preset_is_default() {
+    let keymap = KeyMap::from_config(&overrides(&[("preset", "vim")])).unwrap();
+    assert_eq!(keymap, KeyMap::default());
+    assert_eq!(keymap, KeyMap::with_preset(Preset::Vim));
+    assert_eq!(keymap.resolve(&key(KeyCode::Char('h')), None), Some(Action::SelectLeft));
+}
+
+#[test]
+fn test_arrows_preset_frees_vim_keys() {
+    let keymap = KeyMap::from_config(&overrides(&[("preset", "Arrows"), ("search", "j")])).unwrap();
+
+    assert_eq!(keymap.resolve(&key(KeyCode::Char('j')), None), Some(Action::Search));
+    assert_eq!(keymap.resolve(&key(KeyCode::Char('h')), None), None);
+    assert_eq!(keymap.resolve(&key(KeyCode::Down), None), Some(Action::SelectDown));
+}
+
+#[test]
+fn test_vim_key_taken_by_other_action_is_error() {
+    let err = KeyMap::from_config(&overrides(&[("search", "j")])).unwrap_err();
+    assert!(format!("{:#}", err).contains("select_down"));
+}
+
+#[test]
+fn test_unknown_preset_is_error() {
+    let err = KeyMap::from_config(&overrides(&[("preset", "emacs")])).unwrap_err();
+    assert!(format!("{:#}", err).contains("emacs"));
+}
+
+#[test]
+fn test_selection_action_on_other_arrow_is_error() {
+    let err = KeyMap::from_config(&overrides(&[("select_left", "right")])).unwrap_err();
+    assert!(format!("{:#}", err).contains("select_left"));
+}
+
+// === Help Overlay Tests ===
+
+fn help_keys(keymap: &KeyMap, description: &str) -> Option<String> {
+    keymap
+        .help_sections()
+        .into_iter()
+        .flat_map(|(_, entries)| entries)
+        .find(|e| e.description == description)
+        .map(|e| e.keys)
+}
+
+#[test]
+fn test_help_lists_every_action_once() {
+    let keymap = KeyMap::default();
+    let sections = keymap.help_sections();
+    let categories: Vec<HelpCategory> = sections.iter().map(|(c, _)| *c).collect();
+    assert_eq!(categories, HelpCategory::ALL.to_vec());
+
+    for action in Action::ALL {
+        let listed = sections
+            .iter()
+            .filter(|(category, entries)| *category == action.category() && entries.iter().any(|e| e.description == action.help()))
+            .count();
+        assert_eq!(listed, 1, "{} should be listed once", action.name());
+    }
+}
+
+#[test]
+fn test_help_follows_remapped_keys() {
+    let keymap = KeyMap::default();
+    assert_eq!(help_keys(&keymap, Action::MoveForward.help()).as_deref(), Some("m"));
+
+    let keymap = KeyMap::from_config(&overrides(&[("move_forward", "ctrl+n")])).unwrap();
+    assert_eq!(help_keys(&keymap, Action::MoveForward.help()).as_deref(), Some("Ctrl+n"));
+}
+
+#[test]
+fn test_help_selection_shows_arrow_too() {
+    assert_eq!(KeyMap::default().help_keys(Action::SelectLeft), "h / ←");
+    // Under the arrows preset the arrow is the binding itself
+    assert_eq!(KeyMap::with_preset(Preset::Arrows).help_keys(Action::SelectLeft), "←");
+    assert_eq!(KeyMap::default().help_keys(Action::Search), "/");
+}
+
+#[test]
+fn test_help_lists_builtin_keys() {
+    let keymap = KeyMap::default();
+    assert_eq!(help_keys(&keymap, "Quit").as_deref(), Some("q / Ctrl+c"));
+    assert_eq!(help_keys(&keymap, "This help").as_deref(), Some("?"));
+}
+
+/// Every fixed key the help lists is one a remappable action can't take over
+#[test]
+fn test_builtin_help_keys_are_reserved() {
+    let action_help: Vec<&str> = Action::ALL.iter().map(|a| a.help()).collect();
+    let builtin: Vec<String> = KeyMap::default()
+        .help_sections()
+        .into_iter()
+        .flat_map(|(_, entries)| entries)
+        .filter(|entry| !action_help.contains(&entry.description))
+        .map(|entry| entry.keys)
+        .collect();
+    assert!(builtin.iter().any(|keys| keys == "i"));
+
+    for keys in &builtin {
+        for key in keys.split(" / ") {
+            // Keys with a modifier (Ctrl+c) are free for actions
+            if key.contains('+') {
+                continue;
+            }
+            let result = KeyMap::from_config(&overrides(&[("delete", key)]));
+            assert!(result.is_err(), "built-in key '{}' can be bound to an action", key);
+        }
+    }
+}
+
+#[test]
+fn test_question_mark_is_reserved_for_help() {
+    let err = KeyMap::from_config(&overrides(&[("search", "?")])).unwrap_err();
+    assert!(err.to_string().contains("built-in"));
+}
+
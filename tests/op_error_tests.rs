@@ -0,0 +1,100 @@
+use agtx::op_error::{op_error, OpError};
+use anyhow::Context;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::Duration;
+
+// === Display Tests ===
+
+#[test]
+fn test_command_failed_message() {
+    let err = OpError::CommandFailed {
+        command: "git push".to_string(),
+        code: Some(128),
+        stderr: "fatal: Authentication failed\n".to_string(),
+    };
+    assert_eq!(err.to_string(), "`git push` failed (exit 128): fatal: Authentication failed");
+
+    let killed = OpError::CommandFailed { command: "tmux new-window".to_string(), code: None, stderr: String::new() };
+    assert_eq!(killed.to_string(), "`tmux new-window` failed");
+}
+
+#[test]
+fn test_variant_messages() {
+    assert_eq!(
+        OpError::CommandNotFound { program: "tmux".to_string() }.to_string(),
+        "`tmux` was not found; is it installed and on PATH?"
+    );
+    assert_eq!(
+        OpError::Timeout { op: "git push".to_string(), after: Duration::from_secs(120) }.to_string(),
+        "git push timed out after 120s and was stopped"
+    );
+    assert_eq!(
+        OpError::NotAGitRepo { path: PathBuf::from("/tmp/plain") }.to_string(),
+        "/tmp/plain is not a git repository"
+    );
+    assert_eq!(
+        OpError::WindowNotFound { target: "proj:task-1".to_string() }.to_string(),
+        "tmux window 'proj:task-1' not found"
+    );
+    assert_eq!(
+        OpError::ServerNotRunning { server: "agtx".to_string() }.to_string(),
+        "tmux server 'agtx' is not running"
+    );
+    assert_eq!(
+        OpError::NoCommits { path: PathBuf::from("/tmp/fresh") }.to_string(),
+        "/tmp/fresh has no commits yet; make a first commit before starting tasks"
+    );
+    assert_eq!(
+        OpError::LowDiskSpace { path: PathBuf::from("/repo/.agtx/worktrees"), available_mb: 512, required_mb: 2048 }
+            .to_string(),
+        "only 512 MB free at /repo/.agtx/worktrees, below worktree.min_free_mb (2048 MB)"
+    );
+}
+
+// === Construction Tests ===
+
+#[test]
+fn test_spawn_missing_program_is_command_not_found() {
+    let io_err = Command::new("agtx-definitely-not-installed").output().unwrap_err();
+
+    let err = OpError::spawn("agtx-definitely-not-installed", io_err);
+
+    assert!(matches!(err, OpError::CommandNotFound { ref program } if program == "agtx-definitely-not-installed"));
+    assert!(err.is_fatal());
+}
+
+#[test]
+fn test_failed_captures_exit_code_and_stderr() {
+    let output = Command::new("sh").args(["-c", "echo oops >&2; exit 3"]).output().unwrap();
+
+    match OpError::failed("sh", &output) {
+        OpError::CommandFailed { command, code, stderr } => {
+            assert_eq!(command, "sh");
+            assert_eq!(code, Some(3));
+            assert_eq!(stderr, "oops");
+        }
+        other => panic!("expected CommandFailed, got {:?}", other),
+    }
+}
+
+// === Classification Tests ===
+
+#[test]
+fn test_retryable_and_fatal() {
+    assert!(OpError::WindowNotFound { target: "t".to_string() }.is_retryable());
+    assert!(OpError::Timeout { op: "git push".to_string(), after: Duration::from_secs(1) }.is_retryable());
+    assert!(!OpError::CommandNotFound { program: "git".to_string() }.is_retryable());
+
+    assert!(OpError::NotAGitRepo { path: PathBuf::from("/tmp") }.is_fatal());
+    assert!(!OpError::WindowNotFound { target: "t".to_string() }.is_fatal());
+}
+
+#[test]
+fn test_op_error_found_through_context() {
+    let result: anyhow::Result<()> = Err(OpError::WindowNotFound { target: "proj:task-1".to_string() }.into());
+    let err = result.context("Resuming task").unwrap_err();
+
+    assert!(matches!(op_error(&err), Some(OpError::WindowNotFound { .. })));
+    assert!(op_error(&anyhow::anyhow!("plain error")).is_none());
+}
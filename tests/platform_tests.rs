@@ -0,0 +1,97 @@
+use agtx::git;
+use agtx::platform::{
+    available_space, multiplexer_unsupported, native_path, parse_df_available, powershell_quote, shell_command, shell_program,
+    shell_quote,
+};
+use std::path::{Path, PathBuf};
+
+// === Path Tests ===
+
+#[test]
+fn test_native_path_uses_platform_separators() {
+    assert_eq!(native_path(".agtx/worktrees"), Path::new(".agtx").join("worktrees"));
+    assert_eq!(native_path("wt"), PathBuf::from("wt"));
+    assert_eq!(native_path(".agtx//api.sock"), Path::new(".agtx").join("api.sock"));
+}
+
+#[test]
+fn test_worktree_path_is_joined_per_component() {
+    let project = Path::new("project");
+    assert_eq!(
+        git::worktree_path(project, "fix-login"),
+        project.join(".agtx").join("worktrees").join("fix-login")
+    );
+}
+
+// === Free Space Tests ===
+
+#[test]
+fn test_parse_df_available() {
+    let output = "Filesystem     1024-blocks     Used Available Capacity Mounted on\n\
+                  /dev/sda1        102400000 51200000  40960000      56% /\n";
+    assert_eq!(parse_df_available(output), Some(40960000 * 1024));
+    assert_eq!(parse_df_available("Filesystem 1024-blocks Used Available Capacity Mounted on\n"), None);
+    assert_eq!(parse_df_available("header\n/dev/sda1 10 5 lots 50% /\n"), None);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_available_space_measures_nearest_existing_parent() {
+    let dir = tempfile::tempdir().unwrap();
+    let not_yet = dir.path().join("worktrees/task-1");
+
+    assert!(available_space(&not_yet).unwrap() > 0);
+}
+
+// === Multiplexer Support Tests ===
+
+#[test]
+fn test_multiplexer_unsupported_on_windows_only() {
+    let reason = multiplexer_unsupported("tmux", "windows").unwrap();
+    assert!(reason.contains("tmux isn't supported on native Windows"));
+    assert!(reason.contains("WSL"));
+    assert!(reason.contains("--no-tmux"));
+
+    assert!(multiplexer_unsupported("tmux", "linux").is_none());
+    assert!(multiplexer_unsupported("zellij", "macos").is_none());
+}
+
+// === Shell Tests ===
+
+#[test]
+#[cfg(unix)]
+fn test_shell_command_runs_script() {
+    assert_eq!(shell_program(), "sh");
+    let output = shell_command("echo one && echo two").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "one\ntwo\n");
+}
+
+// === shell_quote Tests ===
+
+#[test]
+fn test_shell_quote_leaves_safe_words_bare() {
+    assert_eq!(shell_quote("task/fix-bug"), "task/fix-bug");
+    assert_eq!(shell_quote("proj:task-1"), "proj:task-1");
+}
+
+#[test]
+fn test_shell_quote_quotes_spaces_and_metacharacters() {
+    assert_eq!(shell_quote("hello world"), "'hello world'");
+    assert_eq!(shell_quote("claude; exec $SHELL"), "'claude; exec $SHELL'");
+    assert_eq!(shell_quote(""), "''");
+}
+
+#[test]
+fn test_shell_quote_escapes_single_quotes() {
+    assert_eq!(shell_quote("it's"), r"'it'\''s'");
+}
+
+// === powershell_quote Tests ===
+
+#[test]
+fn test_powershell_quote_doubles_single_quotes() {
+    assert_eq!(powershell_quote(r"C:\Users\me\repo"), r"'C:\Users\me\repo'");
+    // A quote in the path can't end the literal and run the rest as script
+    assert_eq!(powershell_quote(r"C:\it's'); Remove-Item x; ('"), r"'C:\it''s''); Remove-Item x; ('''");
+}
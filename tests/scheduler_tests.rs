@@ -0,0 +1,203 @@
+//! Tests for the agent dispatch scheduler
+//!
+//! Run with: cargo test --features test-mocks
+
+#![cfg(feature = "test-mocks")]
+
+use agtx::op_error::OpError;
+use agtx::scheduler::{DispatchOutcome, DispatchScheduler, PendingDispatch};
+use agtx::tmux::MockTmuxOperations;
+use mockall::predicate::*;
+
+fn dispatch(task_id: &str) -> PendingDispatch {
+    PendingDispatch {
+        task_id: task_id.to_string(),
+        project: "proj".to_string(),
+        target: format!("proj:{}", task_id),
+        command: "/agtx:execute".to_string(),
+    }
+}
+
+// === Dispatch Tests ===
+
+#[test]
+fn test_unlimited_cap_sends_immediately() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().times(3).returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(0);
+    for id in ["a", "b", "c"] {
+        assert_eq!(scheduler.submit(dispatch(id), &mock_tmux).unwrap(), DispatchOutcome::Sent);
+    }
+    assert_eq!(scheduler.active_count(), 3);
+}
+
+#[test]
+fn test_cap_reached_queues_without_sending() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux
+        .expect_send_keys()
+        .with(eq("proj:a"), eq("/agtx:execute"))
+        .times(1)
+        .returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    assert_eq!(scheduler.submit(dispatch("a"), &mock_tmux).unwrap(), DispatchOutcome::Sent);
+    assert_eq!(scheduler.submit(dispatch("b"), &mock_tmux).unwrap(), DispatchOutcome::Queued(1));
+    assert_eq!(scheduler.submit(dispatch("c"), &mock_tmux).unwrap(), DispatchOutcome::Queued(2));
+
+    assert!(scheduler.is_queued("b"));
+    assert_eq!(scheduler.queued_count(), 2);
+}
+
+#[test]
+fn test_task_leaving_running_dispatches_next_in_queue() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().with(eq("proj:a"), always()).times(1).returning(|_, _| Ok(()));
+    mock_tmux.expect_send_keys().with(eq("proj:b"), always()).times(1).returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.submit(dispatch("a"), &mock_tmux).unwrap();
+    scheduler.submit(dispatch("b"), &mock_tmux).unwrap();
+    scheduler.submit(dispatch("c"), &mock_tmux).unwrap();
+
+    // "a" moved on to Review; b and c are still Running
+    let dispatched = scheduler.sync("proj", &["b", "c"], &mock_tmux);
+
+    assert_eq!(dispatched, vec!["b".to_string()]);
+    assert!(!scheduler.is_queued("b"));
+    assert!(scheduler.is_queued("c"));
+    assert_eq!(scheduler.active_count(), 1);
+}
+
+#[test]
+fn test_sync_drops_queued_task_that_left_running() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().times(1).returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.submit(dispatch("a"), &mock_tmux).unwrap();
+    scheduler.submit(dispatch("b"), &mock_tmux).unwrap();
+
+    // "b" was moved back to Planning before it ever got a slot
+    let dispatched = scheduler.sync("proj", &["a"], &mock_tmux);
+
+    assert!(dispatched.is_empty());
+    assert_eq!(scheduler.queued_count(), 0);
+}
+
+#[test]
+fn test_sync_counts_running_tasks_it_did_not_dispatch() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().never();
+
+    // Two agents were already running when agtx started
+    let mut scheduler = DispatchScheduler::new(2);
+    scheduler.sync("proj", &["x", "y"], &mock_tmux);
+
+    assert_eq!(scheduler.active_count(), 2);
+    assert_eq!(scheduler.submit(dispatch("a"), &mock_tmux).unwrap(), DispatchOutcome::Queued(1));
+}
+
+#[test]
+fn test_sync_leaves_other_projects_alone() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().times(1).returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.submit(dispatch("a"), &mock_tmux).unwrap();
+
+    scheduler.sync("other", &[], &mock_tmux);
+
+    assert_eq!(scheduler.active_count(), 1);
+}
+
+#[test]
+fn test_raising_cap_dispatches_queue() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().times(2).returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.submit(dispatch("a"), &mock_tmux).unwrap();
+    scheduler.submit(dispatch("b"), &mock_tmux).unwrap();
+
+    let dispatched = scheduler.set_cap(0, &mock_tmux);
+
+    assert_eq!(dispatched, vec!["b".to_string()]);
+    assert_eq!(scheduler.queued_count(), 0);
+}
+
+#[test]
+fn test_failed_dispatch_from_queue_is_dropped() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().with(eq("proj:a"), always()).returning(|_, _| Ok(()));
+    mock_tmux
+        .expect_send_keys()
+        .with(eq("proj:b"), always())
+        .returning(|target, _| Err(OpError::WindowNotFound { target: target.to_string() }));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.submit(dispatch("a"), &mock_tmux).unwrap();
+    scheduler.submit(dispatch("b"), &mock_tmux).unwrap();
+
+    let dispatched = scheduler.sync("proj", &["b"], &mock_tmux);
+
+    assert!(dispatched.is_empty());
+    assert_eq!(scheduler.queued_count(), 0);
+    assert_eq!(scheduler.active_count(), 0);
+}
+
+// === Persistence Tests ===
+
+#[test]
+fn test_take_changed_reports_projects_whose_queue_changed() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().returning(|_, _| Ok(()));
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.submit(dispatch("a"), &mock_tmux).unwrap();
+    // Sending right away doesn't touch the queue
+    assert!(scheduler.take_changed().is_empty());
+
+    scheduler.submit(dispatch("b"), &mock_tmux).unwrap();
+    assert_eq!(scheduler.take_changed(), vec!["proj".to_string()]);
+    assert!(scheduler.take_changed().is_empty());
+
+    scheduler.sync("proj", &["b"], &mock_tmux);
+    assert_eq!(scheduler.take_changed(), vec!["proj".to_string()]);
+    assert_eq!(scheduler.queued_for("proj").count(), 0);
+}
+
+#[test]
+fn test_restored_queue_waits_for_a_slot() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().with(eq("proj:b"), always()).times(1).returning(|_, _| Ok(()));
+
+    // A new session: "a" holds the only slot, "b" was queued when agtx quit
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.restore(dispatch("b"));
+    let dispatched = scheduler.sync("proj", &["a", "b"], &mock_tmux);
+    assert!(dispatched.is_empty());
+    assert!(scheduler.is_queued("b"));
+    assert_eq!(scheduler.active_count(), 1);
+
+    // Restoring again (switching back to the project) doesn't duplicate it
+    scheduler.restore(dispatch("b"));
+    assert_eq!(scheduler.queued_count(), 1);
+
+    let dispatched = scheduler.sync("proj", &["b"], &mock_tmux);
+    assert_eq!(dispatched, vec!["b".to_string()]);
+}
+
+#[test]
+fn test_restored_command_for_task_that_left_running_is_dropped() {
+    let mut mock_tmux = MockTmuxOperations::new();
+    mock_tmux.expect_send_keys().never();
+
+    let mut scheduler = DispatchScheduler::new(1);
+    scheduler.restore(dispatch("b"));
+    scheduler.sync("proj", &[], &mock_tmux);
+
+    assert_eq!(scheduler.queued_count(), 0);
+    assert_eq!(scheduler.take_changed(), vec!["proj".to_string()]);
+}
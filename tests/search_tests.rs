@@ -0,0 +1,91 @@
+use agtx::db::{Task, TaskNote, TaskStatus};
+use agtx::tui::search::{self, highlight_segments, match_task, search_tasks, MatchField, SearchQuery};
+
+fn task(title: &str, description: Option<&str>, notes: &[&str]) -> Task {
+    let mut task = Task::new(title, "claude", "project-1");
+    task.description = description.map(str::to_string);
+    task.notes = notes.iter().map(|n| TaskNote { at: chrono::Utc::now(), text: n.to_string() }).collect();
+    task
+}
+
+// === Query Tests ===
+
+#[test]
+fn test_parse_title_only_by_default() {
+    assert_eq!(SearchQuery::parse("Login"), SearchQuery { text: "login".to_string(), everywhere: false });
+}
+
+#[test]
+fn test_parse_everywhere_prefix() {
+    assert_eq!(SearchQuery::parse("+ Redis"), SearchQuery { text: "redis".to_string(), everywhere: true });
+}
+
+// === Matching Tests ===
+
+#[test]
+fn test_title_only_ignores_description_and_notes() {
+    let t = task("Fix login", Some("uses redis"), &["redis is flaky"]);
+    assert_eq!(match_task(&t, &SearchQuery::parse("redis")), None);
+    assert!(matches!(match_task(&t, &SearchQuery::parse("login")), Some((MatchField::Title, _))));
+}
+
+#[test]
+fn test_everywhere_reports_field() {
+    let described = task("Fix login", Some("Session cache lives in Redis"), &[]);
+    let noted = task("Speed up tests", None, &["try the redis mock"]);
+    let query = SearchQuery::parse("+redis");
+
+    assert_eq!(match_task(&described, &query).map(|m| m.0), Some(MatchField::Description));
+    assert_eq!(match_task(&noted, &query).map(|m| m.0), Some(MatchField::Notes));
+}
+
+#[test]
+fn test_everywhere_prefers_title() {
+    let t = task("Redis upgrade", Some("redis 7"), &[]);
+    assert_eq!(match_task(&t, &SearchQuery::parse("+redis")).map(|m| m.0), Some(MatchField::Title));
+}
+
+#[test]
+fn test_search_orders_title_matches_first() {
+    let noted = task("Speed up tests", None, &["redis"]);
+    let described = task("Fix login", Some("redis"), &[]);
+    let titled = task("Redis upgrade", None, &[]);
+    let tasks = vec![&noted, &described, &titled];
+
+    let found = search_tasks(&tasks, &SearchQuery::parse("+redis"), 10);
+
+    let fields: Vec<MatchField> = found.iter().map(|m| m.field).collect();
+    assert_eq!(fields, vec![MatchField::Title, MatchField::Description, MatchField::Notes]);
+    assert_eq!(found[0].title, "Redis upgrade");
+    assert_eq!(found[0].status, TaskStatus::Backlog);
+}
+
+#[test]
+fn test_search_limit_and_empty_query() {
+    let tasks: Vec<Task> = (0..5).map(|i| task(&format!("task {}", i), None, &[])).collect();
+    let refs: Vec<&Task> = tasks.iter().collect();
+    assert_eq!(search_tasks(&refs, &SearchQuery::parse(""), 3).len(), 3);
+    assert_eq!(search_tasks(&refs, &SearchQuery::parse("+"), 10).len(), 5);
+}
+
+#[test]
+fn test_fuzzy_title_match() {
+    assert!(search::fuzzy_score("fix login page", "flp") > 0);
+    assert_eq!(search::fuzzy_score("fix login page", "zzz"), 0);
+}
+
+// === Highlight Tests ===
+
+#[test]
+fn test_highlight_segments() {
+    assert_eq!(
+        highlight_segments("Redis and redis", "redis"),
+        vec![("Redis".to_string(), true), (" and ".to_string(), false), ("redis".to_string(), true)]
+    );
+}
+
+#[test]
+fn test_highlight_without_needle_or_match() {
+    assert_eq!(highlight_segments("plain", ""), vec![("plain".to_string(), false)]);
+    assert_eq!(highlight_segments("plain", "zzz"), vec![("plain".to_string(), false)]);
+}
@@ -0,0 +1,119 @@
+use agtx::db::Task;
+use agtx::slug::{
+    slugify, unique_slug, validate_window_template, window_name, with_free_suffix, DEFAULT_WINDOW_TEMPLATE, TITLE_MAX_LEN,
+};
+
+// === slugify Tests ===
+
+#[test]
+fn test_slugify_normal_title() {
+    assert_eq!(slugify("12345678-abcd-efgh", "Add login feature"), "12345678-Add-login-feature");
+}
+
+#[test]
+fn test_slugify_colon_and_slash() {
+    assert_eq!(slugify("abc12345", "fix: thing / other"), "abc12345-fix-thing-other");
+}
+
+#[test]
+fn test_slugify_special_chars() {
+    let slug = slugify("abc12345", "Fix bug #123 (urgent!)");
+    assert_eq!(slug, "abc12345-Fix-bug-123-urgent");
+}
+
+#[test]
+fn test_slugify_dots_never_make_bad_refs() {
+    // `..` and a trailing `.lock` aren't allowed in git branch names
+    assert_eq!(slugify("abc12345", "bump ../deps.lock"), "abc12345-bump-deps-lock");
+}
+
+#[test]
+fn test_slugify_unicode() {
+    assert_eq!(slugify("abc12345", "café ☕ 修正"), "abc12345-caf");
+    assert_eq!(slugify("abc12345", "修正"), "abc12345");
+}
+
+#[test]
+fn test_slugify_empty_title() {
+    assert_eq!(slugify("12345678", ""), "12345678");
+    assert_eq!(slugify("12345678", "  ::  "), "12345678");
+}
+
+#[test]
+fn test_slugify_truncates_title() {
+    let long_title = "This is a very long task title that should be truncated to thirty characters";
+    let slug = slugify("abcd1234", long_title);
+    assert!(slug.len() <= 9 + TITLE_MAX_LEN);
+    assert!(!slug.ends_with('-'));
+}
+
+#[test]
+fn test_slugify_only_safe_characters() {
+    let slug = slugify("abc12345", "  tabs\tand\nnewlines; $(rm -rf) `x` 'q' \"d\" ~ ^ ? * [ ] \\ @{ }");
+    assert!(slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{}", slug);
+    assert!(!slug.contains("--"));
+}
+
+// === unique_slug Tests ===
+
+#[test]
+fn test_unique_slug_free() {
+    assert_eq!(unique_slug("abc12345", "fix", |_| false), "abc12345-fix");
+}
+
+#[test]
+fn test_unique_slug_appends_counter() {
+    let taken = ["abc12345-fix", "abc12345-fix-2"];
+    assert_eq!(unique_slug("abc12345", "fix", |s| taken.contains(&s)), "abc12345-fix-3");
+}
+
+#[test]
+fn test_with_free_suffix() {
+    assert_eq!(with_free_suffix("Fix-login", |_| false), "Fix-login");
+    assert_eq!(with_free_suffix("Fix-login", |s| s == "Fix-login"), "Fix-login-2");
+}
+
+// === window_name Tests ===
+
+fn task(id: &str, title: &str) -> Task {
+    let mut task = Task::new(title, "claude", "proj");
+    task.id = id.to_string();
+    task
+}
+
+#[test]
+fn test_window_name_default_template() {
+    let task = task("abc12345-0000", "Fix login");
+    assert_eq!(window_name(DEFAULT_WINDOW_TEMPLATE, &task, "abc12345-Fix-login-2"), "task-abc12345-Fix-login-2");
+}
+
+#[test]
+fn test_window_name_placeholders() {
+    let task = task("abc12345-0000", "Fix login");
+    assert_eq!(window_name("{id}_{title}", &task, "unused"), "abc12345_Fix-login");
+    assert_eq!(window_name("w-{slug}", &task, "abc12345-Fix-login"), "w-abc12345-Fix-login");
+}
+
+#[test]
+fn test_window_name_is_tmux_safe() {
+    // ':' and '.' separate session, window and pane in a tmux target
+    let task = task("abc12345-0000", "v1.2: release notes");
+    let name = window_name("agtx:{title}", &task, "slug");
+    assert_eq!(name, "agtx-v1-2-release-notes");
+    assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+}
+
+#[test]
+fn test_window_name_empty_result_falls_back_to_slug() {
+    let task = task("abc12345-0000", "!!!");
+    assert_eq!(window_name("{title}", &task, "abc12345"), "abc12345");
+}
+
+#[test]
+fn test_validate_window_template() {
+    assert!(validate_window_template(DEFAULT_WINDOW_TEMPLATE).is_ok());
+    assert!(validate_window_template("agtx:{title}-{id}").is_ok());
+    assert!(validate_window_template("  ").is_err());
+    let err = validate_window_template("{name}").unwrap_err();
+    assert!(err.to_string().contains("Unknown placeholder"));
+}
@@ -0,0 +1,179 @@
+use agtx::db::{Task, TaskStatus};
+use agtx::snapshot::{self, RestoreSummary, Snapshot};
+use tempfile::TempDir;
+
+fn task(title: &str, status: TaskStatus) -> Task {
+    let mut task = Task::new(title, "claude", "project-1");
+    task.status = status;
+    task
+}
+
+fn running(title: &str, slug: &str) -> Task {
+    let mut task = task(title, TaskStatus::Running);
+    task.session_name = Some(format!("proj:task-{}", slug));
+    task.worktree_path = Some(format!("/proj/.agtx/worktrees/{}", slug));
+    task.branch_name = Some(format!("task/{}", slug));
+    task
+}
+
+// === Name Tests ===
+
+#[test]
+fn test_validate_name() {
+    assert!(snapshot::validate_name("demo-1.before_reset").is_ok());
+    assert!(snapshot::validate_name("").is_err());
+    assert!(snapshot::validate_name(".hidden").is_err());
+    assert!(snapshot::validate_name("../escape").is_err());
+    assert!(snapshot::validate_name("with space").is_err());
+}
+
+// === Save/Load Tests ===
+
+#[test]
+fn test_save_load_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let tasks = vec![task("One", TaskStatus::Backlog), running("Two", "two")];
+
+    let path = snapshot::save(dir.path(), &Snapshot::new("demo", tasks.clone())).unwrap();
+    let loaded = snapshot::load(dir.path(), "demo").unwrap();
+
+    assert!(path.ends_with("demo.json"));
+    assert_eq!(loaded.name, "demo");
+    assert_eq!(loaded.tasks.len(), 2);
+    assert_eq!(loaded.tasks[1].id, tasks[1].id);
+    assert_eq!(loaded.tasks[1].status, TaskStatus::Running);
+    assert_eq!(loaded.tasks[1].session_name, tasks[1].session_name);
+}
+
+#[test]
+fn test_load_missing_snapshot_names_it() {
+    let dir = TempDir::new().unwrap();
+    let err = snapshot::load(dir.path(), "nope").unwrap_err();
+    assert!(err.to_string().contains("nope"));
+}
+
+#[test]
+fn test_list_sorted_json_only() {
+    let dir = TempDir::new().unwrap();
+    assert!(snapshot::list(dir.path()).unwrap().is_empty());
+
+    snapshot::save(dir.path(), &Snapshot::new("zeta", vec![])).unwrap();
+    snapshot::save(dir.path(), &Snapshot::new("alpha", vec![])).unwrap();
+    std::fs::write(snapshot::snapshot_path(dir.path(), "notes").with_extension("txt"), "").unwrap();
+
+    assert_eq!(snapshot::list(dir.path()).unwrap(), vec!["alpha", "zeta"]);
+}
+
+// === Restore Plan Tests ===
+
+#[test]
+fn test_plan_restore_creates_updates_and_removes() {
+    let kept = task("Kept", TaskStatus::Backlog);
+    let mut moved = task("Moved", TaskStatus::Backlog);
+    let gone = task("Gone", TaskStatus::Backlog);
+    let added = task("Added later", TaskStatus::Backlog);
+    let saved = vec![kept.clone(), moved.clone(), gone.clone()];
+    moved.status = TaskStatus::Done;
+    let current = vec![kept, moved.clone(), added.clone()];
+
+    let plan = snapshot::plan_restore(&saved, &current);
+
+    assert_eq!(plan.create.iter().map(|t| &t.id).collect::<Vec<_>>(), vec![&gone.id]);
+    assert_eq!(plan.update.len(), 1);
+    assert_eq!(plan.update[0].id, moved.id);
+    assert_eq!(plan.update[0].status, TaskStatus::Backlog);
+    assert_eq!(plan.remove, vec![added.id]);
+}
+
+#[test]
+fn test_plan_restore_cleans_resources_nothing_refers_to() {
+    let still_running = running("Still running", "a");
+    let mut finished = running("Finished", "b");
+    let extra = running("Started after the snapshot", "c");
+    let saved = vec![still_running.clone(), task("Finished", TaskStatus::Backlog)];
+    finished.id = saved[1].id.clone();
+    let current = vec![still_running, finished, extra];
+
+    let plan = snapshot::plan_restore(&saved, &current);
+
+    let windows: Vec<_> = plan.release.iter().filter_map(|t| t.session_name.as_deref()).collect();
+    let worktrees: Vec<_> = plan.release.iter().filter_map(|t| t.worktree_path.as_deref()).collect();
+    assert_eq!(windows, vec!["proj:task-b", "proj:task-c"]);
+    assert_eq!(worktrees, vec!["/proj/.agtx/worktrees/b", "/proj/.agtx/worktrees/c"]);
+    // Neither restored task is on these branches (the finished one goes back to backlog without one)
+    let branches: Vec<_> = plan.release.iter().filter_map(|t| t.branch_name.as_deref()).collect();
+    assert_eq!(branches, vec!["task/b", "task/c"]);
+}
+
+#[test]
+fn test_plan_restore_releases_only_what_nothing_refers_to() {
+    let current = running("Moved worktree", "m");
+    let mut saved = current.clone();
+    saved.worktree_path = Some("/proj/.agtx/worktrees/elsewhere".to_string());
+
+    let plan = snapshot::plan_restore(&[saved], std::slice::from_ref(&current));
+
+    assert_eq!(plan.release.len(), 1);
+    assert_eq!(plan.release[0].id, current.id);
+    assert_eq!(plan.release[0].session_name, None);
+    assert_eq!(plan.release[0].worktree_path, current.worktree_path);
+    // The restored task's worktree is elsewhere but on the same branch, which must survive
+    assert_eq!(plan.release[0].branch_name, None);
+}
+
+#[test]
+fn test_plan_restore_done_task_releases_resources() {
+    let mut done = running("Done in the snapshot", "d");
+    done.status = TaskStatus::Done;
+    let current = vec![running("Done in the snapshot", "d")];
+    done.id = current[0].id.clone();
+
+    let plan = snapshot::plan_restore(&[done], &current);
+
+    assert_eq!(plan.release.len(), 1);
+    assert_eq!(plan.release[0].session_name.as_deref(), Some("proj:task-d"));
+    assert_eq!(plan.release[0].worktree_path.as_deref(), Some("/proj/.agtx/worktrees/d"));
+}
+
+#[test]
+fn test_plan_restore_unchanged_board_is_empty() {
+    let tasks = vec![running("Same", "s"), task("Idle", TaskStatus::Backlog)];
+    let plan = snapshot::plan_restore(&tasks, &tasks);
+
+    assert!(plan.create.is_empty() && plan.update.is_empty() && plan.remove.is_empty());
+    assert!(plan.release.is_empty());
+}
+
+// === Summary Tests ===
+
+#[test]
+fn test_summary_lines() {
+    let summary = RestoreSummary {
+        tasks_created: 2,
+        tasks_removed: 1,
+        windows_killed: 1,
+        worktrees_created: vec!["Fix bug".to_string()],
+        windows_created: vec!["Fix bug".to_string(), "Add docs".to_string()],
+        kept: vec!["'Spike': Worktree /w has uncommitted changes".to_string()],
+        failures: vec!["closing window proj:task-x: gone".to_string()],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        summary.lines(),
+        vec![
+            "2 task(s) created",
+            "1 task(s) moved to the trash",
+            "1 window(s) closed",
+            "Worktree recreated: Fix bug",
+            "Window recreated: Fix bug, Add docs",
+            "Kept: 'Spike': Worktree /w has uncommitted changes",
+            "Failed: closing window proj:task-x: gone",
+        ]
+    );
+}
+
+#[test]
+fn test_summary_nothing_changed() {
+    assert_eq!(RestoreSummary::default().lines(), vec!["Board already matches the snapshot"]);
+}
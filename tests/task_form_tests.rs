@@ -0,0 +1,144 @@
+use agtx::config::Template;
+use agtx::db::TaskStatus;
+use agtx::tui::task_form::{build_task, parse_from_ref, parse_subdir, parse_tags, FieldError, FormField, TaskForm};
+
+fn form(title: &str, agent: &str) -> TaskForm {
+    TaskForm { title: title.to_string(), agent: agent.to_string(), ..Default::default() }
+}
+
+// === FormField Tests ===
+
+#[test]
+fn test_form_field_tab_order_wraps() {
+    assert_eq!(FormField::Title.next(), FormField::Description);
+    assert_eq!(FormField::Description.next(), FormField::Agent);
+    assert_eq!(FormField::Agent.next(), FormField::Tags);
+    assert_eq!(FormField::Tags.next(), FormField::Subdir);
+    assert_eq!(FormField::Subdir.next(), FormField::FromRef);
+    assert_eq!(FormField::FromRef.next(), FormField::Title);
+    assert_eq!(FormField::Title.prev(), FormField::FromRef);
+    for field in FormField::ALL {
+        assert_eq!(field.next().prev(), field);
+    }
+    assert!(!FormField::Agent.is_text());
+}
+
+// === TaskForm Tests ===
+
+#[test]
+fn test_task_form_new_prefills_from_template() {
+    let template = Template {
+        name: "bugfix".to_string(),
+        description: Some("Steps to reproduce:".to_string()),
+        agent: Some("codex".to_string()),
+        tags: vec!["bug".to_string(), "p1".to_string()],
+        ..Default::default()
+    };
+
+    let prefilled = TaskForm::new(Some(&template), "claude");
+    assert_eq!(prefilled.description, "Steps to reproduce:");
+    assert_eq!(prefilled.agent, "codex");
+    assert_eq!(prefilled.tags, "bug, p1");
+    assert!(prefilled.title.is_empty());
+
+    let blank = TaskForm::new(None, "claude");
+    assert_eq!(blank, TaskForm { agent: "claude".to_string(), ..Default::default() });
+}
+
+// === Validation Tests ===
+
+#[test]
+fn test_parse_tags() {
+    assert_eq!(parse_tags(""), Ok(vec![]));
+    assert_eq!(parse_tags("bug, ui #p1  bug"), Ok(vec!["bug".to_string(), "ui".to_string(), "p1".to_string()]));
+    assert_eq!(parse_tags("bug,,ui"), Ok(vec!["bug".to_string(), "ui".to_string()]));
+    assert!(parse_tags("bug, #").unwrap_err().contains("'#'"));
+}
+
+#[test]
+fn test_parse_subdir() {
+    assert_eq!(parse_subdir(""), Ok(None));
+    assert_eq!(parse_subdir(" . "), Ok(None));
+    assert_eq!(parse_subdir("./crates/api/"), Ok(Some("crates/api".to_string())));
+    assert_eq!(parse_subdir("web\\src"), Ok(Some("web/src".to_string())));
+    assert!(parse_subdir("/etc").unwrap_err().contains("relative"));
+    assert!(parse_subdir("C:\\code").unwrap_err().contains("relative"));
+    assert!(parse_subdir("docs/../..").unwrap_err().contains("leaves the worktree"));
+}
+
+#[test]
+fn test_parse_from_ref() {
+    assert_eq!(parse_from_ref("  "), Ok(None));
+    assert_eq!(parse_from_ref(" feature/login "), Ok(Some("feature/login".to_string())));
+    assert!(parse_from_ref("two words").is_err());
+    assert!(parse_from_ref("--orphan").is_err());
+}
+
+#[test]
+fn test_build_task_from_form() {
+    let mut input = form("  Fix login  ", "codex");
+    input.description = "Use OAuth".to_string();
+    input.tags = "#auth, backend".to_string();
+
+    let task = build_task(&input, "proj", None).unwrap();
+
+    assert_eq!(task.title, "Fix login");
+    assert_eq!(task.description.as_deref(), Some("Use OAuth"));
+    assert_eq!(task.agent, "codex");
+    assert_eq!(task.project_id, "proj");
+    assert_eq!(task.tags, vec!["auth".to_string(), "backend".to_string()]);
+    assert_eq!(task.status, TaskStatus::Backlog);
+    assert!(task.worktree_path.is_none());
+    assert!(task.subdir.is_none());
+}
+
+#[test]
+fn test_build_task_with_subdir() {
+    let mut input = form("Fix API", "claude");
+    input.subdir = "crates/api/".to_string();
+    assert_eq!(build_task(&input, "proj", None).unwrap().subdir.as_deref(), Some("crates/api"));
+
+    input.from_ref = "release/2.0".to_string();
+    assert_eq!(build_task(&input, "proj", None).unwrap().from_ref.as_deref(), Some("release/2.0"));
+
+    input.subdir = "../other".to_string();
+    let errors = build_task(&input, "proj", None).unwrap_err();
+    assert_eq!(errors[0].field, FormField::Subdir);
+}
+
+#[test]
+fn test_build_task_blank_description_is_none() {
+    let mut input = form("Fix login", "claude");
+    input.description = "  \n".to_string();
+    assert!(build_task(&input, "proj", None).unwrap().description.is_none());
+}
+
+#[test]
+fn test_build_task_applies_template_prefix_and_env() {
+    let template = Template {
+        name: "bugfix".to_string(),
+        title_prefix: "fix: ".to_string(),
+        env: [("RUST_BACKTRACE".to_string(), "1".to_string())].into_iter().collect(),
+        ..Default::default()
+    };
+
+    let task = build_task(&form("crash on startup", "claude"), "proj", Some(&template)).unwrap();
+
+    assert_eq!(task.title, "fix: crash on startup");
+    assert_eq!(task.env.get("RUST_BACKTRACE").map(String::as_str), Some("1"));
+}
+
+#[test]
+fn test_build_task_reports_every_invalid_field() {
+    let mut input = form("   ", "");
+    input.tags = "ok, #".to_string();
+
+    let errors = build_task(&input, "proj", None).unwrap_err();
+
+    let fields: Vec<FormField> = errors.iter().map(|e| e.field).collect();
+    assert_eq!(fields, vec![FormField::Title, FormField::Agent, FormField::Tags]);
+    assert_eq!(
+        errors[0],
+        FieldError { field: FormField::Title, message: "Title can't be empty".to_string() }
+    );
+}
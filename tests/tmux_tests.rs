@@ -0,0 +1,297 @@
+use agtx::config::PromptDelivery;
+use agtx::op_error::OpError;
+use agtx::tmux::{
+    chunk_keys, is_agent_server_socket, is_server_not_running, split_target, with_env, zellij_key_bytes, NoTmuxOps,
+    record_exit_code, CaptureBuffer, RateLimitedTmuxOps, SendKeysGate, TmuxOperations, WindowSetup,
+    SEND_KEYS_CHUNK_BYTES,
+};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::collections::BTreeMap;
+
+// === Capture Buffer Tests ===
+
+#[test]
+fn test_capture_buffer_drops_oldest_lines_on_overflow() {
+    let mut buffer = CaptureBuffer::new(3);
+    for line in ["one", "two", "three", "four", "five"] {
+        buffer.push(line);
+    }
+    assert_eq!(buffer.len(), 3);
+    assert_eq!(buffer.lines().collect::<Vec<_>>(), vec!["three", "four", "five"]);
+
+    buffer.truncate(1);
+    assert_eq!(buffer.text(), "five");
+    buffer.push("six");
+    assert_eq!(buffer.text(), "six");
+
+    let mut none = CaptureBuffer::new(0);
+    none.push_capture("output");
+    assert!(none.is_empty());
+}
+
+#[test]
+fn test_capture_buffer_appends_only_new_lines() {
+    let mut buffer = CaptureBuffer::new(100);
+    buffer.push_capture("$ cargo test\nCompiling\n\n\n");
+    // The same screen again adds nothing
+    buffer.push_capture("$ cargo test\nCompiling\n");
+    // The screen scrolled: only what follows the overlap is new
+    buffer.push_capture("Compiling\nRunning 3 tests\nok\n");
+    assert_eq!(buffer.text(), "$ cargo test\nCompiling\nRunning 3 tests\nok");
+
+    // No overlap at all: the whole capture is new
+    buffer.push_capture("warning: unused");
+    assert_eq!(buffer.len(), 5);
+}
+
+#[test]
+fn test_capture_buffer_keeps_repeated_lines() {
+    let mut buffer = CaptureBuffer::new(100);
+    buffer.push_capture("ok\nok");
+    buffer.push_capture("ok\nok\nok");
+    assert_eq!(buffer.text(), "ok\nok\nok");
+}
+
+// === Server Detection Tests ===
+
+#[test]
+fn test_agent_server_socket_detected() {
+    assert!(is_agent_server_socket("/tmp/tmux-1000/agtx,12345,0"));
+    assert!(is_agent_server_socket("/private/tmp/tmux-501/agtx,1,2"));
+}
+
+#[test]
+fn test_other_server_socket_not_detected() {
+    assert!(!is_agent_server_socket("/tmp/tmux-1000/default,12345,0"));
+    assert!(!is_agent_server_socket("/tmp/tmux-1000/agtx-other,1,0"));
+    assert!(!is_agent_server_socket(""));
+}
+
+#[test]
+fn test_server_not_running_detected() {
+    assert!(is_server_not_running("no server running on /tmp/tmux-1000/agtx"));
+    assert!(is_server_not_running("error connecting to /tmp/tmux-1000/agtx (No such file or directory)"));
+    assert!(!is_server_not_running("can't find window: task-1"));
+    assert!(!is_server_not_running(""));
+}
+
+// === Exit Code Tests ===
+
+#[test]
+fn test_record_exit_code_appends_status_write() {
+    assert_eq!(
+        record_exit_code("claude 'go'", std::path::Path::new("/wt/my task/.agtx/agent-exit-code")),
+        "claude 'go'; echo $? > '/wt/my task/.agtx/agent-exit-code'"
+    );
+}
+
+// === Window Env Tests ===
+
+#[test]
+fn test_with_env_exports_before_command() {
+    let env: BTreeMap<String, String> = [
+        ("FOO".to_string(), "bar".to_string()),
+        ("TOKEN".to_string(), "it's $secret".to_string()),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(
+        with_env("claude 'go'", &env),
+        r#"export FOO=bar; export TOKEN='it'\''s $secret'; claude 'go'"#
+    );
+}
+
+#[test]
+fn test_with_env_empty_or_invalid_leaves_command() {
+    assert_eq!(with_env("claude", &BTreeMap::new()), "claude");
+    let env = [("NOT-VALID".to_string(), "x".to_string())].into_iter().collect();
+    assert_eq!(with_env("claude", &env), "claude");
+}
+
+#[test]
+fn test_window_setup_runs_startup_command_before_agent() {
+    let setup = WindowSetup {
+        env: [("NODE_ENV".to_string(), "test".to_string())].into_iter().collect(),
+        startup_command: Some("nvm use".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(setup.wrap("claude 'fix it'"), r#"export NODE_ENV=test; "${SHELL:-sh}" -ic 'nvm use; claude '\''fix it'\'''"#);
+}
+
+/// The startup command and agent run in an interactive shell, which reads the rc file that
+/// defines functions like `nvm`
+#[test]
+#[cfg(unix)]
+fn test_window_setup_startup_command_sees_rc_functions() {
+    let dir = tempfile::tempdir().unwrap();
+    let rc = dir.path().join("rc.sh");
+    std::fs::write(&rc, "greet() { GREETING=\"hi from rc\"; }\n").unwrap();
+    let setup = WindowSetup { startup_command: Some("greet".to_string()), ..Default::default() };
+
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(setup.wrap("printf '%s' \"$GREETING\""))
+        .env("SHELL", "sh")
+        .env("ENV", &rc)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hi from rc");
+}
+
+fn claude(prompt: &str) -> String {
+    format!("claude '{}'", prompt.replace('\'', "'\"'\"'"))
+}
+
+#[test]
+fn test_agent_command_reads_long_prompt_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let prompt = format!("Fix the login flow.\n\n{}", "Don't touch the session store. ".repeat(400));
+    assert!(prompt.len() > 8 * 1024);
+
+    let command = WindowSetup::default().agent_command(&prompt, dir.path(), claude);
+
+    let prompt_file = agtx::git::prompt_file_path(dir.path());
+    assert_eq!(command, format!("claude \"$(cat {})\"", prompt_file.display()));
+    assert_eq!(std::fs::read_to_string(&prompt_file).unwrap(), prompt);
+    // The shell gets the prompt back unchanged, quotes and line breaks included
+    let echoed = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command.replacen("claude", "printf %s", 1))
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8(echoed.stdout).unwrap(), prompt);
+}
+
+#[test]
+fn test_agent_command_short_or_inline_prompt_stays_on_command_line() {
+    let dir = tempfile::tempdir().unwrap();
+    let setup = WindowSetup::default();
+    assert_eq!(setup.agent_command("Fix login", dir.path(), claude), "claude 'Fix login'");
+
+    let long = "a".repeat(PromptDelivery::LONG_PROMPT_BYTES * 2);
+    let inline = WindowSetup { prompt_delivery: PromptDelivery::Inline, ..Default::default() };
+    assert_eq!(inline.agent_command(&long, dir.path(), claude), claude(&long));
+    assert!(!agtx::git::prompt_file_path(dir.path()).exists());
+
+    // An agent command that doesn't quote its prompt can't read it from the file
+    let file = WindowSetup { prompt_delivery: PromptDelivery::File, ..Default::default() };
+    assert_eq!(file.agent_command("Fix login", dir.path(), |p| format!("agent --prompt={}", p)), "agent --prompt=Fix login");
+}
+
+#[test]
+fn test_chunk_keys_splits_long_text_on_char_boundaries() {
+    assert_eq!(chunk_keys("short", SEND_KEYS_CHUNK_BYTES), vec!["short"]);
+    assert_eq!(chunk_keys("", SEND_KEYS_CHUNK_BYTES), vec![""]);
+
+    let text = "Überprüfe den Login-Ablauf ✓ ".repeat(300);
+    let chunks = chunk_keys(&text, SEND_KEYS_CHUNK_BYTES);
+    assert!(chunks.len() > 5);
+    assert!(chunks.iter().all(|c| c.len() <= SEND_KEYS_CHUNK_BYTES && !c.is_empty()));
+    assert_eq!(chunks.concat(), text);
+
+    // A char wider than the limit still goes through, on its own
+    assert_eq!(chunk_keys("✓✓", 2), vec!["✓", "✓"]);
+}
+
+#[test]
+fn test_unique_window_name_only_suffixes_templates_without_slug() {
+    let task = agtx::db::Task::new("Fix login", "claude", "proj");
+    let taken = |name: &str| name == "Fix-login" || name.starts_with("task-");
+
+    // Slugs are unique already, so tmux isn't asked
+    let setup = WindowSetup::default();
+    assert_eq!(setup.unique_window_name(&task, "abc12345-Fix-login", taken), "task-abc12345-Fix-login");
+
+    // Two tasks titled the same would share `{title}`
+    let setup = WindowSetup { name_template: "{title}".to_string(), ..Default::default() };
+    assert_eq!(setup.unique_window_name(&task, "abc12345-Fix-login", taken), "Fix-login-2");
+}
+
+#[test]
+fn test_window_setup_blank_startup_command_is_ignored() {
+    assert_eq!(WindowSetup::default().wrap("claude"), "claude");
+    let setup = WindowSetup { startup_command: Some("  ".to_string()), ..Default::default() };
+    assert_eq!(setup.wrap("claude"), "claude");
+}
+
+// === No-tmux Mode Tests ===
+
+#[test]
+fn test_no_tmux_ops_window_changes_succeed_without_tmux() {
+    let ops = NoTmuxOps;
+    assert!(ops.ensure_session("proj", "/tmp").is_ok());
+    assert!(ops.create_window("proj", "task-fix", "/tmp", Some("claude".to_string())).is_ok());
+    assert!(ops.send_keys("proj:task-fix", "go").is_ok());
+    assert!(ops.kill_window("proj:task-fix").is_ok());
+}
+
+#[test]
+fn test_no_tmux_ops_lookups_report_disabled() {
+    let ops = NoTmuxOps;
+    assert!(matches!(ops.window_exists("proj:task-fix"), Err(OpError::TmuxDisabled)));
+    assert!(matches!(ops.list_windows("proj"), Err(OpError::TmuxDisabled)));
+    assert!(matches!(ops.attach("proj:task-fix"), Err(OpError::TmuxDisabled)));
+    assert!(ops.capture_pane_with_history("proj:task-fix", 100).is_empty());
+}
+
+// === Zellij Mapping Tests ===
+
+#[test]
+fn test_split_target_into_session_and_tab() {
+    assert_eq!(split_target("proj:task-fix"), ("proj", "task-fix"));
+    assert_eq!(split_target("proj"), ("proj", ""));
+}
+
+#[test]
+fn test_zellij_key_bytes_for_named_keys() {
+    assert_eq!(zellij_key_bytes("Enter"), Some(vec![13]));
+    assert_eq!(zellij_key_bytes("Escape"), Some(vec![27]));
+    assert_eq!(zellij_key_bytes("Up"), Some(b"\x1b[A".to_vec()));
+    assert_eq!(zellij_key_bytes("F5"), Some(b"\x1b[15~".to_vec()));
+}
+
+#[test]
+fn test_zellij_key_bytes_plain_text_is_typed() {
+    assert_eq!(zellij_key_bytes("2"), None);
+    assert_eq!(zellij_key_bytes("hello"), None);
+}
+
+// === Send Rate Limit Tests ===
+
+#[test]
+fn test_rapid_sends_are_spaced_by_interval() {
+    let ops = RateLimitedTmuxOps::new(Arc::new(NoTmuxOps), Duration::from_millis(50));
+    let start = Instant::now();
+    ops.send_keys("proj:task-a", "first").unwrap();
+    ops.send_keys("proj:task-b", "second").unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test_gate_serializes_concurrent_senders() {
+    let gate = Arc::new(SendKeysGate::new(Duration::from_millis(30)));
+    let start = Instant::now();
+    let handles: Vec<_> = (0..3)
+        .map(|_| {
+            let gate = Arc::clone(&gate);
+            std::thread::spawn(move || gate.wait())
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // The first send goes straight through; the other two each wait a full interval
+    assert!(start.elapsed() >= Duration::from_millis(60));
+}
+
+#[test]
+fn test_zero_interval_never_waits() {
+    let gate = SendKeysGate::default();
+    let start = Instant::now();
+    for _ in 0..100 {
+        gate.wait();
+    }
+    assert!(start.elapsed() < Duration::from_millis(50));
+}
@@ -0,0 +1,100 @@
+use agtx::db::{Task, TaskStatus};
+use agtx::tui::board::BoardState;
+use agtx::tui::ui_state::UiState;
+use tempfile::TempDir;
+
+fn board_with(tasks: &[(&str, TaskStatus)]) -> BoardState {
+    let mut board = BoardState::new();
+    board.tasks = tasks
+        .iter()
+        .map(|(title, status)| {
+            let mut task = Task::new(*title, "claude", "project-1");
+            task.status = *status;
+            task
+        })
+        .collect();
+    board
+}
+
+// === Capture/Apply Tests ===
+
+#[test]
+fn test_capture_selection_and_scroll() {
+    let mut board = board_with(&[("a", TaskStatus::Running), ("b", TaskStatus::Running)]);
+    board.selected_column = 2;
+    board.selected_row = 1;
+    board.set_scroll_offset(2, 1);
+    let selected = board.selected_task().unwrap().id.clone();
+
+    let state = UiState::capture(&board);
+
+    assert_eq!(state.selected_task, Some(selected));
+    assert_eq!(state.selected_column, 2);
+    assert_eq!(state.scroll.get("running"), Some(&1));
+    // Columns at the top aren't stored
+    assert_eq!(state.scroll.get("backlog"), None);
+}
+
+#[test]
+fn test_apply_selects_saved_task() {
+    let mut board = board_with(&[("a", TaskStatus::Review), ("b", TaskStatus::Review), ("c", TaskStatus::Review)]);
+    let target = board.tasks_in_column(3)[2].id.clone();
+    let mut state = UiState { selected_task: Some(target.clone()), selected_column: 0, ..Default::default() };
+    state.scroll.insert("review".to_string(), 2);
+
+    state.apply(&mut board);
+
+    assert_eq!(board.selected_task().unwrap().id, target);
+    assert_eq!(board.scroll_offset(3), 2);
+    assert_eq!(board.scroll_offset(0), 0);
+}
+
+#[test]
+fn test_apply_missing_task_falls_back_to_column() {
+    let mut board = board_with(&[("a", TaskStatus::Planning)]);
+    board.selected_row = 5;
+    let state = UiState { selected_task: Some("gone".to_string()), selected_column: 1, ..Default::default() };
+
+    state.apply(&mut board);
+
+    assert_eq!(board.selected_column, 1);
+    assert_eq!(board.selected_row, 0);
+}
+
+#[test]
+fn test_apply_clamps_column() {
+    let mut board = board_with(&[]);
+    let state = UiState { selected_column: 42, ..Default::default() };
+
+    state.apply(&mut board);
+
+    assert_eq!(board.selected_column, TaskStatus::columns().len() - 1);
+}
+
+// === Save/Load Tests ===
+
+#[test]
+fn test_save_load_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("projects").join("abc.ui.json");
+    let mut state = UiState { selected_task: Some("task-1".to_string()), selected_column: 3, ..Default::default() };
+    state.scroll.insert("done".to_string(), 7);
+
+    state.save(&path).unwrap();
+
+    assert_eq!(UiState::load(&path).unwrap(), state);
+}
+
+#[test]
+fn test_load_missing_file_is_default() {
+    let dir = TempDir::new().unwrap();
+    assert_eq!(UiState::load(&dir.path().join("none.ui.json")).unwrap(), UiState::default());
+}
+
+#[test]
+fn test_load_invalid_file_errors() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("bad.ui.json");
+    std::fs::write(&path, "not json").unwrap();
+    assert!(UiState::load(&path).is_err());
+}
@@ -0,0 +1,72 @@
+use agtx::agent::usage::{format_count, parse_count, parser_for, ClaudeUsageParser, UsageParser};
+use agtx::db::AgentUsage;
+
+const CLAUDE_SUMMARY: &str = "\
+> /cost
+  ⎿  Total cost:            $0.2456
+     Total duration (API):  1m 2.3s
+     Total duration (wall): 5m 1.2s
+     Total code changes:    10 lines added, 2 lines removed
+     Usage by model:
+         claude-3-5-haiku:  1.2k input, 85 output, 0 cache read, 0 cache write
+            claude-sonnet:  23 input, 4.5k output, 120.3k cache read, 15.2k cache write
+";
+
+// === Claude Parser Tests ===
+
+#[test]
+fn test_claude_usage_summary() {
+    let usage = ClaudeUsageParser.parse(CLAUDE_SUMMARY).unwrap();
+    assert_eq!(usage.tokens_in, 1_200 + 23 + 120_300 + 15_200);
+    assert_eq!(usage.tokens_out, 85 + 4_500);
+    assert_eq!(usage.cost_usd, Some(0.2456));
+}
+
+#[test]
+fn test_claude_usage_last_summary_wins() {
+    let output = "Total cost: $0.10\nUsage by model:\n  claude-sonnet:  10 input, 20 output\n\
+                  some more work\nTotal cost: $0.30\nUsage by model:\n  claude-sonnet:  30 input, 40 output\n";
+    let usage = ClaudeUsageParser.parse(output).unwrap();
+    assert_eq!(usage, AgentUsage { tokens_in: 30, tokens_out: 40, cost_usd: Some(0.30) });
+}
+
+#[test]
+fn test_claude_usage_cost_only() {
+    let usage = ClaudeUsageParser.parse("Total cost: $1.05\n").unwrap();
+    assert_eq!(usage, AgentUsage { tokens_in: 0, tokens_out: 0, cost_usd: Some(1.05) });
+}
+
+#[test]
+fn test_claude_usage_nothing_to_parse() {
+    assert_eq!(ClaudeUsageParser.parse(""), None);
+    assert_eq!(ClaudeUsageParser.parse("> fix the login bug\n✻ Thinking…"), None);
+    assert_eq!(ClaudeUsageParser.parse("Total cost: unknown\n"), None);
+}
+
+#[test]
+fn test_parser_for_agent() {
+    assert!(parser_for("claude").is_some());
+    assert!(parser_for("codex").is_none());
+    assert!(parser_for("unknown").is_none());
+}
+
+// === Count Tests ===
+
+#[test]
+fn test_parse_count() {
+    assert_eq!(parse_count("85"), Some(85));
+    assert_eq!(parse_count("1,234"), Some(1_234));
+    assert_eq!(parse_count("4.5k"), Some(4_500));
+    assert_eq!(parse_count("120.3K"), Some(120_300));
+    assert_eq!(parse_count("1.2m"), Some(1_200_000));
+    assert_eq!(parse_count(""), None);
+    assert_eq!(parse_count("lots"), None);
+    assert_eq!(parse_count("-3"), None);
+}
+
+#[test]
+fn test_format_count() {
+    assert_eq!(format_count(999), "999");
+    assert_eq!(format_count(4_500), "4.5k");
+    assert_eq!(format_count(1_234_567), "1.2M");
+}
@@ -0,0 +1,140 @@
+//! Tests for the status change webhook
+//!
+//! The delivery tests need mocks: cargo test --features test-mocks
+
+use agtx::db::{Task, TaskStatus};
+use agtx::webhook::{curl_config, Payload};
+
+// === Payload Tests ===
+
+#[test]
+fn test_payload_json() {
+    let task = Task::new("Fix login", "claude", "proj");
+    let payload = Payload::new(&task, TaskStatus::Running, TaskStatus::Review);
+
+    let json: serde_json::Value = serde_json::to_value(&payload).unwrap();
+    assert_eq!(json["task_id"], task.id.as_str());
+    assert_eq!(json["title"], "Fix login");
+    assert_eq!(json["old_status"], "running");
+    assert_eq!(json["new_status"], "review");
+    assert!(json["timestamp"].as_str().is_some());
+}
+
+/// Secrets stay off curl's command line; the config curl reads from stdin carries them
+#[test]
+fn test_curl_config_quotes_values() {
+    let config = curl_config(
+        "https://dash.example.com/hook?token=s3cret",
+        Some("Authorization: Bearer \"abc\""),
+        r#"{"title":"Fix \"login\"\\ page"}"#,
+    );
+    let lines: Vec<&str> = config.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            r#"url = "https://dash.example.com/hook?token=s3cret""#,
+            r#"header = "Authorization: Bearer \"abc\"""#,
+            r#"data-binary = "{\"title\":\"Fix \\\"login\\\"\\\\ page\"}""#,
+        ]
+    );
+    assert!(!curl_config("https://x", None, "{}").contains("header"));
+}
+
+/// curl reads the header and body back from the config unchanged
+#[test]
+fn test_curl_sender_posts_header_and_body() {
+    use agtx::webhook::{CurlWebhookSender, WebhookSender};
+    use std::io::{Read, Write};
+
+    if which::which("curl").is_err() {
+        return;
+    }
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 4096];
+        while !String::from_utf8_lossy(&request).ends_with('}') {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    let body = r#"{"title":"Fix \"login\"\\ page"}"#;
+    CurlWebhookSender.post(&url, Some("Authorization: Bearer s3cret".to_string()), body).unwrap();
+
+    let request = server.join().unwrap();
+    assert!(request.starts_with("POST /hook "), "{}", request);
+    assert!(request.contains("Authorization: Bearer s3cret\r\n"), "{}", request);
+    assert!(request.ends_with(&format!("\r\n\r\n{}", body)), "{}", request);
+}
+
+#[cfg(feature = "test-mocks")]
+mod delivery {
+    use super::*;
+    use agtx::config::WebhookConfig;
+    use agtx::op_error::OpError;
+    use agtx::webhook::{deliver, MockWebhookSender};
+
+    fn config(auth_header: Option<&str>) -> WebhookConfig {
+        WebhookConfig {
+            url: Some("https://dash.example.com/hook".to_string()),
+            auth_header: auth_header.map(str::to_string),
+        }
+    }
+
+    fn payload() -> Payload {
+        Payload::new(&Task::new("Fix", "claude", "proj"), TaskStatus::Backlog, TaskStatus::Planning)
+    }
+
+    #[test]
+    fn test_deliver_skips_without_url() {
+        let mut sender = MockWebhookSender::new();
+        sender.expect_post().never();
+        assert!(deliver(&WebhookConfig::default(), &sender, &payload()).is_ok());
+    }
+
+    #[test]
+    fn test_deliver_posts_payload_with_header() {
+        let mut sender = MockWebhookSender::new();
+        sender
+            .expect_post()
+            .withf(|url, header, body| {
+                url == "https://dash.example.com/hook"
+                    && header.as_deref() == Some("Authorization: Bearer abc")
+                    && body.contains("\"new_status\":\"planning\"")
+            })
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        assert!(deliver(&config(Some("Authorization: Bearer abc")), &sender, &payload()).is_ok());
+    }
+
+    #[test]
+    fn test_deliver_retries_once() {
+        let mut sender = MockWebhookSender::new();
+        let mut seq = mockall::Sequence::new();
+        sender
+            .expect_post()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Err(OpError::CommandNotFound { program: "curl".to_string() }));
+        sender.expect_post().times(1).in_sequence(&mut seq).returning(|_, _, _| Ok(()));
+        assert!(deliver(&config(None), &sender, &payload()).is_ok());
+    }
+
+    #[test]
+    fn test_deliver_gives_up_after_retry() {
+        let mut sender = MockWebhookSender::new();
+        sender
+            .expect_post()
+            .times(2)
+            .returning(|_, _, _| Err(OpError::CommandNotFound { program: "curl".to_string() }));
+        assert!(deliver(&config(None), &sender, &payload()).is_err());
+    }
+}
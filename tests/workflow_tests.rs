@@ -0,0 +1,440 @@
+//! Tests for the transition rules (`workflow`) and `tui::advance_task`, the side effects of
+//! each column move
+//!
+//! The `advance_task` tests need mocks: cargo test --features test-mocks
+
+use agtx::db::{Task, TaskStatus};
+use agtx::workflow::{self, TransitionError};
+
+const ALL: [TaskStatus; 5] =
+    [TaskStatus::Backlog, TaskStatus::Planning, TaskStatus::Running, TaskStatus::Review, TaskStatus::Done];
+
+fn task_in(status: TaskStatus) -> Task {
+    let mut task = Task::new("Fix login", "claude", "proj");
+    task.status = status;
+    task
+}
+
+// === Transition Rule Tests ===
+
+#[test]
+fn test_valid_forward_transitions() {
+    for (from, to) in [
+        (TaskStatus::Backlog, TaskStatus::Planning),
+        (TaskStatus::Backlog, TaskStatus::Running),
+        (TaskStatus::Planning, TaskStatus::Running),
+        (TaskStatus::Running, TaskStatus::Review),
+        (TaskStatus::Review, TaskStatus::Done),
+    ] {
+        assert!(workflow::is_valid_forward_transition(from, to), "{:?} -> {:?}", from, to);
+        assert_eq!(workflow::request_transition(&task_in(from), to), Ok(()));
+    }
+}
+
+#[test]
+fn test_invalid_forward_transitions() {
+    let invalid = [
+        (TaskStatus::Backlog, TaskStatus::Review),
+        (TaskStatus::Backlog, TaskStatus::Done),
+        (TaskStatus::Planning, TaskStatus::Review),
+        (TaskStatus::Planning, TaskStatus::Done),
+        (TaskStatus::Running, TaskStatus::Done),
+        (TaskStatus::Done, TaskStatus::Backlog),
+        (TaskStatus::Done, TaskStatus::Planning),
+        (TaskStatus::Done, TaskStatus::Running),
+        (TaskStatus::Done, TaskStatus::Review),
+        (TaskStatus::Review, TaskStatus::Planning),
+        (TaskStatus::Review, TaskStatus::Backlog),
+        (TaskStatus::Running, TaskStatus::Backlog),
+        (TaskStatus::Planning, TaskStatus::Backlog),
+    ];
+    for (from, to) in invalid {
+        assert!(!workflow::is_valid_forward_transition(from, to), "{:?} -> {:?}", from, to);
+    }
+}
+
+#[test]
+fn test_request_transition_rejects_each_invalid_pair() {
+    for from in ALL {
+        for to in ALL {
+            let allowed =
+                workflow::is_valid_forward_transition(from, to) || workflow::is_valid_resume_transition(from, to);
+            if from == to || allowed {
+                continue;
+            }
+            let err = workflow::request_transition(&task_in(from), to).unwrap_err();
+            assert_eq!(err, TransitionError::NotAllowed { title: "Fix login".to_string(), from, to });
+        }
+    }
+}
+
+#[test]
+fn test_request_transition_error_messages() {
+    let err = workflow::request_transition(&task_in(TaskStatus::Backlog), TaskStatus::Done).unwrap_err();
+    assert!(err.to_string().starts_with("'Fix login' can't move from backlog to done"), "{}", err);
+
+    let err = workflow::request_transition(&task_in(TaskStatus::Review), TaskStatus::Review).unwrap_err();
+    assert_eq!(err.to_string(), "'Fix login' is already in review");
+}
+
+#[test]
+fn test_resume_transitions() {
+    assert!(workflow::is_valid_resume_transition(TaskStatus::Running, TaskStatus::Planning));
+    assert!(workflow::is_valid_resume_transition(TaskStatus::Review, TaskStatus::Running));
+    assert!(!workflow::is_valid_resume_transition(TaskStatus::Review, TaskStatus::Planning));
+    assert!(!workflow::is_valid_resume_transition(TaskStatus::Done, TaskStatus::Review));
+    assert_eq!(workflow::resume_status(TaskStatus::Planning), None);
+    assert_eq!(
+        workflow::request_transition(&task_in(TaskStatus::Running), TaskStatus::Planning),
+        Ok(())
+    );
+}
+
+// === Lifecycle Tests ===
+
+#[cfg(feature = "test-mocks")]
+mod lifecycle {
+    use super::*;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use agtx::agent::MockAgentOperations;
+    use agtx::config::{GlobalConfig, MergedConfig, ProjectConfig};
+    use agtx::git::{GitOperations, MockGitOperations};
+    use agtx::tmux::MockTmuxOperations;
+    use agtx::tmux::TmuxOperations;
+    use agtx::tui::{advance_task, DirectMove, MoveHooks, PhaseStart};
+
+    fn config() -> MergedConfig {
+        MergedConfig::merge(&GlobalConfig::default(), &ProjectConfig::default())
+    }
+
+    /// Hooks that record what the board would send instead of sending it
+    #[derive(Default)]
+    struct Recorder {
+        starts: Vec<PhaseStart>,
+        dispatched: Vec<String>,
+        refuse: bool,
+    }
+
+    impl MoveHooks for Recorder {
+        fn start_phase(&mut self, _tmux_ops: &dyn TmuxOperations, start: PhaseStart) {
+            self.starts.push(start);
+        }
+
+        fn dispatch_running(&mut self, _task: &Task, _target: &str, command: String, _tmux_ops: &dyn TmuxOperations) {
+            self.dispatched.push(command);
+        }
+
+        fn transition_allowed(&mut self, _task: &Task, _from: TaskStatus, _to: TaskStatus) -> anyhow::Result<()> {
+            if self.refuse {
+                anyhow::bail!("strict hook failed");
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_advance_task_full_lifecycle() {
+        let calls = Arc::new(Mutex::new(Vec::<String>::new()));
+        let mut mock_git = MockGitOperations::new();
+        let mut mock_tmux = MockTmuxOperations::new();
+        let mut mock_agent = MockAgentOperations::new();
+
+        mock_git.expect_default_branch().returning(|_| Ok("main".to_string()));
+        mock_git.expect_ref_exists().returning(|_, _| Ok(true));
+        mock_git.expect_worktree_exists().returning(|_, _| false);
+        let log = Arc::clone(&calls);
+        mock_git.expect_create_worktree().times(1).returning(move |_, slug, base| {
+            log.lock().unwrap().push(format!("create_worktree {}", base));
+            Ok(format!("/project/.agtx/worktrees/{}", slug))
+        });
+        mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
+        mock_agent.expect_new_session_id().returning(|| None);
+        mock_agent.expect_build_interactive_command().returning(|prompt| format!("claude '{}'", prompt));
+        mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+        let log = Arc::clone(&calls);
+        mock_tmux.expect_create_window().times(1).returning(move |session, _, _, _| {
+            log.lock().unwrap().push(format!("create_window {}", session));
+            Ok(())
+        });
+        // The planning command and prompt go in once the agent shows its input prompt
+        mock_tmux.expect_capture_pane().returning(|_| Ok("❯ ".to_string()));
+        let log = Arc::clone(&calls);
+        mock_tmux.expect_send_keys().times(4).returning(move |_, keys| {
+            assert!(!keys.is_empty());
+            let what = if keys.starts_with('/') { keys.split_whitespace().next().unwrap_or_default() } else { "prompt" };
+            log.lock().unwrap().push(format!("send_keys {}", what));
+            Ok(())
+        });
+        mock_git.expect_is_worktree_dirty().returning(|_| Ok(false));
+        let log = Arc::clone(&calls);
+        mock_tmux.expect_kill_window().times(1).returning(move |_| {
+            log.lock().unwrap().push("kill_window".to_string());
+            Ok(())
+        });
+        let log = Arc::clone(&calls);
+        mock_git.expect_remove_worktree().times(1).returning(move |_, _| {
+            log.lock().unwrap().push("remove_worktree".to_string());
+            Ok(())
+        });
+
+        let config = config();
+        let mut task = Task::new("Fix login", "claude", "my-project");
+        let mut statuses = Vec::new();
+        for _ in 0..4 {
+            advance_task(&mut task, Path::new("/project"), &mock_git, &mock_tmux, &mock_agent, &config, &mut DirectMove).unwrap();
+            statuses.push(task.status);
+        }
+
+        assert_eq!(
+            statuses,
+            vec![TaskStatus::Planning, TaskStatus::Running, TaskStatus::Review, TaskStatus::Done]
+        );
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                "create_worktree main",
+                "create_window my-project",
+                "send_keys /agtx:plan",
+                "send_keys prompt",
+                "send_keys /agtx:execute",
+                "send_keys /agtx:review",
+                "kill_window",
+                "remove_worktree",
+            ]
+        );
+        // The branch stays for reopening; the window and worktree are gone
+        assert!(task.branch_name.is_some());
+        assert_eq!(task.session_name, None);
+        assert_eq!(task.worktree_path, None);
+    }
+
+    /// With Planning human-only, the agent starts without a prompt and only Running and Review
+    /// get a command
+    #[test]
+    fn test_advance_task_skips_columns_without_dispatch() {
+        let project: ProjectConfig = toml::from_str("[columns.planning]\ndispatch_agent = false").unwrap();
+        let config = MergedConfig::merge(&GlobalConfig::default(), &project);
+        assert!(!config.should_dispatch(TaskStatus::Planning));
+
+        let mut mock_git = MockGitOperations::new();
+        let mut mock_tmux = MockTmuxOperations::new();
+        let mut mock_agent = MockAgentOperations::new();
+        mock_git.expect_default_branch().returning(|_| Ok("main".to_string()));
+        mock_git.expect_ref_exists().returning(|_, _| Ok(true));
+        mock_git.expect_worktree_exists().returning(|_, _| false);
+        mock_git
+            .expect_create_worktree()
+            .returning(|_, slug, _| Ok(format!("/project/.agtx/worktrees/{}", slug)));
+        mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
+        mock_agent.expect_new_session_id().returning(|| None);
+        mock_agent
+            .expect_build_interactive_command()
+            .withf(|prompt| prompt.is_empty())
+            .returning(|_| "claude".to_string());
+        mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+        mock_tmux.expect_create_window().returning(|_, _, _, _| Ok(()));
+        mock_tmux.expect_send_keys().times(1).returning(|_, _| Ok(()));
+
+        let mut task = Task::new("Fix login", "claude", "my-project");
+        for _ in 0..2 {
+            advance_task(&mut task, Path::new("/project"), &mock_git, &mock_tmux, &mock_agent, &config, &mut DirectMove).unwrap();
+        }
+        assert_eq!(task.status, TaskStatus::Running);
+
+        // Running itself can be turned off too
+        let project: ProjectConfig = toml::from_str("[columns.running]\ndispatch_agent = false").unwrap();
+        let config = MergedConfig::merge(&GlobalConfig::default(), &project);
+        let mut mock_tmux = MockTmuxOperations::new();
+        mock_tmux.expect_send_keys().never();
+        let mut task = Task::new("Fix login", "claude", "my-project");
+        task.status = TaskStatus::Planning;
+        task.session_name = Some("my-project:task-fix".to_string());
+        advance_task(&mut task, Path::new("/project"), &MockGitOperations::new(), &mock_tmux, &MockAgentOperations::new(), &config, &mut DirectMove)
+            .unwrap();
+        assert_eq!(task.status, TaskStatus::Running);
+    }
+
+    /// A worktree starts from the hooks' base branch, so the board's cached detection is used
+    /// instead of asking git again
+    #[test]
+    fn test_advance_task_uses_hooks_base_branch() {
+        struct Cached;
+        impl MoveHooks for Cached {
+            fn base_branch(&mut self, _config: &MergedConfig, _project_path: &Path, _git_ops: &dyn GitOperations) -> String {
+                "develop".to_string()
+            }
+            fn start_phase(&mut self, _tmux_ops: &dyn TmuxOperations, _start: PhaseStart) {}
+        }
+
+        let mut mock_git = MockGitOperations::new();
+        let mut mock_tmux = MockTmuxOperations::new();
+        let mut mock_agent = MockAgentOperations::new();
+        mock_git.expect_default_branch().never();
+        mock_git.expect_ref_exists().returning(|_, _| Ok(true));
+        mock_git.expect_worktree_exists().returning(|_, _| false);
+        mock_git
+            .expect_create_worktree()
+            .withf(|_, _, base| base == "develop")
+            .times(1)
+            .returning(|_, slug, _| Ok(format!("/project/.agtx/worktrees/{}", slug)));
+        mock_git.expect_initialize_worktree().returning(|_, _, _, _, _| vec![]);
+        mock_agent.expect_new_session_id().returning(|| None);
+        mock_agent.expect_build_interactive_command().returning(|_| "claude".to_string());
+        mock_tmux.expect_ensure_session().returning(|_, _| Ok(()));
+        mock_tmux.expect_create_window().returning(|_, _, _, _| Ok(()));
+
+        let mut task = Task::new("Fix login", "claude", "my-project");
+        advance_task(&mut task, Path::new("/project"), &mock_git, &mock_tmux, &mock_agent, &config(), &mut Cached).unwrap();
+
+        assert_eq!(task.status, TaskStatus::Planning);
+    }
+
+    /// The project's prompt prefix and suffix go out with the Running skill command
+    #[test]
+    fn test_advance_task_running_send_carries_prefix_and_suffix() {
+        let project: ProjectConfig =
+            toml::from_str("prompt_prefix = \"Follow CONTRIBUTING.md.\"\nprompt_suffix = \"Run clippy.\"").unwrap();
+        let config = MergedConfig::merge(&GlobalConfig::default(), &project);
+        let mut task = Task::new("Fix login", "claude", "my-project");
+        task.status = TaskStatus::Planning;
+        task.session_name = Some("my-project:task-fix".to_string());
+        let mut hooks = Recorder::default();
+
+        advance_task(
+            &mut task,
+            Path::new("/project"),
+            &MockGitOperations::new(),
+            &MockTmuxOperations::new(),
+            &MockAgentOperations::new(),
+            &config,
+            &mut hooks,
+        )
+        .unwrap();
+
+        assert_eq!(hooks.dispatched, vec!["/agtx:execute Follow CONTRIBUTING.md.\n\nRun clippy."]);
+    }
+
+    #[test]
+    fn test_advance_task_done_is_an_error() {
+        let mut task = Task::new("Shipped", "claude", "proj");
+        task.status = TaskStatus::Done;
+
+        let result = advance_task(
+            &mut task,
+            Path::new("/project"),
+            &MockGitOperations::new(),
+            &MockTmuxOperations::new(),
+            &MockAgentOperations::new(),
+            &config(),
+            &mut DirectMove,
+        );
+
+        assert!(result.unwrap_err().to_string().contains("already Done"));
+        assert_eq!(task.status, TaskStatus::Done);
+    }
+
+    #[test]
+    fn test_advance_task_dirty_worktree_stays_in_review() {
+        let mut mock_git = MockGitOperations::new();
+        mock_git.expect_is_worktree_dirty().returning(|_| Ok(true));
+        mock_git.expect_remove_worktree().never();
+        let mut mock_tmux = MockTmuxOperations::new();
+        mock_tmux.expect_kill_window().never();
+
+        let mut task = Task::new("Half done", "claude", "proj");
+        task.status = TaskStatus::Review;
+        task.session_name = Some("proj:task-half".to_string());
+        task.worktree_path = Some("/project/.agtx/worktrees/half".to_string());
+
+        let result = advance_task(&mut task, Path::new("/project"), &mock_git, &mock_tmux, &MockAgentOperations::new(), &config(), &mut DirectMove);
+
+        assert!(result.unwrap_err().to_string().contains("uncommitted changes"));
+        assert_eq!(task.status, TaskStatus::Review);
+        assert!(task.worktree_path.is_some());
+    }
+
+    #[test]
+    fn test_advance_task_without_window_sends_nothing() {
+        let mut mock_tmux = MockTmuxOperations::new();
+        mock_tmux.expect_send_keys().never();
+
+        let mut task = Task::new("Headless", "claude", "proj");
+        task.status = TaskStatus::Planning;
+
+        advance_task(&mut task, Path::new("/project"), &MockGitOperations::new(), &mock_tmux, &MockAgentOperations::new(), &config(), &mut DirectMove)
+            .unwrap();
+
+        assert_eq!(task.status, TaskStatus::Running);
+    }
+
+    /// A research window that's still open is reused: no worktree or window is created and the
+    /// planning command goes straight in
+    #[test]
+    fn test_advance_task_reuses_research_window() {
+        let mut mock_git = MockGitOperations::new();
+        mock_git.expect_create_worktree().never();
+        let mut mock_tmux = MockTmuxOperations::new();
+        mock_tmux.expect_window_exists().returning(|_| Ok(true));
+        mock_tmux.expect_create_window().never();
+
+        let mut task = Task::new("Fix login", "claude", "proj");
+        task.session_name = Some("proj:task-fix".to_string());
+        task.worktree_path = Some("/project/.agtx/worktrees/fix".to_string());
+        let mut hooks = Recorder::default();
+        advance_task(&mut task, Path::new("/project"), &mock_git, &mock_tmux, &MockAgentOperations::new(), &config(), &mut hooks)
+            .unwrap();
+
+        assert_eq!(task.status, TaskStatus::Planning);
+        assert_eq!(hooks.starts.len(), 1);
+        assert_eq!(hooks.starts[0].target, "proj:task-fix");
+        assert_eq!(hooks.starts[0].skill_cmd.as_deref(), Some("/agtx:plan"));
+        assert!(!hooks.starts[0].wait_ready);
+    }
+
+    /// The Running command goes through `dispatch_running` (the board's queue), and a refused
+    /// transition keeps the status
+    #[test]
+    fn test_advance_task_refused_transition_keeps_status() {
+        let mut task = Task::new("Fix login", "claude", "proj");
+        task.status = TaskStatus::Planning;
+        task.session_name = Some("proj:task-fix".to_string());
+        let mut hooks = Recorder { refuse: true, ..Default::default() };
+
+        let result = advance_task(
+            &mut task,
+            Path::new("/project"),
+            &MockGitOperations::new(),
+            &MockTmuxOperations::new(),
+            &MockAgentOperations::new(),
+            &config(),
+            &mut hooks,
+        );
+
+        assert!(result.unwrap_err().to_string().contains("strict hook"));
+        assert_eq!(task.status, TaskStatus::Planning);
+        assert_eq!(hooks.dispatched, vec!["/agtx:execute"]);
+    }
+
+    /// The destination column's on-enter prompt is sent once the move is through
+    #[test]
+    fn test_advance_task_sends_on_enter_prompt() {
+        let global: GlobalConfig = toml::from_str("[columns.review]\non_enter_prompt = \"check {title}\"").unwrap();
+        let config = MergedConfig::merge(&global, &ProjectConfig::default());
+        let mut mock_tmux = MockTmuxOperations::new();
+        mock_tmux.expect_send_keys().returning(|_, _| Ok(()));
+
+        let mut task = Task::new("Fix login", "claude", "proj");
+        task.status = TaskStatus::Running;
+        task.session_name = Some("proj:task-fix".to_string());
+        let mut hooks = Recorder::default();
+        advance_task(&mut task, Path::new("/project"), &MockGitOperations::new(), &mock_tmux, &MockAgentOperations::new(), &config, &mut hooks)
+            .unwrap();
+
+        assert_eq!(task.status, TaskStatus::Review);
+        assert_eq!(hooks.starts.len(), 1);
+        assert_eq!(hooks.starts[0].prompt, "check Fix login");
+        assert!(hooks.starts[0].wait_ready);
+    }
+}